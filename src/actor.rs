@@ -1,13 +1,37 @@
 use crate::{
-    bot::Bot, character::Character, level::UpdateContext, message::Message, player::Player,
+    bot::{Bot, BotKind},
+    character::{Character, Team},
+    control_scheme::ControlScheme,
+    error::GameResult,
+    frame_profiler::Subsystem,
+    item::{Item, MAX_PICKUP_RADIUS},
+    level::UpdateContext,
+    message::Message,
+    player::Player,
+    random,
+    weapon::{Weapon, WeaponContainer, WeaponKind},
+    MatchOptions,
 };
-use rg3d::core::{
-    math::vec3::Vec3,
-    pool::{Handle, Pool, PoolIterator, PoolIteratorMut, PoolPairIterator, PoolPairIteratorMut},
-    visitor::{Visit, VisitResult, Visitor},
+use rand::Rng;
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::{
+            Handle, Pool, PoolIterator, PoolIteratorMut, PoolPairIterator, PoolPairIteratorMut,
+        },
+        visitor::{Visit, VisitError, VisitResult, Visitor},
+    },
+    engine::resource_manager::ResourceManager,
+    scene::Scene,
+    utils::log::Log,
+};
+use std::{
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::mpsc::Sender,
+    time::Instant,
 };
-use rg3d::scene::Scene;
-use std::ops::{Deref, DerefMut};
 
 #[allow(clippy::large_enum_variant)]
 pub enum Actor {
@@ -53,6 +77,16 @@ impl Actor {
     pub fn clean_up(&mut self, scene: &mut Scene) {
         static_dispatch!(self, clean_up, scene)
     }
+
+    /// Descriptor to respawn this actor with - same name and team, default loadout.
+    pub fn descriptor(&self) -> ActorDescriptor {
+        let descriptor = match self {
+            Actor::Player(_) => ActorDescriptor::player(),
+            Actor::Bot(bot) if bot.is_zombie() => ActorDescriptor::zombie(bot.definition.kind),
+            Actor::Bot(bot) => ActorDescriptor::bot(bot.definition.kind),
+        };
+        descriptor.with_name(&self.name).with_team(self.team())
+    }
 }
 
 impl Deref for Actor {
@@ -103,12 +137,20 @@ pub struct TargetDescriptor {
     pub ptr: *const Actor,
     pub health: f32,
     pub position: Vec3,
+    /// Velocity of body of actor in meters per second.
+    pub velocity: Vec3,
+    pub team: Team,
+    pub is_player: bool,
+    /// Actor carries an objective, like flag in Capture The Flag.
+    pub is_objective_carrier: bool,
 }
 
 #[derive(Default)]
 pub struct ActorContainer {
     pool: Pool<Actor>,
     target_descriptors: Vec<TargetDescriptor>,
+    /// Buffer for item grid queries, kept to avoid allocations every frame.
+    nearby_items: Vec<(Handle<Item>, Vec3)>,
 }
 
 impl ActorContainer {
@@ -116,6 +158,7 @@ impl ActorContainer {
         Self {
             pool: Default::default(),
             target_descriptors: Default::default(),
+            nearby_items: Default::default(),
         }
     }
 
@@ -157,6 +200,19 @@ impl ActorContainer {
                 ptr: actor,
                 health: actor.health,
                 position: actor.position(&context.scene.physics),
+                velocity: context
+                    .scene
+                    .physics
+                    .borrow_body(actor.get_body())
+                    .get_velocity()
+                    .scale(1.0 / context.time.delta),
+                team: actor.team(),
+                is_player: matches!(actor, Actor::Player(_)),
+                is_objective_carrier: context.flags.is_carrier(handle)
+                    || context
+                        .oddball
+                        .map_or(false, |oddball| oddball.is_carrier(handle))
+                    || context.hill.map_or(false, |hill| hill.is_holder(handle)),
             });
         }
 
@@ -164,26 +220,41 @@ impl ActorContainer {
             let is_dead = actor.is_dead();
 
             match actor {
-                Actor::Bot(bot) => bot.update(handle, context, &self.target_descriptors),
-                Actor::Player(player) => player.update(context),
+                Actor::Bot(bot) => {
+                    let bot_update_start = Instant::now();
+                    bot.update(handle, context, &self.target_descriptors);
+                    context
+                        .profiler
+                        .add(Subsystem::BotAi, bot_update_start.elapsed());
+                }
+                Actor::Player(player) => player.update(handle, context, &self.target_descriptors),
             }
             if !is_dead {
-                for (item_handle, item) in context.items.pair_iter() {
-                    let body = context.scene.physics.borrow_body(actor.get_body());
-                    let distance = (context.scene.graph[item.get_pivot()].global_position()
-                        - body.get_position())
-                    .len();
-                    if distance < 1.25 && !item.is_picked_up() {
-                        actor
-                            .sender
-                            .as_ref()
-                            .unwrap()
-                            .send(Message::PickUpItem {
-                                actor: handle,
-                                item: item_handle,
-                            })
-                            .unwrap();
+                let position = actor.position(&context.scene.physics);
+                context
+                    .item_grid
+                    .query_radius(position, MAX_PICKUP_RADIUS, &mut self.nearby_items);
+                for &(item_handle, item_position) in self.nearby_items.iter() {
+                    let item = context.items.get(item_handle);
+                    if position.distance(&item_position) > item.pickup_radius() {
+                        continue;
+                    }
+                    // Player picks weapons up with interact button, so it won't swap its
+                    // loadout by walking over a weapon by accident.
+                    if let Actor::Player(_) = actor {
+                        if item.get_kind().is_weapon() {
+                            continue;
+                        }
                     }
+                    actor
+                        .sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::PickUpItem {
+                            actor: handle,
+                            item: item_handle,
+                        })
+                        .unwrap();
                 }
             }
 
@@ -241,3 +312,374 @@ impl Visit for ActorContainer {
         visitor.leave_region()
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ActorKind {
+    Player,
+    Bot(BotKind),
+}
+
+impl ActorKind {
+    fn id(self) -> i32 {
+        match self {
+            ActorKind::Player => -1,
+            ActorKind::Bot(kind) => kind.id(),
+        }
+    }
+
+    fn from_id(id: i32) -> Result<Self, String> {
+        match id {
+            -1 => Ok(ActorKind::Player),
+            _ => Ok(ActorKind::Bot(BotKind::from_id(id)?)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpawnPointPolicy {
+    /// Spawn point with least amount of actors nearby, which increases survival probability.
+    Safest,
+    Random,
+    /// Exact position, spawn points of level are ignored.
+    Position(Vec3),
+}
+
+impl SpawnPointPolicy {
+    fn id(self) -> u32 {
+        match self {
+            SpawnPointPolicy::Safest => 0,
+            SpawnPointPolicy::Random => 1,
+            SpawnPointPolicy::Position(_) => 2,
+        }
+    }
+}
+
+impl Visit for SpawnPointPolicy {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        let mut position = match self {
+            SpawnPointPolicy::Position(position) => *position,
+            _ => Vec3::ZERO,
+        };
+        position.visit("Position", visitor)?;
+        if visitor.is_reading() {
+            *self = match id {
+                0 => SpawnPointPolicy::Safest,
+                1 => SpawnPointPolicy::Random,
+                2 => SpawnPointPolicy::Position(position),
+                _ => return Err(VisitError::User(format!("Invalid spawn policy {}", id))),
+            };
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Everything that is needed to spawn an actor. Descriptor is kept while actor waits for
+/// respawn, so actor comes back with the same name, team and loadout.
+#[derive(Clone, Debug)]
+pub struct ActorDescriptor {
+    pub kind: ActorKind,
+    /// Default name of the kind is used if not set.
+    pub name: Option<String>,
+    /// `Team::None` means that team is selected automatically in team based modes.
+    pub team: Team,
+    pub spawn_point: SpawnPointPolicy,
+    /// Weapons that actor gets on spawn.
+    pub loadout: Vec<WeaponKind>,
+    /// Bot is spawned as a zombie (see `zombie_rush` module), ignored for players.
+    pub zombie: bool,
+}
+
+impl Default for ActorDescriptor {
+    fn default() -> Self {
+        Self::player()
+    }
+}
+
+impl ActorDescriptor {
+    pub fn player() -> Self {
+        Self {
+            kind: ActorKind::Player,
+            name: None,
+            team: Team::None,
+            spawn_point: SpawnPointPolicy::Safest,
+            loadout: vec![
+                WeaponKind::M4,
+                WeaponKind::Ak47,
+                WeaponKind::PlasmaRifle,
+                WeaponKind::RocketLauncher,
+            ],
+            zombie: false,
+        }
+    }
+
+    pub fn bot(kind: BotKind) -> Self {
+        Self {
+            kind: ActorKind::Bot(kind),
+            name: None,
+            team: Team::None,
+            spawn_point: SpawnPointPolicy::Safest,
+            loadout: vec![WeaponKind::Ak47],
+            zombie: false,
+        }
+    }
+
+    /// Zombie bot - no weapons, it rushes to players and fights in melee.
+    pub fn zombie(kind: BotKind) -> Self {
+        Self {
+            loadout: Vec::new(),
+            spawn_point: SpawnPointPolicy::Random,
+            zombie: true,
+            ..Self::bot(kind)
+        }
+    }
+
+    pub fn with_name<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = Some(name.as_ref().to_owned());
+        self
+    }
+
+    pub fn with_team(mut self, team: Team) -> Self {
+        self.team = team;
+        self
+    }
+
+    pub fn with_spawn_point(mut self, spawn_point: SpawnPointPolicy) -> Self {
+        self.spawn_point = spawn_point;
+        self
+    }
+}
+
+impl Visit for ActorDescriptor {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind_id = self.kind.id();
+        kind_id.visit("Kind", visitor)?;
+        let mut actor_name = self.name.clone().unwrap_or_default();
+        actor_name.visit("Name", visitor)?;
+        self.team.visit("Team", visitor)?;
+        self.spawn_point.visit("SpawnPoint", visitor)?;
+        let mut loadout = self
+            .loadout
+            .iter()
+            .map(|kind| kind.id())
+            .collect::<Vec<_>>();
+        loadout.visit("Loadout", visitor)?;
+        self.zombie.visit("Zombie", visitor)?;
+
+        if visitor.is_reading() {
+            self.kind = ActorKind::from_id(kind_id)?;
+            self.name = if actor_name.is_empty() {
+                None
+            } else {
+                Some(actor_name)
+            };
+            self.loadout = loadout
+                .into_iter()
+                .map(WeaponKind::new)
+                .collect::<Result<_, _>>()?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+pub struct SpawnPoint {
+    position: Vec3,
+}
+
+impl Default for SpawnPoint {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+        }
+    }
+}
+
+impl Visit for SpawnPoint {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.position.visit("Position", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Everything spawner needs from level.
+pub struct SpawnContext<'a> {
+    pub scene: &'a mut Scene,
+    pub resource_manager: ResourceManager,
+    pub sender: Sender<Message>,
+    pub actors: &'a mut ActorContainer,
+    pub weapons: &'a mut WeaponContainer,
+    pub control_scheme: Option<Rc<RefCell<ControlScheme>>>,
+    pub options: &'a MatchOptions,
+}
+
+impl<'a> SpawnContext<'a> {
+    /// Creates weapon of given kind and puts it into hands of actor.
+    pub async fn give_weapon(
+        &mut self,
+        actor: Handle<Actor>,
+        kind: WeaponKind,
+    ) -> GameResult<Handle<Weapon>> {
+        let mut weapon = Weapon::new(
+            kind,
+            self.resource_manager.clone(),
+            self.scene,
+            self.sender.clone(),
+        )
+        .await?;
+        weapon.set_owner(actor);
+        let weapon_model = weapon.get_model();
+        let actor = self.actors.get_mut(actor);
+        let weapon_handle = self.weapons.add(weapon);
+        actor.add_weapon(weapon_handle);
+        self.scene
+            .graph
+            .link_nodes(weapon_model, actor.weapon_pivot());
+        Ok(weapon_handle)
+    }
+}
+
+/// Spawns actors of every kind the same way - selects spawn point and team, creates actor
+/// and gives it a loadout. Used for first spawn, respawns and actors requested by scripts.
+#[derive(Default)]
+pub struct ActorSpawner {
+    spawn_points: Vec<SpawnPoint>,
+    /// Loadout selected by player, it replaces loadout of descriptor for player actors.
+    /// Not saved - it is taken from settings.
+    player_loadout: Option<Vec<WeaponKind>>,
+}
+
+impl ActorSpawner {
+    pub fn new() -> Self {
+        Self {
+            spawn_points: Default::default(),
+            player_loadout: None,
+        }
+    }
+
+    pub fn set_player_loadout(&mut self, loadout: Vec<WeaponKind>) {
+        self.player_loadout = Some(loadout);
+    }
+
+    pub fn add_spawn_point(&mut self, position: Vec3) {
+        self.spawn_points.push(SpawnPoint { position });
+    }
+
+    pub fn spawn_point_positions(&self) -> Vec<Vec3> {
+        self.spawn_points.iter().map(|pt| pt.position).collect()
+    }
+
+    pub fn select_position(
+        &self,
+        policy: SpawnPointPolicy,
+        scene: &Scene,
+        actors: &ActorContainer,
+    ) -> Vec3 {
+        if let SpawnPointPolicy::Position(position) = policy {
+            return position;
+        }
+        if self.spawn_points.is_empty() {
+            return Vec3::ZERO;
+        }
+        let mut index = random::rng().gen_range(0, self.spawn_points.len());
+        if policy == SpawnPointPolicy::Safest {
+            let mut max_distance = -std::f32::MAX;
+            for (i, pt) in self.spawn_points.iter().enumerate() {
+                let mut sum_distance = 0.0;
+                for actor in actors.iter() {
+                    let position = actor.position(&scene.physics);
+                    sum_distance += pt.position.distance(&position);
+                }
+                if sum_distance > max_distance {
+                    max_distance = sum_distance;
+                    index = i;
+                }
+            }
+        }
+        self.spawn_points[index].position
+    }
+
+    /// Team based modes put actors without team into the smallest team.
+    fn select_team(requested: Team, options: &MatchOptions, actors: &ActorContainer) -> Team {
+        if requested != Team::None || !options.is_team_based() {
+            return requested;
+        }
+        let count = |team| actors.iter().filter(|actor| actor.team() == team).count();
+        if count(Team::Blue) < count(Team::Red) {
+            Team::Blue
+        } else {
+            Team::Red
+        }
+    }
+
+    pub async fn spawn(
+        &self,
+        descriptor: &ActorDescriptor,
+        ctx: &mut SpawnContext<'_>,
+    ) -> GameResult<Handle<Actor>> {
+        let position = self.select_position(descriptor.spawn_point, ctx.scene, ctx.actors);
+        // Zombies are enemies of everyone, so they never join a team.
+        let team = if descriptor.zombie {
+            Team::None
+        } else {
+            Self::select_team(descriptor.team, ctx.options, ctx.actors)
+        };
+
+        let mut actor = match descriptor.kind {
+            ActorKind::Player => {
+                let mut player = Player::new(ctx.scene, ctx.sender.clone());
+                if let Some(control_scheme) = ctx.control_scheme.as_ref() {
+                    player.set_control_scheme(control_scheme.clone());
+                }
+                Actor::Player(player)
+            }
+            ActorKind::Bot(kind) => Actor::Bot(
+                Bot::new(
+                    kind,
+                    ctx.resource_manager.clone(),
+                    ctx.scene,
+                    position,
+                    ctx.sender.clone(),
+                )
+                .await?,
+            ),
+        };
+        if let Some(name) = descriptor.name.as_ref() {
+            actor.name = name.clone();
+        }
+        if let Actor::Bot(bot) = &mut actor {
+            bot.set_zombie(descriptor.zombie);
+        }
+        actor.set_team(team);
+        actor.set_position(&mut ctx.scene.physics, position);
+        let actor = ctx.actors.add(actor);
+
+        let loadout = match (descriptor.kind, self.player_loadout.as_ref()) {
+            (ActorKind::Player, Some(player_loadout)) => player_loadout,
+            _ => &descriptor.loadout,
+        };
+        for &kind in loadout.iter() {
+            if let Err(e) = ctx.give_weapon(actor, kind).await {
+                Log::writeln(format!("WARNING: Weapon {:?} skipped: {}", kind, e));
+            }
+        }
+
+        Ok(actor)
+    }
+}
+
+impl Visit for ActorSpawner {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        // Spawner is just a list of spawn points for now, so it is stored as the list itself.
+        self.spawn_points.visit(name, visitor)
+    }
+}
@@ -0,0 +1,83 @@
+//! Aim error of bots. Instead of shooting at the center of a target with uniform jitter, bot
+//! deviates its shots by an angle sampled from normal distribution. Spread is wide right after
+//! bot spotted its target and narrows down while bot keeps target in sight, it grows when bot
+//! moves or takes damage. Base spread and how fast it narrows down depend on difficulty.
+
+use crate::{actor::Actor, bot::BotDifficultyDefinition, random};
+use rand::Rng;
+use rg3d::core::{math::vec3::Vec3, pool::Handle};
+
+/// Spread right after target was spotted, relative to settled spread.
+const ACQUIRE_PENALTY: f32 = 3.0;
+/// Spread of bot that runs at full speed, relative to spread of standing bot.
+const MOVE_PENALTY: f32 = 1.0;
+/// Max extra spread from damage, relative to settled spread.
+const MAX_DAMAGE_PENALTY: f32 = 2.0;
+/// Speed of recovery from damage penalty, in units of penalty per second.
+const DAMAGE_RECOVERY: f32 = 1.5;
+/// Error never exceeds this amount of standard deviations, so bots don't shoot at walls
+/// behind themselves once in a while.
+const MAX_DEVIATIONS: f32 = 2.5;
+
+#[derive(Default)]
+pub struct AimError {
+    /// Target bot currently keeps in sight.
+    target: Handle<Actor>,
+    /// Time in seconds bot has current target in sight.
+    sight_time: f32,
+    damage_penalty: f32,
+}
+
+/// Standard normal random value, Box-Muller transform.
+fn normal<R: Rng>(rng: &mut R) -> f32 {
+    let u1: f32 = rng.gen_range(std::f32::EPSILON, 1.0);
+    let u2: f32 = rng.gen_range(0.0, 1.0);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+}
+
+impl AimError {
+    /// Tracks time target is in sight, `Handle::NONE` means bot has no target.
+    pub fn update(&mut self, target: Handle<Actor>, dt: f32) {
+        if target != self.target {
+            self.target = target;
+            self.sight_time = 0.0;
+        } else if target.is_some() {
+            self.sight_time += dt;
+        }
+        self.damage_penalty = (self.damage_penalty - DAMAGE_RECOVERY * dt).max(0.0);
+    }
+
+    /// Damage throws aim off, penalty is proportional to share of max health taken.
+    pub fn on_damage(&mut self, amount: f32, max_health: f32) {
+        self.damage_penalty = (self.damage_penalty
+            + MAX_DAMAGE_PENALTY * 2.0 * amount / max_health.max(1.0))
+        .min(MAX_DAMAGE_PENALTY);
+    }
+
+    /// Standard deviation of aim error in degrees. `movement` is speed of bot relative to
+    /// its walk speed, `factor` is extra multiplier (suppression).
+    pub fn spread(&self, definition: &BotDifficultyDefinition, movement: f32, factor: f32) -> f32 {
+        let acquire = ACQUIRE_PENALTY * (-self.sight_time / definition.aim_settle_time).exp();
+        definition.aim_error
+            * (1.0 + acquire)
+            * (1.0 + MOVE_PENALTY * movement.max(0.0).min(1.0))
+            * (1.0 + self.damage_penalty)
+            * factor
+    }
+
+    /// Deviates direction of a shot by random angle with given standard deviation in degrees.
+    pub fn apply(direction: Vec3, spread: f32) -> Option<Vec3> {
+        let right = direction
+            .cross(&Vec3::UP)
+            .normalized()
+            .unwrap_or(Vec3::RIGHT);
+        let up = right.cross(&direction);
+        let mut rng = random::rng();
+        let mut sample = || {
+            let deviations = normal(&mut rng).max(-MAX_DEVIATIONS).min(MAX_DEVIATIONS);
+            (deviations * spread).to_radians().tan()
+        };
+        let (horizontal, vertical) = (sample(), sample());
+        (direction + right.scale(horizontal) + up.scale(vertical)).normalized()
+    }
+}
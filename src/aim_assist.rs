@@ -0,0 +1,166 @@
+//! Aim assist of player, meant for imprecise look input like sticks of a gamepad. When
+//! crosshair passes over a visible enemy, view turns slower so it is easier to stop on the
+//! enemy, and it is gently pulled towards the enemy while player looks around or moves. Both
+//! parts have their own strength in controls options, aim assist is off by default.
+//!
+//! Auto-aim is a separate accessibility option for players who can't aim precisely at all:
+//! shots go right at the visible enemy closest to crosshair within a wide cone. It is meant
+//! for single player only, any online play must turn it off.
+
+use crate::control_scheme::ControlScheme;
+use rg3d::core::math::vec3::Vec3;
+
+/// Enemies further than this are not assisted.
+pub const ASSIST_DISTANCE: f32 = 30.0;
+/// Angle between view and direction to enemy in degrees at which view is pulled towards it.
+const MAGNETISM_CONE: f32 = 8.0;
+/// Angle in degrees at which view slows down over enemy.
+const SLOWDOWN_CONE: f32 = 4.0;
+/// Speed of pull at full magnetism, in degrees per second.
+const MAX_PULL_SPEED: f32 = 30.0;
+/// Share by which look sensitivity drops at full slowdown.
+const MAX_SLOWDOWN: f32 = 0.6;
+/// Angle in degrees around crosshair in which auto-aim finds enemies.
+const AUTO_AIM_CONE: f32 = 15.0;
+
+/// Direction of view given by yaw and pitch in degrees, positive pitch looks down.
+fn view_angles(dir: Vec3) -> (f32, f32) {
+    let len = dir.len();
+    let yaw = dir.x.atan2(dir.z).to_degrees();
+    let pitch = if len > 0.0 {
+        (-dir.y / len).max(-1.0).min(1.0).asin().to_degrees()
+    } else {
+        0.0
+    };
+    (yaw, pitch)
+}
+
+/// Finds visible enemy closest to crosshair within a cone of given angle in degrees, returns
+/// angle to the enemy and its aim point. Visibility is checked only for enemies in the cone.
+fn closest_to_crosshair<F>(
+    head: Vec3,
+    look: Vec3,
+    enemies: &[Vec3],
+    is_visible: F,
+    cone: f32,
+) -> Option<(f32, Vec3)>
+where
+    F: Fn(Vec3) -> bool,
+{
+    let mut closest: Option<(f32, Vec3)> = None;
+    for &point in enemies {
+        let dir = match (point - head).normalized() {
+            Some(dir) => dir,
+            None => continue,
+        };
+        let angle = dir.dot(&look).max(-1.0).min(1.0).acos().to_degrees();
+        if angle <= cone
+            && closest.map_or(true, |(closest, _)| angle < closest)
+            && is_visible(point)
+        {
+            closest = Some((angle, point));
+        }
+    }
+    closest
+}
+
+/// Point at which shots of player go with auto-aim, `None` if auto-aim is off or there is no
+/// visible enemy near crosshair.
+pub fn auto_aim_point<F>(
+    control_scheme: &ControlScheme,
+    head: Vec3,
+    look: Vec3,
+    enemies: &[Vec3],
+    is_visible: F,
+) -> Option<Vec3>
+where
+    F: Fn(Vec3) -> bool,
+{
+    if !control_scheme.auto_aim {
+        return None;
+    }
+    closest_to_crosshair(head, look, enemies, is_visible, AUTO_AIM_CONE).map(|(_, point)| point)
+}
+
+/// Wraps angle in degrees to [-180; 180] range.
+fn wrap_angle(angle: f32) -> f32 {
+    let angle = angle % 360.0;
+    if angle > 180.0 {
+        angle - 360.0
+    } else if angle < -180.0 {
+        angle + 360.0
+    } else {
+        angle
+    }
+}
+
+pub struct AimAssist {
+    /// Multiplier of look sensitivity, less than one while view is over an enemy.
+    sensitivity: f32,
+    /// Player turned view since last update.
+    look_input: bool,
+}
+
+impl Default for AimAssist {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            look_input: false,
+        }
+    }
+}
+
+impl AimAssist {
+    pub fn on_look_input(&mut self) {
+        self.look_input = true;
+    }
+
+    pub fn sensitivity(&self) -> f32 {
+        self.sensitivity
+    }
+
+    /// Finds enemy closest to crosshair among `enemies` (aim points of enemies in range) and
+    /// returns change of yaw and pitch of view in degrees that pulls view towards it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update<F>(
+        &mut self,
+        control_scheme: &ControlScheme,
+        head: Vec3,
+        look: Vec3,
+        enemies: &[Vec3],
+        is_visible: F,
+        moving: bool,
+        dt: f32,
+    ) -> (f32, f32)
+    where
+        F: Fn(Vec3) -> bool,
+    {
+        let look_input = std::mem::replace(&mut self.look_input, false);
+        self.sensitivity = 1.0;
+        if !control_scheme.aim_assist {
+            return (0.0, 0.0);
+        }
+
+        let (angle, point) =
+            match closest_to_crosshair(head, look, enemies, is_visible, MAGNETISM_CONE) {
+                Some(closest) => closest,
+                None => return (0.0, 0.0),
+            };
+        if angle <= SLOWDOWN_CONE {
+            self.sensitivity = 1.0 - MAX_SLOWDOWN * control_scheme.aim_assist_slowdown;
+        }
+        // View isn't pulled while player stands still and doesn't touch controls, so aim
+        // assist never aims on its own.
+        if !look_input && !moving {
+            return (0.0, 0.0);
+        }
+        let (view_yaw, view_pitch) = view_angles(look);
+        let (target_yaw, target_pitch) = view_angles(point - head);
+        let max_pull = MAX_PULL_SPEED * control_scheme.aim_assist_magnetism * dt;
+        let clamp = |delta: f32| delta.max(-max_pull).min(max_pull);
+        (
+            clamp(wrap_angle(target_yaw - view_yaw)),
+            clamp(target_pitch - view_pitch),
+        )
+    }
+}
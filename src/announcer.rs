@@ -0,0 +1,56 @@
+//! Announcer of remaining match time - "5 minutes remaining", "1 minute remaining" and a
+//! countdown of last ten seconds. Announcements are found by comparing match time of previous
+//! and current frames with time limit, so announcer has no state to save. Voice lines are listed
+//! in `assets::sounds::announcer`, they're optional.
+
+use crate::assets::sounds::announcer;
+use std::path::PathBuf;
+
+/// Countdown starts when this amount of seconds is left.
+const COUNTDOWN_START: u32 = announcer::COUNTDOWN;
+/// Time in seconds HUD shows a number of countdown.
+const COUNTDOWN_DURATION: f32 = 0.9;
+/// Time in seconds HUD shows announcement of remaining minutes.
+const MINUTES_DURATION: f32 = 3.0;
+/// Remaining minutes that are announced.
+const MINUTES: [u32; 2] = [5, 1];
+
+pub struct Announcement {
+    pub text: String,
+    pub sound: PathBuf,
+    /// Time in seconds HUD shows text of announcement.
+    pub duration: f32,
+}
+
+/// Returns true if remaining time dropped to `mark` seconds between two frames.
+fn crossed(time_limit: f32, previous: f32, current: f32, mark: f32) -> bool {
+    // Marks that aren't less than match duration would be announced at start, they're skipped.
+    mark < time_limit && time_limit - previous > mark && time_limit - current <= mark
+}
+
+/// Returns announcement which moment was passed between `previous` and `current` match time,
+/// time limit of zero means match has no time limit.
+pub fn announcement(time_limit: f32, previous: f32, current: f32) -> Option<Announcement> {
+    if time_limit <= 0.0 {
+        return None;
+    }
+
+    for &minutes in MINUTES.iter() {
+        if crossed(time_limit, previous, current, minutes as f32 * 60.0) {
+            let unit = if minutes == 1 { "minute" } else { "minutes" };
+            return Some(Announcement {
+                text: format!("{} {} remaining", minutes, unit),
+                sound: announcer::path(&format!("{}_{}", minutes, unit)),
+                duration: MINUTES_DURATION,
+            });
+        }
+    }
+
+    (1..=COUNTDOWN_START)
+        .find(|&second| crossed(time_limit, previous, current, second as f32))
+        .map(|second| Announcement {
+            text: format!("{}", second),
+            sound: announcer::path(&second.to_string()),
+            duration: COUNTDOWN_DURATION,
+        })
+}
@@ -0,0 +1,180 @@
+//! Startup check of game data. Every known file is checked before anything is loaded, so
+//! user sees complete list of missing files at once instead of a crash on first of them.
+
+use crate::{
+    assets,
+    bot::{Bot, BotKind},
+    item::{Item, ItemKind},
+    match_menu::MAPS,
+    mods,
+    navigation::NavigationAction,
+    projectile::{Projectile, ProjectileKind},
+    weapon::{Weapon, WeaponKind},
+    BuildContext, Gui, GuiMessage, UINodeHandle,
+};
+use rg3d::{
+    event::Event,
+    gui::{
+        button::ButtonBuilder,
+        grid::{Column, GridBuilder, Row},
+        message::{ButtonMessage, MessageDirection, UiMessageData, WindowMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        HorizontalAlignment, Thickness,
+    },
+    utils::log::Log,
+};
+
+/// Amount of paths shown in dialog, rest of them can be found in log.
+const MAX_LISTED: usize = 20;
+
+/// Collects every kind of an entity by its numeric ids, ids are sequential.
+fn all_kinds<K, F>(from_id: F) -> Vec<K>
+where
+    F: Fn(u32) -> Result<K, String>,
+{
+    (0..)
+        .map(from_id)
+        .take_while(|kind| kind.is_ok())
+        .filter_map(|kind| kind.ok())
+        .collect()
+}
+
+/// Returns paths of every missing file, taking mods into account.
+pub fn find_missing() -> Vec<String> {
+    let mut paths = assets::all();
+    for kind in all_kinds(WeaponKind::new) {
+        paths.extend(Weapon::get_definition(kind).assets());
+    }
+    for kind in all_kinds(|id| BotKind::from_id(id as i32)) {
+        paths.extend(Bot::get_definition(kind).assets());
+    }
+    for kind in all_kinds(ProjectileKind::new) {
+        paths.extend(Projectile::get_definition(kind).assets());
+    }
+    for kind in all_kinds(ItemKind::from_id) {
+        paths.extend(Item::get_definition(kind).assets());
+    }
+    paths.extend(MAPS.iter().map(|(_, path)| *path));
+
+    paths.sort();
+    paths.dedup();
+
+    let missing = paths
+        .into_iter()
+        .filter(|path| !mods::resolve(path).exists())
+        .map(|path| path.to_owned())
+        .collect::<Vec<_>>();
+
+    for path in missing.iter() {
+        Log::writeln(format!("ERROR: Missing game file {}", path));
+    }
+    for path in assets::sounds::announcer::all() {
+        if !mods::resolve(&path).exists() {
+            Log::writeln(format!("WARNING: Missing optional game file {}", path));
+        }
+    }
+
+    missing
+}
+
+pub struct MissingAssetsDialog {
+    pub window: UINodeHandle,
+    btn_ok: UINodeHandle,
+}
+
+impl MissingAssetsDialog {
+    pub fn new(ctx: &mut BuildContext, missing: &[String]) -> Self {
+        let mut text = format!(
+            "{} game file(s) are missing, game may not work properly.\n\
+            Try to reinstall the game or disable mods.\n\n",
+            missing.len()
+        );
+        for path in missing.iter().take(MAX_LISTED) {
+            text += path;
+            text += "\n";
+        }
+        if missing.len() > MAX_LISTED {
+            text += &format!("...and {} more, see log.\n", missing.len() - MAX_LISTED);
+        }
+
+        let btn_ok;
+        let content = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(
+                    TextBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(0)
+                            .with_margin(Thickness::uniform(4.0)),
+                    )
+                    .with_text(text)
+                    .with_wrap(true)
+                    .build(ctx),
+                )
+                .with_child({
+                    btn_ok = ButtonBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .with_width(100.0)
+                            .with_height(30.0)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_margin(Thickness::uniform(4.0)),
+                    )
+                    .with_text("OK")
+                    .build(ctx);
+                    btn_ok
+                }),
+        )
+        .add_row(Row::auto())
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0))
+            .with_title(WindowTitle::text("Missing Files"))
+            .open(false)
+            .can_resize(false)
+            .can_minimize(false)
+            .can_close(false)
+            .with_content(content)
+            .build(ctx);
+
+        Self { window, btn_ok }
+    }
+
+    pub fn open(&self, ui: &mut Gui) {
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn is_open(&self, ui: &Gui) -> bool {
+        ui.node(self.window).visibility()
+    }
+
+    fn close(&self, ui: &mut Gui) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    /// Dialog has only one button, so either Enter or Escape closes it.
+    pub fn process_input_event(&self, ui: &mut Gui, event: &Event<()>) {
+        match NavigationAction::from_event(event) {
+            Some(NavigationAction::Activate) | Some(NavigationAction::Back) => self.close(ui),
+            _ => (),
+        }
+    }
+
+    pub fn handle_ui_event(&self, ui: &mut Gui, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_ok {
+                self.close(ui);
+            }
+        }
+    }
+}
@@ -15,6 +15,16 @@ pub mod models {
         pub const PLASMA_RIFLE_AMMO: &str = "data/models/yellow_box.FBX";
         pub const AK47_AMMO: &str = "data/models/box_medium.FBX";
         pub const M4_AMMO: &str = "data/models/box_small.FBX";
+        pub const ATTACHMENT: &str = "data/models/box_large.FBX";
+        pub const OVERSHIELD: &str = "data/models/yellow_box.FBX";
+    }
+
+    /// Sub-models of weapon attachments, they're optional and aren't checked on startup.
+    pub mod attachments {
+        pub const SILENCER: &str = "data/models/attachments/silencer.FBX";
+        pub const EXTENDED_MAG: &str = "data/models/attachments/extended_mag.FBX";
+        pub const SCOPE: &str = "data/models/attachments/scope.FBX";
+        pub const LASER_SIGHT: &str = "data/models/attachments/laser_sight.FBX";
     }
 
     pub mod characters {
@@ -62,6 +72,7 @@ pub mod animations {
         pub const DYING: &str = "data/animations/mutant/dying.fbx";
         pub const DEAD: &str = "data/animations/mutant/dead.fbx";
         pub const HIT_REACTION: &str = "data/animations/mutant/hit_reaction.fbx";
+        pub const CROUCH: &str = "data/animations/mutant/crouch.fbx";
     }
 
     pub mod parasite {
@@ -74,6 +85,7 @@ pub mod animations {
         pub const DYING: &str = "data/animations/parasite/dying.fbx";
         pub const DEAD: &str = "data/animations/parasite/dead.fbx";
         pub const HIT_REACTION: &str = "data/animations/parasite/hit_reaction.fbx";
+        pub const CROUCH: &str = "data/animations/parasite/crouch.fbx";
     }
 
     pub mod maw {
@@ -86,6 +98,7 @@ pub mod animations {
         pub const DYING: &str = "data/animations/maw/dying.fbx";
         pub const DEAD: &str = "data/animations/maw/dead.fbx";
         pub const HIT_REACTION: &str = "data/animations/maw/hit_reaction.fbx";
+        pub const CROUCH: &str = "data/animations/maw/crouch.fbx";
     }
 }
 
@@ -93,6 +106,21 @@ pub mod sounds {
     pub const HRTF_HRIR: &str = "data/sounds/IRC_1040_C.bin";
     pub const ITEM_PICKUP: &str = "data/sounds/item_pickup.ogg";
     pub const SOUNDTRACK: &str = "data/sounds/Antonio_Bizarro_Berzerker.ogg";
+    /// Click of a weapon without ammo, it is optional and isn't checked on startup.
+    pub const DRY_FIRE: &str = "data/sounds/dry_fire.ogg";
+
+    /// Looping sounds of weather, they're optional and aren't checked on startup.
+    pub mod ambient {
+        pub const RAIN: &str = "data/sounds/ambient/rain.ogg";
+        pub const SNOW: &str = "data/sounds/ambient/snow_wind.ogg";
+        pub const DUST: &str = "data/sounds/ambient/dust_wind.ogg";
+    }
+
+    /// Looping sounds of flying projectiles, they're optional and aren't checked on startup.
+    pub mod flyby {
+        pub const PLASMA: &str = "data/sounds/flyby/plasma_loop.ogg";
+        pub const ROCKET: &str = "data/sounds/flyby/rocket_loop.ogg";
+    }
 
     pub mod shot {
         pub const AK47: &str = "data/sounds/ak47.ogg";
@@ -106,6 +134,41 @@ pub mod sounds {
         pub const ROCKET: &str = "data/sounds/explosion.ogg";
     }
 
+    /// Voice lines of announcer, they're optional and only reported as warnings on startup.
+    pub mod announcer {
+        use std::path::PathBuf;
+
+        /// Voice lines other than countdown.
+        const LINES: [&str; 7] = [
+            "5_minutes",
+            "1_minute",
+            "hill_moving",
+            "hill_moved",
+            "hill_contested",
+            "round_over",
+            "round_start",
+        ];
+        /// Countdown is read as numbers from this one down to one.
+        pub const COUNTDOWN: u32 = 10;
+
+        pub fn line(name: &str) -> String {
+            format!("data/sounds/announcer/{}.ogg", name)
+        }
+
+        pub fn path(name: &str) -> PathBuf {
+            line(name).into()
+        }
+
+        /// Paths of every voice line.
+        pub fn all() -> Vec<String> {
+            LINES
+                .iter()
+                .map(|name| line(name))
+                .chain((1..=COUNTDOWN).map(|second| line(&second.to_string())))
+                .collect()
+        }
+    }
+
     pub mod footsteps {
         pub const SHOE_STONE: [&str; 4] = [
             "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
@@ -115,3 +178,46 @@ pub mod sounds {
         ];
     }
 }
+
+/// Paths of every file listed above.
+pub fn all() -> Vec<&'static str> {
+    let mut paths = vec![
+        models::weapons::AK47,
+        models::weapons::M4,
+        models::weapons::PLASMA_RIFLE,
+        models::weapons::ROCKET_LAUNCHER,
+        models::projectiles::ROCKET,
+        models::items::MEDKIT,
+        models::items::PLASMA_RIFLE_AMMO,
+        models::items::AK47_AMMO,
+        models::items::M4_AMMO,
+        models::items::ATTACHMENT,
+        models::characters::MUTANT,
+        models::characters::PARASITE,
+        models::characters::MAW,
+        models::maps::DM6,
+        textures::particles::BULLET,
+        textures::particles::SMOKE,
+        textures::particles::CIRCLE,
+        textures::particles::STAR,
+        textures::interface::CHECK_MARK,
+        textures::interface::CIRCLE,
+        textures::interface::HEALTH_ICON,
+        textures::interface::AMMO_ICON,
+        textures::interface::SHIELD_ICON,
+        textures::interface::CROSSHAIR,
+        fonts::SQUARES_BOLD,
+        sounds::HRTF_HRIR,
+        sounds::ITEM_PICKUP,
+        sounds::SOUNDTRACK,
+        sounds::shot::AK47,
+        sounds::shot::M4,
+        sounds::shot::PLASMA_RIFLE,
+        sounds::shot::ROCKET_LAUNCHER,
+        sounds::impact::BULLET,
+        sounds::impact::ROCKET,
+    ];
+    paths.extend_from_slice(&sounds::footsteps::SHOE_STONE);
+    // Animations are checked as a part of bot definitions.
+    paths
+}
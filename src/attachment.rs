@@ -0,0 +1,169 @@
+//! Weapon attachments. Attachment is picked up as an item and is mounted on the weapon that is
+//! held by the actor at the moment of pickup, it modifies effective parameters of that weapon
+//! and is shown as a sub-model linked to `Weapon:Attachment` node of weapon model (or to the
+//! model itself if weapon has no such node). Models of attachments are optional - weapon works
+//! the same way without them.
+
+use crate::{assets, mods};
+use rg3d::{
+    core::{
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{node::Node, Scene},
+    utils::log::Log,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AttachmentKind {
+    Silencer,
+    ExtendedMag,
+    Scope,
+    LaserSight,
+}
+
+impl AttachmentKind {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(AttachmentKind::Silencer),
+            1 => Ok(AttachmentKind::ExtendedMag),
+            2 => Ok(AttachmentKind::Scope),
+            3 => Ok(AttachmentKind::LaserSight),
+            _ => Err(format!("Unknown attachment kind {}", id)),
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            AttachmentKind::Silencer => 0,
+            AttachmentKind::ExtendedMag => 1,
+            AttachmentKind::Scope => 2,
+            AttachmentKind::LaserSight => 3,
+        }
+    }
+}
+
+pub struct AttachmentDefinition {
+    pub model: &'static str,
+    /// Multiplier of radius in which bots can hear a shot of the weapon.
+    pub noise_factor: f32,
+    /// Multiplier of ammo that weapon receives from pickups.
+    pub ammo_factor: f32,
+    /// Zoom of camera when player aims down sights.
+    pub zoom: f32,
+}
+
+pub struct Attachment {
+    kind: AttachmentKind,
+    model: Handle<Node>,
+}
+
+impl Default for Attachment {
+    fn default() -> Self {
+        Self {
+            kind: AttachmentKind::Silencer,
+            model: Handle::NONE,
+        }
+    }
+}
+
+impl Attachment {
+    pub fn get_definition(kind: AttachmentKind) -> &'static AttachmentDefinition {
+        match kind {
+            AttachmentKind::Silencer => {
+                static DEFINITION: AttachmentDefinition = AttachmentDefinition {
+                    model: assets::models::attachments::SILENCER,
+                    noise_factor: 0.25,
+                    ammo_factor: 1.0,
+                    zoom: 1.0,
+                };
+                &DEFINITION
+            }
+            AttachmentKind::ExtendedMag => {
+                static DEFINITION: AttachmentDefinition = AttachmentDefinition {
+                    model: assets::models::attachments::EXTENDED_MAG,
+                    noise_factor: 1.0,
+                    ammo_factor: 1.5,
+                    zoom: 1.0,
+                };
+                &DEFINITION
+            }
+            AttachmentKind::Scope => {
+                static DEFINITION: AttachmentDefinition = AttachmentDefinition {
+                    model: assets::models::attachments::SCOPE,
+                    noise_factor: 1.0,
+                    ammo_factor: 1.0,
+                    zoom: 2.5,
+                };
+                &DEFINITION
+            }
+            AttachmentKind::LaserSight => {
+                static DEFINITION: AttachmentDefinition = AttachmentDefinition {
+                    model: assets::models::attachments::LASER_SIGHT,
+                    noise_factor: 1.0,
+                    ammo_factor: 1.0,
+                    zoom: 1.0,
+                };
+                &DEFINITION
+            }
+        }
+    }
+
+    /// Creates attachment and links its model to given mount node.
+    pub async fn new(
+        kind: AttachmentKind,
+        resource_manager: ResourceManager,
+        scene: &mut Scene,
+        mount: Handle<Node>,
+    ) -> Self {
+        let definition = Self::get_definition(kind);
+
+        let model = match resource_manager
+            .request_model(mods::resolve(definition.model))
+            .await
+        {
+            Ok(model) => {
+                let model = model.instantiate_geometry(scene);
+                scene.graph.link_nodes(model, mount);
+                model
+            }
+            Err(_) => {
+                Log::writeln(format!(
+                    "WARNING: Unable to load attachment model {}, attachment will be invisible.",
+                    definition.model
+                ));
+                Handle::NONE
+            }
+        };
+
+        Self { kind, model }
+    }
+
+    pub fn kind(&self) -> AttachmentKind {
+        self.kind
+    }
+
+    pub fn definition(&self) -> &'static AttachmentDefinition {
+        Self::get_definition(self.kind)
+    }
+
+    pub fn model(&self) -> Handle<Node> {
+        self.model
+    }
+}
+
+impl Visit for Attachment {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind_id = self.kind.id();
+        kind_id.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = AttachmentKind::from_id(kind_id)?;
+        }
+        self.model.visit("Model", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,145 @@
+//! Periodic autosaves into a small set of rotating slots. Autosave is only *scheduled* from
+//! the fixed-step update, actual serialization into memory is performed by the game after a
+//! frame was rendered, in the time that would otherwise be spent sleeping to cap the frame
+//! rate. Choosing a slot and writing it is done by a background thread, so disk never stalls
+//! a frame. If previous autosave is still being written, next one is skipped.
+
+use crate::{data_dir::DataDirs, settings::AutosaveSettings, SAVE_FILE};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+
+pub struct Autosave {
+    time_left: f32,
+    pending: bool,
+    /// Thread that writes last autosave into its slot.
+    writer: Option<JoinHandle<()>>,
+    /// Set by writer thread when it is done.
+    written: Arc<AtomicBool>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            time_left: 0.0,
+            pending: false,
+            writer: None,
+            written: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Autosave {
+    pub fn new(settings: &AutosaveSettings) -> Self {
+        Self {
+            time_left: settings.interval,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if previous autosave is still being written, new autosave must be skipped
+    /// then - two writers must never race for the same slot.
+    pub fn is_writing(&self) -> bool {
+        self.writer.is_some() && !self.written.load(Ordering::Acquire)
+    }
+
+    /// Writes serialized state into next slot on a background thread. Slot is written to
+    /// temporary file first and then replaced, so crash in the middle of writing won't
+    /// corrupt a save. Must not be called while `is_writing` is true.
+    pub fn store(&mut self, data: Vec<u8>, settings: &AutosaveSettings, dirs: &DataDirs) {
+        debug_assert!(!self.is_writing());
+        // Previous writer is done, so joining it doesn't block.
+        self.finish();
+        let slots = Self::slot_paths(settings, dirs);
+        let written = Arc::new(AtomicBool::new(false));
+        self.written = written.clone();
+        self.writer = Some(thread::spawn(move || {
+            let slot = Self::next_slot_path(&slots);
+            let temp_path = slot.with_extension("tmp");
+            let result =
+                std::fs::write(&temp_path, &data).and_then(|_| std::fs::rename(&temp_path, &slot));
+            match result {
+                Ok(_) => println!("Autosaved to {}", slot.display()),
+                Err(e) => println!("Failed to autosave, reason: {}", e),
+            }
+            written.store(true, Ordering::Release);
+        }));
+    }
+
+    /// Waits until last autosave is written.
+    pub fn finish(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+
+    pub fn slot_path(dirs: &DataDirs, index: usize) -> PathBuf {
+        dirs.save_file(&format!("autosave{}.bin", index))
+    }
+
+    fn slot_paths(settings: &AutosaveSettings, dirs: &DataDirs) -> Vec<PathBuf> {
+        (0..settings.slot_count.max(1))
+            .map(|index| Self::slot_path(dirs, index))
+            .collect()
+    }
+
+    /// Returns path to a slot which should be overwritten next - either first empty
+    /// slot or the one with oldest save.
+    fn next_slot_path(slots: &[PathBuf]) -> PathBuf {
+        let mut oldest: Option<(&PathBuf, SystemTime)> = None;
+        for path in slots {
+            match modification_time(path) {
+                None => return path.clone(),
+                Some(time) => {
+                    if oldest.as_ref().map_or(true, |(_, oldest)| time < *oldest) {
+                        oldest = Some((path, time));
+                    }
+                }
+            }
+        }
+        oldest.map_or_else(|| slots[0].clone(), |(path, _)| path.clone())
+    }
+
+    /// Searches for most recent save among manual save and every autosave slot.
+    pub fn most_recent_save(settings: &AutosaveSettings, dirs: &DataDirs) -> Option<PathBuf> {
+        let mut candidates = vec![dirs.save_file(SAVE_FILE)];
+        candidates.extend(Self::slot_paths(settings, dirs));
+
+        candidates
+            .into_iter()
+            .filter_map(|path| modification_time(&path).map(|time| (path, time)))
+            .max_by_key(|(_, time)| *time)
+            .map(|(path, _)| path)
+    }
+
+    /// Counts down time to next autosave, must be called only while there is a match going on.
+    pub fn update(&mut self, settings: &AutosaveSettings, dt: f32) {
+        if settings.enabled {
+            self.time_left -= dt;
+            if self.time_left <= 0.0 {
+                self.time_left = settings.interval;
+                self.pending = true;
+            }
+        }
+    }
+
+    pub fn reset(&mut self, settings: &AutosaveSettings) {
+        self.time_left = settings.interval;
+        self.pending = false;
+    }
+
+    /// Returns true if autosave was requested, resetting the request.
+    pub fn take_pending(&mut self) -> bool {
+        std::mem::replace(&mut self.pending, false)
+    }
+}
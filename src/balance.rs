@@ -0,0 +1,137 @@
+//! Weapon balance report. Run the game with `--balance-report` to print damage per second,
+//! time to kill and ammo efficiency of every weapon, computed from weapon and projectile
+//! definitions. Numbers are theoretical: every shot hits, rockets hit directly, and armor is
+//! counted as health since damage only moves from armor to health. The game exits after the
+//! report, with code 1 if a weapon is out of balance bounds. Same bounds are checked by tests
+//! of this module.
+
+use crate::{
+    projectile::Projectile,
+    weapon::{Weapon, WeaponKind},
+};
+use std::{env, process};
+
+pub const REPORT_FLAG: &str = "--balance-report";
+
+/// Health of a freshly spawned actor and the most health it can have.
+const TARGET_HEALTH: [f32; 2] = [100.0, 150.0];
+/// No weapon may deal more damage per second than this.
+const MAX_DPS: f32 = 150.0;
+/// No weapon may kill a fresh actor faster than this, in seconds.
+const MIN_TIME_TO_KILL: f32 = 0.5;
+/// No weapon may need more time than this to kill an actor with full health, in seconds.
+const MAX_TIME_TO_KILL: f32 = 4.0;
+
+struct WeaponBalance {
+    kind: WeaponKind,
+    damage: f32,
+    dps: f32,
+    shots_to_kill: [u32; 2],
+    time_to_kill: [f32; 2],
+    /// Actors with 100 health that full ammo can kill.
+    kills_per_ammo: u32,
+}
+
+impl WeaponBalance {
+    fn new(kind: WeaponKind) -> Self {
+        let definition = Weapon::get_definition(kind);
+        let damage = Projectile::get_definition(definition.projectile).direct_hit_damage();
+        let interval = definition.shoot_interval as f32;
+        let shots = |health: f32| (health / damage).ceil() as u32;
+        let shots_to_kill = [shots(TARGET_HEALTH[0]), shots(TARGET_HEALTH[1])];
+        // First shot is fired right away, the rest of them wait for interval.
+        let time = |shots: u32| shots.saturating_sub(1) as f32 * interval;
+        let time_to_kill = [time(shots_to_kill[0]), time(shots_to_kill[1])];
+        Self {
+            kind,
+            damage,
+            dps: damage / interval,
+            shots_to_kill,
+            time_to_kill,
+            kills_per_ammo: definition.ammo / shots_to_kill[0].max(1),
+        }
+    }
+
+    fn violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        if self.dps > MAX_DPS {
+            violations.push(format!("DPS {:.1} is above {}", self.dps, MAX_DPS));
+        }
+        if self.time_to_kill[0] < MIN_TIME_TO_KILL {
+            violations.push(format!(
+                "time to kill {:.2} s is below {} s",
+                self.time_to_kill[0], MIN_TIME_TO_KILL
+            ));
+        }
+        if self.time_to_kill[1] > MAX_TIME_TO_KILL {
+            violations.push(format!(
+                "time to kill {:.2} s is above {} s",
+                self.time_to_kill[1], MAX_TIME_TO_KILL
+            ));
+        }
+        violations
+    }
+}
+
+/// Prints balance table of every weapon, returns true if every weapon is within bounds.
+pub fn print_report() -> bool {
+    println!(
+        "{:<16} {:>7} {:>7} {:>14} {:>16} {:>10}",
+        "Weapon", "Damage", "DPS", "Shots 100/150", "TTK 100/150, s", "Kills/ammo"
+    );
+    let mut balanced = true;
+    for &kind in WeaponKind::ALL.iter() {
+        let balance = WeaponBalance::new(kind);
+        println!(
+            "{:<16} {:>7.1} {:>7.1} {:>14} {:>16} {:>10}",
+            balance.kind.name(),
+            balance.damage,
+            balance.dps,
+            format!("{}/{}", balance.shots_to_kill[0], balance.shots_to_kill[1]),
+            format!(
+                "{:.2}/{:.2}",
+                balance.time_to_kill[0], balance.time_to_kill[1]
+            ),
+            balance.kills_per_ammo
+        );
+        for violation in balance.violations() {
+            println!("  {} is out of balance: {}", balance.kind.name(), violation);
+            balanced = false;
+        }
+    }
+    balanced
+}
+
+/// Prints balance report if `--balance-report` flag was given and exits, with code 1 if a
+/// weapon is out of bounds. Does nothing without the flag.
+pub fn report_from_args() {
+    if env::args().any(|arg| arg == REPORT_FLAG) {
+        process::exit(if print_report() { 0 } else { 1 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_weapon_is_within_bounds() {
+        for &kind in WeaponKind::ALL.iter() {
+            let violations = WeaponBalance::new(kind).violations();
+            assert!(
+                violations.is_empty(),
+                "{} is out of balance: {}",
+                kind.name(),
+                violations.join(", ")
+            );
+        }
+    }
+
+    #[test]
+    fn weapon_out_of_bounds_is_reported() {
+        let mut balance = WeaponBalance::new(WeaponKind::M4);
+        balance.dps = MAX_DPS + 1.0;
+        balance.time_to_kill = [MIN_TIME_TO_KILL / 2.0, MAX_TIME_TO_KILL * 2.0];
+        assert_eq!(balance.violations().len(), 3);
+    }
+}
@@ -1,11 +1,22 @@
 use crate::{
     actor::{Actor, TargetDescriptor},
+    aim::AimError,
     assets,
-    character::Character,
-    item::ItemContainer,
-    level::UpdateContext,
+    character::{self, Character},
+    ctf::ObjectiveRole,
+    definition::{self, DefinitionCache},
+    deployable::Deployables,
+    error::{GameError, GameResult},
+    level::{DamageSource, UpdateContext},
     message::Message,
-    weapon::WeaponContainer,
+    mods,
+    morale::Morale,
+    pathfinding::{PathPlanner, PathQuery},
+    projectile::Projectile,
+    random,
+    sound_variation::SoundCategory,
+    spatial_hash::SpatialHash,
+    weapon::{Weapon, WeaponContainer},
     GameTime,
 };
 use rand::Rng;
@@ -16,7 +27,7 @@ use rg3d::{
     },
     core::{
         color::Color,
-        math::{frustum::Frustum, mat4::Mat4, quat::Quat, ray::Ray, vec3::Vec3, SmoothAngle},
+        math::{frustum::Frustum, mat4::Mat4, quat::Quat, vec3::Vec3, SmoothAngle},
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
@@ -24,7 +35,6 @@ use rg3d::{
     physics::{
         convex_shape::{Axis, CapsuleShape, ConvexShape},
         rigid_body::RigidBody,
-        HitKind, RayCastOptions,
     },
     resource::model::Model,
     scene::{
@@ -33,13 +43,14 @@ use rg3d::{
     },
     utils::navmesh::Navmesh,
 };
+use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
     ops::{Deref, DerefMut},
-    path::Path,
     sync::mpsc::Sender,
 };
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum BotKind {
     // Beasts
     Mutant,
@@ -49,6 +60,8 @@ pub enum BotKind {
 }
 
 impl BotKind {
+    pub const ALL: [BotKind; 3] = [BotKind::Mutant, BotKind::Parasite, BotKind::Maw];
+
     pub fn from_id(id: i32) -> Result<Self, String> {
         match id {
             0 => Ok(BotKind::Mutant),
@@ -67,6 +80,99 @@ impl BotKind {
     }
 }
 
+/// Kinds of bots in order they join a match, they are taken in turn so even small match has
+/// variety of enemies.
+pub const ROSTER: [BotKind; 3] = [BotKind::Maw, BotKind::Mutant, BotKind::Parasite];
+
+/// Skill of bots, it defines how well bots aim at their targets (see `aim` module).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for BotDifficulty {
+    fn default() -> Self {
+        BotDifficulty::Normal
+    }
+}
+
+impl BotDifficulty {
+    pub fn id(self) -> u32 {
+        match self {
+            BotDifficulty::Easy => 0,
+            BotDifficulty::Normal => 1,
+            BotDifficulty::Hard => 2,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(BotDifficulty::Easy),
+            1 => Ok(BotDifficulty::Normal),
+            2 => Ok(BotDifficulty::Hard),
+            _ => Err(format!("Unknown bot difficulty {}", id)),
+        }
+    }
+
+    pub fn definition(self) -> &'static BotDifficultyDefinition {
+        match self {
+            BotDifficulty::Easy => {
+                static DEFINITION: BotDifficultyDefinition = BotDifficultyDefinition {
+                    lead_factor: 0.0,
+                    aim_error: 3.0,
+                    aim_settle_time: 2.0,
+                    reaction_time: 0.6,
+                };
+                &DEFINITION
+            }
+            BotDifficulty::Normal => {
+                static DEFINITION: BotDifficultyDefinition = BotDifficultyDefinition {
+                    lead_factor: 0.6,
+                    aim_error: 1.5,
+                    aim_settle_time: 1.2,
+                    reaction_time: 0.35,
+                };
+                &DEFINITION
+            }
+            BotDifficulty::Hard => {
+                static DEFINITION: BotDifficultyDefinition = BotDifficultyDefinition {
+                    lead_factor: 1.0,
+                    aim_error: 0.5,
+                    aim_settle_time: 0.6,
+                    reaction_time: 0.2,
+                };
+                &DEFINITION
+            }
+        }
+    }
+}
+
+impl Visit for BotDifficulty {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct BotDifficultyDefinition {
+    /// How much of target movement is taken into account when aiming, zero means bot aims
+    /// at current position of target and one means perfect leading.
+    pub lead_factor: f32,
+    /// Standard deviation of aim error in degrees of bot that stands still and keeps its
+    /// target in sight for long.
+    pub aim_error: f32,
+    /// Time in seconds in which aim error of freshly spotted target mostly settles down.
+    pub aim_settle_time: f32,
+    /// Delay in seconds between spotting a new target and first shot at it.
+    pub reaction_time: f32,
+}
+
 pub struct Target {
     position: Vec3,
     handle: Handle<Actor>,
@@ -92,6 +198,153 @@ impl Visit for Target {
     }
 }
 
+/// Last known position of a target that went out of sight, bot goes there to search for it.
+pub struct LastSeen {
+    position: Vec3,
+    handle: Handle<Actor>,
+    /// Time in seconds left until bot gives up searching.
+    search_time: f32,
+}
+
+impl Default for LastSeen {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            handle: Default::default(),
+            search_time: 0.0,
+        }
+    }
+}
+
+impl Visit for LastSeen {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.position.visit("Position", visitor)?;
+        self.handle.visit("Handle", visitor)?;
+        self.search_time.visit("SearchTime", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Level of detail of bot "thinking" (target and path selection), selected by distance to
+/// closest player. Animations and movement are updated every frame regardless of level.
+struct AiLod {
+    max_distance: f32,
+    /// Time between target selections in seconds.
+    think_interval: f32,
+    /// Time between path rebuilds in seconds.
+    path_rebuild_interval: f64,
+}
+
+const AI_LODS: [AiLod; 3] = [
+    AiLod {
+        max_distance: 15.0,
+        think_interval: 0.0,
+        path_rebuild_interval: 1.0,
+    },
+    AiLod {
+        max_distance: 40.0,
+        think_interval: 0.25,
+        path_rebuild_interval: 2.0,
+    },
+    AiLod {
+        max_distance: std::f32::MAX,
+        think_interval: 0.75,
+        path_rebuild_interval: 4.0,
+    },
+];
+
+/// Far plane of vision frustum of bots.
+const VISION_DISTANCE: f32 = 7.0;
+
+/// Radius in which bot counts enemies and allies to decide whether it is outnumbered.
+const AWARENESS_RADIUS: f32 = 12.0;
+
+/// Distance from current path point at which bot is considered to be off its path.
+const MAX_PATH_DEVIATION: f32 = 3.0;
+
+/// Zombies come in crowds, so even closest of them think at reduced rate.
+const ZOMBIE_MIN_AI_LOD: usize = 1;
+
+/// Distance at which bot notices a mine and keeps away from it.
+const MINE_AVOID_DISTANCE: f32 = 4.0;
+
+/// Chance that bot notices a mine it sees, checked every time bot thinks.
+const MINE_NOTICE_CHANCE: f64 = 0.3;
+
+/// Height of capsule of body of standing bot.
+const BODY_HEIGHT: f32 = 1.25;
+
+/// Height of capsule of body of crouching bot.
+const CROUCH_BODY_HEIGHT: f32 = 0.6;
+
+/// Speed of change of height of body when bot crouches or stands up, in units per second.
+const CROUCH_SPEED: f32 = 3.0;
+
+/// Vertical speed of bot right after jump in meters per second.
+const JUMP_SPEED: f32 = 4.8;
+
+/// Suppression above which bot crouches when it fights at range.
+const CROUCH_SUPPRESSION: f32 = 0.3;
+
+/// Bot doesn't crouch when its target is closer than this.
+const CROUCH_MIN_DISTANCE: f32 = 4.0;
+
+/// Bot stays crouched at least this amount of seconds, so it doesn't bob up and down.
+const CROUCH_MIN_TIME: f32 = 1.5;
+
+/// Multiplier of walk speed of crouching bot.
+const CROUCH_SPEED_FACTOR: f32 = 0.4;
+
+/// Bot crouches when it is this close to a crouch zone, so it fits in before it enters.
+const CROUCH_ZONE_MARGIN: f32 = 0.5;
+
+/// Multiplier of aim error of crouching bot.
+const CROUCH_AIM_FACTOR: f32 = 0.7;
+
+/// Time in seconds bot searches for a target that went out of sight before it forgets it.
+const SEARCH_TIME: f32 = 8.0;
+
+/// Distance to last known position of a target at which bot considers the place searched.
+const SEARCH_REACH_DISTANCE: f32 = 1.5;
+
+/// Names given to bots which were spawned without explicit name.
+const NAME_POOL: [&str; 16] = [
+    "Grinder", "Rook", "Vex", "Mauler", "Skitter", "Husk", "Razor", "Gnash", "Brute", "Sliver",
+    "Crank", "Fang", "Ghoul", "Hex", "Scab", "Wretch",
+];
+
+/// Picks first name from the pool that isn't taken yet, when the pool is exhausted names get
+/// a number so every bot still has its own name.
+pub fn pick_bot_name<F: Fn(&str) -> bool>(is_taken: F) -> String {
+    for n in 1.. {
+        for name in NAME_POOL.iter() {
+            let name = match n {
+                1 => name.to_string(),
+                n => format!("{} {}", name, n),
+            };
+            if !is_taken(&name) {
+                return name;
+            }
+        }
+    }
+    unreachable!()
+}
+
+fn select_ai_lod(position: Vec3, targets: &[TargetDescriptor], min_lod: usize) -> &'static AiLod {
+    let closest_player = targets
+        .iter()
+        .filter(|desc| desc.is_player)
+        .map(|desc| desc.position.distance(&position))
+        .fold(std::f32::MAX, f32::min);
+    AI_LODS[min_lod..]
+        .iter()
+        .find(|lod| closest_player <= lod.max_distance)
+        .unwrap_or(&AI_LODS[AI_LODS.len() - 1])
+}
+
 pub struct Bot {
     target: Option<Target>,
     kind: BotKind,
@@ -104,16 +357,45 @@ pub struct Bot {
     last_health: f32,
     restoration_time: f32,
     path: Vec<Vec3>,
+    /// Indices of path points at beginning of jump pads and drop-downs, bot jumps there.
+    jump_points: Vec<usize>,
     move_target: Vec3,
     current_path_point: usize,
+    /// Navmesh vertex current path leads to.
+    path_goal: Option<usize>,
     frustum: Frustum,
     last_poi_update_time: f64,
     point_of_interest: Vec3,
+    /// Assigned by level every frame, so it is not saved.
+    objective_role: ObjectiveRole,
     last_path_rebuild_time: f64,
     last_move_dir: Vec3,
     spine: Handle<Node>,
     yaw: SmoothAngle,
     pitch: SmoothAngle,
+    /// Time left until next target selection. Not saved, initial value is random so bots
+    /// think in different frames.
+    think_timer: f32,
+    path_rebuild_interval: f64,
+    difficulty: BotDifficulty,
+    /// Accuracy and reaction speed relative to difficulty, see `rating` module.
+    skill: f32,
+    morale: Morale,
+    /// Not saved, bot aims as if it has just spotted its target after loading.
+    aim_error: AimError,
+    /// Speed of bot relative to its walk speed, aim error grows when bot moves.
+    movement: f32,
+    /// Time left until bot reacts to its target and may shoot at it. Not saved.
+    reaction_timer: f32,
+    /// Target bot has lost sight of and searches for.
+    last_seen: Option<LastSeen>,
+    /// Time left until crouching bot may stand up, bot crouches while it is positive.
+    crouch_time: f32,
+    /// Zombies have no weapons, they ignore items and everyone except players and rush
+    /// to closest player. They don't feel fear either.
+    zombie: bool,
+    /// Position of a mine bot walks around. Not saved.
+    avoided_mine: Option<Vec3>,
 }
 
 impl Deref for Bot {
@@ -144,11 +426,14 @@ impl Default for Bot {
             last_health: 0.0,
             restoration_time: 0.0,
             path: Default::default(),
+            jump_points: Default::default(),
             move_target: Default::default(),
             current_path_point: 0,
+            path_goal: None,
             frustum: Default::default(),
             last_poi_update_time: -10.0,
             point_of_interest: Default::default(),
+            objective_role: Default::default(),
             last_path_rebuild_time: -10.0,
             last_move_dir: Default::default(),
             spine: Default::default(),
@@ -162,10 +447,23 @@ impl Default for Bot {
                 target: 0.0,
                 speed: 260.0f32.to_radians(), // rad/s
             },
+            think_timer: random::rng().gen_range(0.0, AI_LODS[AI_LODS.len() - 1].think_interval),
+            path_rebuild_interval: AI_LODS[0].path_rebuild_interval,
+            difficulty: Default::default(),
+            skill: 1.0,
+            morale: Default::default(),
+            aim_error: Default::default(),
+            movement: 0.0,
+            reaction_timer: 0.0,
+            last_seen: None,
+            crouch_time: 0.0,
+            zombie: false,
+            avoided_mine: None,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct BotDefinition {
     pub scale: f32,
     pub health: f32,
@@ -182,6 +480,8 @@ pub struct BotDefinition {
     pub hit_reaction_animation: &'static str,
     pub dying_animation: &'static str,
     pub dead_animation: &'static str,
+    /// Optional, bot without such animation crouches with walk animation.
+    pub crouch_animation: &'static str,
     pub weapon_hand_name: &'static str,
     pub left_leg_name: &'static str,
     pub right_leg_name: &'static str,
@@ -189,9 +489,102 @@ pub struct BotDefinition {
     pub v_aim_angle_hack: f32,
 }
 
+impl BotDefinition {
+    /// Paths of every file used by bot.
+    pub fn assets(&self) -> Vec<&'static str> {
+        vec![
+            self.model,
+            self.idle_animation,
+            self.walk_animation,
+            self.aim_animation,
+            self.whip_animation,
+            self.jump_animation,
+            self.falling_animation,
+            self.hit_reaction_animation,
+            self.dying_animation,
+            self.dead_animation,
+        ]
+    }
+}
+
+/// Stats of bot that data file can change, see `definition` module.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BotStats {
+    health: Option<f32>,
+    walk_speed: Option<f32>,
+    scale: Option<f32>,
+    weapon_scale: Option<f32>,
+}
+
+impl BotStats {
+    fn apply(&self, definition: &BotDefinition) -> BotDefinition {
+        BotDefinition {
+            health: self.health.unwrap_or(definition.health),
+            walk_speed: self.walk_speed.unwrap_or(definition.walk_speed),
+            scale: self.scale.unwrap_or(definition.scale),
+            weapon_scale: self.weapon_scale.unwrap_or(definition.weapon_scale),
+            ..definition.clone()
+        }
+    }
+}
+
+thread_local! {
+    static DEFINITIONS: RefCell<DefinitionCache<BotDefinition>> = Default::default();
+}
+
+/// Animation resources of a bot, loaded all at once before anything is added to a scene,
+/// so missing file won't leave half-built bot in the scene.
+struct BotAnimations {
+    idle: Model,
+    walk: Model,
+    aim: Model,
+    whip: Model,
+    jump: Model,
+    falling: Model,
+    hit_reaction: Model,
+    dying: Model,
+    dead: Model,
+    crouch: Option<Model>,
+}
+
+impl BotAnimations {
+    async fn load(
+        resource_manager: &ResourceManager,
+        definition: &BotDefinition,
+    ) -> GameResult<Self> {
+        let (idle, walk, aim, whip, jump, falling, hit_reaction, dying, dead, crouch) = rg3d::futures::join!(
+            resource_manager.request_model(mods::resolve(definition.idle_animation)),
+            resource_manager.request_model(mods::resolve(definition.walk_animation)),
+            resource_manager.request_model(mods::resolve(definition.aim_animation)),
+            resource_manager.request_model(mods::resolve(definition.whip_animation)),
+            resource_manager.request_model(mods::resolve(definition.jump_animation)),
+            resource_manager.request_model(mods::resolve(definition.falling_animation)),
+            resource_manager.request_model(mods::resolve(definition.hit_reaction_animation)),
+            resource_manager.request_model(mods::resolve(definition.dying_animation)),
+            resource_manager.request_model(mods::resolve(definition.dead_animation)),
+            resource_manager.request_model(mods::resolve(definition.crouch_animation))
+        );
+
+        Ok(Self {
+            idle: idle.map_err(|_| GameError::missing_asset(definition.idle_animation))?,
+            walk: walk.map_err(|_| GameError::missing_asset(definition.walk_animation))?,
+            aim: aim.map_err(|_| GameError::missing_asset(definition.aim_animation))?,
+            whip: whip.map_err(|_| GameError::missing_asset(definition.whip_animation))?,
+            jump: jump.map_err(|_| GameError::missing_asset(definition.jump_animation))?,
+            falling: falling.map_err(|_| GameError::missing_asset(definition.falling_animation))?,
+            hit_reaction: hit_reaction
+                .map_err(|_| GameError::missing_asset(definition.hit_reaction_animation))?,
+            dying: dying.map_err(|_| GameError::missing_asset(definition.dying_animation))?,
+            dead: dead.map_err(|_| GameError::missing_asset(definition.dead_animation))?,
+            crouch: crouch.ok(),
+        })
+    }
+}
+
 fn prepare_animation(
     scene: &mut Scene,
-    model: Model,
+    model: &Model,
     root: Handle<Node>,
     spine: Handle<Node>,
 ) -> Handle<Animation> {
@@ -216,6 +609,8 @@ struct LocomotionMachine {
     machine: Machine,
     walk_animation: Handle<Animation>,
     walk_state: Handle<State>,
+    /// `Handle::NONE` if bot has no crouch animation.
+    crouch_state: Handle<State>,
 }
 
 impl Default for LocomotionMachine {
@@ -224,6 +619,7 @@ impl Default for LocomotionMachine {
             machine: Default::default(),
             walk_animation: Default::default(),
             walk_state: Default::default(),
+            crouch_state: Default::default(),
         }
     }
 }
@@ -235,6 +631,7 @@ impl Visit for LocomotionMachine {
         self.machine.visit("Machine", visitor)?;
         self.walk_animation.visit("WalkAnimation", visitor)?;
         self.walk_state.visit("WalkState", visitor)?;
+        self.crouch_state.visit("CrouchState", visitor)?;
 
         visitor.leave_region()
     }
@@ -249,25 +646,20 @@ impl LocomotionMachine {
     const IDLE_TO_JUMP_PARAM: &'static str = "IdleToJump";
     const JUMP_TO_FALLING_PARAM: &'static str = "JumpToFalling";
     const FALLING_TO_IDLE_PARAM: &'static str = "FallingToIdle";
+    const IDLE_TO_CROUCH_PARAM: &'static str = "IdleToCrouch";
+    const WALK_TO_CROUCH_PARAM: &'static str = "WalkToCrouch";
+    const CROUCH_TO_IDLE_PARAM: &'static str = "CrouchToIdle";
 
-    async fn new(
-        resource_manager: ResourceManager,
-        definition: &BotDefinition,
+    fn new(
+        animations: &BotAnimations,
         model: Handle<Node>,
         scene: &mut Scene,
         spine: Handle<Node>,
     ) -> Self {
-        let (idle_animation, walk_animation, jump_animation, falling_animation) = rg3d::futures::join!(
-            resource_manager.request_model(definition.idle_animation),
-            resource_manager.request_model(definition.walk_animation),
-            resource_manager.request_model(definition.jump_animation),
-            resource_manager.request_model(definition.falling_animation)
-        );
-
-        let idle_animation = prepare_animation(scene, idle_animation.unwrap(), model, spine);
-        let walk_animation = prepare_animation(scene, walk_animation.unwrap(), model, spine);
-        let jump_animation = prepare_animation(scene, jump_animation.unwrap(), model, spine);
-        let falling_animation = prepare_animation(scene, falling_animation.unwrap(), model, spine);
+        let idle_animation = prepare_animation(scene, &animations.idle, model, spine);
+        let walk_animation = prepare_animation(scene, &animations.walk, model, spine);
+        let jump_animation = prepare_animation(scene, &animations.jump, model, spine);
+        let falling_animation = prepare_animation(scene, &animations.falling, model, spine);
 
         scene
             .animations
@@ -334,11 +726,45 @@ impl LocomotionMachine {
                 Self::FALLING_TO_IDLE_PARAM,
             ));
 
+        let crouch_state = match animations.crouch.as_ref() {
+            Some(crouch) => {
+                let crouch_animation = prepare_animation(scene, crouch, model, spine);
+                let crouch_node =
+                    machine.add_node(machine::PoseNode::make_play_animation(crouch_animation));
+                let crouch_state = machine.add_state(State::new("Crouch", crouch_node));
+                machine
+                    .add_transition(machine::Transition::new(
+                        "Idle->Crouch",
+                        idle_state,
+                        crouch_state,
+                        0.3,
+                        Self::IDLE_TO_CROUCH_PARAM,
+                    ))
+                    .add_transition(machine::Transition::new(
+                        "Walk->Crouch",
+                        walk_state,
+                        crouch_state,
+                        0.3,
+                        Self::WALK_TO_CROUCH_PARAM,
+                    ))
+                    .add_transition(machine::Transition::new(
+                        "Crouch->Idle",
+                        crouch_state,
+                        idle_state,
+                        0.3,
+                        Self::CROUCH_TO_IDLE_PARAM,
+                    ));
+                crouch_state
+            }
+            None => Handle::NONE,
+        };
+
         machine.set_entry_state(idle_state);
 
         Self {
             walk_animation,
             walk_state,
+            crouch_state,
             machine,
         }
     }
@@ -361,16 +787,25 @@ impl LocomotionMachine {
         in_close_combat: bool,
         need_jump: bool,
         has_ground_contact: bool,
+        crouch: bool,
     ) {
+        // Without crouch animation crouching bot keeps walk animation, only its body is lower.
+        let crouch = crouch && self.crouch_state.is_some();
         self.machine
             .set_parameter(
                 Self::IDLE_TO_WALK_PARAM,
-                machine::Parameter::Rule(!in_close_combat),
+                machine::Parameter::Rule(!in_close_combat && !crouch),
             )
             .set_parameter(
                 Self::WALK_TO_IDLE_PARAM,
                 machine::Parameter::Rule(in_close_combat),
             )
+            .set_parameter(Self::IDLE_TO_CROUCH_PARAM, machine::Parameter::Rule(crouch))
+            .set_parameter(Self::WALK_TO_CROUCH_PARAM, machine::Parameter::Rule(crouch))
+            .set_parameter(
+                Self::CROUCH_TO_IDLE_PARAM,
+                machine::Parameter::Rule(!crouch),
+            )
             .set_parameter(
                 Self::WALK_TO_JUMP_PARAM,
                 machine::Parameter::Rule(need_jump),
@@ -413,20 +848,14 @@ impl Default for DyingMachine {
 impl DyingMachine {
     const DYING_TO_DEAD: &'static str = "DyingToDead";
 
-    async fn new(
-        resource_manager: ResourceManager,
-        definition: &BotDefinition,
+    fn new(
+        animations: &BotAnimations,
         model: Handle<Node>,
         scene: &mut Scene,
         spine: Handle<Node>,
     ) -> Self {
-        let (dying_animation, dead_animation) = rg3d::futures::join!(
-            resource_manager.request_model(definition.dying_animation),
-            resource_manager.request_model(definition.dead_animation)
-        );
-
-        let dying_animation = prepare_animation(scene, dying_animation.unwrap(), model, spine);
-        let dead_animation = prepare_animation(scene, dead_animation.unwrap(), model, spine);
+        let dying_animation = prepare_animation(scene, &animations.dying, model, spine);
+        let dead_animation = prepare_animation(scene, &animations.dead, model, spine);
 
         scene
             .animations
@@ -527,23 +956,17 @@ impl CombatMachine {
     const AIM_TO_HIT_REACTION_PARAM: &'static str = "AimToHitReaction";
     const WHIP_TO_HIT_REACTION_PARAM: &'static str = "WhipToHitReaction";
 
-    async fn new(
-        resource_manager: ResourceManager,
+    fn new(
+        animations: &BotAnimations,
         definition: &BotDefinition,
         model: Handle<Node>,
         scene: &mut Scene,
         spine: Handle<Node>,
     ) -> Self {
-        let (aim_animation, whip_animation, hit_reaction_animation) = rg3d::futures::join!(
-            resource_manager.request_model(definition.aim_animation),
-            resource_manager.request_model(definition.whip_animation),
-            resource_manager.request_model(definition.hit_reaction_animation)
-        );
-
-        let aim_animation = prepare_animation(scene, aim_animation.unwrap(), model, spine);
-        let whip_animation = prepare_animation(scene, whip_animation.unwrap(), model, spine);
+        let aim_animation = prepare_animation(scene, &animations.aim, model, spine);
+        let whip_animation = prepare_animation(scene, &animations.whip, model, spine);
         let hit_reaction_animation =
-            prepare_animation(scene, hit_reaction_animation.unwrap(), model, spine);
+            prepare_animation(scene, &animations.hit_reaction, model, spine);
 
         scene
             .animations
@@ -707,7 +1130,26 @@ impl Visit for CombatMachine {
 }
 
 impl Bot {
+    /// Definition of bot with stats from data file, see `definition` module.
     pub fn get_definition(kind: BotKind) -> &'static BotDefinition {
+        DEFINITIONS.with(|definitions| {
+            definitions.borrow_mut().get(kind.id() as usize, || {
+                let stats = definition::load_stats::<BotKind, BotStats>(definition::BOTS_FILE);
+                BotKind::ALL
+                    .iter()
+                    .map(|kind| {
+                        let builtin = Self::builtin_definition(*kind);
+                        stats
+                            .get(kind)
+                            .map_or_else(|| builtin.clone(), |stats| stats.apply(builtin))
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Definition used when data file has no stats for the bot.
+    fn builtin_definition(kind: BotKind) -> &'static BotDefinition {
         match kind {
             BotKind::Mutant => {
                 static DEFINITION: BotDefinition = BotDefinition {
@@ -722,6 +1164,7 @@ impl Bot {
                     dying_animation: assets::animations::mutant::DYING,
                     dead_animation: assets::animations::mutant::DEAD,
                     hit_reaction_animation: assets::animations::mutant::HIT_REACTION,
+                    crouch_animation: assets::animations::mutant::CROUCH,
                     weapon_hand_name: "Mutant:RightHand",
                     left_leg_name: "Mutant:LeftUpLeg",
                     right_leg_name: "Mutant:RightUpLeg",
@@ -747,6 +1190,7 @@ impl Bot {
                     dying_animation: assets::animations::parasite::DYING,
                     dead_animation: assets::animations::parasite::DEAD,
                     hit_reaction_animation: assets::animations::parasite::HIT_REACTION,
+                    crouch_animation: assets::animations::parasite::CROUCH,
                     weapon_hand_name: "RightHand",
                     left_leg_name: "LeftUpLeg",
                     right_leg_name: "RightUpLeg",
@@ -772,6 +1216,7 @@ impl Bot {
                     dying_animation: assets::animations::maw::DYING,
                     dead_animation: assets::animations::maw::DEAD,
                     hit_reaction_animation: assets::animations::maw::HIT_REACTION,
+                    crouch_animation: assets::animations::maw::CROUCH,
                     weapon_hand_name: "RightHand",
                     left_leg_name: "LeftUpLeg",
                     right_leg_name: "RightUpLeg",
@@ -793,15 +1238,16 @@ impl Bot {
         scene: &mut Scene,
         position: Vec3,
         sender: Sender<Message>,
-    ) -> Self {
+    ) -> GameResult<Self> {
         let definition = Self::get_definition(kind);
 
-        let body_height = 1.25;
-
-        let model = resource_manager
-            .request_model(Path::new(definition.model))
-            .await
-            .unwrap()
+        let (model, animations) = rg3d::futures::join!(
+            resource_manager.request_model(mods::resolve(definition.model)),
+            BotAnimations::load(&resource_manager, definition)
+        );
+        let animations = animations?;
+        let model = model
+            .map_err(|_| GameError::missing_asset(definition.model))?
             .instantiate_geometry(scene);
 
         let spine = scene.graph.find_by_name(model, definition.spine);
@@ -809,18 +1255,28 @@ impl Bot {
             print!("WARNING: Spine bone not found, bot won't aim vertically!");
         }
 
+        // Bot without a hand has nothing to hold its weapon.
+        let hand = scene.graph.find_by_name(model, definition.weapon_hand_name);
+        if hand.is_none() {
+            scene.graph.remove_node(model);
+            return Err(GameError::missing_node(
+                definition.model,
+                definition.weapon_hand_name,
+            ));
+        }
+
         let (pivot, body) = {
             let pivot = scene.graph.add_node(Node::Base(Default::default()));
             scene.graph.link_nodes(model, pivot);
             let transform = scene.graph[model].local_transform_mut();
-            transform.set_position(Vec3::new(0.0, -body_height * 0.5, 0.0));
+            transform.set_position(Vec3::new(0.0, -BODY_HEIGHT * 0.5, 0.0));
             transform.set_scale(Vec3::new(
                 definition.scale,
                 definition.scale,
                 definition.scale,
             ));
 
-            let capsule_shape = CapsuleShape::new(0.28, body_height, Axis::Y);
+            let capsule_shape = CapsuleShape::new(0.28, BODY_HEIGHT, Axis::Y);
             let mut capsule_body = RigidBody::new(ConvexShape::Capsule(capsule_shape));
             capsule_body.set_friction(Vec3::new(0.2, 0.0, 0.2));
             capsule_body.set_position(position);
@@ -830,7 +1286,6 @@ impl Bot {
             (pivot, body)
         };
 
-        let hand = scene.graph.find_by_name(model, definition.weapon_hand_name);
         let wpn_scale = definition.weapon_scale * (1.0 / definition.scale);
         let weapon_pivot = Node::Base(
             BaseBuilder::new()
@@ -848,15 +1303,11 @@ impl Bot {
         let weapon_pivot = scene.graph.add_node(weapon_pivot);
         scene.graph.link_nodes(weapon_pivot, hand);
 
-        let locomotion_machine =
-            LocomotionMachine::new(resource_manager.clone(), &definition, model, scene, spine)
-                .await;
-        let combat_machine =
-            CombatMachine::new(resource_manager.clone(), definition, model, scene, spine).await;
-        let dying_machine =
-            DyingMachine::new(resource_manager, definition, model, scene, spine).await;
+        let locomotion_machine = LocomotionMachine::new(&animations, model, scene, spine);
+        let combat_machine = CombatMachine::new(&animations, definition, model, scene, spine);
+        let dying_machine = DyingMachine::new(&animations, model, scene, spine);
 
-        Self {
+        Ok(Self {
             character: Character {
                 pivot,
                 body,
@@ -875,83 +1326,281 @@ impl Bot {
             combat_machine,
             dying_machine,
             ..Default::default()
-        }
+        })
     }
 
     pub fn can_be_removed(&self) -> bool {
         self.dying_machine.machine.active_state() == self.dying_machine.dead_state
     }
 
+    pub fn set_difficulty(&mut self, difficulty: BotDifficulty, skill: f32) {
+        self.difficulty = difficulty;
+        self.skill = skill;
+    }
+
+    pub fn set_zombie(&mut self, zombie: bool) {
+        self.zombie = zombie;
+    }
+
+    pub fn is_zombie(&self) -> bool {
+        self.zombie
+    }
+
+    /// Zombies always know where closest player is, this is much cheaper than vision checks
+    /// of regular bots which matters when there are lots of zombies.
+    fn select_zombie_target(&mut self, position: Vec3, targets: &[TargetDescriptor]) {
+        self.target = targets
+            .iter()
+            .filter(|desc| desc.is_player && desc.health > 0.0)
+            .min_by(|a, b| {
+                a.position
+                    .sqr_distance(&position)
+                    .partial_cmp(&b.position.sqr_distance(&position))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|desc| Target {
+                position: desc.position,
+                handle: desc.handle,
+            });
+        if let Some(target) = self.target.as_ref() {
+            self.point_of_interest = target.position;
+        }
+    }
+
+    pub fn on_near_miss(&mut self) {
+        self.morale.on_near_miss();
+    }
+
+    /// Counts enemies and allies around bot and feeds them to morale.
+    fn update_morale(
+        &mut self,
+        self_handle: Handle<Actor>,
+        position: Vec3,
+        targets: &[TargetDescriptor],
+        dt: f32,
+    ) {
+        let team = self.character.team();
+        let mut enemy_count = 0;
+        let mut enemy_center = Vec3::ZERO;
+        let mut ally_count = 0;
+        for desc in targets {
+            if desc.handle != self_handle
+                && desc.health > 0.0
+                && desc.position.distance(&position) <= AWARENESS_RADIUS
+            {
+                if !team.is_enemy(desc.team) {
+                    ally_count += 1;
+                } else {
+                    enemy_count += 1;
+                    enemy_center += desc.position;
+                }
+            }
+        }
+        if enemy_count > 0 {
+            enemy_center = enemy_center.scale(1.0 / enemy_count as f32);
+        }
+        self.morale
+            .update(dt, position, (enemy_count, enemy_center), ally_count);
+    }
+
+    /// Bot doesn't notice every mine, but once it noticed one it remembers it while it is
+    /// nearby.
+    fn notice_mine(&mut self, self_handle: Handle<Actor>, position: Vec3, mines: &Deployables) {
+        let mine = mines.closest_danger(
+            self_handle,
+            self.character.team(),
+            position,
+            MINE_AVOID_DISTANCE,
+        );
+        self.avoided_mine = match mine {
+            Some(mine) if self.avoided_mine == Some(mine) => Some(mine),
+            Some(mine)
+                if self.frustum.is_contains_point(mine)
+                    && random::rng().gen_bool(MINE_NOTICE_CHANCE) =>
+            {
+                Some(mine)
+            }
+            _ => None,
+        };
+    }
+
+    /// Bends direction of movement away from noticed mine, the closer mine is the harder.
+    fn steer_away_from_mine(&self, position: Vec3, move_dir: Vec3) -> Vec3 {
+        if let Some(mine) = self.avoided_mine {
+            let away = Vec3::new(position.x - mine.x, 0.0, position.z - mine.z);
+            let (away, distance) = away.normalized_ex();
+            if let Some(away) = away {
+                if distance < MINE_AVOID_DISTANCE {
+                    let k = 2.0 * (1.0 - distance / MINE_AVOID_DISTANCE);
+                    return (move_dir + away.scale(k)).normalized().unwrap_or(move_dir);
+                }
+            }
+        }
+        move_dir
+    }
+
     pub fn can_shoot(&self) -> bool {
         self.combat_machine.machine.active_state() == self.combat_machine.aim_state
     }
 
+    /// Selects closest visible enemy, enemies that carry objectives are preferred over closer
+    /// ones. Teammates are never selected.
     fn select_target(
         &mut self,
         self_handle: Handle<Actor>,
         scene: &Scene,
+        actor_grid: &SpatialHash<Handle<Actor>>,
         targets: &[TargetDescriptor],
     ) {
-        self.target = None;
+        let previous = self.target.take();
         let position = self.character.position(&scene.physics);
+        let team = self.character.team();
+        let mut closest_carrier = false;
         let mut closest_distance = std::f32::MAX;
-        let mut raycast_results = Vec::new();
-        'target_loop: for desc in targets {
-            if desc.handle != self_handle && self.frustum.is_contains_point(desc.position) {
-                if let Some(ray) = Ray::from_two_points(&position, &desc.position) {
-                    let options = RayCastOptions {
-                        ignore_bodies: false,
-                        ignore_static_geometries: false,
-                        sort_results: true,
-                    };
-                    if scene.physics.ray_cast(&ray, options, &mut raycast_results) {
-                        'hit_loop: for hit in raycast_results.iter() {
-                            match hit.kind {
-                                HitKind::StaticTriangle { .. } => {
-                                    // Target is behind something.
-                                    continue 'target_loop;
-                                }
-                                HitKind::Body(handle) => {
-                                    if self.character.body == handle {
-                                        continue 'hit_loop;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                let sqr_d = position.sqr_distance(&desc.position);
-                if sqr_d < closest_distance {
+        // Frustum starts at head of bot, so search radius is a bit bigger than vision distance.
+        let mut candidates = Vec::new();
+        actor_grid.query_radius(position, VISION_DISTANCE + 1.0, &mut candidates);
+        for &(handle, target_position) in candidates.iter() {
+            let desc = match targets.iter().find(|desc| desc.handle == handle) {
+                Some(desc) if team.is_enemy(desc.team) => desc,
+                _ => continue,
+            };
+            if handle != self_handle
+                && self.frustum.is_contains_point(target_position)
+                && character::is_in_sight(&scene.physics, position, target_position)
+            {
+                let sqr_d = position.sqr_distance(&target_position);
+                let carrier = desc.is_objective_carrier;
+                if (carrier && !closest_carrier)
+                    || (carrier == closest_carrier && sqr_d < closest_distance)
+                {
                     self.target = Some(Target {
-                        position: desc.position,
-                        handle: desc.handle,
+                        position: target_position,
+                        handle,
                     });
+                    closest_carrier = carrier;
                     closest_distance = sqr_d;
                 }
             }
         }
+
+        match (self.target.as_ref(), previous) {
+            (Some(target), previous) => {
+                // Target bot was already fighting or searching for doesn't take it by surprise.
+                let known = previous.map_or(false, |previous| previous.handle == target.handle)
+                    || self
+                        .last_seen
+                        .as_ref()
+                        .map_or(false, |last_seen| last_seen.handle == target.handle);
+                if !known {
+                    self.reaction_timer = self.difficulty.definition().reaction_time / self.skill;
+                }
+                self.last_seen = None;
+            }
+            (None, Some(previous)) => {
+                self.last_seen = Some(LastSeen {
+                    position: previous.position,
+                    handle: previous.handle,
+                    search_time: SEARCH_TIME,
+                });
+            }
+            (None, None) => (),
+        }
+    }
+
+    /// Leads bot to last known position of lost target until it is searched or bot gives up.
+    fn search_lost_target(&mut self, position: Vec3, time: GameTime) {
+        if let Some(last_seen) = self.last_seen.as_mut() {
+            last_seen.search_time -= time.delta;
+            if last_seen.search_time <= 0.0
+                || last_seen.position.distance(&position) <= SEARCH_REACH_DISTANCE
+            {
+                self.last_seen = None;
+            } else {
+                self.point_of_interest = last_seen.position;
+                // Keeps items from taking over point of interest while bot searches.
+                self.last_poi_update_time = time.elapsed;
+            }
+        }
+    }
+
+    /// Refreshes position of current target without visibility checks, used between target
+    /// selections.
+    fn follow_target(&mut self, targets: &[TargetDescriptor]) {
+        if let Some(target) = self.target.as_mut() {
+            match targets.iter().find(|desc| desc.handle == target.handle) {
+                Some(desc) => target.position = desc.position,
+                None => self.target = None,
+            }
+        }
+    }
+
+    pub fn set_objective_role(&mut self, role: ObjectiveRole) {
+        self.objective_role = role;
     }
 
-    fn select_point_of_interest(&mut self, items: &ItemContainer, scene: &Scene, time: &GameTime) {
+    fn select_point_of_interest(&mut self, self_handle: Handle<Actor>, context: &UpdateContext) {
+        let time = &context.time;
         if time.elapsed - self.last_poi_update_time >= 1.25 {
-            // Select closest non-despawned item as point of interest.
-            let self_position = self.position(&scene.physics);
-            let mut closest_distance = std::f32::MAX;
-            for item in items.iter() {
-                if !item.is_picked_up() {
-                    let item_position = item.position(&scene.graph);
-                    let sqr_d = item_position.sqr_distance(&self_position);
-                    if sqr_d < closest_distance {
-                        closest_distance = sqr_d;
-                        self.point_of_interest = item_position;
-                    }
+            // Objectives of the match come first, items are picked up when there are none.
+            let objective = context
+                .flags
+                .objective_point(self_handle, self.character.team(), self.objective_role)
+                .or_else(|| {
+                    context
+                        .oddball
+                        .and_then(|oddball| oddball.objective_point(self_handle))
+                })
+                .or_else(|| context.hill.and_then(|hill| hill.objective_point()));
+            if let Some(objective) = objective {
+                self.point_of_interest = objective;
+            } else {
+                // Select closest non-despawned item as point of interest, grid contains only
+                // items that can be picked up.
+                let self_position = self.position(&context.scene.physics);
+                if let Some((_, item_position)) = context.item_grid.closest(self_position, |_| true)
+                {
+                    self.point_of_interest = item_position;
                 }
             }
             self.last_poi_update_time = time.elapsed;
         }
     }
 
+    /// Direction of a shot at current target. Target is led by its velocity and flight time of
+    /// projectile, so strafing doesn't dodge every shot, then the direction is deviated by aim
+    /// error so bots aren't perfectly accurate.
+    fn shot_direction(
+        &self,
+        position: Vec3,
+        weapon: &Weapon,
+        targets: &[TargetDescriptor],
+    ) -> Option<Vec3> {
+        let target = self.target.as_ref()?;
+        let definition = self.difficulty.definition();
+
+        let velocity = targets
+            .iter()
+            .find(|desc| desc.handle == target.handle)
+            .map_or(Vec3::ZERO, |desc| desc.velocity);
+        let projectile = Weapon::get_definition(weapon.get_kind()).projectile;
+        // Both speed of projectile and velocity of target are in meters per second.
+        let flight_time =
+            target.position.distance(&position) / Projectile::get_definition(projectile).speed();
+        let aim_point = target.position + velocity.scale(flight_time * definition.lead_factor);
+        let direction = (aim_point - position).normalized()?;
+
+        let factor = if self.is_crouching() {
+            self.morale.jitter_factor() * CROUCH_AIM_FACTOR
+        } else {
+            self.morale.jitter_factor()
+        };
+        let spread = self
+            .aim_error
+            .spread(definition, self.movement, factor / self.skill);
+        AimError::apply(direction, spread)
+    }
+
     fn select_weapon(&mut self, weapons: &WeaponContainer) {
         if self.character.current_weapon().is_some()
             && weapons[self.character.current_weapon()].ammo() == 0
@@ -984,7 +1633,8 @@ impl Bot {
         let up = graph[self.model].up_vector();
         let look_at = head_pos + graph[self.model].look_vector();
         let view_matrix = Mat4::look_at(head_pos, look_at, up).unwrap_or_default();
-        let projection_matrix = Mat4::perspective(60.0f32.to_radians(), 16.0 / 9.0, 0.1, 7.0);
+        let projection_matrix =
+            Mat4::perspective(60.0f32.to_radians(), 16.0 / 9.0, 0.1, VISION_DISTANCE);
         let view_projection_matrix = projection_matrix * view_matrix;
         self.frustum = Frustum::from(view_projection_matrix).unwrap();
     }
@@ -1016,18 +1666,84 @@ impl Bot {
             .set_rotation(Quat::from_axis_angle(Vec3::UP, angle));
     }
 
-    fn rebuild_path(&mut self, position: Vec3, navmesh: &mut Navmesh, time: GameTime) {
+    fn is_crouching(&self) -> bool {
+        self.crouch_time > 0.0
+    }
+
+    /// Shrinks capsule of body when bot crouches and grows it back when bot stands up. Model
+    /// is moved with lower end of capsule, so feet stay on the ground.
+    fn update_body_height(&mut self, body: &mut RigidBody, graph: &mut Graph, dt: f32) {
+        let desired_height = if self.is_crouching() {
+            CROUCH_BODY_HEIGHT
+        } else {
+            BODY_HEIGHT
+        };
+        let capsule = body.get_shape_mut().as_capsule_mut();
+        let height = capsule.get_height();
+        if height == desired_height {
+            return;
+        }
+        let step = CROUCH_SPEED * dt;
+        let new_height = if desired_height < height {
+            (height - step).max(desired_height)
+        } else {
+            (height + step).min(desired_height)
+        };
+        capsule.set_height(new_height);
+        if new_height > height {
+            // Same as for player - body is pushed up, otherwise lower cap of capsule would go
+            // under the ground and physics would throw bot up.
+            let offset = (new_height - height) / 2.0;
+            let vel = body.get_velocity();
+            body.set_position(body.get_position() + Vec3::new(0.0, offset, 0.0));
+            body.set_velocity(vel - Vec3::new(0.0, offset * dt, 0.0));
+        }
+        graph[self.model]
+            .local_transform_mut()
+            .set_position(Vec3::new(0.0, -new_height * 0.5, 0.0));
+    }
+
+    fn rebuild_path(
+        &mut self,
+        position: Vec3,
+        navmesh: &mut Navmesh,
+        path_planner: &mut PathPlanner,
+        time: GameTime,
+    ) {
         let from = position - Vec3::new(0.0, 1.0, 0.0);
         if let Some(from_index) = navmesh.query_closest(from) {
             if let Some(to_index) = navmesh.query_closest(self.point_of_interest) {
-                self.current_path_point = 0;
-                // Rebuild path if target path vertex has changed.
-                if navmesh
-                    .build_path(from_index, to_index, &mut self.path)
-                    .is_ok()
-                {
-                    self.path.reverse();
+                // Current path still leads to the goal, keep following it unless bot was
+                // pushed away from it (by jump pad for example).
+                let on_path = self
+                    .path
+                    .get(self.current_path_point)
+                    .map_or(false, |point| {
+                        point.distance(&position) <= MAX_PATH_DEVIATION
+                    });
+                if self.path_goal == Some(to_index) && on_path {
                     self.last_path_rebuild_time = time.elapsed;
+                    return;
+                }
+
+                match path_planner.find_path(
+                    navmesh,
+                    from_index,
+                    to_index,
+                    &mut self.path,
+                    &mut self.jump_points,
+                ) {
+                    PathQuery::Found => {
+                        self.current_path_point = 0;
+                        self.path_goal = Some(to_index);
+                        self.last_path_rebuild_time = time.elapsed;
+                    }
+                    // Try again on next frame.
+                    PathQuery::Postponed => (),
+                    PathQuery::NotFound => {
+                        self.path_goal = None;
+                        self.last_path_rebuild_time = time.elapsed;
+                    }
                 }
             }
         }
@@ -1043,9 +1759,32 @@ impl Bot {
             self.dying_machine
                 .apply(context.scene, context.time, self.character.is_dead());
         } else {
-            self.select_target(self_handle, context.scene, targets);
+            self.think_timer -= context.time.delta;
+            let think = self.think_timer <= 0.0;
+            if think {
+                let position = self.character.position(&context.scene.physics);
+                let min_lod = if self.zombie { ZOMBIE_MIN_AI_LOD } else { 0 };
+                let lod = select_ai_lod(position, targets, min_lod);
+                self.think_timer = lod.think_interval;
+                self.path_rebuild_interval = lod.path_rebuild_interval;
+                if self.zombie {
+                    self.select_zombie_target(position, targets);
+                } else {
+                    self.update_frustum(position, &context.scene.graph);
+                    self.select_target(self_handle, context.scene, context.actor_grid, targets);
+                    self.select_point_of_interest(self_handle, context);
+                    self.notice_mine(self_handle, position, context.deployables);
+                }
+            } else {
+                self.follow_target(targets);
+            }
             self.select_weapon(context.weapons);
-            self.select_point_of_interest(context.items, context.scene, &context.time);
+            let position = self.character.position(&context.scene.physics);
+            if !self.zombie {
+                self.update_morale(self_handle, position, targets, context.time.delta);
+                self.search_lost_target(position, context.time);
+            }
+            self.reaction_timer -= context.time.delta;
 
             let has_ground_contact = self.character.has_ground_contact(&context.scene.physics);
             let body = context.scene.physics.borrow_body_mut(self.character.body);
@@ -1060,26 +1799,64 @@ impl Bot {
 
             let position = body.get_position();
 
+            // Bot under heavy fire at range crouches to make itself a smaller target.
+            let want_crouch = !self.zombie
+                && has_ground_contact
+                && !in_close_combat
+                && (self.target.is_none() || look_dir.len() >= CROUCH_MIN_DISTANCE)
+                && self.morale.suppression() > CROUCH_SUPPRESSION
+                && !self.morale.is_retreating();
+            // Every bot crouches in low passages, even one that has no crouch animation.
+            let in_crouch_zone = has_ground_contact
+                && (context
+                    .path_planner
+                    .is_crouch_only(position, CROUCH_ZONE_MARGIN)
+                    || context
+                        .path_planner
+                        .is_crouch_only(self.move_target, CROUCH_ZONE_MARGIN));
+            if want_crouch || in_crouch_zone {
+                self.crouch_time = CROUCH_MIN_TIME;
+            } else {
+                self.crouch_time -= context.time.delta;
+            }
+            let speed_factor = if self.is_crouching() {
+                self.character.speed_factor() * CROUCH_SPEED_FACTOR
+            } else {
+                self.character.speed_factor()
+            };
+
+            let mut link_jump = false;
             if let Some(path_point) = self.path.get(self.current_path_point) {
                 self.move_target = *path_point;
-                if self.move_target.distance(&position) <= 2.0
+                // Beginning of a jump must be reached precisely, otherwise bot will jump
+                // over jump pad or too early before a ledge.
+                let is_jump_point = self.jump_points.contains(&self.current_path_point);
+                let reach_distance = if is_jump_point { 0.75 } else { 2.0 };
+                if self.move_target.distance(&position) <= reach_distance
                     && self.current_path_point < self.path.len() - 1
                 {
                     self.current_path_point += 1;
+                    link_jump = is_jump_point;
                 }
             }
 
-            self.update_frustum(position, &context.scene.graph);
-
             if let Some(look_dir) = look_dir.normalized() {
                 self.aim_vertically(look_dir, &mut context.scene.graph, context.time);
                 self.aim_horizontally(look_dir, &mut context.scene.graph, context.time);
 
                 if !in_close_combat {
                     if has_ground_contact {
-                        if let Some(move_dir) = (self.move_target - position).normalized() {
-                            let vel =
-                                move_dir.scale(self.definition.walk_speed * context.time.delta);
+                        let move_target = if self.zombie {
+                            self.move_target
+                        } else {
+                            self.morale
+                                .move_target(position, look_dir, self.move_target)
+                        };
+                        if let Some(move_dir) = (move_target - position).normalized() {
+                            let move_dir = self.steer_away_from_mine(position, move_dir);
+                            let vel = move_dir.scale(
+                                self.definition.walk_speed * speed_factor * context.time.delta,
+                            );
                             body.set_x_velocity(vel.x);
                             body.set_z_velocity(vel.z);
                             self.last_move_dir = move_dir;
@@ -1088,19 +1865,43 @@ impl Bot {
                         // A bit of air control. This helps jump of ledges when there is jump pad below bot.
                         let vel = self
                             .last_move_dir
-                            .scale(self.definition.walk_speed * context.time.delta);
+                            .scale(self.definition.walk_speed * speed_factor * context.time.delta);
                         body.set_x_velocity(vel.x);
                         body.set_z_velocity(vel.z);
                     }
                 }
             }
 
-            let need_jump = look_dir.y >= 0.3 && has_ground_contact && in_close_combat;
+            let need_jump =
+                has_ground_contact && (link_jump || (look_dir.y >= 0.3 && in_close_combat));
             if need_jump {
-                body.set_y_velocity(0.08);
+                body.set_y_velocity(JUMP_SPEED * context.time.delta);
             }
+            self.update_body_height(body, &mut context.scene.graph, context.time.delta);
+            let velocity = body.get_velocity();
+            let walk_step =
+                self.definition.walk_speed * self.character.speed_factor() * context.time.delta;
+            self.movement = if walk_step > 0.0 {
+                Vec3::new(velocity.x, 0.0, velocity.z).len() / walk_step
+            } else {
+                0.0
+            };
+            self.aim_error.update(
+                self.target
+                    .as_ref()
+                    .map_or(Handle::NONE, |target| target.handle),
+                context.time.delta,
+            );
             let was_damaged = self.character.health < self.last_health;
             if was_damaged {
+                self.morale.on_damage(
+                    self.last_health - self.character.health,
+                    self.definition.health,
+                );
+                self.aim_error.on_damage(
+                    self.last_health - self.character.health,
+                    self.definition.health,
+                );
                 let hit_reaction = context
                     .scene
                     .animations
@@ -1119,6 +1920,7 @@ impl Bot {
                 in_close_combat,
                 need_jump,
                 has_ground_contact,
+                self.is_crouching(),
             );
             self.combat_machine.apply(
                 context.scene,
@@ -1130,17 +1932,25 @@ impl Bot {
 
             let sender = self.character.sender.as_ref().unwrap();
 
-            if !in_close_combat && can_aim && self.can_shoot() && self.target.is_some() {
+            if !in_close_combat
+                && can_aim
+                && self.can_shoot()
+                && self.target.is_some()
+                && self.reaction_timer <= 0.0
+            {
                 if let Some(weapon) = self
                     .character
                     .weapons
                     .get(self.character.current_weapon as usize)
                 {
+                    let direction = self
+                        .shot_direction(position, &context.weapons[*weapon], targets)
+                        .unwrap_or(look_dir);
                     sender
                         .send(Message::ShootWeapon {
                             weapon: *weapon,
                             initial_velocity: Vec3::ZERO,
-                            direction: Some(look_dir),
+                            direction: Some(direction),
                         })
                         .unwrap();
                 }
@@ -1158,8 +1968,10 @@ impl Bot {
                         sender
                             .send(Message::DamageActor {
                                 actor: target.handle,
-                                who: Default::default(),
+                                who: self_handle,
                                 amount: 20.0,
+                                source: DamageSource::Melee,
+                                impulse: Vec3::ZERO,
                             })
                             .unwrap();
                     }
@@ -1177,22 +1989,23 @@ impl Bot {
                     if event.signal_id == LocomotionMachine::STEP_SIGNAL && has_ground_contact {
                         sender
                             .send(Message::PlaySound {
-                                path: assets::sounds::footsteps::SHOE_STONE[rand::thread_rng()
-                                    .gen_range(0, assets::sounds::footsteps::SHOE_STONE.len())]
-                                .into(),
+                                path: assets::sounds::footsteps::SHOE_STONE[0].into(),
                                 position,
                                 gain: 1.0,
                                 rolloff_factor: 2.0,
                                 radius: 3.0,
+                                category: SoundCategory::Footstep,
                             })
                             .unwrap();
                     }
                 }
             }
 
-            if context.time.elapsed - self.last_path_rebuild_time >= 1.0 {
+            if think
+                && context.time.elapsed - self.last_path_rebuild_time >= self.path_rebuild_interval
+            {
                 if let Some(navmesh) = context.navmesh.as_mut() {
-                    self.rebuild_path(position, navmesh, context.time);
+                    self.rebuild_path(position, navmesh, context.path_planner, context.time);
                 }
             }
             self.restoration_time -= context.time.delta;
@@ -1212,6 +2025,11 @@ impl Bot {
                 self.target = None;
             }
         }
+        if let Some(last_seen) = self.last_seen.as_ref() {
+            if last_seen.handle == handle {
+                self.last_seen = None;
+            }
+        }
     }
 
     pub fn set_point_of_interest(&mut self, poi: Vec3, time: GameTime) {
@@ -1242,12 +2060,17 @@ impl Visit for Bot {
         self.character.visit("Character", visitor)?;
         self.model.visit("Model", visitor)?;
         self.target.visit("Target", visitor)?;
+        self.last_seen.visit("LastSeen", visitor)?;
         self.locomotion_machine
             .visit("LocomotionMachine", visitor)?;
         self.combat_machine.visit("AimMachine", visitor)?;
         self.restoration_time.visit("RestorationTime", visitor)?;
         self.yaw.visit("Yaw", visitor)?;
         self.pitch.visit("Pitch", visitor)?;
+        self.difficulty.visit("Difficulty", visitor)?;
+        self.skill.visit("Skill", visitor)?;
+        self.morale.visit("Morale", visitor)?;
+        self.zombie.visit("Zombie", visitor)?;
 
         visitor.leave_region()
     }
@@ -1,15 +1,28 @@
-use crate::{message::Message, weapon::Weapon};
+use crate::{effects::EffectKind, message::Message, weapon::Weapon};
 use rg3d::{
     core::{
-        math::vec3::Vec3,
+        math::{ray::Ray, vec3::Vec3},
         pool::Handle,
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
-    physics::{rigid_body::RigidBody, Physics},
+    physics::{rigid_body::RigidBody, HitKind, Physics, RayCastOptions},
     scene::{node::Node, Scene},
 };
 use std::sync::mpsc::Sender;
 
+/// Multiplier of movement speed of character with haste.
+const HASTE_SPEED_FACTOR: f32 = 1.3;
+/// Multiplier of movement speed of flag carrier.
+const FLAG_CARRIER_SPEED_FACTOR: f32 = 0.85;
+/// Amount of overshield given by a pickup.
+pub const MAX_SHIELD: f32 = 100.0;
+/// Overshield is lost by this amount every second.
+const SHIELD_DECAY: f32 = 4.0;
+/// Time between two shimmer effects of character with overshield.
+const SHIMMER_INTERVAL: f32 = 0.3;
+/// Health can't be healed above this, unless handicap says otherwise.
+pub const MAX_HEALTH: f32 = 150.0;
+
 pub struct Character {
     pub name: String,
     pub pivot: Handle<Node>,
@@ -21,6 +34,53 @@ pub struct Character {
     pub weapon_pivot: Handle<Node>,
     pub sender: Option<Sender<Message>>,
     pub team: Team,
+    /// Time left of haste, see `kill_streak` module.
+    haste_time: f32,
+    /// Overshield, absorbs damage before armor and health.
+    shield: f32,
+    /// Not saved, at worst shimmer appears a bit earlier after load.
+    shimmer_timer: f32,
+    /// Set by level every frame, so it isn't saved.
+    carries_flag: bool,
+    handicap: Handicap,
+}
+
+/// Evens out a match between actors of different skill by scaling health of an actor and
+/// damage it deals to others. Factors are set in match menu as percents.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Handicap {
+    /// Multiplier of spawn health and of max health.
+    pub health: f32,
+    pub damage: f32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            damage: 1.0,
+        }
+    }
+}
+
+impl Handicap {
+    pub fn from_percents(health: u32, damage: u32) -> Self {
+        Self {
+            health: health as f32 / 100.0,
+            damage: damage as f32 / 100.0,
+        }
+    }
+}
+
+impl Visit for Handicap {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.health.visit("Health", visitor)?;
+        self.damage.visit("Damage", visitor)?;
+
+        visitor.leave_region()
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
@@ -36,6 +96,33 @@ impl Default for Team {
     }
 }
 
+/// Returns true if level geometry doesn't block sight between two points, bodies of actors
+/// don't block it. Same test is used by bots to see targets and by aim assist of player.
+pub fn is_in_sight(physics: &Physics, from: Vec3, to: Vec3) -> bool {
+    let ray = match Ray::from_two_points(&from, &to) {
+        Some(ray) => ray,
+        None => return true,
+    };
+    let options = RayCastOptions {
+        ignore_bodies: true,
+        ignore_static_geometries: false,
+        sort_results: false,
+    };
+    let mut results = Vec::new();
+    physics.ray_cast(&ray, options, &mut results);
+    !results
+        .iter()
+        .any(|hit| matches!(hit.kind, HitKind::StaticTriangle { .. }))
+}
+
+impl Team {
+    /// Returns true if actor of this team fights actor of other team. Actors without team
+    /// fight everyone.
+    pub fn is_enemy(self, other: Team) -> bool {
+        self == Team::None || self != other
+    }
+}
+
 impl Visit for Team {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut id = match self {
@@ -69,6 +156,11 @@ impl Default for Character {
             weapon_pivot: Handle::NONE,
             sender: None,
             team: Team::None,
+            haste_time: 0.0,
+            shield: 0.0,
+            shimmer_timer: 0.0,
+            carries_flag: false,
+            handicap: Default::default(),
         }
     }
 }
@@ -86,6 +178,9 @@ impl Visit for Character {
         self.current_weapon.visit("CurrentWeapon", visitor)?;
         self.weapon_pivot.visit("WeaponPivot", visitor)?;
         self.team.visit("Team", visitor)?;
+        self.haste_time.visit("HasteTime", visitor)?;
+        self.shield.visit("Shield", visitor)?;
+        self.handicap.visit("Handicap", visitor)?;
 
         visitor.leave_region()
     }
@@ -114,6 +209,73 @@ impl Character {
         self.team
     }
 
+    /// Makes character faster for given amount of seconds.
+    pub fn give_haste(&mut self, time: f32) {
+        self.haste_time = self.haste_time.max(time);
+    }
+
+    pub fn update_haste(&mut self, dt: f32) {
+        self.haste_time = (self.haste_time - dt).max(0.0);
+    }
+
+    pub fn give_shield(&mut self) {
+        self.shield = MAX_SHIELD;
+    }
+
+    pub fn get_shield(&self) -> f32 {
+        self.shield
+    }
+
+    /// Decays overshield and makes character shimmer while it has some.
+    pub fn update_shield(&mut self, physics: &Physics, dt: f32) {
+        if self.shield <= 0.0 || self.is_dead() {
+            return;
+        }
+        self.shield = (self.shield - SHIELD_DECAY * dt).max(0.0);
+        self.shimmer_timer -= dt;
+        if self.shimmer_timer <= 0.0 {
+            self.shimmer_timer = SHIMMER_INTERVAL;
+            if let Some(sender) = self.sender.as_ref() {
+                sender
+                    .send(Message::CreateEffect {
+                        kind: EffectKind::ShieldShimmer,
+                        position: self.position(physics),
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Sets handicap of freshly spawned character, its health is scaled right away.
+    pub fn set_handicap(&mut self, handicap: Handicap) {
+        self.handicap = handicap;
+        self.health *= handicap.health;
+    }
+
+    pub fn handicap(&self) -> Handicap {
+        self.handicap
+    }
+
+    pub fn max_health(&self) -> f32 {
+        MAX_HEALTH * self.handicap.health
+    }
+
+    pub fn set_carries_flag(&mut self, carries_flag: bool) {
+        self.carries_flag = carries_flag;
+    }
+
+    /// Multiplier of movement speed.
+    pub fn speed_factor(&self) -> f32 {
+        let mut factor = 1.0;
+        if self.haste_time > 0.0 {
+            factor *= HASTE_SPEED_FACTOR;
+        }
+        if self.carries_flag {
+            factor *= FLAG_CARRIER_SPEED_FACTOR;
+        }
+        factor
+    }
+
     pub fn get_health(&self) -> f32 {
         self.health
     }
@@ -132,8 +294,14 @@ impl Character {
         physics.borrow_body(self.get_body()).get_position()
     }
 
+    /// Damage is absorbed by overshield first, then by armor and only then by health.
     pub fn damage(&mut self, amount: f32) {
-        let amount = amount.abs();
+        let absorbed = amount.abs().min(self.shield);
+        self.shield -= absorbed;
+        let amount = amount.abs() - absorbed;
+        if amount <= 0.0 {
+            return;
+        }
         if self.armor > 0.0 {
             self.armor -= amount;
             if self.armor < 0.0 {
@@ -147,8 +315,8 @@ impl Character {
     pub fn heal(&mut self, amount: f32) {
         self.health += amount.abs();
 
-        if self.health > 150.0 {
-            self.health = 150.0;
+        if self.health > self.max_health() {
+            self.health = self.max_health();
         }
     }
 
@@ -238,3 +406,47 @@ impl Character {
         scene.physics.remove_body(self.body);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_is_absorbed_by_shield_then_armor_then_health() {
+        let mut character = Character::default();
+        character.give_shield();
+
+        character.damage(MAX_SHIELD + 30.0);
+        assert_eq!(character.get_shield(), 0.0);
+        assert_eq!(character.get_armor(), 70.0);
+        assert_eq!(character.get_health(), 100.0);
+
+        // Armor breaks and the rest goes to health.
+        character.damage(90.0);
+        assert_eq!(character.get_health(), 80.0);
+
+        character.damage(80.0);
+        assert!(character.is_dead());
+    }
+
+    #[test]
+    fn negative_damage_still_hurts() {
+        let mut character = Character {
+            armor: 0.0,
+            ..Default::default()
+        };
+        character.damage(-25.0);
+        assert_eq!(character.get_health(), 75.0);
+    }
+
+    #[test]
+    fn handicap_scales_spawn_health_and_heal_cap() {
+        let mut character = Character::default();
+        character.set_handicap(Handicap::from_percents(50, 100));
+        assert_eq!(character.get_health(), 50.0);
+
+        character.heal(1000.0);
+        assert_eq!(character.get_health(), MAX_HEALTH * 0.5);
+        assert_eq!(character.max_health(), MAX_HEALTH * 0.5);
+    }
+}
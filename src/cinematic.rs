@@ -0,0 +1,225 @@
+//! Cinematic camera - plays back a camera path authored for a map while the match goes on,
+//! useful for making footage. Path is stored next to the map model in a file with
+//! `camera.json` extension (`dm6.camera.json` for `dm6.fbx`), mods can override it:
+//!
+//! ```json
+//! {
+//!     "looped": false,
+//!     "keyframes": [
+//!         { "time": 0.0, "position": [0.0, 3.0, 0.0], "target": [5.0, 1.0, 5.0], "fov": 75.0 },
+//!         { "time": 4.0, "position": [8.0, 4.0, 2.0], "target": [5.0, 1.0, 5.0] }
+//!     ]
+//! }
+//! ```
+//!
+//! Position and target of camera move along Catmull-Rom splines through keyframes, playback
+//! smoothly speeds up at the start and slows down at the end of the path.
+
+use crate::{mods, GameEngine};
+use rg3d::{
+    core::{
+        math::{quat::Quat, vec3::Vec3},
+        pool::Handle,
+    },
+    scene::{base::BaseBuilder, camera::CameraBuilder, node::Node, Scene},
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Duration of speed up and slow down at the ends of a path, in seconds.
+const EASE_TIME: f32 = 1.0;
+
+fn default_fov() -> f32 {
+    75.0
+}
+
+#[derive(Deserialize)]
+struct Keyframe {
+    /// Time since start of playback in seconds.
+    time: f32,
+    position: [f32; 3],
+    /// Point camera looks at.
+    target: [f32; 3],
+    /// Vertical field of view in degrees.
+    #[serde(default = "default_fov")]
+    fov: f32,
+}
+
+#[derive(Deserialize)]
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    /// Playback starts over when path is finished, otherwise cinematic camera is turned off.
+    #[serde(default)]
+    looped: bool,
+}
+
+fn to_vec3(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1.scale(2.0)
+        + (p2 - p0).scale(t)
+        + (p0.scale(2.0) - p1.scale(5.0) + p2.scale(4.0) - p3).scale(t2)
+        + (p1.scale(3.0) - p0 - p2.scale(3.0) + p3).scale(t3))
+    .scale(0.5)
+}
+
+impl CameraPath {
+    /// Returns path of camera path file of a map.
+    pub fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("camera.json")
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read camera path {}: {}", path.display(), e))?;
+        let mut camera_path = serde_json::from_str::<CameraPath>(&data)
+            .map_err(|e| format!("Camera path {} is corrupted: {}", path.display(), e))?;
+        if camera_path.keyframes.len() < 2 {
+            return Err(format!(
+                "Camera path {} must have at least two keyframes",
+                path.display()
+            ));
+        }
+        camera_path
+            .keyframes
+            .sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Ok(camera_path)
+    }
+
+    fn duration(&self) -> f32 {
+        self.keyframes.last().unwrap().time
+    }
+
+    /// Maps real playback time to time on the path, so camera accelerates during first
+    /// seconds and decelerates during last ones instead of starting and stopping abruptly.
+    fn eased_time(&self, time: f32) -> f32 {
+        let duration = self.duration();
+        let ease = EASE_TIME.min(duration * 0.5);
+        if ease <= 0.0 {
+            return time;
+        }
+        let time = time.max(0.0).min(duration);
+        let eased = if time < ease {
+            time * time / (2.0 * ease)
+        } else if time > duration - ease {
+            let left = duration - time;
+            duration - ease - left * left / (2.0 * ease)
+        } else {
+            time - ease * 0.5
+        };
+        eased * duration / (duration - ease)
+    }
+
+    /// Returns position, target and field of view of camera at given time.
+    fn sample(&self, time: f32) -> (Vec3, Vec3, f32) {
+        let time = self.eased_time(time);
+        let keys = &self.keyframes;
+        let last = keys.len() - 1;
+        let i = keys
+            .iter()
+            .rposition(|key| key.time <= time)
+            .unwrap_or(0)
+            .min(last - 1);
+        let (k0, k1, k2, k3) = (
+            &keys[i.saturating_sub(1)],
+            &keys[i],
+            &keys[i + 1],
+            &keys[(i + 2).min(last)],
+        );
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 {
+            ((time - k1.time) / span).max(0.0).min(1.0)
+        } else {
+            1.0
+        };
+        let position = catmull_rom(
+            to_vec3(k0.position),
+            to_vec3(k1.position),
+            to_vec3(k2.position),
+            to_vec3(k3.position),
+            t,
+        );
+        let target = catmull_rom(
+            to_vec3(k0.target),
+            to_vec3(k1.target),
+            to_vec3(k2.target),
+            to_vec3(k3.target),
+            t,
+        );
+        (position, target, k1.fov + (k2.fov - k1.fov) * t)
+    }
+}
+
+pub struct CinematicCamera {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    path: CameraPath,
+    time: f32,
+}
+
+impl CinematicCamera {
+    pub fn new(engine: &mut GameEngine, scene: Handle<Scene>, path: CameraPath) -> Self {
+        let camera = engine.scenes[scene]
+            .graph
+            .add_node(Node::Camera(CameraBuilder::new(BaseBuilder::new()).build()));
+        let mut cinematic = Self {
+            scene,
+            camera,
+            path,
+            time: 0.0,
+        };
+        cinematic.update(engine, 0.0);
+        cinematic
+    }
+
+    /// Removes camera of path and enables given camera.
+    pub fn leave(self, engine: &mut GameEngine, camera: Handle<Node>) {
+        let scene = &mut engine.scenes[self.scene];
+        scene.remove_node(self.camera);
+        if let Node::Camera(camera) = &mut scene.graph[camera] {
+            camera.set_enabled(true);
+        }
+    }
+
+    /// Moves camera along path, returns false when path is over. Match goes on while path is
+    /// played, so cameras that level enables (when player dies or respawns) are turned off
+    /// every frame.
+    pub fn update(&mut self, engine: &mut GameEngine, dt: f32) -> bool {
+        self.time += dt;
+        if self.time > self.path.duration() {
+            if !self.path.looped {
+                return false;
+            }
+            self.time %= self.path.duration();
+        }
+
+        let scene = &mut engine.scenes[self.scene];
+        for (handle, node) in scene.graph.pair_iter_mut() {
+            if let Node::Camera(camera) = node {
+                if handle != self.camera && camera.is_enabled() {
+                    camera.set_enabled(false);
+                }
+            }
+        }
+
+        let (position, target, fov) = self.path.sample(self.time);
+        let look = (target - position).normalized().unwrap_or(Vec3::LOOK);
+        let yaw = look.x.atan2(look.z);
+        let pitch = (-look.y).max(-1.0).min(1.0).asin();
+        let rotation =
+            Quat::from_axis_angle(Vec3::UP, yaw) * Quat::from_axis_angle(Vec3::RIGHT, pitch);
+        if let Node::Camera(camera) = &mut scene.graph[self.camera] {
+            camera
+                .local_transform_mut()
+                .set_position(position)
+                .set_rotation(rotation);
+            camera.set_fov(fov.to_radians());
+        }
+        true
+    }
+}
@@ -0,0 +1,186 @@
+//! Modal window that asks user to confirm an action which can't be undone (quit, end match,
+//! overwrite save). Action is described by a message which is sent only if user agreed.
+
+use crate::{
+    message::Message,
+    navigation::{FocusChain, NavigationAction},
+    BuildContext, Gui, GuiMessage, UINodeHandle,
+};
+use rg3d::{
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    gui::{
+        button::ButtonBuilder,
+        grid::{Column, GridBuilder, Row},
+        message::{ButtonMessage, MessageDirection, TextMessage, UiMessageData, WindowMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        HorizontalAlignment, Thickness, VerticalAlignment,
+    },
+};
+use std::sync::mpsc::Sender;
+
+pub struct ConfirmDialog {
+    sender: Sender<Message>,
+    pub window: UINodeHandle,
+    question: UINodeHandle,
+    btn_yes: UINodeHandle,
+    btn_no: UINodeHandle,
+    /// Message which will be sent if user confirms action. Dialog is open while it is some.
+    action: Option<Message>,
+    focus_chain: FocusChain,
+}
+
+impl ConfirmDialog {
+    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+        let mut make_button = |column: usize, text: &str| {
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(column)
+                    .with_height(30.0)
+                    .with_margin(Thickness::uniform(4.0)),
+            )
+            .with_text(text)
+            .build(ctx)
+        };
+
+        let btn_yes = make_button(0, "Yes");
+        let btn_no = make_button(1, "No");
+
+        let question = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(0)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+
+        let content = GridBuilder::new(
+            WidgetBuilder::new().with_child(question).with_child(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(1)
+                        .with_child(btn_yes)
+                        .with_child(btn_no),
+                )
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            ),
+        )
+        .add_row(Row::strict(60.0))
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0))
+            .with_title(WindowTitle::text("Confirm"))
+            .open(false)
+            .can_resize(false)
+            .can_minimize(false)
+            .can_close(false)
+            .with_content(content)
+            .build(ctx);
+
+        Self {
+            sender,
+            window,
+            question,
+            btn_yes,
+            btn_no,
+            action: None,
+            focus_chain: FocusChain::new(vec![btn_yes, btn_no]),
+        }
+    }
+
+    /// Opens dialog with given question, `action` will be sent only if user answers "Yes".
+    pub fn ask(&mut self, ui: &mut Gui, question: &str, action: Message) {
+        self.action = Some(action);
+        ui.send_message(TextMessage::text(
+            self.question,
+            MessageDirection::ToWidget,
+            question.to_owned(),
+        ));
+        // Safe choice is default one.
+        self.focus_chain.focus(ui, self.btn_no);
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    /// Asks for confirmation only if `needed` is true, otherwise sends `action` immediately.
+    pub fn ask_if(&mut self, ui: &mut Gui, needed: bool, question: &str, action: Message) {
+        if needed {
+            self.ask(ui, question, action);
+        } else {
+            self.sender.send(action).unwrap();
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.action.is_some()
+    }
+
+    pub fn close(&mut self, ui: &mut Gui) {
+        self.action = None;
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn confirm(&mut self, ui: &mut Gui) {
+        if let Some(action) = self.action.take() {
+            self.sender.send(action).unwrap();
+        }
+        self.close(ui);
+    }
+
+    /// Left/Right or Tab switches between answers, Enter activates selected answer, Y/N
+    /// answers directly and Escape cancels.
+    pub fn process_input_event(&mut self, ui: &mut Gui, event: &Event<()>) {
+        if !self.is_open() {
+            return;
+        }
+
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                if let ElementState::Pressed = input.state {
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::Y) => {
+                            self.confirm(ui);
+                            return;
+                        }
+                        Some(VirtualKeyCode::N) => {
+                            self.close(ui);
+                            return;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        if let Some(action) = NavigationAction::from_event(event) {
+            if !self.focus_chain.process(ui, action) {
+                self.close(ui);
+            }
+        }
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_yes {
+                self.confirm(ui);
+            } else if message.destination() == self.btn_no {
+                self.close(ui);
+            }
+        }
+    }
+}
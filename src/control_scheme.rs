@@ -27,6 +27,124 @@ impl ControlButton {
     }
 }
 
+/// Ready-made layouts of buttons. Applying a preset rebinds every button and keeps the rest
+/// of settings, so player can pick the closest layout and fine-tune it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ControlPreset {
+    Wasd,
+    Esdf,
+    /// Movement on arrows, actions on keys around them.
+    Arrows,
+    /// Mouse in left hand with shooting on right button, movement on IJKL.
+    LeftHandedMouse,
+}
+
+impl ControlPreset {
+    pub const ALL: [ControlPreset; 4] = [
+        ControlPreset::Wasd,
+        ControlPreset::Esdf,
+        ControlPreset::Arrows,
+        ControlPreset::LeftHandedMouse,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlPreset::Wasd => "WASD",
+            ControlPreset::Esdf => "ESDF",
+            ControlPreset::Arrows => "Arrows",
+            ControlPreset::LeftHandedMouse => "Left-Handed Mouse",
+        }
+    }
+
+    /// Buttons in order of `ControlScheme::buttons`.
+    fn buttons(self) -> [ControlButton; 18] {
+        use ControlButton::{Key, Mouse, WheelDown, WheelUp};
+        use VirtualKeyCode::*;
+        match self {
+            ControlPreset::Wasd => [
+                Key(W),
+                Key(S),
+                Key(A),
+                Key(D),
+                Key(Space),
+                Key(C),
+                Mouse(3),
+                Mouse(1),
+                WheelUp,
+                WheelDown,
+                Key(LShift),
+                Key(L),
+                Key(F),
+                Key(R),
+                Key(E),
+                Key(G),
+                Key(Q),
+                Key(V),
+            ],
+            ControlPreset::Esdf => [
+                Key(E),
+                Key(D),
+                Key(S),
+                Key(F),
+                Key(Space),
+                Key(V),
+                Mouse(3),
+                Mouse(1),
+                WheelUp,
+                WheelDown,
+                Key(A),
+                Key(L),
+                Key(G),
+                Key(T),
+                Key(R),
+                Key(B),
+                Key(W),
+                Key(Q),
+            ],
+            ControlPreset::Arrows => [
+                Key(Up),
+                Key(Down),
+                Key(Left),
+                Key(Right),
+                Key(RControl),
+                Key(End),
+                Mouse(3),
+                Mouse(1),
+                WheelUp,
+                WheelDown,
+                Key(RShift),
+                Key(Insert),
+                Key(Home),
+                Key(PageUp),
+                Key(Delete),
+                Key(PageDown),
+                Key(RAlt),
+                Key(Return),
+            ],
+            ControlPreset::LeftHandedMouse => [
+                Key(I),
+                Key(K),
+                Key(J),
+                Key(L),
+                Key(Space),
+                Key(N),
+                Mouse(1),
+                Mouse(3),
+                WheelUp,
+                WheelDown,
+                Key(RShift),
+                Key(P),
+                Key(H),
+                Key(Y),
+                Key(U),
+                Key(B),
+                Key(O),
+                Key(M),
+            ],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlButtonDefinition {
     pub description: String,
@@ -46,10 +164,32 @@ pub struct ControlScheme {
     pub next_weapon: ControlButtonDefinition,
     pub prev_weapon: ControlButtonDefinition,
     pub run: ControlButtonDefinition,
+    pub toggle_laser: ControlButtonDefinition,
+    pub flashlight: ControlButtonDefinition,
+    pub reset_range: ControlButtonDefinition,
+    pub interact: ControlButtonDefinition,
+    pub place_mine: ControlButtonDefinition,
+    pub hold_breath: ControlButtonDefinition,
+    pub melee: ControlButtonDefinition,
     pub mouse_sens: f32,
     pub mouse_y_inverse: bool,
     pub smooth_mouse: bool,
     pub shake_camera: bool,
+    /// Weapon is held on the other side of the screen.
+    pub left_handed: bool,
+    /// Extra distance of weapon from camera, negative values bring weapon closer.
+    pub viewmodel_distance: f32,
+    pub hide_viewmodel: bool,
+    /// Heavy hits punch view of player and slow it down for a moment.
+    pub hit_stagger: bool,
+    /// View slows down over enemies and is pulled towards them, see `aim_assist`.
+    pub aim_assist: bool,
+    /// Strength of pull towards enemy, from 0 to 1.
+    pub aim_assist_magnetism: f32,
+    /// Strength of slowdown of view over enemy, from 0 to 1.
+    pub aim_assist_slowdown: f32,
+    /// Accessibility option, shots go at enemy near crosshair, see `aim_assist`.
+    pub auto_aim: bool,
 }
 
 impl Default for ControlScheme {
@@ -99,16 +239,52 @@ impl Default for ControlScheme {
                 description: "Run".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::LShift),
             },
+            toggle_laser: ControlButtonDefinition {
+                description: "Toggle Laser".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::L),
+            },
+            flashlight: ControlButtonDefinition {
+                description: "Flashlight".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::F),
+            },
+            reset_range: ControlButtonDefinition {
+                description: "Reset Range".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::R),
+            },
+            interact: ControlButtonDefinition {
+                description: "Interact".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::E),
+            },
+            place_mine: ControlButtonDefinition {
+                description: "Place Mine".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::G),
+            },
+            hold_breath: ControlButtonDefinition {
+                description: "Hold Breath".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Q),
+            },
+            melee: ControlButtonDefinition {
+                description: "Melee".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::V),
+            },
             mouse_sens: 0.2,
             mouse_y_inverse: false,
             smooth_mouse: true,
             shake_camera: true,
+            left_handed: false,
+            viewmodel_distance: 0.0,
+            hide_viewmodel: false,
+            hit_stagger: false,
+            aim_assist: false,
+            aim_assist_magnetism: 0.5,
+            aim_assist_slowdown: 0.5,
+            auto_aim: false,
         }
     }
 }
 
 impl ControlScheme {
-    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 11] {
+    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 18] {
         [
             &mut self.move_forward,
             &mut self.move_backward,
@@ -121,10 +297,17 @@ impl ControlScheme {
             &mut self.next_weapon,
             &mut self.prev_weapon,
             &mut self.run,
+            &mut self.toggle_laser,
+            &mut self.flashlight,
+            &mut self.reset_range,
+            &mut self.interact,
+            &mut self.place_mine,
+            &mut self.hold_breath,
+            &mut self.melee,
         ]
     }
 
-    pub fn buttons(&self) -> [&ControlButtonDefinition; 11] {
+    pub fn buttons(&self) -> [&ControlButtonDefinition; 18] {
         [
             &self.move_forward,
             &self.move_backward,
@@ -137,10 +320,66 @@ impl ControlScheme {
             &self.next_weapon,
             &self.prev_weapon,
             &self.run,
+            &self.toggle_laser,
+            &self.flashlight,
+            &self.reset_range,
+            &self.interact,
+            &self.place_mine,
+            &self.hold_breath,
+            &self.melee,
         ]
     }
 
     pub fn reset(&mut self) {
         *self = Default::default();
     }
+
+    pub fn apply_preset(&mut self, preset: ControlPreset) {
+        for (definition, button) in self.buttons_mut().iter_mut().zip(preset.buttons().iter()) {
+            definition.button = *button;
+        }
+    }
+
+    /// Moves melee to a free button if its button is taken by another action. Settings saved
+    /// before melee existed get default V for it, which is crouch in ESDF preset and may be
+    /// taken by customized bindings as well. Preset layouts get melee button of that preset,
+    /// customized ones get first free button of a few spare keys.
+    pub fn resolve_melee_conflict(&mut self) {
+        let buttons = self.buttons();
+        let (melee, others) = buttons.split_last().unwrap();
+        let is_free = |button: ControlButton| others.iter().all(|other| other.button != button);
+        if is_free(melee.button) {
+            return;
+        }
+
+        let preset = ControlPreset::ALL.iter().copied().find(|preset| {
+            others
+                .iter()
+                .zip(preset.buttons().iter())
+                .all(|(definition, button)| definition.button == *button)
+        });
+        let button = match preset {
+            Some(preset) => Some(preset.buttons()[others.len()]),
+            None => {
+                use VirtualKeyCode::*;
+                [V, Q, Z, X, B, T, H, N, M]
+                    .iter()
+                    .map(|key| ControlButton::Key(*key))
+                    .find(|button| is_free(*button))
+            }
+        };
+        if let Some(button) = button {
+            self.melee.button = button;
+        }
+    }
+
+    /// Preset that has exactly the same buttons, `None` if buttons were customized.
+    pub fn preset(&self) -> Option<ControlPreset> {
+        ControlPreset::ALL.iter().copied().find(|preset| {
+            self.buttons()
+                .iter()
+                .zip(preset.buttons().iter())
+                .all(|(definition, button)| definition.button == *button)
+        })
+    }
 }
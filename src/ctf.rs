@@ -0,0 +1,322 @@
+//! Flags of capture the flag mode. Flags are meshes of a map named `Flag_Red` and `Flag_Blue`,
+//! place where flag stands at start of a match is its base. Enemy picks flag up by touching it
+//! and captures it by bringing it to its own flag while that flag is at base. Carrier moves
+//! slower, can't use rocket launcher, leaves a trail of team color and is shown to enemies on
+//! HUD. Carrier drops the flag on death, dropped flag returns to base after a while or right
+//! away when somebody of its team touches it.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    character::Team,
+    effects::EffectKind,
+    leader_board::LeaderBoard,
+    message::Message,
+};
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node, Scene},
+};
+use std::sync::mpsc::Sender;
+
+/// Time after which dropped flag returns to its base, in seconds.
+const FLAG_RETURN_TIME: f32 = 30.0;
+/// Distance from flag at which actor touches it.
+const TOUCH_RADIUS: f32 = 1.5;
+/// Time between two puffs of carrier trail.
+const TRAIL_INTERVAL: f32 = 0.1;
+/// Share of bots of a team that defend own flag, the rest attack.
+const DEFENDER_SHARE: f32 = 0.4;
+/// Carried flag is shown above head of carrier.
+const CARRY_OFFSET: Vec3 = Vec3 {
+    x: 0.0,
+    y: 1.2,
+    z: 0.0,
+};
+
+/// Part that bot plays in capture the flag match.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ObjectiveRole {
+    Attack,
+    Defend,
+}
+
+impl Default for ObjectiveRole {
+    fn default() -> Self {
+        ObjectiveRole::Attack
+    }
+}
+
+impl ObjectiveRole {
+    /// Role of bot with given index among bots of a team, first bots defend.
+    pub fn of_member(index: usize, count: usize) -> Self {
+        let defenders = (count as f32 * DEFENDER_SHARE).round() as usize;
+        if index < defenders {
+            ObjectiveRole::Defend
+        } else {
+            ObjectiveRole::Attack
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FlagState {
+    AtBase,
+    Carried(Handle<Actor>),
+    Dropped,
+}
+
+impl Default for FlagState {
+    fn default() -> Self {
+        FlagState::AtBase
+    }
+}
+
+impl Visit for FlagState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id: u32 = match self {
+            FlagState::AtBase => 0,
+            FlagState::Carried(_) => 1,
+            FlagState::Dropped => 2,
+        };
+        id.visit("Id", visitor)?;
+        let mut carrier = match self {
+            FlagState::Carried(carrier) => *carrier,
+            _ => Handle::NONE,
+        };
+        carrier.visit("Carrier", visitor)?;
+        if visitor.is_reading() {
+            *self = match id {
+                0 => FlagState::AtBase,
+                1 => FlagState::Carried(carrier),
+                2 => FlagState::Dropped,
+                _ => return Err(format!("Invalid flag state {}", id).into()),
+            };
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct Flag {
+    team: Team,
+    model: Handle<Node>,
+    base: Vec3,
+    position: Vec3,
+    state: FlagState,
+    return_timer: f32,
+    trail_timer: f32,
+}
+
+impl Flag {
+    /// Makes flag of given team from mesh of a map, current position of the mesh is the base.
+    pub fn new(team: Team, model: Handle<Node>, scene: &Scene) -> Self {
+        let base = scene.graph[model].global_position();
+        Self {
+            team,
+            model,
+            base,
+            position: base,
+            ..Default::default()
+        }
+    }
+
+    pub fn team(&self) -> Team {
+        self.team
+    }
+
+    pub fn state(&self) -> FlagState {
+        self.state
+    }
+
+    fn set_position(&mut self, graph: &mut Graph, position: Vec3) {
+        self.position = position;
+        graph[self.model]
+            .local_transform_mut()
+            .set_position(position);
+    }
+
+    fn return_to_base(&mut self, graph: &mut Graph) {
+        self.state = FlagState::AtBase;
+        let base = self.base;
+        self.set_position(graph, base);
+    }
+
+    fn trail_effect(&self) -> EffectKind {
+        if self.team == Team::Red {
+            EffectKind::RedFlagTrail
+        } else {
+            EffectKind::BlueFlagTrail
+        }
+    }
+}
+
+impl Visit for Flag {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.team.visit("Team", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.base.visit("Base", visitor)?;
+        self.position.visit("Position", visitor)?;
+        self.state.visit("State", visitor)?;
+        self.return_timer.visit("ReturnTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct Flags {
+    flags: Vec<Flag>,
+}
+
+impl Flags {
+    pub fn add(&mut self, flag: Flag) {
+        self.flags.push(flag);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<Flag> {
+        self.flags.iter()
+    }
+
+    /// Returns point that actor of given team and role should go to, `None` if there are no
+    /// flags. Carrier brings enemy flag home and anyone returns own dropped flag. Otherwise
+    /// attackers go for enemy flag and escort their carrier, while defenders guard own flag
+    /// and chase enemy that took it. Carried flag moves with its carrier.
+    pub fn objective_point(
+        &self,
+        actor: Handle<Actor>,
+        team: Team,
+        role: ObjectiveRole,
+    ) -> Option<Vec3> {
+        let own = self.flags.iter().find(|flag| flag.team == team)?;
+        let enemy = self.flags.iter().find(|flag| flag.team != team)?;
+        if enemy.state == FlagState::Carried(actor) {
+            Some(own.base)
+        } else if own.state == FlagState::Dropped {
+            Some(own.position)
+        } else {
+            match role {
+                ObjectiveRole::Attack => Some(enemy.position),
+                ObjectiveRole::Defend => Some(own.position),
+            }
+        }
+    }
+
+    pub fn is_carrier(&self, actor: Handle<Actor>) -> bool {
+        self.flags
+            .iter()
+            .any(|flag| flag.state == FlagState::Carried(actor))
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+        leader_board: &mut LeaderBoard,
+        sender: &Sender<Message>,
+        dt: f32,
+    ) {
+        let notify = |text: String| {
+            sender.send(Message::AddNotification { text }).unwrap();
+        };
+
+        for i in 0..self.flags.len() {
+            match self.flags[i].state {
+                FlagState::Carried(carrier) => {
+                    if !actors.contains(carrier) || actors.get(carrier).is_dead() {
+                        let flag = &mut self.flags[i];
+                        let position = flag.position - CARRY_OFFSET;
+                        flag.set_position(&mut scene.graph, position);
+                        flag.state = FlagState::Dropped;
+                        flag.return_timer = FLAG_RETURN_TIME;
+                        notify(format!("{:?} flag dropped!", flag.team));
+                        continue;
+                    }
+
+                    let carrier = actors.get(carrier);
+                    let carrier_position = carrier.position(&scene.physics);
+                    let flag = &mut self.flags[i];
+                    flag.set_position(&mut scene.graph, carrier_position + CARRY_OFFSET);
+                    flag.trail_timer -= dt;
+                    if flag.trail_timer <= 0.0 {
+                        flag.trail_timer = TRAIL_INTERVAL;
+                        sender
+                            .send(Message::CreateEffect {
+                                kind: flag.trail_effect(),
+                                position: carrier_position,
+                            })
+                            .unwrap();
+                    }
+
+                    // Flag is captured when carrier touches its own flag standing at base.
+                    let captured = self.flags.iter().any(|own| {
+                        own.team == carrier.team()
+                            && own.state == FlagState::AtBase
+                            && own.base.distance(&carrier_position) <= TOUCH_RADIUS
+                    });
+                    if captured {
+                        let flag = &mut self.flags[i];
+                        flag.return_to_base(&mut scene.graph);
+                        leader_board.add_team_score(carrier.team(), 1);
+                        notify(format!(
+                            "{} captured the {:?} flag!",
+                            carrier.name, flag.team
+                        ));
+                    }
+                }
+                FlagState::AtBase | FlagState::Dropped => {
+                    let flag = &mut self.flags[i];
+                    if flag.state == FlagState::Dropped {
+                        flag.return_timer -= dt;
+                        if flag.return_timer <= 0.0 {
+                            flag.return_to_base(&mut scene.graph);
+                            notify(format!("{:?} flag returned!", flag.team));
+                            continue;
+                        }
+                    }
+
+                    let toucher = actors.pair_iter().find(|(_, actor)| {
+                        !actor.is_dead()
+                            && actor.team() != Team::None
+                            && actor.position(&scene.physics).distance(&flag.position)
+                                <= TOUCH_RADIUS
+                    });
+                    if let Some((handle, actor)) = toucher {
+                        if actor.team() == flag.team {
+                            if flag.state == FlagState::Dropped {
+                                flag.return_to_base(&mut scene.graph);
+                                notify(format!(
+                                    "{} returned the {:?} flag!",
+                                    actor.name, flag.team
+                                ));
+                            }
+                        } else if !self.is_carrier(handle) {
+                            let flag = &mut self.flags[i];
+                            flag.state = FlagState::Carried(handle);
+                            flag.trail_timer = 0.0;
+                            notify(format!("{} took the {:?} flag!", actor.name, flag.team));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Visit for Flags {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.flags.visit("Flags", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,129 @@
+//! Mutators that change damage. Every enabled modifier gets a hit before it is dealt and can
+//! change its amount or let attacker drain health, modifiers are applied one after another in
+//! the order of `DamageModifier::ALL`, so double damage also doubles what vampire drains.
+
+use crate::{actor::Actor, settings::MatchSettings};
+use rg3d::core::{
+    pool::Handle,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+/// Part of dealt damage that vampire attacker gets back as health.
+const VAMPIRE_FRACTION: f32 = 0.3;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DamageModifier {
+    DoubleDamage,
+    /// Actor isn't hurt by its own explosions.
+    NoSelfDamage,
+    Vampire,
+}
+
+impl DamageModifier {
+    const ALL: [DamageModifier; 3] = [
+        DamageModifier::DoubleDamage,
+        DamageModifier::NoSelfDamage,
+        DamageModifier::Vampire,
+    ];
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(DamageModifier::DoubleDamage),
+            1 => Ok(DamageModifier::NoSelfDamage),
+            2 => Ok(DamageModifier::Vampire),
+            _ => Err(format!("Unknown damage modifier {}", id)),
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            DamageModifier::DoubleDamage => 0,
+            DamageModifier::NoSelfDamage => 1,
+            DamageModifier::Vampire => 2,
+        }
+    }
+
+    fn is_enabled(self, settings: &MatchSettings) -> bool {
+        match self {
+            DamageModifier::DoubleDamage => settings.double_damage,
+            DamageModifier::NoSelfDamage => settings.no_self_damage,
+            DamageModifier::Vampire => settings.vampire,
+        }
+    }
+
+    fn apply(self, hit: &mut Hit) {
+        match self {
+            DamageModifier::DoubleDamage => hit.amount *= 2.0,
+            DamageModifier::NoSelfDamage => {
+                if hit.who == hit.victim {
+                    hit.amount = 0.0;
+                }
+            }
+            DamageModifier::Vampire => {
+                if hit.who.is_some() && hit.who != hit.victim {
+                    hit.drain = VAMPIRE_FRACTION;
+                }
+            }
+        }
+    }
+}
+
+/// Damage that is about to be dealt.
+pub struct Hit {
+    pub victim: Handle<Actor>,
+    pub who: Handle<Actor>,
+    pub amount: f32,
+    /// Part of damage that was really taken by victim that attacker gets as health.
+    pub drain: f32,
+}
+
+#[derive(Default)]
+pub struct DamageModifiers {
+    modifiers: Vec<DamageModifier>,
+}
+
+impl DamageModifiers {
+    pub fn from_settings(settings: &MatchSettings) -> Self {
+        Self {
+            modifiers: DamageModifier::ALL
+                .iter()
+                .copied()
+                .filter(|modifier| modifier.is_enabled(settings))
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, victim: Handle<Actor>, who: Handle<Actor>, amount: f32) -> Hit {
+        let mut hit = Hit {
+            victim,
+            who,
+            amount,
+            drain: 0.0,
+        };
+        for modifier in self.modifiers.iter() {
+            modifier.apply(&mut hit);
+        }
+        hit
+    }
+}
+
+impl Visit for DamageModifiers {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut ids = self
+            .modifiers
+            .iter()
+            .map(|modifier| modifier.id())
+            .collect::<Vec<u32>>();
+        ids.visit("Modifiers", visitor)?;
+        if visitor.is_reading() {
+            self.modifiers = ids
+                .into_iter()
+                .map(DamageModifier::from_id)
+                .collect::<Result<_, _>>()?;
+        }
+
+        visitor.leave_region()
+    }
+}
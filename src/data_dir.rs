@@ -0,0 +1,138 @@
+//! Platform-specific locations of user files (settings, saves, logs, heatmaps). Game can be
+//! started with `--portable` flag to keep every file in working directory.
+
+use rg3d::utils::log::Log;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+pub const APP_NAME: &str = "rusty-shooter";
+
+pub const PORTABLE_FLAG: &str = "--portable";
+
+/// Engine always writes its log into working directory, the game copies it to logs directory.
+const ENGINE_LOG: &str = "rg3d.log";
+
+pub struct DataDirs {
+    config: PathBuf,
+    saves: PathBuf,
+    logs: PathBuf,
+    heatmaps: PathBuf,
+}
+
+#[cfg(not(target_os = "windows"))]
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+#[cfg(target_os = "windows")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let app_data = env_dir("APPDATA")?.join(APP_NAME);
+    Some((app_data.clone(), app_data))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let support = home_dir()?
+        .join("Library")
+        .join("Application Support")
+        .join(APP_NAME);
+    Some((support.clone(), support))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let config = env_dir("XDG_CONFIG_HOME")
+        .or_else(|| home_dir().map(|home| home.join(".config")))?
+        .join(APP_NAME);
+    let data = env_dir("XDG_DATA_HOME")
+        .or_else(|| home_dir().map(|home| home.join(".local").join("share")))?
+        .join(APP_NAME);
+    Some((config, data))
+}
+
+impl DataDirs {
+    /// Keeps every file in working directory, this is how game worked before.
+    pub fn portable() -> Self {
+        Self {
+            config: PathBuf::new(),
+            saves: PathBuf::new(),
+            logs: PathBuf::new(),
+            heatmaps: PathBuf::new(),
+        }
+    }
+
+    /// Selects directories based on command line arguments, falls back to portable mode
+    /// if platform directories can't be found or created.
+    pub fn from_args() -> Self {
+        if env::args().any(|arg| arg == PORTABLE_FLAG) {
+            return Self::portable();
+        }
+
+        match platform_dirs() {
+            Some((config, data)) => {
+                let dirs = Self {
+                    config,
+                    saves: data.join("saves"),
+                    logs: data.join("logs"),
+                    heatmaps: data.join("heatmaps"),
+                };
+                match dirs.create() {
+                    Ok(_) => dirs,
+                    Err(e) => {
+                        Log::writeln(format!(
+                            "Unable to create data directories ({}), using working directory",
+                            e
+                        ));
+                        Self::portable()
+                    }
+                }
+            }
+            None => Self::portable(),
+        }
+    }
+
+    fn create(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.config)?;
+        std::fs::create_dir_all(&self.saves)?;
+        std::fs::create_dir_all(&self.logs)?;
+        std::fs::create_dir_all(&self.heatmaps)
+    }
+
+    /// Copies log of the engine to logs directory, must be called when nothing is going to be
+    /// logged anymore. Log is copied rather than moved because engine keeps it open.
+    pub fn store_log(&self) {
+        let target = self.logs.join(ENGINE_LOG);
+        if target != Path::new(ENGINE_LOG) {
+            if let Err(e) = std::fs::copy(ENGINE_LOG, &target) {
+                println!("Unable to copy log to {}: {}", target.display(), e);
+            }
+        }
+    }
+
+    pub fn heatmaps_dir(&self) -> &Path {
+        &self.heatmaps
+    }
+
+    pub fn settings_file(&self) -> PathBuf {
+        self.config.join("settings.json")
+    }
+
+    pub fn profile_file(&self) -> PathBuf {
+        self.config.join("profile.json")
+    }
+
+    pub fn save_file(&self, name: &str) -> PathBuf {
+        self.saves.join(name)
+    }
+}
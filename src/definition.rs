@@ -0,0 +1,84 @@
+//! Stats of weapons, projectiles and bots are read from data files in `data/definitions`,
+//! mods can override these files like any other file. File maps kind of weapon, projectile
+//! or bot to the stats it changes, stats missing in the file keep their built-in values:
+//!
+//! ```json
+//! {
+//!     "Ak47": { "ammo": 150, "shoot_interval": 0.12 },
+//!     "RocketLauncher": { "shoot_interval": 1.2 }
+//! }
+//! ```
+//!
+//! Missing or corrupted file means built-in stats for every kind. Asset paths aren't stats,
+//! mods replace assets themselves instead.
+
+use crate::mods;
+use rg3d::utils::log::Log;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, hash::Hash};
+
+pub const WEAPONS_FILE: &str = "data/definitions/weapons.json";
+pub const PROJECTILES_FILE: &str = "data/definitions/projectiles.json";
+pub const BOTS_FILE: &str = "data/definitions/bots.json";
+
+/// Reads stats of every kind from a data file, enabled mods can override the file.
+pub fn load_stats<K, S>(path: &str) -> HashMap<K, S>
+where
+    K: DeserializeOwned + Eq + Hash,
+    S: DeserializeOwned,
+{
+    let path = mods::resolve(path);
+    if !path.exists() {
+        return Default::default();
+    }
+    let stats = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()));
+    match stats {
+        Ok(stats) => stats,
+        Err(e) => {
+            Log::writeln(format!(
+                "Definitions {} are corrupted, built-in stats are used: {}",
+                path.display(),
+                e
+            ));
+            Default::default()
+        }
+    }
+}
+
+/// Definitions of every kind with stats from data file applied. Definitions are built again
+/// when set of enabled mods changes and old ones are leaked: objects hold `&'static`
+/// references to definitions they were created with, and there are only a few definitions.
+pub struct DefinitionCache<D: 'static> {
+    revision: Option<u32>,
+    definitions: Vec<&'static D>,
+}
+
+impl<D: 'static> Default for DefinitionCache<D> {
+    fn default() -> Self {
+        Self {
+            revision: None,
+            definitions: Vec::new(),
+        }
+    }
+}
+
+impl<D: 'static> DefinitionCache<D> {
+    /// Returns definition by id of its kind, `build` must return definitions of every kind
+    /// in order of their ids.
+    pub fn get<F>(&mut self, id: usize, build: F) -> &'static D
+    where
+        F: FnOnce() -> Vec<D>,
+    {
+        let revision = mods::revision();
+        if self.revision != Some(revision) {
+            self.definitions = build()
+                .into_iter()
+                .map(|definition| &*Box::leak(Box::new(definition)))
+                .collect();
+            self.revision = Some(revision);
+        }
+        self.definitions[id]
+    }
+}
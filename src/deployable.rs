@@ -0,0 +1,360 @@
+//! Things that actors place in the world. For now it is only proximity mine - it arms a moment
+//! after it was placed and explodes when an enemy comes close. Armed mine shows a faint laser,
+//! so attentive players (and sometimes bots) can walk around it or shoot it to disarm it. In
+//! team modes mine doesn't react to teammates of its owner and its explosion doesn't hurt them.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    assets,
+    character::Team,
+    effects::EffectKind,
+    level::{self, DamageSource},
+    message::Message,
+    mods, projectile,
+    sound_variation::SoundCategory,
+};
+use rg3d::{
+    core::{
+        color::Color,
+        math::vec3::Vec3,
+        pool::{Handle, Pool},
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{base::BaseBuilder, graph::Graph, node::Node, sprite::SpriteBuilder, Scene},
+};
+use std::{path::PathBuf, sync::mpsc::Sender};
+
+/// Time after placement until mine reacts to enemies, in seconds.
+const ARM_TIME: f32 = 1.5;
+/// Distance from mine at which enemy triggers it, laser shows this distance.
+const TRIGGER_RADIUS: f32 = 2.0;
+const EXPLOSION_RADIUS: f32 = 4.0;
+const EXPLOSION_DAMAGE: f32 = 90.0;
+/// Velocity change at center of explosion in meters per second.
+const EXPLOSION_IMPULSE: f32 = 6.0;
+/// Older mines of an actor are removed when it places more than this amount.
+const MAX_MINES_PER_ACTOR: usize = 3;
+/// How far from center of mine a projectile can pass to hit it.
+const HIT_RADIUS: f32 = 0.3;
+/// Laser is made of small faint sprites, it is cheaper than a light.
+const LASER_DOT_COUNT: usize = 8;
+
+pub struct Mine {
+    owner: Handle<Actor>,
+    team: Team,
+    position: Vec3,
+    model: Handle<Node>,
+    laser: Vec<Handle<Node>>,
+    arm_timer: f32,
+    /// Order of placement, used to find oldest mines of an actor.
+    serial: u32,
+    /// Mine was caught in an explosion and explodes on next update. Not saved.
+    detonated: bool,
+}
+
+impl Default for Mine {
+    fn default() -> Self {
+        Self {
+            owner: Handle::NONE,
+            team: Team::None,
+            position: Vec3::ZERO,
+            model: Handle::NONE,
+            laser: Vec::new(),
+            arm_timer: ARM_TIME,
+            serial: 0,
+            detonated: false,
+        }
+    }
+}
+
+impl Mine {
+    /// Creates mine lying at given position, laser goes along the ground in given direction.
+    pub fn new(
+        owner: Handle<Actor>,
+        team: Team,
+        position: Vec3,
+        direction: Vec3,
+        scene: &mut Scene,
+        resource_manager: ResourceManager,
+    ) -> Self {
+        let texture =
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::CIRCLE));
+        let model = scene.graph.add_node(Node::Sprite(
+            SpriteBuilder::new(BaseBuilder::new())
+                .with_size(0.12)
+                .with_color(Color::opaque(200, 30, 30))
+                .with_texture(texture.clone())
+                .build(),
+        ));
+        scene.graph[model]
+            .local_transform_mut()
+            .set_position(position);
+
+        let direction = Vec3::new(direction.x, 0.0, direction.z)
+            .normalized()
+            .unwrap_or(Vec3::LOOK);
+        let laser = (1..=LASER_DOT_COUNT)
+            .map(|i| {
+                let dot = scene.graph.add_node(Node::Sprite(
+                    SpriteBuilder::new(BaseBuilder::new())
+                        .with_size(0.02)
+                        .with_color(Color::from_rgba(255, 0, 0, 60))
+                        .with_texture(texture.clone())
+                        .build(),
+                ));
+                let offset = direction.scale(TRIGGER_RADIUS * i as f32 / LASER_DOT_COUNT as f32);
+                let node = &mut scene.graph[dot];
+                node.local_transform_mut().set_position(position + offset);
+                node.set_visibility(false);
+                dot
+            })
+            .collect();
+
+        Self {
+            owner,
+            team,
+            position,
+            model,
+            laser,
+            ..Default::default()
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn is_armed(&self) -> bool {
+        self.arm_timer <= 0.0
+    }
+
+    /// Returns true if mine reacts to given actor and can hurt it.
+    fn is_dangerous_for(&self, handle: Handle<Actor>, team: Team) -> bool {
+        handle != self.owner && (self.team == Team::None || team != self.team)
+    }
+
+    fn clean_up(&self, graph: &mut Graph) {
+        graph.remove_node(self.model);
+        for &dot in self.laser.iter() {
+            graph.remove_node(dot);
+        }
+    }
+}
+
+impl Visit for Mine {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.owner.visit("Owner", visitor)?;
+        self.team.visit("Team", visitor)?;
+        self.position.visit("Position", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.laser.visit("Laser", visitor)?;
+        self.arm_timer.visit("ArmTimer", visitor)?;
+        self.serial.visit("Serial", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct Deployables {
+    mines: Pool<Mine>,
+    next_serial: u32,
+}
+
+impl Deployables {
+    /// Places mine, oldest mine of the same owner is removed if it has too many.
+    pub fn place(&mut self, mut mine: Mine, graph: &mut Graph) {
+        let owner = mine.owner;
+        let owned = self
+            .mines
+            .iter()
+            .filter(|other| other.owner == owner)
+            .count();
+        if owned >= MAX_MINES_PER_ACTOR {
+            let oldest = self
+                .mines
+                .pair_iter()
+                .filter(|(_, other)| other.owner == owner)
+                .min_by_key(|(_, other)| other.serial)
+                .map(|(handle, _)| handle);
+            if let Some(oldest) = oldest {
+                self.remove(oldest, graph);
+            }
+        }
+        mine.serial = self.next_serial;
+        self.next_serial += 1;
+        self.mines.spawn(mine);
+    }
+
+    /// Removes mine, returns its position if it still existed.
+    pub fn remove(&mut self, mine: Handle<Mine>, graph: &mut Graph) -> Option<Vec3> {
+        if !self.mines.is_valid_handle(mine) {
+            return None;
+        }
+        let mine = self.mines.free(mine);
+        mine.clean_up(graph);
+        Some(mine.position)
+    }
+
+    /// Returns handle of first mine that lies close to a segment or `Handle::NONE`.
+    pub fn find_on_segment(&self, begin: Vec3, end: Vec3) -> Handle<Mine> {
+        self.mines
+            .pair_iter()
+            .filter(|(_, mine)| {
+                projectile::distance_to_segment(mine.position, begin, end) <= HIT_RADIUS
+            })
+            .min_by(|(_, a), (_, b)| {
+                a.position
+                    .sqr_distance(&begin)
+                    .partial_cmp(&b.position.sqr_distance(&begin))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(handle, _)| handle)
+            .unwrap_or(Handle::NONE)
+    }
+
+    pub fn position(&self, mine: Handle<Mine>) -> Vec3 {
+        self.mines.borrow(mine).position
+    }
+
+    /// Returns every mine with its position.
+    pub fn positions(&self) -> impl Iterator<Item = (Handle<Mine>, Vec3)> + '_ {
+        self.mines
+            .pair_iter()
+            .map(|(handle, mine)| (handle, mine.position))
+    }
+
+    /// Makes mine explode on next update, even if it is not armed yet.
+    pub fn detonate(&mut self, mine: Handle<Mine>) {
+        if self.mines.is_valid_handle(mine) {
+            self.mines.borrow_mut(mine).detonated = true;
+        }
+    }
+
+    /// Returns position of closest mine within given radius that is dangerous for given actor.
+    pub fn closest_danger(
+        &self,
+        actor: Handle<Actor>,
+        team: Team,
+        position: Vec3,
+        radius: f32,
+    ) -> Option<Vec3> {
+        self.mines
+            .iter()
+            .filter(|mine| mine.is_dangerous_for(actor, team))
+            .map(|mine| (mine.position, mine.position.distance(&position)))
+            .filter(|&(_, distance)| distance <= radius)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(mine, _)| mine)
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+        sender: &Sender<Message>,
+        dt: f32,
+    ) {
+        let mut triggered = Vec::new();
+        for (handle, mine) in self.mines.pair_iter_mut() {
+            if mine.detonated {
+                triggered.push(handle);
+                continue;
+            }
+
+            if !mine.is_armed() {
+                mine.arm_timer -= dt;
+                if mine.is_armed() {
+                    for &dot in mine.laser.iter() {
+                        scene.graph[dot].set_visibility(true);
+                    }
+                }
+                continue;
+            }
+
+            let trigger = actors.pair_iter().any(|(actor_handle, actor)| {
+                !actor.is_dead()
+                    && mine.is_dangerous_for(actor_handle, actor.team())
+                    && actor.position(&scene.physics).distance(&mine.position) <= TRIGGER_RADIUS
+            });
+            if trigger {
+                triggered.push(handle);
+            }
+        }
+
+        for handle in triggered {
+            let mine = self.mines.free(handle);
+            mine.clean_up(&mut scene.graph);
+
+            // Owner could be removed already, damage from unknown actor is still dealt.
+            let who = if actors.contains(mine.owner) {
+                mine.owner
+            } else {
+                Handle::NONE
+            };
+            for (actor_handle, actor) in actors.pair_iter() {
+                let distance = actor.position(&scene.physics).distance(&mine.position);
+                if !actor.is_dead()
+                    && mine.is_dangerous_for(actor_handle, actor.team())
+                    && distance < EXPLOSION_RADIUS
+                {
+                    sender
+                        .send(Message::DamageActor {
+                            actor: actor_handle,
+                            who,
+                            amount: EXPLOSION_DAMAGE * (1.0 - distance / EXPLOSION_RADIUS),
+                            source: DamageSource::Mine,
+                            impulse: level::knockback(
+                                mine.position,
+                                actor.position(&scene.physics),
+                                EXPLOSION_IMPULSE * (1.0 - distance / EXPLOSION_RADIUS),
+                            ),
+                        })
+                        .unwrap();
+                }
+            }
+            // Actors are damaged above with respect to teams, explosion only pushes props.
+            sender
+                .send(Message::Explosion {
+                    position: mine.position,
+                    radius: EXPLOSION_RADIUS,
+                    damage: 0.0,
+                    impulse: EXPLOSION_IMPULSE,
+                    knockback: 0.0,
+                    who,
+                    source: DamageSource::Mine,
+                })
+                .unwrap();
+            sender
+                .send(Message::CreateEffect {
+                    kind: EffectKind::Smoke,
+                    position: mine.position,
+                })
+                .unwrap();
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(assets::sounds::impact::ROCKET),
+                    position: mine.position,
+                    gain: 1.0,
+                    rolloff_factor: 4.0,
+                    radius: 5.0,
+                    category: SoundCategory::Impact,
+                })
+                .unwrap();
+        }
+    }
+}
+
+impl Visit for Deployables {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.mines.visit("Mines", visitor)?;
+        self.next_serial.visit("NextSerial", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,110 @@
+//! Rounds of duel mode. Duel is a match of player against a single bot played in rounds -
+//! round ends when one of them dies and survivor wins it. After a short break both are
+//! respawned with fresh health, armor and starting weapons, and items of the map are reset.
+//! Respawn of major items is called out, so timing them is part of the game.
+
+use crate::item::{Item, ItemContainer, ItemKind};
+use rg3d::core::visitor::{Visit, VisitResult, Visitor};
+
+/// Time in seconds between end of a round and start of next one.
+const INTERMISSION: f32 = 3.0;
+/// Respawn of major item is called out this many seconds ahead.
+const ITEM_WARNING: f32 = 10.0;
+
+pub struct Rounds {
+    /// Number of current round, starting from one.
+    round: u32,
+    /// Round is over and next one has not started yet.
+    intermission: bool,
+    intermission_timer: f32,
+}
+
+impl Default for Rounds {
+    fn default() -> Self {
+        Self {
+            round: 1,
+            intermission: false,
+            intermission_timer: 0.0,
+        }
+    }
+}
+
+impl Rounds {
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn is_intermission(&self) -> bool {
+        self.intermission
+    }
+
+    pub fn end_round(&mut self) {
+        self.intermission = true;
+        self.intermission_timer = INTERMISSION;
+    }
+
+    pub fn start_next_round(&mut self) {
+        self.round += 1;
+        self.intermission = false;
+    }
+
+    /// Returns true once, when intermission is over and next round must be started.
+    pub fn update(&mut self, dt: f32) -> bool {
+        if self.intermission && self.intermission_timer > 0.0 {
+            self.intermission_timer -= dt;
+            self.intermission_timer <= 0.0
+        } else {
+            false
+        }
+    }
+}
+
+impl Visit for Rounds {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.round.visit("Round", visitor)?;
+        self.intermission.visit("Intermission", visitor)?;
+        self.intermission_timer
+            .visit("IntermissionTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Items that matter enough to time their respawn.
+fn is_major(item: &Item) -> bool {
+    let kind = item.get_kind();
+    !item.is_temporary()
+        && (kind == ItemKind::Medkit || kind == ItemKind::Overshield || kind.is_weapon())
+}
+
+/// Callout of a major item that was just picked up.
+pub fn pickup_callout(item: &Item) -> Option<String> {
+    if is_major(item) {
+        Some(format!(
+            "{} taken, respawns in {:.0} s",
+            item.get_kind().name(),
+            item.respawn_interval()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Callouts of major items that will respawn soon or respawn right now, must be called
+/// before items are updated.
+pub fn item_callouts(items: &ItemContainer, dt: f32) -> Vec<String> {
+    let mut callouts = Vec::new();
+    for item in items.iter().filter(|item| is_major(item)) {
+        if let Some(time_left) = item.respawn_time_left() {
+            let name = item.get_kind().name();
+            if time_left > ITEM_WARNING && time_left - dt <= ITEM_WARNING {
+                callouts.push(format!("{} respawns in {:.0} s", name, ITEM_WARNING));
+            } else if time_left > 0.0 && time_left - dt <= 0.0 {
+                callouts.push(format!("{} is up!", name));
+            }
+        }
+    }
+    callouts
+}
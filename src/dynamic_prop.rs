@@ -0,0 +1,255 @@
+//! Dynamic props - crates and barrels that are moved by physics. Props are ordinary meshes of
+//! a map named `Physics_Crate`, `Physics_Barrel` or `Physics_ExplosiveBarrel`, level gives each
+//! of them a rigid body sized by bounds of the mesh. Props are pushed by projectiles and by
+//! explosions, explosive barrels blow up when they take enough damage and their explosion can
+//! set off other barrels nearby.
+
+use crate::actor::Actor;
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::{Handle, Pool, PoolPairIterator},
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    physics::{
+        convex_shape::{Axis, BoxShape, CapsuleShape, ConvexShape},
+        rigid_body::RigidBody,
+        Physics,
+    },
+    scene::{node::Node, Scene},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DynamicPropKind {
+    Crate,
+    Barrel,
+    ExplosiveBarrel,
+}
+
+impl DynamicPropKind {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(DynamicPropKind::Crate),
+            1 => Ok(DynamicPropKind::Barrel),
+            2 => Ok(DynamicPropKind::ExplosiveBarrel),
+            _ => Err(format!("Unknown dynamic prop kind {}", id)),
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            DynamicPropKind::Crate => 0,
+            DynamicPropKind::Barrel => 1,
+            DynamicPropKind::ExplosiveBarrel => 2,
+        }
+    }
+}
+
+pub struct DynamicPropDefinition {
+    /// Props with zero health can't be destroyed.
+    pub health: f32,
+    /// Multiplier of impulses applied to prop, lighter props fly further.
+    pub impulse_factor: f32,
+    pub explosion_radius: f32,
+    pub explosion_damage: f32,
+    /// Velocity change at center of explosion in meters per second, it fades out to zero at
+    /// explosion radius.
+    pub explosion_impulse: f32,
+}
+
+pub struct DynamicProp {
+    kind: DynamicPropKind,
+    model: Handle<Node>,
+    body: Handle<RigidBody>,
+    health: f32,
+    /// Actor who did the last damage to prop, it is blamed for explosion of the prop.
+    last_attacker: Handle<Actor>,
+}
+
+impl Default for DynamicProp {
+    fn default() -> Self {
+        Self {
+            kind: DynamicPropKind::Crate,
+            model: Handle::NONE,
+            body: Handle::NONE,
+            health: 0.0,
+            last_attacker: Handle::NONE,
+        }
+    }
+}
+
+impl DynamicProp {
+    pub fn get_definition(kind: DynamicPropKind) -> &'static DynamicPropDefinition {
+        match kind {
+            DynamicPropKind::Crate => {
+                static DEFINITION: DynamicPropDefinition = DynamicPropDefinition {
+                    health: 0.0,
+                    impulse_factor: 1.0,
+                    explosion_radius: 0.0,
+                    explosion_damage: 0.0,
+                    explosion_impulse: 0.0,
+                };
+                &DEFINITION
+            }
+            DynamicPropKind::Barrel => {
+                static DEFINITION: DynamicPropDefinition = DynamicPropDefinition {
+                    health: 0.0,
+                    impulse_factor: 0.6,
+                    explosion_radius: 0.0,
+                    explosion_damage: 0.0,
+                    explosion_impulse: 0.0,
+                };
+                &DEFINITION
+            }
+            DynamicPropKind::ExplosiveBarrel => {
+                static DEFINITION: DynamicPropDefinition = DynamicPropDefinition {
+                    health: 40.0,
+                    impulse_factor: 0.6,
+                    explosion_radius: 5.0,
+                    explosion_damage: 60.0,
+                    explosion_impulse: 24.0,
+                };
+                &DEFINITION
+            }
+        }
+    }
+
+    /// Creates rigid body for given mesh of a map and binds the mesh to it.
+    pub fn new(kind: DynamicPropKind, model: Handle<Node>, scene: &mut Scene) -> Self {
+        let node = &scene.graph[model];
+        let bounds = node.as_mesh().world_bounding_box();
+        let half_extents = (bounds.max - bounds.min).scale(0.5);
+
+        let shape = match kind {
+            DynamicPropKind::Crate => ConvexShape::Box(BoxShape::new(half_extents)),
+            DynamicPropKind::Barrel | DynamicPropKind::ExplosiveBarrel => {
+                let radius = half_extents.x.max(half_extents.z);
+                let height = (half_extents.y - radius).max(0.0) * 2.0;
+                ConvexShape::Capsule(CapsuleShape::new(radius, height, Axis::Y))
+            }
+        };
+        let mut body = RigidBody::new(shape);
+        body.set_position(node.global_position());
+        body.set_friction(Vec3::new(0.6, 0.0, 0.6));
+        let body = scene.physics.add_body(body);
+        scene.physics_binder.bind(model, body);
+
+        Self {
+            kind,
+            model,
+            body,
+            health: Self::get_definition(kind).health,
+            last_attacker: Handle::NONE,
+        }
+    }
+
+    pub fn definition(&self) -> &'static DynamicPropDefinition {
+        Self::get_definition(self.kind)
+    }
+
+    pub fn last_attacker(&self) -> Handle<Actor> {
+        self.last_attacker
+    }
+
+    pub fn position(&self, physics: &Physics) -> Vec3 {
+        physics.borrow_body(self.body).get_position()
+    }
+
+    /// Changes velocity of prop by given change per physics step.
+    pub fn push(&self, physics: &mut Physics, impulse: Vec3) {
+        let body = physics.borrow_body_mut(self.body);
+        let velocity = body.get_velocity();
+        body.set_velocity(velocity + impulse.scale(self.definition().impulse_factor));
+    }
+
+    /// Damages prop, returns true if prop was destroyed by this damage.
+    pub fn damage(&mut self, amount: f32, who: Handle<Actor>) -> bool {
+        if self.definition().health <= 0.0 || self.health <= 0.0 {
+            return false;
+        }
+        if who.is_some() {
+            self.last_attacker = who;
+        }
+        self.health -= amount;
+        self.health <= 0.0
+    }
+
+    pub fn clean_up(&mut self, scene: &mut Scene) {
+        scene.remove_node(self.model);
+        scene.physics.remove_body(self.body);
+    }
+}
+
+impl Visit for DynamicProp {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind_id = self.kind.id();
+        kind_id.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = DynamicPropKind::from_id(kind_id)?;
+        }
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.health.visit("Health", visitor)?;
+        self.last_attacker.visit("LastAttacker", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+pub struct DynamicPropContainer {
+    pool: Pool<DynamicProp>,
+}
+
+impl Default for DynamicPropContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicPropContainer {
+    pub fn new() -> Self {
+        Self { pool: Pool::new() }
+    }
+
+    pub fn add(&mut self, prop: DynamicProp) -> Handle<DynamicProp> {
+        self.pool.spawn(prop)
+    }
+
+    pub fn contains(&self, prop: Handle<DynamicProp>) -> bool {
+        self.pool.is_valid_handle(prop)
+    }
+
+    pub fn get_mut(&mut self, prop: Handle<DynamicProp>) -> &mut DynamicProp {
+        self.pool.borrow_mut(prop)
+    }
+
+    pub fn pair_iter(&self) -> PoolPairIterator<DynamicProp> {
+        self.pool.pair_iter()
+    }
+
+    /// Returns handle of prop that owns given rigid body or `Handle::NONE`.
+    pub fn find_by_body(&self, body: Handle<RigidBody>) -> Handle<DynamicProp> {
+        self.pool
+            .pair_iter()
+            .find(|(_, prop)| prop.body == body)
+            .map(|(handle, _)| handle)
+            .unwrap_or(Handle::NONE)
+    }
+
+    pub fn remove(&mut self, prop: Handle<DynamicProp>, scene: &mut Scene) {
+        self.pool.borrow_mut(prop).clean_up(scene);
+        self.pool.free(prop);
+    }
+}
+
+impl Visit for DynamicPropContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.pool.visit("Pool", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,106 @@
+//! Dynamic shadow distance scaling - monitors frame time and lowers render cost when the game
+//! can't hold target frame rate. The scale is applied to the most expensive distance-based
+//! settings - distances of point and spot shadows. Frame size isn't scaled, renderer of the
+//! engine can't render a scene at lower resolution and upscale it.
+
+use rg3d::renderer::{QualitySettings, Renderer};
+
+const MIN_SCALE: f32 = 0.7;
+const SCALE_STEP: f32 = 0.05;
+/// Amount of frames between adjustments, renderer needs some time to settle after a change.
+const ADJUST_INTERVAL: u32 = 60;
+/// Scale goes up only when frame time is well below target, otherwise it would oscillate.
+const HEADROOM: f32 = 0.8;
+
+pub struct DynamicQuality {
+    scale: f32,
+    /// Settings selected by user, scale is applied to them.
+    base: QualitySettings,
+    /// Settings that were applied by scaler, used to detect changes made in options menu.
+    applied: QualitySettings,
+    frame_time_sum: f32,
+    frame_count: u32,
+}
+
+impl DynamicQuality {
+    pub fn new(renderer: &Renderer) -> Self {
+        let settings = renderer.get_quality_settings();
+        Self {
+            scale: 1.0,
+            base: settings,
+            applied: settings,
+            frame_time_sum: 0.0,
+            frame_count: 0,
+        }
+    }
+
+    /// Current scale of shadow distances, 1.0 means that settings of user are used as is.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Settings selected by user without scale, these must be saved instead of settings
+    /// of renderer.
+    pub fn base_settings(&self, renderer: &Renderer) -> QualitySettings {
+        let current = renderer.get_quality_settings();
+        if current != self.applied {
+            current
+        } else {
+            self.base
+        }
+    }
+
+    fn apply(&mut self, renderer: &mut Renderer) {
+        let mut settings = self.base;
+        settings.point_shadows_distance *= self.scale;
+        settings.spot_shadows_distance *= self.scale;
+        if let Err(e) = renderer.set_quality_settings(&settings) {
+            println!("Failed to set renderer quality settings! Reason: {:?}", e);
+        }
+        self.applied = settings;
+    }
+
+    /// Must be called once per rendered frame with time that frame took.
+    pub fn update(
+        &mut self,
+        renderer: &mut Renderer,
+        enabled: bool,
+        target_fps: f32,
+        frame_time: f32,
+    ) {
+        // Settings were changed in options menu, they become new base.
+        self.base = self.base_settings(renderer);
+
+        if !enabled {
+            if self.scale < 1.0 {
+                self.scale = 1.0;
+                self.apply(renderer);
+            }
+            return;
+        }
+
+        self.frame_time_sum += frame_time;
+        self.frame_count += 1;
+        if self.frame_count < ADJUST_INTERVAL {
+            return;
+        }
+
+        let average_frame_time = self.frame_time_sum / self.frame_count as f32;
+        let target_frame_time = 1.0 / target_fps;
+        self.frame_time_sum = 0.0;
+        self.frame_count = 0;
+
+        let new_scale = if average_frame_time > target_frame_time {
+            (self.scale - SCALE_STEP).max(MIN_SCALE)
+        } else if average_frame_time < target_frame_time * HEADROOM {
+            (self.scale + SCALE_STEP).min(1.0)
+        } else {
+            self.scale
+        };
+
+        if (new_scale - self.scale).abs() > std::f32::EPSILON {
+            self.scale = new_scale;
+            self.apply(renderer);
+        }
+    }
+}
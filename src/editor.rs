@@ -0,0 +1,274 @@
+//! Debug editor for populating maps. It flies the free camera (see `free_camera` module) over
+//! the running match, which is frozen meanwhile, and places entities from a palette at the point under
+//! crosshair: mouse wheel selects an entity, left mouse button places it and Enter exports
+//! placed entities to placement file of the map (see `placement` module), so the map has
+//! them on every start.
+
+use crate::{
+    bot::BotKind,
+    control_scheme::ControlScheme,
+    free_camera::FreeCamera,
+    item::ItemKind,
+    placement::{EntityKind, Placement, PlacementFile},
+    GameEngine, Gui, UINodeHandle,
+};
+use rg3d::{
+    core::{color::Color, math::ray::Ray, math::vec3::Vec3, pool::Handle},
+    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, TextMessage, WidgetMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        HorizontalAlignment, Thickness, VerticalAlignment,
+    },
+    physics::{HitKind, RayCastOptions},
+    scene::{self, Scene},
+};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+/// Entities can be placed at most this far from camera.
+const PLACE_DISTANCE: f32 = 100.0;
+/// Height of marker that shows placed entity.
+const MARKER_HEIGHT: f32 = 2.0;
+
+fn palette() -> Vec<EntityKind> {
+    let mut palette = Vec::new();
+    let mut id = 0;
+    while let Ok(kind) = ItemKind::from_id(id) {
+        palette.push(EntityKind::Item(kind));
+        id += 1;
+    }
+    let mut id = 0;
+    while let Ok(kind) = BotKind::from_id(id) {
+        palette.push(EntityKind::Bot(kind));
+        id += 1;
+    }
+    palette.push(EntityKind::JumpPad);
+    palette.push(EntityKind::SpawnPoint);
+    palette
+}
+
+pub struct DebugEditor {
+    scene: Handle<Scene>,
+    camera: FreeCamera,
+    palette: Vec<EntityKind>,
+    selected: usize,
+    /// Entities placed since editor was opened, they're added to placement file on export.
+    placed: Vec<Placement>,
+    /// Amount of placed entities that are in placement file already.
+    exported: usize,
+    /// Placement file of the map.
+    file: PathBuf,
+    /// Result of last export.
+    status: String,
+    root: UINodeHandle,
+    text: UINodeHandle,
+}
+
+impl DebugEditor {
+    pub fn enter(
+        engine: &mut GameEngine,
+        scene: Handle<Scene>,
+        control_scheme: Rc<RefCell<ControlScheme>>,
+        file: PathBuf,
+    ) -> Self {
+        let camera = FreeCamera::enter(engine, scene, control_scheme);
+
+        let frame_size = engine.renderer.get_frame_size();
+        let ctx = &mut engine.user_interface.build_ctx();
+        let text;
+        let root = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_child({
+                    text = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(10.0))
+                            .with_horizontal_alignment(HorizontalAlignment::Left)
+                            .with_vertical_alignment(VerticalAlignment::Top),
+                    )
+                    .build(ctx);
+                    text
+                })
+                .with_child(
+                    TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Center),
+                    )
+                    .with_text("+")
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let mut editor = Self {
+            scene,
+            camera,
+            palette: palette(),
+            selected: 0,
+            placed: Default::default(),
+            exported: 0,
+            file,
+            status: Default::default(),
+            root,
+            text,
+        };
+        editor.sync_text(&mut engine.user_interface);
+        editor
+    }
+
+    /// Removes free camera and UI of editor. Entities that weren't exported stay in the
+    /// match, but they're gone on next start of the map.
+    pub fn leave(self, engine: &mut GameEngine) {
+        engine.scenes[self.scene].drawing_context.clear_lines();
+        self.camera.leave(engine);
+        engine
+            .user_interface
+            .send_message(WidgetMessage::remove(self.root, MessageDirection::ToWidget));
+    }
+
+    fn sync_text(&self, ui: &mut Gui) {
+        let mut text = format!(
+            "Editor: [Wheel] select, [LMB] place, [Enter] export, [Esc] leave\n\
+             Placed: {}, not exported: {}\n\
+             {}\n",
+            self.placed.len(),
+            self.placed.len() - self.exported,
+            self.status
+        );
+        for (i, kind) in self.palette.iter().enumerate() {
+            let marker = if i == self.selected { ">" } else { " " };
+            text += &format!("{} {}\n", marker, kind.name());
+        }
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    /// Point of level geometry under crosshair.
+    fn point_under_crosshair(&self, engine: &GameEngine) -> Option<Vec3> {
+        let scene = &engine.scenes[self.scene];
+        let camera = &scene.graph[self.camera.camera()];
+        let begin = camera.global_position();
+        let end = begin + camera.look_vector().scale(PLACE_DISTANCE);
+        let ray = Ray::from_two_points(&begin, &end)?;
+        let options = RayCastOptions {
+            ignore_bodies: true,
+            ignore_static_geometries: false,
+            sort_results: true,
+        };
+        let mut results = Vec::new();
+        scene.physics.ray_cast(&ray, options, &mut results);
+        results
+            .iter()
+            .find(|hit| matches!(hit.kind, HitKind::StaticTriangle { .. }))
+            .map(|hit| hit.position)
+    }
+
+    /// Adds entities placed after last export to placement file of the map.
+    fn export(&mut self) -> Result<usize, String> {
+        let mut file = PlacementFile::load(&self.file)?;
+        let new = &self.placed[self.exported..];
+        file.entities.extend_from_slice(new);
+        file.save(&self.file)?;
+        let count = new.len();
+        self.exported = self.placed.len();
+        Ok(count)
+    }
+
+    /// Returns entity that must be added to the match when player clicks somewhere.
+    pub fn process_input_event(
+        &mut self,
+        engine: &mut GameEngine,
+        event: &Event<()>,
+    ) -> Option<Placement> {
+        match event {
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseWheel { delta },
+                ..
+            } => {
+                if let MouseScrollDelta::LineDelta(_, y) = delta {
+                    let count = self.palette.len();
+                    self.selected = if *y > 0.0 {
+                        (self.selected + count - 1) % count
+                    } else {
+                        (self.selected + 1) % count
+                    };
+                    self.sync_text(&mut engine.user_interface);
+                }
+                None
+            }
+            Event::DeviceEvent {
+                event:
+                    DeviceEvent::Button {
+                        button: 1,
+                        state: ElementState::Pressed,
+                    },
+                ..
+            } => {
+                let position = self.point_under_crosshair(engine)?;
+                let placement = Placement::new(self.palette[self.selected], position);
+                self.placed.push(placement.clone());
+                self.sync_text(&mut engine.user_interface);
+                Some(placement)
+            }
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Pressed
+                && input.virtual_keycode == Some(VirtualKeyCode::Return) =>
+            {
+                self.status = match self.export() {
+                    Ok(count) => format!("Exported {} to {}", count, self.file.display()),
+                    Err(e) => e,
+                };
+                self.sync_text(&mut engine.user_interface);
+                None
+            }
+            _ => {
+                self.camera.process_input_event(event);
+                None
+            }
+        }
+    }
+
+    /// Moves camera and marks placed entities, must be called every update while editor is
+    /// active.
+    pub fn update(&mut self, engine: &mut GameEngine, dt: f32) {
+        self.camera.update(engine, dt);
+
+        let drawing_context = &mut engine.scenes[self.scene].drawing_context;
+        drawing_context.clear_lines();
+        for placement in self.placed.iter() {
+            let position = placement.position();
+            let color = match placement.kind {
+                EntityKind::Item(_) => Color::opaque(0, 200, 0),
+                EntityKind::Bot(_) => Color::opaque(200, 0, 0),
+                EntityKind::JumpPad => Color::opaque(200, 200, 0),
+                EntityKind::SpawnPoint => Color::opaque(0, 100, 200),
+            };
+            drawing_context.add_line(scene::Line {
+                begin: position,
+                end: position + Vec3::UP.scale(MARKER_HEIGHT),
+                color,
+            });
+            drawing_context.add_line(scene::Line {
+                begin: position - Vec3::RIGHT.scale(0.25),
+                end: position + Vec3::RIGHT.scale(0.25),
+                color,
+            });
+            drawing_context.add_line(scene::Line {
+                begin: position - Vec3::LOOK.scale(0.25),
+                end: position + Vec3::LOOK.scale(0.25),
+                color,
+            });
+        }
+    }
+}
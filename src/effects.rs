@@ -1,4 +1,4 @@
-use crate::assets;
+use crate::{assets, mods};
 use rand::Rng;
 use rg3d::scene::particle_system::{
     BaseEmitter, BaseEmitterBuilder, Emitter, SphereEmitterBuilder,
@@ -7,14 +7,16 @@ use rg3d::{
     core::{
         color::Color,
         color_gradient::{ColorGradient, GradientPoint},
-        math::vec3::Vec3,
+        math::{aabb::AxisAlignedBoundingBox, vec3::Vec3},
         numeric_range::NumericRange,
+        pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     scene::{
         base::BaseBuilder,
         graph::Graph,
+        light::{BaseLightBuilder, PointLightBuilder},
         node::Node,
         particle_system::{
             CustomEmitter, CustomEmitterFactory, Emit, Particle, ParticleSystem,
@@ -22,26 +24,301 @@ use rg3d::{
         },
         transform::TransformBuilder,
     },
+    sound::{
+        context::Context,
+        source::{
+            generic::GenericSourceBuilder, spatial::SpatialSourceBuilder, SoundSource, Status,
+        },
+    },
+    utils::log::Log,
 };
 use std::{
     ops::{Deref, DerefMut},
-    path::Path,
+    sync::{Arc, Mutex},
 };
 
+/// Color of muzzle flash at full brightness.
+const MUZZLE_FLASH_COLOR: (u8, u8, u8) = (255, 200, 120);
+/// With reduced flashes brightness of dynamic lights can only change smoothly, share of full
+/// brightness per second.
+const FLASH_FADE_IN_SPEED: f32 = 4.0;
+const FLASH_FADE_OUT_SPEED: f32 = 2.0;
+
+/// Brightness of a dynamic light that may flash, like muzzle flash or light of a projectile.
+/// Photosensitive players can reduce flashes in options, then brightness follows its target
+/// gradually and rapid flashes become a dim steady glow.
+#[derive(Default)]
+pub struct LightFader {
+    brightness: f32,
+}
+
+impl LightFader {
+    /// Moves brightness toward target and applies it to the light. The light is hidden when
+    /// it is dark. Without reduced flashes the target is reached right away.
+    pub fn update(
+        &mut self,
+        graph: &mut Graph,
+        light: Handle<Node>,
+        color: (u8, u8, u8),
+        target: f32,
+        reduce_flashes: bool,
+        dt: f32,
+    ) {
+        self.brightness = if !reduce_flashes {
+            target
+        } else if target > self.brightness {
+            (self.brightness + FLASH_FADE_IN_SPEED * dt).min(target)
+        } else {
+            (self.brightness - FLASH_FADE_OUT_SPEED * dt).max(target)
+        };
+        let node = &mut graph[light];
+        node.set_visibility(self.brightness > 0.0);
+        if let Node::Light(light) = node {
+            let (r, g, b) = color;
+            let scale = |channel: u8| (channel as f32 * self.brightness) as u8;
+            light.set_color(Color::opaque(scale(r), scale(g), scale(b)));
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum EffectKind {
     BulletImpact,
     ItemAppear,
     Smoke,
     Steam,
+    MuzzleFlash,
+    /// Bullet impact inside of rain volume.
+    WetBulletImpact,
+    /// Trails of flag carriers, see `ctf` module.
+    RedFlagTrail,
+    BlueFlagTrail,
+    /// Sparkles around character with overshield.
+    ShieldShimmer,
+}
+
+impl EffectKind {
+    pub fn id(self) -> u32 {
+        match self {
+            EffectKind::BulletImpact => 0,
+            EffectKind::ItemAppear => 1,
+            EffectKind::Smoke => 2,
+            EffectKind::Steam => 3,
+            EffectKind::MuzzleFlash => 4,
+            EffectKind::WetBulletImpact => 5,
+            EffectKind::RedFlagTrail => 6,
+            EffectKind::BlueFlagTrail => 7,
+            EffectKind::ShieldShimmer => 8,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(EffectKind::BulletImpact),
+            1 => Ok(EffectKind::ItemAppear),
+            2 => Ok(EffectKind::Smoke),
+            3 => Ok(EffectKind::Steam),
+            4 => Ok(EffectKind::MuzzleFlash),
+            5 => Ok(EffectKind::WetBulletImpact),
+            6 => Ok(EffectKind::RedFlagTrail),
+            7 => Ok(EffectKind::BlueFlagTrail),
+            8 => Ok(EffectKind::ShieldShimmer),
+            _ => Err(format!("Invalid effect kind {}", id)),
+        }
+    }
+
+    /// Returns time in seconds after which effect is over, or `None` for endless effects.
+    fn lifetime(self) -> Option<f32> {
+        match self {
+            EffectKind::BulletImpact => Some(1.0),
+            EffectKind::ItemAppear => Some(1.4),
+            EffectKind::Smoke => Some(5.0),
+            EffectKind::Steam => None,
+            EffectKind::MuzzleFlash => Some(0.05),
+            EffectKind::WetBulletImpact => Some(1.0),
+            EffectKind::RedFlagTrail | EffectKind::BlueFlagTrail => Some(1.0),
+            EffectKind::ShieldShimmer => Some(0.6),
+        }
+    }
 }
 
-pub fn create(kind: EffectKind, graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) {
+fn create(
+    kind: EffectKind,
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vec3,
+) -> Handle<Node> {
     match kind {
-        EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos),
+        EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos, false),
+        EffectKind::WetBulletImpact => create_bullet_impact(graph, resource_manager, pos, true),
         EffectKind::ItemAppear => create_item_appear(graph, resource_manager, pos),
         EffectKind::Smoke => create_smoke(graph, resource_manager, pos),
         EffectKind::Steam => create_steam(graph, resource_manager, pos),
+        EffectKind::MuzzleFlash => create_muzzle_flash(graph, pos),
+        EffectKind::RedFlagTrail => create_flag_trail(graph, resource_manager, pos, (255, 40, 40)),
+        EffectKind::BlueFlagTrail => create_flag_trail(graph, resource_manager, pos, (40, 90, 255)),
+        EffectKind::ShieldShimmer => create_shield_shimmer(graph, resource_manager, pos),
+    }
+}
+
+struct PooledEffect {
+    kind: EffectKind,
+    node: Handle<Node>,
+    /// Effect is hidden and can be reused when there is no time left.
+    time_left: f32,
+    /// Brightness of light of muzzle flash. Not saved, at worst light fades in after load.
+    fader: LightFader,
+}
+
+impl Default for PooledEffect {
+    fn default() -> Self {
+        Self {
+            kind: EffectKind::BulletImpact,
+            node: Handle::NONE,
+            time_left: 0.0,
+            fader: Default::default(),
+        }
+    }
+}
+
+impl Visit for PooledEffect {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind = self.kind.id();
+        kind.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = EffectKind::from_id(kind)?;
+        }
+        self.node.visit("Node", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Short-living effects (impacts, item appearance) are created very often, so instead of
+/// creating and destroying particle systems every time they are hidden when effect is over
+/// and restarted at new position later.
+#[derive(Default)]
+pub struct EffectPool {
+    effects: Vec<PooledEffect>,
+}
+
+impl EffectPool {
+    /// Creates hidden instances of an effect in advance.
+    pub fn prewarm(
+        &mut self,
+        kind: EffectKind,
+        count: usize,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+    ) {
+        for _ in 0..count {
+            let node = create(kind, graph, resource_manager.clone(), Vec3::ZERO);
+            graph[node].set_visibility(false);
+            self.effects.push(PooledEffect {
+                kind,
+                node,
+                time_left: 0.0,
+                fader: Default::default(),
+            });
+        }
+    }
+
+    /// Starts effect at given position. When there are `max_active` effects already, oldest
+    /// effect of same kind is restarted instead, or nothing is spawned if there is no such.
+    pub fn spawn(
+        &mut self,
+        kind: EffectKind,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        pos: Vec3,
+        max_active: usize,
+    ) {
+        let lifetime = match kind.lifetime() {
+            Some(lifetime) => lifetime,
+            None => {
+                // Endless effects are never reused.
+                create(kind, graph, resource_manager, pos);
+                return;
+            }
+        };
+
+        let active = self
+            .effects
+            .iter()
+            .filter(|effect| effect.time_left > 0.0)
+            .count();
+        let limit_reached = active >= max_active;
+        let reusable = if limit_reached {
+            self.effects
+                .iter_mut()
+                .filter(|effect| effect.kind == kind && effect.time_left > 0.0)
+                .min_by(|a, b| {
+                    a.time_left
+                        .partial_cmp(&b.time_left)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+        } else {
+            self.effects
+                .iter_mut()
+                .find(|effect| effect.kind == kind && effect.time_left <= 0.0)
+        };
+        match reusable {
+            Some(effect) => {
+                effect.time_left = lifetime;
+                let node = &mut graph[effect.node];
+                node.set_visibility(true);
+                node.local_transform_mut().set_position(pos);
+                if let Node::ParticleSystem(particle_system) = node {
+                    particle_system.clear_particles();
+                }
+            }
+            None if limit_reached => (),
+            None => {
+                let node = create(kind, graph, resource_manager, pos);
+                self.effects.push(PooledEffect {
+                    kind,
+                    node,
+                    time_left: lifetime,
+                    fader: Default::default(),
+                });
+            }
+        }
+    }
+
+    pub fn update(&mut self, graph: &mut Graph, dt: f32, reduce_flashes: bool) {
+        for effect in self.effects.iter_mut() {
+            if effect.time_left > 0.0 {
+                effect.time_left -= dt;
+                if effect.time_left <= 0.0 {
+                    graph[effect.node].set_visibility(false);
+                }
+            }
+            // Muzzle flash is a light, its visibility is driven by brightness.
+            if effect.kind == EffectKind::MuzzleFlash {
+                let target = if effect.time_left > 0.0 { 1.0 } else { 0.0 };
+                effect.fader.update(
+                    graph,
+                    effect.node,
+                    MUZZLE_FLASH_COLOR,
+                    target,
+                    reduce_flashes,
+                    dt,
+                );
+            }
+        }
+    }
+}
+
+impl Visit for EffectPool {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.effects.visit("Effects", visitor)?;
+
+        visitor.leave_region()
     }
 }
 
@@ -110,16 +387,88 @@ impl Emit for CylinderEmitter {
     }
 }
 
+/// Emits particles in a box, particles of falling weather (rain, snow) are emitted on top
+/// face of the box only.
+#[derive(Clone, Debug)]
+pub struct VolumeEmitter {
+    base: BaseEmitter,
+    size: Vec3,
+    top_only: bool,
+}
+
+impl VolumeEmitter {
+    pub fn new() -> Self {
+        Self {
+            base: Default::default(),
+            size: Vec3::new(1.0, 1.0, 1.0),
+            top_only: false,
+        }
+    }
+}
+
+impl CustomEmitter for VolumeEmitter {
+    fn box_clone(&self) -> Box<dyn CustomEmitter> {
+        Box::new(self.clone())
+    }
+
+    fn get_kind(&self) -> i32 {
+        1
+    }
+}
+
+impl Deref for VolumeEmitter {
+    type Target = BaseEmitter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for VolumeEmitter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Visit for VolumeEmitter {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.size.visit("Size", visitor)?;
+        self.top_only.visit("TopOnly", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Emit for VolumeEmitter {
+    fn emit(&self, _particle_system: &ParticleSystem, particle: &mut Particle) {
+        let mut rng = rand::thread_rng();
+        let half = self.size.scale(0.5);
+        let y = if self.top_only {
+            half.y
+        } else {
+            rng.gen_range(-half.y, half.y)
+        };
+        particle.position = Vec3::new(
+            rng.gen_range(-half.x, half.x),
+            y,
+            rng.gen_range(-half.z, half.z),
+        );
+    }
+}
+
 pub fn register_custom_emitter_factory() {
     if let Ok(mut factory) = CustomEmitterFactory::get() {
         factory.set_callback(Box::new(|kind| match kind {
             0 => Ok(Box::new(CylinderEmitter::new())),
+            1 => Ok(Box::new(VolumeEmitter::new())),
             _ => Err(String::from("invalid custom emitter kind")),
         }))
     }
 }
 
-fn create_steam(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) {
+fn create_steam(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(
         ParticleSystemBuilder::new(
             BaseBuilder::new()
@@ -146,26 +495,65 @@ fn create_steam(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3)
             radius: 0.2,
         }))])
         .with_texture(
-            resource_manager.request_texture(Path::new(assets::textures::particles::SMOKE)),
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::SMOKE)),
         )
         .build(),
-    ));
+    ))
 }
 
-fn create_bullet_impact(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) {
+/// Muzzle flash is just a short flash of light, it is barely visible at day but lights up
+/// surroundings of a shooter at night.
+fn create_muzzle_flash(graph: &mut Graph, pos: Vec3) -> Handle<Node> {
+    graph.add_node(
+        PointLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+            )
+            .with_color({
+                let (r, g, b) = MUZZLE_FLASH_COLOR;
+                Color::opaque(r, g, b)
+            })
+            .with_scatter_enabled(false)
+            .cast_shadows(false),
+        )
+        .with_radius(4.0)
+        .build_node(),
+    )
+}
+
+/// Wet impacts have dim bluish sparks, as if water boiled off the surface.
+fn create_bullet_impact(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vec3,
+    wet: bool,
+) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(
         ParticleSystemBuilder::new(
             BaseBuilder::new()
-                .with_lifetime(1.0)
                 .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
         )
         .with_acceleration(Vec3::new(0.0, -10.0, 0.0))
         .with_color_over_lifetime_gradient({
             let mut gradient = ColorGradient::new();
-            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 0, 0)));
-            gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(255, 160, 0, 255)));
-            gradient.add_point(GradientPoint::new(0.95, Color::from_rgba(255, 120, 0, 255)));
-            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 60, 0, 0)));
+            if wet {
+                gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(200, 220, 255, 0)));
+                gradient.add_point(GradientPoint::new(
+                    0.05,
+                    Color::from_rgba(150, 170, 200, 255),
+                ));
+                gradient.add_point(GradientPoint::new(
+                    0.95,
+                    Color::from_rgba(120, 130, 150, 200),
+                ));
+                gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(100, 110, 120, 0)));
+            } else {
+                gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 0, 0)));
+                gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(255, 160, 0, 255)));
+                gradient.add_point(GradientPoint::new(0.95, Color::from_rgba(255, 120, 0, 255)));
+                gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 60, 0, 0)));
+            }
             gradient
         })
         .with_emitters(vec![SphereEmitterBuilder::new(
@@ -182,17 +570,16 @@ fn create_bullet_impact(graph: &mut Graph, resource_manager: ResourceManager, po
         .with_radius(0.01)
         .build()])
         .with_texture(
-            resource_manager.request_texture(Path::new(assets::textures::particles::CIRCLE)),
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::CIRCLE)),
         )
         .build(),
-    ));
+    ))
 }
 
-fn create_smoke(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) {
+fn create_smoke(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(
         ParticleSystemBuilder::new(
             BaseBuilder::new()
-                .with_lifetime(5.0)
                 .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
         )
         .with_acceleration(Vec3::new(0.0, 0.0, 0.0))
@@ -221,17 +608,20 @@ fn create_smoke(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3)
         .with_radius(0.01)
         .build()])
         .with_texture(
-            resource_manager.request_texture(Path::new(assets::textures::particles::SMOKE)),
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::SMOKE)),
         )
         .build(),
-    ));
+    ))
 }
 
-fn create_item_appear(graph: &mut Graph, resource_manager: ResourceManager, pos: Vec3) {
+fn create_item_appear(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vec3,
+) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(
         ParticleSystemBuilder::new(
             BaseBuilder::new()
-                .with_lifetime(1.4)
                 .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
         )
         .with_acceleration(Vec3::new(0.0, -6.0, 0.0))
@@ -257,8 +647,405 @@ fn create_item_appear(graph: &mut Graph, resource_manager: ResourceManager, pos:
         .with_radius(0.01)
         .build()])
         .with_texture(
-            resource_manager.request_texture(Path::new(assets::textures::particles::STAR)),
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::STAR)),
         )
         .build(),
-    ));
+    ))
+}
+
+fn create_flag_trail(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vec3,
+    (r, g, b): (u8, u8, u8),
+) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(
+        ParticleSystemBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+        )
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(r, g, b, 0)));
+            gradient.add_point(GradientPoint::new(0.10, Color::from_rgba(r, g, b, 200)));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(r, g, b, 0)));
+            gradient
+        })
+        .with_emitters(vec![SphereEmitterBuilder::new(
+            BaseEmitterBuilder::new()
+                .with_max_particles(20)
+                .with_spawn_rate(40)
+                .with_size_modifier_range(NumericRange::new(-0.005, -0.01))
+                .with_size_range(NumericRange::new(0.08, 0.12))
+                .with_x_velocity_range(NumericRange::new(-0.005, 0.005))
+                .with_y_velocity_range(NumericRange::new(0.005, 0.01))
+                .with_z_velocity_range(NumericRange::new(-0.005, 0.005))
+                .resurrect_particles(false),
+        )
+        .with_radius(0.2)
+        .build()])
+        .with_texture(
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::STAR)),
+        )
+        .build(),
+    ))
+}
+
+fn create_shield_shimmer(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vec3,
+) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(
+        ParticleSystemBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+        )
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(90, 220, 255, 0)));
+            gradient.add_point(GradientPoint::new(
+                0.30,
+                Color::from_rgba(90, 220, 255, 160),
+            ));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(90, 220, 255, 0)));
+            gradient
+        })
+        .with_emitters(vec![SphereEmitterBuilder::new(
+            BaseEmitterBuilder::new()
+                .with_max_particles(15)
+                .with_spawn_rate(30)
+                .with_size_modifier_range(NumericRange::new(-0.01, -0.02))
+                .with_size_range(NumericRange::new(0.04, 0.08))
+                .with_x_velocity_range(NumericRange::new(-0.002, 0.002))
+                .with_y_velocity_range(NumericRange::new(0.002, 0.006))
+                .with_z_velocity_range(NumericRange::new(-0.002, 0.002))
+                .resurrect_particles(false),
+        )
+        .with_radius(0.6)
+        .build()])
+        .with_texture(
+            resource_manager.request_texture(mods::resolve(assets::textures::particles::STAR)),
+        )
+        .build(),
+    ))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WeatherKind {
+    Rain,
+    Snow,
+    Dust,
+}
+
+impl WeatherKind {
+    pub fn id(self) -> u32 {
+        match self {
+            WeatherKind::Rain => 0,
+            WeatherKind::Snow => 1,
+            WeatherKind::Dust => 2,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(WeatherKind::Rain),
+            1 => Ok(WeatherKind::Snow),
+            2 => Ok(WeatherKind::Dust),
+            _ => Err(format!("Invalid weather kind {}", id)),
+        }
+    }
+
+    fn from_node_name(name: &str) -> Option<Self> {
+        if name.starts_with("Weather_Rain") {
+            Some(WeatherKind::Rain)
+        } else if name.starts_with("Weather_Snow") {
+            Some(WeatherKind::Snow)
+        } else if name.starts_with("Weather_Dust") {
+            Some(WeatherKind::Dust)
+        } else {
+            None
+        }
+    }
+
+    fn sound(self) -> &'static str {
+        match self {
+            WeatherKind::Rain => assets::sounds::ambient::RAIN,
+            WeatherKind::Snow => assets::sounds::ambient::SNOW,
+            WeatherKind::Dust => assets::sounds::ambient::DUST,
+        }
+    }
+}
+
+struct WeatherVolume {
+    kind: WeatherKind,
+    particle_system: Handle<Node>,
+    sound: Handle<SoundSource>,
+    bounds: AxisAlignedBoundingBox,
+}
+
+impl Default for WeatherVolume {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Rain,
+            particle_system: Handle::NONE,
+            sound: Handle::NONE,
+            bounds: Default::default(),
+        }
+    }
+}
+
+impl Visit for WeatherVolume {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind = self.kind.id();
+        kind.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = WeatherKind::from_id(kind)?;
+        }
+        self.particle_system.visit("ParticleSystem", visitor)?;
+        self.sound.visit("Sound", visitor)?;
+        self.bounds.visit("Bounds", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Weather of a map. Map defines weather with dummies named `Weather_Rain`, `Weather_Snow` or
+/// `Weather_Dust` scaled to size of a volume in meters, each volume gets particles and a
+/// looping ambient sound. Particles and sound instances are stored in scene and sound context
+/// so they're saved with them.
+pub struct Weather {
+    volumes: Vec<WeatherVolume>,
+    enabled: bool,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            volumes: Default::default(),
+            enabled: true,
+        }
+    }
+}
+
+impl Visit for Weather {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.volumes.visit("Volumes", visitor)?;
+        self.enabled.visit("Enabled", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Weather {
+    pub async fn new(
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        sound_context: Arc<Mutex<Context>>,
+    ) -> Self {
+        let descriptions = graph
+            .pair_iter()
+            .filter_map(|(_, node)| {
+                let kind = WeatherKind::from_node_name(node.name())?;
+                // Zero-sized volume can't emit anything.
+                let size = node.local_transform().scale();
+                let size = Vec3::new(size.x.max(0.1), size.y.max(0.1), size.z.max(0.1));
+                Some((kind, node.global_position(), size))
+            })
+            .collect::<Vec<_>>();
+
+        let mut volumes = Vec::new();
+        for (kind, position, size) in descriptions {
+            let particle_system =
+                create_weather_particles(graph, resource_manager.clone(), kind, position, size);
+
+            let sound = match resource_manager
+                .request_sound_buffer(mods::resolve(kind.sound()), true)
+                .await
+            {
+                Ok(buffer) => sound_context.lock().unwrap().add_source(
+                    SpatialSourceBuilder::new(
+                        GenericSourceBuilder::new(buffer.into())
+                            .with_looping(true)
+                            .with_status(Status::Playing)
+                            .with_gain(0.5)
+                            .build()
+                            .unwrap(),
+                    )
+                    .with_position(position)
+                    .with_radius(size.x.max(size.z) * 0.5)
+                    .with_rolloff_factor(1.0)
+                    .build_source(),
+                ),
+                Err(_) => {
+                    Log::writeln(format!(
+                        "WARNING: Unable to load ambient sound {}, weather will be silent.",
+                        kind.sound()
+                    ));
+                    Handle::NONE
+                }
+            };
+
+            let half = size.scale(0.5);
+            volumes.push(WeatherVolume {
+                kind,
+                particle_system,
+                sound,
+                bounds: AxisAlignedBoundingBox::from_min_max(position - half, position + half),
+            });
+        }
+
+        Self {
+            volumes,
+            enabled: true,
+        }
+    }
+
+    /// Shows or hides weather, controlled by quality settings.
+    pub fn set_enabled(
+        &mut self,
+        graph: &mut Graph,
+        sound_context: &Mutex<Context>,
+        enabled: bool,
+    ) {
+        if self.enabled == enabled {
+            return;
+        }
+        self.enabled = enabled;
+
+        let mut sound_context = sound_context.lock().unwrap();
+        for volume in self.volumes.iter() {
+            graph[volume.particle_system].set_visibility(enabled);
+            if volume.sound.is_some() {
+                let source = sound_context.source_mut(volume.sound);
+                if enabled {
+                    source.play();
+                } else {
+                    source.pause();
+                }
+            }
+        }
+    }
+
+    /// Returns true if given point is inside of a rain volume.
+    pub fn is_wet(&self, position: Vec3) -> bool {
+        self.volumes.iter().any(|volume| {
+            volume.kind == WeatherKind::Rain && volume.bounds.is_contains_point(position)
+        })
+    }
+
+    /// Removes ambient sounds, they live in sound context of engine which outlives a level.
+    pub fn clean_up(&mut self, sound_context: &Mutex<Context>) {
+        let mut sound_context = sound_context.lock().unwrap();
+        for volume in self.volumes.iter() {
+            if volume.sound.is_some() {
+                sound_context.remove_source(volume.sound);
+            }
+        }
+        self.volumes.clear();
+    }
+}
+
+fn create_weather_particles(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    kind: WeatherKind,
+    pos: Vec3,
+    size: Vec3,
+) -> Handle<Node> {
+    // Velocities are in meters per frame of fixed update, lifetime is chosen so falling
+    // particles reach bottom of the volume.
+    let (color, texture, particle_size, velocity, spawn_rate) = match kind {
+        WeatherKind::Rain => (
+            Color::from_rgba(170, 190, 220, 160),
+            assets::textures::particles::BULLET,
+            0.02,
+            Vec3::new(0.0, -0.25, 0.0),
+            2000,
+        ),
+        WeatherKind::Snow => (
+            Color::from_rgba(255, 255, 255, 220),
+            assets::textures::particles::CIRCLE,
+            0.03,
+            Vec3::new(0.005, -0.02, 0.0),
+            400,
+        ),
+        WeatherKind::Dust => (
+            Color::from_rgba(180, 150, 110, 60),
+            assets::textures::particles::SMOKE,
+            0.3,
+            Vec3::new(0.01, 0.0, 0.005),
+            50,
+        ),
+    };
+    let lifetime = if velocity.y < 0.0 {
+        size.y / (-velocity.y * crate::FIXED_FPS)
+    } else {
+        5.0
+    };
+    let top_only = kind != WeatherKind::Dust;
+
+    graph.add_node(Node::ParticleSystem(
+        ParticleSystemBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+        )
+        .with_acceleration(Vec3::ZERO)
+        .with_color_over_lifetime_gradient({
+            let transparent = Color::from_rgba(r, g, b, 0);
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, transparent));
+            gradient.add_point(GradientPoint::new(0.10, color));
+            gradient.add_point(GradientPoint::new(0.90, color));
+            gradient.add_point(GradientPoint::new(1.00, transparent));
+            gradient
+        })
+        .with_emitters(vec![Emitter::Custom(Box::new(VolumeEmitter {
+            base: BaseEmitterBuilder::new()
+                .with_max_particles(spawn_rate * lifetime.ceil() as u32)
+                .with_spawn_rate(spawn_rate)
+                .with_lifetime_range(NumericRange::new(lifetime, lifetime))
+                .with_size_range(NumericRange::new(particle_size, particle_size * 1.5))
+                .with_x_velocity_range(NumericRange::new(velocity.x, velocity.x))
+                .with_y_velocity_range(NumericRange::new(velocity.y, velocity.y * 1.1))
+                .with_z_velocity_range(NumericRange::new(velocity.z, velocity.z))
+                .build(),
+            size,
+            top_only,
+        }))])
+        .with_texture(resource_manager.request_texture(mods::resolve(texture)))
+        .build(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visit_test;
+
+    #[test]
+    fn effect_pool_survives_save_and_load() {
+        let mut pool = EffectPool::default();
+        pool.effects.push(PooledEffect {
+            kind: EffectKind::Smoke,
+            time_left: 0.75,
+            ..Default::default()
+        });
+        pool.effects.push(PooledEffect {
+            kind: EffectKind::MuzzleFlash,
+            ..Default::default()
+        });
+
+        let loaded = visit_test::round_trip("EffectPool", &mut pool);
+
+        assert_eq!(loaded.effects.len(), 2);
+        assert_eq!(loaded.effects[0].kind, EffectKind::Smoke);
+        assert_eq!(loaded.effects[0].time_left, 0.75);
+        assert_eq!(loaded.effects[1].kind, EffectKind::MuzzleFlash);
+        assert_eq!(loaded.effects[1].time_left, 0.0);
+    }
 }
@@ -0,0 +1,42 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub enum GameError {
+    /// Model, texture or sound could not be loaded from given path.
+    MissingAsset(PathBuf),
+    /// Node with given name is missing in a loaded model.
+    MissingNode { model: PathBuf, name: String },
+}
+
+impl GameError {
+    pub fn missing_asset<P: AsRef<Path>>(path: P) -> Self {
+        GameError::MissingAsset(path.as_ref().to_owned())
+    }
+
+    pub fn missing_node<P: AsRef<Path>, S: AsRef<str>>(model: P, name: S) -> Self {
+        GameError::MissingNode {
+            model: model.as_ref().to_owned(),
+            name: name.as_ref().to_owned(),
+        }
+    }
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::MissingAsset(path) => {
+                write!(f, "unable to load asset {}", path.display())
+            }
+            GameError::MissingNode { model, name } => {
+                write!(f, "node {} not found in {}", name, model.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+pub type GameResult<T> = Result<T, GameError>;
@@ -0,0 +1,45 @@
+//! Routing of messages to interested subsystems. Every message belongs to a category (see
+//! `Message::category`) and subsystems subscribe to categories they want to receive, so a
+//! new message can be added to existing category without touching dispatch code.
+//!
+//! Subscribers are identified by a tag instead of a trait object, because they're owned by
+//! the game and borrowed mutably only for the time of dispatch.
+
+use crate::message::MessageCategory;
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Subscriber {
+    Game,
+    SoundManager,
+    Level,
+    Hud,
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    subscriptions: HashMap<MessageCategory, Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Subscribes given subscriber to a set of categories. Subscribers of same category
+    /// receive messages in order of subscription.
+    pub fn subscribe(&mut self, subscriber: Subscriber, categories: &[MessageCategory]) {
+        for &category in categories {
+            let subscribers = self.subscriptions.entry(category).or_default();
+            if !subscribers.contains(&subscriber) {
+                subscribers.push(subscriber);
+            }
+        }
+    }
+
+    pub fn subscribers(&self, category: MessageCategory) -> &[Subscriber] {
+        self.subscriptions
+            .get(&category)
+            .map_or(&[], |subscribers| subscribers.as_slice())
+    }
+}
@@ -0,0 +1,182 @@
+//! Per-subsystem frame time breakdown for debug overlay. Subsystems report their time with
+//! `add` during frame, `end_frame` is called once per rendered frame and moves accumulated
+//! times into history, which is shown as text (averages) and as a rolling graph where every
+//! bar is one frame colored by the most expensive subsystem.
+
+use crate::{BuildContext, Gui, UINodeHandle};
+use rg3d::{
+    core::color::Color,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::{MessageDirection, WidgetMessage},
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        Orientation, VerticalAlignment,
+    },
+};
+use std::{fmt::Write, time::Duration};
+
+/// Amount of frames in history, also amount of bars in graph.
+const HISTORY_LENGTH: usize = 100;
+const BAR_WIDTH: f32 = 4.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+/// Frame time that corresponds to full height of graph, in seconds.
+const GRAPH_SCALE: f64 = 1.0 / 30.0;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Subsystem {
+    /// Whole `Level::update`, includes bot AI, projectiles and weapons.
+    Level = 0,
+    BotAi = 1,
+    Projectiles = 2,
+    Weapons = 3,
+    /// Sound is rendered in separate thread, so it is shown but not included in graph.
+    Sound = 4,
+    Ui = 5,
+}
+
+const SUBSYSTEM_COUNT: usize = 6;
+
+const SUBSYSTEMS: [Subsystem; SUBSYSTEM_COUNT] = [
+    Subsystem::Level,
+    Subsystem::BotAi,
+    Subsystem::Projectiles,
+    Subsystem::Weapons,
+    Subsystem::Sound,
+    Subsystem::Ui,
+];
+
+impl Subsystem {
+    fn name(self) -> &'static str {
+        match self {
+            Subsystem::Level => "Level",
+            Subsystem::BotAi => "  Bot AI",
+            Subsystem::Projectiles => "  Projectiles",
+            Subsystem::Weapons => "  Weapons",
+            Subsystem::Sound => "Sound",
+            Subsystem::Ui => "UI",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Subsystem::Level => Color::opaque(127, 127, 127),
+            Subsystem::BotAi => Color::opaque(237, 28, 36),
+            Subsystem::Projectiles => Color::opaque(249, 166, 2),
+            Subsystem::Weapons => Color::opaque(255, 242, 0),
+            Subsystem::Sound => Color::opaque(0, 162, 232),
+            Subsystem::Ui => Color::opaque(34, 177, 76),
+        }
+    }
+}
+
+type FrameTimes = [f64; SUBSYSTEM_COUNT];
+
+pub struct FrameProfiler {
+    pub graph: UINodeHandle,
+    bars: Vec<UINodeHandle>,
+    current: FrameTimes,
+    history: Vec<FrameTimes>,
+    /// Index of oldest frame in history, graph is drawn like on oscilloscope - new bar
+    /// overwrites oldest one instead of shifting whole graph.
+    cursor: usize,
+}
+
+impl FrameProfiler {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let bars = (0..HISTORY_LENGTH)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(BAR_WIDTH)
+                        .with_height(0.0)
+                        .with_vertical_alignment(VerticalAlignment::Bottom)
+                        .with_background(Brush::Solid(Subsystem::Level.color())),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let graph = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_height(GRAPH_HEIGHT)
+                .with_children(&bars),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        Self {
+            graph,
+            bars,
+            current: Default::default(),
+            history: vec![Default::default(); HISTORY_LENGTH],
+            cursor: 0,
+        }
+    }
+
+    /// Adds time to current frame, subsystem may report several times per frame (for
+    /// example when there were several fixed updates).
+    pub fn add(&mut self, subsystem: Subsystem, time: Duration) {
+        self.current[subsystem as usize] += time.as_secs_f64();
+    }
+
+    pub fn end_frame(&mut self, ui: &mut Gui) {
+        let frame = std::mem::take(&mut self.current);
+
+        // Time of level itself without its nested subsystems, so the most expensive one
+        // can be found.
+        let mut self_times = frame;
+        self_times[Subsystem::Level as usize] -= frame[Subsystem::BotAi as usize]
+            + frame[Subsystem::Projectiles as usize]
+            + frame[Subsystem::Weapons as usize];
+        self_times[Subsystem::Sound as usize] = 0.0;
+        let dominant = SUBSYSTEMS
+            .iter()
+            .max_by(|a, b| {
+                self_times[**a as usize]
+                    .partial_cmp(&self_times[**b as usize])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .cloned()
+            .unwrap_or(Subsystem::Level);
+
+        let total = frame[Subsystem::Level as usize] + frame[Subsystem::Ui as usize];
+        let bar = self.bars[self.cursor];
+        ui.send_message(WidgetMessage::height(
+            bar,
+            MessageDirection::ToWidget,
+            ((total / GRAPH_SCALE).min(1.0) * GRAPH_HEIGHT as f64) as f32,
+        ));
+        ui.send_message(WidgetMessage::background(
+            bar,
+            MessageDirection::ToWidget,
+            Brush::Solid(dominant.color()),
+        ));
+
+        self.history[self.cursor] = frame;
+        self.cursor = (self.cursor + 1) % HISTORY_LENGTH;
+    }
+
+    /// Writes average and peak time of every subsystem over history.
+    pub fn write_summary(&self, out: &mut String) {
+        for subsystem in SUBSYSTEMS.iter() {
+            let index = *subsystem as usize;
+            let (sum, max) = self
+                .history
+                .iter()
+                .map(|frame| frame[index])
+                .fold((0.0, 0.0f64), |(sum, max), time| {
+                    (sum + time, max.max(time))
+                });
+            writeln!(
+                out,
+                "{}: {:.2} ms (max {:.2} ms)",
+                subsystem.name(),
+                sum / HISTORY_LENGTH as f64 * 1000.0,
+                max * 1000.0
+            )
+            .unwrap();
+        }
+    }
+}
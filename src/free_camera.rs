@@ -0,0 +1,237 @@
+//! Free camera detached from active one of a scene, debug editor flies it around frozen
+//! match. Camera uses movement keys of control scheme, jump/crouch keys move it up and down,
+//! Q/E rolls it and mouse wheel changes field of view.
+
+use crate::{
+    control_scheme::{ControlButton, ControlScheme},
+    GameEngine,
+};
+use rg3d::{
+    core::{
+        math::{quat::Quat, vec3::Vec3},
+        pool::Handle,
+    },
+    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    scene::{base::BaseBuilder, camera::CameraBuilder, node::Node, Scene},
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Speed of camera in meters per second, doubled when run key is held.
+const MOVE_SPEED: f32 = 4.0;
+/// Speed of roll in degrees per second.
+const ROLL_SPEED: f32 = 45.0;
+const MIN_FOV: f32 = 10.0;
+const MAX_FOV: f32 = 120.0;
+const FOV_STEP: f32 = 5.0;
+
+#[derive(Default)]
+struct Controller {
+    move_forward: bool,
+    move_backward: bool,
+    move_left: bool,
+    move_right: bool,
+    move_up: bool,
+    move_down: bool,
+    roll_left: bool,
+    roll_right: bool,
+    run: bool,
+}
+
+pub struct FreeCamera {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    /// Cameras that were active before free camera, they're enabled back on exit.
+    disabled_cameras: Vec<Handle<Node>>,
+    control_scheme: Rc<RefCell<ControlScheme>>,
+    controller: Controller,
+    // Angles are in degrees.
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+    fov: f32,
+}
+
+impl FreeCamera {
+    /// Creates free camera at the place of currently active camera of the scene.
+    pub fn enter(
+        engine: &mut GameEngine,
+        scene: Handle<Scene>,
+        control_scheme: Rc<RefCell<ControlScheme>>,
+    ) -> Self {
+        let scene_ref = &mut engine.scenes[scene];
+
+        let mut position = Vec3::ZERO;
+        let mut look = Vec3::LOOK;
+        let mut fov = 75.0;
+        let mut disabled_cameras = Vec::new();
+        for (handle, node) in scene_ref.graph.pair_iter_mut() {
+            if let Node::Camera(camera) = node {
+                if camera.is_enabled() {
+                    position = camera.global_position();
+                    look = camera.look_vector();
+                    fov = camera.fov().to_degrees();
+                    camera.set_enabled(false);
+                    disabled_cameras.push(handle);
+                }
+            }
+        }
+
+        let camera = scene_ref.graph.add_node(Node::Camera(
+            CameraBuilder::new(BaseBuilder::new())
+                .with_fov(fov.to_radians())
+                .build(),
+        ));
+
+        let mut free_camera = Self {
+            scene,
+            camera,
+            disabled_cameras,
+            control_scheme,
+            controller: Default::default(),
+            yaw: look.x.atan2(look.z).to_degrees(),
+            pitch: (-look.y).max(-1.0).min(1.0).asin().to_degrees(),
+            roll: 0.0,
+            fov,
+        };
+        free_camera.update_transform(scene_ref, position);
+        free_camera
+    }
+
+    /// Removes free camera and activates cameras that were active before.
+    pub fn leave(self, engine: &mut GameEngine) {
+        let scene = &mut engine.scenes[self.scene];
+        scene.remove_node(self.camera);
+        for &handle in self.disabled_cameras.iter() {
+            if let Node::Camera(camera) = &mut scene.graph[handle] {
+                camera.set_enabled(true);
+            }
+        }
+    }
+
+    pub fn camera(&self) -> Handle<Node> {
+        self.camera
+    }
+
+    fn update_transform(&self, scene: &mut Scene, position: Vec3) {
+        let rotation = Quat::from_axis_angle(Vec3::UP, self.yaw.to_radians())
+            * Quat::from_axis_angle(Vec3::RIGHT, self.pitch.to_radians())
+            * Quat::from_axis_angle(Vec3::LOOK, self.roll.to_radians());
+        if let Node::Camera(camera) = &mut scene.graph[self.camera] {
+            camera
+                .local_transform_mut()
+                .set_position(position)
+                .set_rotation(rotation);
+            camera.set_fov(self.fov.to_radians());
+        }
+    }
+
+    pub fn process_input_event(&mut self, event: &Event<()>) {
+        let control_scheme = self.control_scheme.clone();
+        let control_scheme = control_scheme.borrow();
+
+        let mut control_button = None;
+        let mut control_button_state = ElementState::Released;
+
+        if let Event::DeviceEvent { event, .. } = event {
+            match event {
+                DeviceEvent::MouseMotion { delta } => {
+                    let mouse_sens_y = if control_scheme.mouse_y_inverse {
+                        -control_scheme.mouse_sens
+                    } else {
+                        control_scheme.mouse_sens
+                    };
+                    self.yaw -= delta.0 as f32 * control_scheme.mouse_sens;
+                    self.pitch = (self.pitch + delta.1 as f32 * mouse_sens_y)
+                        .max(-90.0)
+                        .min(90.0);
+                }
+                DeviceEvent::MouseWheel { delta } => {
+                    if let MouseScrollDelta::LineDelta(_, y) = delta {
+                        self.fov = (self.fov - y.signum() * FOV_STEP).max(MIN_FOV).min(MAX_FOV);
+                    }
+                }
+                DeviceEvent::Button { button, state } => {
+                    control_button = Some(ControlButton::Mouse(*button as u8));
+                    control_button_state = *state;
+                }
+                _ => (),
+            }
+        }
+
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                if let Some(code) = input.virtual_keycode {
+                    control_button = Some(ControlButton::Key(code));
+                    control_button_state = input.state;
+                }
+            }
+        }
+
+        if let Some(control_button) = control_button {
+            let pressed = control_button_state == ElementState::Pressed;
+            if control_button == control_scheme.move_forward.button {
+                self.controller.move_forward = pressed;
+            } else if control_button == control_scheme.move_backward.button {
+                self.controller.move_backward = pressed;
+            } else if control_button == control_scheme.move_left.button {
+                self.controller.move_left = pressed;
+            } else if control_button == control_scheme.move_right.button {
+                self.controller.move_right = pressed;
+            } else if control_button == control_scheme.jump.button {
+                self.controller.move_up = pressed;
+            } else if control_button == control_scheme.crouch.button {
+                self.controller.move_down = pressed;
+            } else if control_button == control_scheme.run.button {
+                self.controller.run = pressed;
+            } else if control_button == ControlButton::Key(VirtualKeyCode::Q) {
+                self.controller.roll_left = pressed;
+            } else if control_button == ControlButton::Key(VirtualKeyCode::E) {
+                self.controller.roll_right = pressed;
+            }
+        }
+    }
+
+    /// Moves camera, must be called every update while free camera is active.
+    pub fn update(&mut self, engine: &mut GameEngine, dt: f32) {
+        let scene = &mut engine.scenes[self.scene];
+        let camera = &scene.graph[self.camera];
+
+        let mut velocity = Vec3::ZERO;
+        if self.controller.move_forward {
+            velocity += camera.look_vector();
+        }
+        if self.controller.move_backward {
+            velocity -= camera.look_vector();
+        }
+        if self.controller.move_left {
+            velocity += camera.side_vector();
+        }
+        if self.controller.move_right {
+            velocity -= camera.side_vector();
+        }
+        if self.controller.move_up {
+            velocity += Vec3::UP;
+        }
+        if self.controller.move_down {
+            velocity -= Vec3::UP;
+        }
+        let speed = if self.controller.run {
+            MOVE_SPEED * 2.0
+        } else {
+            MOVE_SPEED
+        };
+        let position = camera.global_position()
+            + velocity
+                .normalized()
+                .map_or(Vec3::ZERO, |direction| direction.scale(speed * dt));
+
+        if self.controller.roll_left {
+            self.roll -= ROLL_SPEED * dt;
+        }
+        if self.controller.roll_right {
+            self.roll += ROLL_SPEED * dt;
+        }
+
+        self.update_transform(scene, position);
+    }
+}
@@ -2,14 +2,15 @@
 //! However most of the styles are used from dark theme of rg3d-ui library so there
 //! is not much.
 
-use crate::{assets, BuildContext, UINodeHandle};
+use crate::{assets, mods, BuildContext, UINodeHandle};
 use rg3d::{
     core::color::Color,
     engine::resource_manager::ResourceManager,
     gui::{
-        brush::Brush, check_box::CheckBoxBuilder, image::ImageBuilder,
-        scroll_bar::ScrollBarBuilder, scroll_viewer::ScrollViewerBuilder, widget::WidgetBuilder,
-        HorizontalAlignment, Orientation, Thickness, VerticalAlignment,
+        border::BorderBuilder, brush::Brush, check_box::CheckBoxBuilder,
+        decorator::DecoratorBuilder, image::ImageBuilder, numeric::NumericUpDownBuilder,
+        scroll_bar::ScrollBarBuilder, scroll_viewer::ScrollViewerBuilder, text::TextBuilder,
+        widget::WidgetBuilder, HorizontalAlignment, Orientation, Thickness, VerticalAlignment,
     },
     utils,
 };
@@ -53,13 +54,40 @@ pub fn create_scroll_bar(
             WidgetBuilder::new().with_background(Brush::Solid(Color::opaque(110, 110, 110))),
         )
         .with_texture(utils::into_gui_texture(
-            resource_manager.request_texture(assets::textures::interface::CIRCLE),
+            resource_manager.request_texture(mods::resolve(assets::textures::interface::CIRCLE)),
         ))
         .build(ctx),
     )
     .build(ctx)
 }
 
+pub struct NumericData {
+    pub min: f32,
+    pub max: f32,
+    pub value: f32,
+    pub step: f32,
+    pub row: usize,
+    pub column: usize,
+    pub margin: Thickness,
+}
+
+/// Creates numeric picker for whole numbers.
+pub fn create_numeric_up_down(ctx: &mut BuildContext, data: NumericData) -> UINodeHandle {
+    NumericUpDownBuilder::new(
+        WidgetBuilder::new()
+            .with_height(30.0)
+            .on_row(data.row)
+            .on_column(data.column)
+            .with_margin(data.margin),
+    )
+    .with_min_value(data.min)
+    .with_max_value(data.max)
+    .with_step(data.step)
+    .with_value(data.value)
+    .with_precision(0)
+    .build(ctx)
+}
+
 pub fn create_check_box(
     ctx: &mut BuildContext,
     resource_manager: ResourceManager,
@@ -80,9 +108,9 @@ pub fn create_check_box(
     .checked(Some(checked))
     .with_check_mark(
         ImageBuilder::new(WidgetBuilder::new())
-            .with_texture(utils::into_gui_texture(
-                resource_manager.request_texture(assets::textures::interface::CHECK_MARK),
-            ))
+            .with_texture(utils::into_gui_texture(resource_manager.request_texture(
+                mods::resolve(assets::textures::interface::CHECK_MARK),
+            )))
             .build(ctx),
     )
     .build(ctx)
@@ -125,3 +153,24 @@ pub fn create_scroll_viewer(
         ))
         .build(ctx)
 }
+
+/// Creates items of dropdown list with given names.
+pub fn create_dropdown_items(ctx: &mut BuildContext, names: &[&str]) -> Vec<UINodeHandle> {
+    names
+        .iter()
+        .map(|name| {
+            DecoratorBuilder::new(BorderBuilder::new(
+                WidgetBuilder::new().with_height(30.0).with_child(
+                    TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Center),
+                    )
+                    .with_text(name)
+                    .build(ctx),
+                ),
+            ))
+            .build(ctx)
+        })
+        .collect()
+}
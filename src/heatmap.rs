@@ -0,0 +1,202 @@
+//! Positions of deaths, kills and item pickups during a match. When match is over they are
+//! exported as JSON and CSV, so level designers can check flow of a map and placement of
+//! spawn points and items. Debug geometry of level shows them as colored crosses.
+
+use rg3d::{
+    core::{
+        color::Color,
+        math::vec3::Vec3,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{self, SceneDrawingContext},
+};
+use serde::Serialize;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Half of size of a cross that marks an event in debug geometry.
+const CROSS_SIZE: f32 = 0.25;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize)]
+pub enum HeatmapEventKind {
+    /// Position of victim.
+    Death,
+    /// Position of killer at the moment of kill.
+    Kill,
+    Pickup,
+}
+
+impl HeatmapEventKind {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(HeatmapEventKind::Death),
+            1 => Ok(HeatmapEventKind::Kill),
+            2 => Ok(HeatmapEventKind::Pickup),
+            _ => Err(format!("Unknown heatmap event kind {}", id)),
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            HeatmapEventKind::Death => 0,
+            HeatmapEventKind::Kill => 1,
+            HeatmapEventKind::Pickup => 2,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            HeatmapEventKind::Death => Color::opaque(220, 30, 30),
+            HeatmapEventKind::Kill => Color::opaque(30, 220, 30),
+            HeatmapEventKind::Pickup => Color::opaque(30, 120, 220),
+        }
+    }
+}
+
+pub struct HeatmapEvent {
+    kind: HeatmapEventKind,
+    position: Vec3,
+    /// Time since start of match in seconds.
+    time: f32,
+}
+
+impl Default for HeatmapEvent {
+    fn default() -> Self {
+        Self {
+            kind: HeatmapEventKind::Death,
+            position: Vec3::ZERO,
+            time: 0.0,
+        }
+    }
+}
+
+impl Visit for HeatmapEvent {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind = self.kind.id();
+        kind.visit("Kind", visitor)?;
+        if visitor.is_reading() {
+            self.kind = HeatmapEventKind::from_id(kind)?;
+        }
+        self.position.visit("Position", visitor)?;
+        self.time.visit("Time", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Layout of exported JSON file.
+#[derive(Serialize)]
+struct HeatmapFile<'a> {
+    map: &'a str,
+    events: Vec<HeatmapRecord>,
+}
+
+#[derive(Serialize)]
+struct HeatmapRecord {
+    kind: HeatmapEventKind,
+    x: f32,
+    y: f32,
+    z: f32,
+    time: f32,
+}
+
+#[derive(Default)]
+pub struct Heatmap {
+    /// Path of map model, it is written to exported files.
+    map: String,
+    events: Vec<HeatmapEvent>,
+}
+
+impl Heatmap {
+    pub fn new(map: &str) -> Self {
+        Self {
+            map: map.to_owned(),
+            events: Default::default(),
+        }
+    }
+
+    pub fn add(&mut self, kind: HeatmapEventKind, position: Vec3, time: f32) {
+        self.events.push(HeatmapEvent {
+            kind,
+            position,
+            time,
+        });
+    }
+
+    /// Writes events to a pair of files with same name and `json` and `csv` extensions in
+    /// given directory. Nothing is written if there were no events.
+    pub fn export(&self, dir: &Path) -> Result<Option<PathBuf>, String> {
+        if self.events.is_empty() {
+            return Ok(None);
+        }
+
+        let map_name = Path::new(&self.map)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "map".to_owned());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = dir.join(format!("heatmap_{}_{}", map_name, timestamp));
+
+        let file = HeatmapFile {
+            map: &self.map,
+            events: self
+                .events
+                .iter()
+                .map(|event| HeatmapRecord {
+                    kind: event.kind,
+                    x: event.position.x,
+                    y: event.position.y,
+                    z: event.position.z,
+                    time: event.time,
+                })
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        std::fs::write(path.with_extension("json"), json).map_err(|e| e.to_string())?;
+
+        let mut csv = "kind,x,y,z,time\n".to_owned();
+        for event in self.events.iter() {
+            writeln!(
+                csv,
+                "{:?},{},{},{},{}",
+                event.kind, event.position.x, event.position.y, event.position.z, event.time
+            )
+            .unwrap();
+        }
+        std::fs::write(path.with_extension("csv"), csv).map_err(|e| e.to_string())?;
+
+        Ok(Some(path))
+    }
+
+    pub fn debug_draw(&self, context: &mut SceneDrawingContext) {
+        for event in self.events.iter() {
+            let color = event.kind.color();
+            for axis in [Vec3::RIGHT, Vec3::UP, Vec3::LOOK].iter() {
+                context.add_line(scene::Line {
+                    begin: event.position - axis.scale(CROSS_SIZE),
+                    end: event.position + axis.scale(CROSS_SIZE),
+                    color,
+                });
+            }
+        }
+    }
+}
+
+impl Visit for Heatmap {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.map.visit("Map", visitor)?;
+        self.events.visit("Events", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
     assets,
+    control_scheme::ControlButton,
     leader_board::{LeaderBoard, LeaderBoardUI},
-    message::Message,
-    GameEngine, GameTime, Gui, MatchOptions, UINodeHandle,
+    message::{Message, MessageCategory},
+    mods, GameEngine, GameTime, Gui, MatchOptions, UINodeHandle,
 };
 use rg3d::{
     core::color::Color,
@@ -23,27 +24,62 @@ use rg3d::{
 };
 use std::{
     collections::VecDeque,
-    path::Path,
     sync::{Arc, Mutex},
 };
 
+/// Width of overshield bar when overshield is full.
+const SHIELD_BAR_WIDTH: f32 = 300.0;
+
+/// Hint about action that is available right now, shown as `[E] Pick up M4`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyPrompt {
+    pub button: ControlButton,
+    pub action: String,
+}
+
 pub struct Hud {
     root: UINodeHandle,
     health: UINodeHandle,
     armor: UINodeHandle,
+    shield: UINodeHandle,
     ammo: UINodeHandle,
+    no_ammo: UINodeHandle,
     time: UINodeHandle,
     message: UINodeHandle,
     message_queue: VecDeque<String>,
     message_timeout: f32,
+    announcement: UINodeHandle,
+    announcement_timeout: f32,
     leader_board: LeaderBoardUI,
     match_limit: UINodeHandle,
     first_score: UINodeHandle,
     second_score: UINodeHandle,
     died: UINodeHandle,
+    quality_scale: UINodeHandle,
+    last_quality_scale: Option<f32>,
+    flag_carrier: UINodeHandle,
+    /// State of objective of the mode, like who holds the skull in oddball.
+    objective: UINodeHandle,
+    /// Rounds won by each duelist in duel.
+    round_score: UINodeHandle,
+    /// Lives left of player in last man standing.
+    lives: UINodeHandle,
+    practice: UINodeHandle,
+    tutorial_prompt: UINodeHandle,
+    key_prompt: UINodeHandle,
+    /// Visibility requested by the game, HUD is actually shown only if it is not HUD-free.
+    visible: bool,
+    /// Master flag of HUD-free mode for capturing footage: HUD and debug widgets are hidden
+    /// regardless of requested visibility.
+    hud_free: bool,
+    /// Widgets outside of HUD, like debug text, that are hidden in HUD-free mode too.
+    debug_widgets: Vec<UINodeHandle>,
 }
 
 impl Hud {
+    pub const SUBSCRIPTIONS: &'static [MessageCategory] =
+        &[MessageCategory::Notification, MessageCategory::Actor];
+
     pub fn new(engine: &mut GameEngine) -> Self {
         let leader_board = LeaderBoardUI::new(engine);
 
@@ -52,7 +88,7 @@ impl Hud {
         let resource_manager = engine.resource_manager.clone();
 
         let font = Font::from_file(
-            Path::new(assets::fonts::SQUARES_BOLD),
+            mods::resolve(assets::fonts::SQUARES_BOLD),
             35.0,
             Font::default_char_set(),
         )
@@ -61,13 +97,24 @@ impl Hud {
 
         let health;
         let armor;
+        let shield;
         let ammo;
+        let no_ammo;
+        let announcement;
         let message;
         let time;
         let first_score;
         let second_score;
         let match_limit;
         let died;
+        let quality_scale;
+        let flag_carrier;
+        let objective;
+        let round_score;
+        let lives;
+        let practice;
+        let tutorial_prompt;
+        let key_prompt;
         let root = GridBuilder::new(
             WidgetBuilder::new()
                 .with_width(frame_size.0 as f32)
@@ -83,10 +130,9 @@ impl Hud {
                             .on_row(0)
                             .on_column(1),
                     )
-                    .with_texture(utils::into_gui_texture(
-                        resource_manager
-                            .request_texture(Path::new(assets::textures::interface::CROSSHAIR)),
-                    ))
+                    .with_texture(utils::into_gui_texture(resource_manager.request_texture(
+                        mods::resolve(assets::textures::interface::CROSSHAIR),
+                    )))
                     .build(ctx),
                 )
                 .with_child({
@@ -208,7 +254,7 @@ impl Hud {
                                     WidgetBuilder::new().with_width(35.0).with_height(35.0),
                                 )
                                 .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture(Path::new(
+                                    resource_manager.request_texture(mods::resolve(
                                         assets::textures::interface::HEALTH_ICON,
                                     )),
                                 ))
@@ -250,7 +296,7 @@ impl Hud {
                                     WidgetBuilder::new().with_width(35.0).with_height(35.0),
                                 )
                                 .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture(Path::new(
+                                    resource_manager.request_texture(mods::resolve(
                                         assets::textures::interface::AMMO_ICON,
                                     )),
                                 ))
@@ -292,7 +338,7 @@ impl Hud {
                                     WidgetBuilder::new().with_width(35.0).with_height(35.0),
                                 )
                                 .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture(Path::new(
+                                    resource_manager.request_texture(mods::resolve(
                                         assets::textures::interface::SHIELD_ICON,
                                     )),
                                 ))
@@ -353,8 +399,210 @@ impl Hud {
                     )
                     .with_font(font)
                     .with_text("You Died")
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
                     .build(ctx);
                     died
+                })
+                .with_child({
+                    quality_scale = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(2)
+                            .with_margin(Thickness::uniform(5.0))
+                            .with_foreground(Brush::Solid(Color::opaque(249, 166, 2)))
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_horizontal_alignment(HorizontalAlignment::Right),
+                    )
+                    .build(ctx);
+                    quality_scale
+                })
+                .with_child({
+                    flag_carrier = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 60.0,
+                                right: 0.0,
+                                bottom: 0.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(255, 60, 60)))
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .build(ctx);
+                    flag_carrier
+                })
+                .with_child({
+                    objective = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 85.0,
+                                right: 0.0,
+                                bottom: 0.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(240, 220, 120)))
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .build(ctx);
+                    objective
+                })
+                .with_child({
+                    round_score = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 110.0,
+                                right: 0.0,
+                                bottom: 0.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(220, 220, 255)))
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .build(ctx);
+                    round_score
+                })
+                .with_child({
+                    lives = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(Thickness::bottom(55.0))
+                            .with_foreground(Brush::Solid(Color::opaque(180, 14, 22)))
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .build(ctx);
+                    lives
+                })
+                .with_child({
+                    practice = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(2)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 40.0,
+                                right: 5.0,
+                                bottom: 0.0,
+                            })
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_horizontal_alignment(HorizontalAlignment::Right),
+                    )
+                    .build(ctx);
+                    practice
+                })
+                .with_child({
+                    tutorial_prompt = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 0.0,
+                                right: 0.0,
+                                bottom: 120.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(255, 220, 90)))
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                    .build(ctx);
+                    tutorial_prompt
+                })
+                .with_child({
+                    shield = BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_width(SHIELD_BAR_WIDTH)
+                            .with_height(12.0)
+                            .with_margin(Thickness::bottom(60.0))
+                            .with_background(Brush::Solid(Color::opaque(90, 220, 255)))
+                            .with_foreground(Brush::Solid(Color::opaque(40, 140, 200)))
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .with_stroke_thickness(Thickness::uniform(2.0))
+                    .build(ctx);
+                    shield
+                })
+                .with_child({
+                    key_prompt = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 80.0,
+                                right: 0.0,
+                                bottom: 0.0,
+                            })
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .build(ctx);
+                    key_prompt
+                })
+                .with_child({
+                    announcement = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 0.0,
+                                right: 0.0,
+                                bottom: 250.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(255, 220, 90)))
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .with_font(font.clone())
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                    .build(ctx);
+                    announcement
+                })
+                .with_child({
+                    no_ammo = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 0.0,
+                                right: 0.0,
+                                bottom: 80.0,
+                            })
+                            .with_foreground(Brush::Solid(Color::opaque(255, 60, 60)))
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_horizontal_alignment(HorizontalAlignment::Center),
+                    )
+                    .with_font(font.clone())
+                    .with_text("NO AMMO")
+                    .build(ctx);
+                    no_ammo
                 }),
         )
         .add_column(Column::stretch())
@@ -368,14 +616,30 @@ impl Hud {
             root,
             health,
             armor,
+            shield,
             ammo,
+            no_ammo,
             message,
             time,
             first_score,
             second_score,
             match_limit,
             died,
+            quality_scale,
+            last_quality_scale: None,
+            flag_carrier,
+            objective,
+            round_score,
+            lives,
+            practice,
+            tutorial_prompt,
+            key_prompt,
+            visible: false,
+            hud_free: false,
+            debug_widgets: Default::default(),
             message_timeout: 0.0,
+            announcement,
+            announcement_timeout: 0.0,
             message_queue: Default::default(),
         }
     }
@@ -396,20 +660,79 @@ impl Hud {
         ));
     }
 
-    pub fn set_ammo(&mut self, ui: &mut Gui, ammo: u32) {
+    /// Shows overshield bar with given fill from 0.0 to 1.0, hides it when there is no
+    /// overshield.
+    pub fn set_shield(&mut self, ui: &mut Gui, fill: f32) {
+        ui.send_message(WidgetMessage::visibility(
+            self.shield,
+            MessageDirection::ToWidget,
+            fill > 0.0,
+        ));
+        ui.send_message(WidgetMessage::width(
+            self.shield,
+            MessageDirection::ToWidget,
+            SHIELD_BAR_WIDTH * fill.min(1.0),
+        ));
+    }
+
+    /// Sets ammo of current weapon, low amount is shown in warning color.
+    pub fn set_ammo(&mut self, ui: &mut Gui, ammo: u32, low: bool) {
         ui.send_message(TextMessage::text(
             self.ammo,
             MessageDirection::ToWidget,
             format!("{}", ammo),
         ));
+        let color = if low {
+            Color::opaque(255, 140, 0)
+        } else {
+            Color::opaque(79, 79, 255)
+        };
+        ui.send_message(WidgetMessage::foreground(
+            self.ammo,
+            MessageDirection::ToWidget,
+            Brush::Solid(color),
+        ));
+    }
+
+    /// Flashes "no ammo" after player tried to shoot an empty weapon.
+    pub fn set_no_ammo(&mut self, ui: &mut Gui, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.no_ammo,
+            MessageDirection::ToWidget,
+            visible,
+        ));
     }
 
     pub fn set_visible(&mut self, ui: &mut Gui, visible: bool) {
+        self.visible = visible;
+        self.sync_visibility(ui);
+    }
+
+    /// Adds widget that is hidden in HUD-free mode along with HUD, widget must be visible.
+    pub fn add_debug_widget(&mut self, widget: UINodeHandle) {
+        self.debug_widgets.push(widget);
+    }
+
+    /// Turns HUD-free mode on or off. Turning it off shows back only what was shown before,
+    /// so HUD stays hidden if it was hidden by menu or editor meanwhile.
+    pub fn toggle_hud_free(&mut self, ui: &mut Gui) {
+        self.hud_free = !self.hud_free;
+        self.sync_visibility(ui);
+    }
+
+    fn sync_visibility(&self, ui: &mut Gui) {
         ui.send_message(WidgetMessage::visibility(
             self.root,
             MessageDirection::ToWidget,
-            visible,
+            self.visible && !self.hud_free,
         ));
+        for &widget in self.debug_widgets.iter() {
+            ui.send_message(WidgetMessage::visibility(
+                widget,
+                MessageDirection::ToWidget,
+                !self.hud_free,
+            ));
+        }
     }
 
     pub fn set_time(&mut self, ui: &mut Gui, time: f32) {
@@ -424,12 +747,152 @@ impl Hud {
         ));
     }
 
-    pub fn set_is_died(&mut self, ui: &mut Gui, is_died: bool) {
+    /// Shows death screen with given text or hides it if there is no text.
+    pub fn set_death_screen(&mut self, ui: &mut Gui, text: Option<String>) {
         ui.send_message(WidgetMessage::visibility(
             self.died,
             MessageDirection::ToWidget,
-            is_died,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.died,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows marker of enemy who carries flag of player's team, hides it if there is none.
+    pub fn set_flag_carrier(&mut self, ui: &mut Gui, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.flag_carrier,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.flag_carrier,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows state of objective of the mode, hides it in modes without one.
+    pub fn set_objective(&mut self, ui: &mut Gui, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.objective,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.objective,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows rounds won by duelists, hides it outside of duel.
+    pub fn set_round_score(&mut self, ui: &mut Gui, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.round_score,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.round_score,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows lives left of player, hides the counter when lives are unlimited.
+    pub fn set_lives(&mut self, ui: &mut Gui, lives: Option<u32>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.lives,
+            MessageDirection::ToWidget,
+            lives.is_some(),
+        ));
+        if let Some(lives) = lives {
+            ui.send_message(TextMessage::text(
+                self.lives,
+                MessageDirection::ToWidget,
+                format!("Lives: {}", lives),
+            ));
+        }
+    }
+
+    /// Shows DPS meter of shooting range, hidden outside of practice mode.
+    pub fn set_practice(&mut self, ui: &mut Gui, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.practice,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.practice,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows prompt of tutorial, hidden outside of tutorial.
+    pub fn set_tutorial_prompt(&mut self, ui: &mut Gui, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.tutorial_prompt,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.tutorial_prompt,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    /// Shows hint about action under crosshair with key that is bound to it.
+    pub fn set_key_prompt(&mut self, ui: &mut Gui, prompt: Option<KeyPrompt>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.key_prompt,
+            MessageDirection::ToWidget,
+            prompt.is_some(),
+        ));
+        if let Some(prompt) = prompt {
+            ui.send_message(TextMessage::text(
+                self.key_prompt,
+                MessageDirection::ToWidget,
+                format!("[{}] {}", prompt.button.name(), prompt.action),
+            ));
+        }
+    }
+
+    /// Shows scale of shadow distances, indicator is hidden when distances are not lowered.
+    pub fn set_quality_scale(&mut self, ui: &mut Gui, scale: Option<f32>) {
+        if scale == self.last_quality_scale {
+            return;
+        }
+        self.last_quality_scale = scale;
+        ui.send_message(WidgetMessage::visibility(
+            self.quality_scale,
+            MessageDirection::ToWidget,
+            scale.is_some(),
         ));
+        if let Some(scale) = scale {
+            ui.send_message(TextMessage::text(
+                self.quality_scale,
+                MessageDirection::ToWidget,
+                format!("Shadow distance: {}%", (scale * 100.0).round()),
+            ));
+        }
     }
 
     pub fn add_message<P: AsRef<str>>(&mut self, message: P) {
@@ -459,9 +922,24 @@ impl Hud {
         &self.leader_board
     }
 
+    pub fn leader_board_mut(&mut self) -> &mut LeaderBoardUI {
+        &mut self.leader_board
+    }
+
     pub fn update(&mut self, ui: &mut Gui, time: &GameTime) {
         self.message_timeout -= time.delta;
 
+        if self.announcement_timeout > 0.0 {
+            self.announcement_timeout -= time.delta;
+            if self.announcement_timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(
+                    self.announcement,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+        }
+
         if self.message_timeout <= 0.0 {
             if let Some(message) = self.message_queue.pop_front() {
                 ui.send_message(TextMessage::text(
@@ -488,14 +966,17 @@ impl Hud {
     ) {
         // TODO: This is probably not correct way of showing leader and second place on HUD
         //  it is better to show player's score and leader/second score of some bot.
-        if let Some((leader_name, leader_score)) = leader_board.highest_personal_score(None) {
+        if let Some((leader_name, leader_score)) =
+            leader_board.highest_personal_score(match_options, None)
+        {
             ui.send_message(TextMessage::text(
                 self.first_score,
                 MessageDirection::ToWidget,
                 format!("{}", leader_score),
             ));
 
-            if let Some((_, second_score)) = leader_board.highest_personal_score(Some(leader_name))
+            if let Some((_, second_score)) =
+                leader_board.highest_personal_score(match_options, Some(leader_name))
             {
                 ui.send_message(TextMessage::text(
                     self.second_score,
@@ -509,6 +990,11 @@ impl Hud {
             MatchOptions::DeathMatch(dm) => dm.frag_limit,
             MatchOptions::TeamDeathMatch(tdm) => tdm.team_frag_limit,
             MatchOptions::CaptureTheFlag(ctf) => ctf.flag_limit,
+            MatchOptions::Oddball(oddball) => oddball.hold_limit,
+            MatchOptions::KingOfTheHill(koth) => koth.hold_limit,
+            MatchOptions::Duel(duel) => duel.rounds_to_win(),
+            MatchOptions::LastManStanding(lms) => lms.lives,
+            MatchOptions::Practice(_) | MatchOptions::Tutorial(_) | MatchOptions::TimeTrial(_) => 0,
         };
         ui.send_message(TextMessage::text(
             self.match_limit,
@@ -526,11 +1012,26 @@ impl Hud {
     ) {
         match message {
             Message::AddNotification { text } => self.add_message(text),
-            Message::AddBot { .. }
-            | Message::RemoveActor { .. }
+            Message::Announce { text, duration, .. } => {
+                ui.send_message(TextMessage::text(
+                    self.announcement,
+                    MessageDirection::ToWidget,
+                    text.clone(),
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    self.announcement,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+                self.announcement_timeout = *duration;
+            }
+            Message::RemoveActor { .. }
             | Message::RespawnActor { .. }
-            | Message::SpawnBot { .. }
-            | Message::SpawnPlayer => {
+            | Message::SpawnActor { .. }
+            | Message::AddBot { .. }
+            | Message::RemoveBot
+            | Message::AdjustScore { .. }
+            | Message::StartRound => {
                 self.update_leader_board_overview(ui, leader_board, match_options)
             }
             _ => (),
@@ -0,0 +1,295 @@
+//! Things that player uses with interact button. Interactables are meshes of a map:
+//!
+//! - `Door_<name>` slides up by its height when used and closes by itself after a while. Bots
+//!   can't press buttons, so doors open for them when they come close.
+//! - `Elevator_<name>` is a platform that moves between its position and a node named
+//!   `Elevator_<name>_Top`.
+//! - `Switch_<target>` uses interactable with mesh named `<target>`, for example
+//!   `Switch_Elevator_Lift` calls elevator `Elevator_Lift` and `Switch_Door_Gate` opens door
+//!   `Door_Gate`.
+//!
+//! Doors and elevators have rigid bodies that are moved explicitly, so they block actors and
+//! carry them.
+
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    physics::{
+        convex_shape::{BoxShape, ConvexShape},
+        rigid_body::RigidBody,
+        Physics,
+    },
+    scene::{node::Node, Scene},
+};
+
+/// Time after which door closes by itself, in seconds.
+const DOOR_OPEN_TIME: f32 = 5.0;
+/// Speed of doors and elevators in meters per second.
+const MOVE_SPEED: f32 = 2.0;
+/// Distance at which bot opens a door.
+const BOT_DOOR_DISTANCE: f32 = 2.0;
+/// Distance between points of a ray that are tested against interactables.
+const RAY_STEP: f32 = 0.1;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InteractableKind {
+    Door,
+    Elevator,
+    Switch,
+}
+
+impl InteractableKind {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(InteractableKind::Door),
+            1 => Ok(InteractableKind::Elevator),
+            2 => Ok(InteractableKind::Switch),
+            _ => Err(format!("Unknown interactable kind {}", id)),
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            InteractableKind::Door => 0,
+            InteractableKind::Elevator => 1,
+            InteractableKind::Switch => 2,
+        }
+    }
+}
+
+pub struct Interactable {
+    kind: InteractableKind,
+    /// Name of mesh, switches find their targets by it.
+    name: String,
+    model: Handle<Node>,
+    /// Switches have no body.
+    body: Handle<RigidBody>,
+    half_extents: Vec3,
+    begin: Vec3,
+    end: Vec3,
+    /// Position on path, 0.0 is at begin and 1.0 is at end.
+    progress: f32,
+    /// Door is open or elevator goes up.
+    active: bool,
+    close_timer: f32,
+}
+
+impl Default for Interactable {
+    fn default() -> Self {
+        Self {
+            kind: InteractableKind::Switch,
+            name: Default::default(),
+            model: Handle::NONE,
+            body: Handle::NONE,
+            half_extents: Vec3::ZERO,
+            begin: Vec3::ZERO,
+            end: Vec3::ZERO,
+            progress: 0.0,
+            active: false,
+            close_timer: 0.0,
+        }
+    }
+}
+
+impl Interactable {
+    /// Makes interactable from given mesh of a map. `top` is end of path of elevator, it is
+    /// ignored by other kinds.
+    pub fn new(
+        kind: InteractableKind,
+        model: Handle<Node>,
+        top: Option<Vec3>,
+        scene: &mut Scene,
+    ) -> Self {
+        let node = &scene.graph[model];
+        let name = node.name().to_owned();
+        let bounds = node.as_mesh().world_bounding_box();
+        let half_extents = (bounds.max - bounds.min).scale(0.5);
+        let begin = node.global_position();
+        let end = match kind {
+            InteractableKind::Door => begin + Vec3::new(0.0, half_extents.y * 2.0, 0.0),
+            InteractableKind::Elevator => top.unwrap_or(begin),
+            InteractableKind::Switch => begin,
+        };
+
+        let body = if kind == InteractableKind::Switch {
+            Handle::NONE
+        } else {
+            let mut body = RigidBody::new(ConvexShape::Box(BoxShape::new(half_extents)));
+            body.set_position(begin);
+            let body = scene.physics.add_body(body);
+            scene.physics_binder.bind(model, body);
+            body
+        };
+
+        Self {
+            kind,
+            name,
+            model,
+            body,
+            half_extents,
+            begin,
+            end,
+            ..Default::default()
+        }
+    }
+
+    /// Name of action that is done when interactable is used, shown in key prompt.
+    pub fn action(&self) -> &'static str {
+        match self.kind {
+            InteractableKind::Door if self.active => "Close door",
+            InteractableKind::Door => "Open door",
+            InteractableKind::Elevator => "Use elevator",
+            InteractableKind::Switch => "Activate switch",
+        }
+    }
+
+    fn position(&self) -> Vec3 {
+        self.begin + (self.end - self.begin).scale(self.progress)
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        let d = point - self.position();
+        d.x.abs() <= self.half_extents.x
+            && d.y.abs() <= self.half_extents.y
+            && d.z.abs() <= self.half_extents.z
+    }
+
+    fn toggle(&mut self) {
+        self.active = !self.active;
+        self.close_timer = DOOR_OPEN_TIME;
+    }
+
+    fn update(&mut self, physics: &mut Physics, dt: f32) {
+        if self.body.is_none() {
+            return;
+        }
+
+        if self.kind == InteractableKind::Door && self.active {
+            self.close_timer -= dt;
+            if self.close_timer <= 0.0 {
+                self.active = false;
+            }
+        }
+
+        let length = self.begin.distance(&self.end);
+        if length > 0.0 {
+            let step = MOVE_SPEED * dt / length;
+            self.progress = if self.active {
+                (self.progress + step).min(1.0)
+            } else {
+                (self.progress - step).max(0.0)
+            };
+        }
+
+        let position = self.position();
+        let body = physics.borrow_body_mut(self.body);
+        body.set_position(position);
+        body.set_velocity(Vec3::ZERO);
+    }
+}
+
+impl Visit for Interactable {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind_id = self.kind.id();
+        kind_id.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = InteractableKind::from_id(kind_id)?;
+        }
+        self.name.visit("Name", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.half_extents.visit("HalfExtents", visitor)?;
+        self.begin.visit("Begin", visitor)?;
+        self.end.visit("End", visitor)?;
+        self.progress.visit("Progress", visitor)?;
+        self.active.visit("Active", visitor)?;
+        self.close_timer.visit("CloseTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct Interactables {
+    interactables: Vec<Interactable>,
+}
+
+impl Interactables {
+    pub fn add(&mut self, interactable: Interactable) {
+        self.interactables.push(interactable);
+    }
+
+    pub fn get(&self, index: usize) -> &Interactable {
+        &self.interactables[index]
+    }
+
+    /// Returns index of first interactable on a segment and distance to it from begin of the
+    /// segment.
+    pub fn find(&self, begin: Vec3, end: Vec3) -> Option<(usize, f32)> {
+        let length = begin.distance(&end);
+        let direction = (end - begin).normalized()?;
+        let mut distance = 0.0;
+        while distance <= length {
+            let point = begin + direction.scale(distance);
+            if let Some(index) = self.interactables.iter().position(|i| i.contains(point)) {
+                return Some((index, distance));
+            }
+            distance += RAY_STEP;
+        }
+        None
+    }
+
+    /// Uses interactable with given index, switch uses every interactable it is connected to.
+    pub fn interact(&mut self, index: usize) {
+        let interactable = &mut self.interactables[index];
+        if interactable.kind == InteractableKind::Switch {
+            let target = interactable.name["Switch_".len()..].to_owned();
+            for interactable in self.interactables.iter_mut() {
+                if interactable.kind != InteractableKind::Switch && interactable.name == target {
+                    interactable.toggle();
+                }
+            }
+        } else {
+            interactable.toggle();
+        }
+    }
+
+    /// Opens door or sends elevator up by name of its mesh, door closes by itself as usual.
+    pub fn open(&mut self, name: &str) {
+        for interactable in self.interactables.iter_mut() {
+            if interactable.kind != InteractableKind::Switch && interactable.name == name {
+                interactable.active = true;
+                interactable.close_timer = DOOR_OPEN_TIME;
+            }
+        }
+    }
+
+    pub fn update(&mut self, physics: &mut Physics, bot_positions: &[Vec3], dt: f32) {
+        for interactable in self.interactables.iter_mut() {
+            if interactable.kind == InteractableKind::Door
+                && bot_positions.iter().any(|position| {
+                    position.distance(&interactable.position()) <= BOT_DOOR_DISTANCE
+                })
+            {
+                interactable.active = true;
+                interactable.close_timer = DOOR_OPEN_TIME;
+            }
+            interactable.update(physics, dt);
+        }
+    }
+}
+
+impl Visit for Interactables {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.interactables.visit("Interactables", visitor)?;
+
+        visitor.leave_region()
+    }
+}
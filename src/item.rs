@@ -1,16 +1,31 @@
-use crate::{assets, effects::EffectKind, message::Message, GameTime};
+use crate::{
+    assets,
+    attachment::AttachmentKind,
+    effects::EffectKind,
+    error::{GameError, GameResult},
+    message::Message,
+    mods, GameTime,
+};
 use rg3d::{
     core::{
         math::vec3::Vec3,
-        pool::{Handle, Pool, PoolIterator, PoolPairIterator},
+        pool::{Handle, Pool, PoolIterator, PoolIteratorMut, PoolPairIterator},
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     scene::{base::BaseBuilder, graph::Graph, node::Node, transform::TransformBuilder, Scene},
 };
-use std::{path::Path, sync::mpsc::Sender};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::Sender;
+
+/// Largest pickup radius of all items, actors look for items to pick up within this radius.
+pub const MAX_PICKUP_RADIUS: f32 = 1.75;
+/// Magnetic items slide toward a moving player within this radius.
+const MAGNET_RADIUS: f32 = 3.0;
+/// Speed of magnetic items in m/s, slow enough to look like a nudge.
+const MAGNET_SPEED: f32 = 1.5;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ItemKind {
     Medkit,
 
@@ -24,10 +39,19 @@ pub enum ItemKind {
     Ak47,
     M4,
     RocketLauncher,
+
+    // Weapon attachments
+    Silencer,
+    ExtendedMag,
+    Scope,
+    LaserSight,
+
+    // Power-ups
+    Overshield,
 }
 
 impl ItemKind {
-    fn from_id(id: u32) -> Result<ItemKind, String> {
+    pub fn from_id(id: u32) -> Result<ItemKind, String> {
         match id {
             0 => Ok(ItemKind::Medkit),
             1 => Ok(ItemKind::Plasma),
@@ -37,10 +61,52 @@ impl ItemKind {
             5 => Ok(ItemKind::Ak47),
             6 => Ok(ItemKind::M4),
             7 => Ok(ItemKind::RocketLauncher),
+            8 => Ok(ItemKind::Silencer),
+            9 => Ok(ItemKind::ExtendedMag),
+            10 => Ok(ItemKind::Scope),
+            11 => Ok(ItemKind::LaserSight),
+            12 => Ok(ItemKind::Overshield),
             _ => Err(format!("Unknown item kind {}", id)),
         }
     }
 
+    /// Attachment that is mounted on current weapon of an actor when item is picked up.
+    pub fn attachment(self) -> Option<AttachmentKind> {
+        match self {
+            ItemKind::Silencer => Some(AttachmentKind::Silencer),
+            ItemKind::ExtendedMag => Some(AttachmentKind::ExtendedMag),
+            ItemKind::Scope => Some(AttachmentKind::Scope),
+            ItemKind::LaserSight => Some(AttachmentKind::LaserSight),
+            _ => None,
+        }
+    }
+
+    /// Weapons lying around are picked up by player with interact button, not by touch.
+    pub fn is_weapon(self) -> bool {
+        matches!(
+            self,
+            ItemKind::PlasmaGun | ItemKind::Ak47 | ItemKind::M4 | ItemKind::RocketLauncher
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Medkit => "Medkit",
+            ItemKind::Plasma => "Plasma Cells",
+            ItemKind::Ak47Ammo => "AK-47 Ammo",
+            ItemKind::M4Ammo => "M4 Ammo",
+            ItemKind::PlasmaGun => "Plasma Rifle",
+            ItemKind::Ak47 => "AK-47",
+            ItemKind::M4 => "M4",
+            ItemKind::RocketLauncher => "Rocket Launcher",
+            ItemKind::Silencer => "Silencer",
+            ItemKind::ExtendedMag => "Extended Mag",
+            ItemKind::Scope => "Scope",
+            ItemKind::LaserSight => "Laser Sight",
+            ItemKind::Overshield => "Overshield",
+        }
+    }
+
     fn id(self) -> u32 {
         match self {
             ItemKind::Medkit => 0,
@@ -51,6 +117,11 @@ impl ItemKind {
             ItemKind::Ak47 => 5,
             ItemKind::M4 => 6,
             ItemKind::RocketLauncher => 7,
+            ItemKind::Silencer => 8,
+            ItemKind::ExtendedMag => 9,
+            ItemKind::Scope => 10,
+            ItemKind::LaserSight => 11,
+            ItemKind::Overshield => 12,
         }
     }
 }
@@ -65,8 +136,10 @@ pub struct Item {
     reactivation_timer: f32,
     active: bool,
     definition: &'static ItemDefinition,
-    sender: Option<Sender<Message>>,
+    pub sender: Option<Sender<Message>>,
     lifetime: Option<f32>,
+    /// Position where item was placed, magnetic item returns there when it is picked up.
+    home: Vec3,
 }
 
 impl Default for Item {
@@ -83,6 +156,7 @@ impl Default for Item {
             definition: Self::get_definition(ItemKind::Medkit),
             sender: None,
             lifetime: None,
+            home: Default::default(),
         }
     }
 }
@@ -91,6 +165,17 @@ pub struct ItemDefinition {
     model: &'static str,
     scale: f32,
     reactivation_interval: f32,
+    /// Actor picks item up when it is closer than this.
+    pickup_radius: f32,
+    /// Item slides toward a nearby moving player when pickup magnetism is on.
+    magnetic: bool,
+}
+
+impl ItemDefinition {
+    /// Paths of every file used by item.
+    pub fn assets(&self) -> Vec<&'static str> {
+        vec![self.model]
+    }
 }
 
 impl Item {
@@ -101,6 +186,8 @@ impl Item {
                     model: assets::models::items::MEDKIT,
                     scale: 1.0,
                     reactivation_interval: 20.0,
+                    pickup_radius: 1.25,
+                    magnetic: true,
                 };
                 &DEFINITION
             }
@@ -109,6 +196,8 @@ impl Item {
                     model: assets::models::items::PLASMA_RIFLE_AMMO,
                     scale: 0.25,
                     reactivation_interval: 15.0,
+                    pickup_radius: 1.25,
+                    magnetic: true,
                 };
                 &DEFINITION
             }
@@ -117,6 +206,8 @@ impl Item {
                     model: assets::models::items::AK47_AMMO,
                     scale: 0.30,
                     reactivation_interval: 14.0,
+                    pickup_radius: 1.25,
+                    magnetic: true,
                 };
                 &DEFINITION
             }
@@ -125,6 +216,8 @@ impl Item {
                     model: assets::models::items::M4_AMMO,
                     scale: 0.30,
                     reactivation_interval: 13.0,
+                    pickup_radius: 1.25,
+                    magnetic: true,
                 };
                 &DEFINITION
             }
@@ -133,6 +226,8 @@ impl Item {
                     model: assets::models::weapons::PLASMA_RIFLE,
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    pickup_radius: 1.25,
+                    magnetic: false,
                 };
                 &DEFINITION
             }
@@ -141,6 +236,8 @@ impl Item {
                     model: assets::models::weapons::AK47,
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    pickup_radius: 1.25,
+                    magnetic: false,
                 };
                 &DEFINITION
             }
@@ -149,6 +246,8 @@ impl Item {
                     model: assets::models::weapons::M4,
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    pickup_radius: 1.25,
+                    magnetic: false,
                 };
                 &DEFINITION
             }
@@ -157,6 +256,28 @@ impl Item {
                     model: assets::models::weapons::ROCKET_LAUNCHER,
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    pickup_radius: 1.25,
+                    magnetic: false,
+                };
+                &DEFINITION
+            }
+            ItemKind::Silencer | ItemKind::ExtendedMag | ItemKind::Scope | ItemKind::LaserSight => {
+                static DEFINITION: ItemDefinition = ItemDefinition {
+                    model: assets::models::items::ATTACHMENT,
+                    scale: 0.25,
+                    reactivation_interval: 45.0,
+                    pickup_radius: 1.0,
+                    magnetic: false,
+                };
+                &DEFINITION
+            }
+            ItemKind::Overshield => {
+                static DEFINITION: ItemDefinition = ItemDefinition {
+                    model: assets::models::items::OVERSHIELD,
+                    scale: 0.4,
+                    reactivation_interval: 60.0,
+                    pickup_radius: 1.75,
+                    magnetic: false,
                 };
                 &DEFINITION
             }
@@ -169,13 +290,13 @@ impl Item {
         scene: &mut Scene,
         resource_manager: ResourceManager,
         sender: Sender<Message>,
-    ) -> Self {
+    ) -> GameResult<Self> {
         let definition = Self::get_definition(kind);
 
         let model = resource_manager
-            .request_model(Path::new(definition.model))
+            .request_model(mods::resolve(definition.model))
             .await
-            .unwrap()
+            .map_err(|_| GameError::missing_asset(definition.model))?
             .instantiate_geometry(scene);
 
         let pivot = scene.graph.add_node(Node::Base(
@@ -195,30 +316,36 @@ impl Item {
 
         scene.graph.link_nodes(model, pivot);
 
-        Self {
+        Ok(Self {
             pivot,
             kind,
             model,
             sender: Some(sender),
+            home: position,
             ..Default::default()
-        }
-    }
-
-    pub fn get_pivot(&self) -> Handle<Node> {
-        self.pivot
+        })
     }
 
     pub fn position(&self, graph: &Graph) -> Vec3 {
         graph[self.pivot].global_position()
     }
 
-    pub fn update(&mut self, graph: &mut Graph, time: GameTime) {
+    pub fn pickup_radius(&self) -> f32 {
+        self.definition.pickup_radius
+    }
+
+    /// `magnet` is position of a moving player that attracts magnetic items.
+    pub fn update(&mut self, graph: &mut Graph, time: GameTime, magnet: Option<Vec3>) {
         self.offset_factor += 1.2 * time.delta;
 
         let amp = 0.085;
         self.dest_offset = Vec3::new(0.0, amp + amp * self.offset_factor.sin(), 0.0);
         self.offset.follow(&self.dest_offset, 0.2);
 
+        if self.definition.magnetic {
+            self.attract(graph, time.delta, magnet);
+        }
+
         let position = graph[self.pivot].global_position();
 
         graph[self.model]
@@ -243,6 +370,25 @@ impl Item {
         }
     }
 
+    fn attract(&mut self, graph: &mut Graph, dt: f32, magnet: Option<Vec3>) {
+        let pivot = &mut graph[self.pivot];
+        let position = pivot.global_position();
+        if self.active {
+            if let Some(magnet) = magnet {
+                if let Some(direction) = (magnet - position).normalized() {
+                    if magnet.distance(&position) <= MAGNET_RADIUS {
+                        pivot
+                            .local_transform_mut()
+                            .set_position(position + direction.scale(MAGNET_SPEED * dt));
+                    }
+                }
+            }
+        } else if position != self.home {
+            // Respawns at its original place.
+            pivot.local_transform_mut().set_position(self.home);
+        }
+    }
+
     pub fn get_kind(&self) -> ItemKind {
         self.kind
     }
@@ -256,6 +402,24 @@ impl Item {
         !self.active
     }
 
+    /// Seconds until picked up item respawns, `None` if it is on its place.
+    pub fn respawn_time_left(&self) -> Option<f32> {
+        if self.active {
+            None
+        } else {
+            Some(self.reactivation_timer)
+        }
+    }
+
+    pub fn respawn_interval(&self) -> f32 {
+        self.definition.reactivation_interval
+    }
+
+    /// Temporary items are dropped by actors and despawn instead of respawning.
+    pub fn is_temporary(&self) -> bool {
+        self.lifetime.is_some()
+    }
+
     fn cleanup(&self, graph: &mut Graph) {
         graph.remove_node(self.pivot)
     }
@@ -292,6 +456,7 @@ impl Visit for Item {
             .visit("ReactivationTimer", visitor)?;
         self.active.visit("Active", visitor)?;
         self.lifetime.visit("Lifetime", visitor)?;
+        self.home.visit("Home", visitor)?;
 
         visitor.leave_region()
     }
@@ -326,6 +491,10 @@ impl ItemContainer {
         self.pool.spawn(item)
     }
 
+    pub fn get(&self, item: Handle<Item>) -> &Item {
+        self.pool.borrow(item)
+    }
+
     pub fn get_mut(&mut self, item: Handle<Item>) -> &mut Item {
         self.pool.borrow_mut(item)
     }
@@ -342,9 +511,13 @@ impl ItemContainer {
         self.pool.iter()
     }
 
-    pub fn update(&mut self, scene: &mut Scene, time: GameTime) {
+    pub fn iter_mut(&mut self) -> PoolIteratorMut<Item> {
+        self.pool.iter_mut()
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, time: GameTime, magnet: Option<Vec3>) {
         for item in self.pool.iter_mut() {
-            item.update(&mut scene.graph, time);
+            item.update(&mut scene.graph, time, magnet);
         }
 
         // Remove temporary items.
@@ -355,4 +528,118 @@ impl ItemContainer {
         }
         self.pool.retain(|i| !i.can_be_removed())
     }
+
+    /// Removes temporary items and puts picked up items back to their places at once.
+    pub fn reset(&mut self, scene: &mut Scene) {
+        for item in self.pool.iter() {
+            if item.is_temporary() {
+                item.cleanup(&mut scene.graph);
+            }
+        }
+        self.pool.retain(|i| !i.is_temporary());
+        for item in self.pool.iter_mut() {
+            item.active = true;
+            item.reactivation_timer = 0.0;
+            scene.graph[item.pivot]
+                .local_transform_mut()
+                .set_position(item.home);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{visit_test, FIXED_FPS};
+    use std::{sync::mpsc, time};
+
+    #[test]
+    fn picked_up_items_survive_save_and_load() {
+        let mut items = ItemContainer::new();
+        items.add(Item {
+            kind: ItemKind::Overshield,
+            reactivation_timer: 12.5,
+            active: false,
+            home: Vec3::new(4.0, 0.0, -2.0),
+            ..Default::default()
+        });
+        items.add(Item {
+            kind: ItemKind::M4Ammo,
+            lifetime: Some(30.0),
+            ..Default::default()
+        });
+
+        let loaded = visit_test::round_trip("Items", &mut items);
+
+        let loaded = loaded.iter().collect::<Vec<_>>();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].kind, ItemKind::Overshield);
+        assert_eq!(loaded[0].reactivation_timer, 12.5);
+        assert!(!loaded[0].active);
+        assert_eq!(loaded[0].home, Vec3::new(4.0, 0.0, -2.0));
+        assert_eq!(loaded[1].kind, ItemKind::M4Ammo);
+        assert_eq!(loaded[1].lifetime, Some(30.0));
+        assert!(loaded[1].active);
+    }
+
+    #[test]
+    fn picked_up_item_respawns_after_its_interval() {
+        let mut scene = Scene::new();
+        let (sender, receiver) = mpsc::channel();
+        let mut items = ItemContainer::new();
+        let item = items.add(Item {
+            kind: ItemKind::Medkit,
+            pivot: scene.graph.add_node(Node::Base(Default::default())),
+            model: scene.graph.add_node(Node::Base(Default::default())),
+            sender: Some(sender),
+            ..Default::default()
+        });
+        items.get_mut(item).pick_up();
+
+        let interval = items.get(item).respawn_interval();
+        let mut time = GameTime {
+            clock: time::Instant::now(),
+            elapsed: 0.0,
+            delta: 1.0 / FIXED_FPS,
+        };
+        let mut steps = 0;
+        while items.get(item).is_picked_up() {
+            items.update(&mut scene, time, None);
+            time.elapsed += time.delta as f64;
+            steps += 1;
+            assert!(
+                time.elapsed <= interval as f64 + 1.0,
+                "item never respawned"
+            );
+        }
+
+        // Respawns within one tick of its interval.
+        assert!((steps as f32 / FIXED_FPS - interval).abs() <= 1.0 / FIXED_FPS);
+        assert!(items.get(item).respawn_time_left().is_none());
+        match receiver.try_recv() {
+            Ok(Message::CreateEffect { kind, .. }) => assert_eq!(kind, EffectKind::ItemAppear),
+            _ => panic!("respawn effect was not requested"),
+        }
+    }
+
+    #[test]
+    fn dropped_item_is_removed_when_picked_up() {
+        let mut scene = Scene::new();
+        let mut items = ItemContainer::new();
+        let item = items.add(Item {
+            pivot: scene.graph.add_node(Node::Base(Default::default())),
+            model: scene.graph.add_node(Node::Base(Default::default())),
+            lifetime: Some(30.0),
+            ..Default::default()
+        });
+        items.get_mut(item).pick_up();
+
+        let time = GameTime {
+            clock: time::Instant::now(),
+            elapsed: 0.0,
+            delta: 1.0 / FIXED_FPS,
+        };
+        items.update(&mut scene, time, None);
+        assert!(!items.contains(item));
+    }
 }
@@ -24,6 +24,13 @@ impl JumpPad {
     pub fn get_force(&self) -> Vec3 {
         self.force
     }
+
+    /// Force of a pad that throws actors from `begin` towards `end`, longer pads throw
+    /// further.
+    pub fn force_between(begin: Vec3, end: Vec3) -> Vec3 {
+        let (force, len) = (end - begin).normalized_ex();
+        force.unwrap_or(Vec3::UP).scale(len / 20.0)
+    }
 }
 
 impl Default for JumpPad {
@@ -0,0 +1,35 @@
+//! Kill streak rewards mutator. Actor that kills 3, 5 or 7 enemies without dying gets a reward:
+//! haste, radar ping that reveals closest enemies or refill of ammo of all its weapons. Streaks
+//! are counted by leader board, so they survive save and load.
+
+/// Duration of haste in seconds.
+pub const HASTE_TIME: f32 = 10.0;
+/// How many closest enemies are revealed by radar ping.
+pub const RADAR_PING_COUNT: usize = 3;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StreakReward {
+    Haste,
+    RadarPing,
+    AmmoRefill,
+}
+
+impl StreakReward {
+    /// Returns reward for given amount of kills in a row, if there is one.
+    pub fn for_streak(streak: u32) -> Option<Self> {
+        match streak {
+            3 => Some(StreakReward::Haste),
+            5 => Some(StreakReward::RadarPing),
+            7 => Some(StreakReward::AmmoRefill),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StreakReward::Haste => "Haste",
+            StreakReward::RadarPing => "Radar Ping",
+            StreakReward::AmmoRefill => "Ammo Refill",
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Hill of king of the hill mode. Hill is a square zone that moves between points of a map
+//! every `MOVE_INTERVAL` seconds, points are nodes of the map named `Hill_*` in order of their
+//! names, maps without them use spawn points. Actor that is alone on the hill gets a point
+//! for every second there, hill with two or more actors on it is contested and nobody scores.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    assets,
+    leader_board::LeaderBoard,
+    message::Message,
+};
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{node::Node, Scene},
+};
+use std::sync::mpsc::Sender;
+
+/// Time in seconds between two moves of the hill.
+const MOVE_INTERVAL: f32 = 60.0;
+/// Move of the hill is announced this many seconds ahead.
+const MOVE_WARNING: f32 = 10.0;
+/// Half of width of the hill.
+pub const HALF_SIZE: f32 = 3.0;
+/// Actor is on the hill while it is this high above its center at most.
+const HEIGHT: f32 = 3.0;
+/// Time in seconds HUD shows callouts of the hill.
+const CALLOUT_DURATION: f32 = 3.0;
+
+/// Who is on the hill.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HillState {
+    Empty,
+    Held(Handle<Actor>),
+    Contested,
+}
+
+impl Default for HillState {
+    fn default() -> Self {
+        HillState::Empty
+    }
+}
+
+impl Visit for HillState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id: u32 = match self {
+            HillState::Empty => 0,
+            HillState::Held(_) => 1,
+            HillState::Contested => 2,
+        };
+        id.visit("Id", visitor)?;
+        let mut holder = match self {
+            HillState::Held(holder) => *holder,
+            _ => Handle::NONE,
+        };
+        holder.visit("Holder", visitor)?;
+        if visitor.is_reading() {
+            *self = match id {
+                0 => HillState::Empty,
+                1 => HillState::Held(holder),
+                2 => HillState::Contested,
+                _ => return Err(format!("Invalid hill state {}", id).into()),
+            };
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct Hill {
+    /// Centers of the hill in order of moves.
+    points: Vec<Vec3>,
+    current: u32,
+    /// Mesh that marks the hill.
+    model: Handle<Node>,
+    move_timer: f32,
+    state: HillState,
+    /// Part of a second that holder spent on the hill and which isn't in leader board yet.
+    hold_timer: f32,
+}
+
+impl Hill {
+    /// Makes hill that starts at first point, `points` must not be empty.
+    pub fn new(points: Vec<Vec3>, model: Handle<Node>, scene: &mut Scene) -> Self {
+        let hill = Self {
+            points,
+            model,
+            move_timer: MOVE_INTERVAL,
+            ..Default::default()
+        };
+        hill.sync_model(scene);
+        hill
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.points[self.current as usize]
+    }
+
+    pub fn state(&self) -> HillState {
+        self.state
+    }
+
+    /// Seconds until the hill moves.
+    pub fn time_to_move(&self) -> f32 {
+        self.move_timer
+    }
+
+    pub fn is_holder(&self, actor: Handle<Actor>) -> bool {
+        self.state == HillState::Held(actor)
+    }
+
+    /// Everyone goes to the hill, to hold it or to push others off it.
+    pub fn objective_point(&self) -> Option<Vec3> {
+        Some(self.position())
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        let center = self.position();
+        (point.x - center.x).abs() <= HALF_SIZE
+            && (point.z - center.z).abs() <= HALF_SIZE
+            && point.y >= center.y - 0.5
+            && point.y <= center.y + HEIGHT
+    }
+
+    fn sync_model(&self, scene: &mut Scene) {
+        scene.graph[self.model]
+            .local_transform_mut()
+            .set_position(self.position());
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+        leader_board: &mut LeaderBoard,
+        sender: &Sender<Message>,
+        dt: f32,
+    ) {
+        let announce = |text: &str, sound: &str| {
+            sender
+                .send(Message::Announce {
+                    text: text.to_owned(),
+                    sound: assets::sounds::announcer::path(sound),
+                    duration: CALLOUT_DURATION,
+                })
+                .unwrap();
+        };
+
+        let previous_timer = self.move_timer;
+        self.move_timer -= dt;
+        if previous_timer > MOVE_WARNING && self.move_timer <= MOVE_WARNING {
+            announce("Hill moves in 10 seconds", "hill_moving");
+        }
+        if self.move_timer <= 0.0 {
+            self.move_timer = MOVE_INTERVAL;
+            self.current = (self.current + 1) % self.points.len() as u32;
+            self.state = HillState::Empty;
+            self.hold_timer = 0.0;
+            self.sync_model(scene);
+            announce("The hill has moved!", "hill_moved");
+            return;
+        }
+
+        let mut occupants = actors
+            .pair_iter()
+            .filter(|(_, actor)| !actor.is_dead() && self.contains(actor.position(&scene.physics)));
+        let state = match (occupants.next(), occupants.next()) {
+            (None, _) => HillState::Empty,
+            (Some((handle, _)), None) => HillState::Held(handle),
+            (Some(_), Some(_)) => HillState::Contested,
+        };
+        if state != self.state {
+            self.hold_timer = 0.0;
+            match state {
+                HillState::Held(holder) => {
+                    let text = format!("{} controls the hill!", actors.get(holder).name);
+                    sender.send(Message::AddNotification { text }).unwrap();
+                }
+                HillState::Contested => announce("Hill contested!", "hill_contested"),
+                HillState::Empty => (),
+            }
+            self.state = state;
+        }
+
+        if let HillState::Held(holder) = self.state {
+            self.hold_timer += dt;
+            let seconds = self.hold_timer as u32;
+            if seconds > 0 {
+                self.hold_timer -= seconds as f32;
+                leader_board.add_hold_time(&actors.get(holder).name, seconds);
+            }
+        }
+    }
+}
+
+impl Visit for Hill {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.points.visit("Points", visitor)?;
+        self.current.visit("Current", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.move_timer.visit("MoveTimer", visitor)?;
+        self.state.visit("State", visitor)?;
+        self.hold_timer.visit("HoldTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,26 +1,96 @@
-use crate::{character::Team, message::Message, GameEngine, Gui, MatchOptions, UINodeHandle};
+use crate::{
+    character::Team, message::Message, session::SessionStandings, weapon::WeaponKind, BuildContext,
+    GameEngine, Gui, MatchOptions, UINodeHandle,
+};
 use rg3d::gui::message::MessageDirection;
 use rg3d::{
     core::{
         color::Color,
+        pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     gui::{
         brush::Brush,
         grid::{Column, GridBuilder, Row},
-        message::WidgetMessage,
+        message::{TextMessage, WidgetMessage},
         text::TextBuilder,
         widget::WidgetBuilder,
         HorizontalAlignment, Thickness, VerticalAlignment,
     },
 };
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// Shots fired with a weapon and how many of them hit an actor. Projectile counts as one hit
+/// no matter how many actors it hit, splash damage isn't a hit.
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Accuracy {
+    pub shots: u32,
+    pub hits: u32,
+}
+
+impl Accuracy {
+    pub fn percent(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            100.0 * self.hits as f32 / self.shots as f32
+        }
+    }
+}
+
+impl Visit for Accuracy {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.shots.visit("Shots", visitor)?;
+        self.hits.visit("Hits", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Formats accuracy like "M4 34% (120/350), Rocket Launcher 50% (4/8)".
+pub fn accuracy_text(accuracy: &[(WeaponKind, Accuracy)]) -> String {
+    accuracy
+        .iter()
+        .map(|(weapon, accuracy)| {
+            format!(
+                "{} {:.0}% ({}/{})",
+                weapon.name(),
+                accuracy.percent(),
+                accuracy.hits,
+                accuracy.shots
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
 #[derive(Copy, Clone)]
 pub struct PersonalScore {
     pub kills: u32,
     pub deaths: u32,
+    pub assists: u32,
+    pub damage_dealt: f32,
+    /// Kills since last death.
+    pub streak: u32,
+    pub best_streak: u32,
+    /// Seconds of holding the skull in oddball or the hill in king of the hill.
+    pub hold_time: u32,
+    /// Rounds won in duel.
+    pub rounds_won: u32,
+    /// Order in which actor was eliminated in last man standing, starting from one. Zero if
+    /// actor is still in the match.
+    pub eliminated: u32,
+    /// Team of actor at its last spawn, used to tell when a whole team is eliminated.
+    pub team: Team,
+    /// Indexed by id of weapon kind.
+    pub accuracy: [Accuracy; WeaponKind::ALL.len()],
 }
 
 impl Default for PersonalScore {
@@ -28,6 +98,31 @@ impl Default for PersonalScore {
         Self {
             kills: 0,
             deaths: 0,
+            assists: 0,
+            damage_dealt: 0.0,
+            streak: 0,
+            best_streak: 0,
+            hold_time: 0,
+            rounds_won: 0,
+            eliminated: 0,
+            team: Team::None,
+            accuracy: Default::default(),
+        }
+    }
+}
+
+impl PersonalScore {
+    /// Score that ranks actor in given mode - hold time in oddball and king of the hill,
+    /// rounds won in duel, lives left in last man standing, frags otherwise.
+    pub fn points(&self, options: &MatchOptions) -> u32 {
+        if options.lives() > 0 {
+            options.lives().saturating_sub(self.deaths)
+        } else if options.scores_hold_time() {
+            self.hold_time
+        } else if options.is_duel() {
+            self.rounds_won
+        } else {
+            self.kills
         }
     }
 }
@@ -38,11 +133,78 @@ impl Visit for PersonalScore {
 
         self.kills.visit("Kills", visitor)?;
         self.deaths.visit("Deaths", visitor)?;
+        self.assists.visit("Assists", visitor)?;
+        self.damage_dealt.visit("DamageDealt", visitor)?;
+        self.streak.visit("Streak", visitor)?;
+        self.best_streak.visit("BestStreak", visitor)?;
+        self.hold_time.visit("HoldTime", visitor)?;
+        self.rounds_won.visit("RoundsWon", visitor)?;
+        self.eliminated.visit("Eliminated", visitor)?;
+        self.team.visit("Team", visitor)?;
+
+        visitor.enter_region("Accuracy")?;
+        for kind in WeaponKind::ALL.iter() {
+            self.accuracy[kind.id() as usize].visit(kind.name(), visitor)?;
+        }
+        visitor.leave_region()?;
 
         visitor.leave_region()
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum MatchWinner {
+    Actor { name: String, score: u32 },
+    Team { team: Team, score: u32 },
+    Draw,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatchEndReason {
+    TimeLimit,
+    ScoreLimit,
+    /// Match was ended by logic of the map, see `logic` module.
+    MapEvent,
+    /// Everyone else was eliminated in last man standing.
+    LastStanding,
+}
+
+/// Result of a match, announced when match is over.
+#[derive(Debug, Clone)]
+pub struct MatchSummary {
+    pub winner: MatchWinner,
+    pub reason: MatchEndReason,
+    /// Name of the actor who dealt most damage during the match and amount of the damage.
+    pub top_damage: Option<(String, f32)>,
+    /// Accuracy of player with every weapon it fired during the match.
+    pub accuracy: Vec<(WeaponKind, Accuracy)>,
+}
+
+impl Display for MatchSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.winner {
+            MatchWinner::Actor { name, score } => write!(f, "{} wins with score {}", name, score)?,
+            MatchWinner::Team { team, score } => {
+                write!(f, "{:?} team wins with score {}", team, score)?
+            }
+            MatchWinner::Draw => write!(f, "Draw")?,
+        }
+        match self.reason {
+            MatchEndReason::TimeLimit => write!(f, " - time limit reached")?,
+            MatchEndReason::ScoreLimit => write!(f, " - score limit reached")?,
+            MatchEndReason::MapEvent => write!(f, " - ended by the map")?,
+            MatchEndReason::LastStanding => write!(f, " - last one standing")?,
+        }
+        if let Some((name, damage)) = &self.top_damage {
+            write!(f, ". Most damage dealt by {} ({:.0})", name, damage)?;
+        }
+        if !self.accuracy.is_empty() {
+            write!(f, ". Your accuracy: {}", accuracy_text(&self.accuracy))?;
+        }
+        Ok(())
+    }
+}
+
 pub struct LeaderBoard {
     personal_score: HashMap<String, PersonalScore>,
     team_score: HashMap<Team, u32>,
@@ -55,12 +217,122 @@ impl LeaderBoard {
             .or_insert_with(Default::default)
     }
 
-    pub fn add_frag<P: AsRef<str>>(&mut self, actor_name: P) {
-        self.get_or_add_actor(actor_name).kills += 1;
+    /// Removes row of actor that left the match.
+    pub fn remove_actor<P: AsRef<str>>(&mut self, actor_name: P) {
+        self.personal_score.remove(actor_name.as_ref());
+    }
+
+    pub fn contains<P: AsRef<str>>(&self, actor_name: P) -> bool {
+        self.personal_score.contains_key(actor_name.as_ref())
+    }
+
+    /// Adds frag and returns amount of kills of the actor since its last death.
+    pub fn add_frag<P: AsRef<str>>(&mut self, actor_name: P) -> u32 {
+        let score = self.get_or_add_actor(actor_name);
+        score.kills += 1;
+        score.streak += 1;
+        score.best_streak = score.best_streak.max(score.streak);
+        score.streak
     }
 
     pub fn add_death<P: AsRef<str>>(&mut self, actor_name: P) {
-        self.get_or_add_actor(actor_name).deaths += 1;
+        let score = self.get_or_add_actor(actor_name);
+        score.deaths += 1;
+        score.streak = 0;
+    }
+
+    pub fn add_assist<P: AsRef<str>>(&mut self, actor_name: P) {
+        self.get_or_add_actor(actor_name).assists += 1;
+    }
+
+    pub fn add_damage<P: AsRef<str>>(&mut self, actor_name: P, amount: f32) {
+        self.get_or_add_actor(actor_name).damage_dealt += amount;
+    }
+
+    pub fn add_shot<P: AsRef<str>>(&mut self, actor_name: P, weapon: WeaponKind) {
+        self.get_or_add_actor(actor_name).accuracy[weapon.id() as usize].shots += 1;
+    }
+
+    pub fn add_hit<P: AsRef<str>>(&mut self, actor_name: P, weapon: WeaponKind) {
+        self.get_or_add_actor(actor_name).accuracy[weapon.id() as usize].hits += 1;
+    }
+
+    /// Returns accuracy of the actor with every weapon it fired at least once.
+    pub fn accuracy<P: AsRef<str>>(&self, actor_name: P) -> Vec<(WeaponKind, Accuracy)> {
+        match self.personal_score.get(actor_name.as_ref()) {
+            Some(score) => WeaponKind::ALL
+                .iter()
+                .map(|&kind| (kind, score.accuracy[kind.id() as usize]))
+                .filter(|(_, accuracy)| accuracy.shots > 0)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns name of the actor who dealt most damage and amount of that damage.
+    fn top_damage(&self) -> Option<(String, f32)> {
+        self.personal_score
+            .iter()
+            .filter(|(_, score)| score.damage_dealt > 0.0)
+            .max_by(|(_, a), (_, b)| a.damage_dealt.partial_cmp(&b.damage_dealt).unwrap())
+            .map(|(name, score)| (name.clone(), score.damage_dealt))
+    }
+
+    /// Adds (or removes if amount is negative) frags, score can't go below zero.
+    pub fn add_score<P: AsRef<str>>(&mut self, actor_name: P, amount: i32) {
+        let score = self.get_or_add_actor(actor_name);
+        score.kills = (score.kills as i64 + amount as i64).max(0) as u32;
+    }
+
+    pub fn add_hold_time<P: AsRef<str>>(&mut self, actor_name: P, seconds: u32) {
+        self.get_or_add_actor(actor_name).hold_time += seconds;
+    }
+
+    pub fn add_round_win<P: AsRef<str>>(&mut self, actor_name: P) {
+        self.get_or_add_actor(actor_name).rounds_won += 1;
+    }
+
+    /// Marks actor as eliminated, it is placed below everyone who is still in the match.
+    pub fn eliminate<P: AsRef<str>>(&mut self, actor_name: P) {
+        let order = self.eliminated_count() + 1;
+        self.get_or_add_actor(actor_name).eliminated = order;
+    }
+
+    pub fn is_eliminated<P: AsRef<str>>(&self, actor_name: P) -> bool {
+        self.personal_score
+            .get(actor_name.as_ref())
+            .map_or(false, |score| score.eliminated > 0)
+    }
+
+    fn eliminated_count(&self) -> u32 {
+        self.personal_score
+            .values()
+            .filter(|score| score.eliminated > 0)
+            .count() as u32
+    }
+
+    /// Amount of members of a team that are still in the match.
+    pub fn team_remaining(&self, team: Team) -> usize {
+        self.personal_score
+            .values()
+            .filter(|score| score.team == team && score.eliminated == 0)
+            .count()
+    }
+
+    /// Rows of leader board from first place to last. Actors that are still in the match go
+    /// first, eliminated ones follow in reverse order of elimination.
+    pub fn standings(&self, options: &MatchOptions) -> Vec<(&String, &PersonalScore)> {
+        let mut standings = self.personal_score.iter().collect::<Vec<_>>();
+        standings.sort_by(|(a_name, a), (b_name, b)| {
+            (b.eliminated == 0, b.eliminated, b.points(options))
+                .cmp(&(a.eliminated == 0, a.eliminated, a.points(options)))
+                .then_with(|| a_name.cmp(b_name))
+        });
+        standings
+    }
+
+    pub fn add_team_score(&mut self, team: Team, amount: u32) {
+        *self.team_score.entry(team).or_insert(0) += amount;
     }
 
     pub fn team_score(&self, team: Team) -> u32 {
@@ -70,10 +342,28 @@ impl LeaderBoard {
         }
     }
 
-    /// Returns record about leader as a pair of character name and its score.
+    /// Score that ranks a team in given mode - sum of lives left of its members in last man
+    /// standing, team score otherwise.
+    pub fn team_points(&self, options: &MatchOptions, team: Team) -> u32 {
+        if options.lives() > 0 {
+            self.personal_score
+                .values()
+                .filter(|score| score.team == team)
+                .map(|score| score.points(options))
+                .sum()
+        } else {
+            self.team_score(team)
+        }
+    }
+
+    /// Returns record about leader as a pair of character name and its score in given mode.
     /// `except` parameter can be used to exclude already found leader and search
     /// for a character at second place.
-    pub fn highest_personal_score(&self, except: Option<&str>) -> Option<(&str, u32)> {
+    pub fn highest_personal_score(
+        &self,
+        options: &MatchOptions,
+        except: Option<&str>,
+    ) -> Option<(&str, u32)> {
         let mut pair = None;
 
         for (name, score) in self.personal_score.iter() {
@@ -82,11 +372,12 @@ impl LeaderBoard {
                     continue;
                 }
             }
+            let points = score.points(options);
             match pair {
-                None => pair = Some((name.as_str(), score.kills)),
+                None => pair = Some((name.as_str(), points)),
                 Some(ref mut pair) => {
-                    if score.kills > pair.1 {
-                        *pair = (name.as_str(), score.kills)
+                    if points > pair.1 {
+                        *pair = (name.as_str(), points)
                     }
                 }
             }
@@ -99,33 +390,95 @@ impl LeaderBoard {
         &self.personal_score
     }
 
-    pub fn is_match_over(&self, options: &MatchOptions) -> bool {
-        match options {
-            MatchOptions::DeathMatch(dm) => {
-                if let Some((_, highest_score)) = self.highest_personal_score(None) {
-                    highest_score >= dm.frag_limit
-                } else {
-                    false
-                }
-            }
-            MatchOptions::TeamDeathMatch(tdm) => {
-                for team_score in self.team_score.values() {
-                    if *team_score >= tdm.team_frag_limit {
-                        return true;
-                    }
-                }
-                false
+    /// Returns current leader and its score, leader is `Draw` if first place is shared.
+    fn leader(&self, options: &MatchOptions) -> (MatchWinner, u32) {
+        if options.is_team_based() {
+            let red = self.team_points(options, Team::Red);
+            let blue = self.team_points(options, Team::Blue);
+            if red > blue {
+                (
+                    MatchWinner::Team {
+                        team: Team::Red,
+                        score: red,
+                    },
+                    red,
+                )
+            } else if blue > red {
+                (
+                    MatchWinner::Team {
+                        team: Team::Blue,
+                        score: blue,
+                    },
+                    blue,
+                )
+            } else {
+                (MatchWinner::Draw, red)
             }
-            MatchOptions::CaptureTheFlag(ctf) => {
-                for team_score in self.team_score.values() {
-                    if *team_score >= ctf.flag_limit {
-                        return true;
+        } else {
+            match self.highest_personal_score(options, None) {
+                Some((name, score)) => {
+                    let tie = self
+                        .highest_personal_score(options, Some(name))
+                        .map_or(false, |(_, second)| second == score);
+                    if tie {
+                        (MatchWinner::Draw, score)
+                    } else {
+                        (
+                            MatchWinner::Actor {
+                                name: name.to_owned(),
+                                score,
+                            },
+                            score,
+                        )
                     }
                 }
-                false
+                None => (MatchWinner::Draw, 0),
             }
         }
     }
+
+    /// Checks end conditions of a match, `elapsed` is time since start of the match in
+    /// seconds. Returns summary if match is over. Limits that are zero are disabled.
+    pub fn evaluate(&self, options: &MatchOptions, elapsed: f32) -> Option<MatchSummary> {
+        let (_, score) = self.leader(options);
+
+        let score_limit = options.score_limit();
+        let time_limit = options.time_limit_secs();
+        let last_standing = if options.lives() == 0 || self.eliminated_count() == 0 {
+            false
+        } else if options.is_team_based() {
+            // Team is out when every its member is eliminated.
+            let remaining = [Team::Red, Team::Blue]
+                .iter()
+                .filter(|team| self.team_remaining(**team) > 0)
+                .count();
+            remaining <= 1
+        } else {
+            self.personal_score.len() as u32 - self.eliminated_count() <= 1
+        };
+        let reason = if last_standing {
+            MatchEndReason::LastStanding
+        } else if score_limit > 0 && score >= score_limit {
+            MatchEndReason::ScoreLimit
+        } else if time_limit > 0.0 && elapsed >= time_limit {
+            MatchEndReason::TimeLimit
+        } else {
+            return None;
+        };
+
+        Some(self.summary(options, reason))
+    }
+
+    /// Summary of a match that ends right now for given reason.
+    pub fn summary(&self, options: &MatchOptions, reason: MatchEndReason) -> MatchSummary {
+        let (winner, _) = self.leader(options);
+        MatchSummary {
+            winner,
+            reason,
+            top_damage: self.top_damage(),
+            accuracy: Vec::new(),
+        }
+    }
 }
 
 impl Default for LeaderBoard {
@@ -148,8 +501,101 @@ impl Visit for LeaderBoard {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum LeaderBoardTab {
+    Match,
+    Session,
+}
+
 pub struct LeaderBoardUI {
     root: UINodeHandle,
+    table: UINodeHandle,
+    session_table: UINodeHandle,
+    /// Names of tabs, shown on end-of-match screen only.
+    tabs: UINodeHandle,
+    tab: LeaderBoardTab,
+    /// True while end-of-match screen is shown, leader board stays on screen then.
+    match_over: bool,
+}
+
+/// Builds a cell of a table, header cells are centered.
+fn table_cell(ctx: &mut BuildContext, row: usize, column: usize, text: &str) -> UINodeHandle {
+    let mut builder = WidgetBuilder::new()
+        .with_margin(Thickness::uniform(3.0))
+        .on_row(row)
+        .on_column(column);
+    if row == 0 {
+        builder = builder
+            .with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center);
+    }
+    TextBuilder::new(builder).with_text(text).build(ctx)
+}
+
+fn build_session_table(ctx: &mut BuildContext, session: &SessionStandings) -> UINodeHandle {
+    let entries = session.sorted();
+
+    let mut children = ["Name", "Matches", "Wins", "Kills", "Deaths"]
+        .iter()
+        .enumerate()
+        .map(|(column, header)| table_cell(ctx, 0, column, header))
+        .collect::<Vec<_>>();
+    for (i, (name, entry)) in entries.iter().enumerate() {
+        let row = i + 1;
+        children.push(table_cell(ctx, row, 0, name));
+        children.push(table_cell(ctx, row, 1, &entry.matches.to_string()));
+        children.push(table_cell(ctx, row, 2, &entry.wins.to_string()));
+        children.push(table_cell(ctx, row, 3, &entry.kills.to_string()));
+        children.push(table_cell(ctx, row, 4, &entry.deaths.to_string()));
+    }
+
+    let matches = session.matches();
+    GridBuilder::new(
+        WidgetBuilder::new()
+            .with_visibility(false)
+            .on_row(1)
+            .on_column(1)
+            .with_background(Brush::Solid(Color::BLACK))
+            .with_child(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(5.0))
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .on_column(0)
+                        .on_row(0),
+                )
+                .with_text(format!(
+                    "Session - {} {} played",
+                    matches,
+                    if matches == 1 { "match" } else { "matches" }
+                ))
+                .build(ctx),
+            )
+            .with_child(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(0)
+                        .on_row(1)
+                        .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
+                        .with_children(&children),
+                )
+                .with_border_thickness(2.0)
+                .add_row(Row::strict(30.0))
+                .add_rows(entries.iter().map(|_| Row::strict(30.0)).collect())
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .draw_border(true)
+                .build(ctx),
+            ),
+    )
+    .add_column(Column::auto())
+    .add_row(Row::auto())
+    .add_row(Row::stretch())
+    .build(ctx)
 }
 
 impl LeaderBoardUI {
@@ -158,20 +604,41 @@ impl LeaderBoardUI {
 
         let ui = &mut engine.user_interface;
 
+        let tabs;
         let root: UINodeHandle = GridBuilder::new(
             WidgetBuilder::new()
                 .with_visibility(false)
                 .with_width(frame_size.0 as f32)
-                .with_height(frame_size.1 as f32),
+                .with_height(frame_size.1 as f32)
+                .with_child({
+                    tabs = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(false)
+                            .with_margin(Thickness::uniform(5.0))
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .on_row(0)
+                            .on_column(1),
+                    )
+                    .build(&mut ui.build_ctx());
+                    tabs
+                }),
         )
         .add_row(Row::stretch())
         .add_row(Row::strict(600.0))
         .add_row(Row::stretch())
         .add_column(Column::stretch())
-        .add_column(Column::strict(500.0))
+        .add_column(Column::strict(680.0))
         .add_column(Column::stretch())
         .build(&mut ui.build_ctx());
-        Self { root }
+        Self {
+            root,
+            table: Handle::NONE,
+            session_table: Handle::NONE,
+            tabs,
+            tab: LeaderBoardTab::Match,
+            match_over: false,
+        }
     }
 
     fn sync_to_model(
@@ -191,7 +658,7 @@ impl LeaderBoardUI {
 
         let mut children = Vec::new();
 
-        for (i, (name, score)) in leader_board.values().iter().enumerate() {
+        for (i, (name, score)) in leader_board.standings(match_options).iter().enumerate() {
             let row = i + 1;
 
             children.push(
@@ -243,6 +710,39 @@ impl LeaderBoardUI {
                 .with_text(kd)
                 .build(ctx),
             );
+
+            children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(3.0))
+                        .on_row(row)
+                        .on_column(4),
+                )
+                .with_text(format!("{}", score.assists))
+                .build(ctx),
+            );
+
+            children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(3.0))
+                        .on_row(row)
+                        .on_column(5),
+                )
+                .with_text(format!("{:.0}", score.damage_dealt))
+                .build(ctx),
+            );
+
+            children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(3.0))
+                        .on_row(row)
+                        .on_column(6),
+                )
+                .with_text(format!("{}", score.best_streak))
+                .build(ctx),
+            );
         }
 
         let table = GridBuilder::new(
@@ -262,6 +762,13 @@ impl LeaderBoardUI {
                             MatchOptions::DeathMatch(dm) => dm.time_limit_secs,
                             MatchOptions::TeamDeathMatch(tdm) => tdm.time_limit_secs,
                             MatchOptions::CaptureTheFlag(ctf) => ctf.time_limit_secs,
+                            MatchOptions::Oddball(oddball) => oddball.time_limit_secs,
+                            MatchOptions::KingOfTheHill(koth) => koth.time_limit_secs,
+                            MatchOptions::Duel(duel) => duel.time_limit_secs,
+                            MatchOptions::LastManStanding(lms) => lms.time_limit_secs,
+                            MatchOptions::Practice(_)
+                            | MatchOptions::Tutorial(_)
+                            | MatchOptions::TimeTrial(_) => 0.0,
                         };
 
                         let seconds = (time_limit_secs % 60.0) as u32;
@@ -281,6 +788,25 @@ impl LeaderBoardUI {
                                 "Capture The Flag - Time Limit {:02}:{:02}:{:02}",
                                 hours, minutes, seconds
                             ),
+                            MatchOptions::Oddball(_) => format!(
+                                "Oddball - Time Limit {:02}:{:02}:{:02}",
+                                hours, minutes, seconds
+                            ),
+                            MatchOptions::KingOfTheHill(_) => format!(
+                                "King of the Hill - Time Limit {:02}:{:02}:{:02}",
+                                hours, minutes, seconds
+                            ),
+                            MatchOptions::Duel(duel) => format!("Duel - Best of {}", duel.rounds),
+                            MatchOptions::LastManStanding(lms) => format!(
+                                "{}Last Man Standing - Time Limit {:02}:{:02}:{:02}",
+                                if lms.teams { "Team " } else { "" },
+                                hours,
+                                minutes,
+                                seconds
+                            ),
+                            MatchOptions::Practice(_) => "Practice".to_owned(),
+                            MatchOptions::Tutorial(_) => "Tutorial".to_owned(),
+                            MatchOptions::TimeTrial(_) => "Time Trial".to_owned(),
                         }
                     })
                     .build(ctx),
@@ -289,7 +815,7 @@ impl LeaderBoardUI {
                     match match_options {
                         MatchOptions::DeathMatch(dm) => {
                             let text = if let Some((name, kills)) =
-                                leader_board.highest_personal_score(None)
+                                leader_board.highest_personal_score(match_options, None)
                             {
                                 format!(
                                     "{} leads with {} frags\nPlaying until {} frags",
@@ -347,6 +873,124 @@ impl LeaderBoardUI {
                             ))
                             .build(ctx)
                         }
+                        MatchOptions::Oddball(oddball) => {
+                            let text = if let Some((name, seconds)) =
+                                leader_board.highest_personal_score(match_options, None)
+                            {
+                                format!(
+                                    "{} leads with {} s of skull time\nPlaying until {} s",
+                                    name, seconds, oddball.hold_limit
+                                )
+                            } else {
+                                format!("Draw\nPlaying until {} s", oddball.hold_limit)
+                            };
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(5.0))
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .on_column(0)
+                                    .on_row(1),
+                            )
+                            .with_text(text)
+                            .build(ctx)
+                        }
+                        MatchOptions::KingOfTheHill(koth) => {
+                            let text = if let Some((name, seconds)) =
+                                leader_board.highest_personal_score(match_options, None)
+                            {
+                                format!(
+                                    "{} leads with {} s on the hill\nPlaying until {} s",
+                                    name, seconds, koth.hold_limit
+                                )
+                            } else {
+                                format!("Draw\nPlaying until {} s", koth.hold_limit)
+                            };
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(5.0))
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .on_column(0)
+                                    .on_row(1),
+                            )
+                            .with_text(text)
+                            .build(ctx)
+                        }
+                        MatchOptions::Duel(duel) => {
+                            let text = if let Some((name, rounds)) =
+                                leader_board.highest_personal_score(match_options, None)
+                            {
+                                format!(
+                                    "{} leads with {} rounds\nPlaying until {} rounds",
+                                    name,
+                                    rounds,
+                                    duel.rounds_to_win()
+                                )
+                            } else {
+                                format!("Draw\nPlaying until {} rounds", duel.rounds_to_win())
+                            };
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(5.0))
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .on_column(0)
+                                    .on_row(1),
+                            )
+                            .with_text(text)
+                            .build(ctx)
+                        }
+                        MatchOptions::LastManStanding(lms) => {
+                            let text = if lms.teams {
+                                format!(
+                                    "Red {} - Blue {} left standing\n{} lives each",
+                                    leader_board.team_remaining(Team::Red),
+                                    leader_board.team_remaining(Team::Blue),
+                                    lms.lives
+                                )
+                            } else {
+                                let remaining = leader_board
+                                    .values()
+                                    .values()
+                                    .filter(|score| score.eliminated == 0)
+                                    .count();
+                                format!("{} left standing\n{} lives each", remaining, lms.lives)
+                            };
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(5.0))
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .on_column(0)
+                                    .on_row(1),
+                            )
+                            .with_text(text)
+                            .build(ctx)
+                        }
+                        MatchOptions::Practice(_) => TextBuilder::new(
+                            WidgetBuilder::new()
+                                .with_margin(Thickness::uniform(5.0))
+                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                .on_column(0)
+                                .on_row(1),
+                        )
+                        .with_text("Shooting range, no limits")
+                        .build(ctx),
+                        MatchOptions::Tutorial(_) => TextBuilder::new(
+                            WidgetBuilder::new()
+                                .with_margin(Thickness::uniform(5.0))
+                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                .on_column(0)
+                                .on_row(1),
+                        )
+                        .with_text("Follow the prompts to learn the basics")
+                        .build(ctx),
+                        MatchOptions::TimeTrial(_) => TextBuilder::new(
+                            WidgetBuilder::new()
+                                .with_margin(Thickness::uniform(5.0))
+                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                .on_column(0)
+                                .on_row(1),
+                        )
+                        .with_text("Run the course from start to finish, no limits")
+                        .build(ctx),
                     }
                 })
                 .with_child(
@@ -399,6 +1043,39 @@ impl LeaderBoardUI {
                                 .with_text("K/D")
                                 .build(ctx),
                             )
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Center)
+                                        .on_column(4)
+                                        .on_row(0),
+                                )
+                                .with_text("Assists")
+                                .build(ctx),
+                            )
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Center)
+                                        .on_column(5)
+                                        .on_row(0),
+                                )
+                                .with_text("Damage")
+                                .build(ctx),
+                            )
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Center)
+                                        .on_column(6)
+                                        .on_row(0),
+                                )
+                                .with_text("Streak")
+                                .build(ctx),
+                            )
                             .with_children(&children),
                     )
                     .with_border_thickness(2.0)
@@ -413,6 +1090,9 @@ impl LeaderBoardUI {
                     .add_column(Column::stretch())
                     .add_column(Column::stretch())
                     .add_column(Column::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::stretch())
                     .draw_border(true)
                     .build(ctx),
                 ),
@@ -423,15 +1103,91 @@ impl LeaderBoardUI {
         .add_row(Row::stretch())
         .build(ctx);
 
-        if let Some(table) = ctx[self.root].children().first() {
-            let table = *table;
-            ui.send_message(WidgetMessage::remove(table, MessageDirection::ToWidget));
+        if self.table.is_some() {
+            ui.send_message(WidgetMessage::remove(
+                self.table,
+                MessageDirection::ToWidget,
+            ));
         }
         ui.send_message(WidgetMessage::link(
             table,
             MessageDirection::ToWidget,
             self.root,
         ));
+        ui.send_message(WidgetMessage::visibility(
+            table,
+            MessageDirection::ToWidget,
+            self.tab == LeaderBoardTab::Match,
+        ));
+        self.table = table;
+    }
+
+    fn set_tab(&mut self, ui: &mut Gui, tab: LeaderBoardTab) {
+        self.tab = tab;
+        ui.send_message(WidgetMessage::visibility(
+            self.table,
+            MessageDirection::ToWidget,
+            tab == LeaderBoardTab::Match,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.session_table,
+            MessageDirection::ToWidget,
+            tab == LeaderBoardTab::Session,
+        ));
+        let text = match tab {
+            LeaderBoardTab::Match => "[ MATCH ]   SESSION",
+            LeaderBoardTab::Session => "MATCH   [ SESSION ]",
+        };
+        ui.send_message(TextMessage::text(
+            self.tabs,
+            MessageDirection::ToWidget,
+            format!(
+                "{}\nLeft/Right - switch tab, Enter - next match, Esc - main menu",
+                text
+            ),
+        ));
+    }
+
+    /// Shows end-of-match screen - final table of the match and standings of the session on
+    /// separate tabs.
+    pub fn show_match_end(&mut self, ui: &mut Gui, session: &SessionStandings) {
+        if self.session_table.is_some() {
+            ui.send_message(WidgetMessage::remove(
+                self.session_table,
+                MessageDirection::ToWidget,
+            ));
+        }
+        self.session_table = build_session_table(&mut ui.build_ctx(), session);
+        ui.send_message(WidgetMessage::link(
+            self.session_table,
+            MessageDirection::ToWidget,
+            self.root,
+        ));
+        self.match_over = true;
+        self.set_tab(ui, LeaderBoardTab::Match);
+        ui.send_message(WidgetMessage::visibility(
+            self.tabs,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        self.set_visible(true, ui);
+    }
+
+    pub fn hide_match_end(&mut self, ui: &mut Gui) {
+        if self.match_over {
+            self.match_over = false;
+            self.set_tab(ui, LeaderBoardTab::Match);
+            ui.send_message(WidgetMessage::visibility(
+                self.tabs,
+                MessageDirection::ToWidget,
+                false,
+            ));
+            self.set_visible(false, ui);
+        }
+    }
+
+    pub fn is_match_over(&self) -> bool {
+        self.match_over
     }
 
     pub fn set_visible(&self, visible: bool, ui: &mut Gui) {
@@ -459,7 +1215,17 @@ impl LeaderBoardUI {
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
                     if let Some(vk) = input.virtual_keycode {
-                        if vk == VirtualKeyCode::Tab {
+                        if self.match_over {
+                            if input.state == ElementState::Pressed
+                                && (vk == VirtualKeyCode::Left || vk == VirtualKeyCode::Right)
+                            {
+                                let tab = match self.tab {
+                                    LeaderBoardTab::Match => LeaderBoardTab::Session,
+                                    LeaderBoardTab::Session => LeaderBoardTab::Match,
+                                };
+                                self.set_tab(&mut engine.user_interface, tab);
+                            }
+                        } else if vk == VirtualKeyCode::Tab {
                             let visible = match input.state {
                                 ElementState::Pressed => true,
                                 ElementState::Released => false,
@@ -482,12 +1248,119 @@ impl LeaderBoardUI {
         match_options: &MatchOptions,
     ) {
         match message {
-            Message::AddBot { .. } => self.sync_to_model(ui, leader_board, match_options),
             Message::RemoveActor { .. } => self.sync_to_model(ui, leader_board, match_options),
-            Message::SpawnBot { .. } => self.sync_to_model(ui, leader_board, match_options),
-            Message::SpawnPlayer => self.sync_to_model(ui, leader_board, match_options),
+            Message::SpawnActor { .. } => self.sync_to_model(ui, leader_board, match_options),
+            Message::AdjustScore { .. } => self.sync_to_model(ui, leader_board, match_options),
             Message::RespawnActor { .. } => self.sync_to_model(ui, leader_board, match_options),
+            Message::AddBot { .. } => self.sync_to_model(ui, leader_board, match_options),
+            Message::RemoveBot => self.sync_to_model(ui, leader_board, match_options),
             _ => (),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeathMatch, LastManStanding};
+
+    fn death_match(frag_limit: u32) -> MatchOptions {
+        MatchOptions::DeathMatch(DeathMatch {
+            time_limit_secs: 600.0,
+            frag_limit,
+        })
+    }
+
+    #[test]
+    fn frags_and_streaks_are_counted() {
+        let mut leader_board = LeaderBoard::default();
+        assert_eq!(leader_board.add_frag("Kira"), 1);
+        assert_eq!(leader_board.add_frag("Kira"), 2);
+        leader_board.add_death("Kira");
+        assert_eq!(leader_board.add_frag("Kira"), 1);
+
+        let score = leader_board.values()["Kira"];
+        assert_eq!(score.kills, 3);
+        assert_eq!(score.deaths, 1);
+        assert_eq!(score.streak, 1);
+        assert_eq!(score.best_streak, 2);
+    }
+
+    #[test]
+    fn score_can_not_go_below_zero() {
+        let mut leader_board = LeaderBoard::default();
+        leader_board.add_frag("Kira");
+        leader_board.add_score("Kira", -5);
+        assert_eq!(leader_board.values()["Kira"].kills, 0);
+    }
+
+    #[test]
+    fn match_ends_when_frag_limit_is_reached() {
+        let options = death_match(3);
+        let mut leader_board = LeaderBoard::default();
+        leader_board.add_frag("Kira");
+        leader_board.add_frag("Dex");
+        leader_board.add_frag("Kira");
+        assert!(leader_board.evaluate(&options, 10.0).is_none());
+
+        leader_board.add_frag("Kira");
+        let summary = leader_board.evaluate(&options, 10.0).unwrap();
+        assert_eq!(summary.reason, MatchEndReason::ScoreLimit);
+        match summary.winner {
+            MatchWinner::Actor { name, score } => {
+                assert_eq!(name, "Kira");
+                assert_eq!(score, 3);
+            }
+            winner => panic!("unexpected winner {:?}", winner),
+        }
+    }
+
+    #[test]
+    fn match_ends_when_time_is_up() {
+        let options = death_match(0);
+        let mut leader_board = LeaderBoard::default();
+        leader_board.add_frag("Kira");
+        leader_board.add_frag("Dex");
+        let summary = leader_board.evaluate(&options, 600.0).unwrap();
+        assert_eq!(summary.reason, MatchEndReason::TimeLimit);
+        assert!(matches!(summary.winner, MatchWinner::Draw));
+    }
+
+    #[test]
+    fn team_is_out_when_every_member_is_eliminated() {
+        let options = MatchOptions::LastManStanding(LastManStanding {
+            time_limit_secs: 600.0,
+            lives: 1,
+            teams: true,
+        });
+        let mut leader_board = LeaderBoard::default();
+        for (name, team) in [
+            ("Kira", Team::Red),
+            ("Dex", Team::Red),
+            ("Vex", Team::Blue),
+            ("Ash", Team::Blue),
+        ]
+        .iter()
+        {
+            leader_board.get_or_add_actor(name).team = *team;
+        }
+
+        for name in ["Kira", "Vex"].iter() {
+            leader_board.add_death(name);
+            leader_board.eliminate(name);
+        }
+        assert!(leader_board.evaluate(&options, 10.0).is_none());
+
+        leader_board.add_death("Ash");
+        leader_board.eliminate("Ash");
+        let summary = leader_board.evaluate(&options, 10.0).unwrap();
+        assert_eq!(summary.reason, MatchEndReason::LastStanding);
+        match summary.winner {
+            MatchWinner::Team { team, score } => {
+                assert_eq!(team, Team::Red);
+                assert_eq!(score, 1);
+            }
+            winner => panic!("unexpected winner {:?}", winner),
+        }
+    }
+}
@@ -1,87 +1,330 @@
+#[cfg(feature = "query_api")]
+use crate::query::{ActorInfo, ActorInfoKind, ItemInfo, LevelSnapshot, ProjectileInfo};
 use crate::{
-    actor::{Actor, ActorContainer},
-    assets,
-    bot::{Bot, BotKind},
-    control_scheme::ControlScheme,
-    effects,
+    actor::{
+        Actor, ActorContainer, ActorDescriptor, ActorKind, ActorSpawner, SpawnContext,
+        SpawnPointPolicy,
+    },
+    announcer, assets,
+    attachment::{Attachment, AttachmentKind},
+    bot::{self, BotDifficulty, BotKind},
+    character::{Handicap, Team},
+    cinematic::CameraPath,
+    control_scheme::{ControlButton, ControlScheme},
+    ctf::{Flag, FlagState, Flags, ObjectiveRole},
+    damage_modifier::DamageModifiers,
+    deployable::{Deployables, Mine},
+    duel::{self, Rounds},
+    dynamic_prop::{DynamicProp, DynamicPropContainer, DynamicPropKind},
+    effects::{EffectKind, EffectPool, Weather},
+    error::{GameError, GameResult},
+    frame_profiler::{FrameProfiler, Subsystem},
+    heatmap::{Heatmap, HeatmapEventKind},
+    hud::KeyPrompt,
+    interactable::{Interactable, InteractableKind, Interactables},
     item::{Item, ItemContainer, ItemKind},
     jump_pad::{JumpPad, JumpPadContainer},
-    leader_board::LeaderBoard,
-    message::Message,
-    player::Player,
-    projectile::{Projectile, ProjectileContainer, ProjectileKind},
+    kill_streak::{self, StreakReward},
+    koth::{self, Hill, HillState},
+    leader_board::{LeaderBoard, MatchEndReason, MatchSummary},
+    lighting::{self, LightingPreset},
+    logic::{LogicAction, MapLogic},
+    material::Surfaces,
+    message::{Message, MessageCategory},
+    mods, navmesh_cache,
+    oddball::Oddball,
+    pathfinding::{OffMeshLink, OffMeshLinkKind, PathPlanner},
+    placement::{EntityKind, Placement, PlacementFile},
+    player,
+    projectile::{self, Projectile, ProjectileContainer, ProjectileKind},
+    prop_streaming::PropStreamer,
+    race::{BestRun, Race, RaceEvent},
+    random, rating,
+    reverb::ReverbZone,
+    scripting::{self, MatchScripts},
+    settings::{MatchSettings, QualitySettings, MAX_BOT_COUNT},
+    shooting_range::{ShootingRange, Target},
+    sound_variation::SoundCategory,
+    soundscape::Soundscape,
+    spatial_hash::SpatialHash,
+    spectator::SpectatorDirector,
+    tags::TagIndex,
+    tutorial::{PlayerState, TutorialGoal, TutorialProgress, TutorialVolume},
     weapon::{Weapon, WeaponContainer, WeaponKind},
+    zombie_rush::ZombieRush,
     GameEngine, GameTime, MatchOptions,
 };
 use rand::Rng;
 use rg3d::{
     core::{
         color::Color,
-        math::{aabb::AxisAlignedBoundingBox, mat3::Mat3, ray::Ray, vec3::*, PositionProvider},
+        math::{
+            aabb::AxisAlignedBoundingBox, mat3::Mat3, mat4::Mat4, quat::Quat, ray::Ray, vec3::*,
+            PositionProvider,
+        },
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
-    event::Event,
+    event::{DeviceEvent, ElementState, Event, WindowEvent},
     physics::RayCastOptions,
     scene,
-    scene::{base::BaseBuilder, camera::CameraBuilder, node::Node, Scene},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        mesh::{
+            surface::{Surface, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::Node,
+        Scene,
+    },
     sound::context::Context,
-    utils::{self, navmesh::Navmesh},
+    utils::{self, log::Log, navmesh::Navmesh},
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{
     cell::RefCell,
-    path::Path,
     rc::Rc,
     sync::{mpsc::Sender, Arc, Mutex},
+    time::Instant,
 };
 
 pub const RESPAWN_TIME: f32 = 4.0;
+/// Time after death after which player can respawn by pressing jump button.
+const QUICK_RESPAWN_DELAY: f32 = 1.0;
+/// Damage older than this amount of seconds doesn't give an assist when victim is killed.
+const ASSIST_TIME: f32 = 10.0;
+/// How many last damage events against player are kept for death report.
+const DAMAGE_LOG_SIZE: usize = 10;
+/// Damage taken earlier than this amount of seconds before death isn't in death report.
+const DAMAGE_REPORT_TIME: f32 = 10.0;
+/// Protection time of a base when map doesn't set it.
+const DEFAULT_BASE_PROTECTION_TIME: f32 = 10.0;
+/// Damage that enemy in protected base takes every second grows by this amount each second.
+const BASE_DAMAGE_GROWTH: f32 = 10.0;
+/// Seconds after protection of a base ends during which spawns of its team don't protect it
+/// again. Spawns during this time are stragglers of the previous wave.
+const BASE_PROTECTION_COOLDOWN: f32 = 20.0;
+
+/// Size of a placed jump pad, see `placement` module.
+const PLACED_JUMP_PAD_SIZE: Vec3 = Vec3 {
+    x: 1.5,
+    y: 0.1,
+    z: 1.5,
+};
+/// Placed jump pads throw actors up to about this height.
+const PLACED_JUMP_PAD_HEIGHT: f32 = 10.0;
+
+/// Radius of ball that stands for skull on maps without `Oddball` mesh.
+const ODDBALL_RADIUS: f32 = 0.3;
+/// Height of that ball above spawn point.
+const ODDBALL_SPAWN_HEIGHT: f32 = 1.0;
+/// Thickness of plate that marks the hill.
+const HILL_MARKER_THICKNESS: f32 = 0.05;
+/// Radius of ball that shows ghost of the best time trial run.
+const RACE_GHOST_RADIUS: f32 = 0.4;
+
+/// Max distance from eyes of player to a thing player can interact with.
+const INTERACTION_DISTANCE: f32 = 2.5;
+/// How far from center of crosshair a thing can be to be interacted with.
+const INTERACTION_RADIUS: f32 = 0.4;
+
+/// Player is reminded to crouch when it is this close to a crouch zone.
+const CROUCH_HINT_DISTANCE: f32 = 1.5;
+
+/// Damage of melee attack of player, same as of claws of bots.
+const MELEE_DAMAGE: f32 = 20.0;
+/// Player hits enemies that are this close to it.
+const MELEE_RANGE: f32 = 2.0;
+/// Cosine of half of angle in front of player in which melee attack hits.
+const MELEE_CONE: f32 = 0.5;
+
+/// Items dropped by killed bots despawn after this many seconds.
+const BOT_DROP_LIFETIME: f32 = 12.0;
+/// Chance that killed bot drops health in addition to ammo.
+const BOT_DROP_HEALTH_CHANCE: f64 = 0.5;
+
+/// Time in seconds HUD shows start and end of a duel round.
+const ROUND_CALLOUT_DURATION: f32 = 2.5;
+
+/// Player attracts magnetic items only when it moves faster than this, in m/s.
+const ITEM_MAGNET_MIN_SPEED: f32 = 0.5;
+
+/// Size of a cell of actor and item grids, a bit bigger than vision distance of bots so
+/// target search touches only a few cells.
+const GRID_CELL_SIZE: f32 = 8.0;
 
 pub struct Level {
+    /// Path of map model.
+    map: String,
     map_root: Handle<Node>,
     pub scene: Handle<Scene>,
     player: Handle<Actor>,
     projectiles: ProjectileContainer,
+    effects: EffectPool,
     pub actors: ActorContainer,
     weapons: WeaponContainer,
     jump_pads: JumpPadContainer,
+    dynamic_props: DynamicPropContainer,
+    flags: Flags,
+    /// Skull of oddball mode, only in that mode.
+    oddball: Option<Oddball>,
+    /// Hill of king of the hill mode, only in that mode.
+    hill: Option<Hill>,
+    /// Course of time trial mode, only in that mode.
+    race: Option<Race>,
+    shooting_range: ShootingRange,
+    surfaces: Surfaces,
+    interactables: Interactables,
+    deployables: Deployables,
+    /// Progress of tutorial, only in tutorial mode.
+    tutorial: Option<TutorialProgress>,
+    /// Rounds of duel, only in that mode.
+    rounds: Option<Rounds>,
     items: ItemContainer,
-    spawn_points: Vec<SpawnPoint>,
+    spawner: ActorSpawner,
     sender: Option<Sender<Message>>,
     pub navmesh: Option<Navmesh>,
     pub control_scheme: Option<Rc<RefCell<ControlScheme>>>,
     death_zones: Vec<DeathZone>,
+    /// Nodes of map by tag, see `tags` module. Not saved.
+    tags: TagIndex,
+    teleporters: Vec<Teleporter>,
+    base_zones: Vec<BaseZone>,
     pub options: MatchOptions,
+    flashlight_battery: bool,
+    lighting: LightingPreset,
+    bot_difficulty: BotDifficulty,
+    /// Skill of bots relative to difficulty, see `rating` module.
+    bot_skill: f32,
+    zombie_rush: Option<ZombieRush>,
+    /// Kill streaks give rewards, see `kill_streak` module.
+    streak_rewards: bool,
+    /// Health and ammo slide toward moving player, see `Item`.
+    pickup_magnetism: bool,
+    /// Killed bots drop ammo and health.
+    bot_drops: bool,
+    damage_modifiers: DamageModifiers,
+    /// Handicaps that actors get on spawn.
+    player_handicap: Handicap,
+    bot_handicap: Handicap,
+    /// Where actors die, kill and pick up items, exported when match is over.
+    heatmap: Heatmap,
+    /// Set by game from settings, not saved.
+    pub quality: QualitySettings,
     time: f32,
     pub leader_board: LeaderBoard,
     respawn_list: Vec<RespawnEntry>,
+    /// Recent damage dealt by actors to each other, used to give assists. Not saved - losing
+    /// a few assists on load is fine.
+    damage_history: Vec<DamageRecord>,
+    /// Circumstances of last death of player, shown while player is dead.
+    player_death: Option<PlayerDeath>,
+    /// Last damage taken by player, summed up in death report. Not saved - only report of
+    /// last death is saved.
+    damage_log: Vec<DamageLogEntry>,
+    /// If false, player stays dead until jump button is pressed.
+    auto_respawn: bool,
     spectator_camera: Handle<Node>,
     target_spectator_position: Vec3,
+    /// Set when match end conditions are met, so end of match is announced only once.
+    /// Not saved - finished match is destroyed right away.
+    ended: bool,
+    /// Drives spectator camera in attract mode, not saved - attract matches aren't saved.
+    director: Option<SpectatorDirector>,
+    /// Drives spectator camera after player was eliminated in last man standing, not saved -
+    /// it is created again when needed.
+    spectator: Option<SpectatorDirector>,
+    /// Match rules scripts, not saved - scripts are reloaded when level is loaded.
+    scripts: Option<MatchScripts>,
+    /// Triggers and actions of the map, not saved - logic is reloaded and starts over when
+    /// level is loaded.
+    logic: MapLogic,
+    /// Spatial partitions for proximity queries, rebuilt every frame so they aren't saved.
+    actor_grid: SpatialHash<Handle<Actor>>,
+    item_grid: SpatialHash<Handle<Item>>,
+    destructible_grid: SpatialHash<Destructible>,
+    /// Cache of navmesh paths, not saved - it is refilled quickly.
+    path_planner: PathPlanner,
+    /// Decorative props around viewer, loaded instances are saved as part of scene and
+    /// streamer itself is rebuilt from placeholders.
+    props: PropStreamer,
+    weather: Weather,
+    /// State of player to apply on its next spawn, set when level is reloaded. Not saved.
+    player_restore: Option<PlayerRestore>,
+    /// Dead bots with time they were noticed dead, extra corpses are removed oldest first.
+    /// Not saved - corpses of loaded match are timed from the moment of load.
+    corpses: Vec<(Handle<Actor>, f32)>,
 }
 
 impl Default for Level {
     fn default() -> Self {
         Self {
+            map: Default::default(),
             map_root: Default::default(),
             projectiles: ProjectileContainer::new(),
+            effects: Default::default(),
             actors: ActorContainer::new(),
             scene: Handle::NONE,
             player: Handle::NONE,
             weapons: WeaponContainer::new(),
             jump_pads: JumpPadContainer::new(),
+            dynamic_props: DynamicPropContainer::new(),
+            flags: Default::default(),
+            oddball: None,
+            hill: None,
+            race: None,
+            shooting_range: ShootingRange::new(),
+            surfaces: Default::default(),
+            interactables: Default::default(),
+            deployables: Default::default(),
+            tutorial: None,
+            rounds: None,
             items: ItemContainer::new(),
-            spawn_points: Default::default(),
+            spawner: ActorSpawner::new(),
             sender: None,
             navmesh: Default::default(),
             control_scheme: None,
             death_zones: Default::default(),
+            tags: Default::default(),
+            teleporters: Default::default(),
+            base_zones: Default::default(),
             options: Default::default(),
+            flashlight_battery: false,
+            lighting: Default::default(),
+            bot_difficulty: Default::default(),
+            bot_skill: 1.0,
+            zombie_rush: None,
+            streak_rewards: false,
+            pickup_magnetism: false,
+            bot_drops: false,
+            damage_modifiers: Default::default(),
+            player_handicap: Default::default(),
+            bot_handicap: Default::default(),
+            heatmap: Default::default(),
+            quality: Default::default(),
             time: 0.0,
             leader_board: Default::default(),
             respawn_list: Default::default(),
+            damage_history: Default::default(),
+            player_death: None,
+            damage_log: Default::default(),
+            auto_respawn: true,
             spectator_camera: Default::default(),
             target_spectator_position: Default::default(),
+            ended: false,
+            director: None,
+            spectator: None,
+            scripts: None,
+            logic: Default::default(),
+            actor_grid: SpatialHash::new(GRID_CELL_SIZE),
+            item_grid: SpatialHash::new(GRID_CELL_SIZE),
+            destructible_grid: SpatialHash::new(GRID_CELL_SIZE),
+            path_planner: PathPlanner::new(),
+            props: Default::default(),
+            weather: Default::default(),
+            player_restore: None,
+            corpses: Default::default(),
         }
     }
 }
@@ -91,18 +334,50 @@ impl Visit for Level {
         visitor.enter_region(name)?;
 
         self.scene.visit("Scene", visitor)?;
+        self.map.visit("Map", visitor)?;
         self.map_root.visit("MapRoot", visitor)?;
         self.player.visit("Player", visitor)?;
         self.actors.visit("Actors", visitor)?;
         self.projectiles.visit("Projectiles", visitor)?;
+        self.items.visit("Items", visitor)?;
+        self.effects.visit("Effects", visitor)?;
         self.weapons.visit("Weapons", visitor)?;
         self.jump_pads.visit("JumpPads", visitor)?;
-        self.spawn_points.visit("SpawnPoints", visitor)?;
+        self.dynamic_props.visit("DynamicProps", visitor)?;
+        self.flags.visit("Flags", visitor)?;
+        self.oddball.visit("Oddball", visitor)?;
+        self.hill.visit("Hill", visitor)?;
+        self.race.visit("Race", visitor)?;
+        self.shooting_range.visit("ShootingRange", visitor)?;
+        self.surfaces.visit("Surfaces", visitor)?;
+        self.interactables.visit("Interactables", visitor)?;
+        self.deployables.visit("Deployables", visitor)?;
+        self.tutorial.visit("Tutorial", visitor)?;
+        self.rounds.visit("Rounds", visitor)?;
+        self.spawner.visit("SpawnPoints", visitor)?;
         self.death_zones.visit("DeathZones", visitor)?;
+        self.teleporters.visit("Teleporters", visitor)?;
+        self.base_zones.visit("BaseZones", visitor)?;
         self.options.visit("Options", visitor)?;
+        self.flashlight_battery
+            .visit("FlashlightBattery", visitor)?;
+        self.lighting.visit("Lighting", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_skill.visit("BotSkill", visitor)?;
+        self.zombie_rush.visit("ZombieRush", visitor)?;
+        self.streak_rewards.visit("StreakRewards", visitor)?;
+        self.pickup_magnetism.visit("PickupMagnetism", visitor)?;
+        self.bot_drops.visit("BotDrops", visitor)?;
+        self.damage_modifiers.visit("DamageModifiers", visitor)?;
+        self.player_handicap.visit("PlayerHandicap", visitor)?;
+        self.bot_handicap.visit("BotHandicap", visitor)?;
+        self.heatmap.visit("Heatmap", visitor)?;
+        self.weather.visit("Weather", visitor)?;
         self.time.visit("Time", visitor)?;
         self.leader_board.visit("LeaderBoard", visitor)?;
         self.respawn_list.visit("RespawnList", visitor)?;
+        self.player_death.visit("PlayerDeath", visitor)?;
+        self.auto_respawn.visit("AutoRespawn", visitor)?;
         self.spectator_camera.visit("SpectatorCamera", visitor)?;
         self.target_spectator_position
             .visit("TargetSpectatorPosition", visitor)?;
@@ -111,6 +386,98 @@ impl Visit for Level {
     }
 }
 
+struct DamageRecord {
+    victim: Handle<Actor>,
+    attacker: String,
+    time: f32,
+}
+
+/// Velocity change in meters per second that pushes actor at given position away from center
+/// of explosion. Push is tilted up, so explosion under feet throws actor into the air.
+pub fn knockback(center: Vec3, position: Vec3, strength: f32) -> Vec3 {
+    let away = (position - center).normalized().unwrap_or(Vec3::UP);
+    (away + Vec3::new(0.0, 0.5, 0.0))
+        .normalized()
+        .unwrap_or(Vec3::UP)
+        .scale(strength)
+}
+
+/// What dealt damage to an actor.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DamageSource {
+    /// Damage that doesn't come from any actor, like protection of team bases.
+    Environment,
+    Melee,
+    /// Direct hit of projectile.
+    Projectile(ProjectileKind),
+    /// Explosion of projectile.
+    Splash(ProjectileKind),
+    /// Explosion of dynamic prop.
+    Barrel,
+    Mine,
+}
+
+impl DamageSource {
+    fn name(self) -> String {
+        match self {
+            DamageSource::Environment => "Environment".to_owned(),
+            DamageSource::Melee => "melee".to_owned(),
+            DamageSource::Projectile(kind) => kind.name().to_owned(),
+            DamageSource::Splash(kind) => format!("{} splash", kind.name()),
+            DamageSource::Barrel => "Barrel explosion".to_owned(),
+            DamageSource::Mine => "Mine".to_owned(),
+        }
+    }
+}
+
+struct DamageLogEntry {
+    /// `None` if damage came from environment or player itself.
+    attacker: Option<String>,
+    source: DamageSource,
+    amount: f32,
+    time: f32,
+}
+
+/// Who killed player, with what and from how far away.
+#[derive(Default)]
+pub struct PlayerDeath {
+    /// `None` if player died on its own, for example fell into death zone.
+    killer: Option<String>,
+    weapon: Option<WeaponKind>,
+    distance: f32,
+    /// Damage taken shortly before death grouped by attacker and source, for example
+    /// "Maw melee 20, Rocket splash 64".
+    damage_report: String,
+}
+
+impl Visit for PlayerDeath {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.killer.visit("Killer", visitor)?;
+        let mut weapon_id = self.weapon.map(|kind| kind.id() as i32).unwrap_or(-1);
+        weapon_id.visit("WeaponId", visitor)?;
+        if visitor.is_reading() {
+            self.weapon = if weapon_id < 0 {
+                None
+            } else {
+                Some(WeaponKind::new(weapon_id as u32)?)
+            };
+        }
+        self.distance.visit("Distance", visitor)?;
+        self.damage_report.visit("DamageReport", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// State of player that is carried over reload of level.
+pub struct PlayerRestore {
+    position: Vec3,
+    health: f32,
+    armor: f32,
+}
+
 pub struct DeathZone {
     bounds: AxisAlignedBoundingBox,
 }
@@ -133,91 +500,123 @@ impl Default for DeathZone {
     }
 }
 
-pub struct UpdateContext<'a> {
-    pub time: GameTime,
-    pub scene: &'a mut Scene,
-    pub sound_context: Arc<Mutex<Context>>,
-    pub items: &'a ItemContainer,
-    pub jump_pads: &'a JumpPadContainer,
-    pub navmesh: Option<&'a mut Navmesh>,
-    pub weapons: &'a WeaponContainer,
+/// Moves every actor that enters its bounds to the exit.
+pub struct Teleporter {
+    bounds: AxisAlignedBoundingBox,
+    exit: Vec3,
 }
 
-struct PlayerRespawnEntry {
-    time_left: f32,
+impl Visit for Teleporter {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.bounds.visit("Bounds", visitor)?;
+        self.exit.visit("Exit", visitor)?;
+
+        visitor.leave_region()
+    }
 }
 
-impl Default for PlayerRespawnEntry {
+impl Default for Teleporter {
     fn default() -> Self {
-        Self { time_left: 0.0 }
+        Self {
+            bounds: Default::default(),
+            exit: Default::default(),
+        }
     }
 }
 
-impl Visit for PlayerRespawnEntry {
+/// Base of a team in team modes. For a while after a spawn wave of the team enemies inside of
+/// the base take damage that grows every second they stay there, so spawning actors aren't
+/// shot right away. A wave is the first spawn of the team after the cooldown.
+pub struct BaseZone {
+    team: Team,
+    bounds: AxisAlignedBoundingBox,
+    /// Set by map with a suffix of node name, `Base_Red_15` is protected for 15 seconds.
+    protection_time: f32,
+    time_left: f32,
+    /// Time until spawn of the team can protect the base again.
+    cooldown: f32,
+    /// Enemies inside of the base and whole seconds they spent there, not saved.
+    intruders: Vec<(Handle<Actor>, u32)>,
+    /// Time to next damage tick.
+    tick_timer: f32,
+}
+
+impl Visit for BaseZone {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         visitor.enter_region(name)?;
 
+        self.team.visit("Team", visitor)?;
+        self.bounds.visit("Bounds", visitor)?;
+        self.protection_time.visit("ProtectionTime", visitor)?;
         self.time_left.visit("TimeLeft", visitor)?;
+        self.cooldown.visit("Cooldown", visitor)?;
 
         visitor.leave_region()
     }
 }
 
-struct BotRespawnEntry {
-    name: String,
-    kind: BotKind,
-    time_left: f32,
-}
-
-impl Default for BotRespawnEntry {
+impl Default for BaseZone {
     fn default() -> Self {
         Self {
-            name: "".to_string(),
-            kind: BotKind::Mutant,
+            team: Team::None,
+            bounds: Default::default(),
+            protection_time: DEFAULT_BASE_PROTECTION_TIME,
             time_left: 0.0,
+            cooldown: 0.0,
+            intruders: Default::default(),
+            tick_timer: 0.0,
         }
     }
 }
 
-impl Visit for BotRespawnEntry {
-    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        visitor.enter_region(name)?;
-
-        self.name.visit("Name", visitor)?;
-        self.time_left.visit("TimeLeft", visitor)?;
+pub struct UpdateContext<'a> {
+    pub time: GameTime,
+    pub scene: &'a mut Scene,
+    pub sound_context: Arc<Mutex<Context>>,
+    pub actor_grid: &'a SpatialHash<Handle<Actor>>,
+    /// Contains only items that can be picked up.
+    pub item_grid: &'a SpatialHash<Handle<Item>>,
+    pub items: &'a ItemContainer,
+    pub deployables: &'a Deployables,
+    pub flags: &'a Flags,
+    pub oddball: Option<&'a Oddball>,
+    pub hill: Option<&'a Hill>,
+    pub jump_pads: &'a JumpPadContainer,
+    pub navmesh: Option<&'a mut Navmesh>,
+    pub path_planner: &'a mut PathPlanner,
+    pub weapons: &'a WeaponContainer,
+    pub profiler: &'a mut FrameProfiler,
+}
 
-        let mut kind_id = self.kind.id();
-        kind_id.visit("Kind", visitor)?;
-        self.kind = BotKind::from_id(kind_id)?;
+/// Thing under crosshair of player that it can interact with.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum LookTarget {
+    Item(Handle<Item>),
+    Interactable(usize),
+}
 
-        visitor.leave_region()
-    }
+/// Thing besides actors that explosions can damage or set off.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Destructible {
+    Prop(Handle<DynamicProp>),
+    /// Only explosive projectiles, like rockets.
+    Projectile(Handle<Projectile>),
+    Mine(Handle<Mine>),
 }
 
-enum RespawnEntry {
-    Bot(BotRespawnEntry),
-    Player(PlayerRespawnEntry),
+/// Actor that waits for respawn, it will be spawned again by the same descriptor.
+struct RespawnEntry {
+    descriptor: ActorDescriptor,
+    time_left: f32,
 }
 
 impl Default for RespawnEntry {
     fn default() -> Self {
-        RespawnEntry::Player(PlayerRespawnEntry::default())
-    }
-}
-
-impl RespawnEntry {
-    fn id(&self) -> u32 {
-        match self {
-            RespawnEntry::Bot { .. } => 0,
-            RespawnEntry::Player { .. } => 1,
-        }
-    }
-
-    fn from_id(id: u32) -> Result<Self, String> {
-        match id {
-            0 => Ok(RespawnEntry::Bot(Default::default())),
-            1 => Ok(RespawnEntry::Player(Default::default())),
-            _ => Err(format!("Invalid RespawnEntry type {}", id)),
+        Self {
+            descriptor: Default::default(),
+            time_left: 0.0,
         }
     }
 }
@@ -226,44 +625,41 @@ impl Visit for RespawnEntry {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         visitor.enter_region(name)?;
 
-        let mut id = self.id();
-        id.visit("Id", visitor)?;
-        if visitor.is_reading() {
-            *self = Self::from_id(id)?;
-        }
-
-        match self {
-            RespawnEntry::Bot(v) => v.visit("Data", visitor)?,
-            RespawnEntry::Player(v) => v.visit("Data", visitor)?,
-        }
+        self.descriptor.visit("Descriptor", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
 
         visitor.leave_region()
     }
 }
 
 impl Level {
+    pub const SUBSCRIPTIONS: &'static [MessageCategory] = &[
+        MessageCategory::Actor,
+        MessageCategory::Weapon,
+        MessageCategory::Item,
+        MessageCategory::Effect,
+    ];
+
     pub async fn new(
         engine: &mut GameEngine,
         control_scheme: Rc<RefCell<ControlScheme>>,
         sender: Sender<Message>,
-        options: MatchOptions,
-    ) -> Level {
+        settings: &MatchSettings,
+    ) -> GameResult<Level> {
         let mut scene = Scene::new();
 
         // Spectator camera is used when there is no player on level.
         // This includes situation when player is dead - all dead actors are removed
-        // from level.
-        let spectator_camera = scene.graph.add_node(Node::Camera(
-            CameraBuilder::new(BaseBuilder::new())
-                .enabled(false)
-                .build(),
-        ));
+        // from level, and time before first spawn while player selects loadout.
+        let spectator_camera = scene
+            .graph
+            .add_node(Node::Camera(CameraBuilder::new(BaseBuilder::new()).build()));
 
         let map_model = engine
             .resource_manager
-            .request_model(Path::new(assets::models::maps::DM6))
+            .request_model(mods::resolve(&settings.map))
             .await
-            .unwrap();
+            .map_err(|_| GameError::missing_asset(&settings.map))?;
 
         // Instantiate map
         let map_root = map_model.instantiate_geometry(&mut scene);
@@ -284,32 +680,87 @@ impl Level {
             println!("Unable to find Polygon node to build collision shape for level!");
         }
 
+        // Sun is saved as a part of scene, only ambient light must be restored on load.
+        lighting::create_sun(&mut scene, map_root, settings.lighting);
+
         let mut level = Level {
             scene: engine.scenes.add(scene),
             sender: Some(sender),
             control_scheme: Some(control_scheme),
+            map: settings.map.clone(),
             map_root,
-            options,
+            options: settings.options(),
+            flashlight_battery: settings.flashlight_battery,
+            lighting: settings.lighting,
+            bot_difficulty: settings.bot_difficulty,
+            bot_skill: 1.0,
+            auto_respawn: settings.auto_respawn,
+            streak_rewards: settings.streak_rewards,
+            pickup_magnetism: settings.pickup_magnetism,
+            bot_drops: settings.bot_drops,
+            damage_modifiers: DamageModifiers::from_settings(settings),
+            player_handicap: Handicap::from_percents(
+                settings.player_health_percent,
+                settings.player_damage_percent,
+            ),
+            bot_handicap: Handicap::from_percents(
+                settings.bot_health_percent,
+                settings.bot_damage_percent,
+            ),
+            heatmap: Heatmap::new(&settings.map),
+            zombie_rush: if settings.zombie_rush {
+                Some(ZombieRush::new())
+            } else {
+                None
+            },
             spectator_camera,
             ..Default::default()
         };
 
+        level.apply_lighting(engine);
         level.build_navmesh(engine);
+        level.collect_props(engine);
+        level.build_tags(engine);
         level.analyze(engine).await;
-        level.spawn_player(engine).await;
-        level
-            .spawn_bot(engine, BotKind::Maw, Some("Maw".to_owned()))
-            .await;
-        level
-            .spawn_bot(engine, BotKind::Mutant, Some("Mutant".to_owned()))
-            .await;
-        level
-            .spawn_bot(engine, BotKind::Parasite, Some("Parasite".to_owned()))
-            .await;
+        level.weather = Weather::new(
+            &mut engine.scenes[level.scene].graph,
+            engine.resource_manager.clone(),
+            engine.sound_context.clone(),
+        )
+        .await;
+        level.send_reverb_zones(engine);
+        level.load_soundscape();
+        level.prewarm(engine).await;
+        level.place_spectator_camera(engine);
+        level.load_placements(engine).await;
+        // Names of bots are taken from the name pool.
+        let bot_count = if !level.options.has_bots() {
+            0
+        } else if level.options.is_duel() {
+            1
+        } else {
+            settings.bot_count as usize
+        };
+        for i in 0..bot_count {
+            level
+                .spawn_actor(
+                    engine,
+                    &ActorDescriptor::bot(bot::ROSTER[i % bot::ROSTER.len()]),
+                )
+                .await;
+        }
+
+        level.load_scripts();
+        level.load_logic();
+        if let Some(scripts) = level.scripts.as_mut() {
+            scripts.on_match_start();
+        }
 
-        level
+        Ok(level)
     }
 
+    /// Loads baked navmesh of the map or builds it from `Navmesh` node if the map has no
+    /// baked navmesh, see `navmesh_cache`.
     pub fn build_navmesh(&mut self, engine: &mut GameEngine) {
         if self.navmesh.is_none() {
             let scene = &mut engine.scenes[self.scene];
@@ -317,17 +768,100 @@ impl Level {
             if navmesh_handle.is_some() {
                 let navmesh_node = &mut scene.graph[navmesh_handle];
                 navmesh_node.set_visibility(false);
-                self.navmesh = Some(Navmesh::from_mesh(navmesh_node.as_mesh()));
+                self.navmesh = navmesh_cache::load(&self.map)
+                    .or_else(|| Some(Navmesh::from_mesh(navmesh_node.as_mesh())));
             } else {
                 println!("Unable to find Navmesh node to build navmesh!")
             }
         }
+        self.build_off_mesh_links(engine);
+    }
+
+    /// Connects parts of navmesh by jump pads (`JumpPad*` with `_End` node), teleporters
+    /// (`Teleporter*` with `_Exit` node) and drop-downs (`DropDown*` at the edge of a ledge
+    /// with `_End` node at landing point). Links aren't saved, they're rebuilt from the map.
+    fn build_off_mesh_links(&mut self, engine: &mut GameEngine) {
+        let navmesh = match self.navmesh.as_mut() {
+            Some(navmesh) => navmesh,
+            None => return,
+        };
+        let scene = &engine.scenes[self.scene];
+        let mut links = Vec::new();
+        for (_, node) in scene.graph.pair_iter() {
+            let name = node.name();
+            let (kind, end_suffix) = if name.starts_with("JumpPad") {
+                (OffMeshLinkKind::JumpPad, "End")
+            } else if name.starts_with("Teleporter") {
+                (OffMeshLinkKind::Teleporter, "Exit")
+            } else if name.starts_with("DropDown") {
+                (OffMeshLinkKind::DropDown, "End")
+            } else {
+                continue;
+            };
+            let end = scene
+                .graph
+                .find_by_name_from_root(format!("{}_{}", name, end_suffix).as_str());
+            if end.is_none() {
+                continue;
+            }
+            let begin = node.global_position();
+            let end = scene.graph[end].global_position();
+            if let (Some(begin_vertex), Some(end_vertex)) =
+                (navmesh.query_closest(begin), navmesh.query_closest(end))
+            {
+                links.push(OffMeshLink {
+                    kind,
+                    begin,
+                    end,
+                    begin_vertex,
+                    end_vertex,
+                });
+            }
+        }
+        self.path_planner.set_links(links);
+    }
+
+    /// Sets ambient light of renderer, it is global so it isn't saved with scene.
+    pub fn apply_lighting(&self, engine: &mut GameEngine) {
+        engine
+            .renderer
+            .set_ambient_color(self.lighting.definition().ambient_color);
+    }
+
+    /// Also rebuilds crouch zones of navmesh, they come from tags and aren't saved.
+    pub fn build_tags(&mut self, engine: &GameEngine) {
+        let graph = &engine.scenes[self.scene].graph;
+        self.tags = TagIndex::build(graph, &self.map);
+        let crouch_zones = self
+            .tags
+            .nodes("crouchzone*")
+            .into_iter()
+            .filter_map(|handle| match &graph[handle] {
+                Node::Mesh(mesh) => Some(mesh.world_bounding_box()),
+                _ => None,
+            })
+            .collect();
+        self.path_planner.set_crouch_zones(crouch_zones);
+    }
+
+    pub fn collect_props(&mut self, engine: &mut GameEngine) {
+        self.props = PropStreamer::collect(&engine.scenes[self.scene]);
     }
 
     pub async fn analyze(&mut self, engine: &mut GameEngine) {
         let mut items = Vec::new();
-        let mut spawn_points = Vec::new();
         let mut death_zones = Vec::new();
+        let mut dynamic_props = Vec::new();
+        let mut flags = Vec::new();
+        let mut oddball = Handle::NONE;
+        let mut hill_points = Vec::new();
+        let mut race_start = Handle::NONE;
+        let mut race_checkpoints = Vec::new();
+        let mut race_finish = Handle::NONE;
+        let mut targets = Vec::new();
+        let mut tutorial_volumes = Vec::new();
+        let mut interactables = Vec::new();
+        let mut base_zones = Vec::new();
         let scene = &mut engine.scenes[self.scene];
         for (handle, node) in scene.graph.pair_iter() {
             let position = node.global_position();
@@ -342,8 +876,7 @@ impl Level {
                 if begin.is_some() && end.is_some() {
                     let begin = scene.graph[begin].global_position();
                     let end = scene.graph[end].global_position();
-                    let (force, len) = (end - begin).normalized_ex();
-                    let force = force.unwrap_or(Vec3::UP).scale(len / 20.0);
+                    let force = JumpPad::force_between(begin, end);
                     let shape = utils::mesh_to_static_geometry(node.as_mesh(), false);
                     let shape = scene.physics.add_static_geometry(shape);
                     scene.static_geometry_binder.bind(shape, handle);
@@ -357,25 +890,127 @@ impl Level {
                 items.push((ItemKind::M4Ammo, position));
             } else if name.starts_with("Ammo_Plasma") {
                 items.push((ItemKind::Plasma, position));
-            } else if name.starts_with("SpawnPoint") {
-                spawn_points.push(node.global_position())
-            } else if name.starts_with("DeathZone") {
+            } else if name.starts_with("Attachment_Silencer") {
+                items.push((ItemKind::Silencer, position));
+            } else if name.starts_with("Attachment_ExtendedMag") {
+                items.push((ItemKind::ExtendedMag, position));
+            } else if name.starts_with("Attachment_Scope") {
+                items.push((ItemKind::Scope, position));
+            } else if name.starts_with("Attachment_LaserSight") {
+                items.push((ItemKind::LaserSight, position));
+            } else if name.starts_with("Overshield") {
+                items.push((ItemKind::Overshield, position));
+            } else if name.starts_with("Hill_") {
+                hill_points.push((name.to_owned(), handle, position));
+            } else if name.starts_with("Base_Red") || name.starts_with("Base_Blue") {
                 if let Node::Mesh(_) = node {
-                    death_zones.push(handle);
+                    let team = if name.starts_with("Base_Red") {
+                        Team::Red
+                    } else {
+                        Team::Blue
+                    };
+                    let protection_time = name
+                        .rsplit('_')
+                        .next()
+                        .and_then(|suffix| suffix.parse().ok())
+                        .unwrap_or(DEFAULT_BASE_PROTECTION_TIME);
+                    base_zones.push((team, protection_time, handle));
+                }
+            } else if name.starts_with("Teleporter") {
+                let exit = scene
+                    .graph
+                    .find_by_name_from_root(format!("{}_Exit", name).as_str());
+                if let (Node::Mesh(mesh), true) = (node, exit.is_some()) {
+                    self.teleporters.push(Teleporter {
+                        bounds: mesh.world_bounding_box(),
+                        exit: scene.graph[exit].global_position(),
+                    });
+                }
+            } else if let Node::Mesh(_) = node {
+                if name.starts_with("Physics_Crate") {
+                    dynamic_props.push((DynamicPropKind::Crate, handle));
+                } else if name.starts_with("Physics_Barrel") {
+                    dynamic_props.push((DynamicPropKind::Barrel, handle));
+                } else if name.starts_with("Physics_ExplosiveBarrel") {
+                    dynamic_props.push((DynamicPropKind::ExplosiveBarrel, handle));
+                } else if name == "Flag_Red" {
+                    flags.push((Team::Red, handle));
+                } else if name == "Flag_Blue" {
+                    flags.push((Team::Blue, handle));
+                } else if name == "Oddball" {
+                    oddball = handle;
+                } else if name == "Race_Start" {
+                    race_start = handle;
+                } else if name == "Race_Finish" {
+                    race_finish = handle;
+                } else if name.starts_with("Race_Checkpoint_") {
+                    race_checkpoints.push((name.to_owned(), handle));
+                } else if name.starts_with("Target_Static") || name.starts_with("Target_Moving") {
+                    let end = scene
+                        .graph
+                        .find_by_name_from_root(format!("{}_End", name).as_str());
+                    let end = if end.is_some() {
+                        Some(scene.graph[end].global_position())
+                    } else {
+                        None
+                    };
+                    targets.push((name.starts_with("Target_Moving"), handle, end));
+                } else if name.starts_with("Elevator_") {
+                    let top = scene
+                        .graph
+                        .find_by_name_from_root(format!("{}_Top", name).as_str());
+                    if top.is_some() {
+                        let top = scene.graph[top].global_position();
+                        interactables.push((InteractableKind::Elevator, handle, Some(top)));
+                    }
+                } else if name.starts_with("Tutorial_") {
+                    if let Ok(step) = name["Tutorial_".len()..].parse::<u32>() {
+                        tutorial_volumes.push((step, handle));
+                    }
+                }
+            }
+        }
+        // Spawn points, hazards and doors can be tagged by tag file of the map too.
+        for handle in self.tags.nodes("spawnpoint*") {
+            self.spawner
+                .add_spawn_point(scene.graph[handle].global_position());
+        }
+        for handle in self.tags.nodes("deathzone*") {
+            if let Node::Mesh(_) = scene.graph[handle] {
+                death_zones.push(handle);
+            }
+        }
+        for handle in self.tags.nodes("crouchzone*") {
+            scene.graph[handle].set_visibility(false);
+        }
+        for (tag, kind) in [
+            ("door", InteractableKind::Door),
+            ("switch", InteractableKind::Switch),
+        ]
+        .iter()
+        {
+            for handle in self.tags.nodes(tag) {
+                if let Node::Mesh(_) = scene.graph[handle] {
+                    interactables.push((*kind, handle, None));
                 }
             }
         }
+        self.surfaces = Surfaces::build(scene, &self.tags, &self.map);
         for (kind, position) in items {
-            self.items.add(
-                Item::new(
-                    kind,
-                    position,
-                    scene,
-                    engine.resource_manager.clone(),
-                    self.sender.as_ref().unwrap().clone(),
-                )
-                .await,
-            );
+            match Item::new(
+                kind,
+                position,
+                scene,
+                engine.resource_manager.clone(),
+                self.sender.as_ref().unwrap().clone(),
+            )
+            .await
+            {
+                Ok(item) => {
+                    self.items.add(item);
+                }
+                Err(e) => Log::writeln(format!("WARNING: Item {:?} skipped: {}", kind, e)),
+            }
         }
         for handle in death_zones {
             let node = &mut scene.graph[handle];
@@ -384,13 +1019,175 @@ impl Level {
                 bounds: node.as_mesh().world_bounding_box(),
             });
         }
-        self.spawn_points = spawn_points
-            .into_iter()
-            .map(|p| SpawnPoint { position: p })
-            .collect();
+        for (kind, handle) in dynamic_props {
+            self.dynamic_props
+                .add(DynamicProp::new(kind, handle, scene));
+        }
+        for (team, protection_time, handle) in base_zones {
+            let node = &mut scene.graph[handle];
+            node.set_visibility(false);
+            if self.options.is_team_based() {
+                self.base_zones.push(BaseZone {
+                    team,
+                    bounds: node.as_mesh().world_bounding_box(),
+                    protection_time,
+                    ..Default::default()
+                });
+            }
+        }
+        for (team, handle) in flags {
+            if let MatchOptions::CaptureTheFlag(_) = self.options {
+                self.flags.add(Flag::new(team, handle, scene));
+            } else {
+                scene.graph[handle].set_visibility(false);
+            }
+        }
+        if self.options.is_oddball() {
+            let (model, spawn) = if oddball.is_some() {
+                (oddball, scene.graph[oddball].global_position())
+            } else {
+                let spawn =
+                    self.spawner
+                        .select_position(SpawnPointPolicy::Random, scene, &self.actors)
+                        + Vec3::UP.scale(ODDBALL_SPAWN_HEIGHT);
+                let mesh = MeshBuilder::new(BaseBuilder::new())
+                    .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
+                        SurfaceSharedData::make_sphere(16, 16, ODDBALL_RADIUS),
+                    )))])
+                    .build();
+                (scene.graph.add_node(Node::Mesh(mesh)), spawn)
+            };
+            self.oddball = Some(Oddball::new(model, spawn, &mut scene.graph));
+        } else if oddball.is_some() {
+            scene.graph[oddball].set_visibility(false);
+        }
+        hill_points.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        for &(_, handle, _) in hill_points.iter() {
+            scene.graph[handle].set_visibility(false);
+        }
+        if self.options.is_king_of_the_hill() {
+            let mut points = hill_points
+                .iter()
+                .map(|&(_, _, position)| position)
+                .collect::<Vec<_>>();
+            if points.is_empty() {
+                points = self.spawner.spawn_point_positions();
+            }
+            if points.is_empty() {
+                points.push(Vec3::ZERO);
+            }
+            let mesh = MeshBuilder::new(BaseBuilder::new())
+                .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
+                    SurfaceSharedData::make_cube(Mat4::scale(Vec3::new(
+                        koth::HALF_SIZE * 2.0,
+                        HILL_MARKER_THICKNESS,
+                        koth::HALF_SIZE * 2.0,
+                    ))),
+                )))])
+                .build();
+            let model = scene.graph.add_node(Node::Mesh(mesh));
+            self.hill = Some(Hill::new(points, model, scene));
+        }
+        race_checkpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut race_gates = race_checkpoints
+            .iter()
+            .map(|&(_, handle)| handle)
+            .collect::<Vec<_>>();
+        race_gates.push(race_start);
+        race_gates.push(race_finish);
+        for &handle in race_gates.iter().filter(|handle| handle.is_some()) {
+            scene.graph[handle].set_visibility(false);
+        }
+        if self.options.is_time_trial() {
+            if race_start.is_some() && race_finish.is_some() {
+                let bounds =
+                    |handle: Handle<Node>| scene.graph[handle].as_mesh().world_bounding_box();
+                let checkpoints = race_checkpoints
+                    .iter()
+                    .map(|&(_, handle)| bounds(handle))
+                    .collect();
+                let (start, finish) = (bounds(race_start), bounds(race_finish));
+                let mesh = MeshBuilder::new(BaseBuilder::new().with_visibility(false))
+                    .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
+                        SurfaceSharedData::make_sphere(16, 16, RACE_GHOST_RADIUS),
+                    )))])
+                    .build();
+                let ghost = scene.graph.add_node(Node::Mesh(mesh));
+                self.race = Some(Race::new(start, checkpoints, finish, ghost));
+            } else {
+                let text = "This map has no race course".to_owned();
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::AddNotification { text })
+                    .unwrap();
+            }
+        }
+        for (moving, handle, end) in targets {
+            if !self.options.is_practice() {
+                scene.graph[handle].set_visibility(false);
+            } else if moving {
+                self.shooting_range
+                    .add(Target::new_moving(handle, end, scene));
+            } else {
+                self.shooting_range.add(Target::new(handle, None, scene));
+            }
+        }
+        for (kind, handle, top) in interactables {
+            self.interactables
+                .add(Interactable::new(kind, handle, top, scene));
+        }
+        if self.options.is_tutorial() {
+            self.tutorial = Some(Default::default());
+        }
+        if self.options.is_duel() {
+            self.rounds = Some(Default::default());
+        }
+        for (step, handle) in tutorial_volumes {
+            let node = &mut scene.graph[handle];
+            node.set_visibility(false);
+            if let Some(tutorial) = self.tutorial.as_mut() {
+                tutorial.add_volume(TutorialVolume::new(
+                    step,
+                    node.as_mesh().world_bounding_box(),
+                ));
+            }
+        }
+    }
+
+    /// Creates projectiles and effects that are used very often in advance, so first seconds
+    /// of a match won't be spent creating scene nodes.
+    async fn prewarm(&mut self, engine: &mut GameEngine) {
+        let scene = &mut engine.scenes[self.scene];
+        for &(kind, count) in &[
+            (ProjectileKind::Bullet, 32),
+            (ProjectileKind::Plasma, 16),
+            (ProjectileKind::Rocket, 4),
+        ] {
+            if let Err(e) = self
+                .projectiles
+                .prewarm(kind, count, engine.resource_manager.clone(), scene)
+                .await
+            {
+                Log::writeln(format!("WARNING: Unable to prewarm {:?}: {}", kind, e));
+            }
+        }
+        for &(kind, count) in &[
+            (EffectKind::BulletImpact, 16),
+            (EffectKind::ItemAppear, 4),
+            (EffectKind::MuzzleFlash, 8),
+        ] {
+            self.effects.prewarm(
+                kind,
+                count,
+                &mut scene.graph,
+                engine.resource_manager.clone(),
+            );
+        }
     }
 
     pub fn destroy(&mut self, engine: &mut GameEngine) {
+        self.weather.clean_up(&engine.sound_context);
         engine.scenes.remove(self.scene);
     }
 
@@ -398,51 +1195,206 @@ impl Level {
         self.player
     }
 
-    pub fn process_input_event(&mut self, event: &Event<()>) -> bool {
-        if self.player.is_some() {
-            if let Actor::Player(player) = self.actors.get_mut(self.player) {
-                return player.process_input_event(event);
+    /// Camera that renders level - camera of player or spectator camera if player is dead.
+    pub fn active_camera(&self) -> Handle<Node> {
+        if self.actors.contains(self.player) {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                return player.camera();
             }
         }
-        false
-    }
-
-    pub fn actors(&self) -> &ActorContainer {
-        &self.actors
+        self.spectator_camera
     }
 
-    pub fn actors_mut(&mut self) -> &mut ActorContainer {
-        &mut self.actors
+    /// Applies view settings to weapon of player right away, used when game is paused.
+    pub fn apply_viewmodel(&mut self, engine: &mut GameEngine) {
+        if self.actors.contains(self.player) {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                player.apply_viewmodel(&mut engine.scenes[self.scene].graph, &self.weapons);
+            }
+        }
     }
 
-    pub fn weapons(&self) -> &WeaponContainer {
-        &self.weapons
+    fn send_reverb_zones(&self, engine: &GameEngine) {
+        let zones = ReverbZone::find_all(&engine.scenes[self.scene].graph);
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::SetReverbZones { zones })
+            .unwrap();
     }
 
-    fn pick(&self, engine: &mut GameEngine, from: Vec3, to: Vec3) -> Vec3 {
-        let scene = &engine.scenes[self.scene];
-        if let Some(ray) = Ray::from_two_points(&from, &to) {
-            let mut intersections = Vec::new();
-            let options = RayCastOptions {
-                ignore_bodies: true,
-                ..Default::default()
-            };
-            scene.physics.ray_cast(&ray, options, &mut intersections);
-            if let Some(pt) = intersections.first() {
-                pt.position
-            } else {
-                from
+    /// Sends ambient emitters of map to sound manager.
+    fn load_soundscape(&self) {
+        match Soundscape::load(Soundscape::file_of_map(&self.map)) {
+            Ok(soundscape) => {
+                for emitter in soundscape.into_emitters() {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::AddAmbientSound {
+                            path: emitter.sound().to_owned(),
+                            position: emitter.position(),
+                            gain: emitter.gain(),
+                            rolloff_factor: emitter.rolloff_factor(),
+                            radius: emitter.radius(),
+                        })
+                        .unwrap();
+                }
             }
-        } else {
-            from
+            Err(e) => Log::writeln(format!("WARNING: {}, map will have no ambience.", e)),
         }
     }
 
-    fn remove_weapon(&mut self, engine: &mut GameEngine, weapon: Handle<Weapon>) {
-        for projectile in self.projectiles.iter_mut() {
-            if projectile.owner == weapon {
-                // Reset owner because handle to weapon will be invalid after weapon freed.
-                projectile.owner = Handle::NONE;
+    /// Adds entities of placement file of the map, see `placement` module.
+    async fn load_placements(&mut self, engine: &mut GameEngine) {
+        match PlacementFile::load(self.placement_file()) {
+            Ok(file) => {
+                for placement in file.entities.iter() {
+                    self.add_placement(engine, placement).await;
+                }
+            }
+            Err(e) => Log::writeln(format!("WARNING: {}, placed entities are skipped.", e)),
+        }
+    }
+
+    pub fn placement_file(&self) -> PathBuf {
+        PlacementFile::file_of_map(&self.map)
+    }
+
+    /// Adds placed entity to the running match. Placed bots respawn at the place where they
+    /// were placed.
+    pub async fn add_placement(&mut self, engine: &mut GameEngine, placement: &Placement) {
+        let position = placement.position();
+        match placement.kind {
+            EntityKind::Item(kind) => {
+                match Item::new(
+                    kind,
+                    position,
+                    &mut engine.scenes[self.scene],
+                    engine.resource_manager.clone(),
+                    self.sender.as_ref().unwrap().clone(),
+                )
+                .await
+                {
+                    Ok(item) => {
+                        self.items.add(item);
+                    }
+                    Err(e) => Log::writeln(format!("WARNING: Item {:?} skipped: {}", kind, e)),
+                }
+            }
+            EntityKind::Bot(kind) => {
+                let descriptor = ActorDescriptor::bot(kind)
+                    .with_spawn_point(SpawnPointPolicy::Position(position));
+                self.spawn_actor(engine, &descriptor).await;
+            }
+            EntityKind::JumpPad => {
+                let scene = &mut engine.scenes[self.scene];
+                // Pad lies on the point, transform is baked into vertices so collision shape
+                // is right before graph calculates global transform of the node.
+                let transform = Mat4::translate(Vec3::new(
+                    position.x,
+                    position.y + PLACED_JUMP_PAD_SIZE.y * 0.5,
+                    position.z,
+                )) * Mat4::scale(PLACED_JUMP_PAD_SIZE);
+                let mesh = MeshBuilder::new(BaseBuilder::new())
+                    .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
+                        SurfaceSharedData::make_cube(transform),
+                    )))])
+                    .build();
+                let shape = scene
+                    .physics
+                    .add_static_geometry(utils::mesh_to_static_geometry(&mesh, false));
+                let handle = scene.graph.add_node(Node::Mesh(mesh));
+                scene.static_geometry_binder.bind(shape, handle);
+                let force = JumpPad::force_between(
+                    position,
+                    position + Vec3::UP.scale(PLACED_JUMP_PAD_HEIGHT),
+                );
+                self.jump_pads.add(JumpPad::new(shape, force));
+            }
+            EntityKind::SpawnPoint => self.spawner.add_spawn_point(position),
+        }
+    }
+
+    pub fn camera_path_file(&self) -> PathBuf {
+        CameraPath::file_of_map(&self.map)
+    }
+
+    pub fn process_input_event(&mut self, event: &Event<()>) -> bool {
+        let pressed = match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Pressed => {
+                input.virtual_keycode.map(ControlButton::Key)
+            }
+            Event::DeviceEvent {
+                event:
+                    DeviceEvent::Button {
+                        button,
+                        state: ElementState::Pressed,
+                    },
+                ..
+            } => Some(ControlButton::Mouse(*button as u8)),
+            _ => None,
+        };
+        let control_scheme = self.control_scheme.clone();
+        if let (Some(pressed), Some(control_scheme)) = (pressed, control_scheme) {
+            let control_scheme = control_scheme.borrow();
+            if self.options.is_practice() && pressed == control_scheme.reset_range.button {
+                self.shooting_range.reset();
+                return true;
+            }
+            if self.player.is_none() && pressed == control_scheme.jump.button {
+                self.respawn_player_now();
+                return true;
+            }
+        }
+
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                return player.process_input_event(event);
+            }
+        }
+        false
+    }
+
+    pub fn actors(&self) -> &ActorContainer {
+        &self.actors
+    }
+
+    pub fn actors_mut(&mut self) -> &mut ActorContainer {
+        &mut self.actors
+    }
+
+    pub fn weapons(&self) -> &WeaponContainer {
+        &self.weapons
+    }
+
+    fn pick(&self, engine: &mut GameEngine, from: Vec3, to: Vec3) -> Vec3 {
+        let scene = &engine.scenes[self.scene];
+        if let Some(ray) = Ray::from_two_points(&from, &to) {
+            let mut intersections = Vec::new();
+            let options = RayCastOptions {
+                ignore_bodies: true,
+                ..Default::default()
+            };
+            scene.physics.ray_cast(&ray, options, &mut intersections);
+            if let Some(pt) = intersections.first() {
+                pt.position
+            } else {
+                from
+            }
+        } else {
+            from
+        }
+    }
+
+    fn remove_weapon(&mut self, engine: &mut GameEngine, weapon: Handle<Weapon>) {
+        for projectile in self.projectiles.iter_mut() {
+            if projectile.owner == weapon {
+                // Reset owner because handle to weapon will be invalid after weapon freed.
+                projectile.owner = Handle::NONE;
             }
         }
         self.weapons[weapon].clean_up(&mut engine.scenes[self.scene]);
@@ -456,20 +1408,19 @@ impl Level {
         kind: WeaponKind,
     ) {
         if self.actors.contains(actor) {
-            let scene = &mut engine.scenes[self.scene];
-            let mut weapon = Weapon::new(
-                kind,
-                engine.resource_manager.clone(),
-                scene,
-                self.sender.as_ref().unwrap().clone(),
-            )
-            .await;
-            weapon.set_owner(actor);
-            let weapon_model = weapon.get_model();
-            let actor = self.actors.get_mut(actor);
-            let weapon_handle = self.weapons.add(weapon);
-            actor.add_weapon(weapon_handle);
-            scene.graph.link_nodes(weapon_model, actor.weapon_pivot());
+            let mut ctx = SpawnContext {
+                scene: &mut engine.scenes[self.scene],
+                resource_manager: engine.resource_manager.clone(),
+                sender: self.sender.as_ref().unwrap().clone(),
+                actors: &mut self.actors,
+                weapons: &mut self.weapons,
+                control_scheme: self.control_scheme.clone(),
+                options: &self.options,
+            };
+            if let Err(e) = ctx.give_weapon(actor, kind).await {
+                Log::writeln(format!("WARNING: Weapon {:?} skipped: {}", kind, e));
+                return;
+            }
 
             self.sender
                 .as_ref()
@@ -481,27 +1432,392 @@ impl Level {
         }
     }
 
-    async fn add_bot(
+    /// Shows level from random spawn point until player is deployed.
+    fn place_spectator_camera(&mut self, engine: &mut GameEngine) {
+        let scene = &mut engine.scenes[self.scene];
+        let position = self
+            .spawner
+            .select_position(SpawnPointPolicy::Random, scene, &self.actors)
+            + Vec3::new(0.0, 2.0, 0.0);
+        scene.graph[self.spectator_camera]
+            .local_transform_mut()
+            .set_position(position);
+        self.target_spectator_position = position;
+    }
+
+    /// Sums up recent damage taken by player by attacker and source, in order of first hit.
+    fn damage_report(&self) -> String {
+        let mut groups: Vec<(Option<&str>, DamageSource, f32)> = Vec::new();
+        for entry in self.damage_log.iter() {
+            if self.time - entry.time > DAMAGE_REPORT_TIME {
+                continue;
+            }
+            let attacker = entry.attacker.as_deref();
+            match groups
+                .iter_mut()
+                .find(|(other, source, _)| *other == attacker && *source == entry.source)
+            {
+                Some((_, _, amount)) => *amount += entry.amount,
+                None => groups.push((attacker, entry.source, entry.amount)),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(attacker, source, amount)| match attacker {
+                Some(attacker) => format!("{} {} {:.0}", attacker, source.name(), amount),
+                None => format!("{} {:.0}", source.name(), amount),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Text of death screen, `None` if player is alive or wasn't deployed yet.
+    pub fn death_screen_text(&self) -> Option<String> {
+        if self.leader_board.is_eliminated(player::PLAYER_NAME) {
+            return Some("You are eliminated\nSpectating the rest of the match".to_owned());
+        }
+
+        let entry = self
+            .respawn_list
+            .iter()
+            .find(|entry| entry.descriptor.kind == ActorKind::Player)?;
+
+        let mut text = match self.player_death.as_ref() {
+            Some(PlayerDeath {
+                killer: Some(killer),
+                weapon,
+                distance,
+                ..
+            }) => {
+                let mut text = format!("Killed by {}\n", killer);
+                if let Some(weapon) = weapon {
+                    text += &format!("{}, ", weapon.name());
+                }
+                text + &format!("{:.0} m\n", distance)
+            }
+            _ => "You Died\n".to_owned(),
+        };
+        if let Some(death) = self.player_death.as_ref() {
+            if !death.damage_report.is_empty() {
+                text += &format!("{}\n", death.damage_report);
+            }
+        }
+
+        if entry.time_left <= RESPAWN_TIME - QUICK_RESPAWN_DELAY {
+            let button = match self.control_scheme.as_ref() {
+                Some(control_scheme) => control_scheme.borrow().jump.button.name(),
+                None => "Jump",
+            };
+            text += &format!("Press [{}] to respawn", button);
+            if self.auto_respawn {
+                text += &format!(" ({:.0})", entry.time_left.ceil());
+            }
+        }
+        Some(text)
+    }
+
+    /// Respawns player right away if it is dead long enough.
+    fn respawn_player_now(&mut self) {
+        if let Some(index) = self.respawn_list.iter().position(|entry| {
+            entry.descriptor.kind == ActorKind::Player
+                && entry.time_left <= RESPAWN_TIME - QUICK_RESPAWN_DELAY
+        }) {
+            let entry = self.respawn_list.remove(index);
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::SpawnActor {
+                    descriptor: entry.descriptor,
+                })
+                .unwrap();
+        }
+    }
+
+    /// Player is spawned only after loadout is selected, this returns true if player wasn't
+    /// spawned yet, is not waiting for respawn and wasn't eliminated.
+    pub fn needs_player_deploy(&self) -> bool {
+        self.player.is_none()
+            && !self.leader_board.is_eliminated(player::PLAYER_NAME)
+            && !self
+                .respawn_list
+                .iter()
+                .any(|entry| entry.descriptor.kind == ActorKind::Player)
+    }
+
+    pub fn set_player_loadout(&mut self, loadout: Vec<WeaponKind>) {
+        self.spawner.set_player_loadout(loadout);
+    }
+
+    /// Sets skill of bots, bots that are already spawned get it too.
+    pub fn set_bot_skill(&mut self, skill: f32) {
+        self.bot_skill = skill;
+        for actor in self.actors.iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                bot.set_difficulty(self.bot_difficulty, skill);
+            }
+        }
+    }
+
+    pub fn bot_skill(&self) -> f32 {
+        self.bot_skill
+    }
+
+    /// Rating of bots of the match, `None` if there are no bots to rate player against.
+    pub fn bot_rating(&self) -> Option<f32> {
+        let has_bots = self
+            .actors
+            .iter()
+            .any(|actor| matches!(actor, Actor::Bot(bot) if !bot.is_zombie()));
+        if has_bots {
+            Some(rating::bot_rating(self.bot_difficulty, self.bot_skill))
+        } else {
+            None
+        }
+    }
+
+    /// Team of player, `None` if player doesn't take part in the match.
+    pub fn player_team(&self) -> Option<Team> {
+        if self.actors.contains(self.player) {
+            return Some(self.actors.get(self.player).team());
+        }
+        self.respawn_list
+            .iter()
+            .find(|entry| entry.descriptor.kind == ActorKind::Player)
+            .map(|entry| entry.descriptor.team)
+    }
+
+    /// State of player to carry over reload of level, `None` if player is dead.
+    pub fn player_restore(&self, engine: &GameEngine) -> Option<PlayerRestore> {
+        if !self.actors.contains(self.player) {
+            return None;
+        }
+        let player = self.actors.get(self.player);
+        Some(PlayerRestore {
+            position: player.position(&engine.scenes[self.scene].physics),
+            health: player.health,
+            armor: player.armor,
+        })
+    }
+
+    pub fn set_player_restore(&mut self, restore: PlayerRestore) {
+        self.player_restore = Some(restore);
+    }
+
+    /// Path of map model.
+    pub fn map(&self) -> &str {
+        &self.map
+    }
+
+    /// Spawns player if it is not deployed yet.
+    pub fn deploy_player(&mut self) {
+        if self.needs_player_deploy() {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::SpawnActor {
+                    descriptor: ActorDescriptor::player(),
+                })
+                .unwrap();
+        }
+    }
+
+    /// Returns descriptor with a name that no other actor of the match has, so score of two
+    /// bots of the same kind isn't merged on the leader board. Bots without a name get one from
+    /// the name pool, zombies share the same name on purpose - they are a faceless crowd.
+    fn with_unique_name(&self, descriptor: &ActorDescriptor) -> ActorDescriptor {
+        let is_taken = |name: &str| {
+            self.actors.iter().any(|actor| actor.name == name)
+                || self
+                    .respawn_list
+                    .iter()
+                    .any(|entry| entry.descriptor.name.as_deref() == Some(name))
+        };
+        let name = match (descriptor.name.as_ref(), descriptor.kind) {
+            (Some(name), _) if descriptor.zombie || !is_taken(name) => return descriptor.clone(),
+            (Some(name), _) => (2..)
+                .map(|n| format!("{} {}", name, n))
+                .find(|name| !is_taken(name))
+                .unwrap(),
+            // Names of bots that left the match are not reused, their score is still there.
+            (None, ActorKind::Bot(_)) => {
+                bot::pick_bot_name(|name| is_taken(name) || self.leader_board.contains(name))
+            }
+            (None, ActorKind::Player) => return descriptor.clone(),
+        };
+        descriptor.clone().with_name(name)
+    }
+
+    /// Spawns actor by given descriptor, returns `Handle::NONE` if actor couldn't be created.
+    async fn spawn_actor(
         &mut self,
         engine: &mut GameEngine,
-        kind: BotKind,
-        position: Vec3,
-        name: Option<String>,
+        descriptor: &ActorDescriptor,
     ) -> Handle<Actor> {
-        let scene = &mut engine.scenes[self.scene];
-        let bot = Bot::new(
-            kind,
-            engine.resource_manager.clone(),
-            scene,
-            position,
-            self.sender.as_ref().unwrap().clone(),
-        )
-        .await;
-        let name = name.unwrap_or_else(|| format!("Bot {:?} {}", kind, self.actors.count()));
-        self.leader_board.get_or_add_actor(&name);
-        let bot = self.actors.add(Actor::Bot(bot));
-        self.give_new_weapon(engine, bot, WeaponKind::Ak47).await;
-        bot
+        let descriptor = &self.with_unique_name(descriptor);
+        let mut ctx = SpawnContext {
+            scene: &mut engine.scenes[self.scene],
+            resource_manager: engine.resource_manager.clone(),
+            sender: self.sender.as_ref().unwrap().clone(),
+            actors: &mut self.actors,
+            weapons: &mut self.weapons,
+            control_scheme: self.control_scheme.clone(),
+            options: &self.options,
+        };
+        let actor = match self.spawner.spawn(descriptor, &mut ctx).await {
+            Ok(actor) => actor,
+            Err(e) => {
+                Log::writeln(format!(
+                    "WARNING: Actor {:?} skipped: {}",
+                    descriptor.kind, e
+                ));
+                return Handle::NONE;
+            }
+        };
+
+        let name = self.actors.get(actor).name.clone();
+        self.leader_board.get_or_add_actor(&name).team = self.actors.get(actor).team();
+        let handicap = match descriptor.kind {
+            ActorKind::Player => self.player_handicap,
+            ActorKind::Bot(_) => self.bot_handicap,
+        };
+        self.actors.get_mut(actor).set_handicap(handicap);
+
+        // Spawn wave of a team protects its base for a while. Later spawns don't prolong it,
+        // otherwise steady respawns would keep the base protected all the time.
+        let team = self.actors.get(actor).team();
+        for zone in self.base_zones.iter_mut().filter(|zone| zone.team == team) {
+            if zone.time_left <= 0.0 && zone.cooldown <= 0.0 {
+                zone.time_left = zone.protection_time;
+                zone.cooldown = zone.protection_time + BASE_PROTECTION_COOLDOWN;
+            }
+        }
+
+        match descriptor.kind {
+            ActorKind::Player => {
+                let scene = &mut engine.scenes[self.scene];
+                if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
+                    spectator_camera.set_enabled(false);
+                }
+                if let Actor::Player(player) = self.actors.get_mut(actor) {
+                    player.set_flashlight_battery_enabled(self.flashlight_battery);
+                    if let Some(restore) = self.player_restore.take() {
+                        player.set_position(&mut scene.physics, restore.position);
+                        player.health = restore.health;
+                        player.armor = restore.armor;
+                    }
+                }
+                self.player = actor;
+            }
+            ActorKind::Bot(_) => {
+                if let Actor::Bot(bot) = self.actors.get_mut(actor) {
+                    bot.set_difficulty(self.bot_difficulty, self.bot_skill);
+                }
+                // Zombies spawn all the time, announcing each of them is just noise.
+                if !descriptor.zombie {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::AddNotification {
+                            text: format!("Bot {} spawned!", name),
+                        })
+                        .unwrap();
+                }
+            }
+        }
+
+        actor
+    }
+
+    /// Bots of the match that can be kicked - alive ones and ones waiting for respawn, with
+    /// their teams. Zombies are not counted, they come and go with waves.
+    fn kickable_bots(&self) -> Vec<(String, Team)> {
+        let alive = self.actors.iter().filter_map(|actor| match actor {
+            Actor::Bot(bot) if !bot.is_zombie() => Some((actor.name.clone(), actor.team())),
+            _ => None,
+        });
+        let waiting = self.respawn_list.iter().filter_map(|entry| {
+            match (entry.descriptor.kind, &entry.descriptor.name) {
+                (ActorKind::Bot(_), Some(name)) if !entry.descriptor.zombie => {
+                    Some((name.clone(), entry.descriptor.team))
+                }
+                _ => None,
+            }
+        });
+        alive.chain(waiting).collect()
+    }
+
+    fn notify(&self, text: String) {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::AddNotification { text })
+            .unwrap();
+    }
+
+    /// Adds a bot to running match, modes without bots and duel keep their bot count.
+    async fn add_bot(&mut self, engine: &mut GameEngine, kind: BotKind, team: Team) {
+        if !self.options.has_bots() || self.options.is_duel() {
+            self.notify("Bots can't be added in this mode".to_owned());
+        } else if self.kickable_bots().len() >= MAX_BOT_COUNT as usize {
+            self.notify(format!("There can be at most {} bots", MAX_BOT_COUNT));
+        } else {
+            self.spawn_actor(engine, &ActorDescriptor::bot(kind).with_team(team))
+                .await;
+        }
+    }
+
+    /// Kicks a bot with the lowest score, in team based modes it is taken from the team with
+    /// most kickable bots. Player isn't counted, so a team that has no bots to kick is never
+    /// chosen just because player is in it.
+    async fn remove_bot(&mut self, engine: &mut GameEngine) {
+        if self.options.is_duel() {
+            self.notify("Bots can't be kicked in this mode".to_owned());
+            return;
+        }
+        let bots = self.kickable_bots();
+        let team = if self.options.is_team_based() {
+            let size = |team: Team| bots.iter().filter(|(_, t)| *t == team).count();
+            match (size(Team::Red), size(Team::Blue)) {
+                (0, 0) => None,
+                (red, blue) if blue > red => Some(Team::Blue),
+                _ => Some(Team::Red),
+            }
+        } else {
+            None
+        };
+        let points = |name: &str| {
+            self.leader_board
+                .values()
+                .get(name)
+                .map_or(0, |score| score.points(&self.options))
+        };
+        let lowest = |team: Option<Team>| {
+            bots.iter()
+                .filter(|(_, t)| team.map_or(true, |team| *t == team))
+                .min_by_key(|(name, _)| points(name))
+                .map(|(name, _)| name.clone())
+        };
+        let kicked = lowest(team).or_else(|| lowest(None));
+        let name = match kicked {
+            Some(name) => name,
+            None => {
+                self.notify("There are no bots to kick".to_owned());
+                return;
+            }
+        };
+
+        let actor = self
+            .actors
+            .pair_iter()
+            .find(|(_, actor)| actor.name == name)
+            .map(|(handle, _)| handle);
+        if let Some(actor) = actor {
+            self.remove_actor(engine, actor).await;
+        }
+        self.respawn_list
+            .retain(|entry| entry.descriptor.name.as_ref() != Some(&name));
+        self.leader_board.remove_actor(&name);
+        self.notify(format!("Bot {} was kicked!", name));
     }
 
     async fn remove_actor(&mut self, engine: &mut GameEngine, actor: Handle<Actor>) {
@@ -538,37 +1854,6 @@ impl Level {
         }
     }
 
-    async fn spawn_player(&mut self, engine: &mut GameEngine) -> Handle<Actor> {
-        let index = self.find_suitable_spawn_point(engine);
-        let spawn_position = self
-            .spawn_points
-            .get(index)
-            .map_or(Vec3::ZERO, |pt| pt.position);
-        let scene = &mut engine.scenes[self.scene];
-        if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
-            spectator_camera.set_enabled(false);
-        }
-        let mut player = Player::new(scene, self.sender.as_ref().unwrap().clone());
-        if let Some(control_scheme) = self.control_scheme.as_ref() {
-            player.set_control_scheme(control_scheme.clone());
-        }
-        self.player = self.actors.add(Actor::Player(player));
-        self.actors
-            .get_mut(self.player)
-            .set_position(&mut scene.physics, spawn_position);
-
-        self.give_new_weapon(engine, self.player, WeaponKind::M4)
-            .await;
-        self.give_new_weapon(engine, self.player, WeaponKind::Ak47)
-            .await;
-        self.give_new_weapon(engine, self.player, WeaponKind::PlasmaRifle)
-            .await;
-        self.give_new_weapon(engine, self.player, WeaponKind::RocketLauncher)
-            .await;
-
-        self.player
-    }
-
     async fn give_item(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: ItemKind) {
         if self.actors.contains(actor) {
             let character = self.actors.get_mut(actor);
@@ -613,10 +1898,36 @@ impl Level {
                         }
                     }
                 }
+                ItemKind::Silencer
+                | ItemKind::ExtendedMag
+                | ItemKind::Scope
+                | ItemKind::LaserSight => {
+                    // Attachment is mounted on weapon in hands, it is wasted if there is none.
+                    let weapon = character.current_weapon();
+                    if let Some(attachment) = kind.attachment() {
+                        self.attach(engine, weapon, attachment).await;
+                    }
+                }
+                ItemKind::Overshield => character.give_shield(),
             }
         }
     }
 
+    async fn attach(
+        &mut self,
+        engine: &mut GameEngine,
+        weapon: Handle<Weapon>,
+        kind: AttachmentKind,
+    ) {
+        if self.weapons.contains(weapon) && !self.weapons[weapon].has_attachment(kind) {
+            let scene = &mut engine.scenes[self.scene];
+            let mount = self.weapons[weapon].attachment_mount(&scene.graph);
+            let attachment =
+                Attachment::new(kind, engine.resource_manager.clone(), scene, mount).await;
+            self.weapons[weapon].attach(attachment, &mut scene.graph);
+        }
+    }
+
     async fn pickup_item(
         &mut self,
         engine: &mut GameEngine,
@@ -636,8 +1947,22 @@ impl Level {
 
             let scene = &mut engine.scenes[self.scene];
             let position = item.position(&scene.graph);
+            self.heatmap
+                .add(HeatmapEventKind::Pickup, position, self.time);
             item.pick_up();
+            if self.rounds.is_some() {
+                if let Some(text) = duel::pickup_callout(item) {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::AddNotification { text })
+                        .unwrap();
+                }
+            }
             let kind = item.get_kind();
+            if let Some(scripts) = self.scripts.as_mut() {
+                scripts.on_item_pickup(&self.actors.get(actor).name, kind);
+            }
             self.sender
                 .as_ref()
                 .unwrap()
@@ -647,6 +1972,7 @@ impl Level {
                     gain: 1.0,
                     rolloff_factor: 3.0,
                     radius: 2.0,
+                    category: SoundCategory::Other,
                 })
                 .unwrap();
             self.give_item(engine, actor, kind).await;
@@ -664,40 +1990,86 @@ impl Level {
         basis: Mat3,
     ) {
         let scene = &mut engine.scenes[self.scene];
-        let projectile = Projectile::new(
-            kind,
-            engine.resource_manager.clone(),
-            scene,
-            direction,
-            position,
-            owner,
-            initial_velocity,
-            self.sender.as_ref().unwrap().clone(),
-            basis,
-        )
-        .await;
-        self.projectiles.add(projectile);
-    }
-
-    async fn shoot_weapon(
-        &mut self,
-        engine: &mut GameEngine,
+        if let Err(e) = self
+            .projectiles
+            .spawn(
+                kind,
+                engine.resource_manager.clone(),
+                scene,
+                direction,
+                position,
+                owner,
+                initial_velocity,
+                self.sender.as_ref().unwrap().clone(),
+                basis,
+            )
+            .await
+        {
+            Log::writeln(format!("WARNING: Projectile {:?} skipped: {}", kind, e));
+        }
+    }
+
+    async fn shoot_weapon(
+        &mut self,
+        engine: &mut GameEngine,
         weapon_handle: Handle<Weapon>,
         initial_velocity: Vec3,
         time: GameTime,
         direction: Option<Vec3>,
     ) {
         if self.weapons.contains(weapon_handle) {
+            // Flag carrier can't use rocket launcher.
+            let weapon = &self.weapons[weapon_handle];
+            if weapon.get_kind() == WeaponKind::RocketLauncher
+                && self.flags.is_carrier(weapon.owner())
+            {
+                return;
+            }
+            // Skull carrier can't shoot, it fights only in melee.
+            if let Some(oddball) = self.oddball.as_ref() {
+                if oddball.is_carrier(weapon.owner()) {
+                    return;
+                }
+            }
+            // Time trial has no combat.
+            if self.options.is_time_trial() {
+                return;
+            }
+
             let scene = &mut engine.scenes[self.scene];
             let weapon = &mut self.weapons[weapon_handle];
             if weapon.try_shoot(scene, time) {
+                if self.actors.contains(weapon.owner()) {
+                    let name = &self.actors.get(weapon.owner()).name;
+                    self.leader_board.add_shot(name, weapon.get_kind());
+                }
                 let kind = weapon.definition.projectile;
                 let position = weapon.get_shot_position(&scene.graph);
+
+                // Bots that hear the shot come to check what's going on.
+                let noise_radius = weapon.noise_radius();
+                for (handle, actor) in self.actors.pair_iter_mut() {
+                    if let Actor::Bot(bot) = actor {
+                        if handle != weapon.owner()
+                            && bot.position(&scene.physics).distance(&position) <= noise_radius
+                        {
+                            bot.set_point_of_interest(position, time);
+                        }
+                    }
+                }
+
                 let direction = direction
                     .unwrap_or_else(|| weapon.get_shot_direction(&scene.graph))
                     .normalized()
                     .unwrap_or_else(|| Vec3::LOOK);
                 let basis = weapon.world_basis(&scene.graph);
+                self.effects.spawn(
+                    EffectKind::MuzzleFlash,
+                    &mut scene.graph,
+                    engine.resource_manager.clone(),
+                    position,
+                    self.quality.max_particle_effects,
+                );
                 self.create_projectile(
                     engine,
                     kind,
@@ -716,61 +2088,41 @@ impl Level {
         self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph)
     }
 
-    fn find_suitable_spawn_point(&self, engine: &mut GameEngine) -> usize {
-        // Find spawn point with least amount of enemies nearby.
-        let scene = &mut engine.scenes[self.scene];
-        let mut index = rand::thread_rng().gen_range(0, self.spawn_points.len());
-        let mut max_distance = -std::f32::MAX;
-        for (i, pt) in self.spawn_points.iter().enumerate() {
-            let mut sum_distance = 0.0;
-            for actor in self.actors.iter() {
-                let position = actor.position(&scene.physics);
-                sum_distance += pt.position.distance(&position);
-            }
-            if sum_distance > max_distance {
-                max_distance = sum_distance;
-                index = i;
-            }
-        }
-        index
-    }
-
-    async fn spawn_bot(
-        &mut self,
-        engine: &mut GameEngine,
-        kind: BotKind,
-        name: Option<String>,
-    ) -> Handle<Actor> {
-        let index = self.find_suitable_spawn_point(engine);
-        let spawn_position = self
-            .spawn_points
-            .get(index)
-            .map_or(Vec3::ZERO, |pt| pt.position);
-
-        let bot = self.add_bot(engine, kind, spawn_position, name).await;
-
-        self.sender
-            .as_ref()
-            .unwrap()
-            .send(Message::AddNotification {
-                text: format!("Bot {} spawned!", self.actors.get(bot).name),
-            })
-            .unwrap();
-
-        bot
-    }
-
+    #[allow(clippy::too_many_arguments)]
     fn damage_actor(
         &mut self,
-        engine: &GameEngine,
+        engine: &mut GameEngine,
         actor: Handle<Actor>,
         who: Handle<Actor>,
         amount: f32,
+        source: DamageSource,
+        impulse: Vec3,
         time: GameTime,
     ) {
         if self.actors.contains(actor)
             && (who.is_none() || who.is_some() && self.actors.contains(who))
         {
+            // Push goes before damage modifiers, so rockets throw their owner up even when
+            // self damage is off - this is how rocket jumps are done.
+            if impulse.len() > 0.0 {
+                let body = engine.scenes[self.scene]
+                    .physics
+                    .borrow_body_mut(self.actors.get(actor).get_body());
+                body.set_velocity(body.get_velocity() + impulse.scale(time.delta));
+            }
+
+            // Handicap scales damage dealt to others, self damage stays the same.
+            let amount = if who.is_some() && who != actor {
+                amount * self.actors.get(who).handicap().damage
+            } else {
+                amount
+            };
+            let hit = self.damage_modifiers.apply(actor, who, amount);
+            if hit.amount <= 0.0 {
+                return;
+            }
+            let amount = hit.amount;
+
             let mut who_name = Default::default();
             let message = if who.is_some() {
                 who_name = self.actors.get(who).name.clone();
@@ -790,22 +2142,925 @@ impl Level {
                 .send(Message::AddNotification { text: message })
                 .unwrap();
 
-            let who_position = if who.is_some() {
-                let scene = &engine.scenes[self.scene];
-                Some(self.actors.get(who).position(&scene.physics))
-            } else {
-                None
-            };
-            let actor = self.actors.get_mut(actor);
-            if let Actor::Bot(bot) = actor {
-                if let Some(who_position) = who_position {
-                    bot.set_point_of_interest(who_position, time);
+            let scene = &engine.scenes[self.scene];
+            let (who_position, who_weapon) = if who.is_some() {
+                let who = self.actors.get(who);
+                let weapon = who.current_weapon();
+                (
+                    Some(who.position(&scene.physics)),
+                    if self.weapons.contains(weapon) {
+                        Some(self.weapons[weapon].get_kind())
+                    } else {
+                        None
+                    },
+                )
+            } else {
+                (None, None)
+            };
+            let victim_name = self.actors.get(actor).name.clone();
+            let victim_position = self.actors.get(actor).position(&scene.physics);
+            let is_player = actor == self.player;
+            let victim = actor;
+
+            // Only damage that was really taken counts, overkill would inflate statistics.
+            if who.is_some() && who != victim && !self.actors.get(victim).is_dead() {
+                let dealt = amount.min(self.actors.get(victim).get_health()).max(0.0);
+                self.leader_board.add_damage(&who_name, dealt);
+                let now = self.time;
+                self.damage_history
+                    .retain(|record| now - record.time < ASSIST_TIME);
+                self.damage_history.push(DamageRecord {
+                    victim,
+                    attacker: who_name.clone(),
+                    time: now,
+                });
+            }
+
+            let drained = hit.drain * amount.min(self.actors.get(victim).get_health()).max(0.0);
+
+            if is_player && !self.actors.get(victim).is_dead() {
+                if self.damage_log.len() >= DAMAGE_LOG_SIZE {
+                    self.damage_log.remove(0);
+                }
+                self.damage_log.push(DamageLogEntry {
+                    attacker: if who.is_some() && who != victim {
+                        Some(who_name.clone())
+                    } else {
+                        None
+                    },
+                    source,
+                    amount,
+                    time: self.time,
+                });
+            }
+
+            let actor = self.actors.get_mut(actor);
+            if let Actor::Bot(bot) = actor {
+                if let Some(who_position) = who_position {
+                    bot.set_point_of_interest(who_position, time);
+                }
+            }
+            let was_dead = actor.is_dead();
+            actor.damage(amount);
+            if !was_dead && actor.is_dead() {
+                // Everyone else who damaged victim recently gets an assist.
+                let mut assists = Vec::<String>::new();
+                for record in self.damage_history.iter() {
+                    if record.victim == victim
+                        && record.attacker != who_name
+                        && !assists.contains(&record.attacker)
+                    {
+                        assists.push(record.attacker.clone());
+                    }
+                }
+                self.damage_history.retain(|record| record.victim != victim);
+                for assist in assists.iter() {
+                    self.leader_board.add_assist(assist);
+                }
+
+                self.heatmap
+                    .add(HeatmapEventKind::Death, victim_position, self.time);
+                if let Some(director) = self.director.as_mut() {
+                    if who.is_some() && who != victim {
+                        director.on_kill(who, self.time);
+                    }
+                }
+                if let Some(who_position) = who_position.filter(|_| who != victim) {
+                    self.heatmap
+                        .add(HeatmapEventKind::Kill, who_position, self.time);
+                }
+
+                // Kill feed. Own explosion kills without giving a frag.
+                let mut text = if who == victim {
+                    format!("{} killed themselves", victim_name)
+                } else if who.is_some() {
+                    format!("{} killed {}", who_name, victim_name)
+                } else {
+                    format!("{} died", victim_name)
+                };
+                if !assists.is_empty() {
+                    text += &format!(" (assist: {})", assists.join(", "));
+                }
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::AddNotification { text })
+                    .unwrap();
+                if is_player {
+                    self.player_death = Some(PlayerDeath {
+                        killer: if who.is_some() && who != victim {
+                            Some(who_name.clone())
+                        } else {
+                            None
+                        },
+                        weapon: who_weapon,
+                        distance: who_position
+                            .map(|position| position.distance(&victim_position))
+                            .unwrap_or_default(),
+                        damage_report: self.damage_report(),
+                    });
+                    self.damage_log.clear();
+                }
+                if self.bot_drops {
+                    self.drop_bot_loot(victim, victim_position);
+                }
+                if who.is_some() && who != victim {
+                    let streak = self.leader_board.add_frag(&who_name);
+                    if let Some(scripts) = self.scripts.as_mut() {
+                        scripts.on_kill(&who_name, &victim_name);
+                    }
+                    // Zombies share one name and one streak, so they get no rewards.
+                    let is_zombie =
+                        matches!(self.actors.get(who), Actor::Bot(bot) if bot.is_zombie());
+                    if self.streak_rewards && !is_zombie {
+                        if let Some(reward) = StreakReward::for_streak(streak) {
+                            self.give_streak_reward(engine, who, streak, reward, time);
+                        }
+                    }
+                }
+            }
+
+            if drained > 0.0 && !self.actors.get(who).is_dead() {
+                self.actors.get_mut(who).heal(drained);
+            }
+        }
+    }
+
+    /// Drops ammo for weapon of killed bot and maybe health, both despawn after a while.
+    fn drop_bot_loot(&self, bot: Handle<Actor>, position: Vec3) {
+        let actor = self.actors.get(bot);
+        if !matches!(actor, Actor::Bot(_)) {
+            return;
+        }
+        let mut drops = Vec::new();
+        let weapon = actor.current_weapon();
+        if self.weapons.contains(weapon) {
+            match self.weapons[weapon].get_kind() {
+                WeaponKind::M4 => drops.push(ItemKind::M4Ammo),
+                WeaponKind::Ak47 => drops.push(ItemKind::Ak47Ammo),
+                WeaponKind::PlasmaRifle => drops.push(ItemKind::Plasma),
+                WeaponKind::RocketLauncher => (),
+            }
+        }
+        if random::rng().gen_bool(BOT_DROP_HEALTH_CHANCE) {
+            drops.push(ItemKind::Medkit);
+        }
+        let sender = self.sender.as_ref().unwrap();
+        for (i, kind) in drops.into_iter().enumerate() {
+            // Side by side, so items don't overlap.
+            let offset = Vec3::new(i as f32 * 0.5, 0.0, 0.0);
+            sender
+                .send(Message::SpawnItem {
+                    kind,
+                    position: position + offset,
+                    adjust_height: true,
+                    lifetime: Some(BOT_DROP_LIFETIME),
+                })
+                .unwrap();
+        }
+    }
+
+    fn give_streak_reward(
+        &mut self,
+        engine: &GameEngine,
+        actor: Handle<Actor>,
+        streak: u32,
+        reward: StreakReward,
+        time: GameTime,
+    ) {
+        let sender = self.sender.as_ref().unwrap();
+        sender
+            .send(Message::AddNotification {
+                text: format!(
+                    "{} - {} kills in a row! {}",
+                    self.actors.get(actor).name,
+                    streak,
+                    reward.name()
+                ),
+            })
+            .unwrap();
+
+        match reward {
+            StreakReward::Haste => self
+                .actors
+                .get_mut(actor)
+                .give_haste(kill_streak::HASTE_TIME),
+            StreakReward::RadarPing => {
+                let physics = &engine.scenes[self.scene].physics;
+                let owner = self.actors.get(actor);
+                let position = owner.position(physics);
+                let mut enemies = self
+                    .actors
+                    .pair_iter()
+                    .filter(|(handle, other)| {
+                        *handle != actor
+                            && !other.is_dead()
+                            && (other.team() == Team::None || other.team() != owner.team())
+                    })
+                    .map(|(_, other)| {
+                        let other_position = other.position(physics);
+                        (
+                            other.name.clone(),
+                            other_position,
+                            other_position.distance(&position),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                enemies.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+                enemies.truncate(kill_streak::RADAR_PING_COUNT);
+
+                if actor == self.player {
+                    let text = enemies
+                        .iter()
+                        .map(|(name, _, distance)| format!("{} {:.0} m", name, distance))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    sender
+                        .send(Message::AddNotification {
+                            text: format!("Radar: {}", text),
+                        })
+                        .unwrap();
+                } else if let Some((_, closest, _)) = enemies.first() {
+                    let closest = *closest;
+                    if let Actor::Bot(bot) = self.actors.get_mut(actor) {
+                        bot.set_point_of_interest(closest, time);
+                    }
+                }
+            }
+            StreakReward::AmmoRefill => {
+                for &weapon in self.actors.get(actor).weapons() {
+                    if self.weapons.contains(weapon) {
+                        self.weapons[weapon].refill_ammo();
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_flags(&mut self, engine: &mut GameEngine, time: GameTime) {
+        self.flags.update(
+            &mut engine.scenes[self.scene],
+            &self.actors,
+            &mut self.leader_board,
+            self.sender.as_ref().unwrap(),
+            time.delta,
+        );
+        let flags = &self.flags;
+        for (handle, actor) in self.actors.pair_iter_mut() {
+            actor.set_carries_flag(flags.is_carrier(handle));
+        }
+        // Bots of a team are split between attack and defense in order of their handles, so
+        // roles stay the same while nobody joins or leaves the team.
+        for &team in [Team::Red, Team::Blue].iter() {
+            let count = self
+                .actors
+                .iter()
+                .filter(|actor| matches!(actor, Actor::Bot(_)) && actor.team() == team)
+                .count();
+            let mut index = 0;
+            for actor in self.actors.iter_mut() {
+                if actor.team() != team {
+                    continue;
+                }
+                if let Actor::Bot(bot) = actor {
+                    bot.set_objective_role(ObjectiveRole::of_member(index, count));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    fn update_oddball(&mut self, engine: &mut GameEngine, dt: f32) {
+        if let Some(oddball) = self.oddball.as_mut() {
+            oddball.update(
+                &mut engine.scenes[self.scene],
+                &self.actors,
+                &mut self.leader_board,
+                self.sender.as_ref().unwrap(),
+                dt,
+            );
+        }
+    }
+
+    fn update_hill(&mut self, engine: &mut GameEngine, dt: f32) {
+        if let Some(hill) = self.hill.as_mut() {
+            hill.update(
+                &mut engine.scenes[self.scene],
+                &self.actors,
+                &mut self.leader_board,
+                self.sender.as_ref().unwrap(),
+                dt,
+            );
+        }
+    }
+
+    fn update_race(&mut self, engine: &mut GameEngine, dt: f32) {
+        let race = match self.race.as_mut() {
+            Some(race) => race,
+            None => return,
+        };
+        let scene = &mut engine.scenes[self.scene];
+        let player = if self.player.is_some() && !self.actors.get(self.player).is_dead() {
+            Some(self.actors.get(self.player).position(&scene.physics))
+        } else {
+            None
+        };
+        let sender = self.sender.as_ref().unwrap();
+        let text = match race.update(&mut scene.graph, player, dt) {
+            Some(RaceEvent::Started) => "Go!".to_owned(),
+            Some(RaceEvent::Checkpoint { index, count, time }) => {
+                format!("Checkpoint {}/{} - {:.2} s", index, count, time)
+            }
+            Some(RaceEvent::Finished { time, best }) => {
+                if best {
+                    if let Some(run) = race.best_run() {
+                        sender
+                            .send(Message::RecordRun {
+                                map: self.map.clone(),
+                                run: run.clone(),
+                            })
+                            .unwrap();
+                    }
+                    format!("Finished in {:.2} s - new best time!", time)
+                } else {
+                    format!("Finished in {:.2} s", time)
+                }
+            }
+            None => return,
+        };
+        sender.send(Message::AddNotification { text }).unwrap();
+    }
+
+    /// Sets best run of the map in time trial, its ghost is replayed in next runs.
+    pub fn set_best_run(&mut self, best: Option<BestRun>) {
+        if let Some(race) = self.race.as_mut() {
+            race.set_best_run(best);
+        }
+    }
+
+    /// Text that tells state of objective of oddball, king of the hill or time trial for HUD,
+    /// `None` in other modes.
+    pub fn objective_marker(&self, engine: &GameEngine) -> Option<String> {
+        self.oddball_marker(engine)
+            .or_else(|| self.hill_marker(engine))
+            .or_else(|| self.race_marker())
+    }
+
+    /// Tells time of current run, passed checkpoints and best time.
+    fn race_marker(&self) -> Option<String> {
+        let race = self.race.as_ref()?;
+        let best = race
+            .best_time()
+            .map_or_else(|| "-".to_owned(), |time| format!("{:.2} s", time));
+        if race.is_running() {
+            let (passed, total) = race.checkpoints();
+            Some(format!(
+                "Time: {:.2} s. Checkpoints: {}/{}. Best: {}",
+                race.time(),
+                passed,
+                total,
+                best
+            ))
+        } else {
+            Some(format!("Leave the start gate to begin. Best: {}", best))
+        }
+    }
+
+    fn hold_time(&self, name: &str) -> u32 {
+        self.leader_board
+            .values()
+            .get(name)
+            .map_or(0, |score| score.hold_time)
+    }
+
+    /// Tells who is on the hill, how far it is and when it moves.
+    fn hill_marker(&self, engine: &GameEngine) -> Option<String> {
+        let hill = self.hill.as_ref()?;
+        if self.player.is_none() {
+            return None;
+        }
+        let physics = &engine.scenes[self.scene].physics;
+        let player = self.actors.get(self.player);
+        let distance = hill.position().distance(&player.position(physics));
+        let state = match hill.state() {
+            HillState::Held(holder) if holder == self.player => "You hold the hill!".to_owned(),
+            HillState::Held(holder) if self.actors.contains(holder) => {
+                format!("Hill held by {}", self.actors.get(holder).name)
+            }
+            HillState::Contested => "Hill contested".to_owned(),
+            _ => "Hill is free".to_owned(),
+        };
+        Some(format!(
+            "{} ({:.0} m), moves in {:.0} s. Your time: {}/{} s",
+            state,
+            distance,
+            hill.time_to_move().ceil(),
+            self.hold_time(&player.name),
+            self.options.score_limit()
+        ))
+    }
+
+    /// Text that tells where skull of oddball mode is and how long player held it.
+    fn oddball_marker(&self, engine: &GameEngine) -> Option<String> {
+        let oddball = self.oddball.as_ref()?;
+        if self.player.is_none() {
+            return None;
+        }
+        let limit = self.options.score_limit();
+        let physics = &engine.scenes[self.scene].physics;
+        let player = self.actors.get(self.player);
+        let distance = oddball.position().distance(&player.position(physics));
+        let text = match oddball.state() {
+            FlagState::Carried(carrier) if carrier == self.player => {
+                format!(
+                    "You hold the skull! {}/{} s",
+                    self.hold_time(&player.name),
+                    limit
+                )
+            }
+            FlagState::Carried(carrier) if self.actors.contains(carrier) => {
+                let name = &self.actors.get(carrier).name;
+                format!(
+                    "Skull carrier: {} ({:.0} m) {}/{} s",
+                    name,
+                    distance,
+                    self.hold_time(name),
+                    limit
+                )
+            }
+            _ => format!(
+                "Skull is free ({:.0} m). Your time: {}/{} s",
+                distance,
+                self.hold_time(&player.name),
+                limit
+            ),
+        };
+        Some(text)
+    }
+
+    /// Text that marks carriers of flag of player's team, `None` if nobody carries it.
+    pub fn flag_carrier_marker(&self, engine: &GameEngine) -> Option<String> {
+        if self.player.is_none() {
+            return None;
+        }
+        let physics = &engine.scenes[self.scene].physics;
+        let player = self.actors.get(self.player);
+        let position = player.position(physics);
+        self.flags
+            .iter()
+            .filter(|flag| flag.team() == player.team())
+            .filter_map(|flag| match flag.state() {
+                FlagState::Carried(carrier) if self.actors.contains(carrier) => {
+                    let carrier = self.actors.get(carrier);
+                    Some(format!(
+                        "Flag carrier: {} ({:.0} m)",
+                        carrier.name,
+                        carrier.position(physics).distance(&position)
+                    ))
+                }
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Returns closest item or interactable under crosshair of player within interaction
+    /// distance. Used both by key prompts and by interact button, so prompt always tells
+    /// what button will do.
+    fn look_target(&self, scene: &Scene) -> Option<LookTarget> {
+        if self.player.is_none() {
+            return None;
+        }
+        let camera = match self.actors.get(self.player) {
+            Actor::Player(player) => &scene.graph[player.camera()],
+            _ => return None,
+        };
+        let begin = camera.global_position();
+        let end = begin
+            + camera
+                .look_vector()
+                .normalized()
+                .unwrap_or_default()
+                .scale(INTERACTION_DISTANCE);
+        let item = self
+            .items
+            .pair_iter()
+            .filter(|(_, item)| !item.is_picked_up())
+            .map(|(handle, item)| (handle, item.position(&scene.graph)))
+            .filter(|&(_, position)| {
+                projectile::distance_to_segment(position, begin, end) <= INTERACTION_RADIUS
+            })
+            .map(|(handle, position)| (handle, position.distance(&begin)))
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let interactable = self.interactables.find(begin, end);
+        match (item, interactable) {
+            (Some((_, item_distance)), Some((index, distance))) if distance < item_distance => {
+                Some(LookTarget::Interactable(index))
+            }
+            (Some((item, _)), _) => Some(LookTarget::Item(item)),
+            (None, Some((index, _))) => Some(LookTarget::Interactable(index)),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns prompt for thing under crosshair of player, if player can interact with it.
+    /// Player that stands in front of a low passage is reminded to crouch.
+    pub fn key_prompt(&self, engine: &GameEngine) -> Option<KeyPrompt> {
+        let scene = &engine.scenes[self.scene];
+        let control_scheme = self.control_scheme.as_ref()?.borrow();
+        let target = match self.look_target(scene) {
+            Some(target) => target,
+            None => {
+                return if self.needs_crouch(scene) {
+                    Some(KeyPrompt {
+                        button: control_scheme.crouch.button,
+                        action: "Crouch".to_owned(),
+                    })
+                } else {
+                    None
+                };
+            }
+        };
+        let button = control_scheme.interact.button;
+        let action = match target {
+            LookTarget::Item(item) => {
+                format!("Pick up {}", self.items.get(item).get_kind().name())
+            }
+            LookTarget::Interactable(index) => self.interactables.get(index).action().to_owned(),
+        };
+        Some(KeyPrompt { button, action })
+    }
+
+    /// Returns true if player stands up near a crouch zone.
+    fn needs_crouch(&self, scene: &Scene) -> bool {
+        if self.player.is_none() {
+            return false;
+        }
+        match self.actors.get(self.player) {
+            Actor::Player(player) => {
+                !player.is_dead()
+                    && !player.is_crouching()
+                    && self
+                        .path_planner
+                        .is_crouch_only(player.position(&scene.physics), CROUCH_HINT_DISTANCE)
+            }
+            _ => false,
+        }
+    }
+
+    /// Uses thing under crosshair when player presses interact button. Items can be picked up
+    /// this way from a distance, weapons can be picked up only this way.
+    fn update_interaction(&mut self, engine: &GameEngine) {
+        if self.player.is_none() {
+            return;
+        }
+        let interact = match self.actors.get_mut(self.player) {
+            Actor::Player(player) => player.take_interact_request(),
+            _ => false,
+        };
+        if interact {
+            match self.look_target(&engine.scenes[self.scene]) {
+                Some(LookTarget::Item(item)) => self
+                    .sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::PickUpItem {
+                        actor: self.player,
+                        item,
+                    })
+                    .unwrap(),
+                Some(LookTarget::Interactable(index)) => self.interactables.interact(index),
+                None => (),
+            }
+        }
+    }
+
+    /// Places mine on request of player and lets mines react to actors around them.
+    fn update_deployables(&mut self, engine: &mut GameEngine, dt: f32) {
+        if self.player.is_some() {
+            let request = match self.actors.get_mut(self.player) {
+                Actor::Player(player) => player.take_place_mine_request() && !player.is_dead(),
+                _ => false,
+            };
+            if request {
+                self.place_mine(engine);
+            }
+        }
+        self.deployables.update(
+            &mut engine.scenes[self.scene],
+            &self.actors,
+            self.sender.as_ref().unwrap(),
+            dt,
+        );
+    }
+
+    /// Player strikes nearest enemy in front of it on request, this is how skull carrier
+    /// fights in oddball.
+    fn update_melee(&mut self, engine: &GameEngine) {
+        if self.player.is_none() {
+            return;
+        }
+        let request = match self.actors.get_mut(self.player) {
+            Actor::Player(player) => player.take_melee_request() && !player.is_dead(),
+            _ => false,
+        };
+        // Time trial has no combat.
+        if !request || self.options.is_time_trial() {
+            return;
+        }
+
+        let scene = &engine.scenes[self.scene];
+        let (position, direction, team) = match self.actors.get(self.player) {
+            Actor::Player(player) => (
+                player.position(&scene.physics),
+                scene.graph[player.camera()]
+                    .look_vector()
+                    .normalized()
+                    .unwrap_or(Vec3::LOOK),
+                player.team(),
+            ),
+            _ => return,
+        };
+        let target = self
+            .actors
+            .pair_iter()
+            .filter(|(handle, actor)| {
+                *handle != self.player && !actor.is_dead() && team.is_enemy(actor.team())
+            })
+            .filter_map(|(handle, actor)| {
+                let offset = actor.position(&scene.physics) - position;
+                let in_front = offset
+                    .normalized()
+                    .map_or(true, |dir| dir.dot(&direction) >= MELEE_CONE);
+                if in_front && offset.len() <= MELEE_RANGE {
+                    Some((handle, offset.len()))
+                } else {
+                    None
+                }
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(handle, _)| handle);
+        if let Some(target) = target {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::DamageActor {
+                    actor: target,
+                    who: self.player,
+                    amount: MELEE_DAMAGE,
+                    source: DamageSource::Melee,
+                    impulse: Vec3::ZERO,
+                })
+                .unwrap();
+        }
+    }
+
+    /// Places mine under feet of player, laser of the mine points where player looks.
+    fn place_mine(&mut self, engine: &mut GameEngine) {
+        let scene = &engine.scenes[self.scene];
+        let (position, direction, team) = match self.actors.get(self.player) {
+            Actor::Player(player) => (
+                player.position(&scene.physics),
+                scene.graph[player.camera()].look_vector(),
+                player.team(),
+            ),
+            _ => return,
+        };
+        let ground = self.pick(engine, position, position - Vec3::new(0.0, 10.0, 0.0));
+        let resource_manager = engine.resource_manager.clone();
+        let scene = &mut engine.scenes[self.scene];
+        let mine = Mine::new(
+            self.player,
+            team,
+            ground + Vec3::new(0.0, 0.05, 0.0),
+            direction,
+            scene,
+            resource_manager,
+        );
+        self.deployables.place(mine, &mut scene.graph);
+    }
+
+    fn disarm_mine(&mut self, engine: &mut GameEngine, mine: Handle<Mine>, who: Handle<Actor>) {
+        let graph = &mut engine.scenes[self.scene].graph;
+        if self.deployables.remove(mine, graph).is_some() && self.actors.contains(who) {
+            let text = format!("{} disarmed a mine!", self.actors.get(who).name);
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AddNotification { text })
+                .unwrap();
+        }
+    }
+
+    fn update_interactables(&mut self, scene: &mut Scene, dt: f32) {
+        let bot_positions = self
+            .actors
+            .iter()
+            .filter(|actor| matches!(actor, Actor::Bot(_)))
+            .map(|actor| actor.position(&scene.physics))
+            .collect::<Vec<_>>();
+        self.interactables
+            .update(&mut scene.physics, &bot_positions, dt);
+    }
+
+    /// Moves targets of shooting range and gives infinite ammo to player in practice mode.
+    fn update_practice(&mut self, engine: &mut GameEngine, time: GameTime) {
+        if !self.options.is_practice() {
+            return;
+        }
+        let scene = &mut engine.scenes[self.scene];
+        self.shooting_range
+            .update(&mut scene.physics, self.time, time.delta);
+        if self.player.is_some() {
+            for &weapon in self.actors.get(self.player).weapons() {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].refill_ammo();
+                }
+            }
+        }
+    }
+
+    /// Position of player that attracts magnetic items, player must be alive and moving.
+    fn item_magnet(&self, scene: &Scene, dt: f32) -> Option<Vec3> {
+        if !self.pickup_magnetism || !self.actors.contains(self.player) {
+            return None;
+        }
+        let player = self.actors.get(self.player);
+        let mut velocity = scene.physics.borrow_body(player.get_body()).get_velocity();
+        velocity.y = 0.0;
+        if player.is_dead() || velocity.len() < ITEM_MAGNET_MIN_SPEED * dt {
+            None
+        } else {
+            Some(player.position(&scene.physics))
+        }
+    }
+
+    fn update_tutorial(&mut self, engine: &GameEngine, dt: f32) {
+        if let (Some(tutorial), true) = (self.tutorial.as_mut(), self.player.is_some()) {
+            let physics = &engine.scenes[self.scene].physics;
+            let player = self.actors.get(self.player);
+            tutorial.update(&PlayerState {
+                position: player.position(physics),
+                vertical_velocity: physics.borrow_body(player.get_body()).get_velocity().y / dt,
+                current_weapon: player.current_weapon(),
+            });
+        }
+    }
+
+    /// Returns prompt of current step of tutorial, only in tutorial mode. Prompt shows keys
+    /// of current control scheme.
+    pub fn tutorial_prompt(&self) -> Option<String> {
+        match (self.tutorial.as_ref(), self.control_scheme.as_ref()) {
+            (Some(tutorial), Some(control_scheme)) => {
+                Some(tutorial.prompt(&control_scheme.borrow()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reports goal of tutorial reached by player, if there is a tutorial.
+    fn tutorial_goal_reached(&mut self, actor: Handle<Actor>, goal: TutorialGoal) {
+        if let (Some(tutorial), true) = (self.tutorial.as_mut(), actor == self.player) {
+            tutorial.on_goal_reached(goal);
+        }
+    }
+
+    /// Returns DPS meter and last hit of shooting range, only in practice mode.
+    pub fn practice_report(&self) -> Option<String> {
+        if self.options.is_practice() {
+            Some(self.shooting_range.report())
+        } else {
+            None
+        }
+    }
+
+    fn update_haste(&mut self, dt: f32) {
+        for actor in self.actors.iter_mut() {
+            actor.update_haste(dt);
+        }
+    }
+
+    fn update_shields(&mut self, engine: &GameEngine, dt: f32) {
+        let physics = &engine.scenes[self.scene].physics;
+        for actor in self.actors.iter_mut() {
+            actor.update_shield(physics, dt);
+        }
+    }
+
+    fn damage_prop(
+        &mut self,
+        engine: &mut GameEngine,
+        prop: Handle<DynamicProp>,
+        who: Handle<Actor>,
+        amount: f32,
+        impulse: Vec3,
+        dt: f32,
+    ) {
+        if !self.dynamic_props.contains(prop) {
+            return;
+        }
+
+        let scene = &mut engine.scenes[self.scene];
+        let dynamic_prop = self.dynamic_props.get_mut(prop);
+        dynamic_prop.push(&mut scene.physics, impulse.scale(dt));
+        if dynamic_prop.damage(amount, who) {
+            // Explosion is sent as a message, so barrels nearby will explode on next frames
+            // one after another instead of all at once.
+            let definition = dynamic_prop.definition();
+            let position = dynamic_prop.position(&scene.physics);
+            let sender = self.sender.as_ref().unwrap();
+            sender
+                .send(Message::Explosion {
+                    position,
+                    radius: definition.explosion_radius,
+                    damage: definition.explosion_damage,
+                    impulse: definition.explosion_impulse,
+                    knockback: definition.explosion_impulse,
+                    who: dynamic_prop.last_attacker(),
+                    source: DamageSource::Barrel,
+                })
+                .unwrap();
+            sender
+                .send(Message::CreateEffect {
+                    kind: EffectKind::Smoke,
+                    position,
+                })
+                .unwrap();
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(assets::sounds::impact::ROCKET),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 4.0,
+                    radius: 5.0,
+                    category: SoundCategory::Impact,
+                })
+                .unwrap();
+            self.dynamic_props.remove(prop, scene);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn explode(
+        &mut self,
+        engine: &mut GameEngine,
+        position: Vec3,
+        radius: f32,
+        damage: f32,
+        impulse: f32,
+        knockback: f32,
+        who: Handle<Actor>,
+        source: DamageSource,
+        time: GameTime,
+    ) {
+        // Actors and destructibles are found by same kind of query, positions in grids are
+        // from start of the frame.
+        let mut destructibles = Vec::new();
+        self.destructible_grid
+            .query_radius(position, radius, &mut destructibles);
+        let sender = self.sender.as_ref().unwrap();
+        for (destructible, destructible_position) in destructibles {
+            match destructible {
+                Destructible::Prop(prop) => {
+                    let (dir, distance) = (destructible_position - position).normalized_ex();
+                    let k = 1.0 - distance / radius;
+                    // Explosive barrels caught in the explosion explode too, one after another.
+                    sender
+                        .send(Message::DamageProp {
+                            prop,
+                            who,
+                            amount: damage * k,
+                            impulse: dir.unwrap_or(Vec3::UP).scale(impulse * k),
+                        })
+                        .unwrap();
                 }
+                Destructible::Projectile(projectile) => self.projectiles.detonate(projectile),
+                Destructible::Mine(mine) => self.deployables.detonate(mine),
             }
-            let was_dead = actor.is_dead();
-            actor.damage(amount);
-            if !was_dead && actor.is_dead() && who.is_some() {
-                self.leader_board.add_frag(who_name)
+        }
+
+        if damage > 0.0 || knockback > 0.0 {
+            let scene = &engine.scenes[self.scene];
+            let mut candidates = Vec::new();
+            self.actor_grid
+                .query_radius(position, radius, &mut candidates);
+            let mut victims = Vec::new();
+            for (handle, _) in candidates {
+                if !self.actors.contains(handle) {
+                    continue;
+                }
+                let actor = self.actors.get(handle);
+                let actor_position = actor.position(&scene.physics);
+                let distance = actor_position.distance(&position);
+                if distance < radius {
+                    let k = 1.0 - distance / radius;
+                    // Dead actors are pushed too, so corpses fly away from explosions.
+                    let amount = if actor.is_dead() { 0.0 } else { damage * k };
+                    let push = knockback(position, actor_position, knockback * k);
+                    victims.push((handle, amount, push));
+                }
+            }
+            for (actor, amount, push) in victims {
+                self.damage_actor(engine, actor, who, amount, source, push, time);
             }
         }
     }
@@ -824,16 +3079,21 @@ impl Level {
             position
         };
         let scene = &mut engine.scenes[self.scene];
-        let mut item = Item::new(
+        match Item::new(
             kind,
             position,
             scene,
             engine.resource_manager.clone(),
             self.sender.as_ref().unwrap().clone(),
         )
-        .await;
-        item.set_lifetime(lifetime);
-        self.items.add(item);
+        .await
+        {
+            Ok(mut item) => {
+                item.set_lifetime(lifetime);
+                self.items.add(item);
+            }
+            Err(e) => Log::writeln(format!("WARNING: Item {:?} skipped: {}", kind, e)),
+        }
     }
 
     pub fn time(&self) -> f32 {
@@ -841,54 +3101,77 @@ impl Level {
     }
 
     fn update_respawn(&mut self, time: GameTime) {
+        // In duel dead actors wait for next round, see `start_round`.
+        if self.rounds.is_some() {
+            return;
+        }
+
         // Respawn is done in deferred manner: we just gather all info needed
         // for respawn, wait some time and then re-create actor. Actor is spawned
         // by sending a message: this is needed because there are some other
         // systems that catches such messages and updates their own state.
         for respawn_entry in self.respawn_list.iter_mut() {
-            match respawn_entry {
-                RespawnEntry::Bot(v) => {
-                    v.time_left -= time.delta;
-                    if v.time_left <= 0.0 {
-                        self.sender
-                            .as_mut()
-                            .unwrap()
-                            .send(Message::SpawnBot {
-                                kind: v.kind,
-                                name: v.name.clone(),
-                            })
-                            .unwrap();
-                    }
-                }
-                RespawnEntry::Player(v) => {
-                    v.time_left -= time.delta;
-                    if v.time_left <= 0.0 {
-                        self.sender
-                            .as_mut()
-                            .unwrap()
-                            .send(Message::SpawnPlayer)
-                            .unwrap();
-                    }
-                }
+            respawn_entry.time_left -= time.delta;
+            // Without auto respawn player waits until it respawns by itself.
+            if !self.auto_respawn && respawn_entry.descriptor.kind == ActorKind::Player {
+                respawn_entry.time_left = respawn_entry.time_left.max(0.0);
+            } else if respawn_entry.time_left <= 0.0 {
+                self.sender
+                    .as_mut()
+                    .unwrap()
+                    .send(Message::SpawnActor {
+                        descriptor: respawn_entry.descriptor.clone(),
+                    })
+                    .unwrap();
             }
         }
 
-        self.respawn_list.retain(|entry| match entry {
-            RespawnEntry::Bot(v) => v.time_left >= 0.0,
-            RespawnEntry::Player(v) => v.time_left >= 0.0,
-        });
+        self.respawn_list.retain(|entry| entry.time_left >= 0.0);
     }
 
-    fn update_spectator_camera(&mut self, scene: &mut Scene) {
+    fn update_spectator_camera(&mut self, scene: &mut Scene, dt: f32) {
+        if self.spectator.is_none() && self.leader_board.is_eliminated(player::PLAYER_NAME) {
+            self.spectator = Some(Default::default());
+        }
+        let look_at = match self.director.as_mut().or(self.spectator.as_mut()) {
+            Some(director) => {
+                match director.update(&self.actors, &self.flags, &scene.physics, self.time, dt) {
+                    Some((position, look_at)) => {
+                        self.target_spectator_position = position;
+                        Some(look_at)
+                    }
+                    None => None,
+                }
+            }
+            None => None,
+        };
         if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
             let mut position = spectator_camera.global_position();
             position.follow(&self.target_spectator_position, 0.1);
             spectator_camera
                 .local_transform_mut()
                 .set_position(position);
+            if let Some(look) = look_at.and_then(|look_at| (look_at - position).normalized()) {
+                let yaw = look.x.atan2(look.z);
+                let pitch = (-look.y).max(-1.0).min(1.0).asin();
+                spectator_camera.local_transform_mut().set_rotation(
+                    Quat::from_axis_angle(Vec3::UP, yaw)
+                        * Quat::from_axis_angle(Vec3::RIGHT, pitch),
+                );
+            }
         }
     }
 
+    /// Turns match into attract mode - nobody plays it and spectator camera follows
+    /// interesting bots, see `spectator` module.
+    pub fn start_attract_mode(&mut self) {
+        self.director = Some(Default::default());
+    }
+
+    pub fn is_attract_mode(&self) -> bool {
+        self.director.is_some()
+    }
+
     fn update_death_zones(&mut self, scene: &Scene) {
         for (handle, actor) in self.actors.pair_iter_mut() {
             for death_zone in self.death_zones.iter() {
@@ -906,97 +3189,557 @@ impl Level {
         }
     }
 
-    fn update_game_ending(&self) {
-        if self.leader_board.is_match_over(&self.options) {
+    fn update_base_zones(&mut self, scene: &Scene, dt: f32) {
+        let sender = self.sender.as_ref().unwrap();
+        for zone in self.base_zones.iter_mut() {
+            zone.time_left = (zone.time_left - dt).max(0.0);
+            zone.cooldown = (zone.cooldown - dt).max(0.0);
+            if zone.time_left <= 0.0 {
+                zone.intruders.clear();
+                continue;
+            }
+
+            zone.tick_timer -= dt;
+            if zone.tick_timer > 0.0 {
+                continue;
+            }
+            zone.tick_timer = 1.0;
+
+            let mut intruders = Vec::new();
+            for (handle, actor) in self.actors.pair_iter() {
+                if actor.team() == zone.team
+                    || actor.is_dead()
+                    || !zone
+                        .bounds
+                        .is_contains_point(actor.position(&scene.physics))
+                {
+                    continue;
+                }
+                let seconds = zone
+                    .intruders
+                    .iter()
+                    .find(|(intruder, _)| *intruder == handle)
+                    .map_or(0, |(_, seconds)| *seconds)
+                    + 1;
+                intruders.push((handle, seconds));
+                if seconds == 1 && handle == self.player {
+                    sender
+                        .send(Message::AddNotification {
+                            text: format!("{:?} base is protected, leave it!", zone.team),
+                        })
+                        .unwrap();
+                }
+                sender
+                    .send(Message::DamageActor {
+                        actor: handle,
+                        who: Handle::NONE,
+                        amount: BASE_DAMAGE_GROWTH * seconds as f32,
+                        source: DamageSource::Environment,
+                        impulse: Vec3::ZERO,
+                    })
+                    .unwrap();
+            }
+            zone.intruders = intruders;
+        }
+    }
+
+    fn update_teleporters(&mut self, scene: &mut Scene) {
+        for actor in self.actors.iter_mut() {
+            let position = actor.position(&scene.physics);
+            if let Some(teleporter) = self
+                .teleporters
+                .iter()
+                .find(|teleporter| teleporter.bounds.is_contains_point(position))
+            {
+                actor.set_position(&mut scene.physics, teleporter.exit);
+            }
+        }
+    }
+
+    fn update_zombie_rush(&mut self, time: GameTime) {
+        if let Some(zombie_rush) = self.zombie_rush.as_mut() {
+            let alive_zombies = self
+                .actors
+                .iter()
+                .filter(
+                    |actor| matches!(actor, Actor::Bot(bot) if bot.is_zombie() && !bot.is_dead()),
+                )
+                .count();
+            zombie_rush.update(time.delta, alive_zombies, self.sender.as_ref().unwrap());
+        }
+    }
+
+    /// Removes dead actors over the limit without waiting for end of their death animation.
+    fn update_corpses(&mut self) {
+        let actors = &self.actors;
+        self.corpses
+            .retain(|(handle, _)| actors.contains(*handle) && actors.get(*handle).is_dead());
+        // Dead player is never removed, it watches own corpse until respawn.
+        for (handle, actor) in self.actors.pair_iter() {
+            if handle != self.player
+                && actor.is_dead()
+                && self.corpses.iter().all(|(corpse, _)| *corpse != handle)
+            {
+                self.corpses.push((handle, self.time));
+            }
+        }
+
+        self.corpses
+            .sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let excess = self.corpses.len().saturating_sub(self.quality.max_corpses);
+        for (handle, _) in self.corpses.drain(..excess) {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::RespawnActor { actor: handle })
+                .unwrap();
+        }
+    }
+
+    /// Position of player, or of spectator camera if player is dead.
+    fn viewer_position(&self, scene: &Scene) -> Vec3 {
+        if self.player.is_some() {
+            self.actors.get(self.player).position(&scene.physics)
+        } else {
+            scene.graph[self.spectator_camera].global_position()
+        }
+    }
+
+    fn update_grids(&mut self, scene: &Scene) {
+        self.actor_grid.rebuild(
+            self.actors
+                .pair_iter()
+                .map(|(handle, actor)| (handle, actor.position(&scene.physics))),
+        );
+        self.item_grid.rebuild(
+            self.items
+                .pair_iter()
+                .filter(|(_, item)| !item.is_picked_up())
+                .map(|(handle, item)| (handle, item.position(&scene.graph))),
+        );
+        let props = self
+            .dynamic_props
+            .pair_iter()
+            .map(|(handle, prop)| (Destructible::Prop(handle), prop.position(&scene.physics)));
+        let projectiles = self
+            .projectiles
+            .pair_iter()
+            .filter(|(_, projectile)| projectile.is_explosive())
+            .map(|(handle, projectile)| {
+                (
+                    Destructible::Projectile(handle),
+                    projectile.get_position(&scene.graph),
+                )
+            });
+        let mines = self
+            .deployables
+            .positions()
+            .map(|(handle, position)| (Destructible::Mine(handle), position));
+        self.destructible_grid
+            .rebuild(props.chain(projectiles).chain(mines));
+    }
+
+    fn update_announcer(&self, previous_time: f32) {
+        if self.ended {
+            return;
+        }
+        if let Some(announcement) =
+            announcer::announcement(self.options.time_limit_secs(), previous_time, self.time)
+        {
             self.sender
                 .as_ref()
                 .unwrap()
-                .send(Message::EndMatch)
+                .send(Message::Announce {
+                    text: announcement.text,
+                    sound: announcement.sound,
+                    duration: announcement.duration,
+                })
                 .unwrap();
         }
     }
 
-    pub fn update(&mut self, engine: &mut GameEngine, time: GameTime) {
+    fn update_game_ending(&mut self) {
+        if self.ended {
+            return;
+        }
+
+        if let Some(summary) = self.leader_board.evaluate(&self.options, self.time) {
+            self.end_match(summary);
+        }
+    }
+
+    fn end_match(&mut self, mut summary: MatchSummary) {
+        summary.accuracy = self.leader_board.accuracy(player::PLAYER_NAME);
+        self.ended = true;
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::EndMatch {
+                summary: Some(summary),
+            })
+            .unwrap();
+    }
+
+    /// Runs logic of the map and executes actions it fired, see `logic` module.
+    fn update_logic(&mut self, engine: &GameEngine, dt: f32) {
+        let scene = &engine.scenes[self.scene];
+        let positions = self
+            .actors
+            .iter()
+            .map(|actor| actor.position(&scene.physics))
+            .collect::<Vec<_>>();
+        let sender = self.sender.as_ref().unwrap().clone();
+        for action in self.logic.update(&positions, dt) {
+            match action {
+                LogicAction::OpenDoor(door) => self.interactables.open(&door),
+                LogicAction::SpawnBots { kind, count } => {
+                    for _ in 0..count {
+                        sender
+                            .send(Message::SpawnActor {
+                                descriptor: ActorDescriptor::bot(kind),
+                            })
+                            .unwrap();
+                    }
+                }
+                LogicAction::PlaySound {
+                    sound,
+                    position,
+                    radius,
+                } => sender
+                    .send(Message::PlaySound {
+                        path: sound,
+                        position,
+                        gain: 1.0,
+                        rolloff_factor: 1.0,
+                        radius,
+                        category: SoundCategory::Other,
+                    })
+                    .unwrap(),
+                LogicAction::EndMatch => {
+                    if !self.ended {
+                        let summary = self
+                            .leader_board
+                            .summary(&self.options, MatchEndReason::MapEvent);
+                        self.end_match(summary);
+                    }
+                }
+                LogicAction::Message(text) => {
+                    sender.send(Message::AddNotification { text }).unwrap()
+                }
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        engine: &mut GameEngine,
+        time: GameTime,
+        profiler: &mut FrameProfiler,
+    ) {
+        let previous_time = self.time;
         self.time += time.delta;
+        self.update_announcer(previous_time);
         self.update_respawn(time);
         let scene = &mut engine.scenes[self.scene];
-        self.update_spectator_camera(scene);
+        self.update_spectator_camera(scene, time.delta);
         self.update_death_zones(scene);
+        self.update_base_zones(scene, time.delta);
+        self.update_teleporters(scene);
+        let weapons_update_start = Instant::now();
         self.weapons.update(scene, &self.actors);
-        self.projectiles
-            .update(scene, &self.actors, &self.weapons, time);
-        self.items.update(scene, time);
+        profiler.add(Subsystem::Weapons, weapons_update_start.elapsed());
+        let projectiles_update_start = Instant::now();
+        let viewer_position = self.viewer_position(scene);
+        self.projectiles.update(
+            scene,
+            &self.actors,
+            &self.weapons,
+            &self.dynamic_props,
+            &self.shooting_range,
+            &self.deployables,
+            &self.surfaces,
+            time,
+            viewer_position,
+            self.quality.max_projectile_lights,
+            self.quality.reduce_flashes,
+        );
+        profiler.add(Subsystem::Projectiles, projectiles_update_start.elapsed());
+        self.effects
+            .update(&mut scene.graph, time.delta, self.quality.reduce_flashes);
+        self.weather.set_enabled(
+            &mut scene.graph,
+            &engine.sound_context,
+            self.quality.weather,
+        );
+        self.update_item_timers(time.delta);
+        let magnet = self.item_magnet(scene, time.delta);
+        self.items.update(scene, time, magnet);
+        self.props.update(
+            scene,
+            &engine.resource_manager,
+            viewer_position,
+            self.quality.prop_stream_distance,
+        );
+        self.update_grids(scene);
+        self.path_planner.begin_frame();
         self.actors.update(&mut UpdateContext {
             time,
             scene,
             sound_context: engine.sound_context.clone(),
+            actor_grid: &self.actor_grid,
+            item_grid: &self.item_grid,
             items: &self.items,
+            deployables: &self.deployables,
+            flags: &self.flags,
+            oddball: self.oddball.as_ref(),
+            hill: self.hill.as_ref(),
             jump_pads: &self.jump_pads,
             navmesh: self.navmesh.as_mut(),
+            path_planner: &mut self.path_planner,
             weapons: &self.weapons,
+            profiler,
         });
+        self.update_corpses();
+        self.update_zombie_rush(time);
+        self.update_haste(time.delta);
+        self.update_shields(engine, time.delta);
+        self.update_flags(engine, time);
+        self.update_oddball(engine, time.delta);
+        self.update_hill(engine, time.delta);
+        self.update_race(engine, time.delta);
+        self.update_rounds(time.delta);
+        self.update_practice(engine, time);
+        self.update_tutorial(engine, time.delta);
+        self.update_interaction(engine);
+        self.update_interactables(&mut engine.scenes[self.scene], time.delta);
+        self.update_deployables(engine, time.delta);
+        self.update_melee(engine);
+        if let Some(scripts) = self.scripts.as_mut() {
+            scripts.on_tick(time.delta);
+        }
+        self.update_logic(engine, time.delta);
         self.update_game_ending();
     }
 
+    /// Eliminates actor that lost its last life in last man standing, returns true if actor
+    /// is out of the match.
+    fn eliminate_if_out_of_lives(&mut self, name: &str) -> bool {
+        let lives = self.options.lives();
+        let deaths = self
+            .leader_board
+            .values()
+            .get(name)
+            .map_or(0, |score| score.deaths);
+        if lives == 0 || deaths < lives {
+            return false;
+        }
+        self.leader_board.eliminate(name);
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::AddNotification {
+                text: format!("{} is eliminated!", name),
+            })
+            .unwrap();
+        true
+    }
+
+    /// Lives left of player in last man standing, `None` if lives are unlimited.
+    pub fn player_lives(&self) -> Option<u32> {
+        let lives = self.options.lives();
+        if lives == 0 {
+            return None;
+        }
+        let deaths = self
+            .leader_board
+            .values()
+            .get(player::PLAYER_NAME)
+            .map_or(0, |score| score.deaths);
+        Some(lives.saturating_sub(deaths))
+    }
+
     pub async fn respawn_actor(&mut self, engine: &mut GameEngine, actor: Handle<Actor>) {
         if self.actors.contains(actor) {
             let name = self.actors.get(actor).name.clone();
 
             self.leader_board.add_death(&name);
+            self.end_round(actor);
 
-            let entry = match self.actors.get(actor) {
-                Actor::Bot(bot) => RespawnEntry::Bot(BotRespawnEntry {
-                    name,
-                    kind: bot.definition.kind,
-                    time_left: RESPAWN_TIME,
-                }),
-                Actor::Player(player) => {
-                    // Turn on spectator camera and prepare its target position. Spectator
-                    // camera will be used to render world until player is despawned.
-                    let scene = &mut engine.scenes[self.scene];
-                    let position = scene.graph[player.camera()].global_position();
-                    if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera]
-                    {
-                        spectator_camera
-                            .set_enabled(true)
-                            .local_transform_mut()
-                            .set_position(position);
-                    }
-                    // Use ray casting to get target position for spectator camera, it is used to
-                    // create "dropping head" effect.
-                    let ray =
-                        Ray::from_two_points(&position, &(position - Vec3::new(0.0, 1000.0, 0.0)))
-                            .unwrap();
-                    let options = RayCastOptions {
-                        ignore_bodies: true,
-                        ignore_static_geometries: false,
-                        sort_results: true,
-                    };
-                    let mut result = Vec::new();
-                    if scene.physics.ray_cast(&ray, options, &mut result) {
-                        if let Some(hit) = result.first() {
-                            self.target_spectator_position = hit.position;
-                            // Prevent see-thru-floor
-                            self.target_spectator_position.y += 0.1;
-                        } else {
-                            self.target_spectator_position = position;
-                        }
+            let is_zombie = matches!(self.actors.get(actor), Actor::Bot(bot) if bot.is_zombie());
+            let eliminated = !is_zombie && self.eliminate_if_out_of_lives(&name);
+
+            let entry = RespawnEntry {
+                descriptor: self.actors.get(actor).descriptor(),
+                time_left: RESPAWN_TIME,
+            };
+
+            if let Actor::Player(player) = self.actors.get(actor) {
+                if self.options.allows_loadout_change() && !eliminated {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ChooseLoadout)
+                        .unwrap();
+                }
+
+                // Turn on spectator camera and prepare its target position. Spectator
+                // camera will be used to render world until player is despawned.
+                let scene = &mut engine.scenes[self.scene];
+                let position = scene.graph[player.camera()].global_position();
+                if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
+                    spectator_camera
+                        .set_enabled(true)
+                        .local_transform_mut()
+                        .set_position(position);
+                }
+                // Use ray casting to get target position for spectator camera, it is used to
+                // create "dropping head" effect.
+                let ray =
+                    Ray::from_two_points(&position, &(position - Vec3::new(0.0, 1000.0, 0.0)))
+                        .unwrap();
+                let options = RayCastOptions {
+                    ignore_bodies: true,
+                    ignore_static_geometries: false,
+                    sort_results: true,
+                };
+                let mut result = Vec::new();
+                if scene.physics.ray_cast(&ray, options, &mut result) {
+                    if let Some(hit) = result.first() {
+                        self.target_spectator_position = hit.position;
+                        // Prevent see-thru-floor
+                        self.target_spectator_position.y += 0.1;
                     } else {
                         self.target_spectator_position = position;
                     }
-
-                    RespawnEntry::Player(PlayerRespawnEntry {
-                        time_left: RESPAWN_TIME,
-                    })
+                } else {
+                    self.target_spectator_position = position;
                 }
-            };
+            }
 
             self.remove_actor(engine, actor).await;
 
-            self.respawn_list.push(entry);
+            // Zombies come back with waves instead of respawning, eliminated actors don't come
+            // back at all.
+            if !is_zombie && !eliminated {
+                self.respawn_list.push(entry);
+            }
+        }
+    }
+
+    fn announce_round(&self, text: String, sound: &str) {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::Announce {
+                text,
+                sound: assets::sounds::announcer::path(sound),
+                duration: ROUND_CALLOUT_DURATION,
+            })
+            .unwrap();
+    }
+
+    /// Ends round of duel when one of duelists dies, survivor wins the round.
+    fn end_round(&mut self, dead: Handle<Actor>) {
+        let rounds = match self.rounds.as_mut() {
+            Some(rounds) if !rounds.is_intermission() => rounds,
+            _ => return,
+        };
+        let mut survivors = self
+            .actors
+            .pair_iter()
+            .filter(|(handle, actor)| *handle != dead && !actor.is_dead());
+        let text = match (survivors.next(), survivors.next()) {
+            (Some((_, winner)), None) => {
+                self.leader_board.add_round_win(&winner.name);
+                format!("{} wins round {}!", winner.name, rounds.round())
+            }
+            _ => format!("Round {} is a draw", rounds.round()),
+        };
+        rounds.end_round();
+        self.announce_round(text, "round_over");
+    }
+
+    fn update_rounds(&mut self, dt: f32) {
+        if self.ended {
+            return;
+        }
+        if let Some(rounds) = self.rounds.as_mut() {
+            if rounds.update(dt) {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::StartRound)
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Respawns both duelists with fresh health, armor and weapons and resets items.
+    async fn start_round(&mut self, engine: &mut GameEngine) {
+        let round = match self.rounds.as_mut() {
+            Some(rounds) if rounds.is_intermission() => {
+                rounds.start_next_round();
+                rounds.round()
+            }
+            _ => return,
+        };
+        let survivors = self
+            .actors
+            .pair_iter()
+            .map(|(handle, actor)| (handle, actor.descriptor()))
+            .collect::<Vec<_>>();
+        let mut descriptors = Vec::new();
+        for (handle, descriptor) in survivors {
+            self.remove_actor(engine, handle).await;
+            descriptors.push(descriptor);
+        }
+        descriptors.extend(self.respawn_list.drain(..).map(|entry| entry.descriptor));
+        // Weapons dropped by removed actors are temporary items, so they are gone too.
+        self.items.reset(&mut engine.scenes[self.scene]);
+        for descriptor in descriptors.iter() {
+            self.spawn_actor(engine, descriptor).await;
+        }
+        self.announce_round(format!("Round {} - fight!", round), "round_start");
+    }
+
+    /// Calls out respawn of major items in duel.
+    fn update_item_timers(&self, dt: f32) {
+        if self.rounds.is_none() {
+            return;
+        }
+        for text in duel::item_callouts(&self.items, dt) {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AddNotification { text })
+                .unwrap();
         }
     }
 
+    /// Text of round score of duel for HUD, `None` in other modes.
+    pub fn round_score(&self) -> Option<String> {
+        let rounds = self.rounds.as_ref()?;
+        let mut scores = self
+            .leader_board
+            .values()
+            .iter()
+            .map(|(name, score)| (name.as_str(), score.rounds_won))
+            .collect::<Vec<_>>();
+        // Player goes first, then opponents in stable order.
+        scores.sort_by_key(|&(name, _)| (name != player::PLAYER_NAME, name));
+        let scores = scores
+            .iter()
+            .map(|(name, won)| format!("{} {}", name, won))
+            .collect::<Vec<_>>()
+            .join(" - ");
+        Some(format!(
+            "Round {} (first to {}): {}",
+            rounds.round(),
+            self.options.score_limit(),
+            scores
+        ))
+    }
+
     pub async fn handle_message(
         &mut self,
         engine: &mut GameEngine,
@@ -1007,18 +3750,12 @@ impl Level {
             &Message::GiveNewWeapon { actor, kind } => {
                 self.give_new_weapon(engine, actor, kind).await;
             }
-            Message::AddBot {
-                kind,
-                position,
-                name,
-            } => {
-                self.add_bot(engine, *kind, *position, name.clone()).await;
-            }
             &Message::RemoveActor { actor } => self.remove_actor(engine, actor).await,
             &Message::GiveItem { actor, kind } => {
                 self.give_item(engine, actor, kind).await;
             }
             &Message::PickUpItem { actor, item } => {
+                self.tutorial_goal_reached(actor, TutorialGoal::PickUpItem);
                 self.pickup_item(engine, actor, item).await;
             }
             &Message::ShootWeapon {
@@ -1026,6 +3763,10 @@ impl Level {
                 initial_velocity,
                 direction,
             } => {
+                if self.weapons.contains(weapon) {
+                    let owner = self.weapons[weapon].owner();
+                    self.tutorial_goal_reached(owner, TutorialGoal::Shoot);
+                }
                 self.shoot_weapon(engine, weapon, initial_velocity, time, direction)
                     .await
             }
@@ -1049,21 +3790,81 @@ impl Level {
                 .await
             }
             &Message::ShowWeapon { weapon, state } => self.show_weapon(engine, weapon, state),
-            Message::SpawnBot { kind, name } => {
-                self.spawn_bot(engine, *kind, Some(name.clone())).await;
+            &Message::ToggleLaser { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].toggle_laser(&mut engine.scenes[self.scene].graph);
+                }
             }
-            &Message::DamageActor { actor, who, amount } => {
-                self.damage_actor(engine, actor, who, amount, time);
+            Message::SpawnActor { descriptor } => {
+                self.spawn_actor(engine, descriptor).await;
             }
-            &Message::CreateEffect { kind, position } => effects::create(
-                kind,
+            &Message::AddBot { kind, team } => self.add_bot(engine, kind, team).await,
+            Message::RemoveBot => self.remove_bot(engine).await,
+            Message::AdjustScore { name, amount } => {
+                self.leader_board.add_score(name, *amount);
+            }
+            &Message::CountHit { who, weapon } => {
+                if self.actors.contains(who) {
+                    self.leader_board
+                        .add_hit(&self.actors.get(who).name, weapon);
+                }
+            }
+            &Message::DamageActor {
+                actor,
+                who,
+                amount,
+                source,
+                impulse,
+            } => {
+                self.damage_actor(engine, actor, who, amount, source, impulse, time);
+            }
+            &Message::NearMiss { actor } => {
+                if self.actors.contains(actor) {
+                    if let Actor::Bot(bot) = self.actors.get_mut(actor) {
+                        bot.on_near_miss();
+                    }
+                }
+            }
+            &Message::DamageProp {
+                prop,
+                who,
+                amount,
+                impulse,
+            } => self.damage_prop(engine, prop, who, amount, impulse, time.delta),
+            &Message::HitTarget {
+                target,
+                position,
+                amount,
+            } => self.shooting_range.hit(
+                &engine.scenes[self.scene].physics,
+                target,
+                position,
+                amount,
+                self.time,
+            ),
+            &Message::DisarmMine { mine, who } => self.disarm_mine(engine, mine, who),
+            &Message::Explosion {
+                position,
+                radius,
+                damage,
+                impulse,
+                knockback,
+                who,
+                source,
+            } => self.explode(
+                engine, position, radius, damage, impulse, knockback, who, source, time,
+            ),
+            &Message::CreateEffect { kind, position } => self.effects.spawn(
+                if kind == EffectKind::BulletImpact && self.weather.is_wet(position) {
+                    EffectKind::WetBulletImpact
+                } else {
+                    kind
+                },
                 &mut engine.scenes[self.scene].graph,
                 engine.resource_manager.clone(),
                 position,
+                self.quality.max_particle_effects,
             ),
-            Message::SpawnPlayer => {
-                self.spawn_player(engine).await;
-            }
             &Message::SpawnItem {
                 kind,
                 position,
@@ -1074,10 +3875,29 @@ impl Level {
                     .await
             }
             &Message::RespawnActor { actor } => self.respawn_actor(engine, actor).await,
+            Message::StartRound => self.start_round(engine).await,
             _ => (),
         }
     }
 
+    /// Loads logic of the map, see `logic` module.
+    pub fn load_logic(&mut self) {
+        self.logic = match MapLogic::load(MapLogic::file_of_map(&self.map)) {
+            Ok(logic) => logic,
+            Err(e) => {
+                Log::writeln(format!("WARNING: {}, map will have no logic.", e));
+                Default::default()
+            }
+        };
+    }
+
+    /// Compiles match scripts, must be called after message sender was set.
+    pub fn load_scripts(&mut self) {
+        if let Some(sender) = self.sender.as_ref() {
+            self.scripts = Some(MatchScripts::load(scripting::SCRIPTS_DIR, sender.clone()));
+        }
+    }
+
     pub fn set_message_sender(&mut self, sender: Sender<Message>) {
         self.sender = Some(sender.clone());
 
@@ -1091,6 +3911,18 @@ impl Level {
         for projectile in self.projectiles.iter_mut() {
             projectile.sender = Some(sender.clone());
         }
+        for item in self.items.iter_mut() {
+            item.sender = Some(sender.clone());
+        }
+    }
+
+    /// Writes heatmap of the match to given directory, see `heatmap` module.
+    pub fn export_heatmap(&self, dir: &Path) {
+        match self.heatmap.export(dir) {
+            Ok(Some(path)) => Log::writeln(format!("Heatmap exported to {}", path.display())),
+            Ok(None) => (),
+            Err(error) => Log::writeln(format!("Unable to export heatmap: {}", error)),
+        }
     }
 
     pub fn debug_draw(&self, engine: &mut GameEngine) {
@@ -1119,27 +3951,87 @@ impl Level {
         for death_zone in self.death_zones.iter() {
             drawing_context.draw_aabb(&death_zone.bounds, Color::opaque(0, 0, 200));
         }
-    }
-}
 
-pub struct SpawnPoint {
-    position: Vec3,
-}
+        for base_zone in self.base_zones.iter() {
+            drawing_context.draw_aabb(&base_zone.bounds, Color::opaque(200, 0, 200));
+        }
 
-impl Default for SpawnPoint {
-    fn default() -> Self {
-        Self {
-            position: Default::default(),
+        for link in self.path_planner.links() {
+            drawing_context.add_line(scene::Line {
+                begin: link.begin,
+                end: link.end,
+                color: Color::opaque(200, 200, 0),
+            });
         }
+
+        self.heatmap.debug_draw(drawing_context);
     }
 }
 
-impl Visit for SpawnPoint {
-    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        visitor.enter_region(name)?;
+#[cfg(feature = "query_api")]
+impl Level {
+    pub fn query_actors(&self, engine: &GameEngine) -> Vec<ActorInfo> {
+        let scene = &engine.scenes[self.scene];
+        self.actors
+            .pair_iter()
+            .map(|(handle, actor)| {
+                let weapon = actor.current_weapon();
+                ActorInfo {
+                    handle,
+                    kind: match actor {
+                        Actor::Player(_) => ActorInfoKind::Player,
+                        Actor::Bot(_) => ActorInfoKind::Bot,
+                    },
+                    name: actor.name.clone(),
+                    position: actor.position(&scene.physics),
+                    health: actor.get_health(),
+                    armor: actor.get_armor(),
+                    team: actor.team(),
+                    weapon: if self.weapons.contains(weapon) {
+                        Some(self.weapons[weapon].get_kind())
+                    } else {
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
 
-        self.position.visit("Position", visitor)?;
+    pub fn query_items(&self, engine: &GameEngine) -> Vec<ItemInfo> {
+        let graph = &engine.scenes[self.scene].graph;
+        self.items
+            .iter()
+            .map(|item| ItemInfo {
+                kind: item.get_kind(),
+                position: item.position(graph),
+                picked_up: item.is_picked_up(),
+            })
+            .collect()
+    }
 
-        visitor.leave_region()
+    pub fn query_projectiles(&self, engine: &GameEngine) -> Vec<ProjectileInfo> {
+        let graph = &engine.scenes[self.scene].graph;
+        self.projectiles
+            .iter()
+            .filter(|projectile| !projectile.is_dead())
+            .map(|projectile| ProjectileInfo {
+                kind: projectile.kind(),
+                position: projectile.get_position(graph),
+                owner: if self.weapons.contains(projectile.owner) {
+                    self.weapons[projectile.owner].owner()
+                } else {
+                    Handle::NONE
+                },
+            })
+            .collect()
+    }
+
+    pub fn snapshot(&self, engine: &GameEngine) -> LevelSnapshot {
+        LevelSnapshot {
+            time: self.time,
+            actors: self.query_actors(engine),
+            items: self.query_items(engine),
+            projectiles: self.query_projectiles(engine),
+        }
     }
 }
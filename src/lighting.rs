@@ -0,0 +1,129 @@
+//! Lighting presets of a match. Preset defines ambient light and color of the sun, if the map
+//! has a `Sun` node the sun is linked to it and takes its direction, so every map can put the
+//! sun where it fits its geometry. At night the sun is replaced with dim moonlight, so the
+//! flashlight and muzzle flashes become the main sources of light.
+
+use rg3d::{
+    core::{
+        color::Color,
+        math::{quat::Quat, vec3::Vec3},
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{
+        base::BaseBuilder,
+        light::{BaseLightBuilder, DirectionalLightBuilder},
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightingPreset {
+    Day,
+    Dusk,
+    Night,
+}
+
+impl Default for LightingPreset {
+    fn default() -> Self {
+        LightingPreset::Day
+    }
+}
+
+impl LightingPreset {
+    pub fn id(self) -> u32 {
+        match self {
+            LightingPreset::Day => 0,
+            LightingPreset::Dusk => 1,
+            LightingPreset::Night => 2,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(LightingPreset::Day),
+            1 => Ok(LightingPreset::Dusk),
+            2 => Ok(LightingPreset::Night),
+            _ => Err(format!("Unknown lighting preset {}", id)),
+        }
+    }
+
+    pub fn definition(self) -> LightingDefinition {
+        match self {
+            LightingPreset::Day => LightingDefinition {
+                ambient_color: Color::opaque(60, 60, 60),
+                sun_color: Color::opaque(255, 250, 235),
+                sun_elevation: 60.0,
+            },
+            LightingPreset::Dusk => LightingDefinition {
+                ambient_color: Color::opaque(45, 35, 40),
+                sun_color: Color::opaque(250, 140, 80),
+                sun_elevation: 10.0,
+            },
+            LightingPreset::Night => LightingDefinition {
+                ambient_color: Color::opaque(8, 10, 16),
+                sun_color: Color::opaque(40, 50, 80),
+                sun_elevation: 45.0,
+            },
+        }
+    }
+}
+
+impl Visit for LightingPreset {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct LightingDefinition {
+    /// Ambient color of renderer, it lights everything uniformly.
+    pub ambient_color: Color,
+    /// Color of directional light, it is sun, setting sun or moon depending on preset.
+    pub sun_color: Color,
+    /// Angle between horizon and the sun in degrees, used when map has no `Sun` node.
+    pub sun_elevation: f32,
+}
+
+/// Creates directional light of given preset.
+pub fn create_sun(
+    scene: &mut Scene,
+    map_root: Handle<Node>,
+    preset: LightingPreset,
+) -> Handle<Node> {
+    let definition = preset.definition();
+
+    let mount = scene.graph.find_by_name(map_root, "Sun");
+    let rotation = if mount.is_some() {
+        Default::default()
+    } else {
+        // Directional light shines along -Y axis of its local space.
+        Quat::from_axis_angle(Vec3::RIGHT, (90.0 - definition.sun_elevation).to_radians())
+    };
+
+    let sun = scene.graph.add_node(
+        DirectionalLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(rotation)
+                        .build(),
+                ),
+            )
+            .with_color(definition.sun_color)
+            .with_scatter_enabled(false),
+        )
+        .build_node(),
+    );
+    if mount.is_some() {
+        scene.graph.link_nodes(sun, mount);
+    }
+    sun
+}
@@ -0,0 +1,196 @@
+//! Loadout screen - player selects weapons to spawn with. It is shown before first spawn in
+//! every match and after death in modes that allow changing loadout (see
+//! `MatchOptions::allows_loadout_change`). Selected loadout is remembered in settings.
+
+use crate::{
+    gui::create_dropdown_items,
+    message::Message,
+    navigation::{FocusChain, NavigationAction},
+    settings::LoadoutSettings,
+    weapon::WeaponKind,
+    Gui, GuiMessage, UINodeHandle,
+};
+use rg3d::{
+    event::Event,
+    gui::{
+        button::ButtonBuilder,
+        dropdown_list::DropdownListBuilder,
+        grid::{Column, GridBuilder, Row},
+        message::{
+            ButtonMessage, DropdownListMessage, MessageDirection, UiMessageData, WindowMessage,
+        },
+        node::UINode,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness,
+    },
+};
+use std::sync::mpsc::Sender;
+
+const WEAPONS: [(&str, WeaponKind); 4] = [
+    ("M4", WeaponKind::M4),
+    ("AK-47", WeaponKind::Ak47),
+    ("Plasma Rifle", WeaponKind::PlasmaRifle),
+    ("Rocket Launcher", WeaponKind::RocketLauncher),
+];
+
+pub struct LoadoutMenu {
+    sender: Sender<Message>,
+    pub window: UINodeHandle,
+    dd_primary: UINodeHandle,
+    dd_secondary: UINodeHandle,
+    btn_deploy: UINodeHandle,
+    focus_chain: FocusChain,
+}
+
+impl LoadoutMenu {
+    pub fn new(ui: &mut Gui, loadout: &LoadoutSettings, sender: Sender<Message>) -> Self {
+        let common_row = Row::strict(36.0);
+
+        let ctx = &mut ui.build_ctx();
+
+        let names = WEAPONS.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+        let dd_primary;
+        let dd_secondary;
+        let btn_deploy;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0))
+            .with_title(WindowTitle::text("Loadout"))
+            .open(false)
+            .can_resize(false)
+            .can_minimize(false)
+            .can_close(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(4.0))
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
+                                .with_text("Primary")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            dd_primary = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_row(0).on_column(1),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_primary
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
+                                .with_text("Secondary")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            dd_secondary = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_row(1).on_column(1),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_secondary
+                        })
+                        .with_child({
+                            btn_deploy = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_text("Deploy")
+                            .build(ctx);
+                            btn_deploy
+                        }),
+                )
+                .add_column(Column::strict(150.0))
+                .add_column(Column::stretch())
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .build(ctx),
+            )
+            .build(ctx);
+
+        let menu = Self {
+            sender,
+            window,
+            dd_primary,
+            dd_secondary,
+            btn_deploy,
+            focus_chain: FocusChain::new(vec![dd_primary, dd_secondary, btn_deploy]),
+        };
+
+        for &(dropdown, kind) in &[
+            (dd_primary, loadout.primary),
+            (dd_secondary, loadout.secondary),
+        ] {
+            let index = WEAPONS.iter().position(|(_, k)| *k == kind).unwrap_or(0);
+            ui.send_message(DropdownListMessage::selection(
+                dropdown,
+                MessageDirection::ToWidget,
+                Some(index),
+            ));
+        }
+
+        menu
+    }
+
+    pub fn open(&mut self, ui: &mut Gui) {
+        self.focus_chain.focus(ui, self.btn_deploy);
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn close(&self, ui: &mut Gui) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn is_open(&self, ui: &Gui) -> bool {
+        ui.node(self.window).visibility()
+    }
+
+    /// Keyboard navigation, returns true if event was consumed. Loadout screen can't be
+    /// closed without deploying, so `Back` is left to the game.
+    pub fn process_input_event(&mut self, ui: &mut Gui, event: &Event<()>) -> bool {
+        match NavigationAction::from_event(event) {
+            Some(action) => self.focus_chain.process(ui, action),
+            None => false,
+        }
+    }
+
+    fn loadout(&self, ui: &Gui) -> LoadoutSettings {
+        let selection = |handle: UINodeHandle| {
+            let index = if let UINode::DropdownList(dropdown_list) = ui.node(handle) {
+                dropdown_list.selection().unwrap_or(0)
+            } else {
+                0
+            };
+            WEAPONS[index.min(WEAPONS.len() - 1)].1
+        };
+
+        LoadoutSettings {
+            primary: selection(self.dd_primary),
+            secondary: selection(self.dd_secondary),
+        }
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_deploy {
+                self.sender
+                    .send(Message::SetLoadout {
+                        loadout: self.loadout(ui),
+                    })
+                    .unwrap();
+                self.close(ui);
+            }
+        }
+    }
+}
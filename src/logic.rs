@@ -0,0 +1,340 @@
+//! Map logic - triggers, timers, counters and relays that drive scripted events of a map
+//! without any code. Logic is stored next to the map model in a file with `logic.json`
+//! extension (`dm6.logic.json` for `dm6.fbx`), mods can override it. Every entity has a name
+//! and `targets` - names of entities that are fired when the entity fires:
+//!
+//! - `Trigger` fires when an actor enters its box, `once` trigger fires only the first time.
+//! - `Timer` fires `interval` seconds after it was fired, `repeat` timer keeps firing after
+//!   that. `autostart` timer starts with the match.
+//! - `Counter` fires when it was fired `count` times and starts counting over.
+//! - `Relay` fires its targets `delay` seconds after it was fired.
+//! - `OpenDoor`, `SpawnBotWave`, `PlaySound`, `EndMatch` and `Message` are actions, they do
+//!   their thing when fired and fire their targets right away.
+//!
+//! ```json
+//! {
+//!     "entities": [
+//!         { "name": "arena", "type": "Trigger", "min": [-4.0, 0.0, -4.0], "max": [4.0, 3.0, 4.0], "once": true, "targets": ["alarm", "waves"] },
+//!         { "name": "alarm", "type": "PlaySound", "sound": "data/sounds/alarm.ogg", "position": [0.0, 2.0, 0.0] },
+//!         { "name": "waves", "type": "Timer", "interval": 20.0, "repeat": true, "targets": ["wave", "wave_count"] },
+//!         { "name": "wave", "type": "SpawnBotWave", "bot": "Mutant", "count": 3 },
+//!         { "name": "wave_count", "type": "Counter", "count": 3, "targets": ["exit"] },
+//!         { "name": "exit", "type": "OpenDoor", "door": "Door_Exit", "targets": ["exit_message"] },
+//!         { "name": "exit_message", "type": "Message", "text": "The exit is open!" }
+//!     ]
+//! }
+//! ```
+//!
+//! Logic only decides what happens, actions are executed by level. Logic isn't saved, it
+//! starts over when a saved game is loaded.
+
+use crate::{bot::BotKind, mods};
+use rg3d::{core::math::vec3::Vec3, utils::log::Log};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Limit of fires during one update, so entities that fire each other in a loop can't hang
+/// the game.
+const MAX_FIRES_PER_UPDATE: usize = 256;
+
+fn default_sound_radius() -> f32 {
+    5.0
+}
+
+fn to_vec3(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum LogicKind {
+    Trigger {
+        min: [f32; 3],
+        max: [f32; 3],
+        #[serde(default)]
+        once: bool,
+    },
+    Timer {
+        interval: f32,
+        #[serde(default)]
+        repeat: bool,
+        #[serde(default)]
+        autostart: bool,
+    },
+    Counter {
+        count: u32,
+    },
+    Relay {
+        #[serde(default)]
+        delay: f32,
+    },
+    OpenDoor {
+        door: String,
+    },
+    SpawnBotWave {
+        bot: BotKind,
+        count: u32,
+    },
+    PlaySound {
+        sound: PathBuf,
+        position: [f32; 3],
+        #[serde(default = "default_sound_radius")]
+        radius: f32,
+    },
+    EndMatch,
+    Message {
+        text: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct LogicEntityDefinition {
+    name: String,
+    #[serde(default)]
+    targets: Vec<String>,
+    #[serde(flatten)]
+    kind: LogicKind,
+}
+
+#[derive(Deserialize)]
+struct LogicFile {
+    entities: Vec<LogicEntityDefinition>,
+}
+
+/// Action that level must execute.
+#[derive(Clone, Debug)]
+pub enum LogicAction {
+    /// Opens door or sends elevator up, by name of its mesh.
+    OpenDoor(String),
+    SpawnBots {
+        kind: BotKind,
+        count: u32,
+    },
+    PlaySound {
+        sound: PathBuf,
+        position: Vec3,
+        radius: f32,
+    },
+    EndMatch,
+    Message(String),
+}
+
+struct LogicEntity {
+    kind: LogicKind,
+    /// Indices of target entities.
+    targets: Vec<usize>,
+    /// Trigger has an actor inside, timer is running.
+    active: bool,
+    /// Once trigger that already fired.
+    spent: bool,
+    /// Time left until timer fires.
+    time_left: f32,
+    /// How many times counter was fired.
+    fired: u32,
+}
+
+#[derive(Default)]
+pub struct MapLogic {
+    entities: Vec<LogicEntity>,
+    /// Fires of relays that wait for their delay - time left and index of relay.
+    delayed: Vec<(f32, usize)>,
+}
+
+impl MapLogic {
+    /// Returns path of logic file of a map.
+    pub fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("logic.json")
+    }
+
+    /// Loads logic of a map, map without logic file has no logic. Targets that don't exist
+    /// are skipped with a warning.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read map logic {}: {}", path.display(), e))?;
+        let file = serde_json::from_str::<LogicFile>(&data)
+            .map_err(|e| format!("Map logic {} is corrupted: {}", path.display(), e))?;
+
+        let names = file
+            .entities
+            .iter()
+            .map(|entity| entity.name.clone())
+            .collect::<Vec<_>>();
+        let entities = file
+            .entities
+            .into_iter()
+            .map(|definition| {
+                let targets = definition
+                    .targets
+                    .iter()
+                    .filter_map(|target| {
+                        let index = names.iter().position(|name| name == target);
+                        if index.is_none() {
+                            Log::writeln(format!(
+                                "WARNING: Map logic entity {} has unknown target {}",
+                                definition.name, target
+                            ));
+                        }
+                        index
+                    })
+                    .collect();
+                let active = match definition.kind {
+                    LogicKind::Timer { autostart, .. } => autostart,
+                    _ => false,
+                };
+                let time_left = match definition.kind {
+                    LogicKind::Timer { interval, .. } => interval,
+                    _ => 0.0,
+                };
+                LogicEntity {
+                    kind: definition.kind,
+                    targets,
+                    active,
+                    spent: false,
+                    time_left,
+                    fired: 0,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            entities,
+            delayed: Default::default(),
+        })
+    }
+
+    /// Runs logic for one update with positions of alive actors, returns actions that level
+    /// must execute.
+    pub fn update(&mut self, actor_positions: &[Vec3], dt: f32) -> Vec<LogicAction> {
+        // Entities that fire their targets this update.
+        let mut firing = Vec::new();
+
+        for (index, entity) in self.entities.iter_mut().enumerate() {
+            match entity.kind {
+                LogicKind::Trigger { min, max, once } => {
+                    let (min, max) = (to_vec3(min), to_vec3(max));
+                    let occupied = actor_positions.iter().any(|p| {
+                        p.x >= min.x
+                            && p.x <= max.x
+                            && p.y >= min.y
+                            && p.y <= max.y
+                            && p.z >= min.z
+                            && p.z <= max.z
+                    });
+                    // Fires only when first actor comes in, not every update while it stays.
+                    if occupied && !entity.active && !entity.spent {
+                        firing.push(index);
+                        entity.spent = once;
+                    }
+                    entity.active = occupied;
+                }
+                LogicKind::Timer {
+                    interval, repeat, ..
+                } if entity.active => {
+                    entity.time_left -= dt;
+                    if entity.time_left <= 0.0 {
+                        firing.push(index);
+                        entity.time_left = interval;
+                        entity.active = repeat;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for (time_left, relay) in self.delayed.iter_mut() {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                firing.push(*relay);
+            }
+        }
+        self.delayed.retain(|(time_left, _)| *time_left > 0.0);
+
+        let mut actions = Vec::new();
+        let mut fires = 0;
+        for index in firing {
+            for &target in self.entities[index].targets.clone().iter() {
+                self.fire(target, &mut actions, &mut fires);
+            }
+        }
+        actions
+    }
+
+    /// Fires entity, its targets are fired right away if it doesn't wait for anything.
+    fn fire(&mut self, index: usize, actions: &mut Vec<LogicAction>, fires: &mut usize) {
+        *fires += 1;
+        if *fires > MAX_FIRES_PER_UPDATE {
+            if *fires == MAX_FIRES_PER_UPDATE + 1 {
+                Log::writeln("WARNING: Map logic fires in a loop, rest of fires are skipped.");
+            }
+            return;
+        }
+
+        let entity = &mut self.entities[index];
+        let pass = match &entity.kind {
+            // Triggers fire only by themselves.
+            LogicKind::Trigger { .. } => false,
+            LogicKind::Timer { interval, .. } => {
+                if !entity.active {
+                    entity.active = true;
+                    entity.time_left = *interval;
+                }
+                false
+            }
+            LogicKind::Counter { count } => {
+                entity.fired += 1;
+                if entity.fired >= *count {
+                    entity.fired = 0;
+                    true
+                } else {
+                    false
+                }
+            }
+            LogicKind::Relay { delay } if *delay > 0.0 => {
+                self.delayed.push((*delay, index));
+                false
+            }
+            LogicKind::Relay { .. } => true,
+            LogicKind::OpenDoor { door } => {
+                actions.push(LogicAction::OpenDoor(door.clone()));
+                true
+            }
+            LogicKind::SpawnBotWave { bot, count } => {
+                actions.push(LogicAction::SpawnBots {
+                    kind: *bot,
+                    count: *count,
+                });
+                true
+            }
+            LogicKind::PlaySound {
+                sound,
+                position,
+                radius,
+            } => {
+                actions.push(LogicAction::PlaySound {
+                    sound: sound.clone(),
+                    position: to_vec3(*position),
+                    radius: *radius,
+                });
+                true
+            }
+            LogicKind::EndMatch => {
+                actions.push(LogicAction::EndMatch);
+                true
+            }
+            LogicKind::Message { text } => {
+                actions.push(LogicAction::Message(text.clone()));
+                true
+            }
+        };
+
+        if pass {
+            for target in self.entities[index].targets.clone() {
+                self.fire(target, actions, fires);
+            }
+        }
+    }
+}
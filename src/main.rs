@@ -7,40 +7,117 @@ extern crate serde;
 extern crate serde_json;
 
 mod actor;
+mod aim;
+mod aim_assist;
+mod announcer;
+mod asset_check;
 mod assets;
+mod attachment;
+mod autosave;
+mod balance;
 mod bot;
 mod character;
+mod cinematic;
+mod confirm_dialog;
 mod control_scheme;
+mod ctf;
+mod damage_modifier;
+mod data_dir;
+mod definition;
+mod deployable;
+mod duel;
+mod dynamic_prop;
+mod dynamic_quality;
+mod editor;
 mod effects;
+mod error;
+mod event_bus;
+mod frame_profiler;
+mod free_camera;
 mod gui;
+mod heatmap;
 mod hud;
+mod interactable;
 mod item;
 mod jump_pad;
+mod kill_streak;
+mod koth;
 mod leader_board;
 mod level;
+mod lighting;
+mod loadout_menu;
+mod logic;
 mod match_menu;
+mod material;
 mod menu;
 mod message;
+mod mods;
+mod morale;
+mod navigation;
+mod navmesh_cache;
+mod oddball;
 mod options_menu;
+mod pathfinding;
+mod pause_menu;
+mod placement;
 mod player;
+mod profile;
 mod projectile;
+mod prop_streaming;
+#[cfg(feature = "query_api")]
+mod query;
+mod race;
+mod random;
+mod rating;
+mod repro;
+mod reverb;
+mod scripting;
+mod session;
 mod settings;
+mod shooting_range;
+mod sound_variation;
+mod soundscape;
+mod spatial_hash;
+mod spectator;
+mod tags;
+mod tutorial;
+#[cfg(test)]
+mod visit_test;
 mod weapon;
+mod zombie_rush;
 
 use crate::{
     actor::Actor,
+    autosave::Autosave,
+    cinematic::{CameraPath, CinematicCamera},
     control_scheme::ControlScheme,
+    data_dir::DataDirs,
+    dynamic_quality::DynamicQuality,
+    editor::DebugEditor,
+    event_bus::{EventBus, Subscriber},
+    frame_profiler::{FrameProfiler, Subsystem},
     hud::Hud,
     level::Level,
+    loadout_menu::LoadoutMenu,
+    match_menu::MAPS,
     menu::Menu,
-    message::Message,
-    settings::{Settings, SoundSettings},
+    message::{Message, MessageCategory},
+    profile::Profile,
+    repro::{RecordedInput, Recording, Replay},
+    reverb::{ReverbPreset, ReverbZone},
+    session::SessionStandings,
+    settings::{
+        AutosaveSettings, LoadoutSettings, MatchSettings, QualitySettings, Settings, SoundSettings,
+        SpeakerMode,
+    },
+    sound_variation::{SoundCategory, SoundVariation},
 };
 use rg3d::{
     core::{
         color::Color,
+        math::vec3::Vec3,
         pool::Handle,
-        visitor::{Visit, VisitResult, Visitor},
+        visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::{resource_manager::ResourceManager, Engine},
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
@@ -48,10 +125,12 @@ use rg3d::{
     gui::{
         message::{MessageDirection, TextMessage, UiMessage},
         node::{StubNode, UINode},
+        stack_panel::StackPanelBuilder,
         text::TextBuilder,
         widget::WidgetBuilder,
         UserInterface,
     },
+    scene::node::Node,
     sound::{
         context::{self, Context},
         effects::{BaseEffect, Effect, EffectInput},
@@ -59,13 +138,13 @@ use rg3d::{
             generic::GenericSourceBuilder, spatial::SpatialSourceBuilder, SoundSource, Status,
         },
     },
-    utils::translate_event,
+    utils::{log::Log, translate_event},
 };
 use std::{
     cell::RefCell,
     fs::File,
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
     sync::{
         mpsc::{self, Receiver, Sender},
@@ -76,7 +155,23 @@ use std::{
 };
 
 const FIXED_FPS: f32 = 60.0;
-const SETTINGS_FILE: &'static str = "settings.json";
+const SAVE_FILE: &str = "save.bin";
+/// Starts and stops playback of camera path of current map.
+const CINEMATIC_KEY: VirtualKeyCode = VirtualKeyCode::F9;
+/// Opens and closes debug editor for placing entities, see `editor` module.
+const EDITOR_KEY: VirtualKeyCode = VirtualKeyCode::F7;
+/// Hides and shows back HUD and debug text, see `Hud::toggle_hud_free`.
+const HUD_FREE_KEY: VirtualKeyCode = VirtualKeyCode::F8;
+/// Starts recording of input in a new match and stops it, see `repro` module.
+const REPRO_KEY: VirtualKeyCode = VirtualKeyCode::F10;
+/// Reloads current map and its data files, see `Game::reload_level`.
+const RELOAD_LEVEL_KEY: VirtualKeyCode = VirtualKeyCode::F5;
+/// Writes snapshot of match state to the log, see `query` module.
+#[cfg(feature = "query_api")]
+const QUERY_KEY: VirtualKeyCode = VirtualKeyCode::F11;
+/// Time in seconds without input in main menu after which attract mode starts.
+const ATTRACT_IDLE_TIME: f32 = 60.0;
+const ATTRACT_BOT_COUNT: u32 = 6;
 
 // Define type aliases for engine structs.
 pub type UiNode = UINode<(), StubNode>;
@@ -88,18 +183,46 @@ pub type BuildContext<'a> = rg3d::gui::BuildContext<'a, (), StubNode>;
 
 pub struct Game {
     menu: Menu,
+    loadout_menu: LoadoutMenu,
     hud: Hud,
     engine: GameEngine,
     level: Option<Level>,
+    editor: Option<DebugEditor>,
+    /// Camera that flies along authored path of the map while match goes on.
+    cinematic: Option<CinematicCamera>,
+    /// Input of player that is being recorded for bug reproduction.
+    recording: Option<Recording>,
+    /// Recorded input that is fed to level instead of input of user.
+    replay: Option<Replay>,
+    /// Simulation ticks since start of current match, recorded input is bound to them.
+    match_tick: u64,
+    /// Time in seconds main menu was shown without any input, attract mode starts when it
+    /// is long enough.
+    menu_idle_time: f32,
     debug_text: UINodeHandle,
     debug_string: String,
+    profiler: FrameProfiler,
     last_tick_time: time::Instant,
     running: bool,
     control_scheme: Rc<RefCell<ControlScheme>>,
     time: GameTime,
     events_receiver: Receiver<Message>,
     events_sender: Sender<Message>,
+    event_bus: EventBus,
     sound_manager: SoundManager,
+    autosave: Autosave,
+    autosave_settings: AutosaveSettings,
+    /// Configuration of last started match, remembered across runs.
+    last_match: MatchSettings,
+    quality: Rc<RefCell<QualitySettings>>,
+    dynamic_quality: DynamicQuality,
+    /// Weapons player spawns with, remembered across runs.
+    loadout: LoadoutSettings,
+    /// Statistics of player across matches.
+    profile: Profile,
+    /// Standings of matches played since main menu was left.
+    session: SessionStandings,
+    dirs: DataDirs,
 }
 
 #[derive(Copy, Clone)]
@@ -118,6 +241,7 @@ pub enum CollisionGroups {
     All = std::isize::MAX,
 }
 
+/// Limits of every match mode are disabled if they're zero.
 #[derive(Copy, Clone, Debug)]
 pub struct DeathMatch {
     pub time_limit_secs: f32,
@@ -127,8 +251,8 @@ pub struct DeathMatch {
 impl Default for DeathMatch {
     fn default() -> Self {
         Self {
-            time_limit_secs: Default::default(),
-            frag_limit: 0,
+            time_limit_secs: 600.0,
+            frag_limit: 30,
         }
     }
 }
@@ -153,8 +277,8 @@ pub struct TeamDeathMatch {
 impl Default for TeamDeathMatch {
     fn default() -> Self {
         Self {
-            time_limit_secs: Default::default(),
-            team_frag_limit: 0,
+            time_limit_secs: 600.0,
+            team_frag_limit: 50,
         }
     }
 }
@@ -179,8 +303,8 @@ pub struct CaptureTheFlag {
 impl Default for CaptureTheFlag {
     fn default() -> Self {
         Self {
-            time_limit_secs: Default::default(),
-            flag_limit: 0,
+            time_limit_secs: 900.0,
+            flag_limit: 5,
         }
     }
 }
@@ -196,11 +320,177 @@ impl Visit for CaptureTheFlag {
     }
 }
 
+/// Free for all match for a skull, see `oddball` module. Score of an actor is the amount of
+/// seconds it held the skull.
+#[derive(Copy, Clone, Debug)]
+pub struct Oddball {
+    pub time_limit_secs: f32,
+    pub hold_limit: u32,
+}
+
+impl Default for Oddball {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 600.0,
+            hold_limit: 120,
+        }
+    }
+}
+
+impl Visit for Oddball {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.hold_limit.visit("HoldLimit", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Free for all match for a moving hill, see `koth` module. Score of an actor is the amount
+/// of seconds it was alone on the hill.
+#[derive(Copy, Clone, Debug)]
+pub struct KingOfTheHill {
+    pub time_limit_secs: f32,
+    pub hold_limit: u32,
+}
+
+impl Default for KingOfTheHill {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 600.0,
+            hold_limit: 120,
+        }
+    }
+}
+
+impl Visit for KingOfTheHill {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.hold_limit.visit("HoldLimit", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Player against a single bot in rounds, see `duel` module. Match is best of `rounds`, so
+/// the first one to win more than half of them wins it.
+#[derive(Copy, Clone, Debug)]
+pub struct Duel {
+    pub time_limit_secs: f32,
+    pub rounds: u32,
+}
+
+impl Default for Duel {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 0.0,
+            rounds: 5,
+        }
+    }
+}
+
+impl Duel {
+    pub fn rounds_to_win(&self) -> u32 {
+        self.rounds / 2 + 1
+    }
+}
+
+impl Visit for Duel {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.rounds.visit("Rounds", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Match where every actor has limited amount of lives. Actor that lost all of them is
+/// eliminated and the last one standing wins. In team variant a team is out when every its
+/// member is eliminated, and the last team standing wins.
+#[derive(Copy, Clone, Debug)]
+pub struct LastManStanding {
+    pub time_limit_secs: f32,
+    pub lives: u32,
+    pub teams: bool,
+}
+
+impl Default for LastManStanding {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 600.0,
+            lives: 3,
+            teams: false,
+        }
+    }
+}
+
+impl Visit for LastManStanding {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.lives.visit("Lives", visitor)?;
+        self.teams.visit("Teams", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Practice on a shooting range, has no limits and no bots.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Practice;
+
+impl Visit for Practice {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Runs through a course of a map against the clock, see `race` module. Has no limits, no
+/// bots and no combat.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TimeTrial;
+
+impl Visit for TimeTrial {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Guided tutorial, see `tutorial` module. Has no limits and no bots.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Tutorial;
+
+impl Visit for Tutorial {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        visitor.leave_region()
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum MatchOptions {
     DeathMatch(DeathMatch),
     TeamDeathMatch(TeamDeathMatch),
     CaptureTheFlag(CaptureTheFlag),
+    Practice(Practice),
+    Tutorial(Tutorial),
+    Oddball(Oddball),
+    KingOfTheHill(KingOfTheHill),
+    TimeTrial(TimeTrial),
+    Duel(Duel),
+    LastManStanding(LastManStanding),
 }
 
 impl MatchOptions {
@@ -209,6 +499,13 @@ impl MatchOptions {
             0 => Ok(MatchOptions::DeathMatch(Default::default())),
             1 => Ok(MatchOptions::TeamDeathMatch(Default::default())),
             2 => Ok(MatchOptions::CaptureTheFlag(Default::default())),
+            3 => Ok(MatchOptions::Practice(Default::default())),
+            4 => Ok(MatchOptions::Tutorial(Default::default())),
+            5 => Ok(MatchOptions::Oddball(Default::default())),
+            6 => Ok(MatchOptions::KingOfTheHill(Default::default())),
+            7 => Ok(MatchOptions::TimeTrial(Default::default())),
+            8 => Ok(MatchOptions::Duel(Default::default())),
+            9 => Ok(MatchOptions::LastManStanding(Default::default())),
             _ => Err(format!("Invalid match options {}", id)),
         }
     }
@@ -218,6 +515,118 @@ impl MatchOptions {
             MatchOptions::DeathMatch(_) => 0,
             MatchOptions::TeamDeathMatch(_) => 1,
             MatchOptions::CaptureTheFlag(_) => 2,
+            MatchOptions::Practice(_) => 3,
+            MatchOptions::Tutorial(_) => 4,
+            MatchOptions::Oddball(_) => 5,
+            MatchOptions::KingOfTheHill(_) => 6,
+            MatchOptions::TimeTrial(_) => 7,
+            MatchOptions::Duel(_) => 8,
+            MatchOptions::LastManStanding(_) => 9,
+        }
+    }
+
+    pub fn time_limit_secs(&self) -> f32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.time_limit_secs,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.time_limit_secs,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.time_limit_secs,
+            MatchOptions::Oddball(oddball) => oddball.time_limit_secs,
+            MatchOptions::KingOfTheHill(koth) => koth.time_limit_secs,
+            MatchOptions::Duel(duel) => duel.time_limit_secs,
+            MatchOptions::LastManStanding(lms) => lms.time_limit_secs,
+            MatchOptions::Practice(_) | MatchOptions::Tutorial(_) | MatchOptions::TimeTrial(_) => {
+                0.0
+            }
+        }
+    }
+
+    pub fn is_team_based(&self) -> bool {
+        match self {
+            MatchOptions::DeathMatch(_)
+            | MatchOptions::Oddball(_)
+            | MatchOptions::KingOfTheHill(_)
+            | MatchOptions::Duel(_)
+            | MatchOptions::Practice(_)
+            | MatchOptions::Tutorial(_)
+            | MatchOptions::TimeTrial(_) => false,
+            MatchOptions::TeamDeathMatch(_) | MatchOptions::CaptureTheFlag(_) => true,
+            MatchOptions::LastManStanding(lms) => lms.teams,
+        }
+    }
+
+    /// Death match and duel are about picking up weapons on the map, so there loadout can
+    /// only be selected before first spawn.
+    pub fn allows_loadout_change(&self) -> bool {
+        match self {
+            MatchOptions::DeathMatch(_) | MatchOptions::Duel(_) => false,
+            MatchOptions::TeamDeathMatch(_)
+            | MatchOptions::CaptureTheFlag(_)
+            | MatchOptions::Oddball(_)
+            | MatchOptions::KingOfTheHill(_)
+            | MatchOptions::LastManStanding(_)
+            | MatchOptions::Practice(_)
+            | MatchOptions::Tutorial(_)
+            | MatchOptions::TimeTrial(_) => true,
+        }
+    }
+
+    pub fn is_practice(&self) -> bool {
+        matches!(self, MatchOptions::Practice(_))
+    }
+
+    pub fn is_tutorial(&self) -> bool {
+        matches!(self, MatchOptions::Tutorial(_))
+    }
+
+    pub fn is_time_trial(&self) -> bool {
+        matches!(self, MatchOptions::TimeTrial(_))
+    }
+
+    /// Practice, tutorial and time trial are played alone.
+    pub fn has_bots(&self) -> bool {
+        !self.is_practice() && !self.is_tutorial() && !self.is_time_trial()
+    }
+
+    pub fn is_oddball(&self) -> bool {
+        matches!(self, MatchOptions::Oddball(_))
+    }
+
+    pub fn is_king_of_the_hill(&self) -> bool {
+        matches!(self, MatchOptions::KingOfTheHill(_))
+    }
+
+    pub fn is_duel(&self) -> bool {
+        matches!(self, MatchOptions::Duel(_))
+    }
+
+    /// Lives of every actor, zero means that lives are unlimited.
+    pub fn lives(&self) -> u32 {
+        match self {
+            MatchOptions::LastManStanding(lms) => lms.lives,
+            _ => 0,
+        }
+    }
+
+    /// Oddball and king of the hill rank actors by hold time instead of frags.
+    pub fn scores_hold_time(&self) -> bool {
+        self.is_oddball() || self.is_king_of_the_hill()
+    }
+
+    /// Returns frag limit, team frag limit, flag limit, hold limit or rounds to win depending
+    /// on mode.
+    pub fn score_limit(&self) -> u32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.frag_limit,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.team_frag_limit,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.flag_limit,
+            MatchOptions::Oddball(oddball) => oddball.hold_limit,
+            MatchOptions::KingOfTheHill(koth) => koth.hold_limit,
+            MatchOptions::Duel(duel) => duel.rounds_to_win(),
+            // Last man standing ends by elimination, not by score.
+            MatchOptions::LastManStanding(_)
+            | MatchOptions::Practice(_)
+            | MatchOptions::Tutorial(_)
+            | MatchOptions::TimeTrial(_) => 0,
         }
     }
 }
@@ -241,33 +650,124 @@ impl Visit for MatchOptions {
             MatchOptions::DeathMatch(o) => o.visit("Data", visitor)?,
             MatchOptions::TeamDeathMatch(o) => o.visit("Data", visitor)?,
             MatchOptions::CaptureTheFlag(o) => o.visit("Data", visitor)?,
+            MatchOptions::Practice(o) => o.visit("Data", visitor)?,
+            MatchOptions::Tutorial(o) => o.visit("Data", visitor)?,
+            MatchOptions::Oddball(o) => o.visit("Data", visitor)?,
+            MatchOptions::KingOfTheHill(o) => o.visit("Data", visitor)?,
+            MatchOptions::TimeTrial(o) => o.visit("Data", visitor)?,
+            MatchOptions::Duel(o) => o.visit("Data", visitor)?,
+            MatchOptions::LastManStanding(o) => o.visit("Data", visitor)?,
         }
 
         visitor.leave_region()
     }
 }
 
+/// Speed of sound in meters per second.
+const SPEED_OF_SOUND: f32 = 343.0;
+/// Velocities are exaggerated for Doppler effect, at real speed of sound projectiles of the
+/// game are too slow to be heard pitch-shifted.
+const DOPPLER_FACTOR: f32 = 8.0;
+const MIN_DOPPLER_PITCH: f32 = 0.5;
+const MAX_DOPPLER_PITCH: f32 = 2.0;
+
+/// Looping sound that follows a scene node, for example flyby sound of a projectile.
+#[derive(Default)]
+struct AttachedSound {
+    node: Handle<Node>,
+    source: Handle<SoundSource>,
+    velocity: Vec3,
+}
+
+impl Visit for AttachedSound {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.node.visit("Node", visitor)?;
+        self.source.visit("Source", visitor)?;
+        self.velocity.visit("Velocity", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Pitch of a sound moving with given velocity relative to listener.
+fn doppler_pitch(
+    source_position: Vec3,
+    source_velocity: Vec3,
+    listener_position: Vec3,
+    listener_velocity: Vec3,
+) -> f32 {
+    let to_listener = match (listener_position - source_position).normalized() {
+        Some(direction) => direction,
+        None => return 1.0,
+    };
+    // Positive speeds mean that source and listener are approaching.
+    let speed_of_sound = SPEED_OF_SOUND / DOPPLER_FACTOR;
+    let source_speed = source_velocity.dot(&to_listener).min(speed_of_sound * 0.9);
+    let listener_speed = -listener_velocity.dot(&to_listener);
+    ((speed_of_sound + listener_speed) / (speed_of_sound - source_speed))
+        .max(MIN_DOPPLER_PITCH)
+        .min(MAX_DOPPLER_PITCH)
+}
+
+/// Distance from listener to test sounds of speaker test.
+const SPEAKER_TEST_DISTANCE: f32 = 2.0;
+/// Time between left and right sounds of speaker test.
+const SPEAKER_TEST_INTERVAL: f32 = 0.8;
+
 pub struct SoundManager {
     context: Arc<Mutex<Context>>,
     music: Handle<SoundSource>,
+    /// Reverb of sounds outside of reverb zones.
     reverb: Handle<Effect>,
+    /// Reverbs of zone presets, indexed by preset id.
+    zone_reverbs: Vec<Handle<Effect>>,
+    zones: Vec<ReverbZone>,
+    attached: Vec<AttachedSound>,
+    /// Used to find velocity of listener for Doppler effect.
+    last_listener_position: Vec3,
+    /// Sounds that failed to load, warning is written only once for each of them.
+    missing_sounds: Vec<PathBuf>,
+    variation: SoundVariation,
+    speaker_mode: SpeakerMode,
+    /// Looping ambient sounds of current level.
+    ambience: Vec<Handle<SoundSource>>,
+    /// Pending sounds of speaker test - time left before a sound and side of listener
+    /// (-1.0 is left, 1.0 is right).
+    speaker_test: Vec<(f32, f32)>,
 }
 
 impl SoundManager {
-    pub fn new(context: Arc<Mutex<Context>>, resource_manager: ResourceManager) -> Self {
-        let buffer = rg3d::futures::executor::block_on(
-            resource_manager
-                .request_sound_buffer(assets::sounds::SOUNDTRACK, true),
-        )
-        .unwrap();
-        let music = context.lock().unwrap().add_source(
-            GenericSourceBuilder::new(buffer.into())
-                .with_looping(true)
-                .with_status(Status::Playing)
-                .with_gain(0.25)
-                .build_source()
-                .unwrap(),
-        );
+    pub const SUBSCRIPTIONS: &'static [MessageCategory] =
+        &[MessageCategory::Sound, MessageCategory::Notification];
+
+    pub fn new(
+        context: Arc<Mutex<Context>>,
+        resource_manager: ResourceManager,
+        settings: &SoundSettings,
+    ) -> Self {
+        SoundSettings::apply_speaker_mode(&mut context.lock().unwrap(), settings.speaker_mode);
+
+        let music = match rg3d::futures::executor::block_on(
+            resource_manager.request_sound_buffer(mods::resolve(assets::sounds::SOUNDTRACK), true),
+        ) {
+            Ok(buffer) => context.lock().unwrap().add_source(
+                GenericSourceBuilder::new(buffer.into())
+                    .with_looping(true)
+                    .with_status(Status::Playing)
+                    .with_gain(0.25)
+                    .build_source()
+                    .unwrap(),
+            ),
+            Err(_) => {
+                Log::writeln(format!(
+                    "WARNING: Unable to load soundtrack {}, music disabled.",
+                    assets::sounds::SOUNDTRACK
+                ));
+                Handle::NONE
+            }
+        };
 
         let mut base_effect = BaseEffect::default();
         base_effect.set_gain(0.7);
@@ -277,17 +777,214 @@ impl SoundManager {
             .lock()
             .unwrap()
             .add_effect(rg3d::sound::effects::Effect::Reverb(reverb));
+        let zone_reverbs = ReverbPreset::ALL
+            .iter()
+            .map(|preset| context.lock().unwrap().add_effect(preset.create_effect()))
+            .collect();
 
         Self {
             context,
             music,
             reverb,
+            zone_reverbs,
+            zones: Default::default(),
+            attached: Default::default(),
+            last_listener_position: Default::default(),
+            missing_sounds: Default::default(),
+            variation: SoundVariation::new(settings.variation.clone()),
+            speaker_mode: settings.speaker_mode,
+            ambience: Default::default(),
+            speaker_test: Default::default(),
         }
     }
 
-    pub async fn handle_message(&mut self, resource_manager: ResourceManager, message: &Message) {
+    /// Removes ambient sounds and reverb zones of level, must be called when level is
+    /// destroyed.
+    pub fn clear_level_sounds(&mut self) {
         let mut context = self.context.lock().unwrap();
+        for source in self.ambience.drain(..) {
+            context.remove_source(source);
+        }
+        for attached in self.attached.drain(..) {
+            context.remove_source(attached.source);
+        }
+        self.zones.clear();
+    }
+
+    /// Reverb for a sound at given position - reverb of zone of the sound, or of zone of
+    /// listener if sound is outside of zones.
+    fn reverb_at(&self, context: &Context, position: Vec3) -> Handle<Effect> {
+        let listener = context.listener().position();
+        self.zones
+            .iter()
+            .find(|zone| zone.contains(position))
+            .or_else(|| self.zones.iter().find(|zone| zone.contains(listener)))
+            .and_then(|zone| self.zone_reverbs.get(zone.preset().id() as usize))
+            .cloned()
+            .unwrap_or(self.reverb)
+    }
+
+    pub fn speaker_mode(&self) -> SpeakerMode {
+        self.speaker_mode
+    }
+
+    pub fn settings(&self) -> SoundSettings {
+        SoundSettings {
+            sound_volume: self.context.lock().unwrap().master_gain(),
+            speaker_mode: self.speaker_mode,
+            variation: self.variation.settings().clone(),
+        }
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn play_sound(
+        &mut self,
+        resource_manager: ResourceManager,
+        path: &Path,
+        position: Vec3,
+        gain: f32,
+        rolloff_factor: f32,
+        radius: f32,
+        category: SoundCategory,
+    ) {
+        let path = self.variation.next_variant(path);
+        if self.missing_sounds.contains(&path) {
+            return;
+        }
+        let shot_buffer = match resource_manager
+            .request_sound_buffer(mods::resolve(&path), false)
+            .await
+        {
+            Ok(buffer) => buffer,
+            Err(_) => {
+                Log::writeln(format!(
+                    "WARNING: Unable to load sound {}, skipped.",
+                    path.display()
+                ));
+                self.missing_sounds.push(path);
+                return;
+            }
+        };
+        let (pitch, gain_factor) = self.variation.randomize(category);
+        let gain = gain * gain_factor;
+        let mut context = self.context.lock().unwrap();
+        let shot_sound = if self.speaker_mode == SpeakerMode::Mono {
+            // Sound without position plays equally in both channels, so distance attenuation
+            // is done here, with the same formula spatial sources use.
+            let distance = context.listener().position().distance(&position);
+            let attenuation =
+                radius / (radius + rolloff_factor * (distance.max(radius) - radius)).max(0.001);
+            GenericSourceBuilder::new(shot_buffer.into())
+                .with_status(Status::Playing)
+                .with_play_once(true)
+                .with_gain(gain * attenuation)
+                .with_pitch(pitch as f64)
+                .build_source()
+                .unwrap()
+        } else {
+            SpatialSourceBuilder::new(
+                GenericSourceBuilder::new(shot_buffer.into())
+                    .with_status(Status::Playing)
+                    .with_play_once(true)
+                    .with_gain(gain)
+                    .with_pitch(pitch as f64)
+                    .build()
+                    .unwrap(),
+            )
+            .with_position(position)
+            .with_radius(radius)
+            .with_rolloff_factor(rolloff_factor)
+            .build_source()
+        };
+        let reverb = self.reverb_at(&context, position);
+        let source = context.add_source(shot_sound);
+        context
+            .effect_mut(reverb)
+            .add_input(EffectInput::direct(source));
+    }
+
+    /// Plays sound without position, it is heard equally everywhere.
+    async fn play_voice(&mut self, resource_manager: ResourceManager, path: &Path) {
+        if self.missing_sounds.iter().any(|missing| missing == path) {
+            return;
+        }
+        match resource_manager
+            .request_sound_buffer(mods::resolve(path), false)
+            .await
+        {
+            Ok(buffer) => {
+                self.context.lock().unwrap().add_source(
+                    GenericSourceBuilder::new(buffer.into())
+                        .with_status(Status::Playing)
+                        .with_play_once(true)
+                        .build_source()
+                        .unwrap(),
+                );
+            }
+            Err(_) => {
+                Log::writeln(format!(
+                    "WARNING: Unable to load sound {}, skipped.",
+                    path.display()
+                ));
+                self.missing_sounds.push(path.to_owned());
+            }
+        }
+    }
+
+    /// Applies Doppler effect to attached sounds and plays pending sounds of speaker test.
+    pub async fn update(&mut self, resource_manager: ResourceManager, dt: f32) {
+        {
+            let mut context = self.context.lock().unwrap();
+            let listener_position = context.listener().position();
+            let listener_velocity = if dt > 0.0 {
+                (listener_position - self.last_listener_position).scale(1.0 / dt)
+            } else {
+                Vec3::ZERO
+            };
+            self.last_listener_position = listener_position;
+            for attached in self.attached.iter() {
+                if let SoundSource::Spatial(source) = context.source_mut(attached.source) {
+                    let pitch = doppler_pitch(
+                        source.position(),
+                        attached.velocity,
+                        listener_position,
+                        listener_velocity,
+                    );
+                    source.set_pitch(pitch as f64);
+                }
+            }
+        }
+
+        for (time, _) in self.speaker_test.iter_mut() {
+            *time -= dt;
+        }
+        let due = self
+            .speaker_test
+            .iter()
+            .filter(|(time, _)| *time <= 0.0)
+            .map(|(_, side)| *side)
+            .collect::<Vec<_>>();
+        self.speaker_test.retain(|(time, _)| *time > 0.0);
+        for side in due {
+            let position = {
+                let context = self.context.lock().unwrap();
+                let listener = context.listener();
+                listener.position() + listener.ear_axis().scale(side * SPEAKER_TEST_DISTANCE)
+            };
+            self.play_sound(
+                resource_manager.clone(),
+                Path::new(assets::sounds::ITEM_PICKUP),
+                position,
+                1.0,
+                1.0,
+                SPEAKER_TEST_DISTANCE,
+                SoundCategory::Other,
+            )
+            .await;
+        }
+    }
+
+    pub async fn handle_message(&mut self, resource_manager: ResourceManager, message: &Message) {
         match message {
             Message::PlaySound {
                 path,
@@ -295,30 +992,145 @@ impl SoundManager {
                 gain,
                 rolloff_factor,
                 radius,
+                category,
             } => {
-                let shot_buffer = resource_manager
-                    .request_sound_buffer(path, false)
-                    .await
-                    .unwrap();
-                let shot_sound = SpatialSourceBuilder::new(
-                    GenericSourceBuilder::new(shot_buffer.into())
-                        .with_status(Status::Playing)
-                        .with_play_once(true)
-                        .with_gain(*gain)
-                        .build()
-                        .unwrap(),
+                self.play_sound(
+                    resource_manager,
+                    path,
+                    *position,
+                    *gain,
+                    *rolloff_factor,
+                    *radius,
+                    *category,
                 )
-                .with_position(*position)
-                .with_radius(*radius)
-                .with_rolloff_factor(*rolloff_factor)
-                .build_source();
-                let source = context.add_source(shot_sound);
-                context
-                    .effect_mut(self.reverb)
-                    .add_input(EffectInput::direct(source));
+                .await
             }
+            Message::Announce { sound, .. } => self.play_voice(resource_manager, sound).await,
             Message::SetMusicVolume { volume } => {
-                context.source_mut(self.music).set_gain(*volume);
+                if self.music.is_some() {
+                    self.context
+                        .lock()
+                        .unwrap()
+                        .source_mut(self.music)
+                        .set_gain(*volume);
+                }
+            }
+            Message::AddAmbientSound {
+                path,
+                position,
+                gain,
+                rolloff_factor,
+                radius,
+            } => {
+                match resource_manager
+                    .request_sound_buffer(mods::resolve(path), true)
+                    .await
+                {
+                    Ok(buffer) => {
+                        let mut context = self.context.lock().unwrap();
+                        let source = context.add_source(
+                            SpatialSourceBuilder::new(
+                                GenericSourceBuilder::new(buffer.into())
+                                    .with_looping(true)
+                                    .with_status(Status::Playing)
+                                    .with_gain(*gain)
+                                    .build()
+                                    .unwrap(),
+                            )
+                            .with_position(*position)
+                            .with_radius(*radius)
+                            .with_rolloff_factor(*rolloff_factor)
+                            .build_source(),
+                        );
+                        let reverb = self.reverb_at(&context, *position);
+                        context
+                            .effect_mut(reverb)
+                            .add_input(EffectInput::direct(source));
+                        self.ambience.push(source);
+                    }
+                    Err(_) => Log::writeln(format!(
+                        "WARNING: Unable to load ambient sound {}, skipped.",
+                        path.display()
+                    )),
+                }
+            }
+            Message::AttachSound {
+                node,
+                path,
+                position,
+                gain,
+                radius,
+            } => {
+                if self.missing_sounds.contains(path) {
+                    return;
+                }
+                match resource_manager
+                    .request_sound_buffer(mods::resolve(path), false)
+                    .await
+                {
+                    Ok(buffer) => {
+                        let mut context = self.context.lock().unwrap();
+                        let source = context.add_source(
+                            SpatialSourceBuilder::new(
+                                GenericSourceBuilder::new(buffer.into())
+                                    .with_looping(true)
+                                    .with_status(Status::Playing)
+                                    .with_gain(*gain)
+                                    .build()
+                                    .unwrap(),
+                            )
+                            .with_position(*position)
+                            .with_radius(*radius)
+                            .build_source(),
+                        );
+                        let reverb = self.reverb_at(&context, *position);
+                        context
+                            .effect_mut(reverb)
+                            .add_input(EffectInput::direct(source));
+                        self.attached.push(AttachedSound {
+                            node: *node,
+                            source,
+                            velocity: Vec3::ZERO,
+                        });
+                    }
+                    Err(_) => {
+                        Log::writeln(format!(
+                            "WARNING: Unable to load sound {}, it will be silent.",
+                            path.display()
+                        ));
+                        self.missing_sounds.push(path.clone());
+                    }
+                }
+            }
+            Message::MoveAttachedSound {
+                node,
+                position,
+                velocity,
+            } => {
+                if let Some(attached) = self.attached.iter_mut().find(|a| a.node == *node) {
+                    attached.velocity = *velocity;
+                    if let SoundSource::Spatial(source) =
+                        self.context.lock().unwrap().source_mut(attached.source)
+                    {
+                        source.set_position(position);
+                    }
+                }
+            }
+            Message::DetachSound { node } => {
+                if let Some(index) = self.attached.iter().position(|a| a.node == *node) {
+                    let attached = self.attached.remove(index);
+                    self.context.lock().unwrap().remove_source(attached.source);
+                }
+            }
+            Message::SetReverbZones { zones } => {
+                self.zones = zones.clone();
+            }
+            Message::SetSpeakerMode { mode } => {
+                SoundSettings::apply_speaker_mode(&mut self.context.lock().unwrap(), *mode);
+                self.speaker_mode = *mode;
+            }
+            Message::TestSpeakers => {
+                self.speaker_test = vec![(0.0, -1.0), (SPEAKER_TEST_INTERVAL, 1.0)];
             }
             _ => {}
         }
@@ -331,12 +1143,18 @@ impl Visit for SoundManager {
 
         self.reverb.visit("Reverb", visitor)?;
         self.music.visit("Music", visitor)?;
+        self.ambience.visit("Ambience", visitor)?;
+        self.zone_reverbs.visit("ZoneReverbs", visitor)?;
+        self.zones.visit("Zones", visitor)?;
+        self.attached.visit("Attached", visitor)?;
 
         visitor.leave_region()
     }
 }
 
 impl Game {
+    pub const SUBSCRIPTIONS: &'static [MessageCategory] = &[MessageCategory::Session];
+
     pub fn run() {
         let events_loop = EventLoop::<()>::new();
 
@@ -351,26 +1169,31 @@ impl Game {
             .with_inner_size(inner_size)
             .with_resizable(true);
 
-        let settings = settings::Settings::load_from_file(SETTINGS_FILE);
+        balance::report_from_args();
+        let dirs = DataDirs::from_args();
+        let settings = settings::Settings::load_from_file(dirs.settings_file());
+        // Mods must be applied before first resource is requested.
+        mods::apply(&settings.mods);
+        let missing_assets = asset_check::find_missing();
         let mut engine = GameEngine::new(window_builder, &events_loop).unwrap();
+        if navmesh_cache::bake_from_args(&mut engine) {
+            return;
+        }
 
 	engine.renderer.quality_settings = settings.renderer;
 
-        {
-            let sound_context = &mut engine.sound_context.lock().unwrap();
-            if settings.sound.hrtf {
-                SoundSettings::hrtf_on(sound_context);
-            } else {
-                SoundSettings::hrtf_off(sound_context);
-            }
-            sound_context.set_master_gain(settings.sound.sound_volume);
-        }
+        engine
+            .sound_context
+            .lock()
+            .unwrap()
+            .set_master_gain(settings.sound.sound_volume);
 
         effects::register_custom_emitter_factory();
 
         engine.renderer.set_ambient_color(Color::opaque(60, 60, 60));
 
         let control_scheme = Rc::new(RefCell::new(settings.controls));
+        let quality = Rc::new(RefCell::new(settings.quality));
 
         let fixed_timestep = 1.0 / FIXED_FPS;
 
@@ -385,26 +1208,69 @@ impl Game {
         let sound_manager = SoundManager::new(
             engine.sound_context.clone(),
             engine.resource_manager.clone(),
+            &settings.sound,
         );
 
+        // Order of subscription defines order in which subscribers receive messages.
+        let mut event_bus = EventBus::new();
+        event_bus.subscribe(Subscriber::Game, Game::SUBSCRIPTIONS);
+        event_bus.subscribe(Subscriber::SoundManager, SoundManager::SUBSCRIPTIONS);
+        event_bus.subscribe(Subscriber::Level, Level::SUBSCRIPTIONS);
+        event_bus.subscribe(Subscriber::Hud, Hud::SUBSCRIPTIONS);
+
         let mut game = Game {
             sound_manager,
             hud: Hud::new(&mut engine),
             running: true,
-            menu: Menu::new(&mut engine, control_scheme.clone(), tx.clone()),
+            menu: Menu::new(
+                &mut engine,
+                control_scheme.clone(),
+                quality.clone(),
+                settings.sound.speaker_mode,
+                &settings.last_match,
+                dirs.save_file(SAVE_FILE),
+                &missing_assets,
+                tx.clone(),
+            ),
+            loadout_menu: LoadoutMenu::new(
+                &mut engine.user_interface,
+                &settings.loadout,
+                tx.clone(),
+            ),
             control_scheme,
             debug_text: Handle::NONE,
+            profiler: FrameProfiler::new(&mut engine.user_interface.build_ctx()),
+            dynamic_quality: DynamicQuality::new(&engine.renderer),
             engine,
             level: None,
+            editor: None,
+            cinematic: None,
+            recording: None,
+            replay: None,
+            match_tick: 0,
+            menu_idle_time: 0.0,
             debug_string: String::new(),
             last_tick_time: time::Instant::now(),
             time,
             events_receiver: rx,
             events_sender: tx,
+            event_bus,
+            autosave: Autosave::new(&settings.autosave),
+            autosave_settings: settings.autosave,
+            last_match: settings.last_match,
+            quality,
+            loadout: settings.loadout,
+            profile: Profile::load_from_file(dirs.profile_file()),
+            session: Default::default(),
+            dirs,
         };
 
         game.create_debug_ui();
 
+        if let Some(path) = Recording::path_from_args() {
+            game.start_replay(&path);
+        }
+
         events_loop.run(move |event, _, control_flow| {
             game.process_input_event(&event);
 
@@ -419,6 +1285,12 @@ impl Game {
 
                         while let Some(ui_event) = game.engine.user_interface.poll_message() {
                             game.menu.handle_ui_event(&mut game.engine, &ui_event);
+                            // Preview of view settings while match is paused.
+                            if let Some(level) = game.level.as_mut() {
+                                level.apply_viewmodel(&mut game.engine);
+                            }
+                            game.loadout_menu
+                                .handle_ui_event(&mut game.engine.user_interface, &ui_event);
                         }
                     }
                     if !game.running {
@@ -428,6 +1300,7 @@ impl Game {
                 }
                 Event::RedrawRequested(_) => {
                     game.update_statistics(game.time.elapsed);
+                    game.update_dynamic_quality();
 
                     // <<<<< ENABLE THIS TO SHOW DEBUG GEOMETRY >>>>>
                     if false {
@@ -436,6 +1309,9 @@ impl Game {
 
                     // Render at max speed
                     game.engine.render(fixed_timestep).unwrap();
+                    // Autosave right after frame was rendered, so it will eat time that
+                    // otherwise would be spent in sleep to cap frame rate.
+                    game.process_autosave();
                     // Make sure to cap update rate to 60 FPS.
                     game.limit_fps(FIXED_FPS as f64);
                 }
@@ -464,11 +1340,18 @@ impl Game {
     }
 
     pub fn create_debug_ui(&mut self) {
-        self.debug_text = TextBuilder::new(WidgetBuilder::new().with_width(400.0))
-            .build(&mut self.engine.user_interface.build_ctx());
+        let ctx = &mut self.engine.user_interface.build_ctx();
+        self.debug_text = TextBuilder::new(WidgetBuilder::new().with_width(400.0)).build(ctx);
+        let debug_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_child(self.debug_text)
+                .with_child(self.profiler.graph),
+        )
+        .build(ctx);
+        self.hud.add_debug_widget(debug_panel);
     }
 
-    pub fn save_game(&mut self) -> VisitResult {
+    fn write_state(&mut self) -> Result<Visitor, VisitError> {
         let mut visitor = Visitor::new();
 
         // Visit engine state first.
@@ -478,18 +1361,44 @@ impl Game {
 
         self.sound_manager.visit("SoundManager", &mut visitor)?;
 
+        Ok(visitor)
+    }
+
+    pub fn save_game(&mut self) -> VisitResult {
+        let mut visitor = self.write_state()?;
+
         // Debug output
-        if let Ok(mut file) = File::create(Path::new("save.txt")) {
+        if let Ok(mut file) = File::create(self.dirs.save_file("save.txt")) {
             file.write_all(visitor.save_text().as_bytes()).unwrap();
         }
 
-        visitor.save_binary(Path::new("save.bin"))
+        visitor.save_binary(self.dirs.save_file(SAVE_FILE))
     }
 
-    pub fn load_game(&mut self) -> VisitResult {
-        println!("Attempting load a save...");
+    /// Serializes state into memory, file is written by autosave thread.
+    fn write_autosave(&mut self) -> VisitResult {
+        let mut data = Vec::new();
+        self.write_state()?.save_binary_to_memory(&mut data)?;
+        self.autosave
+            .store(data, &self.autosave_settings, &self.dirs);
 
-        let mut visitor = Visitor::load_binary(Path::new("save.bin"))?;
+        Ok(())
+    }
+
+    fn process_autosave(&mut self) {
+        if self.autosave.take_pending() {
+            if self.autosave.is_writing() {
+                println!("Previous autosave is still being written, autosave skipped");
+            } else if let Err(e) = self.write_autosave() {
+                println!("Failed to autosave, reason: {}", e);
+            }
+        }
+    }
+
+    pub fn load_game(&mut self, path: &Path) -> VisitResult {
+        println!("Attempting load a save {}...", path.display());
+
+        let mut visitor = Visitor::load_binary(path)?;
 
         // Clean up.
         self.destroy_level();
@@ -507,6 +1416,11 @@ impl Game {
 
         self.sound_manager.visit("SoundManager", &mut visitor)?;
         self.sound_manager.context = self.engine.sound_context.clone();
+        // Speaker mode is a preference of user, not a part of save.
+        SoundSettings::apply_speaker_mode(
+            &mut self.engine.sound_context.lock().unwrap(),
+            self.sound_manager.speaker_mode(),
+        );
 
         // Hide menu only of we successfully loaded a save.
         self.set_menu_visible(false);
@@ -514,50 +1428,186 @@ impl Game {
         // Set control scheme for player.
         if let Some(level) = &mut self.level {
             level.set_message_sender(self.events_sender.clone());
+            level.load_scripts();
+            level.load_logic();
+            level.set_best_run(self.profile.best_run(level.map()).cloned());
             level.build_navmesh(&mut self.engine);
+            level.collect_props(&mut self.engine);
+            level.build_tags(&self.engine);
+            level.apply_lighting(&mut self.engine);
             level.control_scheme = Some(self.control_scheme.clone());
+            level.quality = self.quality.borrow().clone();
+            level.set_player_loadout(self.loadout.weapons());
             let player = level.get_player();
             if let Actor::Player(player) = level.actors_mut().get_mut(player) {
                 player.set_control_scheme(self.control_scheme.clone());
             }
+            if level.needs_player_deploy() {
+                self.loadout_menu.open(&mut self.engine.user_interface);
+            }
         }
 
         self.time.elapsed = self.time.clock.elapsed().as_secs_f64();
+        self.autosave.reset(&self.autosave_settings);
 
         Ok(())
     }
 
     fn destroy_level(&mut self) {
+        self.leave_editor();
+        // Camera of cinematic belongs to scene of level, it's removed with the scene.
+        self.cinematic = None;
+        self.stop_recording();
+        self.replay = None;
+        self.loadout_menu.close(&mut self.engine.user_interface);
+        self.sound_manager.clear_level_sounds();
         if let Some(ref mut level) = self.level.take() {
             level.destroy(&mut self.engine);
             println!("Current level destroyed!");
         }
     }
 
-    fn exit_game(&self, control_flow: &mut rg3d::event_loop::ControlFlow) {
+    fn exit_game(&mut self, control_flow: &mut rg3d::event_loop::ControlFlow) {
+        self.autosave.finish();
         let settings = Settings {
+            version: settings::SETTINGS_VERSION,
             controls: self.control_scheme.borrow().clone(),
-            renderer: self.engine.renderer.get_quality_settings(),
-            sound: SoundSettings::get_from_engine(&self.engine.sound_context.lock().unwrap()),
+            // Dynamic quality may have lowered settings, settings of user must be saved.
+            renderer: self.dynamic_quality.base_settings(&self.engine.renderer),
+            quality: self.quality.borrow().clone(),
+            sound: self.sound_manager.settings(),
+            autosave: self.autosave_settings.clone(),
+            last_match: self.last_match.clone(),
+            mods: mods::settings(),
+            loadout: self.loadout.clone(),
         };
-        settings.write_to_file(SETTINGS_FILE);
+        settings.write_to_file(self.dirs.settings_file());
+        self.dirs.store_log();
         *control_flow = ControlFlow::Exit;
     }
 
-    pub fn start_new_game(&mut self, options: MatchOptions) {
+    /// Replaces current level with a new one, returns false if level can't be created.
+    fn create_level(&mut self, settings: &MatchSettings) -> bool {
         self.destroy_level();
-        self.level = Some(rg3d::futures::executor::block_on(Level::new(
+        let level = rg3d::futures::executor::block_on(Level::new(
             &mut self.engine,
             self.control_scheme.clone(),
             self.events_sender.clone(),
-            options,
-        )));
-        self.set_menu_visible(false);
+            settings,
+        ));
+        match level {
+            Ok(mut level) => {
+                level.quality = self.quality.borrow().clone();
+                level.set_player_loadout(self.loadout.weapons());
+                level.set_best_run(self.profile.best_run(&settings.map).cloned());
+                if settings.adaptive_difficulty {
+                    level.set_bot_skill(
+                        self.profile
+                            .rating
+                            .adaptive_bot_skill(settings.bot_difficulty),
+                    );
+                }
+                self.level = Some(level);
+                self.match_tick = 0;
+                self.autosave.reset(&self.autosave_settings);
+                self.set_menu_visible(false);
+                true
+            }
+            Err(e) => {
+                Log::writeln(format!("Unable to start new game: {}", e));
+                false
+            }
+        }
+    }
+
+    pub fn start_new_game(&mut self, settings: MatchSettings) {
+        self.hud
+            .leader_board_mut()
+            .hide_match_end(&mut self.engine.user_interface);
+        let created = self.create_level(&settings);
+        self.last_match = settings;
+        if created {
+            self.loadout_menu.open(&mut self.engine.user_interface);
+        }
+    }
+
+    /// Continues session with settings of last match on next map of the rotation, maps that
+    /// aren't in the rotation are played again.
+    fn start_next_match(&mut self) {
+        let mut settings = self.last_match.clone();
+        if let Some(index) = MAPS.iter().position(|(_, path)| *path == settings.map) {
+            settings.map = MAPS[(index + 1) % MAPS.len()].1.to_owned();
+        }
+        self.start_new_game(settings);
+    }
+
+    /// Rebuilds current level from map model and data files of the map with settings of last
+    /// match, so changes of a map can be checked without restarting. Player keeps its
+    /// position and health if it is alive, the match itself starts over.
+    fn reload_level(&mut self) {
+        let (map, restore) = match self.level.as_ref() {
+            Some(level) if !level.is_attract_mode() => {
+                (level.map().to_owned(), level.player_restore(&self.engine))
+            }
+            _ => return,
+        };
+        let settings = MatchSettings {
+            map,
+            ..self.last_match.clone()
+        };
+        self.destroy_level();
+        // Nothing uses resources of destroyed level anymore, dropping them makes level read
+        // changed files from disk.
+        self.engine
+            .resource_manager
+            .state()
+            .purge_unused_resources();
+        if self.create_level(&settings) {
+            if let Some(ref mut level) = self.level {
+                if let Some(restore) = restore {
+                    level.set_player_restore(restore);
+                }
+                level.deploy_player();
+            }
+            self.hud.add_message("Level reloaded".to_owned());
+        }
+    }
+
+    /// Starts a match of bots on map of last match without player, see `spectator` module.
+    fn start_attract_mode(&mut self) {
+        let settings = MatchSettings {
+            map: self.last_match.map.clone(),
+            bot_count: ATTRACT_BOT_COUNT,
+            ..Default::default()
+        };
+        if self.create_level(&settings) {
+            if let Some(ref mut level) = self.level {
+                level.start_attract_mode();
+            }
+            self.hud.set_visible(&mut self.engine.user_interface, false);
+        }
+    }
+
+    fn is_attract_mode(&self) -> bool {
+        self.level
+            .as_ref()
+            .map_or(false, |level| level.is_attract_mode())
+    }
+
+    fn leave_attract_mode(&mut self) {
+        self.destroy_level();
+        self.set_menu_visible(true);
+        self.menu_idle_time = 0.0;
     }
 
     pub fn set_menu_visible(&mut self, visible: bool) {
         let ui = &mut self.engine.user_interface;
-        self.menu.set_visible(ui, visible);
+        if visible && self.level.is_none() {
+            // Returned to main menu, session is over.
+            self.session.clear();
+            self.hud.leader_board_mut().hide_match_end(ui);
+        }
+        self.menu.set_visible(ui, visible, self.level.is_some());
         self.hud.set_visible(ui, !visible);
     }
 
@@ -565,85 +1615,255 @@ impl Game {
         self.menu.is_visible(&self.engine.user_interface)
     }
 
+    /// Returns true if some screen that needs mouse cursor is shown instead of the match.
+    fn is_cursor_needed(&self) -> bool {
+        self.is_menu_visible() || self.loadout_menu.is_open(&self.engine.user_interface)
+    }
+
     pub fn update(&mut self, time: GameTime) {
+        let cursor_needed = self.is_cursor_needed();
         let window = self.engine.get_window();
-        window.set_cursor_visible(self.is_menu_visible());
-        let _ = window.set_cursor_grab(!self.is_menu_visible());
+        window.set_cursor_visible(cursor_needed);
+        let _ = window.set_cursor_grab(!cursor_needed);
+
+        // Simulation is frozen in editor, but engine still has to be updated to render UI and
+        // to apply camera transform.
+        if let Some(editor) = self.editor.as_mut() {
+            self.engine.update(0.0);
+            editor.update(&mut self.engine, time.delta);
+            return;
+        }
+
+        // Match is frozen while pause menu is open, scene is updated with zero step like in
+        // editor so physics and animations stay still.
+        let paused = self.level.is_some() && self.menu.is_paused(&self.engine.user_interface);
+        self.engine.update(if paused { 0.0 } else { time.delta });
+        self.profiler.add(Subsystem::Ui, self.engine.ui_time);
+        rg3d::futures::executor::block_on(
+            self.sound_manager
+                .update(self.engine.resource_manager.clone(), time.delta),
+        );
 
-        self.engine.update(time.delta);
+        if self.level.is_none() && self.is_menu_visible() {
+            self.menu_idle_time += time.delta;
+            if self.menu_idle_time >= ATTRACT_IDLE_TIME {
+                self.menu_idle_time = 0.0;
+                self.start_attract_mode();
+            }
+        } else {
+            self.menu_idle_time = 0.0;
+        }
 
         if let Some(ref mut level) = self.level {
-            level.update(&mut self.engine, time);
+            // Options can be changed from pause menu in the middle of a match.
+            level.quality = self.quality.borrow().clone();
+        }
+        if let Some(level) = self.level.as_mut().filter(|_| !paused) {
+            if let Some(replay) = self.replay.as_mut() {
+                for input in replay.take(self.match_tick) {
+                    match input.to_event() {
+                        Some(event) => {
+                            level.process_input_event(&event);
+                        }
+                        None => {
+                            if let RecordedInput::Loadout(loadout) = input {
+                                level.set_player_loadout(loadout.weapons());
+                                level.deploy_player();
+                                self.loadout = loadout;
+                            }
+                        }
+                    }
+                }
+                if replay.is_finished() {
+                    Log::writeln("Input replay is over, control is given back to player");
+                    self.replay = None;
+                }
+            }
+            let level_update_start = Instant::now();
+            level.update(&mut self.engine, time, &mut self.profiler);
+            self.profiler
+                .add(Subsystem::Level, level_update_start.elapsed());
+            self.match_tick += 1;
+            if !level.is_attract_mode() {
+                self.autosave.update(&self.autosave_settings, time.delta);
+            }
+            let flag_carrier = level.flag_carrier_marker(&self.engine);
+            let objective = level.objective_marker(&self.engine);
+            let key_prompt = level.key_prompt(&self.engine);
             let ui = &mut self.engine.user_interface;
             self.hud.set_time(ui, level.time());
+            self.hud.set_flag_carrier(ui, flag_carrier);
+            self.hud.set_objective(ui, objective);
+            self.hud.set_round_score(ui, level.round_score());
+            self.hud.set_lives(ui, level.player_lives());
+            self.hud.set_practice(ui, level.practice_report());
+            self.hud.set_tutorial_prompt(ui, level.tutorial_prompt());
+            self.hud.set_key_prompt(ui, key_prompt);
             let player = level.get_player();
             if player.is_some() {
                 // Sync hud with player state.
                 let player = level.actors().get(player);
                 self.hud.set_health(ui, player.get_health());
                 self.hud.set_armor(ui, player.get_armor());
+                self.hud
+                    .set_shield(ui, player.get_shield() / character::MAX_SHIELD);
                 let current_weapon = player.current_weapon();
                 if current_weapon.is_some() {
+                    let weapon = &level.weapons()[current_weapon];
                     self.hud
-                        .set_ammo(ui, level.weapons()[current_weapon].ammo());
+                        .set_ammo(ui, weapon.ammo(), weapon.is_low_on_ammo());
+                    self.hud.set_no_ammo(ui, weapon.is_dry_firing(time.elapsed));
+                } else {
+                    self.hud.set_no_ammo(ui, false);
+                }
+                self.hud.set_death_screen(ui, None);
+                if self.loadout_menu.is_open(ui) {
+                    // Player was respawned before new loadout was selected.
+                    self.loadout_menu.close(ui);
                 }
-                self.hud.set_is_died(ui, false);
             } else {
-                self.hud.set_is_died(ui, true);
+                self.hud.set_no_ammo(ui, false);
+                self.hud.set_death_screen(ui, level.death_screen_text());
             }
         }
 
+        let cinematic_finished = match self.cinematic.as_mut() {
+            Some(cinematic) => !cinematic.update(&mut self.engine, time.delta),
+            None => false,
+        };
+        if cinematic_finished {
+            self.leave_cinematic();
+        }
+
         self.handle_messages(time);
 
         self.hud.update(&mut self.engine.user_interface, &self.time);
     }
 
-    fn handle_messages(&mut self, time: GameTime) {
-        while let Ok(message) = self.events_receiver.try_recv() {
-            match &message {
-                Message::StartNewGame { options } => {
-                    self.start_new_game(*options);
+    fn handle_session_message(&mut self, message: &Message) {
+        match message {
+            Message::StartNewGame { settings } => {
+                self.start_new_game(settings.clone());
+            }
+            Message::SaveGame => match self.save_game() {
+                Ok(_) => println!("successfully saved"),
+                Err(e) => println!("failed to make a save, reason: {}", e),
+            },
+            Message::LoadGame => {
+                if let Err(e) = self.load_game(&self.dirs.save_file(SAVE_FILE)) {
+                    println!("Failed to load saved game. Reason: {:?}", e);
                 }
-                Message::SaveGame => match self.save_game() {
-                    Ok(_) => println!("successfully saved"),
-                    Err(e) => println!("failed to make a save, reason: {}", e),
-                },
-                Message::LoadGame => {
-                    if let Err(e) = self.load_game() {
-                        println!("Failed to load saved game. Reason: {:?}", e);
+            }
+            Message::ContinueGame => {
+                match Autosave::most_recent_save(&self.autosave_settings, &self.dirs) {
+                    Some(path) => {
+                        if let Err(e) = self.load_game(&path) {
+                            println!("Failed to load saved game. Reason: {:?}", e);
+                        }
                     }
+                    None => println!("There is no save to continue from."),
                 }
-                Message::QuitGame => {
-                    self.destroy_level();
-                    self.running = false;
+            }
+            Message::ResumeGame => {
+                self.set_menu_visible(false);
+            }
+            Message::ChooseLoadout => {
+                self.loadout_menu.open(&mut self.engine.user_interface);
+            }
+            Message::SetLoadout { loadout } => {
+                if let Some(recording) = self.recording.as_mut() {
+                    recording.record(self.match_tick, RecordedInput::Loadout(loadout.clone()));
                 }
-                Message::EndMatch => {
-                    self.destroy_level();
-                    self.hud
-                        .leader_board()
-                        .set_visible(true, &mut self.engine.user_interface);
+                self.loadout = loadout.clone();
+                if let Some(ref mut level) = self.level {
+                    level.set_player_loadout(loadout.weapons());
+                    level.deploy_player();
                 }
-                _ => (),
             }
+            Message::QuitGame => {
+                self.destroy_level();
+                self.running = false;
+            }
+            Message::RecordRun { map, run } => {
+                self.profile.set_best_run(map, run.clone());
+                self.profile.write_to_file(self.dirs.profile_file());
+            }
+            Message::EndMatch { .. } if self.is_attract_mode() => {
+                self.leave_attract_mode();
+            }
+            Message::EndMatch { summary } => {
+                if let Some(level) = self.level.as_ref() {
+                    level.export_heatmap(self.dirs.heatmaps_dir());
+                    if let Some(summary) = summary {
+                        self.session
+                            .add_match(&level.leader_board, &level.actors, summary);
+                    }
+                }
+                if let Some(summary) = summary {
+                    Log::writeln(format!("Match is over: {}", summary));
+                    self.hud.add_message(summary.to_string());
+                    if !summary.accuracy.is_empty() {
+                        self.profile.add_accuracy(&summary.accuracy);
+                        Log::writeln(format!(
+                            "Lifetime accuracy: {}",
+                            self.profile.accuracy_text()
+                        ));
+                    }
+                    // Replayed match was rated when it was played.
+                    if let (Some(level), None) = (self.level.as_ref(), self.replay.as_ref()) {
+                        if let (Some(bot_rating), Some(team)) =
+                            (level.bot_rating(), level.player_team())
+                        {
+                            let result = rating::match_result(summary, team);
+                            self.profile.rating.add_match(bot_rating, result);
+                            Log::writeln(format!("Rating: {:.0}", self.profile.rating.rating));
+                        }
+                    }
+                    self.profile.write_to_file(self.dirs.profile_file());
+                }
+                self.destroy_level();
+                if self.is_menu_visible() {
+                    // Match was ended from pause menu, switch to main menu.
+                    self.set_menu_visible(true);
+                }
+                self.hud
+                    .leader_board_mut()
+                    .show_match_end(&mut self.engine.user_interface, &self.session);
+            }
+            _ => (),
+        }
+    }
 
-            rg3d::futures::executor::block_on(
-                self.sound_manager
-                    .handle_message(self.engine.resource_manager.clone(), &message),
-            );
-
-            if let Some(ref mut level) = self.level {
-                rg3d::futures::executor::block_on(level.handle_message(
-                    &mut self.engine,
-                    &message,
-                    time,
-                ));
-
-                self.hud.handle_message(
-                    &message,
-                    &mut self.engine.user_interface,
-                    &level.leader_board,
-                    &level.options,
-                );
+    fn handle_messages(&mut self, time: GameTime) {
+        while let Ok(message) = self.events_receiver.try_recv() {
+            let subscribers = self.event_bus.subscribers(message.category()).to_vec();
+            for subscriber in subscribers {
+                match subscriber {
+                    Subscriber::Game => self.handle_session_message(&message),
+                    Subscriber::SoundManager => rg3d::futures::executor::block_on(
+                        self.sound_manager
+                            .handle_message(self.engine.resource_manager.clone(), &message),
+                    ),
+                    Subscriber::Level => {
+                        if let Some(ref mut level) = self.level {
+                            rg3d::futures::executor::block_on(level.handle_message(
+                                &mut self.engine,
+                                &message,
+                                time,
+                            ));
+                        }
+                    }
+                    Subscriber::Hud => {
+                        if let Some(ref level) = self.level {
+                            self.hud.handle_message(
+                                &message,
+                                &mut self.engine.user_interface,
+                                &level.leader_board,
+                                &level.options,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
@@ -661,7 +1881,7 @@ impl Game {
                Draw calls: {}\n\
                Up time: {:.2} s\n\
                Sound render time: {:?}\n\
-               UI Time: {:?}",
+               UI Time: {:?}\n",
             statistics.pure_frame_time * 1000.0,
             statistics.capped_frame_time * 1000.0,
             statistics.frames_per_second,
@@ -677,6 +1897,16 @@ impl Game {
         )
         .unwrap();
 
+        let sound_time = self
+            .engine
+            .sound_context
+            .lock()
+            .unwrap()
+            .full_render_duration();
+        self.profiler.add(Subsystem::Sound, sound_time);
+        self.profiler.end_frame(&mut self.engine.user_interface);
+        self.profiler.write_summary(&mut self.debug_string);
+
         self.engine.user_interface.send_message(TextMessage::text(
             self.debug_text,
             MessageDirection::ToWidget,
@@ -684,6 +1914,22 @@ impl Game {
         ));
     }
 
+    fn update_dynamic_quality(&mut self) {
+        let quality = self.quality.borrow();
+        let frame_time = self.engine.renderer.get_statistics().pure_frame_time;
+        self.dynamic_quality.update(
+            &mut self.engine.renderer,
+            quality.dynamic_quality,
+            quality.target_fps,
+            frame_time,
+        );
+        let scale = self.dynamic_quality.scale();
+        self.hud.set_quality_scale(
+            &mut self.engine.user_interface,
+            if scale < 1.0 { Some(scale) } else { None },
+        );
+    }
+
     pub fn limit_fps(&mut self, value: f64) {
         let current_time = time::Instant::now();
         let render_call_duration = current_time
@@ -705,29 +1951,223 @@ impl Game {
             }
         }
 
-        if !self.is_menu_visible() {
+        if let Some(editor) = self.editor.as_mut() {
+            if let Some(placement) = editor.process_input_event(&mut self.engine, event) {
+                if let Some(ref mut level) = self.level {
+                    rg3d::futures::executor::block_on(
+                        level.add_placement(&mut self.engine, &placement),
+                    );
+                }
+            }
+        } else if self.cinematic.is_none() && self.replay.is_none() && !self.is_cursor_needed() {
             if let Some(ref mut level) = self.level {
+                if let Some(recording) = self.recording.as_mut() {
+                    if let Some(input) = RecordedInput::from_event(event) {
+                        recording.record(self.match_tick, input);
+                    }
+                }
                 level.process_input_event(event);
             }
         }
     }
 
+    /// Starts a new match with settings of last match and fresh seed and records input.
+    fn start_recording(&mut self) {
+        let seed = rand::random();
+        random::seed(seed);
+        let settings = self.last_match.clone();
+        self.start_new_game(settings.clone());
+        if let Some(ref level) = self.level {
+            self.recording = Some(Recording::new(seed, settings, level.bot_skill()));
+            self.hud
+                .add_message("Recording input, press F10 to stop".to_owned());
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            let timestamp = time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            recording.write_to_file(self.dirs.save_file(&format!("repro_{}.json", timestamp)));
+        }
+    }
+
+    /// Starts match of a recording and feeds its input to the level.
+    fn start_replay(&mut self, path: &str) {
+        match Recording::load(path) {
+            Ok(recording) => {
+                random::seed(recording.seed);
+                self.start_new_game(recording.settings.clone());
+                if let Some(ref mut level) = self.level {
+                    level.set_bot_skill(recording.bot_skill);
+                    self.replay = Some(Replay::new(recording));
+                }
+            }
+            Err(e) => Log::writeln(format!("Unable to load input recording {}: {}", path, e)),
+        }
+    }
+
+    #[cfg(feature = "query_api")]
+    fn log_snapshot(&self) {
+        if let Some(ref level) = self.level {
+            Log::writeln(format!("{:#?}", level.snapshot(&self.engine)));
+        }
+    }
+
+    /// Starts playback of camera path of current map, see `cinematic` module.
+    fn enter_cinematic(&mut self) {
+        if let Some(ref level) = self.level {
+            match CameraPath::load(level.camera_path_file()) {
+                Ok(path) => {
+                    self.cinematic =
+                        Some(CinematicCamera::new(&mut self.engine, level.scene, path));
+                    self.hud.set_visible(&mut self.engine.user_interface, false);
+                }
+                Err(e) => Log::writeln(e),
+            }
+        }
+    }
+
+    fn leave_cinematic(&mut self) {
+        if let Some(cinematic) = self.cinematic.take() {
+            if let Some(ref level) = self.level {
+                cinematic.leave(&mut self.engine, level.active_camera());
+            }
+            self.hud.set_visible(&mut self.engine.user_interface, true);
+        }
+    }
+
+    fn enter_editor(&mut self) {
+        if let Some(ref level) = self.level {
+            self.editor = Some(DebugEditor::enter(
+                &mut self.engine,
+                level.scene,
+                self.control_scheme.clone(),
+                level.placement_file(),
+            ));
+            self.hud.set_visible(&mut self.engine.user_interface, false);
+        }
+    }
+
+    fn leave_editor(&mut self) {
+        if let Some(editor) = self.editor.take() {
+            editor.leave(&mut self.engine);
+            self.hud.set_visible(&mut self.engine.user_interface, true);
+        }
+    }
+
     pub fn process_input_event(&mut self, event: &Event<()>) {
+        let is_user_input = match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { .. },
+                ..
+            }
+            | Event::WindowEvent {
+                event: WindowEvent::MouseInput { .. },
+                ..
+            } => true,
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { .. },
+                ..
+            } => {
+                self.menu_idle_time = 0.0;
+                false
+            }
+            _ => false,
+        };
+        if is_user_input {
+            self.menu_idle_time = 0.0;
+            // Any key or mouse button stops attract mode, moving mouse doesn't.
+            if self.is_attract_mode() {
+                self.leave_attract_mode();
+                return;
+            }
+        }
+
         self.process_dispatched_event(event);
 
+        let mut consumed_by_menu = self.menu.process_input_event(&mut self.engine, &event);
+        if !consumed_by_menu
+            && !self.is_menu_visible()
+            && self.loadout_menu.is_open(&self.engine.user_interface)
+        {
+            consumed_by_menu = self
+                .loadout_menu
+                .process_input_event(&mut self.engine.user_interface, &event);
+        }
+
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::KeyboardInput { input, .. } = event {
                 if let ElementState::Pressed = input.state {
                     if let Some(key) = input.virtual_keycode {
-                        if key == VirtualKeyCode::Escape {
-                            self.set_menu_visible(!self.is_menu_visible());
+                        #[cfg(feature = "query_api")]
+                        {
+                            if key == QUERY_KEY && !consumed_by_menu {
+                                self.log_snapshot();
+                            }
+                        }
+                        if key == VirtualKeyCode::Escape && !consumed_by_menu {
+                            if self.editor.is_some() {
+                                self.leave_editor();
+                            } else if self.cinematic.is_some() {
+                                self.leave_cinematic();
+                            } else {
+                                self.set_menu_visible(!self.is_menu_visible());
+                            }
+                        } else if key == REPRO_KEY
+                            && !consumed_by_menu
+                            && !self.is_menu_visible()
+                            && self.replay.is_none()
+                        {
+                            if self.recording.is_some() {
+                                self.stop_recording();
+                            } else {
+                                self.start_recording();
+                            }
+                        } else if key == VirtualKeyCode::Return
+                            && !consumed_by_menu
+                            && !self.is_menu_visible()
+                            && self.level.is_none()
+                            && self.hud.leader_board().is_match_over()
+                        {
+                            self.start_next_match();
+                        } else if key == RELOAD_LEVEL_KEY
+                            && !consumed_by_menu
+                            && !self.is_menu_visible()
+                            && self.replay.is_none()
+                        {
+                            self.reload_level();
+                        } else if key == CINEMATIC_KEY
+                            && !consumed_by_menu
+                            && !self.is_menu_visible()
+                            && self.editor.is_none()
+                        {
+                            if self.cinematic.is_some() {
+                                self.leave_cinematic();
+                            } else {
+                                self.enter_cinematic();
+                            }
+                        } else if key == EDITOR_KEY
+                            && !consumed_by_menu
+                            && !self.is_menu_visible()
+                            && self.cinematic.is_none()
+                            && self.replay.is_none()
+                        {
+                            if self.editor.is_some() {
+                                self.leave_editor();
+                            } else {
+                                self.enter_editor();
+                            }
+                        } else if key == HUD_FREE_KEY && !consumed_by_menu {
+                            self.hud.toggle_hud_free(&mut self.engine.user_interface);
                         }
                     }
                 }
             }
         }
 
-        self.menu.process_input_event(&mut self.engine, &event);
         self.hud.process_event(&mut self.engine, &event);
     }
 }
@@ -1,43 +1,201 @@
-use crate::gui::ScrollBarData;
 use crate::{
-    gui::create_scroll_bar, message::Message, DeathMatch, GameEngine, Gui, GuiMessage,
-    MatchOptions, UINodeHandle,
+    assets,
+    bot::BotDifficulty,
+    gui::{create_check_box, create_dropdown_items, create_numeric_up_down, NumericData},
+    lighting::LightingPreset,
+    message::Message,
+    navigation::FocusChain,
+    settings::{
+        MatchMode, MatchSettings, MAX_BOT_COUNT, MAX_HANDICAP, MAX_SCORE_LIMIT, MAX_TIME_LIMIT,
+        MIN_HANDICAP,
+    },
+    BuildContext, GameEngine, Gui, GuiMessage, UINodeHandle,
 };
 use rg3d::{
-    engine::resource_manager::ResourceManager,
+    core::color::Color,
     gui::{
-        border::BorderBuilder,
+        brush::Brush,
         button::ButtonBuilder,
-        decorator::DecoratorBuilder,
         dropdown_list::DropdownListBuilder,
         grid::{Column, GridBuilder, Row},
-        message::{ButtonMessage, UiMessageData},
+        message::{
+            ButtonMessage, CheckBoxMessage, DropdownListMessage, MessageDirection,
+            NumericUpDownMessage, TextMessage, UiMessageData,
+        },
         node::UINode,
         text::TextBuilder,
         text_box::TextBoxBuilder,
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowTitle},
-        HorizontalAlignment, Orientation, Thickness, VerticalAlignment,
+        Thickness, VerticalAlignment,
     },
 };
 use std::sync::mpsc::Sender;
 
+const MODES: [(&str, MatchMode); 11] = [
+    ("Deathmatch", MatchMode::DeathMatch),
+    ("Team Deathmatch", MatchMode::TeamDeathMatch),
+    ("Capture The Flag", MatchMode::CaptureTheFlag),
+    ("Oddball", MatchMode::Oddball),
+    ("King of the Hill", MatchMode::KingOfTheHill),
+    ("Duel", MatchMode::Duel),
+    ("Last Man Standing", MatchMode::LastManStanding),
+    ("Team Last Man Standing", MatchMode::TeamLastManStanding),
+    ("Practice", MatchMode::Practice),
+    ("Time Trial", MatchMode::TimeTrial),
+    ("Tutorial", MatchMode::Tutorial),
+];
+
+const LIGHTING: [(&str, LightingPreset); 3] = [
+    ("Day", LightingPreset::Day),
+    ("Dusk", LightingPreset::Dusk),
+    ("Night", LightingPreset::Night),
+];
+
+const BOT_DIFFICULTIES: [(&str, BotDifficulty); 3] = [
+    ("Easy", BotDifficulty::Easy),
+    ("Normal", BotDifficulty::Normal),
+    ("Hard", BotDifficulty::Hard),
+];
+
+/// Pairs of map name and path to its model.
+pub const MAPS: [(&str, &str); 1] = [("DM6", assets::models::maps::DM6)];
+
+/// Named configurations that can be selected in one click.
+fn presets() -> Vec<(&'static str, MatchSettings)> {
+    vec![
+        (
+            "Quick DM",
+            MatchSettings {
+                mode: MatchMode::DeathMatch,
+                time_limit: 5.0,
+                score_limit: 15,
+                map: assets::models::maps::DM6.to_owned(),
+                bot_count: 3,
+                bot_difficulty: BotDifficulty::Normal,
+                adaptive_difficulty: true,
+                zombie_rush: false,
+                streak_rewards: false,
+                pickup_magnetism: true,
+                bot_drops: false,
+                player_health_percent: 100,
+                player_damage_percent: 100,
+                bot_health_percent: 100,
+                bot_damage_percent: 100,
+                flashlight_battery: false,
+                auto_respawn: true,
+                vampire: false,
+                no_self_damage: false,
+                double_damage: false,
+                lighting: LightingPreset::Day,
+            },
+        ),
+        (
+            "Long CTF",
+            MatchSettings {
+                mode: MatchMode::CaptureTheFlag,
+                time_limit: 30.0,
+                score_limit: 5,
+                map: assets::models::maps::DM6.to_owned(),
+                bot_count: 5,
+                bot_difficulty: BotDifficulty::Hard,
+                adaptive_difficulty: true,
+                zombie_rush: false,
+                streak_rewards: false,
+                pickup_magnetism: true,
+                bot_drops: false,
+                player_health_percent: 100,
+                player_damage_percent: 100,
+                bot_health_percent: 100,
+                bot_damage_percent: 100,
+                flashlight_battery: true,
+                auto_respawn: true,
+                vampire: false,
+                no_self_damage: false,
+                double_damage: false,
+                lighting: LightingPreset::Night,
+            },
+        ),
+    ]
+}
+
 pub struct MatchMenu {
     sender: Sender<Message>,
     pub window: UINodeHandle,
-    sb_frag_limit: UINodeHandle,
-    sb_time_limit: UINodeHandle,
+    dd_match_type: UINodeHandle,
+    dd_map: UINodeHandle,
+    dd_lighting: UINodeHandle,
+    nud_score_limit: UINodeHandle,
+    nud_time_limit: UINodeHandle,
+    nud_bot_count: UINodeHandle,
+    dd_bot_difficulty: UINodeHandle,
+    cb_adaptive_difficulty: UINodeHandle,
+    cb_flashlight_battery: UINodeHandle,
+    cb_zombie_rush: UINodeHandle,
+    cb_auto_respawn: UINodeHandle,
+    cb_streak_rewards: UINodeHandle,
+    cb_vampire: UINodeHandle,
+    cb_no_self_damage: UINodeHandle,
+    cb_double_damage: UINodeHandle,
+    cb_pickup_magnetism: UINodeHandle,
+    cb_bot_drops: UINodeHandle,
+    nud_player_health: UINodeHandle,
+    nud_player_damage: UINodeHandle,
+    nud_bot_health: UINodeHandle,
+    nud_bot_damage: UINodeHandle,
     start_button: UINodeHandle,
+    /// Shows why match can't be started with selected settings.
+    validation_text: UINodeHandle,
+    preset_buttons: Vec<UINodeHandle>,
+    pub focus_chain: FocusChain,
 }
 
 impl MatchMenu {
-    pub fn new(ui: &mut Gui, resource_manager: ResourceManager, sender: Sender<Message>) -> Self {
+    pub fn new(engine: &mut GameEngine, settings: &MatchSettings, sender: Sender<Message>) -> Self {
         let common_row = Row::strict(36.0);
 
+        let resource_manager = engine.resource_manager.clone();
+        let ui = &mut engine.user_interface;
         let ctx = &mut ui.build_ctx();
-        let sb_frag_limit;
-        let sb_time_limit;
+
+        let presets = presets();
+        let preset_buttons = presets
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(i)
+                        .with_margin(Thickness::uniform(2.0)),
+                )
+                .with_text(name)
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let dd_match_type;
+        let dd_map;
+        let dd_lighting;
+        let nud_score_limit;
+        let nud_time_limit;
+        let nud_bot_count;
+        let dd_bot_difficulty;
+        let cb_adaptive_difficulty;
+        let cb_flashlight_battery;
+        let cb_zombie_rush;
+        let cb_auto_respawn;
+        let cb_streak_rewards;
+        let cb_vampire;
+        let cb_no_self_damage;
+        let cb_double_damage;
+        let cb_pickup_magnetism;
+        let cb_bot_drops;
+        let nud_player_health;
+        let nud_player_damage;
+        let nud_bot_health;
+        let nud_bot_damage;
         let start_button;
+        let validation_text;
         let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0))
             .with_title(WindowTitle::text("Match Options"))
             .open(false)
@@ -46,88 +204,368 @@ impl MatchMenu {
                     WidgetBuilder::new()
                         .with_child(
                             TextBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
+                                .with_text("Preset")
+                                .build(ctx),
+                        )
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .on_column(1)
+                                    .with_children(&preset_buttons),
+                            )
+                            .add_row(Row::stretch())
+                            .add_columns(presets.iter().map(|_| Column::stretch()).collect())
+                            .build(ctx),
+                        )
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
                                 .with_text("Match Type")
                                 .build(ctx),
                         )
+                        .with_child({
+                            let names = MODES.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                            dd_match_type = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_column(1).on_row(1),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_match_type
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(2).on_column(0))
+                                .with_text("Map")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            let names = MAPS.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                            dd_map = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_column(1).on_row(2),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_map
+                        })
                         .with_child(
-                            DropdownListBuilder::new(WidgetBuilder::new().on_column(1).on_row(0))
-                                .with_items({
-                                    let mut items = Vec::new();
-                                    for mode in
-                                        ["Deathmatch", "Team Deathmatch", "Capture The Flag"].iter()
-                                    {
-                                        let item = DecoratorBuilder::new(BorderBuilder::new(
-                                            WidgetBuilder::new().with_height(30.0).with_child(
-                                                TextBuilder::new(
-                                                    WidgetBuilder::new()
-                                                        .with_horizontal_alignment(
-                                                            HorizontalAlignment::Center,
-                                                        )
-                                                        .with_vertical_alignment(
-                                                            VerticalAlignment::Center,
-                                                        ),
-                                                )
-                                                .with_text(mode)
-                                                .build(ctx),
-                                            ),
-                                        ))
-                                        .build(ctx);
-                                        items.push(item);
-                                    }
-                                    items
-                                })
+                            TextBuilder::new(WidgetBuilder::new().on_row(3).on_column(0))
+                                .with_text("Lighting")
                                 .build(ctx),
                         )
+                        .with_child({
+                            let names = LIGHTING.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                            dd_lighting = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_column(1).on_row(3),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_lighting
+                        })
                         .with_child(
-                            TextBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
+                            TextBuilder::new(WidgetBuilder::new().on_row(4).on_column(0))
                                 .with_text("Time Limit (min)")
                                 .build(ctx),
                         )
                         .with_child({
-                            sb_time_limit = create_scroll_bar(
+                            nud_time_limit = create_numeric_up_down(
                                 ctx,
-                                resource_manager.clone(),
-                                ScrollBarData {
-                                    min: 5.0,
-                                    max: 60.0,
-                                    value: 10.0,
+                                NumericData {
+                                    min: 0.0,
+                                    max: MAX_TIME_LIMIT,
+                                    value: settings.time_limit,
                                     step: 1.0,
-                                    row: 1,
+                                    row: 4,
                                     column: 1,
                                     margin: Thickness::uniform(2.0),
-                                    show_value: true,
-                                    orientation: Orientation::Horizontal,
                                 },
                             );
-                            sb_time_limit
+                            nud_time_limit
                         })
                         .with_child(
-                            TextBuilder::new(WidgetBuilder::new().on_row(2).on_column(0))
-                                .with_text("Frag Limit")
+                            TextBuilder::new(WidgetBuilder::new().on_row(5).on_column(0))
+                                .with_text("Score Limit")
                                 .build(ctx),
                         )
                         .with_child({
-                            sb_frag_limit = create_scroll_bar(
+                            nud_score_limit = create_numeric_up_down(
                                 ctx,
-                                resource_manager.clone(),
-                                ScrollBarData {
-                                    min: 10.0,
-                                    max: 200.0,
-                                    value: 30.0,
+                                NumericData {
+                                    min: 0.0,
+                                    max: MAX_SCORE_LIMIT as f32,
+                                    value: settings.score_limit as f32,
+                                    step: 1.0,
+                                    row: 5,
+                                    column: 1,
+                                    margin: Thickness::uniform(2.0),
+                                },
+                            );
+                            nud_score_limit
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(6).on_column(0))
+                                .with_text("Bots")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            nud_bot_count = create_numeric_up_down(
+                                ctx,
+                                NumericData {
+                                    min: 0.0,
+                                    max: MAX_BOT_COUNT as f32,
+                                    value: settings.bot_count as f32,
                                     step: 1.0,
-                                    row: 2,
+                                    row: 6,
                                     column: 1,
                                     margin: Thickness::uniform(2.0),
-                                    show_value: true,
-                                    orientation: Orientation::Horizontal,
                                 },
                             );
-                            sb_frag_limit
+                            nud_bot_count
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(7).on_column(0))
+                                .with_text("Bot Difficulty")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            let names = BOT_DIFFICULTIES
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>();
+                            dd_bot_difficulty = DropdownListBuilder::new(
+                                WidgetBuilder::new().on_column(1).on_row(7),
+                            )
+                            .with_items(create_dropdown_items(ctx, &names))
+                            .build(ctx);
+                            dd_bot_difficulty
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(8).on_column(0))
+                                .with_text("Flashlight Battery")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_flashlight_battery = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                8,
+                                1,
+                                settings.flashlight_battery,
+                            );
+                            cb_flashlight_battery
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(9).on_column(0))
+                                .with_text("Zombie Rush")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_zombie_rush = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                9,
+                                1,
+                                settings.zombie_rush,
+                            );
+                            cb_zombie_rush
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(10).on_column(0))
+                                .with_text("Auto Respawn")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_auto_respawn = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                10,
+                                1,
+                                settings.auto_respawn,
+                            );
+                            cb_auto_respawn
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(11).on_column(0))
+                                .with_text("Streak Rewards")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_streak_rewards = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                11,
+                                1,
+                                settings.streak_rewards,
+                            );
+                            cb_streak_rewards
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(12).on_column(0))
+                                .with_text("Vampire")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_vampire = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                12,
+                                1,
+                                settings.vampire,
+                            );
+                            cb_vampire
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(13).on_column(0))
+                                .with_text("No Self Damage")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_no_self_damage = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                13,
+                                1,
+                                settings.no_self_damage,
+                            );
+                            cb_no_self_damage
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(14).on_column(0))
+                                .with_text("Double Damage")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_double_damage = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                14,
+                                1,
+                                settings.double_damage,
+                            );
+                            cb_double_damage
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(15).on_column(0))
+                                .with_text("Adaptive Bots")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_adaptive_difficulty = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                15,
+                                1,
+                                settings.adaptive_difficulty,
+                            );
+                            cb_adaptive_difficulty
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(16).on_column(0))
+                                .with_text("Pickup Magnetism")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_pickup_magnetism = create_check_box(
+                                ctx,
+                                resource_manager.clone(),
+                                16,
+                                1,
+                                settings.pickup_magnetism,
+                            );
+                            cb_pickup_magnetism
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(17).on_column(0))
+                                .with_text("Bot Drops")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            cb_bot_drops =
+                                create_check_box(ctx, resource_manager, 17, 1, settings.bot_drops);
+                            cb_bot_drops
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(18).on_column(0))
+                                .with_text("Player Health %")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            nud_player_health = create_numeric_up_down(
+                                ctx,
+                                NumericData {
+                                    min: MIN_HANDICAP as f32,
+                                    max: MAX_HANDICAP as f32,
+                                    value: settings.player_health_percent as f32,
+                                    step: 5.0,
+                                    row: 18,
+                                    column: 1,
+                                    margin: Thickness::uniform(2.0),
+                                },
+                            );
+                            nud_player_health
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(19).on_column(0))
+                                .with_text("Player Damage %")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            nud_player_damage = create_numeric_up_down(
+                                ctx,
+                                NumericData {
+                                    min: MIN_HANDICAP as f32,
+                                    max: MAX_HANDICAP as f32,
+                                    value: settings.player_damage_percent as f32,
+                                    step: 5.0,
+                                    row: 19,
+                                    column: 1,
+                                    margin: Thickness::uniform(2.0),
+                                },
+                            );
+                            nud_player_damage
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(20).on_column(0))
+                                .with_text("Bot Health %")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            nud_bot_health = create_numeric_up_down(
+                                ctx,
+                                NumericData {
+                                    min: MIN_HANDICAP as f32,
+                                    max: MAX_HANDICAP as f32,
+                                    value: settings.bot_health_percent as f32,
+                                    step: 5.0,
+                                    row: 20,
+                                    column: 1,
+                                    margin: Thickness::uniform(2.0),
+                                },
+                            );
+                            nud_bot_health
+                        })
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_row(21).on_column(0))
+                                .with_text("Bot Damage %")
+                                .build(ctx),
+                        )
+                        .with_child({
+                            nud_bot_damage = create_numeric_up_down(
+                                ctx,
+                                NumericData {
+                                    min: MIN_HANDICAP as f32,
+                                    max: MAX_HANDICAP as f32,
+                                    value: settings.bot_damage_percent as f32,
+                                    step: 5.0,
+                                    row: 21,
+                                    column: 1,
+                                    margin: Thickness::uniform(2.0),
+                                },
+                            );
+                            nud_bot_damage
                         })
                         .with_child(
                             TextBuilder::new(
                                 WidgetBuilder::new()
-                                    .on_row(3)
+                                    .on_row(22)
                                     .on_column(0)
                                     .with_margin(Thickness::uniform(2.0)),
                             )
@@ -138,7 +576,7 @@ impl MatchMenu {
                         .with_child(
                             TextBoxBuilder::new(
                                 WidgetBuilder::new()
-                                    .on_row(3)
+                                    .on_row(22)
                                     .on_column(1)
                                     .with_margin(Thickness::uniform(2.0)),
                             )
@@ -147,10 +585,20 @@ impl MatchMenu {
                         )
                         .with_child({
                             start_button =
-                                ButtonBuilder::new(WidgetBuilder::new().on_row(4).on_column(1))
+                                ButtonBuilder::new(WidgetBuilder::new().on_row(23).on_column(1))
                                     .with_text("Start")
                                     .build(ctx);
                             start_button
+                        })
+                        .with_child({
+                            validation_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(24)
+                                    .on_column(1)
+                                    .with_foreground(Brush::Solid(Color::opaque(220, 40, 40))),
+                            )
+                            .build(ctx);
+                            validation_text
                         }),
                 )
                 .add_column(Column::strict(200.0))
@@ -160,16 +608,239 @@ impl MatchMenu {
                 .add_row(common_row)
                 .add_row(common_row)
                 .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
                 .add_row(Row::stretch())
                 .build(ctx),
             )
             .build(ctx);
-        Self {
+
+        let mut focus_chain = preset_buttons.clone();
+        focus_chain.extend_from_slice(&[
+            dd_match_type,
+            dd_map,
+            dd_lighting,
+            nud_time_limit,
+            nud_score_limit,
+            nud_bot_count,
+            dd_bot_difficulty,
+            cb_flashlight_battery,
+            cb_zombie_rush,
+            cb_auto_respawn,
+            cb_streak_rewards,
+            cb_vampire,
+            cb_no_self_damage,
+            cb_double_damage,
+            cb_adaptive_difficulty,
+            cb_pickup_magnetism,
+            cb_bot_drops,
+            nud_player_health,
+            nud_player_damage,
+            nud_bot_health,
+            nud_bot_damage,
+            start_button,
+        ]);
+
+        let menu = Self {
             sender,
             window,
-            sb_frag_limit,
-            sb_time_limit,
+            dd_match_type,
+            dd_map,
+            dd_lighting,
+            nud_score_limit,
+            nud_time_limit,
+            nud_bot_count,
+            dd_bot_difficulty,
+            cb_adaptive_difficulty,
+            cb_flashlight_battery,
+            cb_zombie_rush,
+            cb_auto_respawn,
+            cb_streak_rewards,
+            cb_vampire,
+            cb_no_self_damage,
+            cb_double_damage,
+            cb_pickup_magnetism,
+            cb_bot_drops,
+            nud_player_health,
+            nud_player_damage,
+            nud_bot_health,
+            nud_bot_damage,
             start_button,
+            validation_text,
+            preset_buttons,
+            focus_chain: FocusChain::new(focus_chain),
+        };
+
+        menu.sync_to_model(ui, settings);
+
+        menu
+    }
+
+    /// Puts given settings into widgets.
+    fn sync_to_model(&self, ui: &mut Gui, settings: &MatchSettings) {
+        let mode = MODES
+            .iter()
+            .position(|(_, mode)| *mode == settings.mode)
+            .unwrap_or(0);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_match_type,
+            MessageDirection::ToWidget,
+            Some(mode),
+        ));
+
+        // Unknown map (for example removed one) is replaced with first map from the list.
+        let map = MAPS
+            .iter()
+            .position(|(_, path)| *path == settings.map)
+            .unwrap_or(0);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_map,
+            MessageDirection::ToWidget,
+            Some(map),
+        ));
+
+        let lighting = LIGHTING
+            .iter()
+            .position(|(_, lighting)| *lighting == settings.lighting)
+            .unwrap_or(0);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_lighting,
+            MessageDirection::ToWidget,
+            Some(lighting),
+        ));
+
+        let bot_difficulty = BOT_DIFFICULTIES
+            .iter()
+            .position(|(_, difficulty)| *difficulty == settings.bot_difficulty)
+            .unwrap_or(1);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_bot_difficulty,
+            MessageDirection::ToWidget,
+            Some(bot_difficulty),
+        ));
+
+        for &(numeric, value) in &[
+            (self.nud_time_limit, settings.time_limit),
+            (self.nud_score_limit, settings.score_limit as f32),
+            (self.nud_bot_count, settings.bot_count as f32),
+            (
+                self.nud_player_health,
+                settings.player_health_percent as f32,
+            ),
+            (
+                self.nud_player_damage,
+                settings.player_damage_percent as f32,
+            ),
+            (self.nud_bot_health, settings.bot_health_percent as f32),
+            (self.nud_bot_damage, settings.bot_damage_percent as f32),
+        ] {
+            ui.send_message(NumericUpDownMessage::value(
+                numeric,
+                MessageDirection::ToWidget,
+                value,
+            ));
+        }
+
+        for &(check_box, value) in &[
+            (self.cb_flashlight_battery, settings.flashlight_battery),
+            (self.cb_zombie_rush, settings.zombie_rush),
+            (self.cb_auto_respawn, settings.auto_respawn),
+            (self.cb_streak_rewards, settings.streak_rewards),
+            (self.cb_vampire, settings.vampire),
+            (self.cb_no_self_damage, settings.no_self_damage),
+            (self.cb_double_damage, settings.double_damage),
+            (self.cb_adaptive_difficulty, settings.adaptive_difficulty),
+            (self.cb_pickup_magnetism, settings.pickup_magnetism),
+            (self.cb_bot_drops, settings.bot_drops),
+        ] {
+            ui.send_message(CheckBoxMessage::checked(
+                check_box,
+                MessageDirection::ToWidget,
+                Some(value),
+            ));
+        }
+
+        self.set_validation_error(ui, "");
+    }
+
+    fn set_validation_error(&self, ui: &mut Gui, error: &str) {
+        ui.send_message(TextMessage::text(
+            self.validation_text,
+            MessageDirection::ToWidget,
+            error.to_owned(),
+        ));
+    }
+
+    /// Collects settings from widgets.
+    fn settings(&self, ui: &Gui) -> MatchSettings {
+        let selection = |handle: UINodeHandle| {
+            if let UINode::DropdownList(dropdown_list) = ui.node(handle) {
+                dropdown_list.selection().unwrap_or(0)
+            } else {
+                0
+            }
+        };
+
+        let value = |handle: UINodeHandle| {
+            if let UINode::NumericUpDown(numeric) = ui.node(handle) {
+                numeric.value().round()
+            } else {
+                0.0
+            }
+        };
+
+        let checked = |handle: UINodeHandle| {
+            if let UINode::CheckBox(check_box) = ui.node(handle) {
+                check_box.checked().unwrap_or(false)
+            } else {
+                false
+            }
+        };
+
+        MatchSettings {
+            mode: MODES[selection(self.dd_match_type).min(MODES.len() - 1)].1,
+            time_limit: value(self.nud_time_limit),
+            score_limit: value(self.nud_score_limit) as u32,
+            map: MAPS[selection(self.dd_map).min(MAPS.len() - 1)]
+                .1
+                .to_owned(),
+            bot_count: value(self.nud_bot_count) as u32,
+            bot_difficulty: BOT_DIFFICULTIES
+                [selection(self.dd_bot_difficulty).min(BOT_DIFFICULTIES.len() - 1)]
+            .1,
+            adaptive_difficulty: checked(self.cb_adaptive_difficulty),
+            lighting: LIGHTING[selection(self.dd_lighting).min(LIGHTING.len() - 1)].1,
+            flashlight_battery: checked(self.cb_flashlight_battery),
+            zombie_rush: checked(self.cb_zombie_rush),
+            auto_respawn: checked(self.cb_auto_respawn),
+            streak_rewards: checked(self.cb_streak_rewards),
+            vampire: checked(self.cb_vampire),
+            no_self_damage: checked(self.cb_no_self_damage),
+            double_damage: checked(self.cb_double_damage),
+            pickup_magnetism: checked(self.cb_pickup_magnetism),
+            bot_drops: checked(self.cb_bot_drops),
+            player_health_percent: value(self.nud_player_health) as u32,
+            player_damage_percent: value(self.nud_player_damage) as u32,
+            bot_health_percent: value(self.nud_bot_health) as u32,
+            bot_damage_percent: value(self.nud_bot_damage) as u32,
         }
     }
 
@@ -179,26 +850,23 @@ impl MatchMenu {
         if let UiMessageData::Button(msg) = message.data() {
             if let ButtonMessage::Click = msg {
                 if message.destination() == self.start_button {
-                    let time_limit_minutes =
-                        if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_time_limit) {
-                            scroll_bar.value()
-                        } else {
-                            0.0
-                        };
-
-                    let frag_limit =
-                        if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_frag_limit) {
-                            scroll_bar.value()
-                        } else {
-                            0.0
-                        };
-
-                    let options = MatchOptions::DeathMatch(DeathMatch {
-                        time_limit_secs: time_limit_minutes * 60.0,
-                        frag_limit: frag_limit as u32,
-                    });
-
-                    self.sender.send(Message::StartNewGame { options }).unwrap();
+                    let settings = self.settings(ui);
+                    match settings.validate() {
+                        Ok(_) => {
+                            self.set_validation_error(ui, "");
+                            self.sender
+                                .send(Message::StartNewGame { settings })
+                                .unwrap();
+                        }
+                        Err(error) => self.set_validation_error(ui, &error),
+                    }
+                } else if let Some(index) = self
+                    .preset_buttons
+                    .iter()
+                    .position(|&button| button == message.destination())
+                {
+                    let (_, settings) = &presets()[index];
+                    self.sync_to_model(ui, settings);
                 }
             }
         }
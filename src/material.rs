@@ -0,0 +1,172 @@
+//! Materials of level surfaces. Collision of a map is built from its `Polygon` mesh, and
+//! that collision stops every projectile. Penetrable surfaces are separate meshes tagged by
+//! their material (see `tags`), for example `Wood_Fence_1` is wood. Bullets pass through
+//! such surfaces and lose damage in proportion to the thickness they pass. A surface thicker
+//! than the penetration of its material stops bullets. Materials can be defined by a file
+//! next to the map model with `materials.json` extension. The file maps tag patterns to
+//! penetration in meters:
+//!
+//! ```json
+//! {
+//!     "materials": {
+//!         "wood": 0.5,
+//!         "glass*": 0.05
+//!     }
+//! }
+//! ```
+//!
+//! Materials `wood` and `sheetmetal` are always defined, the file can override them.
+
+use crate::{mods, tags::TagIndex};
+use rg3d::{
+    core::{
+        math::{ray::Ray, vec3::Vec3},
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    physics::{static_geometry::StaticGeometry, HitKind, Physics, RayCastOptions},
+    scene::{node::Node, Scene},
+    utils::{self, log::Log},
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Materials with their penetration in meters.
+const DEFAULT_MATERIALS: [(&str, f32); 2] = [("wood", 0.5), ("sheetmetal", 0.1)];
+/// Projectile that has less damage left than this share of initial damage is stopped.
+const MIN_DAMAGE_LEFT: f32 = 0.1;
+
+#[derive(Deserialize)]
+struct MaterialFile {
+    materials: HashMap<String, f32>,
+}
+
+impl MaterialFile {
+    fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("materials.json")
+    }
+
+    /// Loads material file of a map, map without material file has default materials only.
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        if !path.exists() {
+            return Ok(Self {
+                materials: Default::default(),
+            });
+        }
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read materials {}: {}", path.display(), e))?;
+        serde_json::from_str::<MaterialFile>(&data)
+            .map_err(|e| format!("Materials {} are corrupted: {}", path.display(), e))
+    }
+}
+
+#[derive(Default)]
+struct Surface {
+    shape: Handle<StaticGeometry>,
+    penetration: f32,
+}
+
+impl Visit for Surface {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.shape.visit("Shape", visitor)?;
+        self.penetration.visit("Penetration", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Penetrable surfaces of a level.
+#[derive(Default)]
+pub struct Surfaces {
+    surfaces: Vec<Surface>,
+}
+
+impl Surfaces {
+    /// Creates collision for every mesh tagged by a material. Mesh tagged by several
+    /// materials gets the first of them in alphabetical order.
+    pub fn build(scene: &mut Scene, tags: &TagIndex, map: &str) -> Self {
+        let mut materials = DEFAULT_MATERIALS
+            .iter()
+            .map(|(tag, penetration)| (tag.to_string(), *penetration))
+            .collect::<BTreeMap<_, _>>();
+        match MaterialFile::load(MaterialFile::file_of_map(map)) {
+            Ok(file) => materials.extend(
+                file.materials
+                    .into_iter()
+                    .map(|(tag, penetration)| (tag.to_lowercase(), penetration)),
+            ),
+            Err(e) => Log::writeln(format!("WARNING: {}, default materials used.", e)),
+        }
+
+        let mut surfaces = Self::default();
+        let mut nodes = Vec::new();
+        for (pattern, penetration) in materials.iter() {
+            for handle in tags.nodes(pattern) {
+                if nodes.contains(&handle) {
+                    continue;
+                }
+                if let Node::Mesh(mesh) = &scene.graph[handle] {
+                    let shape = utils::mesh_to_static_geometry(mesh, false);
+                    let shape = scene.physics.add_static_geometry(shape);
+                    scene.static_geometry_binder.bind(shape, handle);
+                    surfaces.surfaces.push(Surface {
+                        shape,
+                        penetration: *penetration,
+                    });
+                    nodes.push(handle);
+                }
+            }
+        }
+        surfaces
+    }
+
+    /// Finds the point where a projectile that entered the surface at `entry` while moving
+    /// in `dir` leaves it. Returns the exit point and the share of damage left. Returns `None`
+    /// if the surface is not penetrable or too thick.
+    pub fn penetrate(
+        &self,
+        physics: &Physics,
+        shape: Handle<StaticGeometry>,
+        entry: Vec3,
+        dir: Vec3,
+    ) -> Option<(Vec3, f32)> {
+        let penetration = self
+            .surfaces
+            .iter()
+            .find(|surface| surface.shape == shape)?
+            .penetration;
+
+        // Cast ray back from the deepest point that projectile can reach, first hit with the
+        // surface is its exit side.
+        let deepest = entry + dir.scale(penetration);
+        let ray = Ray::from_two_points(&deepest, &entry)?;
+        let mut result = Vec::new();
+        physics.ray_cast(&ray, RayCastOptions::default(), &mut result);
+        result
+            .iter()
+            .find_map(|hit| match hit.kind {
+                HitKind::StaticTriangle { owner, .. } if owner == shape => Some((
+                    hit.position,
+                    1.0 - hit.position.distance(&entry) / penetration,
+                )),
+                _ => None,
+            })
+            .filter(|(_, damage_left)| *damage_left >= MIN_DAMAGE_LEFT)
+    }
+}
+
+impl Visit for Surfaces {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.surfaces.visit("Surfaces", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,6 +1,16 @@
 use crate::{
-    assets, control_scheme::ControlScheme, match_menu::MatchMenu, message::Message,
-    options_menu::OptionsMenu, GameEngine, Gui, GuiMessage, UINodeHandle,
+    asset_check::MissingAssetsDialog,
+    assets,
+    confirm_dialog::ConfirmDialog,
+    control_scheme::ControlScheme,
+    match_menu::MatchMenu,
+    message::Message,
+    mods,
+    navigation::{FocusChain, NavigationAction},
+    options_menu::OptionsMenu,
+    pause_menu::PauseMenu,
+    settings::{MatchSettings, QualitySettings, SpeakerMode},
+    GameEngine, Gui, GuiMessage, UINodeHandle,
 };
 use rg3d::gui::message::MessageDirection;
 use rg3d::gui::ttf::SharedFont;
@@ -18,7 +28,7 @@ use rg3d::{
 };
 use std::{
     cell::RefCell,
-    path::Path,
+    path::PathBuf,
     rc::Rc,
     sync::{mpsc::Sender, Arc, Mutex},
 };
@@ -26,6 +36,7 @@ use std::{
 pub struct Menu {
     sender: Sender<Message>,
     root: UINodeHandle,
+    btn_continue: UINodeHandle,
     btn_new_game: UINodeHandle,
     btn_save_game: UINodeHandle,
     btn_settings: UINodeHandle,
@@ -33,18 +44,30 @@ pub struct Menu {
     btn_quit_game: UINodeHandle,
     options_menu: OptionsMenu,
     match_menu: MatchMenu,
+    pause_menu: PauseMenu,
+    confirm_dialog: ConfirmDialog,
+    /// Shown once at startup if some game files are missing.
+    missing_assets_dialog: Option<MissingAssetsDialog>,
+    save_file: PathBuf,
+    focus_chain: FocusChain,
 }
 
 impl Menu {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         engine: &mut GameEngine,
         control_scheme: Rc<RefCell<ControlScheme>>,
+        quality: Rc<RefCell<QualitySettings>>,
+        speaker_mode: SpeakerMode,
+        match_settings: &MatchSettings,
+        save_file: PathBuf,
+        missing_assets: &[String],
         sender: Sender<Message>,
     ) -> Self {
         let frame_size = engine.renderer.get_frame_size();
 
         let font: Font = Font::from_file(
-            Path::new(assets::fonts::SQUARES_BOLD),
+            mods::resolve(assets::fonts::SQUARES_BOLD),
             31.0,
             Font::default_char_set(),
         )
@@ -53,6 +76,7 @@ impl Menu {
 
         let ctx = &mut engine.user_interface.build_ctx();
 
+        let btn_continue;
         let btn_new_game;
         let btn_settings;
         let btn_save_game;
@@ -73,12 +97,24 @@ impl Menu {
                                 WidgetBuilder::new()
                                     .with_margin(Thickness::uniform(20.0))
                                     .with_child({
-                                        btn_new_game = ButtonBuilder::new(
+                                        btn_continue = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
                                                 .on_row(0)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
+                                        .with_text("Continue")
+                                        .with_font(font.clone())
+                                        .build(ctx);
+                                        btn_continue
+                                    })
+                                    .with_child({
+                                        btn_new_game = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .on_row(1)
+                                                .with_margin(Thickness::uniform(4.0)),
+                                        )
                                         .with_text("New Game")
                                         .with_font(font.clone())
                                         .build(ctx);
@@ -88,7 +124,7 @@ impl Menu {
                                         btn_save_game = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(1)
+                                                .on_row(2)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Save Game")
@@ -100,7 +136,7 @@ impl Menu {
                                         btn_load_game = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(2)
+                                                .on_row(3)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Load Game")
@@ -112,7 +148,7 @@ impl Menu {
                                         btn_settings = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(3)
+                                                .on_row(4)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Settings")
@@ -124,11 +160,11 @@ impl Menu {
                                         btn_quit_game = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(4)
+                                                .on_row(5)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Quit")
-                                        .with_font(font)
+                                        .with_font(font.clone())
                                         .build(ctx);
                                         btn_quit_game
                                     }),
@@ -139,43 +175,76 @@ impl Menu {
                             .add_row(Row::strict(75.0))
                             .add_row(Row::strict(75.0))
                             .add_row(Row::strict(75.0))
+                            .add_row(Row::strict(75.0))
                             .build(ctx),
                         )
                         .build(ctx),
                 ),
         )
         .add_row(Row::stretch())
-        .add_row(Row::strict(500.0))
+        .add_row(Row::strict(575.0))
         .add_row(Row::stretch())
         .add_column(Column::stretch())
         .add_column(Column::strict(400.0))
         .add_column(Column::stretch())
         .build(ctx);
 
-        Self {
+        let pause_menu = PauseMenu::new(ctx, font, frame_size, save_file.clone(), sender.clone());
+        let confirm_dialog = ConfirmDialog::new(ctx, sender.clone());
+        let missing_assets_dialog = if missing_assets.is_empty() {
+            None
+        } else {
+            Some(MissingAssetsDialog::new(ctx, missing_assets))
+        };
+
+        let menu = Self {
             sender: sender.clone(),
             root,
+            btn_continue,
             btn_new_game,
             btn_settings,
             btn_save_game,
             btn_load_game,
             btn_quit_game,
-            options_menu: OptionsMenu::new(engine, control_scheme, sender.clone()),
-            match_menu: MatchMenu::new(
-                &mut engine.user_interface,
-                engine.resource_manager.clone(),
-                sender,
+            pause_menu,
+            confirm_dialog,
+            missing_assets_dialog,
+            save_file,
+            focus_chain: FocusChain::new(vec![
+                btn_continue,
+                btn_new_game,
+                btn_save_game,
+                btn_load_game,
+                btn_settings,
+                btn_quit_game,
+            ]),
+            options_menu: OptionsMenu::new(
+                engine,
+                control_scheme,
+                quality,
+                speaker_mode,
+                sender.clone(),
             ),
+            match_menu: MatchMenu::new(engine, match_settings, sender),
+        };
+
+        if let Some(dialog) = menu.missing_assets_dialog.as_ref() {
+            dialog.open(&mut engine.user_interface);
         }
+
+        menu
     }
 
-    pub fn set_visible(&mut self, ui: &mut Gui, visible: bool) {
+    /// Shows pause menu if there is a match in progress, or main menu otherwise.
+    pub fn set_visible(&mut self, ui: &mut Gui, visible: bool, in_game: bool) {
         ui.send_message(WidgetMessage::visibility(
             self.root,
             MessageDirection::ToWidget,
-            visible,
+            visible && !in_game,
         ));
+        self.pause_menu.set_visible(ui, visible && in_game);
         if !visible {
+            self.confirm_dialog.close(ui);
             ui.send_message(WindowMessage::close(
                 self.options_menu.window,
                 MessageDirection::ToWidget,
@@ -188,39 +257,108 @@ impl Menu {
     }
 
     pub fn is_visible(&self, ui: &Gui) -> bool {
-        ui.node(self.root).visibility()
+        ui.node(self.root).visibility() || ui.node(self.pause_menu.root).visibility()
+    }
+
+    /// Returns true if pause menu is shown over a match, simulation must not run in this case.
+    pub fn is_paused(&self, ui: &Gui) -> bool {
+        ui.node(self.pause_menu.root).visibility()
     }
 
-    pub fn process_input_event(&mut self, engine: &mut GameEngine, event: &Event<()>) {
+    /// Moves keyboard focus in topmost visible window. Returns true if event was consumed,
+    /// false is returned for `Back` action when there is nothing left to close in menu.
+    fn navigate(&mut self, ui: &mut Gui, event: &Event<()>) -> bool {
+        let action = match NavigationAction::from_event(event) {
+            Some(action) => action,
+            None => return false,
+        };
+
+        let (focus_chain, window) = if ui.node(self.options_menu.window).visibility() {
+            (
+                &mut self.options_menu.focus_chain,
+                Some(self.options_menu.window),
+            )
+        } else if ui.node(self.match_menu.window).visibility() {
+            (
+                &mut self.match_menu.focus_chain,
+                Some(self.match_menu.window),
+            )
+        } else if ui.node(self.pause_menu.root).visibility() {
+            (&mut self.pause_menu.focus_chain, None)
+        } else {
+            (&mut self.focus_chain, None)
+        };
+
+        if focus_chain.process(ui, action) {
+            true
+        } else if let Some(window) = window {
+            ui.send_message(WindowMessage::close(window, MessageDirection::ToWidget));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if event was consumed by menu (dialog or keyboard navigation) and must
+    /// not be processed by the game.
+    pub fn process_input_event(&mut self, engine: &mut GameEngine, event: &Event<()>) -> bool {
+        let missing_assets_dialog = self
+            .missing_assets_dialog
+            .as_ref()
+            .filter(|dialog| dialog.is_open(&engine.user_interface));
+        let consumed = if let Some(dialog) = missing_assets_dialog {
+            dialog.process_input_event(&mut engine.user_interface, event);
+            true
+        } else if self.confirm_dialog.is_open() {
+            self.confirm_dialog
+                .process_input_event(&mut engine.user_interface, event);
+            true
+        } else if self.is_visible(&engine.user_interface) && !self.options_menu.is_waiting_input() {
+            self.navigate(&mut engine.user_interface, event)
+        } else {
+            false
+        };
+
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::Resized(new_size) = event {
-                engine.user_interface.send_message(WidgetMessage::width(
-                    self.root,
-                    MessageDirection::ToWidget,
-                    new_size.width as f32,
-                ));
-                engine.user_interface.send_message(WidgetMessage::height(
-                    self.root,
-                    MessageDirection::ToWidget,
-                    new_size.height as f32,
-                ));
+                for &root in &[self.root, self.pause_menu.root] {
+                    engine.user_interface.send_message(WidgetMessage::width(
+                        root,
+                        MessageDirection::ToWidget,
+                        new_size.width as f32,
+                    ));
+                    engine.user_interface.send_message(WidgetMessage::height(
+                        root,
+                        MessageDirection::ToWidget,
+                        new_size.height as f32,
+                    ));
+                }
             }
         }
 
         self.options_menu.process_input_event(engine, event);
+
+        consumed
     }
 
     pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage) {
         if let UiMessageData::Button(msg) = message.data() {
             if let ButtonMessage::Click = msg {
-                if message.destination() == self.btn_new_game {
+                if message.destination() == self.btn_continue {
+                    self.sender.send(Message::ContinueGame).unwrap();
+                } else if message.destination() == self.btn_new_game {
                     engine.user_interface.send_message(WindowMessage::open(
                         self.match_menu.window,
                         MessageDirection::ToWidget,
                         true,
                     ));
                 } else if message.destination() == self.btn_save_game {
-                    self.sender.send(Message::SaveGame).unwrap();
+                    self.confirm_dialog.ask_if(
+                        &mut engine.user_interface,
+                        self.save_file.exists(),
+                        "Overwrite save?",
+                        Message::SaveGame,
+                    );
                 } else if message.destination() == self.btn_load_game {
                     self.sender.send(Message::LoadGame).unwrap();
                 } else if message.destination() == self.btn_quit_game {
@@ -235,6 +373,17 @@ impl Menu {
             }
         }
 
+        self.pause_menu.handle_ui_event(
+            engine,
+            message,
+            self.options_menu.window,
+            &mut self.confirm_dialog,
+        );
+        self.confirm_dialog
+            .handle_ui_event(&mut engine.user_interface, message);
+        if let Some(dialog) = self.missing_assets_dialog.as_ref() {
+            dialog.handle_ui_event(&mut engine.user_interface, message);
+        }
         self.options_menu.handle_ui_event(engine, message);
         self.match_menu.handle_ui_event(engine, message);
     }
@@ -10,37 +10,77 @@
 //! all kinds of games, but at least it very useful for first-person shooters.
 
 use crate::{
-    actor::Actor,
+    actor::{Actor, ActorDescriptor},
     bot::BotKind,
+    character::Team,
+    deployable::Mine,
+    dynamic_prop::DynamicProp,
     effects::EffectKind,
     item::{Item, ItemKind},
+    leader_board::MatchSummary,
+    level::DamageSource,
     projectile::ProjectileKind,
+    race::BestRun,
+    reverb::ReverbZone,
+    settings::{LoadoutSettings, MatchSettings, SpeakerMode},
+    shooting_range::Target,
+    sound_variation::SoundCategory,
     weapon::{Weapon, WeaponKind},
-    MatchOptions,
 };
-use rg3d::core::{math::mat3::Mat3, math::vec3::Vec3, pool::Handle};
+use rg3d::{
+    core::{math::mat3::Mat3, math::vec3::Vec3, pool::Handle},
+    scene::node::Node,
+};
 use std::path::PathBuf;
 
+/// Messages are grouped by categories, subsystems subscribe to categories they're interested
+/// in (see `event_bus` module).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MessageCategory {
+    /// Game flow - starting, saving, loading and ending matches.
+    Session,
+    /// Spawning, damaging and removing actors, changes of their score.
+    Actor,
+    Weapon,
+    Item,
+    Effect,
+    Sound,
+    Notification,
+}
+
 #[derive(Debug)]
 pub enum Message {
     GiveNewWeapon {
         actor: Handle<Actor>,
         kind: WeaponKind,
     },
-    AddBot {
-        kind: BotKind,
-        position: Vec3,
-        name: Option<String>,
-    },
     RemoveActor {
         actor: Handle<Actor>,
     },
-    /// Spawns new bot at random spawn point. Selection of spawn point can be based on some
-    /// particular heuristic, leading to good selection (like do spawn at a point with least
-    /// enemies nearby, which will increase survival probability)
-    SpawnBot {
+    /// Spawns new actor as described, spawn point is selected by policy of descriptor (for
+    /// example a point with least enemies nearby, which will increase survival probability).
+    SpawnActor {
+        descriptor: ActorDescriptor,
+    },
+    /// Adds a bot to running match, `Team::None` puts it into the smallest team in team based
+    /// modes.
+    AddBot {
         kind: BotKind,
+        team: Team,
+    },
+    /// Kicks a bot out of running match together with its row of leader board. In team based
+    /// modes the bot is taken from the largest team, so teams stay balanced.
+    RemoveBot,
+    /// Adds given amount of frags to actor with given name, amount can be negative. Used by
+    /// match scripts to implement custom scoring rules.
+    AdjustScore {
         name: String,
+        amount: i32,
+    },
+    /// Projectile shot from a weapon of given kind hit an actor, counts for accuracy.
+    CountHit {
+        who: Handle<Actor>,
+        weapon: WeaponKind,
     },
     /// Gives item of specified kind to a given actor. Basically it means that actor will take
     /// item and consume it immediately (heal itself, add ammo, etc.)
@@ -79,23 +119,82 @@ pub enum Message {
         gain: f32,
         rolloff_factor: f32,
         radius: f32,
+        /// Defines how much pitch and gain of the sound vary between plays.
+        category: SoundCategory,
     },
     ShowWeapon {
         weapon: Handle<Weapon>,
         state: bool,
     },
+    /// Switches laser of weapon with laser sight attachment.
+    ToggleLaser {
+        weapon: Handle<Weapon>,
+    },
     DamageActor {
         actor: Handle<Actor>,
         /// Actor who damaged target actor, can be Handle::NONE if damage came from environment
         /// or not from any actor.
         who: Handle<Actor>,
         amount: f32,
+        source: DamageSource,
+        /// Velocity change of damaged actor in meters per second, actor is pushed even if it
+        /// takes no damage.
+        impulse: Vec3,
+    },
+    /// Damages dynamic prop and pushes it by given impulse.
+    DamageProp {
+        prop: Handle<DynamicProp>,
+        /// Actor who damaged the prop, can be Handle::NONE.
+        who: Handle<Actor>,
+        amount: f32,
+        /// Velocity change of the prop in meters per second.
+        impulse: Vec3,
+    },
+    /// Projectile hit a target of shooting range at given point.
+    HitTarget {
+        target: Handle<Target>,
+        position: Vec3,
+        amount: f32,
+    },
+    /// Projectile hit a mine, which disarms it.
+    DisarmMine {
+        mine: Handle<Mine>,
+        /// Actor who shot the mine, can be Handle::NONE.
+        who: Handle<Actor>,
+    },
+    /// Pushes dynamic props and damages actors and props within given radius, damage and impulse
+    /// fade out with distance from center of explosion. Actor who caused explosion is damaged
+    /// too, unless no self damage mutator is enabled.
+    Explosion {
+        position: Vec3,
+        radius: f32,
+        damage: f32,
+        /// Velocity change of props at center of explosion in meters per second.
+        impulse: f32,
+        /// Velocity change of actors at center of explosion in meters per second, launches
+        /// them away and up.
+        knockback: f32,
+        who: Handle<Actor>,
+        /// What exploded, actors damaged by explosion get it as source of damage.
+        source: DamageSource,
+    },
+    /// Projectile passed close to actor or hit something next to it, bots get suppressed
+    /// by this.
+    NearMiss {
+        actor: Handle<Actor>,
     },
     CreateEffect {
         kind: EffectKind,
         position: Vec3,
     },
-    SpawnPlayer,
+    /// Announcement of remaining match time, HUD shows text and sound manager plays voice
+    /// line at the same time.
+    Announce {
+        text: String,
+        sound: PathBuf,
+        /// Time in seconds text stays on screen.
+        duration: f32,
+    },
     /// HUD listens such events and puts them into queue.
     AddNotification {
         text: String,
@@ -104,16 +203,125 @@ pub enum Message {
     RespawnActor {
         actor: Handle<Actor>,
     },
+    /// Starts next round of duel - respawns every actor and resets items.
+    StartRound,
     /// Save game state to a file. TODO: Add filename field.
     SaveGame,
     /// Loads game state from a file. TODO: Add filename field.
     LoadGame,
+    /// Loads most recent save - either manual save or autosave.
+    ContinueGame,
+    /// Closes pause menu and returns to current match.
+    ResumeGame,
+    /// Opens loadout screen, sent before first spawn of player and on its death in modes
+    /// that allow changing loadout.
+    ChooseLoadout,
+    /// Loadout was selected on loadout screen, it is remembered and applied to player on
+    /// next spawn. Player is spawned right away if it wasn't deployed yet.
+    SetLoadout {
+        loadout: LoadoutSettings,
+    },
     StartNewGame {
-        options: MatchOptions,
+        settings: MatchSettings,
     },
     QuitGame,
     SetMusicVolume {
         volume: f32,
     },
-    EndMatch,
+    /// Starts looping sound that follows a scene node and is pitch-shifted by Doppler effect.
+    AttachSound {
+        node: Handle<Node>,
+        path: PathBuf,
+        position: Vec3,
+        gain: f32,
+        radius: f32,
+    },
+    /// Moves sound attached to a node, velocity is in meters per second.
+    MoveAttachedSound {
+        node: Handle<Node>,
+        position: Vec3,
+        velocity: Vec3,
+    },
+    DetachSound {
+        node: Handle<Node>,
+    },
+    /// Reverb zones of level, see `reverb` module.
+    SetReverbZones {
+        zones: Vec<ReverbZone>,
+    },
+    /// Adds looping ambient sound of level, see `soundscape` module.
+    AddAmbientSound {
+        path: PathBuf,
+        position: Vec3,
+        gain: f32,
+        rolloff_factor: f32,
+        radius: f32,
+    },
+    SetSpeakerMode {
+        mode: SpeakerMode,
+    },
+    /// Plays a sound on the left of listener and then on the right.
+    TestSpeakers,
+    /// Ends current match. Summary is provided by level when end conditions are met, it is
+    /// `None` if match was ended by player.
+    EndMatch {
+        summary: Option<MatchSummary>,
+    },
+    /// New best time trial run on a map, it is kept in profile.
+    RecordRun {
+        map: String,
+        run: BestRun,
+    },
+}
+
+impl Message {
+    pub fn category(&self) -> MessageCategory {
+        match self {
+            Message::RemoveActor { .. }
+            | Message::SpawnActor { .. }
+            | Message::AddBot { .. }
+            | Message::RemoveBot
+            | Message::AdjustScore { .. }
+            | Message::CountHit { .. }
+            | Message::DamageActor { .. }
+            | Message::NearMiss { .. }
+            | Message::RespawnActor { .. }
+            | Message::StartRound => MessageCategory::Actor,
+            Message::GiveNewWeapon { .. }
+            | Message::CreateProjectile { .. }
+            | Message::ShootWeapon { .. }
+            | Message::ShowWeapon { .. }
+            | Message::ToggleLaser { .. }
+            | Message::DamageProp { .. }
+            | Message::HitTarget { .. }
+            | Message::DisarmMine { .. }
+            | Message::Explosion { .. } => MessageCategory::Weapon,
+            Message::GiveItem { .. } | Message::PickUpItem { .. } | Message::SpawnItem { .. } => {
+                MessageCategory::Item
+            }
+            Message::CreateEffect { .. } => MessageCategory::Effect,
+            Message::PlaySound { .. }
+            | Message::SetMusicVolume { .. }
+            | Message::AddAmbientSound { .. }
+            | Message::SetReverbZones { .. }
+            | Message::AttachSound { .. }
+            | Message::MoveAttachedSound { .. }
+            | Message::DetachSound { .. }
+            | Message::SetSpeakerMode { .. }
+            | Message::TestSpeakers => MessageCategory::Sound,
+            Message::AddNotification { .. } | Message::Announce { .. } => {
+                MessageCategory::Notification
+            }
+            Message::SaveGame
+            | Message::LoadGame
+            | Message::ContinueGame
+            | Message::ResumeGame
+            | Message::ChooseLoadout
+            | Message::SetLoadout { .. }
+            | Message::StartNewGame { .. }
+            | Message::QuitGame
+            | Message::EndMatch { .. }
+            | Message::RecordRun { .. } => MessageCategory::Session,
+        }
+    }
 }
@@ -0,0 +1,141 @@
+//! Mods (asset packs) are directories inside `mods/` that mirror layout of game directory.
+//! Every file of enabled mod overrides game file with same relative path, for example
+//! `mods/hd_weapons/data/models/ak47.FBX` replaces `data/models/ak47.FBX`. If several mods
+//! provide same file, mod which name goes last in alphabetical order wins. Stats of
+//! weapons, projectiles and bots are data files too, see `definition` module.
+//!
+//! Every resource must be requested through `resolve`, otherwise mods won't be able to
+//! override it. Resources are requested from main thread only, so mod layers are kept in
+//! thread local storage instead of being passed to every place that loads something.
+
+use rg3d::utils::log::Log;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+pub const MODS_DIR: &str = "mods";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModSettings {
+    /// Names of mods that were disabled by user. Disabled list is stored instead of enabled
+    /// one, so newly installed mods are enabled by default.
+    pub disabled: Vec<String>,
+}
+
+#[derive(Default)]
+struct ModState {
+    settings: ModSettings,
+    /// Root directories of enabled mods, in order of increasing priority.
+    layers: Vec<PathBuf>,
+    /// Incremented every time layers change, so data built from resolved files knows when
+    /// it has to be built again.
+    revision: u32,
+}
+
+thread_local! {
+    static STATE: RefCell<ModState> = RefCell::new(Default::default());
+}
+
+/// Returns names of every mod in mods directory, sorted alphabetically.
+pub fn available() -> Vec<String> {
+    let mut names = match std::fs::read_dir(MODS_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+fn update_layers(state: &mut ModState) {
+    state.layers = available()
+        .into_iter()
+        .filter(|name| !state.settings.disabled.contains(name))
+        .map(|name| Path::new(MODS_DIR).join(name))
+        .collect();
+    state.revision += 1;
+
+    for layer in state.layers.iter() {
+        Log::writeln(format!("Mod {} is enabled", layer.display()));
+    }
+}
+
+/// Scans mods directory and enables every mod that is not disabled in settings.
+pub fn apply(settings: &ModSettings) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.settings = settings.clone();
+        update_layers(&mut state);
+    })
+}
+
+pub fn settings() -> ModSettings {
+    STATE.with(|state| state.borrow().settings.clone())
+}
+
+/// Revision of enabled mods, it changes every time mods are enabled or disabled.
+pub fn revision() -> u32 {
+    STATE.with(|state| state.borrow().revision)
+}
+
+pub fn is_enabled(name: &str) -> bool {
+    STATE.with(|state| !state.borrow().settings.disabled.iter().any(|n| n == name))
+}
+
+/// Enables or disables mod. Resources that were already loaded are not reloaded, so change
+/// affects only resources that will be requested after it (next match).
+pub fn set_enabled(name: &str, enabled: bool) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.settings.disabled.retain(|n| n != name);
+        if !enabled {
+            state.settings.disabled.push(name.to_owned());
+        }
+        update_layers(&mut state);
+    })
+}
+
+/// Maps path of game file to the file that should actually be loaded - either a file from
+/// enabled mod with highest priority or the path itself.
+pub fn resolve<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    STATE.with(|state| {
+        state
+            .borrow()
+            .layers
+            .iter()
+            .rev()
+            .map(|layer| layer.join(path))
+            .find(|overridden| overridden.exists())
+            .unwrap_or_else(|| path.to_owned())
+    })
+}
+
+/// Lists files of a directory merged from game directory and every enabled mod. Mods can add
+/// new files and override existing ones, returned paths are already resolved.
+pub fn read_dir<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let dir = dir.as_ref();
+    let mut files = BTreeMap::new();
+
+    let mut roots = vec![PathBuf::new()];
+    STATE.with(|state| roots.extend(state.borrow().layers.iter().cloned()));
+
+    for root in roots {
+        if let Ok(entries) = std::fs::read_dir(root.join(dir)) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.is_file() {
+                    files.insert(entry.file_name(), path);
+                }
+            }
+        }
+    }
+
+    files.into_iter().map(|(_, path)| path).collect()
+}
@@ -0,0 +1,139 @@
+//! Morale of bots. Heavy incoming fire suppresses a bot - it shoots less accurate and strafes
+//! instead of walking its path. Damage, near misses and being outnumbered lower morale, and bot
+//! with low morale occasionally retreats away from its enemies for a while.
+
+use crate::random;
+use rand::Rng;
+use rg3d::core::{
+    math::vec3::Vec3,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+/// Suppression above which bot stops following its path and strafes.
+const STRAFE_THRESHOLD: f32 = 0.5;
+/// Morale below which outnumbered bot may start to retreat.
+const RETREAT_THRESHOLD: f32 = 0.3;
+const RETREAT_TIME: f32 = 3.0;
+/// Min time between two retreats.
+const RETREAT_COOLDOWN: f32 = 10.0;
+/// Time after which bot changes direction of strafing.
+const STRAFE_SWITCH_TIME: f32 = 1.2;
+
+pub struct Morale {
+    /// From 0 (broken) to 1 (confident).
+    morale: f32,
+    /// From 0 (calm) to 1 (pinned down), goes up from incoming fire and fades out quickly.
+    suppression: f32,
+    retreat_time: f32,
+    retreat_cooldown: f32,
+    retreat_dir: Vec3,
+    strafe_time: f32,
+    strafe_sign: f32,
+}
+
+impl Default for Morale {
+    fn default() -> Self {
+        Self {
+            morale: 1.0,
+            suppression: 0.0,
+            retreat_time: 0.0,
+            retreat_cooldown: 0.0,
+            retreat_dir: Default::default(),
+            strafe_time: 0.0,
+            strafe_sign: 1.0,
+        }
+    }
+}
+
+impl Morale {
+    pub fn on_damage(&mut self, amount: f32, max_health: f32) {
+        self.morale = (self.morale - 0.5 * amount / max_health).max(0.0);
+        self.suppression = (self.suppression + 0.3).min(1.0);
+    }
+
+    /// Called when a projectile passed close to bot or hit something next to it.
+    pub fn on_near_miss(&mut self) {
+        self.morale = (self.morale - 0.03).max(0.0);
+        self.suppression = (self.suppression + 0.2).min(1.0);
+    }
+
+    /// `enemies` is an amount and center of enemies around bot, `allies` is an amount of allies
+    /// around bot (not counting bot itself).
+    pub fn update(&mut self, dt: f32, position: Vec3, enemies: (usize, Vec3), allies: usize) {
+        let (enemy_count, enemy_center) = enemies;
+        let outnumbered = enemy_count > allies + 1;
+
+        let recovery = if outnumbered { -0.02 } else { 0.05 };
+        self.morale = (self.morale + recovery * dt).max(0.0).min(1.0);
+        self.suppression = (self.suppression - 0.5 * dt).max(0.0);
+
+        self.strafe_time -= dt;
+        if self.strafe_time <= 0.0 {
+            self.strafe_time = STRAFE_SWITCH_TIME;
+            self.strafe_sign = if random::rng().gen_bool(0.5) {
+                1.0
+            } else {
+                -1.0
+            };
+        }
+
+        self.retreat_time -= dt;
+        self.retreat_cooldown -= dt;
+        if outnumbered
+            && self.morale < RETREAT_THRESHOLD
+            && self.retreat_cooldown <= 0.0
+            // Half a chance per second, so bots don't break all at the same moment.
+            && random::rng().gen_range(0.0, 1.0) < 0.5 * dt
+        {
+            let away = position - enemy_center;
+            self.retreat_dir = Vec3::new(away.x, 0.0, away.z)
+                .normalized()
+                .unwrap_or(Vec3::LOOK);
+            self.retreat_time = RETREAT_TIME;
+            self.retreat_cooldown = RETREAT_COOLDOWN;
+        }
+    }
+
+    pub fn is_retreating(&self) -> bool {
+        self.retreat_time > 0.0
+    }
+
+    pub fn suppression(&self) -> f32 {
+        self.suppression
+    }
+
+    /// Multiplier of aim deviation, suppressed bot is three times less accurate.
+    pub fn jitter_factor(&self) -> f32 {
+        1.0 + 2.0 * self.suppression
+    }
+
+    /// Selects point bot should move to: given path point normally, a point to the side when
+    /// bot is suppressed or a point away from enemies when it retreats.
+    pub fn move_target(&self, position: Vec3, look_dir: Vec3, path_point: Vec3) -> Vec3 {
+        if self.is_retreating() {
+            position + self.retreat_dir.scale(5.0)
+        } else if self.suppression > STRAFE_THRESHOLD {
+            let side = look_dir
+                .cross(&Vec3::UP)
+                .normalized()
+                .unwrap_or(Vec3::RIGHT);
+            position + side.scale(2.0 * self.strafe_sign)
+        } else {
+            path_point
+        }
+    }
+}
+
+impl Visit for Morale {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.morale.visit("Morale", visitor)?;
+        self.suppression.visit("Suppression", visitor)?;
+        self.retreat_time.visit("RetreatTime", visitor)?;
+        self.retreat_cooldown.visit("RetreatCooldown", visitor)?;
+        self.retreat_dir.visit("RetreatDir", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,232 @@
+//! Keyboard navigation of menus. Every menu keeps a chain of widgets that can be focused,
+//! Up/Down (or Tab) moves focus along the chain, Left/Right changes value of focused slider,
+//! check box or dropdown list, Enter or Space activates focused widget and Escape backs out
+//! of current window. Focused widget is highlighted by changing foreground brush of it and
+//! all its descendants, so it works for any kind of widget without special styles.
+
+use crate::{Gui, UINodeHandle};
+use rg3d::{
+    core::color::Color,
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    gui::{
+        brush::Brush,
+        message::{
+            ButtonMessage, CheckBoxMessage, DropdownListMessage, MessageDirection,
+            NumericUpDownMessage, ScrollBarMessage, WidgetMessage,
+        },
+        node::UINode,
+    },
+};
+
+const FOCUS_COLOR: Color = Color::opaque(255, 170, 0);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NavigationAction {
+    Previous,
+    Next,
+    Decrease,
+    Increase,
+    Activate,
+    Back,
+}
+
+impl NavigationAction {
+    pub fn from_event(event: &Event<()>) -> Option<Self> {
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                if let ElementState::Pressed = input.state {
+                    return match input.virtual_keycode? {
+                        VirtualKeyCode::Up => Some(NavigationAction::Previous),
+                        VirtualKeyCode::Down | VirtualKeyCode::Tab => Some(NavigationAction::Next),
+                        VirtualKeyCode::Left => Some(NavigationAction::Decrease),
+                        VirtualKeyCode::Right => Some(NavigationAction::Increase),
+                        VirtualKeyCode::Return
+                        | VirtualKeyCode::NumpadEnter
+                        | VirtualKeyCode::Space => Some(NavigationAction::Activate),
+                        VirtualKeyCode::Escape | VirtualKeyCode::Back => {
+                            Some(NavigationAction::Back)
+                        }
+                        _ => None,
+                    };
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct FocusChain {
+    items: Vec<UINodeHandle>,
+    focused: Option<usize>,
+    /// Original foreground of every highlighted node, restored when focus moves away.
+    highlighted: Vec<(UINodeHandle, Brush)>,
+}
+
+impl FocusChain {
+    pub fn new(items: Vec<UINodeHandle>) -> Self {
+        Self {
+            items,
+            focused: None,
+            highlighted: Vec::new(),
+        }
+    }
+
+    pub fn focused(&self) -> Option<UINodeHandle> {
+        self.focused.map(|i| self.items[i])
+    }
+
+    pub fn focus(&mut self, ui: &mut Gui, handle: UINodeHandle) {
+        if let Some(index) = self.items.iter().position(|&item| item == handle) {
+            self.remove_highlight(ui);
+            self.focused = Some(index);
+            self.highlight(ui, handle);
+        }
+    }
+
+    /// Moves focus to next visible item, wrapping around at the ends of the chain. Items on
+    /// hidden tabs (or otherwise invisible) are skipped.
+    fn step(&mut self, ui: &mut Gui, forward: bool) {
+        let count = self.items.len();
+        let mut index = self.focused;
+        for _ in 0..count {
+            let next = match index {
+                None if forward => 0,
+                None => count - 1,
+                Some(i) if forward => (i + 1) % count,
+                Some(i) => (i + count - 1) % count,
+            };
+            index = Some(next);
+            let handle = self.items[next];
+            if ui.node(handle).is_globally_visible() {
+                self.focus(ui, handle);
+                return;
+            }
+        }
+    }
+
+    /// Performs given action on the chain. Returns false if action wasn't handled, this is
+    /// always the case for `Back` - owner of the chain decides what to close.
+    pub fn process(&mut self, ui: &mut Gui, action: NavigationAction) -> bool {
+        let focused = self
+            .focused()
+            .filter(|&handle| ui.node(handle).is_globally_visible());
+
+        match action {
+            NavigationAction::Previous => self.step(ui, false),
+            NavigationAction::Next => self.step(ui, true),
+            NavigationAction::Decrease | NavigationAction::Increase => {
+                let forward = action == NavigationAction::Increase;
+                let adjusted = match focused {
+                    Some(handle) => adjust(ui, handle, forward),
+                    None => false,
+                };
+                // Left/Right moves focus between widgets that have no value, like buttons
+                // in a row.
+                if !adjusted {
+                    self.step(ui, forward);
+                }
+            }
+            NavigationAction::Activate => {
+                if let Some(handle) = focused {
+                    activate(ui, handle);
+                }
+            }
+            NavigationAction::Back => return false,
+        }
+
+        true
+    }
+
+    fn highlight(&mut self, ui: &mut Gui, handle: UINodeHandle) {
+        let mut stack = vec![handle];
+        while let Some(node_handle) = stack.pop() {
+            let node = ui.node(node_handle);
+            stack.extend_from_slice(node.children());
+            self.highlighted.push((node_handle, node.foreground()));
+            ui.send_message(WidgetMessage::foreground(
+                node_handle,
+                MessageDirection::ToWidget,
+                Brush::Solid(FOCUS_COLOR),
+            ));
+        }
+    }
+
+    fn remove_highlight(&mut self, ui: &mut Gui) {
+        for (handle, brush) in self.highlighted.drain(..) {
+            ui.send_message(WidgetMessage::foreground(
+                handle,
+                MessageDirection::ToWidget,
+                brush,
+            ));
+        }
+    }
+}
+
+/// Searches for nearest button among given node and its ancestors. Useful to find buttons
+/// created by compound widgets, like tab headers. Returns `Handle::NONE` if there is none.
+pub fn enclosing_button(ui: &Gui, mut handle: UINodeHandle) -> UINodeHandle {
+    while handle.is_some() {
+        let node = ui.node(handle);
+        if let UINode::Button(_) = node {
+            return handle;
+        }
+        handle = node.parent();
+    }
+    UINodeHandle::NONE
+}
+
+/// Changes value of a widget one step up or down, returns false if widget has no value.
+fn adjust(ui: &mut Gui, handle: UINodeHandle, forward: bool) -> bool {
+    let message = match ui.node(handle) {
+        UINode::ScrollBar(scroll_bar) => {
+            let delta = if forward {
+                scroll_bar.step()
+            } else {
+                -scroll_bar.step()
+            };
+            let value = (scroll_bar.value() + delta)
+                .max(scroll_bar.min_value())
+                .min(scroll_bar.max_value());
+            ScrollBarMessage::value(handle, MessageDirection::ToWidget, value)
+        }
+        UINode::NumericUpDown(numeric) => {
+            // Numeric pickers in menus are used for whole numbers only, picker clamps value
+            // by itself.
+            let delta = if forward { 1.0 } else { -1.0 };
+            NumericUpDownMessage::value(handle, MessageDirection::ToWidget, numeric.value() + delta)
+        }
+        UINode::CheckBox(check_box) => {
+            let checked = check_box.checked().unwrap_or(false);
+            CheckBoxMessage::checked(handle, MessageDirection::ToWidget, Some(!checked))
+        }
+        UINode::DropdownList(dropdown_list) => {
+            let count = dropdown_list.items().len();
+            if count == 0 {
+                return false;
+            }
+            let selection = match dropdown_list.selection() {
+                Some(i) if forward => (i + 1).min(count - 1),
+                Some(i) => i.saturating_sub(1),
+                None => 0,
+            };
+            DropdownListMessage::selection(handle, MessageDirection::ToWidget, Some(selection))
+        }
+        _ => return false,
+    };
+    ui.send_message(message);
+    true
+}
+
+fn activate(ui: &mut Gui, handle: UINodeHandle) {
+    match ui.node(handle) {
+        UINode::Button(_) => {
+            // Pretend that button was clicked, handlers does not care about direction.
+            ui.send_message(ButtonMessage::click(handle, MessageDirection::FromWidget));
+        }
+        UINode::CheckBox(_) | UINode::DropdownList(_) => {
+            adjust(ui, handle, true);
+        }
+        _ => (),
+    }
+}
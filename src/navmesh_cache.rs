@@ -0,0 +1,89 @@
+//! Baked navmeshes. Building navmesh from `Navmesh` node of a map takes a while on big maps,
+//! so it can be baked once: run the game with `--bake-navmesh <map>`, for example
+//! `--bake-navmesh data/models/dm6.fbx`. Baked navmesh is written next to the map model with
+//! `navmesh.bin` extension and level loads it instead of building navmesh on every start.
+//! Cache isn't checked against the map, so navmesh must be baked again after it was edited -
+//! delete the file to go back to building navmesh at load time.
+
+use crate::{mods, GameEngine};
+use rg3d::{
+    core::visitor::{Visit, Visitor},
+    scene::Scene,
+    utils::{log::Log, navmesh::Navmesh},
+};
+use std::{env, path::PathBuf};
+
+pub const BAKE_FLAG: &str = "--bake-navmesh";
+
+/// Cache of a map lives next to the map file that is actually loaded, so map overridden by a
+/// mod doesn't pick up navmesh of the original map.
+fn file_of_map(map: &str) -> PathBuf {
+    mods::resolve(map).with_extension("navmesh.bin")
+}
+
+/// Path of map passed with `--bake-navmesh` flag.
+pub fn map_from_args() -> Option<String> {
+    env::args().skip_while(|arg| arg != BAKE_FLAG).nth(1)
+}
+
+/// Loads baked navmesh of a map, `None` if the map has no cache or the cache is corrupted.
+pub fn load(map: &str) -> Option<Navmesh> {
+    let path = file_of_map(map);
+    if !path.exists() {
+        return None;
+    }
+    let mut navmesh = Navmesh::default();
+    match Visitor::load_binary(&path).and_then(|mut visitor| navmesh.visit("Navmesh", &mut visitor))
+    {
+        Ok(_) => {
+            Log::writeln(format!("Navmesh loaded from {}", path.display()));
+            Some(navmesh)
+        }
+        Err(e) => {
+            Log::writeln(format!(
+                "WARNING: Navmesh cache {} is corrupted: {}, navmesh will be built from map.",
+                path.display(),
+                e
+            ));
+            None
+        }
+    }
+}
+
+/// Builds navmesh from `Navmesh` node of a map and writes it to cache of the map, returns
+/// path of written file.
+pub async fn bake(engine: &mut GameEngine, map: &str) -> Result<PathBuf, String> {
+    let model = engine
+        .resource_manager
+        .request_model(mods::resolve(map))
+        .await
+        .map_err(|_| format!("Unable to load map {}", map))?;
+    let mut scene = Scene::new();
+    let root = model.instantiate_geometry(&mut scene);
+    let navmesh_node = scene.graph.find_by_name(root, "Navmesh");
+    if navmesh_node.is_none() {
+        return Err(format!("Map {} has no Navmesh node", map));
+    }
+    let mut navmesh = Navmesh::from_mesh(scene.graph[navmesh_node].as_mesh());
+
+    let path = file_of_map(map);
+    let mut visitor = Visitor::new();
+    navmesh
+        .visit("Navmesh", &mut visitor)
+        .and_then(|_| visitor.save_binary(&path))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Bakes navmesh of map given by `--bake-navmesh` flag, returns true if the flag was given.
+pub fn bake_from_args(engine: &mut GameEngine) -> bool {
+    let map = match map_from_args() {
+        Some(map) => map,
+        None => return false,
+    };
+    match rg3d::futures::executor::block_on(bake(engine, &map)) {
+        Ok(path) => Log::writeln(format!("Navmesh of {} baked to {}", map, path.display())),
+        Err(e) => Log::writeln(format!("Unable to bake navmesh: {}", e)),
+    }
+    true
+}
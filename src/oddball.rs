@@ -0,0 +1,162 @@
+//! Skull of oddball mode. Skull is a mesh of a map named `Oddball`, place where it lies at
+//! start of a match is its spawn, maps without such mesh get a plain ball at a random spawn
+//! point. Anyone picks skull up by touching it and gets a point for every second of holding
+//! it. Carrier can't shoot, so it fights only in melee - bots with claws and player with
+//! melee button, and it is shown to everyone on HUD. Carrier drops the skull on death,
+//! dropped skull returns to its spawn after a while. Skull uses flag states of capture the
+//! flag mode.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    ctf::FlagState,
+    leader_board::LeaderBoard,
+    message::Message,
+};
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node, Scene},
+};
+use std::sync::mpsc::Sender;
+
+/// Time after which dropped skull returns to its spawn, in seconds.
+const RETURN_TIME: f32 = 20.0;
+/// Distance from skull at which actor touches it.
+const TOUCH_RADIUS: f32 = 1.5;
+/// Carried skull is shown above head of carrier.
+const CARRY_OFFSET: Vec3 = Vec3 {
+    x: 0.0,
+    y: 1.2,
+    z: 0.0,
+};
+
+#[derive(Default)]
+pub struct Oddball {
+    model: Handle<Node>,
+    spawn: Vec3,
+    position: Vec3,
+    state: FlagState,
+    return_timer: f32,
+    /// Part of a second that carrier held skull and which isn't in leader board yet.
+    hold_timer: f32,
+}
+
+impl Oddball {
+    /// Makes skull from a model and puts it to its spawn.
+    pub fn new(model: Handle<Node>, spawn: Vec3, graph: &mut Graph) -> Self {
+        let mut oddball = Self {
+            model,
+            spawn,
+            ..Default::default()
+        };
+        oddball.set_position(graph, spawn);
+        oddball
+    }
+
+    pub fn state(&self) -> FlagState {
+        self.state
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn is_carrier(&self, actor: Handle<Actor>) -> bool {
+        self.state == FlagState::Carried(actor)
+    }
+
+    /// Returns point that actor should go to - skull or actor that carries it. Carrier has
+    /// nowhere to go, it just stays alive.
+    pub fn objective_point(&self, actor: Handle<Actor>) -> Option<Vec3> {
+        if self.is_carrier(actor) {
+            None
+        } else {
+            Some(self.position)
+        }
+    }
+
+    fn set_position(&mut self, graph: &mut Graph, position: Vec3) {
+        self.position = position;
+        graph[self.model]
+            .local_transform_mut()
+            .set_position(position);
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+        leader_board: &mut LeaderBoard,
+        sender: &Sender<Message>,
+        dt: f32,
+    ) {
+        let notify = |text: String| {
+            sender.send(Message::AddNotification { text }).unwrap();
+        };
+
+        match self.state {
+            FlagState::Carried(carrier) => {
+                if !actors.contains(carrier) || actors.get(carrier).is_dead() {
+                    let position = self.position - CARRY_OFFSET;
+                    self.set_position(&mut scene.graph, position);
+                    self.state = FlagState::Dropped;
+                    self.return_timer = RETURN_TIME;
+                    self.hold_timer = 0.0;
+                    notify("Skull dropped!".to_owned());
+                    return;
+                }
+
+                let carrier = actors.get(carrier);
+                let carrier_position = carrier.position(&scene.physics);
+                self.set_position(&mut scene.graph, carrier_position + CARRY_OFFSET);
+                self.hold_timer += dt;
+                let seconds = self.hold_timer as u32;
+                if seconds > 0 {
+                    self.hold_timer -= seconds as f32;
+                    leader_board.add_hold_time(&carrier.name, seconds);
+                }
+            }
+            FlagState::AtBase | FlagState::Dropped => {
+                if self.state == FlagState::Dropped {
+                    self.return_timer -= dt;
+                    if self.return_timer <= 0.0 {
+                        self.state = FlagState::AtBase;
+                        let spawn = self.spawn;
+                        self.set_position(&mut scene.graph, spawn);
+                        notify("Skull reset!".to_owned());
+                        return;
+                    }
+                }
+
+                let position = self.position;
+                let toucher = actors.pair_iter().find(|(_, actor)| {
+                    !actor.is_dead()
+                        && actor.position(&scene.physics).distance(&position) <= TOUCH_RADIUS
+                });
+                if let Some((handle, actor)) = toucher {
+                    self.state = FlagState::Carried(handle);
+                    self.hold_timer = 0.0;
+                    notify(format!("{} took the skull!", actor.name));
+                }
+            }
+        }
+    }
+}
+
+impl Visit for Oddball {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.model.visit("Model", visitor)?;
+        self.spawn.visit("Spawn", visitor)?;
+        self.position.visit("Position", visitor)?;
+        self.state.visit("State", visitor)?;
+        self.return_timer.visit("ReturnTimer", visitor)?;
+        self.hold_timer.visit("HoldTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,23 +1,27 @@
 use crate::gui::ScrollBarData;
 use crate::{
-    control_scheme::{ControlButton, ControlScheme},
-    gui::{create_check_box, create_scroll_bar, create_scroll_viewer},
+    control_scheme::{ControlButton, ControlPreset, ControlScheme},
+    gui::{create_check_box, create_dropdown_items, create_scroll_bar, create_scroll_viewer},
     message::Message,
-    settings::SoundSettings,
+    mods,
+    navigation::{self, FocusChain},
+    settings::{QualitySettings as GameQualitySettings, SoundSettings, SpeakerMode},
     GameEngine, GuiMessage, UINodeHandle,
 };
 use rg3d::gui::message::MessageDirection;
 use rg3d::{
-    event::{Event, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
     gui::{
         border::BorderBuilder,
         button::ButtonBuilder,
         decorator::DecoratorBuilder,
+        dropdown_list::DropdownListBuilder,
         grid::{Column, GridBuilder, Row},
         list_view::ListViewBuilder,
         message::TextMessage,
         message::{
-            ButtonMessage, CheckBoxMessage, ListViewMessage, ScrollBarMessage, UiMessageData,
+            ButtonMessage, CheckBoxMessage, DropdownListMessage, ListViewMessage, ScrollBarMessage,
+            UiMessageData,
         },
         node::UINode,
         tab_control::{TabControlBuilder, TabDefinition},
@@ -27,10 +31,23 @@ use rg3d::{
         HorizontalAlignment, Orientation, Thickness, VerticalAlignment,
     },
     monitor::VideoMode,
+    renderer::QualitySettings,
     window::Fullscreen,
 };
 use std::{cell::RefCell, rc::Rc, sync::mpsc::Sender};
 
+const SPEAKER_MODES: [(&str, SpeakerMode); 3] = [
+    ("HRTF (Headphones)", SpeakerMode::Hrtf),
+    ("Stereo", SpeakerMode::Stereo),
+    ("Mono", SpeakerMode::Mono),
+];
+
+/// Index of preset in control preset dropdown, `None` for customized buttons.
+fn control_preset_index(control_scheme: &ControlScheme) -> Option<usize> {
+    let preset = control_scheme.preset()?;
+    ControlPreset::ALL.iter().position(|p| *p == preset)
+}
+
 pub struct OptionsMenu {
     pub window: UINodeHandle,
     sender: Sender<Message>,
@@ -45,23 +62,45 @@ pub struct OptionsMenu {
     sb_point_shadow_distance: UINodeHandle,
     sb_spot_shadow_distance: UINodeHandle,
     cb_use_light_scatter: UINodeHandle,
+    cb_dynamic_quality: UINodeHandle,
+    cb_weather: UINodeHandle,
+    cb_reduce_flashes: UINodeHandle,
     video_modes: Vec<VideoMode>,
     control_scheme: Rc<RefCell<ControlScheme>>,
+    quality: Rc<RefCell<GameQualitySettings>>,
     control_scheme_buttons: Vec<UINodeHandle>,
     active_control_button: Option<usize>,
     sb_mouse_sens: UINodeHandle,
     cb_mouse_y_inverse: UINodeHandle,
     cb_smooth_mouse: UINodeHandle,
     cb_shake_camera: UINodeHandle,
+    cb_left_handed: UINodeHandle,
+    sb_viewmodel_distance: UINodeHandle,
+    cb_hide_viewmodel: UINodeHandle,
+    cb_hit_stagger: UINodeHandle,
+    cb_aim_assist: UINodeHandle,
+    sb_aim_assist_magnetism: UINodeHandle,
+    sb_aim_assist_slowdown: UINodeHandle,
+    cb_auto_aim: UINodeHandle,
+    dd_control_preset: UINodeHandle,
     btn_reset_control_scheme: UINodeHandle,
-    cb_use_hrtf: UINodeHandle,
+    dd_speaker_mode: UINodeHandle,
+    /// Mode is kept here because sound context has the same renderer for stereo and mono.
+    speaker_mode: SpeakerMode,
+    btn_test_speakers: UINodeHandle,
     btn_reset_audio_settings: UINodeHandle,
+    btn_reset_graphics_settings: UINodeHandle,
+    /// Check boxes of mods found at startup, paired with names of mods.
+    mod_check_boxes: Vec<(UINodeHandle, String)>,
+    pub focus_chain: FocusChain,
 }
 
 impl OptionsMenu {
     pub fn new(
         engine: &mut GameEngine,
         control_scheme: Rc<RefCell<ControlScheme>>,
+        quality: Rc<RefCell<GameQualitySettings>>,
+        speaker_mode: SpeakerMode,
         sender: Sender<Message>,
     ) -> Self {
         let video_modes: Vec<VideoMode> = engine
@@ -94,17 +133,38 @@ impl OptionsMenu {
         let cb_mouse_y_inverse;
         let cb_smooth_mouse;
         let cb_shake_camera;
+        let cb_left_handed;
+        let sb_viewmodel_distance;
+        let cb_hide_viewmodel;
+        let cb_hit_stagger;
+        let cb_aim_assist;
+        let sb_aim_assist_magnetism;
+        let sb_aim_assist_slowdown;
+        let cb_auto_aim;
+        let dd_control_preset;
         let btn_reset_control_scheme;
         let mut control_scheme_buttons = Vec::new();
-        let cb_use_hrtf;
+        let dd_speaker_mode;
+        let btn_test_speakers;
         let btn_reset_audio_settings;
+        let btn_reset_graphics_settings;
         let cb_use_light_scatter;
+        let cb_dynamic_quality;
+        let cb_weather;
+        let cb_reduce_flashes;
+        let graphics_header;
+        let sound_header;
+        let controls_header;
+        let mods_header;
+        let mut mod_check_boxes = Vec::new();
         let tab_control = TabControlBuilder::new(WidgetBuilder::new())
             .with_tab(TabDefinition {
                 header: {
-                    TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Graphics")
-                        .build(ctx)
+                    graphics_header =
+                        TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+                            .with_text("Graphics")
+                            .build(ctx);
+                    graphics_header
                 },
                 content: {
                     GridBuilder::new(
@@ -361,6 +421,77 @@ impl OptionsMenu {
                                     settings.light_scatter_enabled,
                                 );
                                 cb_use_light_scatter
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(9)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Shadow Distance Scaling")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_dynamic_quality = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    9,
+                                    1,
+                                    quality.borrow().dynamic_quality,
+                                );
+                                cb_dynamic_quality
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(10)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Weather Effects")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_weather = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    10,
+                                    1,
+                                    quality.borrow().weather,
+                                );
+                                cb_weather
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(11)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Reduce Flashes")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_reduce_flashes = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    11,
+                                    1,
+                                    quality.borrow().reduce_flashes,
+                                );
+                                cb_reduce_flashes
+                            })
+                            .with_child({
+                                btn_reset_graphics_settings = ButtonBuilder::new(
+                                    WidgetBuilder::new().on_row(12).with_margin(margin),
+                                )
+                                .with_text("Reset")
+                                .build(ctx);
+                                btn_reset_graphics_settings
                             }),
                     )
                     .add_row(Row::strict(200.0))
@@ -372,6 +503,10 @@ impl OptionsMenu {
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
                     .add_column(Column::strict(250.0))
                     .add_column(Column::stretch())
                     .build(ctx)
@@ -379,9 +514,11 @@ impl OptionsMenu {
             })
             .with_tab(TabDefinition {
                 header: {
-                    TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Sound")
-                        .build(ctx)
+                    sound_header =
+                        TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+                            .with_text("Sound")
+                            .build(ctx);
+                    sound_header
                 },
                 content: {
                     GridBuilder::new(
@@ -451,23 +588,45 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Use HRTF")
+                                .with_text("Speakers")
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
-                                cb_use_hrtf = create_check_box(
-                                    ctx,
-                                    resource_manager.clone(),
-                                    2,
-                                    1,
-                                    SoundSettings::is_hrtf(&engine.sound_context.lock().unwrap()),
-                                );
-                                cb_use_hrtf
+                                let names = SPEAKER_MODES
+                                    .iter()
+                                    .map(|(name, _)| *name)
+                                    .collect::<Vec<_>>();
+                                dd_speaker_mode = DropdownListBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(2)
+                                        .on_column(1)
+                                        .with_margin(margin),
+                                )
+                                .with_items(create_dropdown_items(ctx, &names))
+                                .with_selected(
+                                    SPEAKER_MODES
+                                        .iter()
+                                        .position(|(_, mode)| *mode == speaker_mode)
+                                        .unwrap_or(0),
+                                )
+                                .build(ctx);
+                                dd_speaker_mode
+                            })
+                            .with_child({
+                                btn_test_speakers = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(3)
+                                        .on_column(1)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Test Left / Right")
+                                .build(ctx);
+                                btn_test_speakers
                             })
                             .with_child({
                                 btn_reset_audio_settings = ButtonBuilder::new(
-                                    WidgetBuilder::new().on_row(3).with_margin(margin),
+                                    WidgetBuilder::new().on_row(4).with_margin(margin),
                                 )
                                 .with_text("Reset")
                                 .build(ctx);
@@ -478,6 +637,7 @@ impl OptionsMenu {
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(common_row)
                     .add_column(Column::strict(250.0))
                     .add_column(Column::stretch())
                     .build(ctx)
@@ -485,16 +645,18 @@ impl OptionsMenu {
             })
             .with_tab(TabDefinition {
                 header: {
-                    TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Controls")
-                        .build(ctx)
+                    controls_header =
+                        TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+                            .with_text("Controls")
+                            .build(ctx);
+                    controls_header
                 },
                 content: {
                     let mut children = Vec::new();
 
                     for (row, button) in control_scheme.borrow().buttons().iter().enumerate() {
                         // Offset by total amount of rows that goes before
-                        let row = row + 4;
+                        let row = row + 13;
 
                         let text = TextBuilder::new(
                             WidgetBuilder::new()
@@ -613,10 +775,233 @@ impl OptionsMenu {
                                 );
                                 cb_shake_camera
                             })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(4)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Left Handed")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_left_handed = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    4,
+                                    1,
+                                    control_scheme.borrow().left_handed,
+                                );
+                                cb_left_handed
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(5)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Weapon Distance")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                sb_viewmodel_distance = create_scroll_bar(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    ScrollBarData {
+                                        min: -0.03,
+                                        max: 0.03,
+                                        value: control_scheme.borrow().viewmodel_distance,
+                                        step: 0.005,
+                                        row: 5,
+                                        column: 1,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_viewmodel_distance
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(6)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Hide Weapon")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_hide_viewmodel = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    6,
+                                    1,
+                                    control_scheme.borrow().hide_viewmodel,
+                                );
+                                cb_hide_viewmodel
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(7)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Hit Stagger")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_hit_stagger = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    7,
+                                    1,
+                                    control_scheme.borrow().hit_stagger,
+                                );
+                                cb_hit_stagger
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(8)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Aim Assist")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_aim_assist = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    8,
+                                    1,
+                                    control_scheme.borrow().aim_assist,
+                                );
+                                cb_aim_assist
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(9)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Aim Assist Pull")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                sb_aim_assist_magnetism = create_scroll_bar(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    ScrollBarData {
+                                        min: 0.0,
+                                        max: 1.0,
+                                        value: control_scheme.borrow().aim_assist_magnetism,
+                                        step: 0.1,
+                                        row: 9,
+                                        column: 1,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_aim_assist_magnetism
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(10)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Aim Assist Slowdown")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                sb_aim_assist_slowdown = create_scroll_bar(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    ScrollBarData {
+                                        min: 0.0,
+                                        max: 1.0,
+                                        value: control_scheme.borrow().aim_assist_slowdown,
+                                        step: 0.1,
+                                        row: 10,
+                                        column: 1,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_aim_assist_slowdown
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(11)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Auto-Aim (Accessibility)")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_auto_aim = create_check_box(
+                                    ctx,
+                                    resource_manager.clone(),
+                                    11,
+                                    1,
+                                    control_scheme.borrow().auto_aim,
+                                );
+                                cb_auto_aim
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(12)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text("Preset")
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                let names = ControlPreset::ALL
+                                    .iter()
+                                    .map(|preset| preset.name())
+                                    .collect::<Vec<_>>();
+                                let mut builder = DropdownListBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(12)
+                                        .on_column(1)
+                                        .with_margin(margin),
+                                )
+                                .with_items(create_dropdown_items(ctx, &names));
+                                // Customized buttons match no preset, nothing is selected then.
+                                if let Some(index) = control_preset_index(&control_scheme.borrow())
+                                {
+                                    builder = builder.with_selected(index);
+                                }
+                                dd_control_preset = builder.build(ctx);
+                                dd_control_preset
+                            })
                             .with_child({
                                 btn_reset_control_scheme = ButtonBuilder::new(
                                     WidgetBuilder::new()
-                                        .on_row(4 + control_scheme.borrow().buttons().len())
+                                        .on_row(13 + control_scheme.borrow().buttons().len())
                                         .with_margin(margin),
                                 )
                                 .with_text("Reset")
@@ -631,6 +1016,15 @@ impl OptionsMenu {
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
                     .add_rows(
                         (0..control_scheme.borrow().buttons().len())
                             .map(|_| common_row)
@@ -640,6 +1034,65 @@ impl OptionsMenu {
                     .build(ctx)
                 },
             })
+            .with_tab(TabDefinition {
+                header: {
+                    mods_header =
+                        TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+                            .with_text("Mods")
+                            .build(ctx);
+                    mods_header
+                },
+                content: {
+                    let mut children = Vec::new();
+
+                    let names = mods::available();
+                    for (row, name) in names.iter().enumerate() {
+                        let text = TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(row)
+                                .on_column(0)
+                                .with_margin(margin),
+                        )
+                        .with_text(name.as_str())
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx);
+                        children.push(text);
+
+                        let check_box = create_check_box(
+                            ctx,
+                            resource_manager.clone(),
+                            row,
+                            1,
+                            mods::is_enabled(name),
+                        );
+                        children.push(check_box);
+                        mod_check_boxes.push((check_box, name.clone()));
+                    }
+
+                    let hint = if names.is_empty() {
+                        format!("No mods found in {} directory", mods::MODS_DIR)
+                    } else {
+                        "Changes will take effect in next match".to_owned()
+                    };
+                    children.push(
+                        TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(names.len())
+                                .on_column(0)
+                                .with_margin(margin),
+                        )
+                        .with_text(hint)
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx),
+                    );
+
+                    GridBuilder::new(WidgetBuilder::new().with_children(&children))
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .add_rows((0..=names.len()).map(|_| common_row).collect())
+                        .build(ctx)
+                },
+            })
             .build(ctx);
 
         let options_window: UINodeHandle =
@@ -649,6 +1102,49 @@ impl OptionsMenu {
                 .with_content(tab_control)
                 .build(ctx);
 
+        // Headers are wrapped into buttons by tab control, focus should be on these buttons.
+        let ui = &engine.user_interface;
+        let mut focus_chain = vec![navigation::enclosing_button(ui, graphics_header)];
+        focus_chain.extend_from_slice(&[
+            cb_fullscreen,
+            cb_spot_shadows,
+            cb_soft_spot_shadows,
+            sb_spot_shadow_distance,
+            cb_point_shadows,
+            cb_soft_point_shadows,
+            sb_point_shadow_distance,
+            cb_use_light_scatter,
+            cb_dynamic_quality,
+            cb_weather,
+            cb_reduce_flashes,
+            btn_reset_graphics_settings,
+            navigation::enclosing_button(ui, sound_header),
+            sb_sound_volume,
+            sb_music_volume,
+            dd_speaker_mode,
+            btn_test_speakers,
+            btn_reset_audio_settings,
+            navigation::enclosing_button(ui, controls_header),
+            sb_mouse_sens,
+            cb_mouse_y_inverse,
+            cb_smooth_mouse,
+            cb_shake_camera,
+            cb_left_handed,
+            sb_viewmodel_distance,
+            cb_hide_viewmodel,
+            cb_hit_stagger,
+            cb_aim_assist,
+            sb_aim_assist_magnetism,
+            sb_aim_assist_slowdown,
+            cb_auto_aim,
+            dd_control_preset,
+        ]);
+        focus_chain.extend_from_slice(&control_scheme_buttons);
+        focus_chain.push(btn_reset_control_scheme);
+        focus_chain.push(navigation::enclosing_button(ui, mods_header));
+        focus_chain.extend(mod_check_boxes.iter().map(|(check_box, _)| *check_box));
+        focus_chain.retain(|handle| handle.is_some());
+
         Self {
             sender,
             window: options_window,
@@ -664,16 +1160,47 @@ impl OptionsMenu {
             sb_spot_shadow_distance,
             video_modes,
             control_scheme,
+            quality,
             control_scheme_buttons,
             active_control_button: None,
             sb_mouse_sens,
             cb_mouse_y_inverse,
             cb_smooth_mouse,
             cb_shake_camera,
+            cb_left_handed,
+            sb_viewmodel_distance,
+            cb_hide_viewmodel,
+            cb_hit_stagger,
+            cb_aim_assist,
+            sb_aim_assist_magnetism,
+            sb_aim_assist_slowdown,
+            cb_auto_aim,
+            dd_control_preset,
             btn_reset_control_scheme,
-            cb_use_hrtf,
+            dd_speaker_mode,
+            speaker_mode,
+            btn_test_speakers,
             btn_reset_audio_settings,
+            btn_reset_graphics_settings,
             cb_use_light_scatter,
+            cb_dynamic_quality,
+            cb_weather,
+            cb_reduce_flashes,
+            mod_check_boxes,
+            focus_chain: FocusChain::new(focus_chain),
+        }
+    }
+
+    /// Returns true if control scheme button waits for a key to be pressed, every key must
+    /// go to the button then.
+    pub fn is_waiting_input(&self) -> bool {
+        self.active_control_button.is_some()
+    }
+
+    fn set_speaker_mode(&mut self, mode: SpeakerMode) {
+        if mode != self.speaker_mode {
+            self.speaker_mode = mode;
+            self.sender.send(Message::SetSpeakerMode { mode }).unwrap();
         }
     }
 
@@ -694,17 +1221,39 @@ impl OptionsMenu {
         sync_check_box(self.cb_point_shadows, settings.point_shadows_enabled);
         sync_check_box(self.cb_soft_point_shadows, settings.point_soft_shadows);
         sync_check_box(self.cb_use_light_scatter, settings.light_scatter_enabled);
+        sync_check_box(
+            self.cb_dynamic_quality,
+            self.quality.borrow().dynamic_quality,
+        );
+        sync_check_box(self.cb_weather, self.quality.borrow().weather);
+        sync_check_box(self.cb_reduce_flashes, self.quality.borrow().reduce_flashes);
         sync_check_box(self.cb_mouse_y_inverse, control_scheme.mouse_y_inverse);
         sync_check_box(self.cb_smooth_mouse, control_scheme.smooth_mouse);
         sync_check_box(self.cb_shake_camera, control_scheme.shake_camera);
-        let is_hrtf = if let rg3d::sound::renderer::Renderer::HrtfRenderer(_) =
-            engine.sound_context.lock().unwrap().renderer()
-        {
-            true
-        } else {
-            false
-        };
-        sync_check_box(self.cb_use_hrtf, is_hrtf);
+        sync_check_box(self.cb_left_handed, control_scheme.left_handed);
+        sync_check_box(self.cb_hide_viewmodel, control_scheme.hide_viewmodel);
+        sync_check_box(self.cb_hit_stagger, control_scheme.hit_stagger);
+        sync_check_box(self.cb_aim_assist, control_scheme.aim_assist);
+        sync_check_box(self.cb_auto_aim, control_scheme.auto_aim);
+        for (check_box, name) in self.mod_check_boxes.iter() {
+            sync_check_box(*check_box, mods::is_enabled(name));
+        }
+
+        let speaker_mode = SPEAKER_MODES
+            .iter()
+            .position(|(_, mode)| *mode == self.speaker_mode)
+            .unwrap_or(0);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_speaker_mode,
+            MessageDirection::ToWidget,
+            Some(speaker_mode),
+        ));
+
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_control_preset,
+            MessageDirection::ToWidget,
+            control_preset_index(&control_scheme),
+        ));
 
         let sync_scroll_bar = |handle: UINodeHandle, value: f32| {
             ui.send_message(ScrollBarMessage::value(
@@ -719,6 +1268,18 @@ impl OptionsMenu {
         );
         sync_scroll_bar(self.sb_spot_shadow_distance, settings.spot_shadows_distance);
         sync_scroll_bar(self.sb_mouse_sens, control_scheme.mouse_sens);
+        sync_scroll_bar(
+            self.sb_viewmodel_distance,
+            control_scheme.viewmodel_distance,
+        );
+        sync_scroll_bar(
+            self.sb_aim_assist_magnetism,
+            control_scheme.aim_assist_magnetism,
+        );
+        sync_scroll_bar(
+            self.sb_aim_assist_slowdown,
+            control_scheme.aim_assist_slowdown,
+        );
         sync_scroll_bar(
             self.sb_sound_volume,
             engine.sound_context.lock().unwrap().master_gain(),
@@ -756,8 +1317,12 @@ impl OptionsMenu {
                     }
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(code) = input.virtual_keycode {
-                        control_button = Some(ControlButton::Key(code));
+                    // Ignore releases, otherwise release of Enter that activated the button
+                    // from keyboard would be bound.
+                    if let ElementState::Pressed = input.state {
+                        if let Some(code) = input.virtual_keycode {
+                            control_button = Some(ControlButton::Key(code));
+                        }
                     }
                 }
                 WindowEvent::MouseInput { button, .. } => {
@@ -790,6 +1355,16 @@ impl OptionsMenu {
                         control_button;
 
                     self.active_control_button = None;
+
+                    // Rebound button may turn layout into custom one or into some preset.
+                    let control_preset = control_preset_index(&self.control_scheme.borrow());
+                    engine
+                        .user_interface
+                        .send_message(DropdownListMessage::selection(
+                            self.dd_control_preset,
+                            MessageDirection::ToWidget,
+                            control_preset,
+                        ));
                 }
             }
         }
@@ -818,6 +1393,12 @@ impl OptionsMenu {
                         settings.spot_shadows_distance = *new_value;
                     } else if message.destination() == self.sb_mouse_sens {
                         self.control_scheme.borrow_mut().mouse_sens = *new_value;
+                    } else if message.destination() == self.sb_viewmodel_distance {
+                        self.control_scheme.borrow_mut().viewmodel_distance = *new_value;
+                    } else if message.destination() == self.sb_aim_assist_magnetism {
+                        self.control_scheme.borrow_mut().aim_assist_magnetism = *new_value;
+                    } else if message.destination() == self.sb_aim_assist_slowdown {
+                        self.control_scheme.borrow_mut().aim_assist_slowdown = *new_value;
                     } else if message.destination() == self.sb_music_volume {
                         self.sender
                             .send(Message::SetMusicVolume { volume: *new_value })
@@ -838,6 +1419,26 @@ impl OptionsMenu {
                     }
                 }
             }
+            UiMessageData::DropdownList(DropdownListMessage::SelectionChanged(Some(index)))
+                if message.destination() == self.dd_speaker_mode
+                    && message.direction() == MessageDirection::FromWidget =>
+            {
+                if let Some((_, mode)) = SPEAKER_MODES.get(*index) {
+                    self.set_speaker_mode(*mode);
+                }
+            }
+            UiMessageData::DropdownList(DropdownListMessage::SelectionChanged(Some(index)))
+                if message.destination() == self.dd_control_preset
+                    && message.direction() == MessageDirection::FromWidget =>
+            {
+                if let Some(preset) = ControlPreset::ALL.get(*index) {
+                    // Selection sent back by sync_to_model matches current buttons already.
+                    if self.control_scheme.borrow().preset() != Some(*preset) {
+                        self.control_scheme.borrow_mut().apply_preset(*preset);
+                        self.sync_to_model(engine);
+                    }
+                }
+            }
             UiMessageData::CheckBox(msg) => {
                 println!("checkbox");
                 let CheckBoxMessage::Check(value) = msg;
@@ -857,15 +1458,30 @@ impl OptionsMenu {
                     control_scheme.smooth_mouse = value;
                 } else if message.destination() == self.cb_shake_camera {
                     control_scheme.shake_camera = value;
+                } else if message.destination() == self.cb_left_handed {
+                    control_scheme.left_handed = value;
+                } else if message.destination() == self.cb_hide_viewmodel {
+                    control_scheme.hide_viewmodel = value;
+                } else if message.destination() == self.cb_hit_stagger {
+                    control_scheme.hit_stagger = value;
+                } else if message.destination() == self.cb_aim_assist {
+                    control_scheme.aim_assist = value;
+                } else if message.destination() == self.cb_auto_aim {
+                    control_scheme.auto_aim = value;
                 } else if message.destination() == self.cb_use_light_scatter {
                     settings.light_scatter_enabled = value;
-                } else if message.destination() == self.cb_use_hrtf {
-                    let mut sound_context = engine.sound_context.lock().unwrap();
-                    if value {
-                        SoundSettings::hrtf_on(&mut sound_context);
-                    } else {
-                        SoundSettings::hrtf_off(&mut sound_context);
-                    }
+                } else if message.destination() == self.cb_dynamic_quality {
+                    self.quality.borrow_mut().dynamic_quality = value;
+                } else if message.destination() == self.cb_weather {
+                    self.quality.borrow_mut().weather = value;
+                } else if message.destination() == self.cb_reduce_flashes {
+                    self.quality.borrow_mut().reduce_flashes = value;
+                } else if let Some((_, name)) = self
+                    .mod_check_boxes
+                    .iter()
+                    .find(|(check_box, _)| *check_box == message.destination())
+                {
+                    mods::set_enabled(name, value);
                 }
             }
             UiMessageData::Button(msg) => {
@@ -875,7 +1491,24 @@ impl OptionsMenu {
                         self.control_scheme.borrow_mut().reset();
                         self.sync_to_model(engine);
                     } else if message.destination() == self.btn_reset_audio_settings {
-                        engine.sound_context.lock().unwrap().set_master_gain(1.0);
+                        let defaults = SoundSettings::default();
+                        engine
+                            .sound_context
+                            .lock()
+                            .unwrap()
+                            .set_master_gain(defaults.sound_volume);
+                        self.set_speaker_mode(defaults.speaker_mode);
+                        self.sync_to_model(engine);
+                    } else if message.destination() == self.btn_test_speakers {
+                        self.sender.send(Message::TestSpeakers).unwrap();
+                    } else if message.destination() == self.btn_reset_graphics_settings {
+                        if let Err(err) = engine
+                            .renderer
+                            .set_quality_settings(&QualitySettings::default())
+                        {
+                            println!("Failed to set renderer quality settings! Reason: {:?}", err);
+                        }
+                        *self.quality.borrow_mut() = Default::default();
                         self.sync_to_model(engine);
                     }
 
@@ -0,0 +1,248 @@
+//! Shared path finding for bots. Navmesh is static, so paths between navmesh vertices are
+//! cached for whole match, and amount of actual path builds per frame is limited so a crowd
+//! of bots that decided to repath at once won't cause a spike.
+//!
+//! Navmesh of a map can have disconnected parts that are reachable only by jump pads,
+//! teleporters or by dropping down from ledges. These are described by off-mesh links and
+//! planner routes bots through one link when it is shorter than walking or when there is
+//! no walkable path at all.
+//!
+//! Parts of navmesh inside crouch zones (meshes tagged `crouchzone`) are crouch-only, bots
+//! crouch while passing them. Crouching is slow, so such parts cost more when planner picks
+//! between walking and going through a link.
+
+use rg3d::{
+    core::math::{aabb::AxisAlignedBoundingBox, vec3::Vec3},
+    utils::navmesh::Navmesh,
+};
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OffMeshLinkKind {
+    JumpPad,
+    Teleporter,
+    DropDown,
+}
+
+/// One-way connection between two points of navmesh that can't be walked.
+pub struct OffMeshLink {
+    pub kind: OffMeshLinkKind,
+    pub begin: Vec3,
+    pub end: Vec3,
+    pub begin_vertex: usize,
+    pub end_vertex: usize,
+}
+
+impl OffMeshLink {
+    /// Bot must jump when it reaches beginning of such link.
+    pub fn needs_jump(&self) -> bool {
+        match self.kind {
+            OffMeshLinkKind::JumpPad | OffMeshLinkKind::DropDown => true,
+            OffMeshLinkKind::Teleporter => false,
+        }
+    }
+}
+
+/// Path that was built once, it is copied to bots on cache hit.
+struct CachedPath {
+    points: Vec<Vec3>,
+    jump_points: Vec<usize>,
+}
+
+/// Max amount of path builds (cache misses) per frame across all bots.
+const MAX_PATH_BUILDS_PER_FRAME: usize = 2;
+/// Cache is cleared when it grows above this amount of paths, there are not so many
+/// distinct goals on a level so it is very rare.
+const MAX_CACHED_PATHS: usize = 512;
+/// Cost of a unit of crouch-only path relative to walkable one, bots crouch at 0.4 of walk
+/// speed.
+const CROUCH_COST: f32 = 2.5;
+
+pub enum PathQuery {
+    Found,
+    /// Path building budget of current frame is spent, query should be repeated later.
+    Postponed,
+    NotFound,
+}
+
+pub struct PathPlanner {
+    /// Paths from first vertex to second, already in order of traversal.
+    cache: HashMap<(usize, usize), CachedPath>,
+    /// Walkable paths between vertices, parts of paths through links. `None` means there
+    /// is no walkable path.
+    legs: HashMap<(usize, usize), Option<Vec<Vec3>>>,
+    links: Vec<OffMeshLink>,
+    crouch_zones: Vec<AxisAlignedBoundingBox>,
+    builds_left: usize,
+}
+
+impl Default for PathPlanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathPlanner {
+    pub fn new() -> Self {
+        Self {
+            cache: Default::default(),
+            legs: Default::default(),
+            links: Default::default(),
+            crouch_zones: Default::default(),
+            builds_left: MAX_PATH_BUILDS_PER_FRAME,
+        }
+    }
+
+    /// Must be called once per frame before any query.
+    pub fn begin_frame(&mut self) {
+        self.builds_left = MAX_PATH_BUILDS_PER_FRAME;
+    }
+
+    /// Replaces off-mesh links, cached paths are dropped because they could be built
+    /// without these links.
+    pub fn set_links(&mut self, links: Vec<OffMeshLink>) {
+        self.links = links;
+        self.cache.clear();
+        self.legs.clear();
+    }
+
+    pub fn links(&self) -> &[OffMeshLink] {
+        &self.links
+    }
+
+    /// Replaces crouch zones, cached paths are dropped because their costs have changed.
+    pub fn set_crouch_zones(&mut self, zones: Vec<AxisAlignedBoundingBox>) {
+        self.crouch_zones = zones;
+        self.cache.clear();
+    }
+
+    /// Returns true if point is inside of a crouch zone or closer than `margin` to it.
+    pub fn is_crouch_only(&self, point: Vec3, margin: f32) -> bool {
+        self.crouch_zones.iter().any(|zone| {
+            point.x >= zone.min.x - margin
+                && point.x <= zone.max.x + margin
+                && point.y >= zone.min.y - margin
+                && point.y <= zone.max.y + margin
+                && point.z >= zone.min.z - margin
+                && point.z <= zone.max.z + margin
+        })
+    }
+
+    /// Length of path where crouch-only parts are weighted by `CROUCH_COST`.
+    fn path_cost(&self, points: &[Vec3]) -> f32 {
+        points
+            .windows(2)
+            .map(|pair| {
+                let length = pair[0].distance(&pair[1]);
+                let middle = (pair[0] + pair[1]).scale(0.5);
+                if self.is_crouch_only(middle, 0.0) {
+                    length * CROUCH_COST
+                } else {
+                    length
+                }
+            })
+            .sum()
+    }
+
+    /// Builds walkable path between given vertices, partial paths that don't reach
+    /// destination are treated as absent.
+    fn leg(&mut self, navmesh: &mut Navmesh, from: usize, to: usize) -> Option<Vec<Vec3>> {
+        if let Some(leg) = self.legs.get(&(from, to)) {
+            return leg.clone();
+        }
+
+        let mut built = Vec::new();
+        let leg = if navmesh.build_path(from, to, &mut built).is_ok() {
+            // Navmesh gives path from end to start.
+            built.reverse();
+            let destination = navmesh.vertices()[to].position();
+            match built.last() {
+                Some(last) if last.distance(&destination) < 0.01 => Some(built),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if self.legs.len() >= MAX_CACHED_PATHS {
+            self.legs.clear();
+        }
+        self.legs.insert((from, to), leg.clone());
+        leg
+    }
+
+    /// Writes path between given navmesh vertices into `path` and indices of points at which
+    /// bot must jump into `jump_points`. Both are left untouched if path wasn't found or was
+    /// postponed.
+    pub fn find_path(
+        &mut self,
+        navmesh: &mut Navmesh,
+        from: usize,
+        to: usize,
+        path: &mut Vec<Vec3>,
+        jump_points: &mut Vec<usize>,
+    ) -> PathQuery {
+        if let Some(cached) = self.cache.get(&(from, to)) {
+            path.clear();
+            path.extend_from_slice(&cached.points);
+            jump_points.clear();
+            jump_points.extend_from_slice(&cached.jump_points);
+            return PathQuery::Found;
+        }
+
+        if self.builds_left == 0 {
+            return PathQuery::Postponed;
+        }
+        self.builds_left -= 1;
+
+        let mut best = self.leg(navmesh, from, to).map(|points| CachedPath {
+            points,
+            jump_points: Vec::new(),
+        });
+
+        for i in 0..self.links.len() {
+            let (begin_vertex, end_vertex) = (self.links[i].begin_vertex, self.links[i].end_vertex);
+            if let (Some(before), Some(after)) = (
+                self.leg(navmesh, from, begin_vertex),
+                self.leg(navmesh, end_vertex, to),
+            ) {
+                let link = &self.links[i];
+                let mut points = before;
+                points.push(link.begin);
+                let jump_points = if link.needs_jump() {
+                    vec![points.len() - 1]
+                } else {
+                    Vec::new()
+                };
+                points.push(link.end);
+                points.extend_from_slice(&after);
+
+                if best.as_ref().map_or(true, |best| {
+                    self.path_cost(&points) < self.path_cost(&best.points)
+                }) {
+                    best = Some(CachedPath {
+                        points,
+                        jump_points,
+                    });
+                }
+            }
+        }
+
+        match best {
+            Some(best) => {
+                path.clear();
+                path.extend_from_slice(&best.points);
+                jump_points.clear();
+                jump_points.extend_from_slice(&best.jump_points);
+
+                if self.cache.len() >= MAX_CACHED_PATHS {
+                    self.cache.clear();
+                }
+                self.cache.insert((from, to), best);
+
+                PathQuery::Found
+            }
+            None => PathQuery::NotFound,
+        }
+    }
+}
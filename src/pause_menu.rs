@@ -0,0 +1,183 @@
+use crate::{
+    bot, character::Team, confirm_dialog::ConfirmDialog, message::Message, navigation::FocusChain,
+    BuildContext, GameEngine, Gui, GuiMessage, UINodeHandle,
+};
+use rg3d::gui::{
+    button::ButtonBuilder,
+    grid::{Column, GridBuilder, Row},
+    message::{ButtonMessage, MessageDirection, UiMessageData, WidgetMessage, WindowMessage},
+    ttf::SharedFont,
+    widget::WidgetBuilder,
+    window::{WindowBuilder, WindowTitle},
+    Thickness,
+};
+use std::{path::PathBuf, sync::mpsc::Sender};
+
+/// Menu that is shown instead of main menu when there is a match in progress.
+pub struct PauseMenu {
+    sender: Sender<Message>,
+    pub root: UINodeHandle,
+    btn_resume: UINodeHandle,
+    btn_save_game: UINodeHandle,
+    btn_settings: UINodeHandle,
+    btn_add_bot: UINodeHandle,
+    btn_kick_bot: UINodeHandle,
+    btn_end_match: UINodeHandle,
+    btn_quit_game: UINodeHandle,
+    save_file: PathBuf,
+    /// Index of kind of next added bot in bot roster.
+    next_bot: usize,
+    pub focus_chain: FocusChain,
+}
+
+impl PauseMenu {
+    pub fn new(
+        ctx: &mut BuildContext,
+        font: SharedFont,
+        frame_size: (u32, u32),
+        save_file: PathBuf,
+        sender: Sender<Message>,
+    ) -> Self {
+        let mut make_button = |row: usize, text: &str| {
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(0)
+                    .on_row(row)
+                    .with_margin(Thickness::uniform(4.0)),
+            )
+            .with_text(text)
+            .with_font(font.clone())
+            .build(ctx)
+        };
+
+        let btn_resume = make_button(0, "Resume");
+        let btn_save_game = make_button(1, "Save Game");
+        let btn_settings = make_button(2, "Settings");
+        let btn_add_bot = make_button(3, "Add Bot");
+        let btn_kick_bot = make_button(4, "Kick Bot");
+        let btn_end_match = make_button(5, "End Match");
+        let btn_quit_game = make_button(6, "Quit");
+
+        let content = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(20.0))
+                .with_child(btn_resume)
+                .with_child(btn_save_game)
+                .with_child(btn_settings)
+                .with_child(btn_add_bot)
+                .with_child(btn_kick_bot)
+                .with_child(btn_end_match)
+                .with_child(btn_quit_game),
+        )
+        .add_column(Column::stretch())
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .add_row(Row::strict(75.0))
+        .build(ctx);
+
+        let root = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_child(
+                    WindowBuilder::new(WidgetBuilder::new().on_row(1).on_column(1))
+                        .can_resize(false)
+                        .can_minimize(false)
+                        .can_close(false)
+                        .with_title(WindowTitle::text("Paused"))
+                        .with_content(content)
+                        .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::strict(650.0))
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .add_column(Column::strict(400.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        Self {
+            sender,
+            root,
+            btn_resume,
+            btn_save_game,
+            btn_settings,
+            btn_add_bot,
+            btn_kick_bot,
+            btn_end_match,
+            btn_quit_game,
+            save_file,
+            next_bot: 0,
+            focus_chain: FocusChain::new(vec![
+                btn_resume,
+                btn_save_game,
+                btn_settings,
+                btn_add_bot,
+                btn_kick_bot,
+                btn_end_match,
+                btn_quit_game,
+            ]),
+        }
+    }
+
+    pub fn set_visible(&self, ui: &mut Gui, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    pub fn handle_ui_event(
+        &mut self,
+        engine: &mut GameEngine,
+        message: &GuiMessage,
+        options_window: UINodeHandle,
+        confirm_dialog: &mut ConfirmDialog,
+    ) {
+        let ui = &mut engine.user_interface;
+        if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_resume {
+                self.sender.send(Message::ResumeGame).unwrap();
+            } else if message.destination() == self.btn_save_game {
+                confirm_dialog.ask_if(
+                    ui,
+                    self.save_file.exists(),
+                    "Overwrite save?",
+                    Message::SaveGame,
+                );
+            } else if message.destination() == self.btn_settings {
+                ui.send_message(WindowMessage::open(
+                    options_window,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            } else if message.destination() == self.btn_add_bot {
+                let kind = bot::ROSTER[self.next_bot % bot::ROSTER.len()];
+                self.next_bot += 1;
+                self.sender
+                    .send(Message::AddBot {
+                        kind,
+                        team: Team::None,
+                    })
+                    .unwrap();
+            } else if message.destination() == self.btn_kick_bot {
+                self.sender.send(Message::RemoveBot).unwrap();
+            } else if message.destination() == self.btn_end_match {
+                confirm_dialog.ask(
+                    ui,
+                    "End current match?",
+                    Message::EndMatch { summary: None },
+                );
+            } else if message.destination() == self.btn_quit_game {
+                confirm_dialog.ask(ui, "Quit without saving?", Message::QuitGame);
+            }
+        }
+    }
+}
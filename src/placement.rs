@@ -0,0 +1,94 @@
+//! Entities placed on a map in addition to the ones in the map model - items, bots, jump pads
+//! and spawn points. They are stored next to the map model in a file with `entities.json`
+//! extension (`dm6.entities.json` for `dm6.fbx`), usually exported from debug editor (see
+//! `editor` module), mods can override it:
+//!
+//! ```json
+//! {
+//!     "entities": [
+//!         { "kind": { "Item": "Medkit" }, "position": [2.0, 0.5, -4.0] },
+//!         { "kind": { "Bot": "Mutant" }, "position": [10.0, 0.0, 3.0] },
+//!         { "kind": "JumpPad", "position": [0.0, 0.0, 8.0] },
+//!         { "kind": "SpawnPoint", "position": [-6.0, 0.5, 1.0] }
+//!     ]
+//! }
+//! ```
+//!
+//! Level adds the entities when match starts, placed bots are spawned in addition to bots of
+//! match settings.
+
+use crate::{bot::BotKind, item::ItemKind, mods};
+use rg3d::core::math::vec3::Vec3;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum EntityKind {
+    Item(ItemKind),
+    Bot(BotKind),
+    /// Pad that launches actors straight up.
+    JumpPad,
+    SpawnPoint,
+}
+
+impl EntityKind {
+    pub fn name(self) -> String {
+        match self {
+            EntityKind::Item(kind) => format!("Item: {}", kind.name()),
+            EntityKind::Bot(kind) => format!("Bot: {:?}", kind),
+            EntityKind::JumpPad => "Jump Pad".to_owned(),
+            EntityKind::SpawnPoint => "Spawn Point".to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Placement {
+    pub kind: EntityKind,
+    position: [f32; 3],
+}
+
+impl Placement {
+    pub fn new(kind: EntityKind, position: Vec3) -> Self {
+        Self {
+            kind,
+            position: [position.x, position.y, position.z],
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        Vec3::new(self.position[0], self.position[1], self.position[2])
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct PlacementFile {
+    pub entities: Vec<Placement>,
+}
+
+impl PlacementFile {
+    /// Returns path of placement file of a map.
+    pub fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("entities.json")
+    }
+
+    /// Loads placement file of a map, map without placement file has no placed entities.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read placements {}: {}", path.display(), e))?;
+        serde_json::from_str::<PlacementFile>(&data)
+            .map_err(|e| format!("Placements {} are corrupted: {}", path.display(), e))
+    }
+
+    /// Writes placement file, file of a mod is overwritten if the mod overrides it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = mods::resolve(path);
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Unable to write placements {}: {}", path.display(), e))
+    }
+}
@@ -1,14 +1,18 @@
 use crate::{
+    actor::{Actor, TargetDescriptor},
+    aim_assist::{self, AimAssist},
     assets,
-    character::Character,
+    character::{self, Character},
     control_scheme::{ControlButton, ControlScheme},
     level::UpdateContext,
     message::Message,
-    FIXED_FPS,
+    sound_variation::SoundCategory,
+    weapon::{Weapon, WeaponContainer, WeaponKind},
 };
 use rand::Rng;
 use rg3d::{
     core::{
+        color::Color,
         math::{mat3::Mat3, quat::Quat, vec3::Vec3},
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
@@ -18,7 +22,15 @@ use rg3d::{
         convex_shape::{Axis, CapsuleShape, ConvexShape},
         rigid_body::RigidBody,
     },
-    scene::{base::BaseBuilder, camera::CameraBuilder, node::Node, Scene},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{BaseLightBuilder, SpotLightBuilder},
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
     sound::context::Context,
 };
 use std::ops::{Deref, DerefMut};
@@ -28,6 +40,217 @@ use std::{
     sync::{mpsc::Sender, Arc, Mutex},
 };
 
+/// Vertical field of view of camera in degrees.
+const FOV: f32 = 75.0;
+/// Vertical speed of player right after jump in meters per second.
+const JUMP_SPEED: f32 = 4.2;
+/// Time in seconds for which full battery of flashlight lasts.
+const BATTERY_LIFETIME: f32 = 120.0;
+/// Battery recharges this times slower than it drains.
+const BATTERY_RECHARGE_SLOWDOWN: f32 = 3.0;
+/// Flashlight starts to flicker when battery charge goes below this level.
+const LOW_BATTERY: f32 = 0.2;
+/// Sway of weapon in degrees when player stands still with full stamina.
+const IDLE_SWAY: f32 = 0.15;
+/// Extra sway of weapon in degrees when player walks, it is multiplied by speed.
+const MOVE_SWAY: f32 = 0.5;
+/// Extra sway of weapon in degrees when stamina is empty.
+const TIRED_SWAY: f32 = 0.8;
+/// Multiplier of sway while player holds breath.
+const HELD_BREATH_SWAY: f32 = 0.1;
+/// Time in seconds between two melee attacks.
+const MELEE_INTERVAL: f32 = 0.6;
+/// Time in seconds for which full stamina lasts while player holds breath.
+const BREATH_HOLD_TIME: f32 = 4.0;
+/// Time in seconds for which full stamina lasts while player runs.
+const RUN_STAMINA_TIME: f32 = 8.0;
+/// Time in seconds in which empty stamina is fully restored.
+const STAMINA_RECOVERY_TIME: f32 = 5.0;
+/// Player can't hold breath again after running out of stamina until it recovers to this level.
+const BREATH_RECOVERY: f32 = 0.3;
+/// Single hit that takes this amount of health or more staggers player.
+const HEAVY_HIT: f32 = 25.0;
+/// Time in seconds during which staggered player moves slower.
+const STAGGER_TIME: f32 = 0.4;
+/// Multiplier of speed of staggered player.
+const STAGGER_SPEED_FACTOR: f32 = 0.5;
+/// View punch in degrees per point of damage of heavy hit.
+const VIEW_PUNCH_PER_DAMAGE: f32 = 0.1;
+const MAX_VIEW_PUNCH: f32 = 6.0;
+/// Speed of recovery of view from punch, view punch goes down this times per second.
+const VIEW_PUNCH_RECOVERY: f32 = 8.0;
+/// Name of player in leader board.
+pub const PLAYER_NAME: &str = "Player";
+
+/// Spot light attached to camera of player. When battery is enabled flashlight drains it
+/// while on, flickers when it is low and turns off when it is empty, battery recharges
+/// while flashlight is off.
+struct Flashlight {
+    light: Handle<Node>,
+    enabled: bool,
+    battery_enabled: bool,
+    /// Charge of battery in [0; 1] range.
+    battery: f32,
+}
+
+impl Default for Flashlight {
+    fn default() -> Self {
+        Self {
+            light: Handle::NONE,
+            enabled: false,
+            battery_enabled: false,
+            battery: 1.0,
+        }
+    }
+}
+
+impl Visit for Flashlight {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.light.visit("Light", visitor)?;
+        self.enabled.visit("Enabled", visitor)?;
+        self.battery_enabled.visit("BatteryEnabled", visitor)?;
+        self.battery.visit("Battery", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Flashlight {
+    fn new(scene: &mut Scene, camera: Handle<Node>) -> Self {
+        let light = scene.graph.add_node(
+            SpotLightBuilder::new(
+                BaseLightBuilder::new(
+                    // Spot light shines along -Y axis of its local space, rotate it to
+                    // shine where camera looks.
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_rotation(Quat::from_axis_angle(
+                                Vec3::RIGHT,
+                                (-90.0f32).to_radians(),
+                            ))
+                            .build(),
+                    ),
+                )
+                .with_color(Color::opaque(255, 244, 220))
+                .with_scatter_enabled(false),
+            )
+            .with_hotspot_cone_angle(30.0f32.to_radians())
+            .with_falloff_angle_delta(10.0f32.to_radians())
+            .with_distance(25.0)
+            .build_node(),
+        );
+        scene.graph.link_nodes(light, camera);
+        scene.graph[light].set_visibility(false);
+
+        Self {
+            light,
+            ..Default::default()
+        }
+    }
+
+    fn toggle(&mut self) {
+        if self.enabled || !self.battery_enabled || self.battery > 0.0 {
+            self.enabled = !self.enabled;
+        }
+    }
+
+    fn update(&mut self, graph: &mut Graph, dt: f32) {
+        let mut lit = self.enabled;
+
+        if self.battery_enabled {
+            if self.enabled {
+                self.battery = (self.battery - dt / BATTERY_LIFETIME).max(0.0);
+                if self.battery <= 0.0 {
+                    self.enabled = false;
+                    lit = false;
+                } else if self.battery < LOW_BATTERY {
+                    // Flicker more often as battery goes down.
+                    let chance = 0.5 * (1.0 - self.battery / LOW_BATTERY);
+                    lit = rand::thread_rng().gen::<f32>() >= chance;
+                }
+            } else {
+                self.battery =
+                    (self.battery + dt / (BATTERY_LIFETIME * BATTERY_RECHARGE_SLOWDOWN)).min(1.0);
+            }
+        }
+
+        if self.light.is_some() {
+            graph[self.light].set_visibility(lit);
+        }
+    }
+}
+
+/// Idle sway of weapon. Sway grows when player moves and when stamina is low, holding breath
+/// steadies scoped weapon for a few seconds at cost of stamina. Weapon model is rotated by
+/// sway, so it affects direction of shots.
+struct WeaponSway {
+    /// Stamina in [0; 1] range.
+    stamina: f32,
+    /// Set when stamina runs out while holding breath or running.
+    out_of_breath: bool,
+    time: f32,
+    amplitude: f32,
+}
+
+impl Default for WeaponSway {
+    fn default() -> Self {
+        Self {
+            stamina: 1.0,
+            out_of_breath: false,
+            time: 0.0,
+            amplitude: IDLE_SWAY,
+        }
+    }
+}
+
+impl Visit for WeaponSway {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.stamina.visit("Stamina", visitor)?;
+        self.out_of_breath.visit("OutOfBreath", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl WeaponSway {
+    /// Returns yaw and pitch of sway in degrees. `movement` is speed multiplier of player or
+    /// zero if player stands still.
+    fn update(&mut self, dt: f32, movement: f32, running: bool, hold_breath: bool) -> (f32, f32) {
+        let holding = hold_breath && !self.out_of_breath;
+        if holding {
+            self.stamina -= dt / BREATH_HOLD_TIME;
+        } else if running {
+            self.stamina -= dt / RUN_STAMINA_TIME;
+        } else {
+            self.stamina += dt / STAMINA_RECOVERY_TIME;
+        }
+        self.stamina = self.stamina.max(0.0).min(1.0);
+        if self.stamina <= 0.0 {
+            self.out_of_breath = true;
+        } else if self.stamina >= BREATH_RECOVERY {
+            self.out_of_breath = false;
+        }
+
+        let mut amplitude = IDLE_SWAY + MOVE_SWAY * movement + TIRED_SWAY * (1.0 - self.stamina);
+        if holding {
+            amplitude *= HELD_BREATH_SWAY;
+        }
+        // Amplitude changes smoothly, so weapon doesn't jump when breath is held or released.
+        self.amplitude += (amplitude - self.amplitude) * (dt * 5.0).min(1.0);
+
+        // Figure eight, like natural sway of hands.
+        self.time += dt;
+        (
+            self.amplitude * (self.time * 0.9).sin(),
+            self.amplitude * 0.5 * (self.time * 1.8).sin(),
+        )
+    }
+}
+
 pub struct Controller {
     move_forward: bool,
     move_backward: bool,
@@ -38,6 +261,13 @@ pub struct Controller {
     jump: bool,
     run: bool,
     shoot: bool,
+    hold_breath: bool,
+    /// Set when interact button is pressed, level consumes it.
+    interact: bool,
+    /// Set when place mine button is pressed, level consumes it.
+    place_mine: bool,
+    /// Set when melee button is pressed, level consumes it.
+    melee: bool,
 }
 
 impl Default for Controller {
@@ -52,6 +282,10 @@ impl Default for Controller {
             jump: false,
             run: false,
             shoot: false,
+            hold_breath: false,
+            interact: false,
+            place_mine: false,
+            melee: false,
         }
     }
 }
@@ -69,6 +303,8 @@ pub struct Player {
     crouch_speed_multiplier: f32,
     stand_body_height: f32,
     crouch_body_height: f32,
+    /// Walk speed in meters per second. Not saved, older saves have it in meters per physics
+    /// step.
     move_speed: f32,
     camera_offset: Vec3,
     camera_dest_offset: Vec3,
@@ -77,13 +313,29 @@ pub struct Player {
     head_position: Vec3,
     look_direction: Vec3,
     up_direction: Vec3,
+    /// Position of weapon relative to camera, depends on current weapon and view settings.
     weapon_position: Vec3,
     weapon_offset: Vec3,
     weapon_dest_offset: Vec3,
+    /// Speed of change of height of body when player crouches or stands up, in meters per
+    /// second.
     crouch_speed: f32,
     stand_up_speed: f32,
     ads_mouse_sensitivity_multiplier: f32,
     listener_basis: Mat3,
+    flashlight: Flashlight,
+    sway: WeaponSway,
+    /// Speed multiplier of player in last frame, zero if player stood still.
+    movement: f32,
+    last_health: f32,
+    /// Time left until staggered player moves at full speed again.
+    stagger_time: f32,
+    /// Pitch and yaw in degrees by which heavy hit has thrown view, they fade out quickly.
+    view_punch: (f32, f32),
+    /// Time left until next melee attack, not saved.
+    melee_cooldown: f32,
+    /// Not saved, it is rebuilt on the first frame.
+    aim_assist: AimAssist,
     control_scheme: Option<Rc<RefCell<ControlScheme>>>,
 }
 
@@ -111,7 +363,7 @@ impl Default for Player {
             stand_body_height: 0.5,
             dest_pitch: 0.0,
             dest_yaw: 0.0,
-            move_speed: 0.058,
+            move_speed: 3.48,
             run_speed_multiplier: 1.75,
             crouch_speed_multiplier: 0.5,
             crouch_body_height: 0.01,
@@ -127,10 +379,18 @@ impl Default for Player {
             weapon_position: Vec3::new(-0.035, -0.052, 0.02),
             weapon_offset: Default::default(),
             weapon_dest_offset: Default::default(),
-            crouch_speed: 0.1,
-            stand_up_speed: 0.1,
+            crouch_speed: 6.0,
+            stand_up_speed: 6.0,
             ads_mouse_sensitivity_multiplier: 0.5,
             listener_basis: Default::default(),
+            flashlight: Default::default(),
+            sway: Default::default(),
+            movement: 0.0,
+            last_health: 0.0,
+            stagger_time: 0.0,
+            view_punch: (0.0, 0.0),
+            melee_cooldown: 0.0,
+            aim_assist: Default::default(),
             control_scheme: None,
         }
     }
@@ -151,9 +411,10 @@ impl Visit for Player {
             .visit("RunSpeedMultiplier", visitor)?;
         self.stand_body_height.visit("StandBodyRadius", visitor)?;
         self.crouch_body_height.visit("CrouchBodyRadius", visitor)?;
-        self.move_speed.visit("MoveSpeed", visitor)?;
         self.camera_offset.visit("CameraOffset", visitor)?;
         self.camera_dest_offset.visit("CameraDestOffset", visitor)?;
+        self.flashlight.visit("Flashlight", visitor)?;
+        self.sway.visit("Sway", visitor)?;
 
         visitor.leave_region()
     }
@@ -161,9 +422,11 @@ impl Visit for Player {
 
 impl Player {
     pub fn new(scene: &mut Scene, sender: Sender<Message>) -> Player {
-        let camera_handle = scene
-            .graph
-            .add_node(Node::Camera(CameraBuilder::new(BaseBuilder::new()).build()));
+        let camera_handle = scene.graph.add_node(Node::Camera(
+            CameraBuilder::new(BaseBuilder::new())
+                .with_fov(FOV.to_radians())
+                .build(),
+        ));
 
         let height = Self::default().stand_body_height;
         let mut camera_pivot = Node::Base(Default::default());
@@ -211,27 +474,29 @@ impl Player {
                 body: body_handle,
                 weapon_pivot: weapon_pivot_handle,
                 sender: Some(sender),
-                name: "Player".to_owned(),
+                name: PLAYER_NAME.to_owned(),
                 ..Default::default()
             },
             camera: camera_handle,
             camera_pivot: camera_pivot_handle,
+            flashlight: Flashlight::new(scene, camera_handle),
             ..Default::default()
         }
     }
 
-    fn handle_crouch(&mut self, body: &mut RigidBody) {
+    fn handle_crouch(&mut self, body: &mut RigidBody, dt: f32) {
         let capsule = body.get_shape_mut().as_capsule_mut();
         let current_height = capsule.get_height();
         if self.controller.crouch {
-            let new_height = current_height - self.crouch_speed;
+            let new_height = current_height - self.crouch_speed * dt;
             if new_height < self.crouch_body_height {
                 capsule.set_height(self.crouch_body_height);
             } else {
                 capsule.set_height(new_height);
             }
         } else {
-            let new_height = (current_height + self.stand_up_speed).min(self.stand_body_height);
+            let new_height =
+                (current_height + self.stand_up_speed * dt).min(self.stand_body_height);
             // Divide by 2.0 because we want to know offset of cap of capsule relative to its center.
             let offset = (new_height - capsule.get_height()) / 2.0;
             capsule.set_height(new_height);
@@ -245,9 +510,9 @@ impl Player {
             let vel = body.get_velocity();
             // Push body up.
             body.set_position(body.get_position() + Vec3::new(0.0, offset, 0.0));
-            // Set new velocity. We divide offset by FIXED_FPS because we need to find speed
-            // and its units are (units/frame - units per frame).
-            body.set_velocity(vel - Vec3::new(0.0, offset / FIXED_FPS, 0.0));
+            // Set new velocity, slowed down by offset per second to damp the push. Velocity of
+            // body is in meters per physics step, so speed is scaled by time step.
+            body.set_velocity(vel - Vec3::new(0.0, offset * dt, 0.0));
         };
     }
 
@@ -255,6 +520,31 @@ impl Player {
         self.camera
     }
 
+    /// Returns true once after interact button was pressed.
+    pub fn is_crouching(&self) -> bool {
+        self.controller.crouch
+    }
+
+    pub fn take_interact_request(&mut self) -> bool {
+        std::mem::replace(&mut self.controller.interact, false)
+    }
+
+    /// Returns true once after place mine button was pressed.
+    pub fn take_place_mine_request(&mut self) -> bool {
+        std::mem::replace(&mut self.controller.place_mine, false)
+    }
+
+    /// Returns true once after melee button was pressed, if player is ready to strike again.
+    pub fn take_melee_request(&mut self) -> bool {
+        let request = std::mem::replace(&mut self.controller.melee, false);
+        if request && self.melee_cooldown <= 0.0 {
+            self.melee_cooldown = MELEE_INTERVAL;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn set_control_scheme(&mut self, control_scheme: Rc<RefCell<ControlScheme>>) {
         self.control_scheme = Some(control_scheme);
     }
@@ -325,6 +615,7 @@ impl Player {
         let has_ground_contact = self.character.has_ground_contact(&context.scene.physics);
         let body = context.scene.physics.borrow_body_mut(self.character.body);
 
+        self.movement = 0.0;
         if has_ground_contact {
             let mut moving = false;
             let mut sprinting = false;
@@ -337,8 +628,18 @@ impl Player {
                     sprinting = true;
                 }
 
-                body.set_x_velocity(velocity.x * self.move_speed * speed_multiplier);
-                body.set_z_velocity(velocity.z * self.move_speed * speed_multiplier);
+                self.movement = speed_multiplier;
+                let stagger_factor = if self.stagger_time > 0.0 {
+                    STAGGER_SPEED_FACTOR
+                } else {
+                    1.0
+                };
+                let speed = self.move_speed
+                    * speed_multiplier
+                    * self.character.speed_factor()
+                    * stagger_factor;
+                body.set_x_velocity(velocity.x * speed * context.time.delta);
+                body.set_z_velocity(velocity.z * speed * context.time.delta);
                 self.handle_view_bobbing(context.time.elapsed as f32, speed_multiplier);
             }
 
@@ -356,12 +657,12 @@ impl Player {
 
         if self.controller.jump {
             if has_ground_contact {
-                body.set_y_velocity(0.07);
+                body.set_y_velocity(JUMP_SPEED * context.time.delta);
             }
             self.controller.jump = false;
         }
 
-        self.handle_crouch(body);
+        self.handle_crouch(body, context.time.delta);
 
         self.feet_position = body.get_position();
         self.feet_position.y -= body.get_shape().as_capsule().get_height();
@@ -394,13 +695,73 @@ impl Player {
             self.pitch = self.dest_pitch;
         }
 
+        let (punch_pitch, punch_yaw) = self.view_punch;
         context.scene.graph[self.character.pivot]
             .local_transform_mut()
-            .set_rotation(Quat::from_axis_angle(Vec3::UP, self.yaw.to_radians()));
+            .set_rotation(Quat::from_axis_angle(
+                Vec3::UP,
+                (self.yaw + punch_yaw).to_radians(),
+            ));
 
         context.scene.graph[self.camera_pivot]
             .local_transform_mut()
-            .set_rotation(Quat::from_axis_angle(Vec3::RIGHT, self.pitch.to_radians()));
+            .set_rotation(Quat::from_axis_angle(
+                Vec3::RIGHT,
+                (self.pitch + punch_pitch).to_radians(),
+            ));
+    }
+
+    /// Heavy single hit throws view of player up and to a side and slows player down for a
+    /// moment, same as hit reaction of bots. Disabled by default, competitive players prefer
+    /// steady view.
+    fn update_hit_reaction(&mut self, dt: f32) {
+        let damage = self.last_health - self.character.health;
+        self.last_health = self.character.health;
+
+        let enabled = self
+            .control_scheme
+            .as_ref()
+            .map_or(false, |control_scheme| control_scheme.borrow().hit_stagger);
+        if enabled && damage >= HEAVY_HIT {
+            let punch = (damage * VIEW_PUNCH_PER_DAMAGE).min(MAX_VIEW_PUNCH);
+            let side = if rand::thread_rng().gen_bool(0.5) {
+                1.0
+            } else {
+                -1.0
+            };
+            self.view_punch = (-punch, side * punch * 0.5);
+            self.stagger_time = STAGGER_TIME;
+        }
+
+        self.stagger_time -= dt;
+        let recovery = (-VIEW_PUNCH_RECOVERY * dt).exp();
+        self.view_punch = (self.view_punch.0 * recovery, self.view_punch.1 * recovery);
+    }
+
+    /// Rotates weapon by sway, or whole camera while aiming down sights, so sway can be seen
+    /// in scope.
+    fn update_sway(&mut self, graph: &mut Graph, zoom: f32, dt: f32) {
+        let scoped = self.controller.ads && zoom > 1.0;
+        let running = self.controller.run && self.movement > 1.0;
+        let (yaw, pitch) = self.sway.update(
+            dt,
+            self.movement,
+            running,
+            self.controller.hold_breath && scoped,
+        );
+        let rotation = Quat::from_axis_angle(Vec3::UP, yaw.to_radians())
+            * Quat::from_axis_angle(Vec3::RIGHT, pitch.to_radians());
+        let (camera_rotation, weapon_rotation) = if self.controller.ads {
+            (rotation, Quat::default())
+        } else {
+            (Quat::default(), rotation)
+        };
+        graph[self.camera]
+            .local_transform_mut()
+            .set_rotation(camera_rotation);
+        graph[self.character.weapon_pivot]
+            .local_transform_mut()
+            .set_rotation(weapon_rotation);
     }
 
     fn update_listener(&mut self, sound_context: Arc<Mutex<Context>>) {
@@ -434,6 +795,8 @@ impl Player {
                     } else {
                         control_scheme.mouse_sens
                     };
+                    let mouse_sens = mouse_sens * self.aim_assist.sensitivity();
+                    self.aim_assist.on_look_input();
 
                     self.dest_yaw -= delta.0 as f32 * mouse_sens;
 
@@ -510,6 +873,26 @@ impl Player {
                     self.controller.run = true;
                 } else if control_button == control_scheme.jump.button {
                     self.controller.jump = true;
+                } else if control_button == control_scheme.interact.button {
+                    self.controller.interact = true;
+                } else if control_button == control_scheme.place_mine.button {
+                    self.controller.place_mine = true;
+                } else if control_button == control_scheme.hold_breath.button {
+                    self.controller.hold_breath = true;
+                } else if control_button == control_scheme.melee.button {
+                    self.controller.melee = true;
+                } else if control_button == control_scheme.flashlight.button {
+                    self.flashlight.toggle();
+                } else if control_button == control_scheme.toggle_laser.button {
+                    let weapon = self.character.current_weapon();
+                    if weapon.is_some() {
+                        self.character
+                            .sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::ToggleLaser { weapon })
+                            .unwrap();
+                    }
                 }
             }
             ElementState::Released => {
@@ -525,6 +908,8 @@ impl Player {
                     self.controller.move_right = false;
                 } else if control_button == control_scheme.run.button {
                     self.controller.run = false;
+                } else if control_button == control_scheme.hold_breath.button {
+                    self.controller.hold_breath = false;
                 }
             }
         }
@@ -532,14 +917,108 @@ impl Player {
         false
     }
 
-    pub fn update(&mut self, context: &mut UpdateContext) {
+    /// Moves weapon to position of current weapon and view settings, or hides it. Called
+    /// every frame and when view settings are changed in options, so changes can be seen
+    /// while game is paused.
+    pub fn apply_viewmodel(&mut self, graph: &mut Graph, weapons: &WeaponContainer) {
+        let control_scheme = match self.control_scheme.clone() {
+            Some(control_scheme) => control_scheme,
+            None => return,
+        };
+        let control_scheme = control_scheme.borrow();
+
+        let kind = self
+            .character
+            .weapons
+            .get(self.character.current_weapon as usize)
+            .map_or(WeaponKind::M4, |weapon| weapons[*weapon].get_kind());
+        let [x, y, z] = Weapon::get_definition(kind).view_offset;
+        let x = if control_scheme.left_handed { -x } else { x };
+        self.weapon_position = Vec3::new(x, y, z + control_scheme.viewmodel_distance);
+
+        let weapon_base_pivot = graph[self.character.weapon_pivot].parent();
+        graph[weapon_base_pivot]
+            .local_transform_mut()
+            .set_position(self.weapon_position);
+        graph[self.character.weapon_pivot].set_visibility(!control_scheme.hide_viewmodel);
+    }
+
+    /// Aim points of alive enemies in range of aim assist and auto-aim.
+    fn assist_targets(
+        &self,
+        self_handle: Handle<Actor>,
+        targets: &[TargetDescriptor],
+    ) -> Vec<Vec3> {
+        let team = self.character.team();
+        targets
+            .iter()
+            .filter(|desc| {
+                desc.handle != self_handle
+                    && desc.health > 0.0
+                    && team.is_enemy(desc.team)
+                    && desc.position.distance(&self.head_position) <= aim_assist::ASSIST_DISTANCE
+            })
+            .map(|desc| desc.position)
+            .collect()
+    }
+
+    /// Pulls view towards visible enemy under crosshair, see `aim_assist` module.
+    fn update_aim_assist(&mut self, context: &UpdateContext, enemies: &[Vec3]) {
+        let control_scheme = match self.control_scheme.clone() {
+            Some(control_scheme) => control_scheme,
+            None => return,
+        };
+        let head = self.head_position;
+        let physics = &context.scene.physics;
+        let (yaw, pitch) = self.aim_assist.update(
+            &control_scheme.borrow(),
+            head,
+            self.look_direction,
+            enemies,
+            |point| character::is_in_sight(physics, head, point),
+            self.movement > 0.0,
+            context.time.delta,
+        );
+        self.dest_yaw += yaw;
+        self.dest_pitch = (self.dest_pitch + pitch).max(-90.0).min(90.0);
+    }
+
+    pub fn update(
+        &mut self,
+        self_handle: Handle<Actor>,
+        context: &mut UpdateContext,
+        targets: &[TargetDescriptor],
+    ) {
+        self.apply_viewmodel(&mut context.scene.graph, context.weapons);
+        self.update_hit_reaction(context.time.delta);
+        self.melee_cooldown = (self.melee_cooldown - context.time.delta).max(0.0);
         self.update_movement(context);
+        let enemies = self.assist_targets(self_handle, targets);
+        self.update_aim_assist(context, &enemies);
+
+        self.flashlight
+            .update(&mut context.scene.graph, context.time.delta);
+
+        // Scope zooms camera while aiming down sights.
+        let zoom = match self
+            .character
+            .weapons
+            .get(self.character.current_weapon as usize)
+        {
+            Some(weapon) if self.controller.ads => context.weapons[*weapon].zoom(),
+            _ => 1.0,
+        };
+        self.update_sway(&mut context.scene.graph, zoom, context.time.delta);
 
         if let Some(current_weapon_handle) = self
             .character
             .weapons
             .get(self.character.current_weapon as usize)
         {
+            if let Node::Camera(camera) = &mut context.scene.graph[self.camera] {
+                camera.set_fov((FOV / zoom).to_radians());
+            }
+
             let velocity = context
                 .scene
                 .physics
@@ -547,6 +1026,22 @@ impl Player {
                 .get_velocity();
 
             if self.controller.shoot {
+                let head = self.head_position;
+                let physics = &context.scene.physics;
+                let direction = self.control_scheme.as_ref().and_then(|control_scheme| {
+                    aim_assist::auto_aim_point(
+                        &control_scheme.borrow(),
+                        head,
+                        self.look_direction,
+                        &enemies,
+                        |point| character::is_in_sight(physics, head, point),
+                    )
+                    .map(|point| {
+                        point
+                            - context.weapons[*current_weapon_handle]
+                                .get_shot_position(&context.scene.graph)
+                    })
+                });
                 self.character
                     .sender
                     .as_ref()
@@ -554,7 +1049,7 @@ impl Player {
                     .send(Message::ShootWeapon {
                         weapon: *current_weapon_handle,
                         initial_velocity: velocity,
-                        direction: None,
+                        direction,
                     })
                     .unwrap();
             }
@@ -566,13 +1061,12 @@ impl Player {
                 .as_ref()
                 .unwrap()
                 .send(Message::PlaySound {
-                    path: assets::sounds::footsteps::SHOE_STONE[rand::thread_rng()
-                        .gen_range(0, assets::sounds::footsteps::SHOE_STONE.len())]
-                    .into(),
+                    path: assets::sounds::footsteps::SHOE_STONE[0].into(),
                     position: self.character.position(&context.scene.physics),
                     gain: 1.0,
                     rolloff_factor: 2.0,
                     radius: 3.0,
+                    category: SoundCategory::Footstep,
                 })
                 .unwrap();
 
@@ -582,6 +1076,11 @@ impl Player {
         self.update_listener(context.sound_context.clone());
     }
 
+    /// Enables battery of flashlight, without it flashlight works forever.
+    pub fn set_flashlight_battery_enabled(&mut self, enabled: bool) {
+        self.flashlight.battery_enabled = enabled;
+    }
+
     pub fn clean_up(&mut self, scene: &mut Scene) {
         self.character.clean_up(scene)
     }
@@ -0,0 +1,88 @@
+//! Player statistics accumulated across matches. Profile is stored as JSON next to settings
+//! and is updated every time a match is over.
+
+use crate::{
+    leader_board::{self, Accuracy},
+    race::BestRun,
+    rating::Rating,
+    weapon::WeaponKind,
+};
+use rg3d::utils::log::Log;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Shots and hits of player with every weapon it ever fired.
+    pub accuracy: Vec<(WeaponKind, Accuracy)>,
+    /// Rating of player in matches against bots, see `rating` module.
+    pub rating: Rating,
+    /// Best time trial run on every map, by path of map.
+    pub best_runs: Vec<(String, BestRun)>,
+}
+
+impl Profile {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        match std::fs::read_to_string(path.as_ref())
+            .map(|data| serde_json::from_str::<Profile>(&data))
+        {
+            Ok(Ok(profile)) => profile,
+            Ok(Err(error)) => {
+                Log::writeln(format!(
+                    "Profile {} is corrupted ({}), starting new one",
+                    path.as_ref().display(),
+                    error
+                ));
+                Self::default()
+            }
+            // Profile doesn't exist until first match is over.
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) {
+        if let Err(error) = serde_json::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(path.as_ref(), data).map_err(|e| e.to_string()))
+        {
+            Log::writeln(format!("Error saving profile: {}", error))
+        }
+    }
+
+    /// Adds accuracy of a finished match to lifetime accuracy.
+    pub fn add_accuracy(&mut self, accuracy: &[(WeaponKind, Accuracy)]) {
+        for &(weapon, match_accuracy) in accuracy.iter() {
+            match self.accuracy.iter_mut().find(|(kind, _)| *kind == weapon) {
+                Some((_, total)) => {
+                    total.shots += match_accuracy.shots;
+                    total.hits += match_accuracy.hits;
+                }
+                None => self.accuracy.push((weapon, match_accuracy)),
+            }
+        }
+    }
+
+    pub fn best_run(&self, map: &str) -> Option<&BestRun> {
+        self.best_runs
+            .iter()
+            .find(|(run_map, _)| run_map == map)
+            .map(|(_, run)| run)
+    }
+
+    /// Replaces best run on a map.
+    pub fn set_best_run(&mut self, map: &str, run: BestRun) {
+        match self
+            .best_runs
+            .iter_mut()
+            .find(|(run_map, _)| run_map == map)
+        {
+            Some((_, best)) => *best = run,
+            None => self.best_runs.push((map.to_owned(), run)),
+        }
+    }
+
+    pub fn accuracy_text(&self) -> String {
+        leader_board::accuracy_text(&self.accuracy)
+    }
+}
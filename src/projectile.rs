@@ -1,8 +1,17 @@
 use crate::{
     actor::{Actor, ActorContainer},
     assets,
-    effects::EffectKind,
+    definition::{self, DefinitionCache},
+    deployable::{Deployables, Mine},
+    dynamic_prop::{DynamicProp, DynamicPropContainer},
+    effects::{EffectKind, LightFader},
+    error::{GameError, GameResult},
+    level::DamageSource,
+    material::Surfaces,
     message::Message,
+    mods,
+    shooting_range::{ShootingRange, Target},
+    sound_variation::SoundCategory,
     weapon::{Weapon, WeaponContainer},
     CollisionGroups, GameTime,
 };
@@ -11,14 +20,15 @@ use rg3d::{
     core::{
         color::Color,
         math::{mat3::Mat3, quat::Quat, ray::Ray, vec3::Vec3},
-        pool::{Handle, Pool, PoolIteratorMut},
+        pool::{Handle, Pool, PoolIteratorMut, PoolPairIterator},
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     physics::{
         convex_shape::{ConvexShape, SphereShape},
         rigid_body::{CollisionFlags, RigidBody},
-        HitKind, RayCastOptions,
+        static_geometry::StaticGeometry,
+        HitKind, Physics, RayCastOptions,
     },
     scene::{
         base::BaseBuilder,
@@ -26,13 +36,73 @@ use rg3d::{
         light::{BaseLightBuilder, PointLightBuilder},
         node::Node,
         sprite::SpriteBuilder,
-        transform::TransformBuilder,
         Scene,
     },
 };
-use std::{path::PathBuf, sync::mpsc::Sender};
+use serde::Deserialize;
+use std::{cell::RefCell, path::PathBuf, sync::mpsc::Sender};
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Max distance between actor and path of projectile at which actor feels a near miss.
+const NEAR_MISS_DISTANCE: f32 = 1.5;
+/// Time in seconds after shot during which projectile passes through its owner, so it won't
+/// hit owner right at the muzzle. After that owner can be hit by its own projectile, whether
+/// it gets damage depends on "No Self Damage" modifier.
+const OWNER_GRACE_PERIOD: f32 = 0.25;
+
+/// Distance between a point and a segment.
+pub fn distance_to_segment(point: Vec3, begin: Vec3, end: Vec3) -> f32 {
+    let segment = end - begin;
+    let sqr_len = segment.dot(&segment);
+    let t = if sqr_len > 0.0 {
+        ((point - begin).dot(&segment) / sqr_len).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+    point.distance(&(begin + segment.scale(t)))
+}
+
+/// Distance between two segments and position of the closest point on first segment as a
+/// fraction of its length.
+fn segment_to_segment(begin1: Vec3, end1: Vec3, begin2: Vec3, end2: Vec3) -> (f32, f32) {
+    let d1 = end1 - begin1;
+    let d2 = end2 - begin2;
+    let r = begin1 - begin2;
+    let a = d1.dot(&d1);
+    let e = d2.dot(&d2);
+    let f = d2.dot(&r);
+    let (s, t) = if a <= std::f32::EPSILON && e <= std::f32::EPSILON {
+        (0.0, 0.0)
+    } else if a <= std::f32::EPSILON {
+        (0.0, (f / e).max(0.0).min(1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e <= std::f32::EPSILON {
+            ((-c / a).max(0.0).min(1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denom = a * e - b * b;
+            // Parallel segments have no single closest pair, any point of first one will do.
+            let s = if denom > std::f32::EPSILON {
+                ((b * f - c * e) / denom).max(0.0).min(1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).max(0.0).min(1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).max(0.0).min(1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    let closest1 = begin1 + d1.scale(s);
+    let closest2 = begin2 + d2.scale(t);
+    (closest1.distance(&closest2), s)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
 pub enum ProjectileKind {
     Plasma,
     Bullet,
@@ -40,6 +110,12 @@ pub enum ProjectileKind {
 }
 
 impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 3] = [
+        ProjectileKind::Plasma,
+        ProjectileKind::Bullet,
+        ProjectileKind::Rocket,
+    ];
+
     pub fn new(id: u32) -> Result<Self, String> {
         match id {
             0 => Ok(ProjectileKind::Plasma),
@@ -56,6 +132,14 @@ impl ProjectileKind {
             ProjectileKind::Rocket => 2,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProjectileKind::Plasma => "Plasma",
+            ProjectileKind::Bullet => "Bullet",
+            ProjectileKind::Rocket => "Rocket",
+        }
+    }
 }
 
 pub struct Projectile {
@@ -66,8 +150,14 @@ pub struct Projectile {
     /// environment. Some projectiles do not have rigid body - they're ray-based -
     /// interaction with environment handled with ray cast.
     body: Handle<RigidBody>,
+    /// Point light attached to model, only plasma balls and rockets have it.
+    light: Handle<Node>,
+    /// Brightness of light. Not saved, at worst light fades in after load.
+    light_fader: LightFader,
     dir: Vec3,
     lifetime: f32,
+    /// Time in seconds since shot.
+    age: f32,
     rotation_angle: f32,
     /// Handle of weapons from which projectile was fired.
     pub owner: Handle<Weapon>,
@@ -77,6 +167,14 @@ pub struct Projectile {
     last_position: Vec3,
     definition: &'static ProjectileDefinition,
     pub sender: Option<Sender<Message>>,
+    /// Actors that already were notified about near miss of this projectile. Not saved - at
+    /// worst an actor will be notified twice.
+    near_misses: Vec<Handle<Actor>>,
+    /// Share of damage left after penetrating surfaces.
+    damage_scale: f32,
+    /// Surface that projectile has penetrated last time and point where it left the surface.
+    /// Hits with the surface before that point are ignored.
+    exit: Option<(Handle<StaticGeometry>, Vec3)>,
 }
 
 impl Default for Projectile {
@@ -86,17 +184,24 @@ impl Default for Projectile {
             model: Default::default(),
             dir: Default::default(),
             body: Default::default(),
+            light: Default::default(),
+            light_fader: Default::default(),
             lifetime: 0.0,
+            age: 0.0,
             rotation_angle: 0.0,
             owner: Default::default(),
             initial_velocity: Default::default(),
             last_position: Default::default(),
             definition: Self::get_definition(ProjectileKind::Plasma),
             sender: None,
+            near_misses: Default::default(),
+            damage_scale: 1.0,
+            exit: None,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct ProjectileDefinition {
     damage: f32,
     speed: f32,
@@ -104,50 +209,234 @@ pub struct ProjectileDefinition {
     /// Means that movement of projectile controlled by code, not physics.
     /// However projectile still could have rigid body to detect collisions.
     is_kinematic: bool,
+    /// Velocity change of dynamic prop hit by projectile in meters per second.
+    impulse: f32,
+    /// Velocity change of actor hit by projectile, or of actor at center of explosion for
+    /// projectiles with splash, in meters per second.
+    knockback: f32,
+    /// Projectiles with non-zero splash radius explode when they die.
+    splash_radius: f32,
+    splash_damage: f32,
     impact_sound: &'static str,
+    /// Looping sound heard while projectile flies by.
+    flyby_sound: Option<&'static str>,
+    /// Color of point light attached to projectile, projectiles without it have no light.
+    light_color: Option<(u8, u8, u8)>,
+}
+
+impl ProjectileDefinition {
+    /// Speed of projectile in meters per second.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Damage of a hit right into an actor, projectiles with splash add full splash damage.
+    pub fn direct_hit_damage(&self) -> f32 {
+        self.damage + self.splash_damage
+    }
+
+    /// Paths of every file used by projectile, projectile models and textures are not
+    /// configurable, so there is only impact sound.
+    pub fn assets(&self) -> Vec<&'static str> {
+        vec![self.impact_sound]
+    }
+}
+
+/// Stats of projectile that data file can change, see `definition` module.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectileStats {
+    damage: Option<f32>,
+    speed: Option<f32>,
+    lifetime: Option<f32>,
+    impulse: Option<f32>,
+    knockback: Option<f32>,
+    splash_radius: Option<f32>,
+    splash_damage: Option<f32>,
+}
+
+impl ProjectileStats {
+    fn apply(&self, definition: &ProjectileDefinition) -> ProjectileDefinition {
+        ProjectileDefinition {
+            damage: self.damage.unwrap_or(definition.damage),
+            speed: self.speed.unwrap_or(definition.speed),
+            lifetime: self.lifetime.unwrap_or(definition.lifetime),
+            impulse: self.impulse.unwrap_or(definition.impulse),
+            knockback: self.knockback.unwrap_or(definition.knockback),
+            splash_radius: self.splash_radius.unwrap_or(definition.splash_radius),
+            splash_damage: self.splash_damage.unwrap_or(definition.splash_damage),
+            ..definition.clone()
+        }
+    }
+}
+
+thread_local! {
+    static DEFINITIONS: RefCell<DefinitionCache<ProjectileDefinition>> = Default::default();
 }
 
 impl Projectile {
+    /// Definition of projectile with stats from data file, see `definition` module.
     pub fn get_definition(kind: ProjectileKind) -> &'static ProjectileDefinition {
+        DEFINITIONS.with(|definitions| {
+            definitions.borrow_mut().get(kind.id() as usize, || {
+                let stats = definition::load_stats::<ProjectileKind, ProjectileStats>(
+                    definition::PROJECTILES_FILE,
+                );
+                ProjectileKind::ALL
+                    .iter()
+                    .map(|kind| {
+                        let builtin = Self::builtin_definition(*kind);
+                        stats
+                            .get(kind)
+                            .map_or_else(|| builtin.clone(), |stats| stats.apply(builtin))
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Definition used when data file has no stats for the projectile.
+    fn builtin_definition(kind: ProjectileKind) -> &'static ProjectileDefinition {
         match kind {
             ProjectileKind::Plasma => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 30.0,
-                    speed: 0.15,
+                    speed: 9.0,
                     lifetime: 10.0,
                     is_kinematic: true,
+                    impulse: 3.0,
+                    knockback: 0.6,
+                    splash_radius: 0.0,
+                    splash_damage: 0.0,
                     impact_sound: assets::sounds::impact::BULLET,
+                    flyby_sound: Some(assets::sounds::flyby::PLASMA),
+                    light_color: Some((0, 162, 232)),
                 };
                 &DEFINITION
             }
             ProjectileKind::Bullet => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 15.0,
-                    speed: 5.0,
+                    speed: 300.0,
                     lifetime: 10.0,
                     is_kinematic: true,
+                    impulse: 1.8,
+                    knockback: 0.0,
+                    splash_radius: 0.0,
+                    splash_damage: 0.0,
                     impact_sound: assets::sounds::impact::BULLET,
+                    flyby_sound: None,
+                    light_color: None,
                 };
                 &DEFINITION
             }
             ProjectileKind::Rocket => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 30.0,
-                    speed: 0.5,
+                    speed: 30.0,
                     lifetime: 10.0,
                     is_kinematic: true,
+                    impulse: 6.0,
+                    knockback: 7.2,
+                    splash_radius: 3.0,
+                    splash_damage: 20.0,
                     impact_sound: assets::sounds::impact::ROCKET,
+                    flyby_sound: Some(assets::sounds::flyby::ROCKET),
+                    light_color: Some((255, 127, 0)),
                 };
                 &DEFINITION
             }
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn new(
+    /// Creates scene objects (model and optional rigid body) for projectile of given kind,
+    /// objects are created inactive and must be placed by `new`.
+    async fn create_scene_objects(
         kind: ProjectileKind,
         resource_manager: ResourceManager,
         scene: &mut Scene,
+    ) -> GameResult<SceneObjects> {
+        let (model, body, light) = match &kind {
+            ProjectileKind::Plasma => {
+                let size = rand::thread_rng().gen_range(0.09, 0.12);
+
+                let (r, g, b) = Self::get_definition(kind).light_color.unwrap_or_default();
+                let color = Color::opaque(r, g, b);
+                let texture = resource_manager
+                    .request_texture(mods::resolve(assets::textures::particles::BULLET));
+                let model = scene.graph.add_node(Node::Sprite(
+                    SpriteBuilder::new(BaseBuilder::new())
+                        .with_size(size)
+                        .with_color(color)
+                        .with_texture(texture)
+                        .build(),
+                ));
+
+                let light = scene.graph.add_node(
+                    PointLightBuilder::new(
+                        BaseLightBuilder::new(BaseBuilder::new()).with_color(color),
+                    )
+                    .with_radius(1.5)
+                    .build_node(),
+                );
+
+                scene.graph.link_nodes(light, model);
+
+                let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(size)));
+                body.set_gravity(Vec3::ZERO);
+
+                (model, scene.physics.add_body(body), light)
+            }
+            ProjectileKind::Bullet => {
+                let texture = resource_manager
+                    .request_texture(mods::resolve(assets::textures::particles::BULLET));
+                let model = scene.graph.add_node(Node::Sprite(
+                    SpriteBuilder::new(BaseBuilder::new())
+                        .with_size(0.05)
+                        .with_texture(texture)
+                        .build(),
+                ));
+
+                (model, Handle::NONE, Handle::NONE)
+            }
+            ProjectileKind::Rocket => {
+                let resource = resource_manager
+                    .request_model(mods::resolve(assets::models::projectiles::ROCKET))
+                    .await
+                    .map_err(|_| GameError::missing_asset(assets::models::projectiles::ROCKET))?;
+                let model = resource.instantiate_geometry(scene);
+                let (r, g, b) = Self::get_definition(kind).light_color.unwrap_or_default();
+                let light = scene.graph.add_node(
+                    PointLightBuilder::new(
+                        BaseLightBuilder::new(BaseBuilder::new())
+                            .with_color(Color::opaque(r, g, b)),
+                    )
+                    .with_radius(1.5)
+                    .build_node(),
+                );
+                scene.graph.link_nodes(light, model);
+                (model, Handle::NONE, light)
+            }
+        };
+
+        if model.is_some() && body.is_some() {
+            scene.physics_binder.bind(model, body);
+        }
+
+        let mut objects = SceneObjects {
+            kind,
+            model,
+            body,
+            light,
+        };
+        objects.deactivate(scene);
+        Ok(objects)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        objects: SceneObjects,
+        scene: &mut Scene,
         dir: Vec3,
         position: Vec3,
         owner: Handle<Weapon>,
@@ -155,94 +444,47 @@ impl Projectile {
         sender: Sender<Message>,
         basis: Mat3,
     ) -> Self {
+        let SceneObjects {
+            kind,
+            model,
+            body,
+            light,
+        } = objects;
         let definition = Self::get_definition(kind);
 
-        let (model, body) = {
-            match &kind {
-                ProjectileKind::Plasma => {
-                    let size = rand::thread_rng().gen_range(0.09, 0.12);
-
-                    let color = Color::opaque(0, 162, 232);
-                    let model = scene.graph.add_node(Node::Sprite(
-                        SpriteBuilder::new(BaseBuilder::new())
-                            .with_size(size)
-                            .with_color(color)
-                            .with_texture(
-                                resource_manager.request_texture(assets::textures::particles::BULLET),
-                            )
-                            .build(),
-                    ));
-
-                    let light = scene.graph.add_node(
-                        PointLightBuilder::new(
-                            BaseLightBuilder::new(BaseBuilder::new()).with_color(color),
-                        )
-                        .with_radius(1.5)
-                        .build_node(),
-                    );
-
-                    scene.graph.link_nodes(light, model);
-
-                    let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(size)));
-                    body.set_gravity(Vec3::ZERO);
-                    body.set_position(position);
-                    body.collision_group = CollisionGroups::Projectile as u64;
-                    // Projectile-Projectile collisions is disabled.
-                    body.collision_mask =
-                        CollisionGroups::All as u64 & !(CollisionGroups::Projectile as u64);
-                    body.collision_flags = CollisionFlags::DISABLE_COLLISION_RESPONSE;
-
-                    (model, scene.physics.add_body(body))
-                }
-                ProjectileKind::Bullet => {
-                    let model = scene.graph.add_node(Node::Sprite(
-                        SpriteBuilder::new(
-                            BaseBuilder::new().with_local_transform(
-                                TransformBuilder::new()
-                                    .with_local_position(position)
-                                    .build(),
-                            ),
-                        )
-                        .with_size(0.05)
-                        .with_texture(
-                            resource_manager.request_texture(assets::textures::particles::BULLET),
-                        )
-                        .build(),
-                    ));
-
-                    (model, Handle::NONE)
-                }
-                ProjectileKind::Rocket => {
-                    let resource = resource_manager
-                        .request_model(assets::models::projectiles::ROCKET)
-                        .await
-                        .unwrap();
-                    let model = resource.instantiate_geometry(scene);
-                    scene.graph[model]
-                        .local_transform_mut()
-                        .set_rotation(Quat::from(basis))
-                        .set_position(position);
-                    let light = scene.graph.add_node(
-                        PointLightBuilder::new(
-                            BaseLightBuilder::new(BaseBuilder::new())
-                                .with_color(Color::opaque(255, 127, 0)),
-                        )
-                        .with_radius(1.5)
-                        .build_node(),
-                    );
-                    scene.graph.link_nodes(light, model);
-                    (model, Handle::NONE)
-                }
+        scene.graph[model].set_visibility(true);
+        if body.is_some() {
+            let body = scene.physics.borrow_body_mut(body);
+            body.set_position(position);
+            body.collision_group = CollisionGroups::Projectile as u64;
+            // Projectile-Projectile collisions is disabled.
+            body.collision_mask =
+                CollisionGroups::All as u64 & !(CollisionGroups::Projectile as u64);
+            body.collision_flags = CollisionFlags::DISABLE_COLLISION_RESPONSE;
+        } else {
+            let transform = scene.graph[model].local_transform_mut();
+            transform.set_position(position);
+            if kind == ProjectileKind::Rocket {
+                transform.set_rotation(Quat::from(basis));
             }
-        };
+        }
 
-        if model.is_some() && body.is_some() {
-            scene.physics_binder.bind(model, body);
+        if let Some(flyby_sound) = definition.flyby_sound {
+            sender
+                .send(Message::AttachSound {
+                    node: model,
+                    path: flyby_sound.into(),
+                    position,
+                    gain: 0.6,
+                    radius: 2.0,
+                })
+                .unwrap();
         }
 
         Self {
             lifetime: definition.lifetime,
             body,
+            light,
             initial_velocity,
             dir: dir.normalized().unwrap_or(Vec3::UP),
             kind,
@@ -259,15 +501,101 @@ impl Projectile {
         self.lifetime <= 0.0
     }
 
+    #[cfg(feature = "query_api")]
+    pub fn kind(&self) -> ProjectileKind {
+        self.kind
+    }
+
     pub fn kill(&mut self) {
         self.lifetime = 0.0;
     }
 
+    /// Returns true if projectile explodes when it dies.
+    pub fn is_explosive(&self) -> bool {
+        self.definition.splash_radius > 0.0
+    }
+
+    fn damage(&self) -> f32 {
+        self.definition.damage * self.damage_scale
+    }
+
+    fn create_impact(&self, position: Vec3) {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::CreateEffect {
+                kind: EffectKind::BulletImpact,
+                position,
+            })
+            .unwrap();
+    }
+
+    /// Finds actor whose capsule crossed path of projectile during this tick. Ray cast tests
+    /// bodies at their current positions only, so it misses actors that ran across the path.
+    /// Test is done in frame of actor: projectile moves relative to actor while actor stays
+    /// still. Hits beyond `limit` are ignored, returns actor and point of hit.
+    fn sweep_actors(
+        &self,
+        physics: &Physics,
+        actors: &ActorContainer,
+        owner: Handle<Actor>,
+        position: Vec3,
+        limit: Option<Vec3>,
+    ) -> Option<(Handle<Actor>, Vec3)> {
+        let path = position - self.last_position;
+        let path_len = path.len();
+        if path_len <= std::f32::EPSILON {
+            return None;
+        }
+        let max_fraction = limit.map_or(1.0, |limit| (limit - self.last_position).len() / path_len);
+
+        let mut closest: Option<(Handle<Actor>, f32)> = None;
+        for (handle, actor) in actors.pair_iter() {
+            if actor.is_dead() || self.passes_through(owner, handle) {
+                continue;
+            }
+            let body = physics.borrow_body(actor.get_body());
+            let capsule = match body.get_shape() {
+                ConvexShape::Capsule(capsule) => capsule,
+                _ => continue,
+            };
+            // Velocity of body is in units per physics step, same as movement of projectile.
+            let center = body.get_position();
+            let previous_center = center - body.get_velocity();
+            let half_height = capsule.get_height() * 0.5;
+            let (distance, fraction) = segment_to_segment(
+                self.last_position - previous_center,
+                position - center,
+                Vec3::new(0.0, -half_height, 0.0),
+                Vec3::new(0.0, half_height, 0.0),
+            );
+            if distance <= capsule.get_radius()
+                && fraction <= max_fraction
+                && closest.map_or(true, |(_, closest)| fraction < closest)
+            {
+                closest = Some((handle, fraction));
+            }
+        }
+
+        closest.map(|(handle, fraction)| (handle, self.last_position + path.scale(fraction)))
+    }
+
+    /// Returns true if projectile passes through given actor, which is the case for owner
+    /// of weapon during grace period.
+    fn passes_through(&self, owner: Handle<Actor>, actor: Handle<Actor>) -> bool {
+        actor == owner && self.age < OWNER_GRACE_PERIOD
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         scene: &mut Scene,
         actors: &ActorContainer,
         weapons: &WeaponContainer,
+        props: &DynamicPropContainer,
+        targets: &ShootingRange,
+        deployables: &Deployables,
+        surfaces: &Surfaces,
         time: GameTime,
     ) {
         // Fetch current position of projectile.
@@ -278,8 +606,19 @@ impl Projectile {
         };
 
         let mut hits: Vec<Hit> = Vec::new();
+        let mut prop_hits: Vec<Handle<DynamicProp>> = Vec::new();
+        let mut target_hits: Vec<(Handle<Target>, Vec3)> = Vec::new();
+        let mut mine_hit: Handle<Mine> = Handle::NONE;
         let mut effect_position = None;
 
+        // Weapon could be already removed together with its owner.
+        let (who, weapon_kind) = if weapons.contains(self.owner) {
+            let weapon = &weapons[self.owner];
+            (weapon.owner(), Some(weapon.get_kind()))
+        } else {
+            (Handle::NONE, None)
+        };
+
         // Do ray based intersection tests for every kind of projectiles. This will help to handle
         // fast moving projectiles.
         if let Some(ray) = Ray::from_two_points(&self.last_position, &position) {
@@ -292,22 +631,60 @@ impl Projectile {
                 'hit_loop: for hit in result.iter() {
                     if let HitKind::Body(body) = hit.kind {
                         for (actor_handle, actor) in actors.pair_iter() {
-                            if actor.get_body() == body && self.owner.is_some() {
-                                let weapon = &weapons[self.owner];
-                                // Ignore intersections with owners of weapon.
-                                if weapon.owner() != actor_handle {
-                                    hits.push(Hit {
-                                        actor: actor_handle,
-                                        who: weapon.owner(),
-                                    });
-
-                                    self.kill();
-                                    effect_position = Some(hit.position);
-                                    break 'hit_loop;
-                                }
+                            // Weapon could be removed while projectile is in flight.
+                            if actor.get_body() == body
+                                && weapons.contains(self.owner)
+                                && !self.passes_through(who, actor_handle)
+                            {
+                                hits.push(Hit {
+                                    actor: actor_handle,
+                                    who,
+                                });
+
+                                self.kill();
+                                effect_position = Some(hit.position);
+                                break 'hit_loop;
                             }
                         }
+                        let prop = props.find_by_body(body);
+                        if prop.is_some() {
+                            prop_hits.push(prop);
+
+                            self.kill();
+                            effect_position = Some(hit.position);
+                            break 'hit_loop;
+                        }
+                        let target = targets.find_by_body(body);
+                        if target.is_some() {
+                            target_hits.push((target, hit.position));
+
+                            self.kill();
+                            effect_position = Some(hit.position);
+                            break 'hit_loop;
+                        }
                     } else {
+                        if let HitKind::StaticTriangle { owner, .. } = hit.kind {
+                            if let Some((surface, exit)) = self.exit {
+                                if surface == owner && (hit.position - exit).dot(&ray.dir) <= 0.0 {
+                                    continue 'hit_loop;
+                                }
+                            }
+                            // Only bullets penetrate surfaces.
+                            if self.body.is_none() {
+                                if let Some((exit, damage_left)) = surfaces.penetrate(
+                                    &scene.physics,
+                                    owner,
+                                    hit.position,
+                                    ray.dir.normalized().unwrap_or_default(),
+                                ) {
+                                    self.damage_scale *= damage_left;
+                                    self.exit = Some((owner, exit));
+                                    self.create_impact(hit.position);
+                                    self.create_impact(exit);
+                                    continue 'hit_loop;
+                                }
+                            }
+                        }
                         self.kill();
                         effect_position = Some(hit.position);
                         break 'hit_loop;
@@ -316,9 +693,42 @@ impl Projectile {
             }
         }
 
+        if hits.is_empty() && weapons.contains(self.owner) {
+            if let Some((actor, hit_position)) =
+                self.sweep_actors(&scene.physics, actors, who, position, effect_position)
+            {
+                // Actor was in front of whatever ray has hit.
+                prop_hits.clear();
+                target_hits.clear();
+                hits.push(Hit { actor, who });
+
+                self.kill();
+                effect_position = Some(hit_position);
+            }
+        }
+
+        // Mines have no rigid bodies, so they're checked separately. Mine behind something that
+        // was hit is safe.
+        let mine = deployables.find_on_segment(self.last_position, position);
+        if mine.is_some() {
+            let mine_position = deployables.position(mine);
+            let blocked = effect_position.map_or(false, |hit_position: Vec3| {
+                hit_position.sqr_distance(&self.last_position)
+                    < mine_position.sqr_distance(&self.last_position)
+            });
+            if !blocked {
+                mine_hit = mine;
+
+                self.kill();
+                effect_position = Some(mine_position);
+            }
+        }
+
         // Movement of kinematic projectiles are controlled explicitly.
         if self.definition.is_kinematic {
-            let total_velocity = self.initial_velocity + self.dir.scale(self.definition.speed);
+            // Initial velocity comes from body of shooter, so it is per physics step already.
+            let total_velocity =
+                self.initial_velocity + self.dir.scale(self.definition.speed * time.delta);
 
             // Special case for projectiles with rigid body.
             if self.body.is_some() {
@@ -327,13 +737,11 @@ impl Projectile {
 
                     // Check if we got contact with any actor and damage it then.
                     for (actor_handle, actor) in actors.pair_iter() {
-                        if contact.body == actor.get_body() && self.owner.is_some() {
-                            // Prevent self-damage.
-                            let weapon = &weapons[self.owner];
-                            if weapon.owner() != actor_handle {
+                        if contact.body == actor.get_body() && weapons.contains(self.owner) {
+                            if !self.passes_through(who, actor_handle) {
                                 hits.push(Hit {
                                     actor: actor_handle,
-                                    who: weapon.owner(),
+                                    who,
                                 });
                             } else {
                                 // Make sure that projectile won't die on contact with owner.
@@ -342,6 +750,16 @@ impl Projectile {
                         }
                     }
 
+                    let prop = props.find_by_body(contact.body);
+                    if prop.is_some() {
+                        prop_hits.push(prop);
+                    }
+
+                    let target = targets.find_by_body(contact.body);
+                    if target.is_some() {
+                        target_hits.push((target, contact.position));
+                    }
+
                     if !owner_contact {
                         self.kill();
                         effect_position = Some(contact.position);
@@ -371,18 +789,12 @@ impl Projectile {
         self.initial_velocity.follow(&Vec3::ZERO, 0.15);
 
         self.lifetime -= time.delta;
+        self.age += time.delta;
 
         if self.lifetime <= 0.0 {
             let pos = effect_position.unwrap_or_else(|| self.get_position(&scene.graph));
 
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::CreateEffect {
-                    kind: EffectKind::BulletImpact,
-                    position: pos,
-                })
-                .unwrap();
+            self.create_impact(pos);
 
             self.sender
                 .as_ref()
@@ -393,14 +805,58 @@ impl Projectile {
                     gain: 1.0,
                     rolloff_factor: 4.0,
                     radius: 3.0,
+                    category: SoundCategory::Impact,
                 })
                 .unwrap();
+
+            if self.definition.splash_radius > 0.0 {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::Explosion {
+                        position: pos,
+                        radius: self.definition.splash_radius,
+                        damage: self.definition.splash_damage,
+                        impulse: self.definition.impulse,
+                        knockback: self.definition.knockback,
+                        who,
+                        source: DamageSource::Splash(self.kind),
+                    })
+                    .unwrap();
+            }
+        }
+
+        for (actor_handle, actor) in actors.pair_iter() {
+            if actor_handle != who
+                && !self.near_misses.contains(&actor_handle)
+                && !hits.iter().any(|hit| hit.actor == actor_handle)
+                && distance_to_segment(actor.position(&scene.physics), self.last_position, position)
+                    <= NEAR_MISS_DISTANCE
+            {
+                self.near_misses.push(actor_handle);
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::NearMiss {
+                        actor: actor_handle,
+                    })
+                    .unwrap();
+            }
         }
 
         // List of hit actors can contain same actor multiple times in a row because this list could
         // be filled from ray casting as well as from contact information of rigid body, fix this
         // to not damage actor twice or more times with one projectile.
         hits.dedup_by(|a, b| a.actor == b.actor);
+        if let Some(weapon) = weapon_kind {
+            if hits.iter().any(|hit| hit.actor != who) {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::CountHit { who, weapon })
+                    .unwrap();
+            }
+        }
         for hit in hits {
             self.sender
                 .as_ref()
@@ -408,7 +864,59 @@ impl Projectile {
                 .send(Message::DamageActor {
                     actor: hit.actor,
                     who: hit.who,
-                    amount: self.definition.damage,
+                    amount: self.damage(),
+                    source: DamageSource::Projectile(self.kind),
+                    impulse: self.dir.scale(self.definition.knockback),
+                })
+                .unwrap();
+        }
+
+        prop_hits.dedup();
+        for prop in prop_hits {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::DamageProp {
+                    prop,
+                    who,
+                    amount: self.damage(),
+                    impulse: self.dir.scale(self.definition.impulse),
+                })
+                .unwrap();
+        }
+
+        target_hits.dedup_by(|a, b| a.0 == b.0);
+        for (target, position) in target_hits {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::HitTarget {
+                    target,
+                    position,
+                    amount: self.damage(),
+                })
+                .unwrap();
+        }
+
+        if mine_hit.is_some() {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::DisarmMine {
+                    mine: mine_hit,
+                    who,
+                })
+                .unwrap();
+        }
+
+        if self.definition.flyby_sound.is_some() {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::MoveAttachedSound {
+                    node: self.model,
+                    position,
+                    velocity: (position - self.last_position).scale(1.0 / time.delta),
                 })
                 .unwrap();
         }
@@ -420,16 +928,78 @@ impl Projectile {
         graph[self.model].global_position()
     }
 
-    fn clean_up(&mut self, scene: &mut Scene) {
-        if self.body.is_some() {
-            scene.physics.remove_body(self.body);
+    /// Hides scene objects of dead projectile so they can be reused by next projectile of
+    /// same kind.
+    fn recycle(&mut self, scene: &mut Scene) -> SceneObjects {
+        if self.definition.flyby_sound.is_some() {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::DetachSound { node: self.model })
+                .unwrap();
         }
+        let mut objects = SceneObjects {
+            kind: self.kind,
+            model: std::mem::replace(&mut self.model, Handle::NONE),
+            body: std::mem::replace(&mut self.body, Handle::NONE),
+            light: std::mem::replace(&mut self.light, Handle::NONE),
+        };
+        objects.deactivate(scene);
+        objects
+    }
+}
+
+/// Scene objects of a projectile. Creation of nodes and bodies on every shot is expensive, so
+/// objects of dead projectiles are kept hidden and reused instead of being removed.
+pub struct SceneObjects {
+    kind: ProjectileKind,
+    model: Handle<Node>,
+    body: Handle<RigidBody>,
+    light: Handle<Node>,
+}
+
+impl Default for SceneObjects {
+    fn default() -> Self {
+        Self {
+            kind: ProjectileKind::Plasma,
+            model: Handle::NONE,
+            body: Handle::NONE,
+            light: Handle::NONE,
+        }
+    }
+}
+
+impl SceneObjects {
+    fn deactivate(&mut self, scene: &mut Scene) {
         if self.model.is_some() {
-            scene.graph.remove_node(self.model);
+            scene.graph[self.model].set_visibility(false);
+        }
+        if self.body.is_some() {
+            // Body stays in physics world, but it must not collide with anything.
+            let body = scene.physics.borrow_body_mut(self.body);
+            body.collision_group = 0;
+            body.collision_mask = 0;
         }
     }
 }
 
+impl Visit for SceneObjects {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind = self.kind.id();
+        kind.visit("KindId", visitor)?;
+        if visitor.is_reading() {
+            self.kind = ProjectileKind::new(kind)?;
+        }
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.light.visit("Light", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
 struct Hit {
     actor: Handle<Actor>,
     who: Handle<Actor>,
@@ -447,12 +1017,30 @@ impl Visit for Projectile {
 
         self.definition = Self::get_definition(self.kind);
         self.lifetime.visit("Lifetime", visitor)?;
+        self.age.visit("Age", visitor)?;
+        self.damage_scale.visit("DamageScale", visitor)?;
         self.dir.visit("Direction", visitor)?;
         self.model.visit("Model", visitor)?;
         self.body.visit("Body", visitor)?;
+        self.light.visit("Light", visitor)?;
         self.rotation_angle.visit("RotationAngle", visitor)?;
         self.initial_velocity.visit("InitialVelocity", visitor)?;
+        // Ray of first update after load must start where projectile was, not at origin.
+        self.last_position.visit("LastPosition", visitor)?;
         self.owner.visit("Owner", visitor)?;
+        // Projectile saved inside a penetrable surface must keep passing through it after load,
+        // otherwise it hits exit face of the surface right away.
+        let mut exit_surface = self.exit.map_or(Handle::NONE, |(surface, _)| surface);
+        let mut exit_point = self.exit.map_or(Vec3::ZERO, |(_, point)| point);
+        exit_surface.visit("ExitSurface", visitor)?;
+        exit_point.visit("ExitPoint", visitor)?;
+        if visitor.is_reading() {
+            self.exit = if exit_surface.is_some() {
+                Some((exit_surface, exit_point))
+            } else {
+                None
+            };
+        }
 
         visitor.leave_region()
     }
@@ -460,36 +1048,170 @@ impl Visit for Projectile {
 
 pub struct ProjectileContainer {
     pool: Pool<Projectile>,
+    /// Hidden scene objects that are ready to be reused.
+    free: Vec<SceneObjects>,
+    /// Alive projectiles with lights and their squared distances to viewer, reused every
+    /// frame.
+    lights: Vec<(Handle<Projectile>, f32)>,
 }
 
 impl ProjectileContainer {
     pub fn new() -> Self {
-        Self { pool: Pool::new() }
+        Self {
+            pool: Pool::new(),
+            free: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+
+    /// Creates scene objects for given amount of projectiles in advance, so first shots won't
+    /// cause any allocations.
+    pub async fn prewarm(
+        &mut self,
+        kind: ProjectileKind,
+        count: usize,
+        resource_manager: ResourceManager,
+        scene: &mut Scene,
+    ) -> GameResult<()> {
+        for _ in 0..count {
+            let objects =
+                Projectile::create_scene_objects(kind, resource_manager.clone(), scene).await?;
+            self.free.push(objects);
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn spawn(
+        &mut self,
+        kind: ProjectileKind,
+        resource_manager: ResourceManager,
+        scene: &mut Scene,
+        dir: Vec3,
+        position: Vec3,
+        owner: Handle<Weapon>,
+        initial_velocity: Vec3,
+        sender: Sender<Message>,
+        basis: Mat3,
+    ) -> GameResult<Handle<Projectile>> {
+        let objects = match self.free.iter().position(|objects| objects.kind == kind) {
+            Some(index) => self.free.swap_remove(index),
+            None => Projectile::create_scene_objects(kind, resource_manager, scene).await?,
+        };
+        let projectile = Projectile::new(
+            objects,
+            scene,
+            dir,
+            position,
+            owner,
+            initial_velocity,
+            sender,
+            basis,
+        );
+        Ok(self.pool.spawn(projectile))
     }
 
-    pub fn add(&mut self, projectile: Projectile) -> Handle<Projectile> {
-        self.pool.spawn(projectile)
+    #[cfg(feature = "query_api")]
+    pub fn iter(&self) -> rg3d::core::pool::PoolIterator<Projectile> {
+        self.pool.iter()
     }
 
     pub fn iter_mut(&mut self) -> PoolIteratorMut<Projectile> {
         self.pool.iter_mut()
     }
 
+    pub fn pair_iter(&self) -> PoolPairIterator<Projectile> {
+        self.pool.pair_iter()
+    }
+
+    /// Makes explosive projectile explode on next update, others are left intact.
+    pub fn detonate(&mut self, projectile: Handle<Projectile>) {
+        if self.pool.is_valid_handle(projectile) {
+            let projectile = self.pool.borrow_mut(projectile);
+            if projectile.is_explosive() {
+                projectile.kill();
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         scene: &mut Scene,
         actors: &ActorContainer,
         weapons: &WeaponContainer,
+        props: &DynamicPropContainer,
+        targets: &ShootingRange,
+        deployables: &Deployables,
+        surfaces: &Surfaces,
         time: GameTime,
+        viewer_position: Vec3,
+        max_lights: usize,
+        reduce_flashes: bool,
     ) {
         for projectile in self.pool.iter_mut() {
-            projectile.update(scene, actors, weapons, time);
+            projectile.update(
+                scene,
+                actors,
+                weapons,
+                props,
+                targets,
+                deployables,
+                surfaces,
+                time,
+            );
             if projectile.is_dead() {
-                projectile.clean_up(scene);
+                self.free.push(projectile.recycle(scene));
             }
         }
 
         self.pool.retain(|proj| !proj.is_dead());
+
+        self.apply_light_budget(
+            scene,
+            viewer_position,
+            max_lights,
+            reduce_flashes,
+            time.delta,
+        );
+    }
+
+    /// Every plasma ball and rocket has a point light, which is expensive when there are
+    /// lots of them. Only lights of projectiles closest to viewer are kept enabled.
+    fn apply_light_budget(
+        &mut self,
+        scene: &mut Scene,
+        viewer_position: Vec3,
+        max_lights: usize,
+        reduce_flashes: bool,
+        dt: f32,
+    ) {
+        self.lights.clear();
+        for (handle, projectile) in self.pool.pair_iter() {
+            if projectile.light.is_some() {
+                let position = projectile.get_position(&scene.graph);
+                self.lights
+                    .push((handle, position.sqr_distance(&viewer_position)));
+            }
+        }
+
+        if self.lights.len() > max_lights {
+            self.lights
+                .sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        for (i, (handle, _)) in self.lights.iter().enumerate() {
+            let projectile = &mut self.pool[*handle];
+            let target = if i < max_lights { 1.0 } else { 0.0 };
+            projectile.light_fader.update(
+                &mut scene.graph,
+                projectile.light,
+                projectile.definition.light_color.unwrap_or_default(),
+                target,
+                reduce_flashes,
+                dt,
+            );
+        }
     }
 }
 
@@ -498,7 +1220,47 @@ impl Visit for ProjectileContainer {
         visitor.enter_region(name)?;
 
         self.pool.visit("Pool", visitor)?;
+        self.free.visit("Free", visitor)?;
 
         visitor.leave_region()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visit_test;
+
+    #[test]
+    fn projectile_survives_save_and_load() {
+        let mut weapons = Pool::new();
+        let owner = weapons.spawn(Weapon::default());
+        let surface = Handle::new(3, 1);
+        let mut projectile = Projectile {
+            kind: ProjectileKind::Rocket,
+            lifetime: 4.0,
+            age: 1.5,
+            damage_scale: 0.5,
+            dir: Vec3::new(0.0, 0.0, 1.0),
+            last_position: Vec3::new(1.0, 2.0, 3.0),
+            owner,
+            exit: Some((surface, Vec3::new(1.0, 2.0, 3.5))),
+            ..Default::default()
+        };
+
+        let loaded = visit_test::round_trip("Projectile", &mut projectile);
+
+        assert_eq!(loaded.kind, ProjectileKind::Rocket);
+        assert_eq!(loaded.lifetime, 4.0);
+        assert_eq!(loaded.age, 1.5);
+        assert_eq!(loaded.damage_scale, 0.5);
+        assert_eq!(loaded.dir, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(loaded.last_position, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(loaded.owner, owner);
+        assert_eq!(loaded.exit, Some((surface, Vec3::new(1.0, 2.0, 3.5))));
+        assert!(std::ptr::eq(
+            loaded.definition,
+            Projectile::get_definition(ProjectileKind::Rocket)
+        ));
+    }
+}
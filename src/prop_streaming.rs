@@ -0,0 +1,122 @@
+//! Streaming of decorative props on big maps. Instead of baking heavy props into map model,
+//! map contains empty placeholder nodes named `Prop_<model>` (suffixes like `.001` are
+//! ignored), model `data/models/props/<model>.FBX` is loaded in background when viewer comes
+//! close to a placeholder and is removed when viewer goes away. Props are decoration only,
+//! collision geometry of the map is a separate `Polygon` node which is always resident.
+
+use crate::mods;
+use rg3d::{
+    core::{math::vec3::Vec3, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    futures::FutureExt,
+    resource::model::Model,
+    scene::{node::Node, Scene},
+    utils::log::Log,
+};
+use std::path::PathBuf;
+
+const PLACEHOLDER_PREFIX: &str = "Prop_";
+const PROPS_DIR: &str = "data/models/props";
+/// Props are unloaded a bit farther than they're loaded, so a viewer that stands on the
+/// border won't make a prop load and unload every frame.
+const UNLOAD_DISTANCE_FACTOR: f32 = 1.25;
+
+enum PropState {
+    Unloaded,
+    /// Model was requested and is being loaded by resource manager.
+    Loading(Model),
+    /// Root of model instance, it is linked to the placeholder.
+    Loaded(Handle<Node>),
+    /// Model is missing or broken, prop won't be requested again.
+    Failed,
+}
+
+struct StreamedProp {
+    placeholder: Handle<Node>,
+    position: Vec3,
+    path: PathBuf,
+    state: PropState,
+}
+
+#[derive(Default)]
+pub struct PropStreamer {
+    props: Vec<StreamedProp>,
+}
+
+impl PropStreamer {
+    /// Finds placeholders in given scene. Instances that are already linked to placeholders
+    /// (for example in a loaded save) are picked up as loaded props.
+    pub fn collect(scene: &Scene) -> Self {
+        let props = scene
+            .graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let name = node.name().strip_prefix(PLACEHOLDER_PREFIX)?;
+                let model = name.split('.').next().unwrap_or(name);
+                let state = match node.children().first() {
+                    Some(&instance) => PropState::Loaded(instance),
+                    None => PropState::Unloaded,
+                };
+                Some(StreamedProp {
+                    placeholder: handle,
+                    position: node.global_position(),
+                    path: PathBuf::from(format!("{}/{}.FBX", PROPS_DIR, model)),
+                    state,
+                })
+            })
+            .collect();
+
+        Self { props }
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        viewer_position: Vec3,
+        load_distance: f32,
+    ) {
+        let load_distance_sqr = load_distance * load_distance;
+        let unload_distance = load_distance * UNLOAD_DISTANCE_FACTOR;
+        let unload_distance_sqr = unload_distance * unload_distance;
+
+        for prop in self.props.iter_mut() {
+            let distance_sqr = prop.position.sqr_distance(&viewer_position);
+
+            let state = std::mem::replace(&mut prop.state, PropState::Unloaded);
+            prop.state = match state {
+                PropState::Unloaded if distance_sqr < load_distance_sqr => {
+                    PropState::Loading(resource_manager.request_model(mods::resolve(&prop.path)))
+                }
+                PropState::Loading(model) => {
+                    if distance_sqr > unload_distance_sqr {
+                        // Viewer went away before model was loaded, resource manager will
+                        // drop it when it won't be used by anyone.
+                        PropState::Unloaded
+                    } else {
+                        match model.clone().now_or_never() {
+                            Some(Ok(model)) => {
+                                let instance = model.instantiate_geometry(scene);
+                                scene.graph.link_nodes(instance, prop.placeholder);
+                                PropState::Loaded(instance)
+                            }
+                            Some(Err(_)) => {
+                                Log::writeln(format!(
+                                    "WARNING: Unable to load prop {}!",
+                                    prop.path.display()
+                                ));
+                                PropState::Failed
+                            }
+                            None => PropState::Loading(model),
+                        }
+                    }
+                }
+                PropState::Loaded(instance) if distance_sqr > unload_distance_sqr => {
+                    scene.remove_node(instance);
+                    PropState::Unloaded
+                }
+                state => state,
+            };
+        }
+    }
+}
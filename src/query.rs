@@ -0,0 +1,54 @@
+//! Read-only view of match state for tools, console and scripting, enabled by `query_api`
+//! feature. Level fills these structures on request, so observers never touch internals of
+//! level and can't change anything in it. Press F11 in a match to write snapshot of the
+//! match to the log.
+
+use crate::{
+    actor::Actor, character::Team, item::ItemKind, projectile::ProjectileKind, weapon::WeaponKind,
+};
+use rg3d::core::{math::vec3::Vec3, pool::Handle};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ActorInfoKind {
+    Player,
+    Bot,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActorInfo {
+    pub handle: Handle<Actor>,
+    pub kind: ActorInfoKind,
+    pub name: String,
+    pub position: Vec3,
+    pub health: f32,
+    pub armor: f32,
+    pub team: Team,
+    /// Weapon in hands, `None` if actor has no weapons.
+    pub weapon: Option<WeaponKind>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ItemInfo {
+    pub kind: ItemKind,
+    pub position: Vec3,
+    /// Picked up item is waiting for respawn.
+    pub picked_up: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectileInfo {
+    pub kind: ProjectileKind,
+    pub position: Vec3,
+    /// Actor who fired the projectile, `Handle::NONE` if it is gone.
+    pub owner: Handle<Actor>,
+}
+
+/// State of a match at some moment.
+#[derive(Clone, Debug)]
+pub struct LevelSnapshot {
+    /// Time in seconds since start of the match.
+    pub time: f32,
+    pub actors: Vec<ActorInfo>,
+    pub items: Vec<ItemInfo>,
+    pub projectiles: Vec<ProjectileInfo>,
+}
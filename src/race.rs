@@ -0,0 +1,216 @@
+//! Course of time trial mode. Course is made of meshes of a map - `Race_Start` gate,
+//! `Race_Checkpoint_*` gates that must be passed in order of their names and `Race_Finish`
+//! gate. Run starts when player leaves start gate, so going through it again restarts the
+//! run. Position of player is recorded during a run, record of the best run of a map is kept
+//! in profile and replayed by a ghost in next runs.
+
+use rg3d::{
+    core::{
+        math::{aabb::AxisAlignedBoundingBox, vec3::Vec3},
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node},
+};
+use serde::{Deserialize, Serialize};
+
+/// Time between two recorded positions of a run.
+const GHOST_INTERVAL: f32 = 0.1;
+
+/// Time and recorded positions of the best run on a map.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BestRun {
+    pub time: f32,
+    /// Positions of player every `GHOST_INTERVAL` seconds.
+    pub ghost: Vec<[f32; 3]>,
+}
+
+impl BestRun {
+    /// Position of ghost at given time of a run, `None` when the run is over.
+    fn ghost_position(&self, time: f32) -> Option<Vec3> {
+        let index = (time / GHOST_INTERVAL) as usize;
+        let to_vec3 = |p: [f32; 3]| Vec3::new(p[0], p[1], p[2]);
+        let begin = to_vec3(*self.ghost.get(index)?);
+        let end = to_vec3(*self.ghost.get(index + 1)?);
+        let t = (time - index as f32 * GHOST_INTERVAL) / GHOST_INTERVAL;
+        Some(begin + (end - begin).scale(t))
+    }
+}
+
+pub enum RaceEvent {
+    Started,
+    Checkpoint {
+        index: usize,
+        count: usize,
+        time: f32,
+    },
+    Finished {
+        time: f32,
+        best: bool,
+    },
+}
+
+#[derive(Default)]
+pub struct Race {
+    start: AxisAlignedBoundingBox,
+    checkpoints: Vec<AxisAlignedBoundingBox>,
+    finish: AxisAlignedBoundingBox,
+    running: bool,
+    /// Index of checkpoint that must be passed next.
+    next_checkpoint: u32,
+    time: f32,
+    /// Player was in start gate last update, run starts when player leaves the gate.
+    in_start: bool,
+    recording: Vec<Vec3>,
+    record_timer: f32,
+    /// Not saved - it is taken from profile.
+    best: Option<BestRun>,
+    ghost: Handle<Node>,
+}
+
+impl Race {
+    pub fn new(
+        start: AxisAlignedBoundingBox,
+        checkpoints: Vec<AxisAlignedBoundingBox>,
+        finish: AxisAlignedBoundingBox,
+        ghost: Handle<Node>,
+    ) -> Self {
+        Self {
+            start,
+            checkpoints,
+            finish,
+            ghost,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_best_run(&mut self, best: Option<BestRun>) {
+        self.best = best;
+    }
+
+    pub fn best_time(&self) -> Option<f32> {
+        self.best.as_ref().map(|best| best.time)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Amount of checkpoints passed in current run and amount of all checkpoints.
+    pub fn checkpoints(&self) -> (usize, usize) {
+        (self.next_checkpoint as usize, self.checkpoints.len())
+    }
+
+    /// Returns record of finished run.
+    fn record(&self) -> BestRun {
+        BestRun {
+            time: self.time,
+            ghost: self.recording.iter().map(|p| [p.x, p.y, p.z]).collect(),
+        }
+    }
+
+    /// Advances run by position of player, `None` if player is dead or not deployed - run
+    /// stops then. Returns event of the run if something happened.
+    pub fn update(
+        &mut self,
+        graph: &mut Graph,
+        player: Option<Vec3>,
+        dt: f32,
+    ) -> Option<RaceEvent> {
+        let event = match player {
+            Some(position) => self.advance(position, dt),
+            None => {
+                self.running = false;
+                self.in_start = false;
+                None
+            }
+        };
+
+        let ghost_position = match (&self.best, self.running) {
+            (Some(best), true) => best.ghost_position(self.time),
+            _ => None,
+        };
+        let ghost = &mut graph[self.ghost];
+        ghost.set_visibility(ghost_position.is_some());
+        if let Some(position) = ghost_position {
+            ghost.local_transform_mut().set_position(position);
+        }
+
+        event
+    }
+
+    fn advance(&mut self, position: Vec3, dt: f32) -> Option<RaceEvent> {
+        let in_start = self.start.is_contains_point(position);
+        let left_start = self.in_start && !in_start;
+        self.in_start = in_start;
+        if left_start {
+            self.running = true;
+            self.next_checkpoint = 0;
+            self.time = 0.0;
+            self.recording = vec![position];
+            self.record_timer = GHOST_INTERVAL;
+            return Some(RaceEvent::Started);
+        }
+        if !self.running {
+            return None;
+        }
+
+        self.time += dt;
+        self.record_timer -= dt;
+        if self.record_timer <= 0.0 {
+            self.record_timer += GHOST_INTERVAL;
+            self.recording.push(position);
+        }
+
+        let next = self.next_checkpoint as usize;
+        if let Some(checkpoint) = self.checkpoints.get(next) {
+            if checkpoint.is_contains_point(position) {
+                self.next_checkpoint += 1;
+                return Some(RaceEvent::Checkpoint {
+                    index: next + 1,
+                    count: self.checkpoints.len(),
+                    time: self.time,
+                });
+            }
+        } else if self.finish.is_contains_point(position) {
+            self.running = false;
+            let best = self.best_time().map_or(true, |best| self.time < best);
+            if best {
+                self.best = Some(self.record());
+            }
+            return Some(RaceEvent::Finished {
+                time: self.time,
+                best,
+            });
+        }
+        None
+    }
+
+    /// Record of the best run, to be kept in profile.
+    pub fn best_run(&self) -> Option<&BestRun> {
+        self.best.as_ref()
+    }
+}
+
+impl Visit for Race {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.start.visit("Start", visitor)?;
+        self.checkpoints.visit("Checkpoints", visitor)?;
+        self.finish.visit("Finish", visitor)?;
+        self.running.visit("Running", visitor)?;
+        self.next_checkpoint.visit("NextCheckpoint", visitor)?;
+        self.time.visit("Time", visitor)?;
+        self.in_start.visit("InStart", visitor)?;
+        self.recording.visit("Recording", visitor)?;
+        self.record_timer.visit("RecordTimer", visitor)?;
+        self.ghost.visit("Ghost", visitor)?;
+
+        visitor.leave_region()
+    }
+}
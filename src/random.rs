@@ -0,0 +1,41 @@
+//! Random number generator of game simulation. Unlike `rand::thread_rng` it can be seeded, so
+//! recorded input replayed with the same seed gives the same match (see `repro` module).
+//! Cosmetic randomness (particles, sounds) still uses `rand::thread_rng` - amount of particles
+//! depends on quality settings and must not shift the sequence of simulation.
+
+use rand::{rngs::StdRng, Error, RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Restarts sequence of random numbers from given seed.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Handle to random number generator of current thread, use it instead of `rand::thread_rng`.
+pub fn rng() -> GameRng {
+    GameRng
+}
+
+pub struct GameRng;
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
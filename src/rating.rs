@@ -0,0 +1,98 @@
+//! Elo-style rating of player and adaptive difficulty of bots. After every finished match
+//! against bots rating of player moves toward its results: win counts as one, draw as half and
+//! loss as zero. Bots have rating too, it is defined by selected difficulty and skill of bots.
+//! Adaptive difficulty picks skill of bots so that player wins with `TARGET_WIN_RATE`
+//! probability, skill scales aim error and reaction time of bots within limits, so selected
+//! difficulty still matters.
+
+use crate::{
+    bot::BotDifficulty,
+    character::Team,
+    leader_board::{MatchSummary, MatchWinner},
+    player,
+};
+use serde::{Deserialize, Serialize};
+
+/// Rating of new player, equal to rating of bots of normal difficulty.
+const INITIAL_RATING: f32 = 1000.0;
+/// Max change of rating per match.
+const K_FACTOR: f32 = 32.0;
+/// Difference of ratings at which stronger side is expected to win ten times more often.
+const SCALE: f32 = 400.0;
+/// Share of matches player should win against adaptive bots.
+const TARGET_WIN_RATE: f32 = 0.5;
+const MIN_BOT_SKILL: f32 = 0.5;
+const MAX_BOT_SKILL: f32 = 2.0;
+
+fn difficulty_rating(difficulty: BotDifficulty) -> f32 {
+    match difficulty {
+        BotDifficulty::Easy => INITIAL_RATING - 200.0,
+        BotDifficulty::Normal => INITIAL_RATING,
+        BotDifficulty::Hard => INITIAL_RATING + 200.0,
+    }
+}
+
+/// Rating of bots of given difficulty and skill.
+pub fn bot_rating(difficulty: BotDifficulty, skill: f32) -> f32 {
+    difficulty_rating(difficulty) + SCALE * skill.log10()
+}
+
+/// Result of player in a finished match: 1 for win, 0.5 for draw and 0 for loss.
+pub fn match_result(summary: &MatchSummary, player_team: Team) -> f32 {
+    match &summary.winner {
+        MatchWinner::Actor { name, .. } => {
+            if name == player::PLAYER_NAME {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        MatchWinner::Team { team, .. } => {
+            if *team == player_team {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        MatchWinner::Draw => 0.5,
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rating {
+    pub rating: f32,
+    /// Amount of rated matches.
+    pub matches: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            matches: 0,
+        }
+    }
+}
+
+impl Rating {
+    /// Probability of player to win against opponent with given rating.
+    fn expected_result(&self, opponent_rating: f32) -> f32 {
+        1.0 / (1.0 + 10.0f32.powf((opponent_rating - self.rating) / SCALE))
+    }
+
+    /// Moves rating toward result of a match against opponent with given rating.
+    pub fn add_match(&mut self, opponent_rating: f32, result: f32) {
+        self.rating += K_FACTOR * (result - self.expected_result(opponent_rating));
+        self.matches += 1;
+    }
+
+    /// Skill of bots of given difficulty that player beats with target probability.
+    pub fn adaptive_bot_skill(&self, difficulty: BotDifficulty) -> f32 {
+        let target_rating = self.rating + SCALE * (1.0 / TARGET_WIN_RATE - 1.0).log10();
+        10.0f32
+            .powf((target_rating - difficulty_rating(difficulty)) / SCALE)
+            .max(MIN_BOT_SKILL)
+            .min(MAX_BOT_SKILL)
+    }
+}
@@ -0,0 +1,216 @@
+//! Repro capture - records input of player together with seed of random number generator and
+//! match settings, so a movement or physics bug can be replayed exactly. Recording starts a new
+//! match with the settings of last match and a fresh seed. Input is stored with the number of
+//! simulation ticks since start of the match and is fed back at the same ticks when game is
+//! started with `--replay-input <file>` flag.
+
+use crate::settings::{LoadoutSettings, MatchSettings};
+use rg3d::{
+    event::{
+        DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, MouseScrollDelta,
+        VirtualKeyCode, WindowEvent,
+    },
+    utils::log::Log,
+    window::WindowId,
+};
+use serde::{Deserialize, Serialize};
+use std::{env, path::Path};
+
+pub const REPLAY_FLAG: &str = "--replay-input";
+
+/// Input that affects a match, only input that reaches level is recorded - menus are skipped.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecordedInput {
+    Key {
+        key: VirtualKeyCode,
+        scancode: u32,
+        pressed: bool,
+    },
+    MouseButton {
+        button: u32,
+        pressed: bool,
+    },
+    MouseMotion {
+        dx: f64,
+        dy: f64,
+    },
+    MouseWheel {
+        dx: f32,
+        dy: f32,
+    },
+    /// Loadout selected on loadout screen, player is deployed with it.
+    Loadout(LoadoutSettings),
+}
+
+fn to_state(pressed: bool) -> ElementState {
+    if pressed {
+        ElementState::Pressed
+    } else {
+        ElementState::Released
+    }
+}
+
+impl RecordedInput {
+    pub fn from_event(event: &Event<()>) -> Option<Self> {
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } => input.virtual_keycode.map(|key| RecordedInput::Key {
+                key,
+                scancode: input.scancode,
+                pressed: input.state == ElementState::Pressed,
+            }),
+            Event::DeviceEvent { event, .. } => match event {
+                DeviceEvent::Button { button, state } => Some(RecordedInput::MouseButton {
+                    button: *button,
+                    pressed: *state == ElementState::Pressed,
+                }),
+                DeviceEvent::MouseMotion { delta } => Some(RecordedInput::MouseMotion {
+                    dx: delta.0,
+                    dy: delta.1,
+                }),
+                DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::LineDelta(dx, dy),
+                } => Some(RecordedInput::MouseWheel { dx: *dx, dy: *dy }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Converts input back to a window event, `None` for input that isn't an event.
+    #[allow(deprecated)]
+    pub fn to_event(&self) -> Option<Event<'static, ()>> {
+        // Dummy ids are fine - level doesn't care which window or device input came from.
+        let device_id = unsafe { DeviceId::dummy() };
+        match *self {
+            RecordedInput::Key {
+                key,
+                scancode,
+                pressed,
+            } => Some(Event::WindowEvent {
+                window_id: unsafe { WindowId::dummy() },
+                event: WindowEvent::KeyboardInput {
+                    device_id,
+                    input: KeyboardInput {
+                        scancode,
+                        state: to_state(pressed),
+                        virtual_keycode: Some(key),
+                        modifiers: Default::default(),
+                    },
+                    is_synthetic: true,
+                },
+            }),
+            RecordedInput::MouseButton { button, pressed } => Some(Event::DeviceEvent {
+                device_id,
+                event: DeviceEvent::Button {
+                    button,
+                    state: to_state(pressed),
+                },
+            }),
+            RecordedInput::MouseMotion { dx, dy } => Some(Event::DeviceEvent {
+                device_id,
+                event: DeviceEvent::MouseMotion { delta: (dx, dy) },
+            }),
+            RecordedInput::MouseWheel { dx, dy } => Some(Event::DeviceEvent {
+                device_id,
+                event: DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::LineDelta(dx, dy),
+                },
+            }),
+            RecordedInput::Loadout(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimedInput {
+    /// Number of simulation ticks since start of match.
+    tick: u64,
+    input: RecordedInput,
+}
+
+fn default_bot_skill() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Recording {
+    pub seed: u64,
+    pub settings: MatchSettings,
+    /// Skill of bots picked by adaptive difficulty, rating of player changes between runs.
+    #[serde(default = "default_bot_skill")]
+    pub bot_skill: f32,
+    inputs: Vec<TimedInput>,
+}
+
+impl Recording {
+    pub fn new(seed: u64, settings: MatchSettings, bot_skill: f32) -> Self {
+        Self {
+            seed,
+            settings,
+            bot_skill,
+            inputs: Default::default(),
+        }
+    }
+
+    pub fn record(&mut self, tick: u64, input: RecordedInput) {
+        self.inputs.push(TimedInput { tick, input });
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path.as_ref()).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) {
+        match serde_json::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(path.as_ref(), data).map_err(|e| e.to_string()))
+        {
+            Ok(_) => Log::writeln(format!(
+                "Input recording saved to {}",
+                path.as_ref().display()
+            )),
+            Err(error) => Log::writeln(format!("Error saving input recording: {}", error)),
+        }
+    }
+
+    /// Path of recording passed with `--replay-input` flag.
+    pub fn path_from_args() -> Option<String> {
+        env::args().skip_while(|arg| arg != REPLAY_FLAG).nth(1)
+    }
+}
+
+/// Feeds recorded input back at the ticks it was recorded at.
+pub struct Replay {
+    inputs: Vec<TimedInput>,
+    next: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            inputs: recording.inputs,
+            next: 0,
+        }
+    }
+
+    /// Returns input recorded at given tick.
+    pub fn take(&mut self, tick: u64) -> Vec<RecordedInput> {
+        let mut inputs = Vec::new();
+        while let Some(timed) = self.inputs.get(self.next) {
+            if timed.tick > tick {
+                break;
+            }
+            inputs.push(timed.input.clone());
+            self.next += 1;
+        }
+        inputs
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.inputs.len()
+    }
+}
@@ -0,0 +1,129 @@
+//! Reverb zones. Map defines them with dummies named `Reverb_Cave`, `Reverb_Corridor` or
+//! `Reverb_OpenAir` scaled to size of a zone in meters. Sound manager sends sounds that start
+//! inside a zone to reverb of the zone, sounds outside of any zone take zone of listener, and
+//! default reverb is used when listener is outside of zones too.
+
+use rg3d::{
+    core::{
+        math::{aabb::AxisAlignedBoundingBox, vec3::Vec3},
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::graph::Graph,
+    sound::effects::{reverb::Reverb, BaseEffect, Effect},
+};
+use std::time::Duration;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReverbPreset {
+    Cave,
+    Corridor,
+    OpenAir,
+}
+
+impl ReverbPreset {
+    pub const ALL: [ReverbPreset; 3] = [
+        ReverbPreset::Cave,
+        ReverbPreset::Corridor,
+        ReverbPreset::OpenAir,
+    ];
+
+    pub fn id(self) -> u32 {
+        match self {
+            ReverbPreset::Cave => 0,
+            ReverbPreset::Corridor => 1,
+            ReverbPreset::OpenAir => 2,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(ReverbPreset::Cave),
+            1 => Ok(ReverbPreset::Corridor),
+            2 => Ok(ReverbPreset::OpenAir),
+            _ => Err(format!("Unknown reverb preset {}", id)),
+        }
+    }
+
+    fn from_node_name(name: &str) -> Option<Self> {
+        if name.starts_with("Reverb_Cave") {
+            Some(ReverbPreset::Cave)
+        } else if name.starts_with("Reverb_Corridor") {
+            Some(ReverbPreset::Corridor)
+        } else if name.starts_with("Reverb_OpenAir") {
+            Some(ReverbPreset::OpenAir)
+        } else {
+            None
+        }
+    }
+
+    /// Creates reverb effect of preset, caves echo long and loud, open air barely echoes.
+    pub fn create_effect(self) -> Effect {
+        let (gain, decay_time) = match self {
+            ReverbPreset::Cave => (0.9, 5.0),
+            ReverbPreset::Corridor => (0.6, 1.2),
+            ReverbPreset::OpenAir => (0.15, 0.4),
+        };
+        let mut base_effect = BaseEffect::default();
+        base_effect.set_gain(gain);
+        let mut reverb = Reverb::new(base_effect);
+        reverb.set_decay_time(Duration::from_secs_f32(decay_time));
+        Effect::Reverb(reverb)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReverbZone {
+    preset: ReverbPreset,
+    bounds: AxisAlignedBoundingBox,
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        Self {
+            preset: ReverbPreset::Cave,
+            bounds: Default::default(),
+        }
+    }
+}
+
+impl Visit for ReverbZone {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut preset = self.preset.id();
+        preset.visit("PresetId", visitor)?;
+        if visitor.is_reading() {
+            self.preset = ReverbPreset::from_id(preset)?;
+        }
+        self.bounds.visit("Bounds", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl ReverbZone {
+    /// Collects reverb zones of a map.
+    pub fn find_all(graph: &Graph) -> Vec<ReverbZone> {
+        graph
+            .pair_iter()
+            .filter_map(|(_, node)| {
+                let preset = ReverbPreset::from_node_name(node.name())?;
+                let scale = node.local_transform().scale();
+                let half = Vec3::new(scale.x.abs(), scale.y.abs(), scale.z.abs()).scale(0.5);
+                let position = node.global_position();
+                Some(ReverbZone {
+                    preset,
+                    bounds: AxisAlignedBoundingBox::from_min_max(position - half, position + half),
+                })
+            })
+            .collect()
+    }
+
+    pub fn preset(&self) -> ReverbPreset {
+        self.preset
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        self.bounds.is_contains_point(position)
+    }
+}
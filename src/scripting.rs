@@ -0,0 +1,204 @@
+//! Match rules scripting. Every `*.rhai` file from scripts directory is compiled when match
+//! starts, script may define any of following hooks:
+//!
+//! - `on_match_start()`
+//! - `on_kill(killer, victim)` - names of actors.
+//! - `on_item_pickup(actor, item)` - name of actor and kind of item, like `"medkit"`.
+//! - `on_tick(dt)` - called every fixed update.
+//!
+//! Scripts can use `spawn_bot(kind, name)`, `spawn_item(kind, x, y, z)`,
+//! `add_score(name, amount)` and `hud_message(text)`. API functions just send messages, so
+//! scripts can't break level in the middle of an update.
+
+use crate::{actor::ActorDescriptor, bot::BotKind, item::ItemKind, message::Message, mods};
+use rg3d::{core::math::vec3::Vec3, utils::log::Log};
+use rhai::{Dynamic, Engine, EvalAltResult, FuncArgs, ImmutableString, Scope, AST, FLOAT, INT};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+pub const SCRIPTS_DIR: &str = "data/scripts";
+
+const BOT_KINDS: [(&str, BotKind); 3] = [
+    ("mutant", BotKind::Mutant),
+    ("parasite", BotKind::Parasite),
+    ("maw", BotKind::Maw),
+];
+
+const ITEM_KINDS: [(&str, ItemKind); 13] = [
+    ("medkit", ItemKind::Medkit),
+    ("plasma", ItemKind::Plasma),
+    ("ak47_ammo", ItemKind::Ak47Ammo),
+    ("m4_ammo", ItemKind::M4Ammo),
+    ("plasma_gun", ItemKind::PlasmaGun),
+    ("ak47", ItemKind::Ak47),
+    ("m4", ItemKind::M4),
+    ("rocket_launcher", ItemKind::RocketLauncher),
+    ("silencer", ItemKind::Silencer),
+    ("extended_mag", ItemKind::ExtendedMag),
+    ("scope", ItemKind::Scope),
+    ("laser_sight", ItemKind::LaserSight),
+    ("overshield", ItemKind::Overshield),
+];
+
+fn bot_kind_from_name(name: &str) -> Option<BotKind> {
+    BOT_KINDS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, kind)| *kind)
+}
+
+fn item_kind_from_name(name: &str) -> Option<ItemKind> {
+    ITEM_KINDS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, kind)| *kind)
+}
+
+pub fn item_kind_name(kind: ItemKind) -> &'static str {
+    ITEM_KINDS
+        .iter()
+        .find(|(_, k)| *k == kind)
+        .map_or("unknown", |(name, _)| *name)
+}
+
+struct Script {
+    path: PathBuf,
+    ast: AST,
+    scope: Scope<'static>,
+}
+
+pub struct MatchScripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl MatchScripts {
+    /// Compiles every script from given directory (including scripts from mods). Scripts with
+    /// errors are skipped, so broken mod won't prevent match from starting.
+    pub fn load<P: AsRef<Path>>(dir: P, sender: Sender<Message>) -> Self {
+        let engine = create_engine(sender);
+
+        // Order of hooks must not depend on file system, files are sorted by name.
+        let paths = mods::read_dir(dir)
+            .into_iter()
+            .filter(|path| path.extension().map_or(false, |ext| ext == "rhai"));
+
+        let mut scripts = Vec::new();
+        for path in paths {
+            match engine.compile_file(path.clone()) {
+                Ok(ast) => scripts.push(Script {
+                    path,
+                    ast,
+                    scope: Scope::new(),
+                }),
+                Err(e) => Log::writeln(format!(
+                    "Unable to compile script {}: {}",
+                    path.display(),
+                    e
+                )),
+            }
+        }
+
+        Self { engine, scripts }
+    }
+
+    fn call<A: FuncArgs + Clone>(&mut self, hook: &str, args: A) {
+        for script in self.scripts.iter_mut() {
+            let result = self.engine.call_fn::<_, Dynamic>(
+                &mut script.scope,
+                &script.ast,
+                hook,
+                args.clone(),
+            );
+            if let Err(e) = result {
+                // Hooks are optional.
+                let missing_hook = match &*e {
+                    EvalAltResult::ErrorFunctionNotFound(name, _) => name.starts_with(hook),
+                    _ => false,
+                };
+                if !missing_hook {
+                    Log::writeln(format!(
+                        "Script {} failed in {}: {}",
+                        script.path.display(),
+                        hook,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn on_match_start(&mut self) {
+        self.call("on_match_start", ());
+    }
+
+    pub fn on_kill(&mut self, killer: &str, victim: &str) {
+        self.call("on_kill", (killer.to_owned(), victim.to_owned()));
+    }
+
+    pub fn on_item_pickup(&mut self, actor: &str, kind: ItemKind) {
+        self.call(
+            "on_item_pickup",
+            (actor.to_owned(), item_kind_name(kind).to_owned()),
+        );
+    }
+
+    pub fn on_tick(&mut self, dt: f32) {
+        self.call("on_tick", (dt as FLOAT,));
+    }
+}
+
+fn create_engine(sender: Sender<Message>) -> Engine {
+    let mut engine = Engine::new();
+
+    let s = sender.clone();
+    engine.register_fn(
+        "spawn_bot",
+        move |kind: ImmutableString, name: ImmutableString| match bot_kind_from_name(&kind) {
+            Some(kind) => s
+                .send(Message::SpawnActor {
+                    descriptor: ActorDescriptor::bot(kind).with_name(name.to_string()),
+                })
+                .unwrap(),
+            None => Log::writeln(format!("Script tried to spawn unknown bot {}", kind)),
+        },
+    );
+
+    let s = sender.clone();
+    engine.register_fn(
+        "spawn_item",
+        move |kind: ImmutableString, x: FLOAT, y: FLOAT, z: FLOAT| match item_kind_from_name(&kind)
+        {
+            Some(kind) => s
+                .send(Message::SpawnItem {
+                    kind,
+                    position: Vec3::new(x as f32, y as f32, z as f32),
+                    adjust_height: true,
+                    lifetime: None,
+                })
+                .unwrap(),
+            None => Log::writeln(format!("Script tried to spawn unknown item {}", kind)),
+        },
+    );
+
+    let s = sender.clone();
+    engine.register_fn("add_score", move |name: ImmutableString, amount: INT| {
+        s.send(Message::AdjustScore {
+            name: name.to_string(),
+            amount: amount as i32,
+        })
+        .unwrap()
+    });
+
+    engine.register_fn("hud_message", move |text: ImmutableString| {
+        sender
+            .send(Message::AddNotification {
+                text: text.to_string(),
+            })
+            .unwrap()
+    });
+
+    engine
+}
@@ -0,0 +1,82 @@
+//! Standings of a session - consecutive matches played without returning to main menu. Each
+//! finished match adds kills, deaths and wins of its participants, end-of-match screen shows
+//! the standings on a separate tab. Standings live in memory only and are cleared when main
+//! menu is shown.
+
+use crate::{
+    actor::ActorContainer,
+    leader_board::{LeaderBoard, MatchSummary, MatchWinner},
+};
+use std::collections::HashMap;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct SessionEntry {
+    pub matches: u32,
+    pub wins: u32,
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+#[derive(Default)]
+pub struct SessionStandings {
+    entries: HashMap<String, SessionEntry>,
+    matches: u32,
+}
+
+impl SessionStandings {
+    /// Adds results of a finished match. Members of a winning team get a win each, actors
+    /// that left before end of match are credited with their score but not with a win.
+    pub fn add_match(
+        &mut self,
+        leader_board: &LeaderBoard,
+        actors: &ActorContainer,
+        summary: &MatchSummary,
+    ) {
+        self.matches += 1;
+
+        for (name, score) in leader_board.values() {
+            let entry = self.entries.entry(name.clone()).or_default();
+            entry.matches += 1;
+            entry.kills += score.kills;
+            entry.deaths += score.deaths;
+        }
+
+        match &summary.winner {
+            MatchWinner::Actor { name, .. } => {
+                self.entries.entry(name.clone()).or_default().wins += 1;
+            }
+            MatchWinner::Team { team, .. } => {
+                for actor in actors.iter().filter(|actor| actor.team() == *team) {
+                    self.entries.entry(actor.name.clone()).or_default().wins += 1;
+                }
+            }
+            MatchWinner::Draw => (),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.matches = 0;
+    }
+
+    /// Amount of finished matches in the session.
+    pub fn matches(&self) -> u32 {
+        self.matches
+    }
+
+    /// Returns participants ordered by wins, ties are broken by kills.
+    pub fn sorted(&self) -> Vec<(&str, SessionEntry)> {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), *entry))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a_name, a), (b_name, b)| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.kills.cmp(&a.kills))
+                .then(a_name.cmp(b_name))
+        });
+        entries
+    }
+}
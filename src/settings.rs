@@ -1,82 +1,544 @@
-use crate::assets;
+use crate::{
+    assets,
+    bot::BotDifficulty,
+    lighting::LightingPreset,
+    mods::{self, ModSettings},
+    weapon::WeaponKind,
+    CaptureTheFlag, DeathMatch, Duel, KingOfTheHill, LastManStanding, MatchOptions, Oddball,
+    Practice, TeamDeathMatch, TimeTrial, Tutorial,
+};
 use rg3d::{sound::context, sound::context::Context, utils::log::Log};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Version of settings layout, must be incremented each time when layout changes
+/// in incompatible way, and appropriate migration step must be added to `migrate`.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// How positional sounds are mixed into output channels.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpeakerMode {
+    /// Head-related transfer function, best for headphones.
+    Hrtf,
+    /// Plain left-right panning, for speakers.
+    Stereo,
+    /// Every sound plays equally in both channels and only gets quieter with distance, for
+    /// players with hearing loss in one ear or with a single speaker.
+    Mono,
+}
+
+impl Default for SpeakerMode {
+    fn default() -> Self {
+        SpeakerMode::Hrtf
+    }
+}
+
+/// Max relative deviation of pitch of repeated sounds, per category of sounds. Zero turns
+/// variation off, see `sound_variation` module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundVariationSettings {
+    pub footsteps: f32,
+    pub shots: f32,
+    pub impacts: f32,
+    pub other: f32,
+}
+
+impl Default for SoundVariationSettings {
+    fn default() -> Self {
+        Self {
+            footsteps: 0.08,
+            shots: 0.04,
+            impacts: 0.1,
+            other: 0.0,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoundSettings {
     pub sound_volume: f32,
-    pub hrtf: bool,
+    pub speaker_mode: SpeakerMode,
+    pub variation: SoundVariationSettings,
 }
 
 impl Default for SoundSettings {
     fn default() -> Self {
         Self {
             sound_volume: 1.0,
-            hrtf: true,
+            speaker_mode: SpeakerMode::default(),
+            variation: SoundVariationSettings::default(),
         }
     }
 }
 
 impl SoundSettings {
-    pub fn is_hrtf(sound_context: &Context) -> bool {
-        if let rg3d::sound::renderer::Renderer::HrtfRenderer(_) = sound_context.renderer() {
-            true
-        } else {
-            false
+    /// Sets renderer of sound context for given mode. Mono has no renderer of its own - it
+    /// uses default one and sound manager plays sounds without position (see `SoundManager`).
+    pub fn apply_speaker_mode(sound_context: &mut Context, mode: SpeakerMode) {
+        match mode {
+            SpeakerMode::Hrtf => Self::hrtf_on(sound_context),
+            SpeakerMode::Stereo | SpeakerMode::Mono => Self::hrtf_off(sound_context),
         }
     }
 
-    pub fn hrtf_on(sound_context: &mut Context) {
-        let hrtf_sphere = rg3d::sound::hrtf::HrirSphere::from_file(
-            assets::sounds::HRTF_HRIR,
+    /// Switches to HRTF renderer, default renderer is kept if HRIR sphere can't be loaded.
+    fn hrtf_on(sound_context: &mut Context) {
+        match rg3d::sound::hrtf::HrirSphere::from_file(
+            mods::resolve(assets::sounds::HRTF_HRIR),
             context::SAMPLE_RATE,
-        )
-        .unwrap();
-        sound_context.set_renderer(rg3d::sound::renderer::Renderer::HrtfRenderer(
-            rg3d::sound::renderer::hrtf::HrtfRenderer::new(hrtf_sphere),
-        ));
+        ) {
+            Ok(hrtf_sphere) => {
+                sound_context.set_renderer(rg3d::sound::renderer::Renderer::HrtfRenderer(
+                    rg3d::sound::renderer::hrtf::HrtfRenderer::new(hrtf_sphere),
+                ))
+            }
+            Err(e) => Log::writeln(format!("Unable to load HRIR sphere: {:?}", e)),
+        }
     }
 
-    pub fn hrtf_off(sound_context: &mut Context) {
+    fn hrtf_off(sound_context: &mut Context) {
         sound_context.set_renderer(rg3d::sound::renderer::Renderer::Default);
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// Time between autosaves in seconds.
+    pub interval: f32,
+    /// Amount of rotating autosave slots.
+    pub slot_count: usize,
+}
+
+/// Autosave can't happen more often than this, in seconds.
+const MIN_AUTOSAVE_INTERVAL: f32 = 30.0;
+
+impl AutosaveSettings {
+    /// Brings values from settings file into sane range.
+    fn clamped(mut self) -> Self {
+        // `max` also replaces NaN.
+        self.interval = self.interval.max(MIN_AUTOSAVE_INTERVAL);
+        self
+    }
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 300.0,
+            slot_count: 3,
+        }
+    }
+}
+
+/// Limits of the game side of scene cost, complement to renderer quality settings. Lower
+/// values help on low-end machines in big fights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualitySettings {
+    /// Max amount of projectiles with enabled dynamic light, lights of projectiles closest
+    /// to viewer are preferred.
+    pub max_projectile_lights: usize,
+    /// Max amount of simultaneously playing short-living particle effects (impacts, item
+    /// appearance).
+    pub max_particle_effects: usize,
+    /// Max amount of dead bots that are still playing death animation, oldest of the rest
+    /// are removed right away. Dead player isn't counted.
+    pub max_corpses: usize,
+    /// Distance from viewer at which decorative props of big maps are loaded.
+    pub prop_stream_distance: f32,
+    /// Lowers shadow distances when frame rate drops below target, see `dynamic_quality`.
+    pub dynamic_quality: bool,
+    pub target_fps: f32,
+    /// Rain, snow and dust particles and their ambient sounds on maps that have them.
+    pub weather: bool,
+    /// Accessibility option for photosensitive players: muzzle flashes and lights of
+    /// projectiles fade in and out instead of flashing, see `effects::LightFader`.
+    pub reduce_flashes: bool,
+}
 
-    pub fn get_from_engine(sound_context: &Context) -> Self {
+impl Default for QualitySettings {
+    fn default() -> Self {
         Self {
-            sound_volume: sound_context.master_gain(),
-            hrtf: Self::is_hrtf(sound_context),
+            max_projectile_lights: 8,
+            max_particle_effects: 32,
+            max_corpses: 4,
+            prop_stream_distance: 40.0,
+            dynamic_quality: false,
+            target_fps: 60.0,
+            weather: true,
+            reduce_flashes: false,
+        }
+    }
+}
+
+/// Weapons that player spawns with, selected on loadout screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadoutSettings {
+    pub primary: WeaponKind,
+    pub secondary: WeaponKind,
+}
+
+impl Default for LoadoutSettings {
+    fn default() -> Self {
+        Self {
+            primary: WeaponKind::M4,
+            secondary: WeaponKind::PlasmaRifle,
+        }
+    }
+}
+
+impl LoadoutSettings {
+    pub fn weapons(&self) -> Vec<WeaponKind> {
+        if self.primary == self.secondary {
+            vec![self.primary]
+        } else {
+            vec![self.primary, self.secondary]
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchMode {
+    DeathMatch,
+    TeamDeathMatch,
+    CaptureTheFlag,
+    Practice,
+    Tutorial,
+    Oddball,
+    KingOfTheHill,
+    TimeTrial,
+    Duel,
+    LastManStanding,
+    TeamLastManStanding,
+}
+
+impl MatchMode {
+    /// Practice, tutorial and time trial last until player leaves them.
+    pub fn has_limits(self) -> bool {
+        self != MatchMode::Practice && self != MatchMode::Tutorial && self != MatchMode::TimeTrial
+    }
+}
+
+pub const MAX_TIME_LIMIT: f32 = 120.0;
+pub const MAX_SCORE_LIMIT: u32 = 500;
+pub const MAX_BOT_COUNT: u32 = 16;
+/// Bounds of handicap percents, see `Handicap`.
+pub const MIN_HANDICAP: u32 = 25;
+pub const MAX_HANDICAP: u32 = 200;
+
+/// Configuration of a match as it was selected in match menu, last used one is remembered
+/// across runs. Zero time or score limit means that limit is disabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSettings {
+    pub mode: MatchMode,
+    /// Time limit in minutes.
+    pub time_limit: f32,
+    /// Frag limit for death match modes, flag limit for capture the flag, hold limit for
+    /// oddball and king of the hill, number of rounds of duel or lives of last man standing.
+    pub score_limit: u32,
+    /// Path to map model.
+    pub map: String,
+    pub bot_count: u32,
+    pub bot_difficulty: BotDifficulty,
+    /// Skill of bots follows rating of player, see `rating` module.
+    pub adaptive_difficulty: bool,
+    /// Waves of melee zombies spawn and rush to player, see `zombie_rush` module.
+    pub zombie_rush: bool,
+    /// Kill streaks give rewards, see `kill_streak` module.
+    pub streak_rewards: bool,
+    /// Flashlight of player drains battery and has to recharge.
+    pub flashlight_battery: bool,
+    /// Dead player respawns after a delay, otherwise it waits until player presses jump.
+    pub auto_respawn: bool,
+    /// Damage mutators, see `damage_modifier` module.
+    pub vampire: bool,
+    pub no_self_damage: bool,
+    pub double_damage: bool,
+    /// Health and ammo slide toward a nearby moving player, turn it off for competitive
+    /// matches.
+    pub pickup_magnetism: bool,
+    /// Killed bots drop ammo and health that despawns after a while.
+    pub bot_drops: bool,
+    /// Handicap of player and of bots in percents of max health and of damage dealt.
+    pub player_health_percent: u32,
+    pub player_damage_percent: u32,
+    pub bot_health_percent: u32,
+    pub bot_damage_percent: u32,
+    pub lighting: LightingPreset,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            mode: MatchMode::DeathMatch,
+            time_limit: 10.0,
+            score_limit: 30,
+            map: assets::models::maps::DM6.to_owned(),
+            bot_count: 3,
+            bot_difficulty: BotDifficulty::Normal,
+            adaptive_difficulty: true,
+            zombie_rush: false,
+            streak_rewards: false,
+            pickup_magnetism: true,
+            bot_drops: false,
+            player_health_percent: 100,
+            player_damage_percent: 100,
+            bot_health_percent: 100,
+            bot_damage_percent: 100,
+            flashlight_battery: false,
+            auto_respawn: true,
+            vampire: false,
+            no_self_damage: false,
+            double_damage: false,
+            lighting: LightingPreset::Day,
+        }
+    }
+}
+
+impl MatchSettings {
+    /// Checks that match with these settings can be started and will end eventually.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.time_limit < 0.0 || self.time_limit > MAX_TIME_LIMIT {
+            return Err(format!(
+                "Time limit must be between 0 and {} minutes",
+                MAX_TIME_LIMIT
+            ));
+        }
+        if self.score_limit > MAX_SCORE_LIMIT {
+            return Err(format!("Score limit must not exceed {}", MAX_SCORE_LIMIT));
+        }
+        if self.time_limit == 0.0 && self.score_limit == 0 && self.mode.has_limits() {
+            return Err("Either time limit or score limit must be set".to_owned());
+        }
+        if self.bot_count > MAX_BOT_COUNT {
+            return Err(format!("There can be at most {} bots", MAX_BOT_COUNT));
+        }
+        let handicaps = [
+            self.player_health_percent,
+            self.player_damage_percent,
+            self.bot_health_percent,
+            self.bot_damage_percent,
+        ];
+        if handicaps
+            .iter()
+            .any(|percent| *percent < MIN_HANDICAP || *percent > MAX_HANDICAP)
+        {
+            return Err(format!(
+                "Handicaps must be between {}% and {}%",
+                MIN_HANDICAP, MAX_HANDICAP
+            ));
+        }
+        if self.mode == MatchMode::Duel && self.score_limit == 0 {
+            return Err("Duel needs at least one round".to_owned());
+        }
+        let last_man_standing = matches!(
+            self.mode,
+            MatchMode::LastManStanding | MatchMode::TeamLastManStanding
+        );
+        if last_man_standing && self.score_limit == 0 {
+            return Err("Last Man Standing needs at least one life".to_owned());
+        }
+        if last_man_standing && self.bot_count == 0 {
+            return Err("Last Man Standing needs at least one bot".to_owned());
+        }
+        if self.mode == MatchMode::TeamDeathMatch && self.bot_count == 0 {
+            return Err("Team Deathmatch needs at least one bot".to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn options(&self) -> MatchOptions {
+        let time_limit_secs = self.time_limit * 60.0;
+        match self.mode {
+            MatchMode::DeathMatch => MatchOptions::DeathMatch(DeathMatch {
+                time_limit_secs,
+                frag_limit: self.score_limit,
+            }),
+            MatchMode::TeamDeathMatch => MatchOptions::TeamDeathMatch(TeamDeathMatch {
+                time_limit_secs,
+                team_frag_limit: self.score_limit,
+            }),
+            MatchMode::CaptureTheFlag => MatchOptions::CaptureTheFlag(CaptureTheFlag {
+                time_limit_secs,
+                flag_limit: self.score_limit,
+            }),
+            MatchMode::Practice => MatchOptions::Practice(Practice),
+            MatchMode::Tutorial => MatchOptions::Tutorial(Tutorial),
+            MatchMode::TimeTrial => MatchOptions::TimeTrial(TimeTrial),
+            MatchMode::Oddball => MatchOptions::Oddball(Oddball {
+                time_limit_secs,
+                hold_limit: self.score_limit,
+            }),
+            MatchMode::KingOfTheHill => MatchOptions::KingOfTheHill(KingOfTheHill {
+                time_limit_secs,
+                hold_limit: self.score_limit,
+            }),
+            MatchMode::Duel => MatchOptions::Duel(Duel {
+                time_limit_secs,
+                rounds: self.score_limit,
+            }),
+            MatchMode::LastManStanding => MatchOptions::LastManStanding(LastManStanding {
+                time_limit_secs,
+                lives: self.score_limit,
+                teams: false,
+            }),
+            MatchMode::TeamLastManStanding => MatchOptions::LastManStanding(LastManStanding {
+                time_limit_secs,
+                lives: self.score_limit,
+                teams: true,
+            }),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub renderer: rg3d::renderer::QualitySettings,
     #[serde(default)]
+    pub quality: QualitySettings,
+    #[serde(default)]
     pub controls: crate::control_scheme::ControlScheme,
     #[serde(default)]
     pub sound: SoundSettings,
+    #[serde(default)]
+    pub autosave: AutosaveSettings,
+    #[serde(default)]
+    pub last_match: MatchSettings,
+    #[serde(default)]
+    pub mods: ModSettings,
+    #[serde(default)]
+    pub loadout: LoadoutSettings,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             renderer: rg3d::renderer::QualitySettings::default(),
+            quality: QualitySettings::default(),
             controls: crate::control_scheme::ControlScheme::default(),
             sound: SoundSettings::default(),
+            autosave: AutosaveSettings::default(),
+            last_match: MatchSettings::default(),
+            mods: ModSettings::default(),
+            loadout: LoadoutSettings::default(),
+        }
+    }
+}
+
+/// Reads a section of settings field-by-field: every field that is missing or has invalid
+/// value is replaced with its default value, unknown fields are ignored. This works for types
+/// without serde defaults (like engine settings) too.
+fn read_section<T>(root: &Value, name: &str) -> T
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let defaults = T::default();
+
+    let mut merged = match serde_json::to_value(&defaults) {
+        Ok(merged) => merged,
+        Err(_) => return defaults,
+    };
+
+    match (&mut merged, root.get(name)) {
+        (Value::Object(merged), Some(Value::Object(section))) => {
+            for (field, value) in section {
+                if let Some(default_value) = merged.get(field).cloned() {
+                    merged.insert(field.clone(), value.clone());
+                    // Check that new value is valid, and revert it if it is not.
+                    if serde_json::from_value::<T>(Value::Object(merged.clone())).is_err() {
+                        Log::writeln(format!(
+                            "Invalid value of settings field {}.{}, using default",
+                            name, field
+                        ));
+                        merged.insert(field.clone(), default_value);
+                    }
+                } else {
+                    Log::writeln(format!("Unknown settings field {}.{} ignored", name, field));
+                }
+            }
+        }
+        (merged, Some(value)) => {
+            if serde_json::from_value::<T>(value.clone()).is_ok() {
+                *merged = value.clone();
+            }
         }
+        (_, None) => Log::writeln(format!("Settings section {} is missing", name)),
+    }
+
+    serde_json::from_value(merged).unwrap_or(defaults)
+}
+
+/// Brings settings file of older version to current layout.
+fn migrate(root: &mut Value, mut version: u32) {
+    while version < SETTINGS_VERSION {
+        // Settings prior to versioning (version 0) had same layout as version 1, so there is
+        // nothing to convert for them. Every future layout change must add its step here.
+        if version == 1 {
+            // HRTF switch became speaker mode.
+            if let Some(Value::Object(sound)) = root.get_mut("sound") {
+                if let Some(hrtf) = sound.remove("hrtf").and_then(|hrtf| hrtf.as_bool()) {
+                    let mode = if hrtf {
+                        SpeakerMode::Hrtf
+                    } else {
+                        SpeakerMode::Stereo
+                    };
+                    if let Ok(mode) = serde_json::to_value(mode) {
+                        sound.insert("speaker_mode".to_owned(), mode);
+                    }
+                }
+            }
+        }
+        version += 1;
+        Log::writeln(format!("Settings migrated to version {}", version));
+    }
+
+    if let Value::Object(root) = root {
+        root.insert("version".to_owned(), Value::from(SETTINGS_VERSION));
     }
 }
 
 impl Settings {
-    pub fn load_from_file(filename: &str) -> Self {
-        if let Ok(Ok(settings)) = std::fs::read_to_string(std::path::Path::new(filename))
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let filename = path.as_ref().display();
+        if let Ok(Ok(mut root)) = std::fs::read_to_string(path.as_ref())
             .as_ref()
-            .and_then(|f| serde::export::Ok(serde_json::from_str(f)))
+            .map(|f| serde_json::from_str::<Value>(f))
         {
+            let version = root.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+            if version > SETTINGS_VERSION {
+                Log::writeln(format!(
+                    "Settings file {} has newer version {}, unknown fields will be ignored",
+                    filename, version
+                ));
+            } else {
+                migrate(&mut root, version);
+            }
+
             Log::writeln("Successfully loaded settings".to_string());
-            settings
+
+            let mut controls: crate::control_scheme::ControlScheme =
+                read_section(&root, "controls");
+            if root.pointer("/controls/melee").is_none() {
+                controls.resolve_melee_conflict();
+            }
+
+            Self {
+                version: SETTINGS_VERSION,
+                renderer: read_section(&root, "renderer"),
+                quality: read_section(&root, "quality"),
+                controls,
+                sound: read_section(&root, "sound"),
+                autosave: read_section::<AutosaveSettings>(&root, "autosave").clamped(),
+                last_match: read_section(&root, "last_match"),
+                mods: read_section(&root, "mods"),
+                loadout: read_section(&root, "loadout"),
+            }
         } else {
             // Unable to read settings file, so fall back to defaults
             Log::writeln(format!(
@@ -87,10 +549,11 @@ impl Settings {
         }
     }
 
-    pub fn write_to_file(&self, filename: &str) {
-        if let Err(error) = serde_json::to_string(self).and_then(|data| {
-            serde::export::Ok(std::fs::write(std::path::Path::new(filename), data))
-        }) {
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) {
+        let filename = path.as_ref().display();
+        if let Err(error) = serde_json::to_string(self)
+            .and_then(|data| serde::export::Ok(std::fs::write(path.as_ref(), data)))
+        {
             Log::writeln(format!("Error saving settings: {}", error))
         } else {
             Log::writeln(format!("Succesfully saved settings to {}", filename));
@@ -0,0 +1,281 @@
+//! Targets of practice mode. Targets are meshes of a map named `Target_Static` and
+//! `Target_Moving`, moving target slides back and forth between its initial position and a node
+//! named `<target name>_End` (or a few meters to the side if there is no such node). Targets
+//! can't be destroyed, they only report where they were hit and how hard, shooting range sums
+//! damage of recent hits to show damage per second.
+
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::{Handle, Pool},
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    physics::{
+        convex_shape::{BoxShape, ConvexShape},
+        rigid_body::RigidBody,
+        Physics,
+    },
+    scene::{node::Node, Scene},
+};
+
+/// Path length of moving target without explicit end node.
+const DEFAULT_MOVE_DISTANCE: f32 = 3.0;
+/// Speed of moving targets in meters per second.
+const MOVE_SPEED: f32 = 2.0;
+/// Damage dealt during this amount of seconds is used to calculate damage per second.
+const DPS_WINDOW: f32 = 3.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HitZone {
+    Head,
+    Body,
+    Legs,
+}
+
+impl HitZone {
+    /// Picks zone by height of hit, where 0.0 is bottom of a target and 1.0 is top of it.
+    fn from_height(height: f32) -> Self {
+        if height > 0.8 {
+            HitZone::Head
+        } else if height > 0.4 {
+            HitZone::Body
+        } else {
+            HitZone::Legs
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HitZone::Head => "Head",
+            HitZone::Body => "Body",
+            HitZone::Legs => "Legs",
+        }
+    }
+}
+
+pub struct Target {
+    model: Handle<Node>,
+    body: Handle<RigidBody>,
+    half_height: f32,
+    begin: Vec3,
+    end: Vec3,
+    /// Position on path, 0.0 is at begin and 1.0 is at end.
+    progress: f32,
+    /// 1.0 when target moves towards end, -1.0 when it moves back.
+    direction: f32,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self {
+            model: Handle::NONE,
+            body: Handle::NONE,
+            half_height: 0.0,
+            begin: Vec3::ZERO,
+            end: Vec3::ZERO,
+            progress: 0.0,
+            direction: 1.0,
+        }
+    }
+}
+
+impl Target {
+    /// Creates rigid body for given mesh of a map and binds the mesh to it. Static target
+    /// has no end of path.
+    pub fn new(model: Handle<Node>, end: Option<Vec3>, scene: &mut Scene) -> Self {
+        let node = &scene.graph[model];
+        let bounds = node.as_mesh().world_bounding_box();
+        let half_extents = (bounds.max - bounds.min).scale(0.5);
+        let begin = node.global_position();
+
+        let mut body = RigidBody::new(ConvexShape::Box(BoxShape::new(half_extents)));
+        body.set_position(begin);
+        let body = scene.physics.add_body(body);
+        scene.physics_binder.bind(model, body);
+
+        Self {
+            model,
+            body,
+            half_height: half_extents.y,
+            begin,
+            end: end.unwrap_or(begin),
+            ..Default::default()
+        }
+    }
+
+    /// Makes moving target from given mesh, it moves to the side if map doesn't set end
+    /// of path.
+    pub fn new_moving(model: Handle<Node>, end: Option<Vec3>, scene: &mut Scene) -> Self {
+        let node = &scene.graph[model];
+        let side = node.side_vector().normalized().unwrap_or(Vec3::RIGHT);
+        let end = end.unwrap_or_else(|| node.global_position() + side.scale(DEFAULT_MOVE_DISTANCE));
+        Self::new(model, Some(end), scene)
+    }
+
+    fn is_moving(&self) -> bool {
+        self.begin != self.end
+    }
+
+    fn position(&self) -> Vec3 {
+        self.begin + (self.end - self.begin).scale(self.progress)
+    }
+
+    /// Moves target along its path. Position is set explicitly every frame, so target
+    /// doesn't fall and can't be pushed by anything.
+    fn update(&mut self, physics: &mut Physics, dt: f32) {
+        if self.is_moving() {
+            let length = self.begin.distance(&self.end);
+            self.progress += self.direction * MOVE_SPEED * dt / length;
+            if self.progress >= 1.0 {
+                self.progress = 1.0;
+                self.direction = -1.0;
+            } else if self.progress <= 0.0 {
+                self.progress = 0.0;
+                self.direction = 1.0;
+            }
+        }
+
+        let position = self.position();
+        let body = physics.borrow_body_mut(self.body);
+        body.set_position(position);
+        body.set_velocity(Vec3::ZERO);
+    }
+
+    fn reset(&mut self) {
+        self.progress = 0.0;
+        self.direction = 1.0;
+    }
+
+    fn hit_zone(&self, physics: &Physics, position: Vec3) -> HitZone {
+        let center = physics.borrow_body(self.body).get_position();
+        let bottom = center.y - self.half_height;
+        HitZone::from_height((position.y - bottom) / (2.0 * self.half_height).max(0.001))
+    }
+}
+
+impl Visit for Target {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.half_height.visit("HalfHeight", visitor)?;
+        self.begin.visit("Begin", visitor)?;
+        self.end.visit("End", visitor)?;
+        self.progress.visit("Progress", visitor)?;
+        self.direction.visit("Direction", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Targets and statistics of hits. Statistics are not saved - practice session starts over
+/// after load.
+pub struct ShootingRange {
+    targets: Pool<Target>,
+    /// Time and amount of damage of hits within DPS window.
+    recent_hits: Vec<(f32, f32)>,
+    total_damage: f32,
+    hit_count: u32,
+    last_hit: Option<(HitZone, f32)>,
+}
+
+impl Default for ShootingRange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShootingRange {
+    pub fn new() -> Self {
+        Self {
+            targets: Pool::new(),
+            recent_hits: Vec::new(),
+            total_damage: 0.0,
+            hit_count: 0,
+            last_hit: None,
+        }
+    }
+
+    pub fn add(&mut self, target: Target) -> Handle<Target> {
+        self.targets.spawn(target)
+    }
+
+    /// Returns handle of target that owns given rigid body or `Handle::NONE`.
+    pub fn find_by_body(&self, body: Handle<RigidBody>) -> Handle<Target> {
+        self.targets
+            .pair_iter()
+            .find(|(_, target)| target.body == body)
+            .map(|(handle, _)| handle)
+            .unwrap_or(Handle::NONE)
+    }
+
+    pub fn update(&mut self, physics: &mut Physics, time: f32, dt: f32) {
+        for target in self.targets.iter_mut() {
+            target.update(physics, dt);
+        }
+        self.recent_hits
+            .retain(|&(hit_time, _)| time - hit_time <= DPS_WINDOW);
+    }
+
+    pub fn hit(
+        &mut self,
+        physics: &Physics,
+        target: Handle<Target>,
+        position: Vec3,
+        amount: f32,
+        time: f32,
+    ) {
+        if !self.targets.is_valid_handle(target) {
+            return;
+        }
+        let zone = self.targets.borrow(target).hit_zone(physics, position);
+        self.recent_hits.push((time, amount));
+        self.total_damage += amount;
+        self.hit_count += 1;
+        self.last_hit = Some((zone, amount));
+    }
+
+    pub fn dps(&self) -> f32 {
+        self.recent_hits
+            .iter()
+            .map(|&(_, amount)| amount)
+            .sum::<f32>()
+            / DPS_WINDOW
+    }
+
+    /// Returns moving targets to start of their paths and clears statistics.
+    pub fn reset(&mut self) {
+        for target in self.targets.iter_mut() {
+            target.reset();
+        }
+        self.recent_hits.clear();
+        self.total_damage = 0.0;
+        self.hit_count = 0;
+        self.last_hit = None;
+    }
+
+    /// Text of DPS meter and last hit for HUD.
+    pub fn report(&self) -> String {
+        let mut text = format!(
+            "DPS {:.1}   Total {:.0}   Hits {}",
+            self.dps(),
+            self.total_damage,
+            self.hit_count
+        );
+        if let Some((zone, amount)) = self.last_hit {
+            text += &format!("\nLast hit: {} {:.0}", zone.name(), amount);
+        }
+        text
+    }
+}
+
+impl Visit for ShootingRange {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.targets.visit("Targets", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,108 @@
+//! Variation of repeated sounds. A sound played many times in a row (footsteps, automatic fire)
+//! sounds mechanical when every instance is identical, so each instance gets slightly random
+//! pitch and gain, and sounds that have several recordings cycle through them. Recordings of a
+//! sound are either a known group (footsteps) or files next to it with numeric suffix:
+//! `ak47.ogg`, `ak47_2.ogg`, `ak47_3.ogg` and so on - mods can add variants this way.
+
+use crate::{assets, mods, settings::SoundVariationSettings};
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Known groups of recordings of same sound.
+const GROUPS: [&[&str]; 1] = [&assets::sounds::footsteps::SHOE_STONE];
+/// Gain varies more than pitch - small changes of pitch are much easier to hear.
+const GAIN_TO_PITCH_VARIATION: f32 = 2.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SoundCategory {
+    Footstep,
+    Shot,
+    Impact,
+    Other,
+}
+
+impl SoundCategory {
+    /// Max relative deviation of pitch of sounds of the category.
+    fn variation(self, settings: &SoundVariationSettings) -> f32 {
+        let amount = match self {
+            SoundCategory::Footstep => settings.footsteps,
+            SoundCategory::Shot => settings.shots,
+            SoundCategory::Impact => settings.impacts,
+            SoundCategory::Other => settings.other,
+        };
+        amount.max(0.0).min(0.5)
+    }
+}
+
+/// Finds files `<name>_2.<ext>`, `<name>_3.<ext>`... next to given sound.
+fn find_numbered_variants(path: &Path) -> Vec<PathBuf> {
+    let mut variants = vec![path.to_owned()];
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => (stem.to_string_lossy(), extension.to_string_lossy()),
+        _ => return variants,
+    };
+    for n in 2.. {
+        let variant = path.with_file_name(format!("{}_{}.{}", stem, n, extension));
+        if !mods::resolve(&variant).exists() {
+            break;
+        }
+        variants.push(variant);
+    }
+    variants
+}
+
+#[derive(Default)]
+pub struct SoundVariation {
+    settings: SoundVariationSettings,
+    /// Recordings of sounds, found on first play of a sound.
+    variants: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Index of next recording of each sound.
+    next: HashMap<PathBuf, usize>,
+}
+
+impl SoundVariation {
+    pub fn new(settings: SoundVariationSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn settings(&self) -> &SoundVariationSettings {
+        &self.settings
+    }
+
+    /// Returns next recording of a sound in round-robin order.
+    pub fn next_variant(&mut self, path: &Path) -> PathBuf {
+        let variants = self.variants.entry(path.to_owned()).or_insert_with(|| {
+            match GROUPS
+                .iter()
+                .find(|group| group.iter().any(|p| Path::new(p) == path))
+            {
+                Some(group) => group.iter().map(PathBuf::from).collect(),
+                None => find_numbered_variants(path),
+            }
+        });
+        let next = self.next.entry(path.to_owned()).or_insert(0);
+        let variant = variants[*next % variants.len()].clone();
+        *next = (*next + 1) % variants.len();
+        variant
+    }
+
+    /// Returns random pitch and gain multipliers for a sound of given category.
+    pub fn randomize(&self, category: SoundCategory) -> (f32, f32) {
+        let variation = category.variation(&self.settings);
+        if variation <= 0.0 {
+            return (1.0, 1.0);
+        }
+        // Cosmetic randomness, it must not touch generator of simulation.
+        let mut rng = rand::thread_rng();
+        let pitch = 1.0 + rng.gen_range(-variation, variation);
+        let gain_variation = variation * GAIN_TO_PITCH_VARIATION;
+        let gain = 1.0 - rng.gen_range(0.0, gain_variation.min(0.9));
+        (pitch, gain)
+    }
+}
@@ -0,0 +1,92 @@
+//! Ambient soundscape of a map - looping sounds placed around the map (wind, machinery hum,
+//! dripping water). Emitters are stored next to the map model in a file with `ambient.json`
+//! extension (`dm6.ambient.json` for `dm6.fbx`), mods can override it:
+//!
+//! ```json
+//! {
+//!     "emitters": [
+//!         { "sound": "data/sounds/ambient/wind.ogg", "position": [0.0, 6.0, 0.0], "radius": 20.0 },
+//!         { "sound": "data/sounds/ambient/hum.ogg", "position": [12.0, 1.0, -4.0], "radius": 3.0, "gain": 0.6 }
+//!     ]
+//! }
+//! ```
+//!
+//! Level reads the file and sends emitters to sound manager, which owns the sources.
+
+use crate::mods;
+use rg3d::core::math::vec3::Vec3;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+fn default_gain() -> f32 {
+    1.0
+}
+
+fn default_rolloff_factor() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+pub struct AmbientEmitter {
+    /// Path to a sound file, it is played in a loop.
+    sound: PathBuf,
+    position: [f32; 3],
+    /// Distance at which sound starts to fade out.
+    radius: f32,
+    #[serde(default = "default_gain")]
+    gain: f32,
+    #[serde(default = "default_rolloff_factor")]
+    rolloff_factor: f32,
+}
+
+impl AmbientEmitter {
+    pub fn sound(&self) -> &Path {
+        &self.sound
+    }
+
+    pub fn position(&self) -> Vec3 {
+        Vec3::new(self.position[0], self.position[1], self.position[2])
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    pub fn rolloff_factor(&self) -> f32 {
+        self.rolloff_factor
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Soundscape {
+    emitters: Vec<AmbientEmitter>,
+}
+
+impl Soundscape {
+    /// Returns path of soundscape file of a map.
+    pub fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("ambient.json")
+    }
+
+    /// Loads soundscape of a map, map without soundscape file has empty soundscape.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        if !path.exists() {
+            return Ok(Self {
+                emitters: Default::default(),
+            });
+        }
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read soundscape {}: {}", path.display(), e))?;
+        serde_json::from_str::<Soundscape>(&data)
+            .map_err(|e| format!("Soundscape {} is corrupted: {}", path.display(), e))
+    }
+
+    pub fn into_emitters(self) -> Vec<AmbientEmitter> {
+        self.emitters
+    }
+}
@@ -0,0 +1,99 @@
+//! Uniform grid that splits space into cubic cells, so queries like "every actor within
+//! radius" have to check only entries from a few cells instead of every entity on level.
+//! Entities are moving all the time, so grid is simply rebuilt every frame.
+
+use rg3d::core::math::vec3::Vec3;
+use std::collections::HashMap;
+
+type CellKey = (i32, i32, i32);
+
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<CellKey, Vec<(T, Vec3)>>,
+    len: usize,
+}
+
+impl<T: Copy> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: Default::default(),
+            len: 0,
+        }
+    }
+
+    fn key(&self, position: Vec3) -> CellKey {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Replaces content of the grid, memory of cells is reused.
+    pub fn rebuild<I: IntoIterator<Item = (T, Vec3)>>(&mut self, entries: I) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.len = 0;
+        for (value, position) in entries {
+            let key = self.key(position);
+            self.cells
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .push((value, position));
+            self.len += 1;
+        }
+    }
+
+    /// Collects every entry within given radius around center, `result` is cleared first.
+    pub fn query_radius(&self, center: Vec3, radius: f32, result: &mut Vec<(T, Vec3)>) {
+        result.clear();
+        let min = self.key(center - Vec3::new(radius, radius, radius));
+        let max = self.key(center + Vec3::new(radius, radius, radius));
+        let sqr_radius = radius * radius;
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    if let Some(cell) = self.cells.get(&(x, y, z)) {
+                        result.extend(
+                            cell.iter()
+                                .filter(|(_, position)| {
+                                    position.sqr_distance(&center) <= sqr_radius
+                                })
+                                .cloned(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns closest entry that satisfies the filter. Search starts from cells around given
+    /// point and grows until something is found.
+    pub fn closest<F>(&self, center: Vec3, mut filter: F) -> Option<(T, Vec3)>
+    where
+        F: FnMut(T) -> bool,
+    {
+        let mut candidates = Vec::new();
+        let mut radius = self.cell_size;
+        while self.len > 0 {
+            self.query_radius(center, radius, &mut candidates);
+            let closest = candidates
+                .iter()
+                .filter(|(value, _)| filter(*value))
+                .min_by(|(_, a), (_, b)| {
+                    a.sqr_distance(&center)
+                        .partial_cmp(&b.sqr_distance(&center))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .cloned();
+            // Everything was checked already if query returned every entry.
+            if closest.is_some() || candidates.len() == self.len {
+                return closest;
+            }
+            radius *= 2.0;
+        }
+        None
+    }
+}
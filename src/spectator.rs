@@ -0,0 +1,155 @@
+//! Director of spectator camera for attract mode - bots play a match without human players
+//! and camera follows the most interesting actor: one who just scored kills, carries a flag or
+//! fights a duel on low health. Camera stays on an actor for a while before it looks for a
+//! better one, and slowly orbits around it.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    ctf::Flags,
+};
+use rg3d::{
+    core::{
+        math::{ray::Ray, vec3::Vec3},
+        pool::Handle,
+    },
+    physics::{Physics, RayCastOptions},
+};
+
+/// Camera doesn't leave an actor sooner than this amount of seconds.
+const MIN_SHOT_TIME: f32 = 4.0;
+/// Camera switches to another actor after this amount of seconds even if current one is
+/// still the most interesting.
+const MAX_SHOT_TIME: f32 = 12.0;
+/// Kills older than this amount of seconds don't make killer interesting anymore.
+const KILL_MEMORY: f32 = 6.0;
+const LOW_HEALTH: f32 = 40.0;
+/// Actor on low health with an enemy closer than this fights a duel.
+const DUEL_DISTANCE: f32 = 15.0;
+const CAMERA_DISTANCE: f32 = 4.0;
+const CAMERA_HEIGHT: f32 = 2.0;
+/// Speed of orbit of camera around followed actor, in radians per second.
+const ORBIT_SPEED: f32 = 0.3;
+/// Camera is kept this far from walls that are between it and followed actor.
+const WALL_OFFSET: f32 = 0.3;
+
+#[derive(Default)]
+pub struct SpectatorDirector {
+    focus: Handle<Actor>,
+    shot_time: f32,
+    orbit_angle: f32,
+    /// Killers and times of their kills.
+    kills: Vec<(Handle<Actor>, f32)>,
+}
+
+impl SpectatorDirector {
+    pub fn on_kill(&mut self, killer: Handle<Actor>, time: f32) {
+        self.kills.push((killer, time));
+    }
+
+    fn interest(
+        &self,
+        handle: Handle<Actor>,
+        actors: &ActorContainer,
+        flags: &Flags,
+        physics: &Physics,
+        time: f32,
+    ) -> f32 {
+        let actor = actors.get(handle);
+        let mut interest = 1.0;
+        if flags.is_carrier(handle) {
+            interest += 3.0;
+        }
+        interest += 2.0
+            * self
+                .kills
+                .iter()
+                .filter(|(killer, kill_time)| *killer == handle && time - kill_time < KILL_MEMORY)
+                .count() as f32;
+        if actor.get_health() < LOW_HEALTH {
+            let position = actor.position(physics);
+            let duel = actors.pair_iter().any(|(other_handle, other)| {
+                other_handle != handle
+                    && !other.is_dead()
+                    && actor.team().is_enemy(other.team())
+                    && other.position(physics).distance(&position) < DUEL_DISTANCE
+            });
+            if duel {
+                interest += 2.0;
+            }
+        }
+        interest
+    }
+
+    /// Selects actor to follow, returns position of camera and point it should look at or
+    /// `None` if there is nobody to follow.
+    pub fn update(
+        &mut self,
+        actors: &ActorContainer,
+        flags: &Flags,
+        physics: &Physics,
+        time: f32,
+        dt: f32,
+    ) -> Option<(Vec3, Vec3)> {
+        self.kills
+            .retain(|(_, kill_time)| time - kill_time < KILL_MEMORY);
+        self.shot_time += dt;
+        self.orbit_angle += ORBIT_SPEED * dt;
+
+        let focus_alive = actors.contains(self.focus) && !actors.get(self.focus).is_dead();
+        if !focus_alive || self.shot_time >= MIN_SHOT_TIME {
+            // Current actor is dropped after long shot, so camera doesn't stick to one actor.
+            let exclude = if focus_alive && self.shot_time >= MAX_SHOT_TIME {
+                self.focus
+            } else {
+                Handle::NONE
+            };
+            let best = actors
+                .pair_iter()
+                .filter(|(handle, actor)| *handle != exclude && !actor.is_dead())
+                .map(|(handle, _)| (handle, self.interest(handle, actors, flags, physics, time)))
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            if let Some((best, best_interest)) = best {
+                let current_interest = if focus_alive && exclude.is_none() {
+                    self.interest(self.focus, actors, flags, physics, time)
+                } else {
+                    0.0
+                };
+                if best != self.focus && best_interest > current_interest {
+                    self.focus = best;
+                    self.shot_time = 0.0;
+                }
+            }
+        }
+
+        if !actors.contains(self.focus) {
+            return None;
+        }
+
+        let target = actors.get(self.focus).position(physics) + Vec3::new(0.0, 0.5, 0.0);
+        let mut position = target
+            + Vec3::new(
+                self.orbit_angle.sin() * CAMERA_DISTANCE,
+                CAMERA_HEIGHT,
+                self.orbit_angle.cos() * CAMERA_DISTANCE,
+            );
+        // Walls between camera and actor would hide the actor, camera is moved in front of them.
+        if let Some(ray) = Ray::from_two_points(&target, &position) {
+            let options = RayCastOptions {
+                ignore_bodies: true,
+                ignore_static_geometries: false,
+                sort_results: true,
+            };
+            let mut result = Vec::new();
+            if physics.ray_cast(&ray, options, &mut result) {
+                if let Some(hit) = result.first() {
+                    let (direction, distance) = (hit.position - target).normalized_ex();
+                    if let Some(direction) = direction {
+                        position = target + direction.scale((distance - WALL_OFFSET).max(0.0));
+                    }
+                }
+            }
+        }
+
+        Some((position, target))
+    }
+}
@@ -0,0 +1,143 @@
+//! Tags of scene nodes. Every node of a map is tagged by its name: name is split by `_` and
+//! each leading part becomes a tag, so `SpawnPoint_Red_1` has tags `spawnpoint`,
+//! `spawnpoint_red` and `spawnpoint_red_1`. Tags are lower case. Extra tags can be given in a
+//! file next to the map model with `tags.json` extension (`dm6.tags.json` for `dm6.fbx`),
+//! it maps tags to patterns of node names:
+//!
+//! ```json
+//! {
+//!     "tags": {
+//!         "spawnpoint": ["Start_*"],
+//!         "door": ["Gate*", "Hatch_2"]
+//!     }
+//! }
+//! ```
+//!
+//! Lookups take patterns too, `*` matches any amount of characters: `jumppad_*`.
+
+use crate::mods;
+use rg3d::{
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node},
+    utils::log::Log,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Checks if text matches pattern where `*` stands for any amount of any characters.
+fn matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // Split always yields at least one part.
+    let first = parts.next().unwrap();
+    if !text.starts_with(first) {
+        return false;
+    }
+    let mut rest = &text[first.len()..];
+    let mut last = None;
+    for part in parts {
+        if let Some(previous) = last.replace(part) {
+            match rest.find(previous) {
+                Some(index) => rest = &rest[index + previous.len()..],
+                None => return false,
+            }
+        }
+    }
+    match last {
+        // Last part is anchored to the end of text.
+        Some(last) => rest.ends_with(last),
+        None => rest.is_empty(),
+    }
+}
+
+#[derive(Deserialize)]
+struct TagFile {
+    tags: HashMap<String, Vec<String>>,
+}
+
+impl TagFile {
+    fn file_of_map(map: &str) -> PathBuf {
+        Path::new(map).with_extension("tags.json")
+    }
+
+    /// Loads tag file of a map, map without tag file has no extra tags.
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = mods::resolve(path);
+        if !path.exists() {
+            return Ok(Self {
+                tags: Default::default(),
+            });
+        }
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read tags {}: {}", path.display(), e))?;
+        serde_json::from_str::<TagFile>(&data)
+            .map_err(|e| format!("Tags {} are corrupted: {}", path.display(), e))
+    }
+}
+
+/// Nodes of a map by tag. Not saved, it is rebuilt from the scene.
+#[derive(Default)]
+pub struct TagIndex {
+    // Ordered, so lookups return nodes in same order every run and replays stay in sync.
+    tags: BTreeMap<String, Vec<Handle<Node>>>,
+}
+
+impl TagIndex {
+    /// Tags nodes of the graph by their names and by tag file of the map.
+    pub fn build(graph: &Graph, map: &str) -> Self {
+        let mut index = Self::default();
+
+        for (handle, node) in graph.pair_iter() {
+            let name = node.name().to_lowercase();
+            for (i, _) in name.match_indices('_') {
+                index.add(&name[..i], handle);
+            }
+            index.add(&name, handle);
+        }
+
+        match TagFile::load(TagFile::file_of_map(map)) {
+            Ok(file) => {
+                for (tag, patterns) in file.tags.iter() {
+                    let patterns = patterns
+                        .iter()
+                        .map(|pattern| pattern.to_lowercase())
+                        .collect::<Vec<_>>();
+                    for (handle, node) in graph.pair_iter() {
+                        let name = node.name().to_lowercase();
+                        if patterns.iter().any(|pattern| matches(pattern, &name)) {
+                            index.add(&tag.to_lowercase(), handle);
+                        }
+                    }
+                }
+            }
+            Err(e) => Log::writeln(format!("WARNING: {}, map will have no extra tags.", e)),
+        }
+
+        index
+    }
+
+    fn add(&mut self, tag: &str, handle: Handle<Node>) {
+        if tag.is_empty() {
+            return;
+        }
+        let nodes = self.tags.entry(tag.to_owned()).or_default();
+        if !nodes.contains(&handle) {
+            nodes.push(handle);
+        }
+    }
+
+    /// Returns nodes that have a tag matching the pattern, every node once.
+    pub fn nodes(&self, pattern: &str) -> Vec<Handle<Node>> {
+        let mut nodes = Vec::new();
+        for (_, tagged) in self.tags.iter().filter(|(tag, _)| matches(pattern, tag)) {
+            for &handle in tagged {
+                if !nodes.contains(&handle) {
+                    nodes.push(handle);
+                }
+            }
+        }
+        nodes
+    }
+}
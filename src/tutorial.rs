@@ -0,0 +1,191 @@
+//! Guided tutorial. Tutorial is a sequence of steps, each step shows a prompt on HUD and is done
+//! when player does what prompt asks. Prompts are built from current control scheme every frame,
+//! so they show actual keys even if player rebinds them in the middle of tutorial.
+//!
+//! Map can guide player with trigger volumes - meshes named `Tutorial_<step number>` (numbers
+//! start from 1), entering a volume skips tutorial ahead to its step.
+
+use crate::{
+    control_scheme::{ControlButtonDefinition, ControlScheme},
+    weapon::Weapon,
+};
+use rg3d::core::{
+    math::{aabb::AxisAlignedBoundingBox, vec3::Vec3},
+    pool::Handle,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+/// How far player must walk to finish movement step.
+const MOVE_DISTANCE: f32 = 3.0;
+/// Vertical velocity of player in meters per second. Jump gives 4.2, jump pads launch much
+/// faster.
+const JUMP_VELOCITY: f32 = 1.8;
+const JUMP_PAD_VELOCITY: f32 = 7.2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TutorialGoal {
+    Move,
+    Jump,
+    Shoot,
+    SwitchWeapon,
+    PickUpItem,
+    UseJumpPad,
+}
+
+const STEPS: [TutorialGoal; 6] = [
+    TutorialGoal::Move,
+    TutorialGoal::Jump,
+    TutorialGoal::Shoot,
+    TutorialGoal::SwitchWeapon,
+    TutorialGoal::PickUpItem,
+    TutorialGoal::UseJumpPad,
+];
+
+impl TutorialGoal {
+    fn prompt(self, controls: &ControlScheme) -> String {
+        let key = |definition: &ControlButtonDefinition| format!("[{}]", definition.button.name());
+        match self {
+            TutorialGoal::Move => format!(
+                "Use {} {} {} {} to move around",
+                key(&controls.move_forward),
+                key(&controls.move_left),
+                key(&controls.move_backward),
+                key(&controls.move_right)
+            ),
+            TutorialGoal::Jump => format!("Press {} to jump", key(&controls.jump)),
+            TutorialGoal::Shoot => format!(
+                "Press {} to shoot, hold {} to aim down sights",
+                key(&controls.shoot),
+                key(&controls.ads)
+            ),
+            TutorialGoal::SwitchWeapon => format!(
+                "Use {} and {} to switch weapons",
+                key(&controls.next_weapon),
+                key(&controls.prev_weapon)
+            ),
+            TutorialGoal::PickUpItem => {
+                "Walk over an item to pick it up - medkits heal, ammo refills weapons".to_owned()
+            }
+            TutorialGoal::UseJumpPad => format!(
+                "Step on a jump pad to get launched, hold {} to run there faster",
+                key(&controls.run)
+            ),
+        }
+    }
+}
+
+/// Trigger volume of a step of tutorial.
+#[derive(Default)]
+pub struct TutorialVolume {
+    step: u32,
+    bounds: AxisAlignedBoundingBox,
+}
+
+impl TutorialVolume {
+    /// Step numbers of volumes start from 1, as in names of meshes.
+    pub fn new(step: u32, bounds: AxisAlignedBoundingBox) -> Self {
+        Self { step, bounds }
+    }
+}
+
+impl Visit for TutorialVolume {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.step.visit("Step", visitor)?;
+        self.bounds.visit("Bounds", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// State of player that is needed to check goals, collected by level every frame.
+pub struct PlayerState {
+    pub position: Vec3,
+    pub vertical_velocity: f32,
+    pub current_weapon: Handle<Weapon>,
+}
+
+#[derive(Default)]
+pub struct TutorialProgress {
+    step: u32,
+    volumes: Vec<TutorialVolume>,
+    /// Position of player when current step started, `None` until player is spawned.
+    start_position: Option<Vec3>,
+    start_weapon: Handle<Weapon>,
+}
+
+impl TutorialProgress {
+    pub fn add_volume(&mut self, volume: TutorialVolume) {
+        self.volumes.push(volume);
+    }
+
+    fn goal(&self) -> Option<TutorialGoal> {
+        STEPS.get(self.step as usize).copied()
+    }
+
+    fn advance_to(&mut self, step: u32) {
+        self.step = step;
+        self.start_position = None;
+    }
+
+    /// Reports something player did that can't be seen from its state, like a shot.
+    pub fn on_goal_reached(&mut self, goal: TutorialGoal) {
+        if self.goal() == Some(goal) {
+            self.advance_to(self.step + 1);
+        }
+    }
+
+    pub fn update(&mut self, player: &PlayerState) {
+        for volume in self.volumes.iter() {
+            if volume.step > self.step && volume.bounds.is_contains_point(player.position) {
+                let step = volume.step - 1;
+                self.advance_to(step);
+                break;
+            }
+        }
+
+        let start_position = match self.start_position {
+            Some(start_position) => start_position,
+            None => {
+                self.start_position = Some(player.position);
+                self.start_weapon = player.current_weapon;
+                return;
+            }
+        };
+
+        let reached = match self.goal() {
+            Some(TutorialGoal::Move) => start_position.distance(&player.position) >= MOVE_DISTANCE,
+            Some(TutorialGoal::Jump) => player.vertical_velocity > JUMP_VELOCITY,
+            Some(TutorialGoal::SwitchWeapon) => player.current_weapon != self.start_weapon,
+            Some(TutorialGoal::UseJumpPad) => player.vertical_velocity > JUMP_PAD_VELOCITY,
+            Some(TutorialGoal::Shoot) | Some(TutorialGoal::PickUpItem) | None => false,
+        };
+        if reached {
+            self.advance_to(self.step + 1);
+        }
+    }
+
+    pub fn prompt(&self, controls: &ControlScheme) -> String {
+        match self.goal() {
+            Some(goal) => format!(
+                "Step {}/{}: {}",
+                self.step + 1,
+                STEPS.len(),
+                goal.prompt(controls)
+            ),
+            None => "Tutorial complete! Try Practice mode or start a match".to_owned(),
+        }
+    }
+}
+
+impl Visit for TutorialProgress {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.step.visit("Step", visitor)?;
+        self.volumes.visit("Volumes", visitor)?;
+
+        visitor.leave_region()
+    }
+}
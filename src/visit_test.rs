@@ -0,0 +1,21 @@
+//! Helpers for tests of save and load of game state.
+
+use crate::data_dir::APP_NAME;
+use rg3d::core::visitor::{Visit, Visitor};
+use std::{env, fs, process};
+
+/// Writes value to a save file and reads it back into a default instance, just like a save
+/// and load of the game does. `name` must be unique across tests, it names the file.
+pub fn round_trip<T: Visit + Default>(name: &str, value: &mut T) -> T {
+    let path = env::temp_dir().join(format!("{}-{}-{}.bin", APP_NAME, process::id(), name));
+
+    let mut visitor = Visitor::new();
+    value.visit(name, &mut visitor).unwrap();
+    visitor.save_binary(&path).unwrap();
+
+    let mut loaded = T::default();
+    let mut visitor = Visitor::load_binary(&path).unwrap();
+    loaded.visit(name, &mut visitor).unwrap();
+    fs::remove_file(&path).unwrap();
+    loaded
+}
@@ -1,5 +1,14 @@
 use crate::{
-    actor::Actor, actor::ActorContainer, assets, message::Message, projectile::ProjectileKind,
+    actor::Actor,
+    actor::ActorContainer,
+    assets,
+    attachment::{Attachment, AttachmentKind},
+    definition::{self, DefinitionCache},
+    error::{GameError, GameResult},
+    message::Message,
+    mods,
+    projectile::ProjectileKind,
+    sound_variation::SoundCategory,
     GameTime,
 };
 use rg3d::{
@@ -19,13 +28,15 @@ use rg3d::{
         Scene,
     },
 };
+use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
     ops::{Index, IndexMut},
-    path::{Path, PathBuf},
+    path::PathBuf,
     sync::mpsc::Sender,
 };
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum WeaponKind {
     M4,
     Ak47,
@@ -34,6 +45,13 @@ pub enum WeaponKind {
 }
 
 impl WeaponKind {
+    pub const ALL: [WeaponKind; 4] = [
+        WeaponKind::M4,
+        WeaponKind::Ak47,
+        WeaponKind::PlasmaRifle,
+        WeaponKind::RocketLauncher,
+    ];
+
     pub fn id(self) -> u32 {
         match self {
             WeaponKind::M4 => 0,
@@ -43,6 +61,15 @@ impl WeaponKind {
         }
     }
 
+    pub fn name(self) -> &'static str {
+        match self {
+            WeaponKind::M4 => "M4",
+            WeaponKind::Ak47 => "AK-47",
+            WeaponKind::PlasmaRifle => "Plasma Rifle",
+            WeaponKind::RocketLauncher => "Rocket Launcher",
+        }
+    }
+
     pub fn new(id: u32) -> Result<Self, String> {
         match id {
             0 => Ok(WeaponKind::M4),
@@ -62,19 +89,66 @@ pub struct Weapon {
     offset: Vec3,
     dest_offset: Vec3,
     last_shot_time: f64,
+    /// Time of last attempt to shoot without ammo, not saved.
+    last_dry_fire_time: f64,
     shot_position: Vec3,
     owner: Handle<Actor>,
     ammo: u32,
+    attachments: Vec<Attachment>,
+    laser_enabled: bool,
     pub definition: &'static WeaponDefinition,
     pub sender: Option<Sender<Message>>,
 }
 
+/// Radius in which bots hear a shot of a weapon without attachments.
+const SHOT_NOISE_RADIUS: f32 = 30.0;
+/// Minimal time between clicks of empty weapon, so automatic weapons don't rattle.
+const DRY_FIRE_INTERVAL: f64 = 0.3;
+/// Time HUD shows "no ammo" after a click of empty weapon.
+const NO_AMMO_FLASH_TIME: f64 = 0.6;
+/// Share of initial ammo below which weapon is low on ammo.
+const LOW_AMMO_FRACTION: f32 = 0.2;
+
+#[derive(Clone)]
 pub struct WeaponDefinition {
     pub model: &'static str,
     pub shot_sound: &'static str,
     pub ammo: u32,
     pub projectile: ProjectileKind,
     pub shoot_interval: f64,
+    /// Position of weapon relative to camera of player, for right-handed view.
+    pub view_offset: [f32; 3],
+}
+
+impl WeaponDefinition {
+    /// Paths of every file used by weapon.
+    pub fn assets(&self) -> Vec<&'static str> {
+        vec![self.model, self.shot_sound]
+    }
+}
+
+/// Stats of weapon that data file can change, see `definition` module.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WeaponStats {
+    ammo: Option<u32>,
+    shoot_interval: Option<f64>,
+    view_offset: Option<[f32; 3]>,
+}
+
+impl WeaponStats {
+    fn apply(&self, definition: &WeaponDefinition) -> WeaponDefinition {
+        WeaponDefinition {
+            ammo: self.ammo.unwrap_or(definition.ammo),
+            shoot_interval: self.shoot_interval.unwrap_or(definition.shoot_interval),
+            view_offset: self.view_offset.unwrap_or(definition.view_offset),
+            ..definition.clone()
+        }
+    }
+}
+
+thread_local! {
+    static DEFINITIONS: RefCell<DefinitionCache<WeaponDefinition>> = Default::default();
 }
 
 impl Default for Weapon {
@@ -87,9 +161,12 @@ impl Default for Weapon {
             shot_point: Handle::NONE,
             dest_offset: Vec3::ZERO,
             last_shot_time: 0.0,
+            last_dry_fire_time: std::f64::NEG_INFINITY,
             shot_position: Vec3::ZERO,
             owner: Handle::NONE,
             ammo: 250,
+            attachments: Default::default(),
+            laser_enabled: false,
             definition: Self::get_definition(WeaponKind::M4),
             sender: None,
         }
@@ -114,13 +191,35 @@ impl Visit for Weapon {
         self.last_shot_time.visit("LastShotTime", visitor)?;
         self.owner.visit("Owner", visitor)?;
         self.ammo.visit("Ammo", visitor)?;
+        self.attachments.visit("Attachments", visitor)?;
+        self.laser_enabled.visit("LaserEnabled", visitor)?;
 
         visitor.leave_region()
     }
 }
 
 impl Weapon {
+    /// Definition of weapon with stats from data file, see `definition` module.
     pub fn get_definition(kind: WeaponKind) -> &'static WeaponDefinition {
+        DEFINITIONS.with(|definitions| {
+            definitions.borrow_mut().get(kind.id() as usize, || {
+                let stats =
+                    definition::load_stats::<WeaponKind, WeaponStats>(definition::WEAPONS_FILE);
+                WeaponKind::ALL
+                    .iter()
+                    .map(|kind| {
+                        let builtin = Self::builtin_definition(*kind);
+                        stats
+                            .get(kind)
+                            .map_or_else(|| builtin.clone(), |stats| stats.apply(builtin))
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Definition used when data file has no stats for the weapon.
+    fn builtin_definition(kind: WeaponKind) -> &'static WeaponDefinition {
         match kind {
             WeaponKind::M4 => {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
@@ -129,6 +228,7 @@ impl Weapon {
                     ammo: 200,
                     projectile: ProjectileKind::Bullet,
                     shoot_interval: 0.15,
+                    view_offset: [-0.035, -0.052, 0.02],
                 };
                 &DEFINITION
             }
@@ -139,6 +239,7 @@ impl Weapon {
                     ammo: 200,
                     projectile: ProjectileKind::Bullet,
                     shoot_interval: 0.15,
+                    view_offset: [-0.035, -0.052, 0.02],
                 };
                 &DEFINITION
             }
@@ -149,6 +250,7 @@ impl Weapon {
                     ammo: 100,
                     projectile: ProjectileKind::Plasma,
                     shoot_interval: 0.25,
+                    view_offset: [-0.035, -0.05, 0.02],
                 };
                 &DEFINITION
             }
@@ -159,6 +261,7 @@ impl Weapon {
                     ammo: 100,
                     projectile: ProjectileKind::Rocket,
                     shoot_interval: 1.5,
+                    view_offset: [-0.04, -0.055, 0.025],
                 };
                 &DEFINITION
             }
@@ -170,47 +273,37 @@ impl Weapon {
         resource_manager: ResourceManager,
         scene: &mut Scene,
         sender: Sender<Message>,
-    ) -> Weapon {
+    ) -> GameResult<Weapon> {
         let definition = Self::get_definition(kind);
 
         let model = resource_manager
-            .request_model(Path::new(definition.model))
+            .request_model(mods::resolve(definition.model))
             .await
-            .unwrap()
+            .map_err(|_| GameError::missing_asset(definition.model))?
             .instantiate_geometry(scene);
 
-        let laser_dot = scene.graph.add_node(
-            PointLightBuilder::new(
-                BaseLightBuilder::new(BaseBuilder::new())
-                    .with_color(Color::opaque(255, 0, 0))
-                    .with_scatter_enabled(false)
-                    .cast_shadows(false),
-            )
-            .with_radius(0.5)
-            .build_node(),
-        );
-
         let shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
 
         if shot_point.is_none() {
             println!("Shot point not found!");
         }
 
-        Weapon {
+        Ok(Weapon {
             kind,
-            laser_dot,
             model,
             shot_point,
             definition,
             ammo: definition.ammo,
             sender: Some(sender),
             ..Default::default()
-        }
+        })
     }
 
     pub fn set_visibility(&self, visibility: bool, graph: &mut Graph) {
         graph[self.model].set_visibility(visibility);
-        graph[self.laser_dot].set_visibility(visibility);
+        if self.laser_dot.is_some() {
+            graph[self.laser_dot].set_visibility(visibility && self.is_laser_enabled());
+        }
     }
 
     pub fn get_model(&self) -> Handle<Node> {
@@ -248,11 +341,98 @@ impl Weapon {
         graph[self.model].global_transform().basis()
     }
 
+    /// Adds ammo from a pickup, amount is scaled by attachments.
     pub fn add_ammo(&mut self, amount: u32) {
-        self.ammo += amount;
+        let factor = self
+            .attachments
+            .iter()
+            .fold(1.0, |factor, a| factor * a.definition().ammo_factor);
+        self.ammo += (amount as f32 * factor) as u32;
+    }
+
+    /// Fills ammo up to the amount weapon is given with.
+    pub fn refill_ammo(&mut self) {
+        self.ammo = self.ammo.max(Self::get_definition(self.kind).ammo);
+    }
+
+    /// Node to which models of attachments are linked.
+    pub fn attachment_mount(&self, graph: &Graph) -> Handle<Node> {
+        let mount = graph.find_by_name(self.model, "Weapon:Attachment");
+        if mount.is_some() {
+            mount
+        } else {
+            self.model
+        }
+    }
+
+    pub fn has_attachment(&self, kind: AttachmentKind) -> bool {
+        self.attachments.iter().any(|a| a.kind() == kind)
+    }
+
+    /// Mounts attachment on the weapon, weapon can have only one attachment of each kind
+    /// so attachment is removed if weapon already has one of the same kind.
+    pub fn attach(&mut self, attachment: Attachment, graph: &mut Graph) {
+        if self.has_attachment(attachment.kind()) {
+            if attachment.model().is_some() {
+                graph.remove_node(attachment.model());
+            }
+            return;
+        }
+
+        if attachment.kind() == AttachmentKind::LaserSight {
+            // Laser dot is a light, so it is created only for weapons with laser sight.
+            self.laser_dot = graph.add_node(
+                PointLightBuilder::new(
+                    BaseLightBuilder::new(BaseBuilder::new())
+                        .with_color(Color::opaque(255, 0, 0))
+                        .with_scatter_enabled(false)
+                        .cast_shadows(false),
+                )
+                .with_radius(0.5)
+                .build_node(),
+            );
+            self.laser_enabled = true;
+        }
+        self.attachments.push(attachment);
+
+        let visible = graph[self.model].visibility();
+        self.set_visibility(visible, graph);
+    }
+
+    /// Radius in which bots can hear a shot of the weapon.
+    pub fn noise_radius(&self) -> f32 {
+        self.attachments
+            .iter()
+            .fold(SHOT_NOISE_RADIUS, |radius, a| {
+                radius * a.definition().noise_factor
+            })
+    }
+
+    /// Zoom of camera when aiming down sights.
+    pub fn zoom(&self) -> f32 {
+        self.attachments
+            .iter()
+            .fold(1.0, |zoom: f32, a| zoom.max(a.definition().zoom))
+    }
+
+    pub fn is_laser_enabled(&self) -> bool {
+        self.laser_enabled && self.has_attachment(AttachmentKind::LaserSight)
+    }
+
+    /// Switches laser of laser sight, does nothing if weapon has no laser sight.
+    pub fn toggle_laser(&mut self, graph: &mut Graph) {
+        if self.has_attachment(AttachmentKind::LaserSight) {
+            self.laser_enabled = !self.laser_enabled;
+            let visible = graph[self.model].visibility();
+            self.set_visibility(visible, graph);
+        }
     }
 
     fn update_laser_sight(&self, graph: &mut Graph, physics: &Physics, actors: &ActorContainer) {
+        if !self.is_laser_enabled() || self.laser_dot.is_none() {
+            return;
+        }
+
         let mut laser_dot_position = Vec3::ZERO;
         let model = &graph[self.model];
         let begin = model.global_position();
@@ -285,6 +465,15 @@ impl Weapon {
         self.ammo
     }
 
+    pub fn is_low_on_ammo(&self) -> bool {
+        (self.ammo as f32) < self.definition.ammo as f32 * LOW_AMMO_FRACTION
+    }
+
+    /// Returns true if weapon was recently fired without ammo.
+    pub fn is_dry_firing(&self, time: f64) -> bool {
+        time - self.last_dry_fire_time < NO_AMMO_FLASH_TIME
+    }
+
     pub fn owner(&self) -> Handle<Actor> {
         self.owner
     }
@@ -310,19 +499,41 @@ impl Weapon {
                         gain: 1.0,
                         rolloff_factor: 5.0,
                         radius: 3.0,
+                        category: SoundCategory::Shot,
                     })
                     .unwrap();
             }
 
             true
         } else {
+            if self.ammo == 0
+                && time.elapsed - self.last_dry_fire_time
+                    >= DRY_FIRE_INTERVAL.max(self.definition.shoot_interval)
+            {
+                self.last_dry_fire_time = time.elapsed;
+                let position = self.get_shot_position(&scene.graph);
+                if let Some(sender) = self.sender.as_ref() {
+                    sender
+                        .send(Message::PlaySound {
+                            path: PathBuf::from(assets::sounds::DRY_FIRE),
+                            position,
+                            gain: 0.5,
+                            rolloff_factor: 5.0,
+                            radius: 1.0,
+                            category: SoundCategory::Other,
+                        })
+                        .unwrap();
+                }
+            }
             false
         }
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
         scene.graph.remove_node(self.model);
-        scene.graph.remove_node(self.laser_dot);
+        if self.laser_dot.is_some() {
+            scene.graph.remove_node(self.laser_dot);
+        }
     }
 }
 
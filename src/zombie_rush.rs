@@ -0,0 +1,92 @@
+//! Zombie rush mutator. Zombies come in waves, every next wave is bigger and spawns faster.
+//! Zombies are cheap bots without weapons that rush to players and fight in melee, they don't
+//! respawn - new zombies are spawned by waves instead. Amount of zombies alive at the same time
+//! is limited, the rest of a wave waits until somebody kills some.
+
+use crate::{actor::ActorDescriptor, bot::BotKind, message::Message};
+use rg3d::core::visitor::{Visit, VisitResult, Visitor};
+use std::sync::mpsc::Sender;
+
+const FIRST_WAVE_SIZE: u32 = 6;
+/// How many zombies each next wave adds.
+const WAVE_GROWTH: u32 = 4;
+const MAX_ALIVE_ZOMBIES: usize = 24;
+/// Pause between end of a wave and start of the next one, in seconds.
+const WAVE_BREAK: f32 = 8.0;
+
+const ZOMBIE_KINDS: [BotKind; 3] = [BotKind::Mutant, BotKind::Parasite, BotKind::Maw];
+
+pub struct ZombieRush {
+    wave: u32,
+    /// Zombies of current wave that are not spawned yet.
+    spawn_left: u32,
+    spawn_timer: f32,
+    break_timer: f32,
+}
+
+impl Default for ZombieRush {
+    fn default() -> Self {
+        Self {
+            wave: 0,
+            spawn_left: 0,
+            spawn_timer: 0.0,
+            break_timer: WAVE_BREAK,
+        }
+    }
+}
+
+impl ZombieRush {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time between spawns of two zombies, waves spawn faster and faster.
+    fn spawn_interval(&self) -> f32 {
+        (1.0 - 0.1 * self.wave as f32).max(0.2)
+    }
+
+    pub fn update(&mut self, dt: f32, alive_zombies: usize, sender: &Sender<Message>) {
+        if self.spawn_left == 0 {
+            // Next wave starts after a break when everyone from previous wave is dead.
+            if alive_zombies == 0 {
+                self.break_timer -= dt;
+                if self.break_timer <= 0.0 {
+                    self.wave += 1;
+                    self.spawn_left = FIRST_WAVE_SIZE + WAVE_GROWTH * (self.wave - 1);
+                    self.break_timer = WAVE_BREAK;
+                    sender
+                        .send(Message::AddNotification {
+                            text: format!("Wave {}!", self.wave),
+                        })
+                        .unwrap();
+                }
+            }
+            return;
+        }
+
+        self.spawn_timer -= dt;
+        if self.spawn_timer <= 0.0 && alive_zombies < MAX_ALIVE_ZOMBIES {
+            self.spawn_timer = self.spawn_interval();
+            self.spawn_left -= 1;
+            let kind = ZOMBIE_KINDS[self.spawn_left as usize % ZOMBIE_KINDS.len()];
+            sender
+                .send(Message::SpawnActor {
+                    descriptor: ActorDescriptor::zombie(kind).with_name("Zombie"),
+                })
+                .unwrap();
+        }
+    }
+}
+
+impl Visit for ZombieRush {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.wave.visit("Wave", visitor)?;
+        self.spawn_left.visit("SpawnLeft", visitor)?;
+        self.spawn_timer.visit("SpawnTimer", visitor)?;
+        self.break_timer.visit("BreakTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}